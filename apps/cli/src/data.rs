@@ -0,0 +1,132 @@
+use crate::{
+  error::{CliError, Result},
+  record::{CsvRow, Format, Sample},
+};
+use perfx_core::{
+  adb::{snapshot_values, MetricsSnapshot},
+  schema::{check_version, legacy_network_rates, legacy_power_is_cumulative, legacy_version},
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// 各指标的数值序列，键与桌面端图表一致（fps、cpu、memory 等）
+pub type Series = BTreeMap<String, Vec<f64>>;
+
+/// 桌面端导出的会话 JSON 中的采样
+#[derive(Debug, Deserialize)]
+struct SessionSample {
+  #[serde(default)]
+  t_ms: u64,
+  values: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SessionInfo {
+  #[serde(default = "legacy_version")]
+  schema_version: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct SessionFile {
+  #[serde(default)]
+  session: SessionInfo,
+  samples: Vec<SessionSample>,
+}
+
+pub fn from_samples(samples: &[Sample]) -> Series {
+  let mut series = Series::new();
+  for sample in samples {
+    push(&mut series, snapshot_values(&sample.metrics));
+  }
+  series
+}
+
+/// 读取 `perfx record` 导出的 CSV / JSON / JSONL，或桌面端导出的会话 JSON
+pub fn load(path: &Path) -> Result<Series> {
+  let text = fs::read_to_string(path)?;
+  let mut series = Series::new();
+  let format = Format::from_path(path);
+  // 是否含有版本 3 之前的数据，其中的 power 可能是累计 mAh
+  let mut legacy = false;
+  if format == Format::Jsonl {
+    for line in text.lines().filter(|l| !l.trim().is_empty()) {
+      let mut snapshot: MetricsSnapshot = serde_json::from_str(line)?;
+      legacy |= snapshot.schema_version < 3;
+      snapshot.upgrade()?;
+      push(&mut series, snapshot_values(&snapshot));
+    }
+    return Ok(upgrade_power(series, legacy));
+  }
+  if format == Format::Csv {
+    for row in csv::Reader::from_reader(text.as_bytes()).deserialize::<CsvRow>() {
+      push(&mut series, snapshot_values(&row?.into()));
+    }
+    // CSV 不带版本号，没有 power_mah 列的按旧格式处理
+    legacy = !series.contains_key("power_mah");
+    return Ok(upgrade_power(series, legacy));
+  }
+
+  match serde_json::from_str::<Value>(&text)? {
+    Value::Array(samples) => {
+      for sample in samples {
+        let mut snapshot: MetricsSnapshot = serde_json::from_value(sample)?;
+        legacy |= snapshot.schema_version < 3;
+        snapshot.upgrade()?;
+        push(&mut series, snapshot_values(&snapshot));
+      }
+    }
+    value @ Value::Object(_) => {
+      let mut session: SessionFile = serde_json::from_value(value)?;
+      check_version(session.session.schema_version)?;
+      legacy = session.session.schema_version < 3;
+      if session.session.schema_version < 2 {
+        upgrade_network(&mut session.samples);
+      }
+      for sample in session.samples {
+        push(&mut series, sample.values);
+      }
+    }
+    _ => {
+      return Err(CliError::Usage(format!(
+        "{} 不是 perfx 导出的采样文件",
+        path.display()
+      )))
+    }
+  }
+  Ok(upgrade_power(series, legacy))
+}
+
+/// 旧数据中累计的 power 改记为 power_mah，与新版本含义一致
+fn upgrade_power(mut series: Series, legacy: bool) -> Series {
+  if legacy && series.get("power").is_some_and(|v| legacy_power_is_cumulative(v)) {
+    if let Some(values) = series.remove("power") {
+      series.insert("power_mah".to_string(), values);
+    }
+  }
+  series
+}
+
+/// 版本 2 之前会话中累计的 network 改为速率，与新版本含义一致
+fn upgrade_network(samples: &mut [SessionSample]) {
+  let points: Vec<(u64, f64)> = samples
+    .iter()
+    .filter_map(|s| s.values.get("network").map(|v| (s.t_ms, *v)))
+    .collect();
+  let mut rates = legacy_network_rates(&points).into_iter();
+  for sample in samples.iter_mut() {
+    if !sample.values.contains_key("network") {
+      continue;
+    }
+    match rates.next().flatten() {
+      Some(rate) => sample.values.insert("network".to_string(), rate),
+      None => sample.values.remove("network"),
+    };
+  }
+}
+
+fn push(series: &mut Series, values: BTreeMap<String, f64>) {
+  for (metric, value) in values {
+    series.entry(metric).or_default().push(value);
+  }
+}
@@ -0,0 +1,24 @@
+use perfx_core::{adb::error::AdbError, schema::UnsupportedSchema};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CliError {
+  #[error(transparent)]
+  Adb(#[from] AdbError),
+  #[error("{0}")]
+  Usage(String),
+  #[error(transparent)]
+  Schema(#[from] UnsupportedSchema),
+  #[error("文件读写失败: {0}")]
+  Io(#[from] std::io::Error),
+  #[error("CSV 写入失败: {0}")]
+  Csv(#[from] csv::Error),
+  #[error("JSON 写入失败: {0}")]
+  Json(#[from] serde_json::Error),
+  #[error("无法连接控制服务，请确认桌面端已开启: {0}")]
+  Connect(String),
+  #[error("控制服务返回错误: {0}")]
+  Server(String),
+}
+
+pub type Result<T> = std::result::Result<T, CliError>;
@@ -0,0 +1,77 @@
+use crate::{data::Series, error::Result};
+use perfx_core::analysis::{
+  gate::{evaluate_checks, CheckStatus, GateCheck, GateConfig},
+  summary::{summarize_values, MetricSummary},
+};
+use std::{fs, path::Path};
+
+pub fn load_config(path: Option<&Path>) -> Result<GateConfig> {
+  match path {
+    Some(path) => Ok(serde_json::from_str(&fs::read_to_string(path)?)?),
+    None => Ok(GateConfig::default()),
+  }
+}
+
+fn summarize(series: &Series) -> Vec<MetricSummary> {
+  series
+    .iter()
+    .filter_map(|(metric, values)| summarize_values(metric, values))
+    .collect()
+}
+
+pub fn evaluate(current: &Series, baseline: &Series, config: &GateConfig) -> Vec<GateCheck> {
+  evaluate_checks(&summarize(current), &summarize(baseline), config)
+}
+
+pub fn passed(checks: &[GateCheck]) -> bool {
+  checks.iter().all(|c| c.status == CheckStatus::Pass)
+}
+
+/// 以对齐的表格打印检查结果
+pub fn print_table(checks: &[GateCheck]) {
+  println!(
+    "{:<16} {:<5} {:>10} {:>10} {:>8} {:>6}  结果",
+    "指标", "统计", "基线", "本次", "变化", "容差"
+  );
+  for check in checks {
+    let status = match check.status {
+      CheckStatus::Pass => "通过",
+      CheckStatus::Fail => "失败",
+      CheckStatus::Missing => "缺失",
+    };
+    let stat = serde_json::to_value(check.stat)
+      .ok()
+      .and_then(|v| v.as_str().map(str::to_string))
+      .unwrap_or_default();
+    println!(
+      "{:<16} {:<5} {:>10} {:>10} {:>8} {:>5}%  {}{}",
+      check.metric,
+      stat,
+      number(check.baseline),
+      number(check.current),
+      check
+        .delta_pct
+        .map_or_else(|| "-".into(), |d| format!("{d:+.1}%")),
+      check.tolerance_pct,
+      status,
+      check
+        .reason
+        .as_deref()
+        .map(|r| format!("：{r}"))
+        .unwrap_or_default()
+    );
+  }
+  let failed = checks
+    .iter()
+    .filter(|c| c.status != CheckStatus::Pass)
+    .count();
+  if failed == 0 {
+    println!("门禁通过，共 {} 项", checks.len());
+  } else {
+    println!("门禁未通过：{failed}/{} 项", checks.len());
+  }
+}
+
+fn number(value: Option<f64>) -> String {
+  value.map_or_else(|| "-".into(), |v| format!("{v:.2}"))
+}
@@ -0,0 +1,286 @@
+mod data;
+mod error;
+mod gate;
+mod mark;
+mod record;
+
+use crate::error::{CliError, Result};
+use clap::{Parser, Subcommand};
+use perfx_core::{
+  adb::{
+    list_apps, list_devices,
+    provider::{register_property_metrics, register_shell_metrics},
+    script::register_metric_scripts,
+    set_adb_path, MetricKey,
+  },
+  settings::{load_settings, Settings},
+};
+use std::{path::PathBuf, process::ExitCode};
+
+#[derive(Debug, Parser)]
+#[command(name = "perfx", version, about = "Android 应用性能采集命令行")]
+struct Cli {
+  /// adb 可执行文件路径，默认取桌面端设置，未设置时使用 PATH 中的 adb
+  #[arg(long, global = true)]
+  adb: Option<String>,
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+  /// 列出已连接设备
+  Devices,
+  /// 列出设备上的应用
+  Apps {
+    #[arg(short, long)]
+    device: Option<String>,
+    /// 按包名过滤
+    keyword: Option<String>,
+    /// Android 用户 ID（工作资料、分身等），默认当前用户
+    #[arg(short, long)]
+    user: Option<u32>,
+  },
+  /// 按固定时长录制，结束后导出 CSV / JSON
+  Record {
+    #[arg(short, long)]
+    device: Option<String>,
+    #[arg(short, long)]
+    package: String,
+    /// 录制时长（秒）；jsonl 格式下可省略，持续输出直到中断
+    #[arg(short = 't', long)]
+    duration: Option<u64>,
+    /// 采样间隔（毫秒），默认取桌面端设置
+    #[arg(long)]
+    interval_ms: Option<u64>,
+    /// 逗号分隔的指标，如 fps,cpu,memory；默认取桌面端设置
+    #[arg(short, long, value_delimiter = ',')]
+    metrics: Vec<MetricKey>,
+    /// 输出文件（可为命名管道），扩展名决定格式；不指定时写到标准输出
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// 覆盖按扩展名推断的格式；jsonl 边录边写，每个采样一行
+    #[arg(long)]
+    format: Option<record::Format>,
+  },
+  /// 与基线比较，任一指标劣化超过容差时以退出码 1 结束，用于 CI 阻断合并
+  Gate {
+    /// 基线数据：record 导出的 CSV / JSON，或桌面端导出的会话 JSON
+    #[arg(long)]
+    baseline: PathBuf,
+    /// 容差配置 JSON，格式与桌面端门禁配置相同；不指定时各指标容差 5%
+    #[arg(long)]
+    tolerances: Option<PathBuf>,
+    /// 本次数据文件；不指定时按 --package / --duration 现场录制
+    #[arg(long)]
+    current: Option<PathBuf>,
+    #[arg(short, long)]
+    device: Option<String>,
+    #[arg(short, long)]
+    package: Option<String>,
+    /// 现场录制时长（秒）
+    #[arg(short = 't', long)]
+    duration: Option<u64>,
+    #[arg(long)]
+    interval_ms: Option<u64>,
+    #[arg(short, long, value_delimiter = ',')]
+    metrics: Vec<MetricKey>,
+    /// 保存现场录制的数据，便于作为下次的基线
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// 以 JSON 输出检查结果
+    #[arg(long)]
+    json: bool,
+  },
+  /// 在桌面端录制中的会话插入标记，需在桌面端开启控制服务
+  Mark {
+    /// 标记名称，如测试步骤名
+    name: String,
+    /// 有多个会话在录制时按设备选择
+    #[arg(short, long)]
+    device: Option<String>,
+    #[arg(short, long)]
+    session: Option<i64>,
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+    #[arg(long, default_value_t = 7391)]
+    port: u16,
+    /// 控制服务令牌，可通过环境变量 PERFX_TOKEN 提供
+    #[arg(long, env = "PERFX_TOKEN", hide_env_values = true)]
+    token: String,
+  },
+}
+
+fn main() -> ExitCode {
+  let cli = Cli::parse();
+  // 与桌面端共用的设置文件读取失败时退回内置默认值
+  let settings = load_settings().unwrap_or_else(|e| {
+    eprintln!("警告: {e}，使用默认设置");
+    Settings::default()
+  });
+  let adb = cli
+    .adb
+    .or_else(|| settings.adb_path.clone())
+    .unwrap_or_else(|| "adb".into());
+  set_adb_path(Some(adb));
+  register_property_metrics(settings.property_metrics.clone());
+  for (key, e) in register_shell_metrics(settings.shell_metrics.clone()) {
+    eprintln!("警告: 命令指标 {key} 无效: {e}");
+  }
+  for (name, e) in register_metric_scripts(settings.metric_scripts.clone()) {
+    eprintln!("警告: 指标脚本 {name} 无效: {e}");
+  }
+  match run(cli.command, &settings) {
+    Ok(code) => code,
+    Err(e) => {
+      eprintln!("错误: {e}");
+      ExitCode::FAILURE
+    }
+  }
+}
+
+fn run(command: Command, settings: &Settings) -> Result<ExitCode> {
+  match command {
+    Command::Devices => {
+      for device in list_devices()? {
+        println!(
+          "{}\t{}\t{}",
+          device.id,
+          device.state,
+          device.model.as_deref().unwrap_or("-")
+        );
+      }
+    }
+    Command::Apps {
+      device,
+      keyword,
+      user,
+    } => {
+      let device = pick_device(device)?;
+      for app in list_apps(&device, keyword.as_deref(), user)? {
+        let kind = if app.is_system { "system" } else { "user" };
+        println!("{}\t{}", app.package, kind);
+      }
+    }
+    Command::Record {
+      device,
+      package,
+      duration,
+      interval_ms,
+      metrics,
+      output,
+      format,
+    } => {
+      let device = pick_device(device)?;
+      let (interval_ms, metrics) = sampling(interval_ms, metrics, settings);
+      let format = match (format, &output) {
+        (Some(format), _) => format,
+        (None, Some(path)) => record::Format::from_path(path),
+        (None, None) => record::Format::Csv,
+      };
+      if format == record::Format::Jsonl {
+        record::stream(
+          &device,
+          &package,
+          &metrics,
+          duration,
+          interval_ms,
+          output.as_deref(),
+        )?;
+        return Ok(ExitCode::SUCCESS);
+      }
+      let Some(duration) = duration else {
+        return Err(CliError::Usage(
+          "CSV / JSON 格式需要 --duration；持续输出请用 --format jsonl".into(),
+        ));
+      };
+      let samples = record::record(&device, &package, &metrics, duration, interval_ms)?;
+      record::write(&samples, format, output.as_deref())?;
+    }
+    Command::Gate {
+      baseline,
+      tolerances,
+      current,
+      device,
+      package,
+      duration,
+      interval_ms,
+      metrics,
+      output,
+      json,
+    } => {
+      let config = gate::load_config(tolerances.as_deref())?;
+      let (interval_ms, metrics) = sampling(interval_ms, metrics, settings);
+      let baseline = data::load(&baseline)?;
+      let current = match current {
+        Some(path) => data::load(&path)?,
+        None => {
+          let (Some(package), Some(duration)) = (package, duration) else {
+            return Err(CliError::Usage(
+              "未指定 --current 时需要 --package 与 --duration 现场录制".into(),
+            ));
+          };
+          let device = pick_device(device)?;
+          let samples = record::record(&device, &package, &metrics, duration, interval_ms)?;
+          if let Some(path) = &output {
+            record::write(&samples, record::Format::from_path(path), Some(path))?;
+          }
+          data::from_samples(&samples)
+        }
+      };
+      let checks = gate::evaluate(&current, &baseline, &config);
+      if json {
+        println!("{}", serde_json::to_string_pretty(&checks)?);
+      } else {
+        gate::print_table(&checks);
+      }
+      if !gate::passed(&checks) {
+        return Ok(ExitCode::FAILURE);
+      }
+    }
+    Command::Mark {
+      name,
+      device,
+      session,
+      host,
+      port,
+      token,
+    } => {
+      let endpoint = mark::Endpoint { host, port, token };
+      let marker = mark::mark(&endpoint, &name, device.as_deref(), session)?;
+      println!("{}\t{}", marker.t_ms, marker.name);
+    }
+  }
+  Ok(ExitCode::SUCCESS)
+}
+
+/// 未通过参数指定的采样间隔与指标取桌面端设置
+fn sampling(
+  interval_ms: Option<u64>,
+  metrics: Vec<MetricKey>,
+  settings: &Settings,
+) -> (u64, Vec<MetricKey>) {
+  let interval_ms = interval_ms.unwrap_or(settings.interval_ms);
+  let metrics = if metrics.is_empty() {
+    settings.default_metrics.clone()
+  } else {
+    metrics
+  };
+  (interval_ms, metrics)
+}
+
+/// 未指定设备时，仅连接一台设备则直接使用
+fn pick_device(device: Option<String>) -> Result<String> {
+  if let Some(device) = device {
+    return Ok(device);
+  }
+  let online: Vec<_> = list_devices()?
+    .into_iter()
+    .filter(|d| d.state == "device")
+    .collect();
+  match online.as_slice() {
+    [only] => Ok(only.id.clone()),
+    [] => Err(CliError::Usage("没有已连接的设备".into())),
+    _ => Err(CliError::Usage("连接了多台设备，请用 --device 指定".into())),
+  }
+}
@@ -0,0 +1,47 @@
+use crate::error::{CliError, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+/// 桌面端控制服务的地址与令牌
+pub struct Endpoint {
+  pub host: String,
+  pub port: u16,
+  pub token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Marker {
+  pub t_ms: u64,
+  pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+  error: String,
+}
+
+/// 在录制中的会话当前时刻插入标记；未指定会话时由服务端按设备或唯一录制会话确定
+pub fn mark(
+  endpoint: &Endpoint,
+  name: &str,
+  device: Option<&str>,
+  session: Option<i64>,
+) -> Result<Marker> {
+  let url = format!("http://{}:{}/api/markers", endpoint.host, endpoint.port);
+  let response = ureq::post(&url)
+    .set("Authorization", &format!("Bearer {}", endpoint.token))
+    .send_json(json!({ "name": name, "device_id": device, "session_id": session }));
+  match response {
+    Ok(response) => response
+      .into_json()
+      .map_err(|e| CliError::Server(format!("响应无法解析: {e}"))),
+    Err(ureq::Error::Status(status, response)) => {
+      let message = response
+        .into_json::<ErrorBody>()
+        .map(|b| b.error)
+        .unwrap_or_else(|_| format!("HTTP {status}"));
+      Err(CliError::Server(message))
+    }
+    Err(e) => Err(CliError::Connect(e.to_string())),
+  }
+}
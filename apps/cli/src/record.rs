@@ -0,0 +1,240 @@
+use crate::error::Result;
+use clap::ValueEnum;
+use perfx_core::{
+  adb::{MetricKey, MetricsSnapshot},
+  sampler::{self, SampleConfig},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+  fs::{File, OpenOptions},
+  io::{self, Write},
+  path::Path,
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+  Csv,
+  Json,
+  /// 每个采样一行 JSON，边录边写，便于接 jq 或其他进程
+  Jsonl,
+}
+
+impl Format {
+  pub fn from_path(path: &Path) -> Self {
+    match path.extension().and_then(|e| e.to_str()) {
+      Some(ext) if ext.eq_ignore_ascii_case("json") => Self::Json,
+      Some(ext) if ext.eq_ignore_ascii_case("jsonl") || ext.eq_ignore_ascii_case("ndjson") => {
+        Self::Jsonl
+      }
+      _ => Self::Csv,
+    }
+  }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Sample {
+  /// 相对录制开始的毫秒数
+  pub t_ms: u64,
+  #[serde(flatten)]
+  pub metrics: MetricsSnapshot,
+}
+
+/// CSV 列，与桌面端导出保持一致的单位
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CsvRow {
+  t_ms: u64,
+  fps: Option<f64>,
+  cpu: Option<f64>,
+  memory_mb: Option<f64>,
+  power: Option<f64>,
+  /// 旧版本导出的 CSV 没有该列
+  #[serde(default)]
+  power_mah: Option<f64>,
+  network_kbps: Option<f64>,
+  battery_level: Option<f64>,
+  battery_temp_c: Option<f64>,
+}
+
+impl From<CsvRow> for MetricsSnapshot {
+  fn from(row: CsvRow) -> Self {
+    Self {
+      fps: row.fps,
+      cpu: row.cpu,
+      memory_mb: row.memory_mb,
+      power: row.power,
+      power_mah: row.power_mah,
+      network_kbps: row.network_kbps,
+      battery_level: row.battery_level,
+      battery_temp_c: row.battery_temp_c,
+      ..Default::default()
+    }
+  }
+}
+
+/// 按间隔采样直到达到时长（不指定时一直采集），单次采集失败只打印警告；
+/// 回调返回 false 时提前结束
+fn sample_loop(
+  device: &str,
+  package: &str,
+  metrics: &[MetricKey],
+  duration_secs: Option<u64>,
+  interval_ms: u64,
+  mut on_sample: impl FnMut(Sample) -> Result<bool>,
+) -> Result<usize> {
+  let config = SampleConfig {
+    interval: Duration::from_millis(interval_ms),
+    duration: duration_secs.map(Duration::from_secs),
+    ..SampleConfig::new(device, package, metrics)
+  };
+
+  match duration_secs {
+    Some(secs) => eprintln!("开始录制 {device} / {package}，时长 {secs}s"),
+    None => eprintln!("开始录制 {device} / {package}，按 Ctrl+C 结束"),
+  }
+  let count = sampler::sample_loop(&config, |t_ms, result| match result {
+    Ok(snapshot) => {
+      eprintln!(
+        "[{:>6.1}s] fps={} cpu={} mem={}",
+        t_ms as f64 / 1000.0,
+        show(snapshot.fps),
+        show(snapshot.cpu),
+        show(snapshot.memory_mb)
+      );
+      on_sample(Sample {
+        t_ms,
+        metrics: snapshot,
+      })
+    }
+    Err(e) => {
+      eprintln!("采集失败: {e}");
+      Ok(true)
+    }
+  })?;
+  eprintln!("录制结束，共 {count} 个采样");
+  Ok(count)
+}
+
+pub fn record(
+  device: &str,
+  package: &str,
+  metrics: &[MetricKey],
+  duration_secs: u64,
+  interval_ms: u64,
+) -> Result<Vec<Sample>> {
+  let mut samples = Vec::new();
+  sample_loop(
+    device,
+    package,
+    metrics,
+    Some(duration_secs),
+    interval_ms,
+    |sample| {
+      samples.push(sample);
+      Ok(true)
+    },
+  )?;
+  Ok(samples)
+}
+
+/// JSONL 的一行，在采样之外带上绝对时间与来源
+#[derive(Serialize)]
+struct StreamLine<'a> {
+  timestamp_ms: u128,
+  device: &'a str,
+  package: &'a str,
+  #[serde(flatten)]
+  sample: &'a Sample,
+}
+
+/// 边采集边按 JSONL 写到标准输出或文件（可为命名管道），每行立即刷新；
+/// 读端关闭（如 `| head`）时正常结束
+pub fn stream(
+  device: &str,
+  package: &str,
+  metrics: &[MetricKey],
+  duration_secs: Option<u64>,
+  interval_ms: u64,
+  output: Option<&Path>,
+) -> Result<()> {
+  let mut out: Box<dyn Write> = match output {
+    Some(path) => Box::new(
+      OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)?,
+    ),
+    None => Box::new(io::stdout().lock()),
+  };
+  sample_loop(
+    device,
+    package,
+    metrics,
+    duration_secs,
+    interval_ms,
+    |sample| {
+      let line = StreamLine {
+        timestamp_ms: SystemTime::now()
+          .duration_since(UNIX_EPOCH)
+          .map(|d| d.as_millis())
+          .unwrap_or(0),
+        device,
+        package,
+        sample: &sample,
+      };
+      let mut text = serde_json::to_string(&line)?;
+      text.push('\n');
+      match out.write_all(text.as_bytes()).and_then(|_| out.flush()) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(false),
+        Err(e) => Err(e.into()),
+      }
+    },
+  )?;
+  Ok(())
+}
+
+fn show(value: Option<f64>) -> String {
+  value.map_or_else(|| "-".into(), |v| format!("{v:.1}"))
+}
+
+pub fn write(samples: &[Sample], format: Format, output: Option<&Path>) -> Result<()> {
+  let out: Box<dyn Write> = match output {
+    Some(path) => Box::new(File::create(path)?),
+    None => Box::new(io::stdout().lock()),
+  };
+  match format {
+    Format::Json => serde_json::to_writer_pretty(out, samples)?,
+    Format::Jsonl => {
+      let mut out = out;
+      for sample in samples {
+        serde_json::to_writer(&mut out, sample)?;
+        out.write_all(b"\n")?;
+      }
+      out.flush()?;
+    }
+    Format::Csv => {
+      let mut writer = csv::Writer::from_writer(out);
+      for sample in samples {
+        let m = &sample.metrics;
+        writer.serialize(CsvRow {
+          t_ms: sample.t_ms,
+          fps: m.fps,
+          cpu: m.cpu,
+          memory_mb: m.memory_mb,
+          power: m.power,
+          power_mah: m.power_mah,
+          network_kbps: m.network_kbps,
+          battery_level: m.battery_level,
+          battery_temp_c: m.battery_temp_c,
+        })?;
+      }
+      writer.flush()?;
+    }
+  }
+  if let Some(path) = output {
+    eprintln!("已导出到 {}", path.display());
+  }
+  Ok(())
+}
@@ -1,3 +1,14 @@
+const PROTO_ROOT: &str = "../../../proto";
+const CONTROL_PROTO: &str = "perfx/v1/control.proto";
+
 fn main() {
+  // 用纯 Rust 的 protox 解析 .proto，构建环境无需安装 protoc
+  println!("cargo:rerun-if-changed={PROTO_ROOT}/{CONTROL_PROTO}");
+  let descriptors =
+    protox::compile([CONTROL_PROTO], [PROTO_ROOT]).expect("解析 control.proto 失败");
+  tonic_prost_build::configure()
+    .build_client(false)
+    .compile_fds(descriptors)
+    .expect("生成 gRPC 代码失败");
   tauri_build::build()
 }
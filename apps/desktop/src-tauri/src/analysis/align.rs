@@ -0,0 +1,69 @@
+use crate::{
+  analysis::error::{AnalysisError, Result},
+  session::SessionData,
+};
+use serde::{Deserialize, Serialize};
+
+/// 多会话对齐方式
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Alignment {
+  /// 以会话开始时刻对齐
+  #[default]
+  Elapsed,
+  /// 以同名标记对齐
+  Marker { name: String },
+}
+
+/// 计算会话在对齐后的时间零点（会话内的 t_ms）
+pub fn anchor_ms(data: &SessionData, alignment: &Alignment) -> Result<u64> {
+  match alignment {
+    Alignment::Elapsed => Ok(0),
+    Alignment::Marker { name } => {
+      data
+        .marker(name)
+        .map(|m| m.t_ms)
+        .ok_or_else(|| AnalysisError::MarkerNotFound {
+          session_id: data.session.id,
+          name: name.clone(),
+        })
+    }
+  }
+}
+
+/// 将序列平移到对齐后的时间轴（负值表示在锚点之前）
+pub fn shift(series: &[(u64, f64)], anchor_ms: u64) -> Vec<(i64, f64)> {
+  series
+    .iter()
+    .map(|(t, v)| (*t as i64 - anchor_ms as i64, *v))
+    .collect()
+}
+
+/// 按固定步长重采样（取不晚于网格点的最近一个采样），超出 `max_gap_ms` 视为缺失
+pub fn resample(
+  series: &[(i64, f64)],
+  start: i64,
+  end: i64,
+  step: i64,
+  max_gap_ms: i64,
+) -> Vec<Option<f64>> {
+  let mut out = Vec::new();
+  if step <= 0 || end < start {
+    return out;
+  }
+
+  let mut cursor = 0usize;
+  let mut t = start;
+  while t <= end {
+    while cursor + 1 < series.len() && series[cursor + 1].0 <= t {
+      cursor += 1;
+    }
+    let value = series
+      .get(cursor)
+      .filter(|(ts, _)| *ts <= t && t - *ts <= max_gap_ms)
+      .map(|(_, v)| *v);
+    out.push(value);
+    t += step;
+  }
+  out
+}
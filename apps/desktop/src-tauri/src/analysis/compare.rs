@@ -0,0 +1,184 @@
+use crate::{
+  analysis::{
+    align::{anchor_ms, resample, shift, Alignment},
+    error::Result,
+    summary::{summarize_values, MetricSummary},
+  },
+  session::SessionData,
+};
+use serde::{Deserialize, Serialize};
+
+// 对齐网格步长，与前端采样间隔一致
+const GRID_STEP_MS: i64 = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionComparison {
+  pub a: i64,
+  pub b: i64,
+  pub alignment: Alignment,
+  pub a_anchor_ms: u64,
+  pub b_anchor_ms: u64,
+  pub metrics: Vec<MetricComparison>,
+}
+
+/// 对齐窗口内单个指标的对比结果，delta 均为 b - a；
+/// 只有一方采集了该指标时，另一方与 delta 为空，统计取自该方的整段数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricComparison {
+  pub metric: String,
+  pub a: Option<MetricSummary>,
+  pub b: Option<MetricSummary>,
+  pub delta: Option<StatDelta>,
+  pub avg_delta_pct: Option<f64>,
+  pub points: Vec<AlignedPoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatDelta {
+  pub min: f64,
+  pub avg: f64,
+  pub max: f64,
+  pub p50: f64,
+  pub p90: f64,
+  pub p95: f64,
+  pub p99: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlignedPoint {
+  pub t_ms: i64, // 相对对齐锚点
+  pub a: Option<f64>,
+  pub b: Option<f64>,
+}
+
+/// 对齐两个会话，在重叠时间窗内逐指标计算统计差异
+pub fn compare_sessions(
+  a: &SessionData,
+  b: &SessionData,
+  alignment: &Alignment,
+) -> Result<SessionComparison> {
+  let a_anchor = anchor_ms(a, alignment)?;
+  let b_anchor = anchor_ms(b, alignment)?;
+
+  let mut names = a.metric_names();
+  for name in b.metric_names() {
+    if !names.contains(&name) {
+      names.push(name);
+    }
+  }
+
+  let mut metrics = Vec::new();
+  for metric in names {
+    let series_a = shift(&a.series(&metric), a_anchor);
+    let series_b = shift(&b.series(&metric), b_anchor);
+    let (first_a, first_b) = match (series_a.first(), series_b.first()) {
+      (Some(first_a), Some(first_b)) => (first_a, first_b),
+      (None, None) => continue,
+      _ => {
+        metrics.extend(one_sided(&metric, &series_a, &series_b));
+        continue;
+      }
+    };
+
+    // 只比较两者都有数据的重叠区间
+    let start = first_a.0.max(first_b.0);
+    let end = series_a[series_a.len() - 1]
+      .0
+      .min(series_b[series_b.len() - 1].0);
+    if end < start {
+      continue;
+    }
+
+    let grid_a = resample(&series_a, start, end, GRID_STEP_MS, GRID_STEP_MS * 2);
+    let grid_b = resample(&series_b, start, end, GRID_STEP_MS, GRID_STEP_MS * 2);
+    let points: Vec<AlignedPoint> = grid_a
+      .iter()
+      .zip(grid_b.iter())
+      .enumerate()
+      .map(|(i, (a, b))| AlignedPoint {
+        t_ms: start + i as i64 * GRID_STEP_MS,
+        a: *a,
+        b: *b,
+      })
+      .collect();
+
+    let in_window = |series: &[(i64, f64)]| -> Vec<f64> {
+      series
+        .iter()
+        .filter(|(t, _)| *t >= start && *t <= end)
+        .map(|(_, v)| *v)
+        .collect()
+    };
+    let (Some(stats_a), Some(stats_b)) = (
+      summarize_values(&metric, &in_window(&series_a)),
+      summarize_values(&metric, &in_window(&series_b)),
+    ) else {
+      continue;
+    };
+
+    let delta = StatDelta {
+      min: stats_b.min - stats_a.min,
+      avg: stats_b.avg - stats_a.avg,
+      max: stats_b.max - stats_a.max,
+      p50: stats_b.p50 - stats_a.p50,
+      p90: stats_b.p90 - stats_a.p90,
+      p95: stats_b.p95 - stats_a.p95,
+      p99: stats_b.p99 - stats_a.p99,
+    };
+    let avg_delta_pct = (stats_a.avg.abs() > f64::EPSILON).then(|| delta.avg / stats_a.avg * 100.0);
+
+    metrics.push(MetricComparison {
+      metric,
+      a: Some(stats_a),
+      b: Some(stats_b),
+      delta: Some(delta),
+      avg_delta_pct,
+      points,
+    });
+  }
+
+  Ok(SessionComparison {
+    a: a.session.id,
+    b: b.session.id,
+    alignment: alignment.clone(),
+    a_anchor_ms: a_anchor,
+    b_anchor_ms: b_anchor,
+    metrics,
+  })
+}
+
+/// 只有一方有数据的指标：统计该方整段数据，另一方留空
+fn one_sided(
+  metric: &str,
+  series_a: &[(i64, f64)],
+  series_b: &[(i64, f64)],
+) -> Option<MetricComparison> {
+  let only_b = series_a.is_empty();
+  let series = if only_b { series_b } else { series_a };
+  let start = series.first()?.0;
+  let end = series[series.len() - 1].0;
+  let values: Vec<f64> = series.iter().map(|(_, v)| *v).collect();
+  let stats = summarize_values(metric, &values)?;
+  let points = resample(series, start, end, GRID_STEP_MS, GRID_STEP_MS * 2)
+    .into_iter()
+    .enumerate()
+    .map(|(i, value)| AlignedPoint {
+      t_ms: start + i as i64 * GRID_STEP_MS,
+      a: value.filter(|_| !only_b),
+      b: value.filter(|_| only_b),
+    })
+    .collect();
+  let (a, b) = if only_b {
+    (None, Some(stats))
+  } else {
+    (Some(stats), None)
+  };
+  Some(MetricComparison {
+    metric: metric.to_string(),
+    a,
+    b,
+    delta: None,
+    avg_delta_pct: None,
+    points,
+  })
+}
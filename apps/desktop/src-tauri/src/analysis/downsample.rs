@@ -0,0 +1,123 @@
+use crate::{analysis::error::Result, session::load_session};
+use serde::{Deserialize, Serialize};
+
+// 未指定目标点数时的默认值，约为常见图表宽度的两倍
+const DEFAULT_MAX_POINTS: usize = 2000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesQuery {
+  pub session_id: i64,
+  #[serde(default)]
+  pub metrics: Vec<String>,
+  #[serde(default)]
+  pub from_ms: Option<u64>,
+  #[serde(default)]
+  pub to_ms: Option<u64>,
+  /// 每个指标最多返回的点数，0 表示返回全部原始点
+  #[serde(default)]
+  pub max_points: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricSeries {
+  pub metric: String,
+  /// 范围内的原始点数
+  pub total: usize,
+  pub downsampled: bool,
+  /// `[t_ms, value]` 点列
+  pub points: Vec<(u64, f64)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeriesResult {
+  pub session_id: i64,
+  pub from_ms: u64,
+  pub to_ms: u64,
+  pub series: Vec<MetricSeries>,
+}
+
+/// 按时间范围读取指标序列，超过目标点数时用 LTTB 降采样；缩放到小范围时即为原始精度
+pub fn query_series(query: &SeriesQuery) -> Result<SeriesResult> {
+  let data = load_session(query.session_id)?;
+  let from_ms = query.from_ms.unwrap_or(0);
+  let to_ms = query
+    .to_ms
+    .or_else(|| data.samples.last().map(|s| s.t_ms))
+    .unwrap_or(0);
+  let max_points = query.max_points.unwrap_or(DEFAULT_MAX_POINTS);
+
+  let metrics = if query.metrics.is_empty() {
+    data.metric_names()
+  } else {
+    query.metrics.clone()
+  };
+  let series = metrics
+    .into_iter()
+    .map(|metric| {
+      let points: Vec<(u64, f64)> = data
+        .series(&metric)
+        .into_iter()
+        .filter(|(t, _)| (from_ms..=to_ms).contains(t))
+        .collect();
+      let total = points.len();
+      let downsampled = max_points > 0 && total > max_points;
+      MetricSeries {
+        metric,
+        total,
+        downsampled,
+        points: if downsampled {
+          lttb(&points, max_points)
+        } else {
+          points
+        },
+      }
+    })
+    .collect();
+
+  Ok(SeriesResult {
+    session_id: query.session_id,
+    from_ms,
+    to_ms,
+    series,
+  })
+}
+
+/// Largest-Triangle-Three-Buckets 降采样，保留首尾点与视觉上的峰谷
+pub fn lttb(points: &[(u64, f64)], threshold: usize) -> Vec<(u64, f64)> {
+  if threshold >= points.len() || threshold < 3 {
+    return points.to_vec();
+  }
+
+  let mut sampled = Vec::with_capacity(threshold);
+  sampled.push(points[0]);
+
+  // 去掉首尾后其余点均分为 threshold - 2 个桶
+  let bucket_size = (points.len() - 2) as f64 / (threshold - 2) as f64;
+  let mut selected = 0usize;
+  for bucket in 0..threshold - 2 {
+    let start = (bucket as f64 * bucket_size) as usize + 1;
+    let end = ((bucket + 1) as f64 * bucket_size) as usize + 1;
+
+    // 下一个桶的平均点作为三角形的第三个顶点，最后一个桶对应末尾点
+    let next_end = (((bucket + 2) as f64 * bucket_size) as usize + 1).min(points.len());
+    let next = &points[end..next_end];
+    let avg_t = next.iter().map(|(t, _)| *t as f64).sum::<f64>() / next.len() as f64;
+    let avg_v = next.iter().map(|(_, v)| *v).sum::<f64>() / next.len() as f64;
+
+    let (at, av) = (points[selected].0 as f64, points[selected].1);
+    let mut max_area = -1.0;
+    let mut max_index = start;
+    for (index, (t, v)) in points.iter().enumerate().take(end).skip(start) {
+      let area = ((at - avg_t) * (v - av) - (at - *t as f64) * (avg_v - av)).abs();
+      if area > max_area {
+        max_area = area;
+        max_index = index;
+      }
+    }
+    sampled.push(points[max_index]);
+    selected = max_index;
+  }
+
+  sampled.push(points[points.len() - 1]);
+  sampled
+}
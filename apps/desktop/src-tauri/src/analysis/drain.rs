@@ -0,0 +1,139 @@
+use crate::session::{Sample, SessionData};
+use serde::{Deserialize, Serialize};
+
+// 缺少电压采样时使用的锂电池标称电压
+const NOMINAL_VOLTAGE_V: f64 = 3.85;
+// 相邻采样间隔超过该值时视为中断，不对空档积分
+const MAX_GAP_MS: u64 = 10_000;
+const MS_PER_HOUR: f64 = 3_600_000.0;
+
+/// 一段时间内的耗电量，由电流采样按时间积分得到
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrainStats {
+  /// 实际参与积分的时长
+  pub covered_ms: u64,
+  pub charge_mah: f64,
+  pub energy_mwh: f64,
+  /// 平均放电电流，即每小时耗电 mAh
+  pub avg_current_ma: f64,
+  pub avg_power_mw: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentDrain {
+  /// 分段起始标记名，第一段为空
+  pub name: String,
+  pub from_ms: u64,
+  pub to_ms: u64,
+  pub drain: DrainStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatteryDrain {
+  #[serde(flatten)]
+  pub total: DrainStats,
+  /// 没有电压采样、按标称电压换算能耗
+  pub voltage_estimated: bool,
+  /// 电量百分比的下降值，可与积分结果互相印证
+  pub level_drop_pct: Option<f64>,
+  pub segments: Vec<SegmentDrain>,
+}
+
+/// 对功耗（电流，mA）采样做梯形积分，估算整场及各标记分段的耗电量
+///
+/// 没有功耗采样时返回 None。
+pub fn estimate_drain(data: &SessionData) -> Option<BatteryDrain> {
+  let total = integrate(&data.samples, 0, u64::MAX)?;
+  let voltage_estimated = !data
+    .samples
+    .iter()
+    .any(|s| s.values.contains_key("battery_voltage"));
+
+  let levels = data.series("battery");
+  let level_drop_pct = match (levels.first(), levels.last()) {
+    (Some((_, first)), Some((_, last))) if levels.len() > 1 => Some(first - last),
+    _ => None,
+  };
+
+  let segments = segment_bounds(data)
+    .into_iter()
+    .filter_map(|(name, from_ms, to_ms)| {
+      let drain = integrate(&data.samples, from_ms, to_ms)?;
+      Some(SegmentDrain {
+        name,
+        from_ms,
+        to_ms,
+        drain,
+      })
+    })
+    .collect();
+
+  Some(BatteryDrain {
+    total,
+    voltage_estimated,
+    level_drop_pct,
+    segments,
+  })
+}
+
+/// 按标记切分的 `(起始标记名, from_ms, to_ms)`，第一段名称为空；没有标记时为空
+pub(crate) fn segment_bounds(data: &SessionData) -> Vec<(String, u64, u64)> {
+  if data.markers.is_empty() {
+    return Vec::new();
+  }
+  let mut bounds: Vec<(String, u64)> = vec![(String::new(), 0)];
+  bounds.extend(data.markers.iter().map(|m| (m.name.clone(), m.t_ms)));
+  bounds.sort_by_key(|(_, t)| *t);
+  let end_ms = data.samples.last().map_or(0, |s| s.t_ms);
+  bounds
+    .iter()
+    .enumerate()
+    .map(|(index, (name, from_ms))| {
+      let to_ms = bounds.get(index + 1).map_or(end_ms, |(_, t)| *t);
+      (name.clone(), *from_ms, to_ms)
+    })
+    .collect()
+}
+
+/// 对 [from_ms, to_ms] 内的采样积分；电流取绝对值，兼容充放电符号相反的机型
+fn integrate(samples: &[Sample], from_ms: u64, to_ms: u64) -> Option<DrainStats> {
+  let points: Vec<(u64, f64, f64)> = samples
+    .iter()
+    .filter(|s| (from_ms..=to_ms).contains(&s.t_ms))
+    .filter_map(|s| {
+      let current = s.values.get("power").copied().filter(|v| v.is_finite())?;
+      let voltage = s
+        .values
+        .get("battery_voltage")
+        .copied()
+        .filter(|v| *v > 0.0)
+        .unwrap_or(NOMINAL_VOLTAGE_V);
+      Some((s.t_ms, current.abs(), voltage))
+    })
+    .collect();
+
+  let (mut covered_ms, mut charge, mut energy) = (0u64, 0.0, 0.0);
+  for pair in points.windows(2) {
+    let ((t0, i0, v0), (t1, i1, v1)) = (pair[0], pair[1]);
+    let dt = t1.saturating_sub(t0);
+    if dt == 0 || dt > MAX_GAP_MS {
+      continue;
+    }
+    let hours = dt as f64 / MS_PER_HOUR;
+    charge += (i0 + i1) / 2.0 * hours;
+    energy += (i0 * v0 + i1 * v1) / 2.0 * hours;
+    covered_ms += dt;
+  }
+  if covered_ms == 0 {
+    return None;
+  }
+
+  let hours = covered_ms as f64 / MS_PER_HOUR;
+  Some(DrainStats {
+    covered_ms,
+    charge_mah: charge,
+    energy_mwh: energy,
+    avg_current_ma: charge / hours,
+    avg_power_mw: energy / hours,
+  })
+}
@@ -0,0 +1,208 @@
+use crate::{
+  adb::energy::{EnergyProbe, PolicyResidency, PowerProfile},
+  analysis::{
+    drain::segment_bounds,
+    error::{AnalysisError, Result},
+  },
+  session::{
+    energy::{cached_profile, load_probes},
+    load_session, SessionData,
+  },
+};
+use serde::{Deserialize, Serialize};
+
+const MS_PER_HOUR: f64 = 3_600_000.0;
+// time_in_state 的计数单位为 10ms
+const TICK_MS: f64 = 10.0;
+// 读不到亮度时按中等亮度估算
+const DEFAULT_BRIGHTNESS: f64 = 0.5;
+
+/// 按部件拆分的估算耗电量（mAh）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComponentEnergy {
+  pub cpu_mah: f64,
+  pub screen_mah: f64,
+  /// 系统待机与唤醒基础耗电
+  pub base_mah: f64,
+  pub total_mah: f64,
+}
+
+impl ComponentEnergy {
+  fn add_scaled(&mut self, other: &ComponentEnergy, ratio: f64) {
+    self.cpu_mah += other.cpu_mah * ratio;
+    self.screen_mah += other.screen_mah * ratio;
+    self.base_mah += other.base_mah * ratio;
+    self.total_mah += other.total_mah * ratio;
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentEnergy {
+  /// 分段起始标记名，第一段为空
+  pub name: String,
+  pub from_ms: u64,
+  pub to_ms: u64,
+  pub energy: ComponentEnergy,
+}
+
+/// 基于 power_profile 的能耗估算，适用于没有 ODPM 供电轨的设备
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnergyEstimate {
+  pub session_id: i64,
+  /// 首末两次探测之间的时长
+  pub covered_ms: u64,
+  pub total: ComponentEnergy,
+  /// 亮屏时长占比
+  pub screen_on_pct: Option<f64>,
+  pub segments: Vec<SegmentEnergy>,
+}
+
+pub fn session_energy(session_id: i64) -> Result<EnergyEstimate> {
+  let data = load_session(session_id)?;
+  let probes = load_probes(session_id)?;
+  if probes.len() < 2 {
+    return Err(AnalysisError::NoEnergyData(session_id));
+  }
+  let profile = match &data.meta.build_fingerprint {
+    Some(fingerprint) => cached_profile(fingerprint)?,
+    None => None,
+  }
+  .ok_or(AnalysisError::NoPowerProfile(session_id))?;
+  Ok(estimate_energy(&data, &probes, &profile))
+}
+
+/// 相邻两次探测之间按频率驻留、各核忙碌比例与屏幕状态估算耗电，再按时间重叠分摊到标记分段
+pub fn estimate_energy(
+  data: &SessionData,
+  probes: &[EnergyProbe],
+  profile: &PowerProfile,
+) -> EnergyEstimate {
+  let intervals: Vec<(u64, u64, ComponentEnergy, bool)> = probes
+    .windows(2)
+    .filter(|pair| pair[1].t_ms > pair[0].t_ms)
+    .map(|pair| {
+      let (energy, screen_on) = interval_energy(&pair[0], &pair[1], profile);
+      (pair[0].t_ms, pair[1].t_ms, energy, screen_on)
+    })
+    .collect();
+
+  let spread = |from_ms: u64, to_ms: u64| -> ComponentEnergy {
+    let mut energy = ComponentEnergy::default();
+    for (t0, t1, interval, _) in &intervals {
+      let overlap = to_ms.min(*t1).saturating_sub(from_ms.max(*t0));
+      if overlap > 0 {
+        energy.add_scaled(interval, overlap as f64 / (t1 - t0) as f64);
+      }
+    }
+    energy
+  };
+
+  let covered_ms: u64 = intervals.iter().map(|(t0, t1, ..)| t1 - t0).sum();
+  let screen_on_ms: u64 = intervals
+    .iter()
+    .filter(|(.., screen_on)| *screen_on)
+    .map(|(t0, t1, ..)| t1 - t0)
+    .sum();
+  let segments = segment_bounds(data)
+    .into_iter()
+    .map(|(name, from_ms, to_ms)| SegmentEnergy {
+      energy: spread(from_ms, to_ms),
+      name,
+      from_ms,
+      to_ms,
+    })
+    .collect();
+
+  EnergyEstimate {
+    session_id: data.session.id,
+    covered_ms,
+    total: spread(0, u64::MAX),
+    screen_on_pct: (covered_ms > 0).then(|| screen_on_ms as f64 / covered_ms as f64 * 100.0),
+    segments,
+  }
+}
+
+fn interval_energy(
+  from: &EnergyProbe,
+  to: &EnergyProbe,
+  profile: &PowerProfile,
+) -> (ComponentEnergy, bool) {
+  let hours = (to.t_ms - from.t_ms) as f64 / MS_PER_HOUR;
+  let mut energy = ComponentEnergy::default();
+
+  for (cluster, (before, after)) in from.policies.iter().zip(&to.policies).enumerate() {
+    let busy = busy_ratio(from, to, &after.cpus);
+    let cores = after.cpus.len().max(1) as f64;
+    let cluster_ma = profile
+      .item(&[&format!("cpu.cluster_power.cluster{cluster}")])
+      .unwrap_or(0.0);
+    for (freq, ticks) in &after.time_in_state {
+      let prev = residency(before, *freq);
+      let spent_h = ticks.saturating_sub(prev) as f64 * TICK_MS / MS_PER_HOUR;
+      let core_ma = core_power(profile, cluster, *freq).unwrap_or(0.0);
+      energy.cpu_mah += spent_h * (core_ma * cores + cluster_ma) * busy;
+    }
+  }
+
+  // 以区间开始时的屏幕状态代表整个区间
+  let screen_on = from.screen_on.unwrap_or(true);
+  if screen_on {
+    let on_ma = profile
+      .item(&["screen.on.display0", "screen.on"])
+      .unwrap_or(0.0);
+    let full_ma = profile
+      .item(&["screen.full.display0", "screen.full"])
+      .unwrap_or(0.0);
+    let brightness = from.brightness.unwrap_or(DEFAULT_BRIGHTNESS);
+    energy.screen_mah = (on_ma + full_ma * brightness) * hours;
+    energy.base_mah = profile.item(&["cpu.active"]).unwrap_or(0.0) * hours;
+  }
+  energy.base_mah += profile.item(&["cpu.suspend", "cpu.idle"]).unwrap_or(0.0) * hours;
+  energy.total_mah = energy.cpu_mah + energy.screen_mah + energy.base_mah;
+  (energy, screen_on)
+}
+
+fn residency(policy: &PolicyResidency, freq: u64) -> u64 {
+  policy
+    .time_in_state
+    .iter()
+    .find(|(f, _)| *f == freq)
+    .map_or(0, |(_, ticks)| *ticks)
+}
+
+/// 簇内各核在区间内的忙碌比例，读不到 /proc/stat 时按满载估算
+fn busy_ratio(from: &EnergyProbe, to: &EnergyProbe, cpus: &[u32]) -> f64 {
+  let (mut busy, mut total) = (0u64, 0u64);
+  for cpu in cpus {
+    if let (Some((b0, t0)), Some((b1, t1))) = (from.cpu_jiffies.get(cpu), to.cpu_jiffies.get(cpu)) {
+      busy += b1.saturating_sub(*b0);
+      total += t1.saturating_sub(*t0);
+    }
+  }
+  if total == 0 {
+    return 1.0;
+  }
+  busy as f64 / total as f64
+}
+
+/// 单核在指定频率下的电流：新版按簇给出 core_speeds / core_power，旧版为 cpu.speeds / cpu.active 数组
+fn core_power(profile: &PowerProfile, cluster: usize, freq_khz: u64) -> Option<f64> {
+  let (speeds, powers) = match (
+    profile.array(&format!("cpu.core_speeds.cluster{cluster}")),
+    profile.array(&format!("cpu.core_power.cluster{cluster}")),
+  ) {
+    (Some(speeds), Some(powers)) => (speeds, powers),
+    _ => (profile.array("cpu.speeds")?, profile.array("cpu.active")?),
+  };
+  // 取最接近的频点，profile 与内核频率表不一定完全一致
+  let index = speeds
+    .iter()
+    .enumerate()
+    .min_by(|(_, a), (_, b)| {
+      let da = (**a - freq_khz as f64).abs();
+      let db = (**b - freq_khz as f64).abs();
+      da.total_cmp(&db)
+    })
+    .map(|(index, _)| index)?;
+  powers.get(index).or(powers.last()).copied()
+}
@@ -0,0 +1,50 @@
+use crate::{
+  i18n::{detail, Localized},
+  session::error::SessionError,
+};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AnalysisError {
+  #[error(transparent)]
+  Session(#[from] SessionError),
+  #[error("会话 {session_id} 中未找到标记: {name}")]
+  MarkerNotFound { session_id: i64, name: String },
+  #[error("会话 {0} 没有可用的基线")]
+  NoBaseline(i64),
+  #[error("会话 {0} 没有记录能耗估算所需的频率数据，请在录制时开启能耗估算")]
+  NoEnergyData(i64),
+  #[error("会话 {0} 的设备缺少已缓存的 power_profile，无法估算能耗")]
+  NoPowerProfile(i64),
+  #[error("火焰图生成失败: {0}")]
+  Flamegraph(String),
+}
+
+pub type Result<T> = std::result::Result<T, AnalysisError>;
+
+impl Localized for AnalysisError {
+  fn code(&self) -> &'static str {
+    match self {
+      Self::Session(e) => e.code(),
+      Self::MarkerNotFound { .. } => "MARKER_NOT_FOUND",
+      Self::NoBaseline(_) => "NO_BASELINE",
+      Self::NoEnergyData(_) => "NO_ENERGY_DATA",
+      Self::NoPowerProfile(_) => "NO_POWER_PROFILE",
+      Self::Flamegraph(_) => "FLAMEGRAPH",
+    }
+  }
+
+  fn params(&self) -> Value {
+    match self {
+      Self::Session(e) => e.params(),
+      Self::MarkerNotFound { session_id, name } => {
+        json!({ "sessionId": session_id, "name": name })
+      }
+      Self::NoBaseline(id) | Self::NoEnergyData(id) | Self::NoPowerProfile(id) => {
+        json!({ "sessionId": id })
+      }
+      Self::Flamegraph(d) => detail(d),
+    }
+  }
+}
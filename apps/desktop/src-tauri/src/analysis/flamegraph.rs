@@ -0,0 +1,193 @@
+use crate::{
+  analysis::error::{AnalysisError, Result},
+  session::{
+    capture::SIMPLEPERF_REPORT,
+    error::SessionError,
+    events::{
+      add_attachment, attachment_owner, load_attachment, query_attachments, AttachmentInfo,
+    },
+    store::open,
+  },
+};
+use inferno::flamegraph::{self, Options};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 火焰图的一个节点，`value` 为自身及子节点的事件数之和
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FlameNode {
+  pub name: String,
+  pub value: u64,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub children: Vec<FlameNode>,
+}
+
+/// 解析 `simpleperf report-sample --show-callchain` 的输出，按线程名为根折叠调用栈
+///
+/// 每个样本以 `sample:` 开始，`callchain:` 之前的 symbol 为叶子函数，其后依次为调用者。
+pub fn collapse_report_sample(text: &str) -> BTreeMap<String, u64> {
+  let mut stacks = BTreeMap::new();
+  let mut sample: Option<Sample> = None;
+  for line in text.lines() {
+    let line = line.trim();
+    if line == "sample:" {
+      if let Some(done) = sample.take() {
+        done.fold_into(&mut stacks);
+      }
+      sample = Some(Sample::default());
+      continue;
+    }
+    let Some(current) = sample.as_mut() else {
+      continue;
+    };
+    let Some((key, value)) = line.split_once(':') else {
+      continue;
+    };
+    let value = value.trim();
+    match key {
+      "event_count" => current.count = value.parse().unwrap_or(1),
+      "thread_name" => current.thread = value.to_string(),
+      "file" => current.file = value.to_string(),
+      "symbol" => {
+        let file = std::mem::take(&mut current.file);
+        current.frames.push(frame_name(value, &file));
+      }
+      _ => {}
+    }
+  }
+  if let Some(done) = sample {
+    done.fold_into(&mut stacks);
+  }
+  stacks
+}
+
+#[derive(Default)]
+struct Sample {
+  thread: String,
+  count: u64,
+  file: String,
+  /// 叶子在前
+  frames: Vec<String>,
+}
+
+impl Sample {
+  fn fold_into(self, stacks: &mut BTreeMap<String, u64>) {
+    if self.frames.is_empty() {
+      return;
+    }
+    let thread = if self.thread.is_empty() {
+      "[unknown]".to_string()
+    } else {
+      sanitize(&self.thread)
+    };
+    let mut key = thread;
+    for frame in self.frames.iter().rev() {
+      key.push(';');
+      key.push_str(frame);
+    }
+    *stacks.entry(key).or_default() += self.count.max(1);
+  }
+}
+
+/// 没有符号时以所在文件名代替，分号是折叠格式的分隔符需替换
+fn frame_name(symbol: &str, file: &str) -> String {
+  if !symbol.is_empty() && symbol != "unknown" {
+    return sanitize(symbol);
+  }
+  let file = file.rsplit('/').next().unwrap_or(file);
+  if file.is_empty() {
+    "[unknown]".to_string()
+  } else {
+    format!("[{}]", sanitize(file))
+  }
+}
+
+fn sanitize(name: &str) -> String {
+  name.replace(';', ":")
+}
+
+/// 每行 `栈;帧 数量`，可直接交给 inferno / flamegraph.pl / speedscope
+pub fn folded_text(stacks: &BTreeMap<String, u64>) -> String {
+  let mut text = String::new();
+  for (stack, count) in stacks {
+    text.push_str(stack);
+    text.push(' ');
+    text.push_str(&count.to_string());
+    text.push('\n');
+  }
+  text
+}
+
+pub fn render_svg(folded: &str, title: &str) -> Result<Vec<u8>> {
+  let mut options = Options::default();
+  options.title = title.to_string();
+  options.count_name = "events".to_string();
+  let mut svg = Vec::new();
+  flamegraph::from_lines(&mut options, folded.lines(), &mut svg)
+    .map_err(|e| AnalysisError::Flamegraph(e.to_string()))?;
+  Ok(svg)
+}
+
+/// 转为 d3-flame-graph 等前端组件使用的嵌套结构
+pub fn flame_tree(stacks: &BTreeMap<String, u64>) -> FlameNode {
+  let mut root = FlameNode {
+    name: "all".to_string(),
+    ..Default::default()
+  };
+  for (stack, count) in stacks {
+    root.value += count;
+    let mut node = &mut root;
+    for frame in stack.split(';') {
+      let index = match node.children.iter().position(|c| c.name == frame) {
+        Some(index) => index,
+        None => {
+          node.children.push(FlameNode {
+            name: frame.to_string(),
+            ..Default::default()
+          });
+          node.children.len() - 1
+        }
+      };
+      node = &mut node.children[index];
+      node.value += count;
+    }
+  }
+  root
+}
+
+/// 由会话中保存的 perf.data 附件生成折叠栈、SVG 与 JSON 火焰图，附加到同一事件
+///
+/// 使用停止采样时在设备上解析并一同保存的样本文本，设备断开后仍可生成。
+pub fn generate_flamegraph(attachment_id: i64) -> Result<Vec<AttachmentInfo>> {
+  let conn = open()?;
+  let (session_id, event_id) = attachment_owner(&conn, attachment_id)?;
+  let report = query_attachments(&conn, session_id)?
+    .into_iter()
+    .find(|a| event_id.is_some() && a.event_id == event_id && a.name == SIMPLEPERF_REPORT)
+    .ok_or_else(|| AnalysisError::Flamegraph("该采样没有保存解析后的样本".into()))?;
+  let report = load_attachment(report.id)?;
+  let stacks = collapse_report_sample(&String::from_utf8_lossy(&report));
+  if stacks.is_empty() {
+    return Err(AnalysisError::Flamegraph("采样数据中没有调用栈".into()));
+  }
+
+  let folded = folded_text(&stacks);
+  let svg = render_svg(&folded, "CPU 火焰图")?;
+  let tree = serde_json::to_vec(&flame_tree(&stacks)).map_err(SessionError::from)?;
+  let mut attachments = Vec::new();
+  for (name, content) in [
+    ("perf.folded", folded.as_bytes()),
+    ("flamegraph.svg", svg.as_slice()),
+    ("flamegraph.json", tree.as_slice()),
+  ] {
+    attachments.push(add_attachment(
+      &conn,
+      session_id,
+      event_id,
+      "flamegraph",
+      name,
+      content,
+    )?);
+  }
+  Ok(attachments)
+}
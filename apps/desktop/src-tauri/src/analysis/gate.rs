@@ -0,0 +1,76 @@
+use crate::{
+  analysis::{
+    error::{AnalysisError, Result},
+    summary::summarize,
+  },
+  session::{
+    baseline::baseline_for_session,
+    load_session,
+    store::{get_kv, set_kv},
+    SessionData,
+  },
+};
+use perfx_core::analysis::gate::evaluate_checks;
+pub use perfx_core::analysis::gate::{CheckStatus, GateCheck, GateConfig};
+use serde::{Deserialize, Serialize};
+
+const CONFIG_KEY: &str = "gate_config";
+
+/// 门禁结果：passed 为 false 时 failures 列出未通过的指标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GateReport {
+  pub session_id: i64,
+  pub baseline_session_id: i64,
+  pub passed: bool,
+  pub failures: Vec<String>,
+  pub checks: Vec<GateCheck>,
+}
+
+pub fn load_config() -> Result<GateConfig> {
+  Ok(get_kv(CONFIG_KEY)?.unwrap_or_default())
+}
+
+pub fn save_config(config: &GateConfig) -> Result<()> {
+  Ok(set_kv(CONFIG_KEY, config)?)
+}
+
+/// 载入会话及其基线（未指定时使用同组合的基线）后执行门禁
+pub fn gate_session(
+  session_id: i64,
+  baseline_session_id: Option<i64>,
+  config: &GateConfig,
+) -> Result<GateReport> {
+  let baseline_id = match baseline_session_id {
+    Some(id) => id,
+    None => {
+      baseline_for_session(session_id)?
+        .ok_or(AnalysisError::NoBaseline(session_id))?
+        .session_id
+    }
+  };
+  let current = load_session(session_id)?;
+  let baseline = load_session(baseline_id)?;
+  Ok(evaluate_gate(&current, &baseline, config))
+}
+
+/// 按配置逐项比较本次与基线会话
+pub fn evaluate_gate(
+  current: &SessionData,
+  baseline: &SessionData,
+  config: &GateConfig,
+) -> GateReport {
+  let checks = evaluate_checks(&summarize(current), &summarize(baseline), config);
+
+  let failures: Vec<String> = checks
+    .iter()
+    .filter(|c| c.status != CheckStatus::Pass)
+    .map(|c| c.metric.clone())
+    .collect();
+  GateReport {
+    session_id: current.session.id,
+    baseline_session_id: baseline.session.id,
+    passed: failures.is_empty(),
+    failures,
+    checks,
+  }
+}
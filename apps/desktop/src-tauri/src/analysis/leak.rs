@@ -0,0 +1,224 @@
+use crate::{
+  analysis::error::{AnalysisError, Result},
+  session::SessionData,
+};
+use serde::{Deserialize, Serialize};
+
+// 拟合所需的最少采样数与最短时长
+const MIN_POINTS: usize = 10;
+const MIN_SPAN_MS: u64 = 60_000;
+// Mann-Kendall 为 O(n²)，超过该点数时先按时间分桶取平均
+const MAX_TREND_POINTS: usize = 2_000;
+// 增长速度达到该值且趋势显著时判定为疑似泄漏
+pub const DEFAULT_LEAK_MB_PER_HOUR: f64 = 10.0;
+const MS_PER_HOUR: f64 = 3_600_000.0;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LeakQuery {
+  pub session_id: i64,
+  /// 仅分析这些标记开始的稳态分段，为空时使用整场会话
+  #[serde(default)]
+  pub segments: Vec<String>,
+  #[serde(default)]
+  pub threshold_mb_per_hour: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LeakVerdict {
+  /// 显著上升且增长速度超过阈值
+  Probable,
+  /// 上升趋势显著但增长较慢
+  Possible,
+  None,
+  InsufficientData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeakAnalysis {
+  pub session_id: i64,
+  pub segments: Vec<String>,
+  pub points: usize,
+  pub span_ms: u64,
+  /// 线性回归斜率及其 95% 置信区间，单位 MB/h
+  pub slope_mb_per_hour: Option<f64>,
+  pub slope_ci: Option<(f64, f64)>,
+  pub r_squared: Option<f64>,
+  /// Mann-Kendall 检验统计量与双侧 p 值
+  pub trend_z: Option<f64>,
+  pub p_value: Option<f64>,
+  pub threshold_mb_per_hour: f64,
+  pub verdict: LeakVerdict,
+}
+
+/// 对内存序列做线性回归与 Mann-Kendall 趋势检验，判断是否存在持续增长
+pub fn analyze_leak(data: &SessionData, query: &LeakQuery) -> Result<LeakAnalysis> {
+  let ranges = segment_ranges(data, &query.segments)?;
+  let points: Vec<(u64, f64)> = data
+    .series("memory")
+    .into_iter()
+    .filter(|(t, v)| v.is_finite() && ranges.iter().any(|(from, to)| (from..to).contains(&t)))
+    .collect();
+  let span_ms = match (points.first(), points.last()) {
+    (Some((first, _)), Some((last, _))) => last - first,
+    _ => 0,
+  };
+  let threshold = query
+    .threshold_mb_per_hour
+    .unwrap_or(DEFAULT_LEAK_MB_PER_HOUR);
+  let mut analysis = LeakAnalysis {
+    session_id: data.session.id,
+    segments: query.segments.clone(),
+    points: points.len(),
+    span_ms,
+    slope_mb_per_hour: None,
+    slope_ci: None,
+    r_squared: None,
+    trend_z: None,
+    p_value: None,
+    threshold_mb_per_hour: threshold,
+    verdict: LeakVerdict::InsufficientData,
+  };
+  if points.len() < MIN_POINTS || span_ms < MIN_SPAN_MS {
+    return Ok(analysis);
+  }
+
+  let fit = linear_fit(&points);
+  let z = mann_kendall_z(&bucket_means(&points, MAX_TREND_POINTS));
+  let p_value = 2.0 * (1.0 - normal_cdf(z.abs()));
+  let slope = fit.slope * MS_PER_HOUR;
+  let margin = 1.96 * fit.slope_se * MS_PER_HOUR;
+
+  analysis.slope_mb_per_hour = Some(slope);
+  analysis.slope_ci = Some((slope - margin, slope + margin));
+  analysis.r_squared = Some(fit.r_squared);
+  analysis.trend_z = Some(z);
+  analysis.p_value = Some(p_value);
+  analysis.verdict = if z > 0.0 && p_value < 0.01 && slope >= threshold && slope > margin {
+    LeakVerdict::Probable
+  } else if z > 0.0 && p_value < 0.05 && slope > 0.0 {
+    LeakVerdict::Possible
+  } else {
+    LeakVerdict::None
+  };
+  Ok(analysis)
+}
+
+/// 各标记分段的 [起, 止) 区间，分段止于下一个标记
+fn segment_ranges(data: &SessionData, names: &[String]) -> Result<Vec<(u64, u64)>> {
+  if names.is_empty() {
+    return Ok(vec![(0, u64::MAX)]);
+  }
+  let mut markers: Vec<_> = data.markers.iter().collect();
+  markers.sort_by_key(|m| m.t_ms);
+  names
+    .iter()
+    .map(|name| {
+      let index = markers
+        .iter()
+        .position(|m| &m.name == name)
+        .ok_or_else(|| AnalysisError::MarkerNotFound {
+          session_id: data.session.id,
+          name: name.clone(),
+        })?;
+      let end = markers.get(index + 1).map_or(u64::MAX, |m| m.t_ms);
+      Ok((markers[index].t_ms, end))
+    })
+    .collect()
+}
+
+struct LinearFit {
+  /// MB/ms
+  slope: f64,
+  slope_se: f64,
+  r_squared: f64,
+}
+
+fn linear_fit(points: &[(u64, f64)]) -> LinearFit {
+  let n = points.len() as f64;
+  let mean_x = points.iter().map(|(t, _)| *t as f64).sum::<f64>() / n;
+  let mean_y = points.iter().map(|(_, v)| v).sum::<f64>() / n;
+  let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+  for (t, v) in points {
+    let dx = *t as f64 - mean_x;
+    let dy = v - mean_y;
+    sxx += dx * dx;
+    sxy += dx * dy;
+    syy += dy * dy;
+  }
+  if sxx <= 0.0 {
+    return LinearFit {
+      slope: 0.0,
+      slope_se: 0.0,
+      r_squared: 0.0,
+    };
+  }
+  let slope = sxy / sxx;
+  let residual = (syy - slope * sxy).max(0.0);
+  LinearFit {
+    slope,
+    slope_se: (residual / (n - 2.0) / sxx).sqrt(),
+    r_squared: if syy > 0.0 { slope * sxy / syy } else { 0.0 },
+  }
+}
+
+/// 按时间顺序等分成至多 max 个桶并取均值
+fn bucket_means(points: &[(u64, f64)], max: usize) -> Vec<f64> {
+  if points.len() <= max {
+    return points.iter().map(|(_, v)| *v).collect();
+  }
+  let size = points.len().div_ceil(max);
+  points
+    .chunks(size)
+    .map(|chunk| chunk.iter().map(|(_, v)| v).sum::<f64>() / chunk.len() as f64)
+    .collect()
+}
+
+/// Mann-Kendall 趋势检验的标准化统计量，含并列值修正
+fn mann_kendall_z(values: &[f64]) -> f64 {
+  let n = values.len();
+  let mut s: i64 = 0;
+  for i in 0..n {
+    for j in i + 1..n {
+      s += match values[j].total_cmp(&values[i]) {
+        std::cmp::Ordering::Greater => 1,
+        std::cmp::Ordering::Less => -1,
+        std::cmp::Ordering::Equal => 0,
+      };
+    }
+  }
+
+  let mut sorted = values.to_vec();
+  sorted.sort_by(|a, b| a.total_cmp(b));
+  let ties: f64 = sorted
+    .chunk_by(|a, b| a == b)
+    .map(|group| group.len() as f64)
+    .filter(|t| *t > 1.0)
+    .map(|t| t * (t - 1.0) * (2.0 * t + 5.0))
+    .sum();
+  let n = n as f64;
+  let variance = (n * (n - 1.0) * (2.0 * n + 5.0) - ties) / 18.0;
+  if variance <= 0.0 {
+    return 0.0;
+  }
+  match s {
+    s if s > 0 => (s - 1) as f64 / variance.sqrt(),
+    s if s < 0 => (s + 1) as f64 / variance.sqrt(),
+    _ => 0.0,
+  }
+}
+
+/// 标准正态分布函数，erf 采用 Abramowitz–Stegun 7.1.26 近似
+fn normal_cdf(x: f64) -> f64 {
+  let z = x.abs() / std::f64::consts::SQRT_2;
+  let t = 1.0 / (1.0 + 0.327_591_1 * z);
+  let poly = t
+    * (0.254_829_592
+      + t * (-0.284_496_736 + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+  let erf = 1.0 - poly * (-z * z).exp();
+  if x >= 0.0 {
+    0.5 * (1.0 + erf)
+  } else {
+    0.5 * (1.0 - erf)
+  }
+}
@@ -0,0 +1,19 @@
+pub mod align;
+pub mod compare;
+pub mod downsample;
+pub mod drain;
+pub mod energy;
+pub mod error;
+pub mod flamegraph;
+pub mod gate;
+pub mod leak;
+pub mod narrative;
+pub mod overlay;
+pub mod regression;
+pub mod score;
+pub mod summary;
+
+pub use compare::{compare_sessions, SessionComparison};
+pub use downsample::{query_series, SeriesQuery, SeriesResult};
+pub use overlay::{query_overlay, OverlayQuery, OverlayResult};
+pub use summary::{session_summary, summarize, summarize_in, summary_for, SessionSummary};
@@ -0,0 +1,109 @@
+use crate::{
+  analysis::{
+    drain::BatteryDrain,
+    regression::{RegressionReport, Verdict},
+    score::SessionScore,
+    summary::MetricSummary,
+  },
+  session::{
+    events::{EventKind, SessionEvent},
+    metric_label,
+    store::elapsed_label,
+    SessionData,
+  },
+};
+
+/// 汇总、对比与分析结果，生成摘要所需的全部输入
+pub struct NarrativeInput<'a> {
+  pub data: &'a SessionData,
+  pub metrics: &'a [MetricSummary],
+  pub score: &'a SessionScore,
+  pub drain: Option<&'a BatteryDrain>,
+  pub regression: Option<&'a RegressionReport>,
+  pub events: &'a [SessionEvent],
+}
+
+/// 生成一句话会话摘要，用于导出与通知，如
+/// “平均 FPS 54.2（较基线 -6.0%），卡顿 3 次，内存峰值 612 MB，00:14:32 开始温控降频”
+pub fn narrate(input: &NarrativeInput) -> String {
+  let delta = |metric: &str| -> String {
+    input
+      .regression
+      .and_then(|r| r.verdicts.iter().find(|v| v.metric == metric))
+      .map(|v| format!("（较基线 {:+.1}%）", v.delta_pct))
+      .unwrap_or_default()
+  };
+  let stats = |metric: &str| input.metrics.iter().find(|m| m.metric == metric);
+  let count = |kind: EventKind| input.events.iter().filter(|e| e.kind == kind).count();
+  let mut parts: Vec<String> = Vec::new();
+
+  if let Some(fps) = stats("fps") {
+    parts.push(format!("平均 FPS {:.1}{}", fps.avg, delta("fps")));
+    match count(EventKind::Jank) {
+      0 => parts.push("无明显卡顿".into()),
+      n => parts.push(format!("卡顿 {n} 次")),
+    }
+  }
+  if let Some(cpu) = stats("cpu") {
+    parts.push(format!("CPU 平均 {:.1}%{}", cpu.avg, delta("cpu")));
+  }
+  if let Some(memory) = stats("memory") {
+    parts.push(format!("内存峰值 {:.0} MB{}", memory.max, delta("memory")));
+  }
+  if let Some(drain) = input.drain {
+    parts.push(format!(
+      "平均电流 {:.0} mA，共耗电 {:.1} mAh",
+      drain.total.avg_current_ma, drain.total.charge_mah
+    ));
+  }
+
+  for (kind, label) in [
+    (EventKind::Crash, "崩溃"),
+    (EventKind::NativeCrash, "Native 崩溃"),
+    (EventKind::Anr, "ANR"),
+    (EventKind::Disconnected, "设备掉线"),
+  ] {
+    match count(kind) {
+      0 => {}
+      n => parts.push(format!("{label} {n} 次")),
+    }
+  }
+  if let Some(event) = input
+    .events
+    .iter()
+    .find(|e| e.kind == EventKind::ThrottleBegin)
+  {
+    parts.push(format!(
+      "{} 开始温控降频",
+      time_label(input.data, event.t_ms)
+    ));
+  }
+
+  if let Some(regression) = input.regression.filter(|r| r.regressed) {
+    let labels: Vec<&str> = regression
+      .verdicts
+      .iter()
+      .filter(|v| v.verdict == Verdict::Regressed)
+      .map(|v| metric_label(&v.metric).0)
+      .collect();
+    parts.push(format!("相对基线劣化：{}", labels.join("、")));
+  }
+  if let Some(score) = input.score.score {
+    parts.push(format!("综合评分 {score:.0}"));
+  }
+
+  if parts.is_empty() {
+    return "会话没有可汇总的采样数据。".into();
+  }
+  format!("{}。", parts.join("，"))
+}
+
+/// 优先使用前端记录的时刻，没有时使用相对开始的时长
+fn time_label(data: &SessionData, t_ms: u64) -> String {
+  data
+    .samples
+    .iter()
+    .find(|s| s.t_ms >= t_ms)
+    .and_then(|s| s.time.clone())
+    .unwrap_or_else(|| elapsed_label(t_ms))
+}
@@ -0,0 +1,181 @@
+use crate::{
+  analysis::{
+    align::{anchor_ms, resample, shift, Alignment},
+    error::Result,
+  },
+  session::{load_session, SessionData},
+};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_MAX_POINTS: usize = 1000;
+// 时间轴网格的最小步长，与前端采样间隔一致
+const MIN_STEP_MS: i64 = 1000;
+// 进度轴以万分比表示，重采样时按整数处理
+const PROGRESS_SCALE: i64 = 10_000;
+
+/// 叠加图的横轴
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum OverlayAxis {
+  /// 以会话开始时刻对齐
+  #[default]
+  Start,
+  /// 以同名标记对齐，横轴为相对标记的毫秒数
+  Marker { name: String },
+  /// 按各自时长归一化为 0–100% 进度
+  Progress,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlayQuery {
+  pub session_ids: Vec<i64>,
+  /// 为空时取所有会话出现过的指标
+  #[serde(default)]
+  pub metrics: Vec<String>,
+  #[serde(default)]
+  pub axis: OverlayAxis,
+  #[serde(default)]
+  pub max_points: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlaySession {
+  pub session_id: i64,
+  pub name: String,
+  /// 对齐零点在会话内的 t_ms
+  pub anchor_ms: u64,
+  pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlayMetric {
+  pub metric: String,
+  /// 与 sessions 一一对应，每行与 axis 等长，缺失为 null
+  pub values: Vec<Vec<Option<f64>>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlayResult {
+  pub axis: OverlayAxis,
+  /// 公共横轴：时间对齐时为毫秒（可为负），进度对齐时为百分比
+  pub t: Vec<f64>,
+  pub sessions: Vec<OverlaySession>,
+  pub metrics: Vec<OverlayMetric>,
+}
+
+pub fn query_overlay(query: &OverlayQuery) -> Result<OverlayResult> {
+  let sessions = query
+    .session_ids
+    .iter()
+    .map(|id| load_session(*id))
+    .collect::<std::result::Result<Vec<_>, _>>()?;
+  overlay_sessions(&sessions, query)
+}
+
+/// 将多个会话重采样到同一横轴，前端可直接按下标叠加绘制
+pub fn overlay_sessions(sessions: &[SessionData], query: &OverlayQuery) -> Result<OverlayResult> {
+  let max_points = query.max_points.unwrap_or(DEFAULT_MAX_POINTS).max(2) as i64;
+  let anchors = sessions
+    .iter()
+    .map(|data| match &query.axis {
+      OverlayAxis::Start | OverlayAxis::Progress => Ok(0),
+      OverlayAxis::Marker { name } => anchor_ms(data, &Alignment::Marker { name: name.clone() }),
+    })
+    .collect::<Result<Vec<u64>>>()?;
+  let durations: Vec<u64> = sessions
+    .iter()
+    .map(|data| data.samples.last().map_or(0, |s| s.t_ms))
+    .collect();
+
+  // 各会话的横轴坐标：时间对齐时平移，进度对齐时按时长缩放
+  let position = |index: usize, series: &[(u64, f64)]| -> Vec<(i64, f64)> {
+    match query.axis {
+      OverlayAxis::Progress => {
+        let duration = durations[index].max(1) as i64;
+        series
+          .iter()
+          .map(|(t, v)| (*t as i64 * PROGRESS_SCALE / duration, *v))
+          .collect()
+      }
+      _ => shift(series, anchors[index]),
+    }
+  };
+
+  let (start, end, step, max_gap) = match query.axis {
+    OverlayAxis::Progress => {
+      let step = (PROGRESS_SCALE / (max_points - 1)).max(1);
+      // 允许的空档折算为最短会话中两个采样间隔对应的进度
+      let shortest = durations
+        .iter()
+        .copied()
+        .filter(|d| *d > 0)
+        .min()
+        .unwrap_or(1) as i64;
+      let gap = (MIN_STEP_MS * 2 * PROGRESS_SCALE / shortest).max(step);
+      (0, PROGRESS_SCALE, step, gap)
+    }
+    _ => {
+      let start = anchors.iter().map(|a| -(*a as i64)).min().unwrap_or(0);
+      let end = durations
+        .iter()
+        .zip(&anchors)
+        .map(|(d, a)| *d as i64 - *a as i64)
+        .max()
+        .unwrap_or(0);
+      let step = ((end - start) / (max_points - 1)).max(MIN_STEP_MS);
+      (start, end, step, step.max(MIN_STEP_MS * 2))
+    }
+  };
+
+  let metrics = if query.metrics.is_empty() {
+    let mut names: Vec<String> = Vec::new();
+    for name in sessions.iter().flat_map(|data| data.metric_names()) {
+      if !names.contains(&name) {
+        names.push(name);
+      }
+    }
+    names
+  } else {
+    query.metrics.clone()
+  };
+  let metrics = metrics
+    .into_iter()
+    .map(|metric| OverlayMetric {
+      values: sessions
+        .iter()
+        .enumerate()
+        .map(|(index, data)| {
+          let series = position(index, &data.series(&metric));
+          resample(&series, start, end, step, max_gap)
+        })
+        .collect(),
+      metric,
+    })
+    .collect();
+
+  let t = (0..)
+    .map(|i| start + i * step)
+    .take_while(|t| *t <= end)
+    .map(|t| match query.axis {
+      OverlayAxis::Progress => t as f64 * 100.0 / PROGRESS_SCALE as f64,
+      _ => t as f64,
+    })
+    .collect();
+  let sessions = sessions
+    .iter()
+    .zip(anchors.iter().zip(&durations))
+    .map(|(data, (anchor_ms, duration_ms))| OverlaySession {
+      session_id: data.session.id,
+      name: data.session.name.clone(),
+      anchor_ms: *anchor_ms,
+      duration_ms: *duration_ms,
+    })
+    .collect();
+
+  Ok(OverlayResult {
+    axis: query.axis.clone(),
+    t,
+    sessions,
+    metrics,
+  })
+}
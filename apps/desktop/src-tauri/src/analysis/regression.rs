@@ -0,0 +1,91 @@
+use crate::{
+  analysis::summary::{summarize, MetricSummary},
+  session::SessionData,
+};
+use serde::{Deserialize, Serialize};
+
+pub use perfx_core::analysis::gate::{metric_direction, Direction, DEFAULT_TOLERANCE_PCT};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+  Improved,
+  Unchanged,
+  Regressed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricVerdict {
+  pub metric: String,
+  pub direction: Direction,
+  pub baseline_avg: f64,
+  pub current_avg: f64,
+  pub delta_pct: f64,
+  pub tolerance_pct: f64,
+  pub verdict: Verdict,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegressionReport {
+  pub baseline_session_id: i64,
+  pub verdicts: Vec<MetricVerdict>,
+  pub regressed: bool,
+}
+
+/// 以平均值的相对变化判定每个指标相对基线是否劣化
+pub fn evaluate(
+  current: &SessionData,
+  baseline: &SessionData,
+  tolerance_pct: f64,
+) -> RegressionReport {
+  let baseline_stats = summarize(baseline);
+  let verdicts: Vec<MetricVerdict> = summarize(current)
+    .into_iter()
+    .filter_map(|stats| {
+      let direction = metric_direction(&stats.metric)?;
+      let base = baseline_stats.iter().find(|b| b.metric == stats.metric)?;
+      Some(judge(&stats, base, direction, tolerance_pct))
+    })
+    .collect();
+
+  RegressionReport {
+    baseline_session_id: baseline.session.id,
+    regressed: verdicts.iter().any(|v| v.verdict == Verdict::Regressed),
+    verdicts,
+  }
+}
+
+fn judge(
+  current: &MetricSummary,
+  base: &MetricSummary,
+  direction: Direction,
+  tolerance_pct: f64,
+) -> MetricVerdict {
+  let delta_pct = if base.avg.abs() > f64::EPSILON {
+    (current.avg - base.avg) / base.avg.abs() * 100.0
+  } else {
+    0.0
+  };
+  // 统一换算为“变好”的百分比
+  let gain_pct = match direction {
+    Direction::HigherIsBetter => delta_pct,
+    Direction::LowerIsBetter => -delta_pct,
+  };
+  let verdict = if gain_pct < -tolerance_pct {
+    Verdict::Regressed
+  } else if gain_pct > tolerance_pct {
+    Verdict::Improved
+  } else {
+    Verdict::Unchanged
+  };
+
+  MetricVerdict {
+    metric: current.metric.clone(),
+    direction,
+    baseline_avg: base.avg,
+    current_avg: current.avg,
+    delta_pct,
+    tolerance_pct,
+    verdict,
+  }
+}
@@ -0,0 +1,393 @@
+use crate::{
+  analysis::{error::Result, summary::percentile},
+  session::{
+    startup::STARTUP_METRIC,
+    store::{get_kv, set_kv},
+    SessionData,
+  },
+};
+use serde::{Deserialize, Serialize};
+
+const MODEL_KEY: &str = "score_model";
+// 少于该采样数的分段不单独评分
+const MIN_SEGMENT_SAMPLES: usize = 2;
+
+/// 各项权重，缺少数据的项不参与加权
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoreWeights {
+  pub fps_stability: f64,
+  pub jank_rate: f64,
+  pub startup: f64,
+  pub memory: f64,
+  pub power: f64,
+}
+
+impl Default for ScoreWeights {
+  fn default() -> Self {
+    Self {
+      fps_stability: 0.35,
+      jank_rate: 0.25,
+      startup: 0.1,
+      memory: 0.15,
+      power: 0.15,
+    }
+  }
+}
+
+/// 各项的满分 / 零分阈值，介于两者之间线性计分
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoreTargets {
+  pub target_fps: f64,
+  /// 帧率较上一采样下降超过该百分比计为一次卡顿
+  pub jank_drop_pct: f64,
+  pub max_jank_rate_pct: f64,
+  pub startup_good_ms: f64,
+  pub startup_bad_ms: f64,
+  pub memory_good_mb: f64,
+  pub memory_bad_mb: f64,
+  pub power_good_ma: f64,
+  pub power_bad_ma: f64,
+}
+
+impl Default for ScoreTargets {
+  fn default() -> Self {
+    Self {
+      target_fps: 60.0,
+      jank_drop_pct: 30.0,
+      max_jank_rate_pct: 10.0,
+      startup_good_ms: 500.0,
+      startup_bad_ms: 3000.0,
+      memory_good_mb: 300.0,
+      memory_bad_mb: 1000.0,
+      power_good_ma: 200.0,
+      power_bad_ma: 800.0,
+    }
+  }
+}
+
+/// 评分模型：同一模型下的分数可跨版本直接比较
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScoreModel {
+  #[serde(default)]
+  pub weights: ScoreWeights,
+  #[serde(default)]
+  pub targets: ScoreTargets,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreComponent {
+  FpsStability,
+  JankRate,
+  Startup,
+  Memory,
+  Power,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentScore {
+  pub component: ScoreComponent,
+  /// 参与计分的原始值：平均 FPS、卡顿率 %、启动 TotalTime 中位数 ms、内存 P95 MB、平均功耗 mA
+  pub value: f64,
+  pub score: f64,
+  pub weight: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentScore {
+  /// 分段起始标记名，第一段为空
+  pub name: String,
+  pub from_ms: u64,
+  pub to_ms: u64,
+  pub score: Option<f64>,
+  pub components: Vec<ComponentScore>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionScore {
+  /// 0–100，没有任何可计分数据时为 None
+  pub score: Option<f64>,
+  pub components: Vec<ComponentScore>,
+  pub segments: Vec<SegmentScore>,
+}
+
+pub fn load_model() -> Result<ScoreModel> {
+  Ok(get_kv(MODEL_KEY)?.unwrap_or_default())
+}
+
+pub fn save_model(model: &ScoreModel) -> Result<()> {
+  Ok(set_kv(MODEL_KEY, model)?)
+}
+
+/// 计算整场会话及按标记切分的各分段得分
+pub fn score_session(data: &SessionData, model: &ScoreModel) -> SessionScore {
+  let components = score_range(data, model, 0, u64::MAX);
+
+  let mut bounds: Vec<(String, u64)> = vec![(String::new(), 0)];
+  bounds.extend(data.markers.iter().map(|m| (m.name.clone(), m.t_ms)));
+  bounds.sort_by_key(|(_, t)| *t);
+  let end_ms = data.samples.last().map(|s| s.t_ms).unwrap_or(0);
+
+  let segments = if data.markers.is_empty() {
+    Vec::new()
+  } else {
+    bounds
+      .iter()
+      .enumerate()
+      .filter_map(|(index, (name, from_ms))| {
+        let to_ms = bounds.get(index + 1).map(|(_, t)| *t).unwrap_or(end_ms + 1);
+        let count = data
+          .samples
+          .iter()
+          .filter(|s| (*from_ms..to_ms).contains(&s.t_ms))
+          .count();
+        if count < MIN_SEGMENT_SAMPLES {
+          return None;
+        }
+        let components = score_range(data, model, *from_ms, to_ms);
+        Some(SegmentScore {
+          name: name.clone(),
+          from_ms: *from_ms,
+          to_ms: to_ms.min(end_ms),
+          score: weighted(&components),
+          components,
+        })
+      })
+      .collect()
+  };
+
+  SessionScore {
+    score: weighted(&components),
+    components,
+    segments,
+  }
+}
+
+/// 对 [from_ms, to_ms) 内的采样逐项计分；启动耗时取启动测试记录的 `am start -W` TotalTime
+fn score_range(
+  data: &SessionData,
+  model: &ScoreModel,
+  from_ms: u64,
+  to_ms: u64,
+) -> Vec<ComponentScore> {
+  let series = |metric: &str| -> Vec<f64> {
+    data
+      .series(metric)
+      .into_iter()
+      .filter(|(t, _)| (from_ms..to_ms).contains(t))
+      .map(|(_, v)| v)
+      .filter(|v| v.is_finite())
+      .collect()
+  };
+  let weights = &model.weights;
+  let targets = &model.targets;
+  let mut components = Vec::new();
+
+  let fps = series("fps");
+  if !fps.is_empty() {
+    let avg = mean(&fps);
+    if avg > 0.0 {
+      let cv = std_dev(&fps, avg) / avg;
+      let score = 100.0 * (avg / targets.target_fps).min(1.0) * (1.0 - cv.min(1.0));
+      components.push(component(
+        ScoreComponent::FpsStability,
+        avg,
+        score,
+        weights.fps_stability,
+      ));
+    }
+  }
+  if fps.len() > 1 {
+    let drop = 1.0 - targets.jank_drop_pct / 100.0;
+    let janks = fps.windows(2).filter(|w| w[1] < w[0] * drop).count();
+    let rate_pct = janks as f64 / (fps.len() - 1) as f64 * 100.0;
+    let score = linear(rate_pct, 0.0, targets.max_jank_rate_pct);
+    components.push(component(
+      ScoreComponent::JankRate,
+      rate_pct,
+      score,
+      weights.jank_rate,
+    ));
+  }
+  let mut startup = series(STARTUP_METRIC);
+  if !startup.is_empty() {
+    startup.sort_by(|a, b| a.total_cmp(b));
+    let startup_ms = percentile(&startup, 50.0);
+    let score = linear(startup_ms, targets.startup_good_ms, targets.startup_bad_ms);
+    components.push(component(
+      ScoreComponent::Startup,
+      startup_ms,
+      score,
+      weights.startup,
+    ));
+  }
+
+  let mut memory = series("memory");
+  if !memory.is_empty() {
+    memory.sort_by(|a, b| a.total_cmp(b));
+    let p95 = percentile(&memory, 95.0);
+    let score = linear(p95, targets.memory_good_mb, targets.memory_bad_mb);
+    components.push(component(
+      ScoreComponent::Memory,
+      p95,
+      score,
+      weights.memory,
+    ));
+  }
+  // 放电电流在部分机型上为负值，按绝对值评分
+  let power: Vec<f64> = series("power").iter().map(|v| v.abs()).collect();
+  if !power.is_empty() {
+    let avg = mean(&power);
+    let score = linear(avg, targets.power_good_ma, targets.power_bad_ma);
+    components.push(component(ScoreComponent::Power, avg, score, weights.power));
+  }
+  components
+}
+
+fn component(component: ScoreComponent, value: f64, score: f64, weight: f64) -> ComponentScore {
+  ComponentScore {
+    component,
+    value,
+    score: score.clamp(0.0, 100.0),
+    weight: weight.max(0.0),
+  }
+}
+
+fn weighted(components: &[ComponentScore]) -> Option<f64> {
+  let total_weight: f64 = components.iter().map(|c| c.weight).sum();
+  if total_weight <= 0.0 {
+    return None;
+  }
+  Some(components.iter().map(|c| c.score * c.weight).sum::<f64>() / total_weight)
+}
+
+/// 不超过 good 得满分，达到 bad 得零分，越小越好
+fn linear(value: f64, good: f64, bad: f64) -> f64 {
+  if bad <= good {
+    return if value <= good { 100.0 } else { 0.0 };
+  }
+  100.0 * ((bad - value) / (bad - good)).clamp(0.0, 1.0)
+}
+
+fn mean(values: &[f64]) -> f64 {
+  values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn std_dev(values: &[f64], mean: f64) -> f64 {
+  let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+  variance.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::session::{Marker, Sample, Session};
+  use perfx_core::schema::SCHEMA_VERSION;
+
+  fn session(samples: Vec<(u64, &[(&str, f64)])>, markers: &[(u64, &str)]) -> SessionData {
+    SessionData {
+      session: Session {
+        id: 1,
+        name: String::new(),
+        device_id: String::new(),
+        device_model: None,
+        app_package: String::new(),
+        app_label: None,
+        metrics: Vec::new(),
+        start_time: 0,
+        end_time: 0,
+        duration: 0,
+        created_at: 0,
+        schema_version: SCHEMA_VERSION,
+      },
+      samples: samples
+        .into_iter()
+        .map(|(t_ms, values)| Sample {
+          t_ms,
+          time: None,
+          values: values.iter().map(|(k, v)| (k.to_string(), *v)).collect(),
+        })
+        .collect(),
+      markers: markers
+        .iter()
+        .map(|(t_ms, name)| Marker {
+          id: 0,
+          t_ms: *t_ms,
+          name: name.to_string(),
+        })
+        .collect(),
+      meta: Default::default(),
+    }
+  }
+
+  fn find(score: &SessionScore, component: ScoreComponent) -> Option<&ComponentScore> {
+    score.components.iter().find(|c| c.component == component)
+  }
+
+  #[test]
+  fn linear_scores_between_targets() {
+    assert_eq!(linear(100.0, 200.0, 800.0), 100.0);
+    assert_eq!(linear(500.0, 200.0, 800.0), 50.0);
+    assert_eq!(linear(900.0, 200.0, 800.0), 0.0);
+    // 阈值配置颠倒时退化为是否达标
+    assert_eq!(linear(100.0, 200.0, 200.0), 100.0);
+    assert_eq!(linear(300.0, 200.0, 200.0), 0.0);
+  }
+
+  #[test]
+  fn startup_uses_median_total_time_not_marker() {
+    let data = session(
+      vec![
+        (0, &[("startup", 400.0)]),
+        (5_000, &[("startup", 1_750.0)]),
+        (10_000, &[("startup", 3_200.0)]),
+      ],
+      &[
+        (0, "#1"),
+        (5_000, "#2"),
+        (10_000, "#3"),
+        (60_000, "startup"),
+      ],
+    );
+    let score = score_session(&data, &ScoreModel::default());
+    let startup = find(&score, ScoreComponent::Startup).unwrap();
+    assert_eq!(startup.value, 1_750.0);
+    assert_eq!(startup.score, 50.0);
+  }
+
+  #[test]
+  fn missing_components_are_left_out_of_weighting() {
+    // 只有内存：总分等于内存分，不因其他项缺失被拉低
+    let data = session(
+      vec![(0, &[("memory", 650.0)]), (1_000, &[("memory", 650.0)])],
+      &[],
+    );
+    let score = score_session(&data, &ScoreModel::default());
+    assert_eq!(score.components.len(), 1);
+    assert_eq!(score.score, Some(50.0));
+  }
+
+  #[test]
+  fn weights_combine_component_scores() {
+    let components = [
+      component(ScoreComponent::Memory, 0.0, 100.0, 3.0),
+      component(ScoreComponent::Power, 0.0, 0.0, 1.0),
+    ];
+    assert_eq!(weighted(&components), Some(75.0));
+    // 负权重按 0 处理，全部为 0 时没有分数
+    let zero = [component(ScoreComponent::Memory, 0.0, 100.0, -1.0)];
+    assert_eq!(weighted(&zero), None);
+  }
+
+  #[test]
+  fn power_scores_discharge_current_by_magnitude() {
+    let data = session(
+      vec![(0, &[("power", -500.0)]), (1_000, &[("power", -500.0)])],
+      &[],
+    );
+    let score = score_session(&data, &ScoreModel::default());
+    assert_eq!(find(&score, ScoreComponent::Power).unwrap().score, 50.0);
+  }
+}
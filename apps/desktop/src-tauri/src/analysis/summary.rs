@@ -0,0 +1,102 @@
+use crate::{
+  analysis::{
+    drain::{estimate_drain, BatteryDrain},
+    error::Result,
+    narrative::{narrate, NarrativeInput},
+    regression::{evaluate, RegressionReport, DEFAULT_TOLERANCE_PCT},
+    score::{load_model, score_session, SessionScore},
+  },
+  settings::report_units,
+  session::{
+    baseline::baseline_for_session, events::list_events, load_session, Session, SessionData,
+  },
+};
+use perfx_core::units::Units;
+use serde::{Deserialize, Serialize};
+
+pub use perfx_core::analysis::summary::{percentile, summarize_values, MetricSummary};
+
+/// 会话汇总：各指标统计、综合评分，以及存在基线时相对基线的劣化判定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSummary {
+  pub session: Session,
+  /// 按 `units` 换算后的统计
+  pub metrics: Vec<MetricSummary>,
+  #[serde(default)]
+  pub units: Units,
+  pub score: SessionScore,
+  /// 有功耗采样时的耗电估算
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub drain: Option<BatteryDrain>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub regression: Option<RegressionReport>,
+  /// 一句话摘要，用于导出与通知
+  pub narrative: String,
+}
+
+pub fn session_summary(session_id: i64) -> Result<SessionSummary> {
+  summary_for(&load_session(session_id)?)
+}
+
+/// 为已加载的会话生成汇总，基线与事件从数据库读取
+pub fn summary_for(data: &SessionData) -> Result<SessionSummary> {
+  let session_id = data.session.id;
+  let regression = match baseline_for_session(session_id)? {
+    Some(baseline) => {
+      let baseline_data = load_session(baseline.session_id)?;
+      Some(evaluate(data, &baseline_data, DEFAULT_TOLERANCE_PCT))
+    }
+    None => None,
+  };
+  let units = report_units();
+  let metrics = summarize_in(data, &units);
+  let score = score_session(data, &load_model()?);
+  let drain = estimate_drain(data);
+  let events = list_events(session_id)?;
+  let narrative = narrate(&NarrativeInput {
+    data,
+    metrics: &metrics,
+    score: &score,
+    drain: drain.as_ref(),
+    regression: regression.as_ref(),
+    events: &events,
+  });
+
+  Ok(SessionSummary {
+    session: data.session.clone(),
+    metrics,
+    units,
+    score,
+    drain,
+    regression,
+    narrative,
+  })
+}
+
+/// 计算会话中每个指标的最小/平均/最大值及分位数
+pub fn summarize(data: &SessionData) -> Vec<MetricSummary> {
+  data
+    .metric_names()
+    .into_iter()
+    .filter_map(|metric| {
+      let values: Vec<f64> = data.series(&metric).into_iter().map(|(_, v)| v).collect();
+      summarize_values(&metric, &values)
+    })
+    .collect()
+}
+
+/// 按展示单位统计，用于导出与界面；回归与门禁仍按基准单位比较
+pub fn summarize_in(data: &SessionData, units: &Units) -> Vec<MetricSummary> {
+  let samples = data.samples_in(units);
+  data
+    .metric_names()
+    .into_iter()
+    .filter_map(|metric| {
+      let values: Vec<f64> = samples
+        .iter()
+        .filter_map(|s| s.values.get(&metric).copied())
+        .collect();
+      summarize_values(&metric, &values)
+    })
+    .collect()
+}
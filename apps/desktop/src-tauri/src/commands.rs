@@ -1,17 +1,46 @@
-use crate::adb::{
-  command::{run_device, run_host},
-  collect_metrics, list_apps, list_devices, set_adb_path, AppInfo, DeviceInfo, MetricKey,
-  MetricsSnapshot,
+use crate::{
+  adb::{
+    app::stop_background_apps,
+    capabilities::{probe_capabilities, Capability},
+    command::{run_device, run_host},
+    energy::reset_batterystats,
+    collect_metrics_with, list_apps, list_devices,
+    procstats::{fetch_procstats, ProcStats},
+    set_adb_path,
+    user::{list_users, AndroidUser},
+    AppInfo, CollectOptions, DeviceInfo, InterfaceFilter, MetricKey, MetricsSnapshot,
+  },
+  i18n::CommandError,
 };
 use log::{error, info};
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use tauri::async_runtime::spawn_blocking;
 
+pub mod analysis;
+pub mod diagnostics;
+pub mod export;
+pub mod farm;
+pub mod hotkey;
+pub mod notify;
+pub mod overlay;
+pub mod profiling;
+pub mod recording;
+pub mod runner;
+pub mod server;
+pub mod session;
+pub mod settings;
+pub mod updater;
+pub mod webhook;
+pub mod windows;
+
 #[derive(Debug, Deserialize)]
 pub struct ListAppsPayload {
   pub device_id: String,
   #[serde(default)]
   pub keyword: Option<String>,
+  #[serde(default)]
+  pub user: Option<u32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -19,10 +48,18 @@ pub struct MetricsPayload {
   pub device_id: String,
   pub package: String,
   pub metrics: Vec<MetricKey>,
+  #[serde(default)]
+  pub all_processes: bool,
+  #[serde(default)]
+  pub process_breakdown: bool,
+  #[serde(default)]
+  pub user: Option<u32>,
+  #[serde(default)]
+  pub interfaces: InterfaceFilter,
 }
 
 #[tauri::command]
-pub async fn tauri_list_devices() -> Result<Vec<DeviceInfo>, String> {
+pub async fn tauri_list_devices() -> Result<Vec<DeviceInfo>, CommandError> {
   spawn_blocking(|| {
     match list_devices() {
       Ok(devices) => {
@@ -40,24 +77,63 @@ pub async fn tauri_list_devices() -> Result<Vec<DeviceInfo>, String> {
     }
   })
     .await
-  .map_err(|e| format!("异步执行错误: {}", e.to_string()))?
-  .map_err(|e| format!("ADB错误: {}", e.to_string()))
+  .map_err(CommandError::from)?
+  .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_list_apps(payload: ListAppsPayload) -> Result<Vec<AppInfo>, CommandError> {
+  spawn_blocking(move || list_apps(&payload.device_id, payload.keyword.as_deref(), payload.user))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
 }
 
 #[tauri::command]
-pub async fn tauri_list_apps(payload: ListAppsPayload) -> Result<Vec<AppInfo>, String> {
-  spawn_blocking(move || list_apps(&payload.device_id, payload.keyword.as_deref()))
+pub async fn tauri_list_users(device_id: String) -> Result<Vec<AndroidUser>, CommandError> {
+  spawn_blocking(move || list_users(&device_id))
     .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e| e.to_string())
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
 }
 
 #[tauri::command]
-pub async fn tauri_get_metrics(payload: MetricsPayload) -> Result<MetricsSnapshot, String> {
-  spawn_blocking(move || collect_metrics(&payload.device_id, &payload.package, &payload.metrics))
+pub async fn tauri_get_metrics(payload: MetricsPayload) -> Result<MetricsSnapshot, CommandError> {
+  spawn_blocking(move || {
+    let options = CollectOptions {
+      keep_raw: false,
+      all_processes: payload.all_processes,
+      process_breakdown: payload.process_breakdown,
+      user: payload.user,
+      interfaces: payload.interfaces,
+    };
+    collect_metrics_with(&payload.device_id, &payload.package, &payload.metrics, options)
+      .map(|(snapshot, _)| snapshot)
+  })
     .await
-    .map_err(|e| e.to_string())?
-    .map_err(|e| e.to_string())
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+fn default_procstats_hours() -> u32 {
+  3
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProcstatsPayload {
+  pub device_id: String,
+  pub package: String,
+  #[serde(default = "default_procstats_hours")]
+  pub hours: u32,
+}
+
+/// 读取 framework 统计的进程状态驻留与内存，补充采样 PSS 之外更长时段的视角
+#[tauri::command]
+pub async fn tauri_get_procstats(payload: ProcstatsPayload) -> Result<ProcStats, CommandError> {
+  spawn_blocking(move || fetch_procstats(&payload.device_id, &payload.package, payload.hours))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
 }
 
 #[derive(Debug, Deserialize)]
@@ -67,7 +143,7 @@ pub struct ExecuteAdbCommandPayload {
 }
 
 #[tauri::command]
-pub async fn tauri_execute_adb_command(payload: ExecuteAdbCommandPayload) -> Result<String, String> {
+pub async fn tauri_execute_adb_command(payload: ExecuteAdbCommandPayload) -> Result<String, CommandError> {
   spawn_blocking(move || {
     if let Some(device_id) = payload.device_id {
       run_device(&device_id, &payload.args.iter().map(|s| s.as_str()).collect::<Vec<_>>())
@@ -76,17 +152,54 @@ pub async fn tauri_execute_adb_command(payload: ExecuteAdbCommandPayload) -> Res
     }
   })
   .await
-  .map_err(|e| format!("异步执行错误: {}", e.to_string()))?
-  .map_err(|e| format!("ADB命令执行失败: {}", e.to_string()))
+  .map_err(CommandError::from)?
+  .map_err(CommandError::from)
 }
 
 #[tauri::command]
-pub async fn tauri_set_adb_path(path: Option<String>) -> Result<(), String> {
+pub async fn tauri_set_adb_path(path: Option<String>) -> Result<(), CommandError> {
   spawn_blocking(move || {
-    set_adb_path(path);
+    set_adb_path(path.clone());
+    crate::settings::set_saved_adb_path(path)
   })
   .await
-  .map_err(|e| e.to_string())?;
-  Ok(())
+  .map_err(CommandError::from)?
+  .map_err(CommandError::from)
 }
 
+
+/// 手动清空 batterystats，可选开启完整唤醒锁历史
+#[tauri::command]
+pub async fn tauri_reset_batterystats(
+  device_id: String,
+  full_wake_history: bool,
+) -> Result<(), CommandError> {
+  spawn_blocking(move || reset_batterystats(&device_id, full_wake_history))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+/// 结束目标应用与白名单以外的第三方应用，返回被结束的包名
+#[tauri::command]
+pub async fn tauri_stop_background_apps(
+  device_id: String,
+  package: String,
+  allowlist: Vec<String>,
+) -> Result<Vec<String>, CommandError> {
+  spawn_blocking(move || stop_background_apps(&device_id, &package, &allowlist))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+/// 探测设备上各指标能否采集，界面据此置灰不支持的指标
+#[tauri::command]
+pub async fn tauri_probe_capabilities(
+  device_id: String,
+  package: String,
+) -> Result<BTreeMap<String, Capability>, CommandError> {
+  spawn_blocking(move || probe_capabilities(&device_id, &package))
+    .await
+    .map_err(CommandError::from)
+}
@@ -0,0 +1,173 @@
+use crate::{
+  analysis::{
+    align::Alignment,
+    compare_sessions,
+    energy::{session_energy, EnergyEstimate},
+    error::Result as AnalysisResult,
+    gate::{gate_session, load_config, save_config, GateConfig, GateReport},
+    leak::{analyze_leak, LeakAnalysis, LeakQuery},
+    query_overlay, query_series,
+    score::{load_model, save_model, score_session, ScoreModel, SessionScore},
+    session_summary, OverlayQuery, OverlayResult, SeriesQuery, SeriesResult, SessionComparison,
+    SessionSummary,
+  },
+  i18n::CommandError,
+  session::{
+    anomaly::{detect, Anomaly},
+    load_session,
+  },
+  webhook::notify_gate,
+};
+use serde::Deserialize;
+use tauri::async_runtime::spawn_blocking;
+
+#[derive(Debug, Deserialize)]
+pub struct CompareSessionsPayload {
+  pub a: i64,
+  pub b: i64,
+  #[serde(default)]
+  pub alignment: Alignment,
+}
+
+#[tauri::command]
+pub async fn tauri_compare_sessions(
+  payload: CompareSessionsPayload,
+) -> Result<SessionComparison, CommandError> {
+  spawn_blocking(move || -> AnalysisResult<SessionComparison> {
+    let a = load_session(payload.a)?;
+    let b = load_session(payload.b)?;
+    compare_sessions(&a, &b, &payload.alignment)
+  })
+  .await
+  .map_err(CommandError::from)?
+  .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_get_session_summary(session_id: i64) -> Result<SessionSummary, CommandError> {
+  spawn_blocking(move || session_summary(session_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+/// 图表取数：长会话按目标点数降采样，缩放时传入范围获取原始精度
+#[tauri::command]
+pub async fn tauri_query_series(query: SeriesQuery) -> Result<SeriesResult, CommandError> {
+  spawn_blocking(move || query_series(&query))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+/// 叠加对比取数：多个会话按开始、标记或进度对齐到同一横轴
+#[tauri::command]
+pub async fn tauri_query_overlay(query: OverlayQuery) -> Result<OverlayResult, CommandError> {
+  spawn_blocking(move || query_overlay(&query))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_get_score_model() -> Result<ScoreModel, CommandError> {
+  spawn_blocking(load_model)
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_set_score_model(model: ScoreModel) -> Result<(), CommandError> {
+  spawn_blocking(move || save_model(&model))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_get_session_score(session_id: i64) -> Result<SessionScore, CommandError> {
+  spawn_blocking(move || -> AnalysisResult<SessionScore> {
+    let data = load_session(session_id)?;
+    Ok(score_session(&data, &load_model()?))
+  })
+  .await
+  .map_err(CommandError::from)?
+  .map_err(CommandError::from)
+}
+
+/// 离线检测会话中的异常采样点，按时间排序
+#[tauri::command]
+pub async fn tauri_list_anomalies(session_id: i64) -> Result<Vec<Anomaly>, CommandError> {
+  spawn_blocking(move || -> AnalysisResult<Vec<Anomaly>> {
+    let data = load_session(session_id)?;
+    Ok(detect(&data.samples))
+  })
+  .await
+  .map_err(CommandError::from)?
+  .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_analyze_memory_leak(query: LeakQuery) -> Result<LeakAnalysis, CommandError> {
+  spawn_blocking(move || -> AnalysisResult<LeakAnalysis> {
+    let data = load_session(query.session_id)?;
+    analyze_leak(&data, &query)
+  })
+  .await
+  .map_err(CommandError::from)?
+  .map_err(CommandError::from)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EvaluateGatePayload {
+  pub session_id: i64,
+  /// 缺省使用该会话所属组合的基线
+  #[serde(default)]
+  pub baseline_session_id: Option<i64>,
+  /// 缺省使用已保存的门禁配置
+  #[serde(default)]
+  pub config: Option<GateConfig>,
+}
+
+#[tauri::command]
+pub async fn tauri_get_gate_config() -> Result<GateConfig, CommandError> {
+  spawn_blocking(load_config)
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_set_gate_config(config: GateConfig) -> Result<(), CommandError> {
+  spawn_blocking(move || save_config(&config))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+/// 按容差配置将会话与基线比较，返回可供 CI 判定的通过 / 失败结果
+#[tauri::command]
+pub async fn tauri_evaluate_gate(payload: EvaluateGatePayload) -> Result<GateReport, CommandError> {
+  spawn_blocking(move || -> AnalysisResult<GateReport> {
+    let config = match payload.config {
+      Some(config) => config,
+      None => load_config()?,
+    };
+    let report = gate_session(payload.session_id, payload.baseline_session_id, &config)?;
+    notify_gate(&report);
+    Ok(report)
+  })
+  .await
+  .map_err(CommandError::from)?
+  .map_err(CommandError::from)
+}
+
+/// 基于设备 power_profile 估算整场及各标记分段的部件能耗
+#[tauri::command]
+pub async fn tauri_estimate_energy(session_id: i64) -> Result<EnergyEstimate, CommandError> {
+  spawn_blocking(move || session_energy(session_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
@@ -0,0 +1,29 @@
+use crate::{
+  diagnostics::{bundle::export_support_bundle, collect, Diagnostics},
+  i18n::CommandError,
+};
+use std::path::PathBuf;
+use tauri::{async_runtime::spawn_blocking, AppHandle, Manager};
+
+/// 汇总排查问题所需的环境信息
+#[tauri::command]
+pub async fn tauri_get_diagnostics(app: AppHandle) -> Result<Diagnostics, CommandError> {
+  spawn_blocking(move || collect(&app))
+    .await
+    .map_err(CommandError::from)
+}
+
+/// 导出可附在 issue 中的支持包（zip）
+#[tauri::command]
+pub async fn tauri_export_support_bundle(
+  app: AppHandle,
+  path: PathBuf,
+) -> Result<(), CommandError> {
+  spawn_blocking(move || {
+    let log_dir = app.path().app_log_dir().ok();
+    export_support_bundle(&collect(&app), log_dir.as_deref(), &path)
+  })
+  .await
+  .map_err(CommandError::from)?
+  .map_err(CommandError::from)
+}
@@ -0,0 +1,97 @@
+use crate::{
+  export::{
+    error::Result as ExportResult, export_bundle, export_json, export_session_junit, export_xlsx,
+  },
+  i18n::CommandError,
+  notify::notify_export,
+  session::load_session,
+};
+use serde::Deserialize;
+use std::{path::PathBuf, time::Instant};
+use tauri::{async_runtime::spawn_blocking, AppHandle};
+
+/// 在阻塞线程中导出，耗时较长时完成后发系统通知
+async fn run_export(
+  app: AppHandle,
+  path: PathBuf,
+  f: impl FnOnce(&PathBuf) -> ExportResult<()> + Send + 'static,
+) -> Result<(), CommandError> {
+  let started = Instant::now();
+  let target = path.clone();
+  let result = spawn_blocking(move || f(&target))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from);
+  notify_export(&app, &path, started, result.is_ok());
+  result
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportSessionPayload {
+  pub session_id: i64,
+  pub path: PathBuf,
+}
+
+#[tauri::command]
+pub async fn tauri_export_xlsx(
+  app: AppHandle,
+  payload: ExportSessionPayload,
+) -> Result<(), CommandError> {
+  run_export(app, payload.path, move |path| {
+    let data = load_session(payload.session_id)?;
+    export_xlsx(&data, path)
+  })
+  .await
+}
+
+#[tauri::command]
+pub async fn tauri_export_json(
+  app: AppHandle,
+  payload: ExportSessionPayload,
+) -> Result<(), CommandError> {
+  run_export(app, payload.path, move |path| {
+    let data = load_session(payload.session_id)?;
+    export_json(&data, path)
+  })
+  .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportBundlePayload {
+  pub session_id: i64,
+  pub path: PathBuf,
+  /// 设置后导出加密的会话包
+  #[serde(default)]
+  pub passphrase: Option<String>,
+}
+
+#[tauri::command]
+pub async fn tauri_export_bundle(
+  app: AppHandle,
+  payload: ExportBundlePayload,
+) -> Result<(), CommandError> {
+  run_export(app, payload.path, move |path| {
+    let data = load_session(payload.session_id)?;
+    export_bundle(&data, path, payload.passphrase.as_deref())
+  })
+  .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportJunitPayload {
+  pub session_id: i64,
+  pub path: PathBuf,
+  #[serde(default)]
+  pub baseline_session_id: Option<i64>,
+}
+
+#[tauri::command]
+pub async fn tauri_export_junit(
+  app: AppHandle,
+  payload: ExportJunitPayload,
+) -> Result<(), CommandError> {
+  run_export(app, payload.path, move |path| {
+    export_session_junit(payload.session_id, payload.baseline_session_id, path)
+  })
+  .await
+}
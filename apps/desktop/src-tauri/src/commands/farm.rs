@@ -0,0 +1,61 @@
+use crate::farm::{
+  get_farm_config, list_farm_devices, list_reservations, release_device, reserve_device,
+  set_farm_config, FarmConfig, FarmDevice, Reservation,
+};
+use crate::i18n::CommandError;
+use serde::Deserialize;
+use tauri::async_runtime::spawn_blocking;
+
+#[tauri::command]
+pub async fn tauri_get_farm_config() -> Result<Option<FarmConfig>, CommandError> {
+  spawn_blocking(get_farm_config)
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_set_farm_config(config: FarmConfig) -> Result<(), CommandError> {
+  spawn_blocking(move || set_farm_config(&config))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_list_farm_devices() -> Result<Vec<FarmDevice>, CommandError> {
+  spawn_blocking(list_farm_devices)
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReservePayload {
+  pub serial: String,
+  #[serde(default)]
+  pub release_on_stop: bool,
+}
+
+#[tauri::command]
+pub async fn tauri_reserve_farm_device(
+  payload: ReservePayload,
+) -> Result<Reservation, CommandError> {
+  spawn_blocking(move || reserve_device(&payload.serial, payload.release_on_stop))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_release_farm_device(adb_id: String) -> Result<(), CommandError> {
+  spawn_blocking(move || release_device(&adb_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub fn tauri_list_farm_reservations() -> Vec<Reservation> {
+  list_reservations()
+}
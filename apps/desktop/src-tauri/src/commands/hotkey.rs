@@ -0,0 +1,14 @@
+use crate::hotkey::{get_hotkeys, set_hotkeys, HotkeyConfig};
+use crate::i18n::CommandError;
+use tauri::AppHandle;
+
+#[tauri::command]
+pub fn tauri_get_hotkeys() -> Result<HotkeyConfig, CommandError> {
+  get_hotkeys().map_err(CommandError::from)
+}
+
+/// 保存后立即生效
+#[tauri::command]
+pub fn tauri_set_hotkeys(app: AppHandle, config: HotkeyConfig) -> Result<(), CommandError> {
+  set_hotkeys(&app, &config).map_err(CommandError::from)
+}
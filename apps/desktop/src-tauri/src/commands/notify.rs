@@ -0,0 +1,14 @@
+use crate::{
+  i18n::CommandError,
+  notify::{get_notification_settings, set_notification_settings, NotificationSettings},
+};
+
+#[tauri::command]
+pub fn tauri_get_notification_settings() -> Result<NotificationSettings, CommandError> {
+  get_notification_settings().map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub fn tauri_set_notification_settings(settings: NotificationSettings) -> Result<(), CommandError> {
+  set_notification_settings(&settings).map_err(CommandError::from)
+}
@@ -0,0 +1,15 @@
+use crate::{
+  i18n::CommandError,
+  overlay::{hide_overlay, show_overlay},
+};
+use tauri::AppHandle;
+
+#[tauri::command]
+pub fn tauri_show_overlay(app: AppHandle) -> Result<(), CommandError> {
+  show_overlay(&app).map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub fn tauri_hide_overlay(app: AppHandle) -> Result<(), CommandError> {
+  hide_overlay(&app).map_err(CommandError::from)
+}
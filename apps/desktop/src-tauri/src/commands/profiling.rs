@@ -0,0 +1,193 @@
+use crate::{
+  adb::perfetto::{HeapprofdConfig, PerfettoConfig},
+  analysis::flamegraph::generate_flamegraph,
+  events::emit_bugreport_progress,
+  i18n::CommandError,
+  session::{
+    capture::{
+      capture_bugreport, dump_heap, list_captures, start_heapprofd, start_method_trace,
+      start_perfetto, start_simpleperf, start_tcpdump, stop_capture, CaptureKind, CaptureStatus,
+      HeapDumpOptions, MethodTraceOptions, SavedBugreport, SimpleperfOptions, TcpdumpOptions,
+    },
+    dropbox::{collect_dropbox, CollectedEntry, DropboxOptions},
+    events::{AttachmentInfo, SessionEvent},
+  },
+};
+use log::warn;
+use serde::Deserialize;
+use std::path::PathBuf;
+use tauri::{async_runtime::spawn_blocking, AppHandle};
+
+#[derive(Debug, Deserialize)]
+pub struct SimpleperfPayload {
+  pub session_id: i64,
+  #[serde(default)]
+  pub options: SimpleperfOptions,
+}
+
+#[tauri::command]
+pub async fn tauri_start_simpleperf(
+  payload: SimpleperfPayload,
+) -> Result<CaptureStatus, CommandError> {
+  spawn_blocking(move || start_simpleperf(payload.session_id, &payload.options))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TcpdumpPayload {
+  pub session_id: i64,
+  #[serde(default)]
+  pub options: TcpdumpOptions,
+}
+
+/// 开始设备端抓包，需要 root；停止后 pcap 作为会话附件保存
+#[tauri::command]
+pub async fn tauri_start_tcpdump(payload: TcpdumpPayload) -> Result<CaptureStatus, CommandError> {
+  spawn_blocking(move || start_tcpdump(payload.session_id, &payload.options))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PerfettoPayload {
+  pub session_id: i64,
+  #[serde(default)]
+  pub config: PerfettoConfig,
+}
+
+#[tauri::command]
+pub async fn tauri_start_perfetto(payload: PerfettoPayload) -> Result<CaptureStatus, CommandError> {
+  spawn_blocking(move || start_perfetto(payload.session_id, &payload.config))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HeapprofdPayload {
+  pub session_id: i64,
+  #[serde(default)]
+  pub config: HeapprofdConfig,
+}
+
+#[tauri::command]
+pub async fn tauri_start_heapprofd(
+  payload: HeapprofdPayload,
+) -> Result<CaptureStatus, CommandError> {
+  spawn_blocking(move || start_heapprofd(payload.session_id, &payload.config))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MethodTracePayload {
+  pub session_id: i64,
+  #[serde(default)]
+  pub options: MethodTraceOptions,
+}
+
+#[tauri::command]
+pub async fn tauri_start_method_trace(
+  payload: MethodTracePayload,
+) -> Result<CaptureStatus, CommandError> {
+  spawn_blocking(move || start_method_trace(payload.session_id, &payload.options))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StopCapturePayload {
+  pub session_id: i64,
+  pub kind: CaptureKind,
+}
+
+/// 停止抓取并把结果文件保存到会话，返回带附件的抓取事件；CPU 采样同时生成火焰图
+#[tauri::command]
+pub async fn tauri_stop_capture(payload: StopCapturePayload) -> Result<SessionEvent, CommandError> {
+  spawn_blocking(move || {
+    let mut event = stop_capture(payload.session_id, payload.kind).map_err(CommandError::from)?;
+    if payload.kind == CaptureKind::Simpleperf {
+      if let Some(id) = event.attachments.first().map(|a| a.id) {
+        match generate_flamegraph(id) {
+          Ok(attachments) => event.attachments.extend(attachments),
+          Err(e) => warn!("生成火焰图失败: {}", e),
+        }
+      }
+    }
+    Ok(event)
+  })
+  .await
+  .map_err(CommandError::from)?
+}
+
+/// 由已保存的 perf.data 附件重新生成火焰图
+#[tauri::command]
+pub async fn tauri_generate_flamegraph(
+  attachment_id: i64,
+) -> Result<Vec<AttachmentInfo>, CommandError> {
+  spawn_blocking(move || generate_flamegraph(attachment_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HeapDumpPayload {
+  pub session_id: i64,
+  #[serde(default)]
+  pub options: HeapDumpOptions,
+}
+
+#[tauri::command]
+pub async fn tauri_dump_heap(payload: HeapDumpPayload) -> Result<SessionEvent, CommandError> {
+  spawn_blocking(move || dump_heap(payload.session_id, &payload.options))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+/// 生成 bugreport 并保存到会话或 `path`，进度通过 `capture://bugreport-progress` 事件推送
+#[tauri::command]
+pub async fn tauri_capture_bugreport(
+  app: AppHandle,
+  device_id: String,
+  session_id: Option<i64>,
+  path: Option<PathBuf>,
+) -> Result<SavedBugreport, CommandError> {
+  spawn_blocking(move || {
+    capture_bugreport(&device_id, session_id, path.as_deref(), |percent, message| {
+      emit_bugreport_progress(&app, &device_id, percent, message)
+    })
+  })
+  .await
+  .map_err(CommandError::from)?
+  .map_err(CommandError::from)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DropboxPayload {
+  pub session_id: i64,
+  #[serde(default)]
+  pub options: DropboxOptions,
+}
+
+/// 拉取近期的崩溃 / ANR / tombstone 记录，覆盖未监听 logcat 时发生的问题
+#[tauri::command]
+pub async fn tauri_collect_dropbox(
+  payload: DropboxPayload,
+) -> Result<Vec<CollectedEntry>, CommandError> {
+  spawn_blocking(move || collect_dropbox(payload.session_id, &payload.options))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub fn tauri_list_captures() -> Vec<CaptureStatus> {
+  list_captures()
+}
@@ -0,0 +1,457 @@
+use crate::{
+  adb::{
+    connectivity::{ConnectivityState, Radio},
+    cpufreq::{CpuLock, CpuPolicy},
+    display::{DisplaySettings, DisplayState},
+    game::GameModeInfo,
+    hwui::{DeviceOverlay, OverlayState},
+    idle::{IdleState, StandbyBucket},
+    monkey::MonkeyConfig,
+    proxy::ProxyConfig,
+    settings::AnimationScales,
+  },
+  i18n::CommandError,
+  session::{
+    animation::{get_animation_scales, restore_animations, set_animations},
+    connectivity::{get_connectivity, restore_saved_connectivity, set_connectivity},
+    cpufreq::{get_cpu_policies, lock_cpu, restore_cpu, AppliedCpuLock},
+    display::{get_display, restore_saved_display, set_display},
+    game::{get_game_mode, reset_game, set_game, GameModeRequest},
+    hwui::{get_device_overlays, restore_device_overlays, set_device_overlay},
+    idle::{get_idle_state, get_standby_bucket, set_bucket, set_doze},
+    logcat::{query_logcat, LogEntry, LogcatQuery},
+    monkey::{list_monkey_runs, start_monkey, stop_monkey, MonkeyStatus},
+    proxy::{get_proxy, restore_proxy, set_proxy},
+    raw::{load_raw, RawDumpRecord},
+    recorder::{
+      capture_recording_screenshot, list_recordings, mark_recording, set_recording_paused,
+      start_recording, stop_recording, RecordingConfig, RecordingStatus,
+    },
+    screenshot::{list_screenshots, load_screenshot, ScreenshotInfo},
+    script::{list_script_runs, run_script, stop_script, ScriptRun, ScriptSource},
+    startup::{load_startup_run, run_startup_benchmark, StartupBenchConfig, StartupRun},
+    Marker, Session,
+  },
+};
+use serde::Deserialize;
+use tauri::{async_runtime::spawn_blocking, ipc::Response};
+
+#[tauri::command]
+pub async fn tauri_start_recording(config: RecordingConfig) -> Result<i64, CommandError> {
+  spawn_blocking(move || start_recording(crate::settings::apply_defaults(config)))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_stop_recording(session_id: i64) -> Result<Session, CommandError> {
+  spawn_blocking(move || stop_recording(session_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub fn tauri_list_recordings() -> Vec<RecordingStatus> {
+  list_recordings()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MarkRecordingPayload {
+  pub session_id: i64,
+  pub name: String,
+}
+
+#[tauri::command]
+pub async fn tauri_mark_recording(payload: MarkRecordingPayload) -> Result<Marker, CommandError> {
+  spawn_blocking(move || mark_recording(payload.session_id, &payload.name))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+/// 暂停采样，返回插入的“暂停”标记
+#[tauri::command]
+pub async fn tauri_pause_recording(session_id: i64) -> Result<Marker, CommandError> {
+  spawn_blocking(move || set_recording_paused(session_id, true))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_resume_recording(session_id: i64) -> Result<Marker, CommandError> {
+  spawn_blocking(move || set_recording_paused(session_id, false))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RawDumpsPayload {
+  pub session_id: i64,
+  #[serde(default)]
+  pub from_ms: u64,
+  #[serde(default)]
+  pub to_ms: Option<u64>,
+  #[serde(default)]
+  pub kind: Option<String>,
+}
+
+#[tauri::command]
+pub async fn tauri_get_raw_dumps(
+  payload: RawDumpsPayload,
+) -> Result<Vec<RawDumpRecord>, CommandError> {
+  spawn_blocking(move || {
+    load_raw(
+      payload.session_id,
+      payload.from_ms,
+      payload.to_ms.unwrap_or(i64::MAX as u64),
+      payload.kind.as_deref(),
+    )
+  })
+  .await
+  .map_err(CommandError::from)?
+  .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_query_logcat(query: LogcatQuery) -> Result<Vec<LogEntry>, CommandError> {
+  spawn_blocking(move || query_logcat(&query))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub fn tauri_capture_screenshot(session_id: i64) -> Result<(), CommandError> {
+  capture_recording_screenshot(session_id).map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_list_screenshots(session_id: i64) -> Result<Vec<ScreenshotInfo>, CommandError> {
+  spawn_blocking(move || list_screenshots(session_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+/// 以二进制返回 JPEG 数据，避免序列化为数字数组
+#[tauri::command]
+pub async fn tauri_get_screenshot(id: i64) -> Result<Response, CommandError> {
+  spawn_blocking(move || load_screenshot(id))
+    .await
+    .map_err(CommandError::from)?
+    .map(Response::new)
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_run_startup_benchmark(
+  config: StartupBenchConfig,
+) -> Result<StartupRun, CommandError> {
+  spawn_blocking(move || run_startup_benchmark(config))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_get_startup_run(session_id: i64) -> Result<Option<StartupRun>, CommandError> {
+  spawn_blocking(move || load_startup_run(session_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MonkeyPayload {
+  pub session_id: i64,
+  #[serde(default)]
+  pub config: MonkeyConfig,
+}
+
+#[tauri::command]
+pub async fn tauri_start_monkey(payload: MonkeyPayload) -> Result<MonkeyStatus, CommandError> {
+  spawn_blocking(move || start_monkey(payload.session_id, &payload.config))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_stop_monkey(session_id: i64) -> Result<(), CommandError> {
+  spawn_blocking(move || stop_monkey(session_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub fn tauri_list_monkey_runs() -> Vec<MonkeyStatus> {
+  list_monkey_runs()
+}
+
+#[tauri::command]
+pub async fn tauri_get_connectivity(device_id: String) -> Result<ConnectivityState, CommandError> {
+  spawn_blocking(move || get_connectivity(&device_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_set_connectivity(
+  device_id: String,
+  radio: Radio,
+  enabled: bool,
+) -> Result<ConnectivityState, CommandError> {
+  spawn_blocking(move || set_connectivity(&device_id, radio, enabled))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_restore_connectivity(
+  device_id: String,
+) -> Result<Option<ConnectivityState>, CommandError> {
+  spawn_blocking(move || restore_saved_connectivity(&device_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_get_cpu_policies(device_id: String) -> Result<Vec<CpuPolicy>, CommandError> {
+  spawn_blocking(move || get_cpu_policies(&device_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_lock_cpu(
+  device_id: String,
+  lock: CpuLock,
+) -> Result<AppliedCpuLock, CommandError> {
+  spawn_blocking(move || lock_cpu(&device_id, lock))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_restore_cpu(device_id: String) -> Result<bool, CommandError> {
+  spawn_blocking(move || restore_cpu(&device_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_get_animation_scales(
+  device_id: String,
+) -> Result<AnimationScales, CommandError> {
+  spawn_blocking(move || get_animation_scales(&device_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_set_animation_scales(
+  device_id: String,
+  scales: AnimationScales,
+) -> Result<AnimationScales, CommandError> {
+  spawn_blocking(move || set_animations(&device_id, &scales))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_restore_animation_scales(device_id: String) -> Result<bool, CommandError> {
+  spawn_blocking(move || restore_animations(&device_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_get_display(device_id: String) -> Result<DisplayState, CommandError> {
+  spawn_blocking(move || get_display(&device_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_set_display(
+  device_id: String,
+  settings: DisplaySettings,
+) -> Result<DisplayState, CommandError> {
+  spawn_blocking(move || set_display(&device_id, settings))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_restore_display(device_id: String) -> Result<bool, CommandError> {
+  spawn_blocking(move || restore_saved_display(&device_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_get_device_overlays(device_id: String) -> Result<OverlayState, CommandError> {
+  spawn_blocking(move || get_device_overlays(&device_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_set_device_overlay(
+  device_id: String,
+  overlay: DeviceOverlay,
+  enabled: bool,
+) -> Result<(), CommandError> {
+  spawn_blocking(move || set_device_overlay(&device_id, overlay, enabled))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_restore_device_overlays(device_id: String) -> Result<bool, CommandError> {
+  spawn_blocking(move || restore_device_overlays(&device_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_get_proxy(device_id: String) -> Result<Option<String>, CommandError> {
+  spawn_blocking(move || get_proxy(&device_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_set_proxy(device_id: String, config: ProxyConfig) -> Result<(), CommandError> {
+  spawn_blocking(move || set_proxy(&device_id, &config))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_restore_proxy(device_id: String) -> Result<bool, CommandError> {
+  spawn_blocking(move || restore_proxy(&device_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_get_idle_state(device_id: String) -> Result<IdleState, CommandError> {
+  spawn_blocking(move || get_idle_state(&device_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+/// 强制进入（`enabled`）或退出 deep Doze
+#[tauri::command]
+pub async fn tauri_set_doze(device_id: String, enabled: bool) -> Result<IdleState, CommandError> {
+  spawn_blocking(move || set_doze(&device_id, enabled))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_get_standby_bucket(
+  device_id: String,
+  package: String,
+) -> Result<StandbyBucket, CommandError> {
+  spawn_blocking(move || get_standby_bucket(&device_id, &package))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_set_standby_bucket(
+  device_id: String,
+  package: String,
+  bucket: StandbyBucket,
+) -> Result<(), CommandError> {
+  spawn_blocking(move || set_bucket(&device_id, &package, bucket))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_get_game_mode(
+  device_id: String,
+  package: String,
+) -> Result<GameModeInfo, CommandError> {
+  spawn_blocking(move || get_game_mode(&device_id, &package))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_set_game_mode(
+  device_id: String,
+  package: String,
+  request: GameModeRequest,
+) -> Result<GameModeInfo, CommandError> {
+  spawn_blocking(move || set_game(&device_id, &package, &request))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_reset_game_mode(
+  device_id: String,
+  package: String,
+) -> Result<GameModeInfo, CommandError> {
+  spawn_blocking(move || reset_game(&device_id, &package))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScriptPayload {
+  pub session_id: i64,
+  pub source: ScriptSource,
+}
+
+#[tauri::command]
+pub async fn tauri_run_script(payload: ScriptPayload) -> Result<ScriptRun, CommandError> {
+  spawn_blocking(move || run_script(payload.session_id, payload.source))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub fn tauri_stop_script(session_id: i64) -> Result<(), CommandError> {
+  stop_script(session_id).map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub fn tauri_list_script_runs() -> Vec<ScriptRun> {
+  list_script_runs()
+}
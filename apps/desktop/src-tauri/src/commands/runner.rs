@@ -0,0 +1,61 @@
+use crate::{
+  i18n::CommandError,
+  runner::{
+    cancel_run,
+    error::RunnerError,
+    list_runs,
+    scenario::{load_scenario, parse_scenario, start_scenario, Scenario},
+    timed::{start_timed_run, TimedRunConfig},
+    RunStatus,
+  },
+};
+use serde::Deserialize;
+use std::path::PathBuf;
+use tauri::async_runtime::spawn_blocking;
+
+#[tauri::command]
+pub fn tauri_start_timed_run(config: TimedRunConfig) -> RunStatus {
+  start_timed_run(config)
+}
+
+#[tauri::command]
+pub fn tauri_cancel_run(id: u64) -> Result<(), CommandError> {
+  cancel_run(id).map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub fn tauri_list_runs() -> Vec<RunStatus> {
+  list_runs()
+}
+
+/// 校验场景文本并返回解析结果，供编辑器预览步骤
+#[tauri::command]
+pub fn tauri_parse_scenario(text: String) -> Result<Scenario, CommandError> {
+  parse_scenario(&text).map_err(CommandError::from)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RunScenarioPayload {
+  /// 场景文件路径，与 scenario 二选一
+  #[serde(default)]
+  pub path: Option<PathBuf>,
+  #[serde(default)]
+  pub scenario: Option<Scenario>,
+  #[serde(default)]
+  pub device_id: Option<String>,
+}
+
+#[tauri::command]
+pub async fn tauri_run_scenario(payload: RunScenarioPayload) -> Result<RunStatus, CommandError> {
+  spawn_blocking(move || {
+    let scenario = match (payload.scenario, payload.path) {
+      (Some(scenario), _) => scenario,
+      (None, Some(path)) => load_scenario(&path)?,
+      (None, None) => return Err(RunnerError::Scenario("未提供场景".into())),
+    };
+    start_scenario(scenario, payload.device_id)
+  })
+  .await
+  .map_err(CommandError::from)?
+  .map_err(CommandError::from)
+}
@@ -0,0 +1,45 @@
+use crate::{
+  events::emit_remote_frame,
+  i18n::CommandError,
+  server::{
+    remote::{connect_remote, disconnect_remote, remote_status, RemoteConfig, RemoteStatus},
+    server_status, start_server, stop_server, ServerConfig, ServerStatus,
+  },
+};
+use tauri::AppHandle;
+
+#[tauri::command]
+pub async fn tauri_start_server(config: ServerConfig) -> Result<ServerStatus, CommandError> {
+  start_server(config).await.map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub fn tauri_stop_server() -> Result<(), CommandError> {
+  stop_server().map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub fn tauri_server_status() -> Option<ServerStatus> {
+  server_status()
+}
+
+/// 只读观看另一台实例的实时录制，帧通过 `remote://frame` 事件推送
+#[tauri::command]
+pub async fn tauri_connect_remote(
+  app: AppHandle,
+  config: RemoteConfig,
+) -> Result<RemoteStatus, CommandError> {
+  connect_remote(config, move |frame| emit_remote_frame(&app, frame))
+    .await
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub fn tauri_disconnect_remote() -> Result<(), CommandError> {
+  disconnect_remote().map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub fn tauri_remote_status() -> Option<RemoteStatus> {
+  remote_status()
+}
@@ -0,0 +1,226 @@
+use crate::{
+  i18n::CommandError,
+  session::{
+    add_marker,
+    alerts::{delete_rule, list_rules, save_rule, AlertRule},
+    baseline::{clear_baseline, list_baselines, set_baseline, Baseline},
+    error::Result as SessionResult,
+    events::{list_events, load_attachment, SessionEvent},
+    import_file, list_markers, load_session,
+    meta::{capture_meta, get_meta, update_meta, SessionMetaPatch},
+    retention::{
+      db_usage, load_policy, run_maintenance, save_policy, DbUsage, MaintenanceReport,
+      RetentionPolicy,
+    },
+    Marker, SessionMeta,
+  },
+};
+use serde::Deserialize;
+use std::path::PathBuf;
+use tauri::{async_runtime::spawn_blocking, ipc::Response};
+
+#[derive(Debug, Deserialize)]
+pub struct ImportSessionPayload {
+  pub path: PathBuf,
+  #[serde(default)]
+  pub name: Option<String>,
+  /// 加密会话包的口令
+  #[serde(default)]
+  pub passphrase: Option<String>,
+}
+
+#[tauri::command]
+pub async fn tauri_import_session(payload: ImportSessionPayload) -> Result<i64, CommandError> {
+  spawn_blocking(move || import_file(&payload.path, payload.name, payload.passphrase.as_deref()))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddMarkerPayload {
+  pub session_id: i64,
+  pub t_ms: u64,
+  pub name: String,
+}
+
+#[tauri::command]
+pub async fn tauri_add_marker(payload: AddMarkerPayload) -> Result<Marker, CommandError> {
+  spawn_blocking(move || add_marker(payload.session_id, payload.t_ms, &payload.name))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_list_markers(session_id: i64) -> Result<Vec<Marker>, CommandError> {
+  spawn_blocking(move || list_markers(session_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_list_session_events(session_id: i64) -> Result<Vec<SessionEvent>, CommandError> {
+  spawn_blocking(move || list_events(session_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_get_attachment(id: i64) -> Result<Response, CommandError> {
+  spawn_blocking(move || load_attachment(id))
+    .await
+    .map_err(CommandError::from)?
+    .map(Response::new)
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_get_session_meta(session_id: i64) -> Result<SessionMeta, CommandError> {
+  spawn_blocking(move || get_meta(session_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateSessionMetaPayload {
+  pub session_id: i64,
+  #[serde(flatten)]
+  pub patch: SessionMetaPatch,
+}
+
+#[tauri::command]
+pub async fn tauri_update_session_meta(
+  payload: UpdateSessionMetaPayload,
+) -> Result<SessionMeta, CommandError> {
+  spawn_blocking(move || update_meta(payload.session_id, payload.patch))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetScenarioPayload {
+  pub session_id: i64,
+  pub scenario: String,
+}
+
+/// 仅修改场景，等同于只带 `scenario` 的 `tauri_update_session_meta`
+#[tauri::command]
+pub async fn tauri_set_session_scenario(
+  payload: SetScenarioPayload,
+) -> Result<SessionMeta, CommandError> {
+  let patch = SessionMetaPatch {
+    scenario: Some(payload.scenario),
+    ..Default::default()
+  };
+  spawn_blocking(move || update_meta(payload.session_id, patch))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+/// 从会话对应的设备读取设备信息与应用版本，通常在保存报告后立即调用
+#[tauri::command]
+pub async fn tauri_capture_session_meta(session_id: i64) -> Result<SessionMeta, CommandError> {
+  spawn_blocking(move || -> SessionResult<SessionMeta> {
+    let data = load_session(session_id)?;
+    capture_meta(
+      session_id,
+      &data.session.device_id,
+      &data.session.app_package,
+    )
+  })
+  .await
+  .map_err(CommandError::from)?
+  .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_set_baseline(session_id: i64) -> Result<Baseline, CommandError> {
+  spawn_blocking(move || set_baseline(session_id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_clear_baseline(id: i64) -> Result<(), CommandError> {
+  spawn_blocking(move || clear_baseline(id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_list_baselines() -> Result<Vec<Baseline>, CommandError> {
+  spawn_blocking(list_baselines)
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_get_retention_policy() -> Result<RetentionPolicy, CommandError> {
+  spawn_blocking(load_policy)
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+/// 保存后立即按新策略执行一次清理
+#[tauri::command]
+pub async fn tauri_set_retention_policy(
+  policy: RetentionPolicy,
+) -> Result<MaintenanceReport, CommandError> {
+  spawn_blocking(move || {
+    save_policy(&policy)?;
+    run_maintenance()
+  })
+  .await
+  .map_err(CommandError::from)?
+  .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_run_db_maintenance() -> Result<MaintenanceReport, CommandError> {
+  spawn_blocking(run_maintenance)
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_get_db_usage() -> Result<DbUsage, CommandError> {
+  spawn_blocking(db_usage)
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_list_alert_rules() -> Result<Vec<AlertRule>, CommandError> {
+  spawn_blocking(list_rules)
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_save_alert_rule(rule: AlertRule) -> Result<AlertRule, CommandError> {
+  spawn_blocking(move || save_rule(&rule))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_delete_alert_rule(id: i64) -> Result<(), CommandError> {
+  spawn_blocking(move || delete_rule(id))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
@@ -0,0 +1,32 @@
+use crate::{
+  i18n::CommandError,
+  settings::{get_settings, set_settings, update_selection, AppSettings, Selection},
+};
+use tauri::{async_runtime::spawn_blocking, AppHandle};
+
+#[tauri::command]
+pub async fn tauri_get_settings() -> Result<AppSettings, CommandError> {
+  spawn_blocking(get_settings)
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_set_settings(
+  app: AppHandle,
+  settings: AppSettings,
+) -> Result<AppSettings, CommandError> {
+  spawn_blocking(move || set_settings(&app, settings))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_update_selection(selection: Selection) -> Result<(), CommandError> {
+  spawn_blocking(move || update_selection(selection))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
@@ -0,0 +1,35 @@
+use crate::{
+  i18n::CommandError,
+  updater::{
+    apply_update, check_update, download_update, get_update_settings, set_update_settings,
+    UpdateInfo, UpdateSettings,
+  },
+};
+use tauri::AppHandle;
+
+#[tauri::command]
+pub fn tauri_get_update_settings() -> Result<UpdateSettings, CommandError> {
+  get_update_settings().map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub fn tauri_set_update_settings(settings: UpdateSettings) -> Result<(), CommandError> {
+  set_update_settings(&settings).map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_check_update(app: AppHandle) -> Result<Option<UpdateInfo>, CommandError> {
+  check_update(&app).await.map_err(CommandError::from)
+}
+
+/// 后台下载，进度通过 `update://progress` 事件推送
+#[tauri::command]
+pub async fn tauri_download_update(app: AppHandle) -> Result<Option<UpdateInfo>, CommandError> {
+  download_update(&app).await.map_err(CommandError::from)
+}
+
+/// 安装成功后应用直接重启，不会返回
+#[tauri::command]
+pub fn tauri_apply_update(app: AppHandle) -> Result<(), CommandError> {
+  apply_update(&app).map_err(CommandError::from)
+}
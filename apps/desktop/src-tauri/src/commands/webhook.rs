@@ -0,0 +1,30 @@
+use crate::{
+  i18n::CommandError,
+  webhook::{list_webhooks, save_webhooks, test_webhook, WebhookConfig},
+};
+use tauri::async_runtime::spawn_blocking;
+
+#[tauri::command]
+pub async fn tauri_list_webhooks() -> Result<Vec<WebhookConfig>, CommandError> {
+  spawn_blocking(list_webhooks)
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+/// 整体替换 Webhook 列表
+#[tauri::command]
+pub async fn tauri_save_webhooks(webhooks: Vec<WebhookConfig>) -> Result<(), CommandError> {
+  spawn_blocking(move || save_webhooks(&webhooks))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub async fn tauri_test_webhook(webhook: WebhookConfig) -> Result<(), CommandError> {
+  spawn_blocking(move || test_webhook(&webhook))
+    .await
+    .map_err(CommandError::from)?
+    .map_err(CommandError::from)
+}
@@ -0,0 +1,32 @@
+use crate::{
+  i18n::CommandError,
+  windows::{
+    close_chart_window, list_chart_windows, open_chart_window, send_history, ChartBinding,
+    ChartWindow,
+  },
+};
+use tauri::{AppHandle, WebviewWindow};
+
+#[tauri::command]
+pub fn tauri_open_chart_window(
+  app: AppHandle,
+  binding: ChartBinding,
+) -> Result<String, CommandError> {
+  open_chart_window(&app, binding).map_err(CommandError::from)
+}
+
+#[tauri::command]
+pub fn tauri_list_chart_windows() -> Vec<ChartWindow> {
+  list_chart_windows()
+}
+
+#[tauri::command]
+pub fn tauri_close_chart_window(app: AppHandle, label: String) -> Result<(), CommandError> {
+  close_chart_window(&app, &label).map_err(CommandError::from)
+}
+
+/// 由图表窗口页面在开始监听后调用
+#[tauri::command]
+pub fn tauri_chart_window_ready(app: AppHandle, window: WebviewWindow) {
+  send_history(&app, window.label());
+}
@@ -0,0 +1,210 @@
+//! `perfx://` 链接：CI 页面上的“采集此版本”等链接可直接拉起应用并开始录制
+//!
+//! - `perfx://session/start?device=SERIAL&package=com.foo&metrics=fps,cpu&duration=300`
+//! - `perfx://session/stop?device=SERIAL`
+//!
+//! 链接只能设置设备、应用、指标、时长与采样间隔，录制前后的 shell 命令等选项不开放
+
+use crate::{
+  adb::{list_devices, MetricKey},
+  runner::timed::{start_timed_run, TimedRunConfig},
+  session::recorder::{
+    active_recording, start_recording, stop_recording, RecordingConfig, RecordingOptions,
+  },
+};
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+use thiserror::Error;
+
+pub const DEEP_LINK_EVENT: &str = "deeplink://handled";
+
+const SCHEME: &str = "perfx";
+
+#[derive(Debug, Error)]
+pub enum DeepLinkError {
+  #[error("不支持的链接: {0}")]
+  Unsupported(String),
+  #[error("链接缺少参数 {0}")]
+  Missing(&'static str),
+  #[error("链接参数 {0} 无效: {1}")]
+  Invalid(&'static str, String),
+  #[error("未指定设备，且当前连接了 {0} 台设备")]
+  AmbiguousDevice(usize),
+  #[error("{0}")]
+  Action(String),
+}
+
+pub type Result<T> = std::result::Result<T, DeepLinkError>;
+
+#[derive(Debug, Clone)]
+pub enum DeepLinkAction {
+  Start {
+    config: Box<RecordingConfig>,
+    /// 指定时到时自动停止
+    duration_secs: Option<u64>,
+  },
+  Stop {
+    device_id: Option<String>,
+  },
+}
+
+/// 处理结果，以事件通知前端
+#[derive(Debug, Clone, Serialize)]
+struct Handled {
+  url: String,
+  ok: bool,
+  message: String,
+}
+
+/// 未指定设备时，只连接了一台设备则使用它
+fn resolve_device(device: Option<&String>) -> Result<String> {
+  if let Some(device) = device {
+    return Ok(device.clone());
+  }
+  let devices = list_devices().map_err(|e| DeepLinkError::Action(e.to_string()))?;
+  let online: Vec<_> = devices
+    .into_iter()
+    .filter(|d| d.state == "device")
+    .collect();
+  match online.as_slice() {
+    [only] => Ok(only.id.clone()),
+    _ => Err(DeepLinkError::AmbiguousDevice(online.len())),
+  }
+}
+
+pub fn parse(url: &Url) -> Result<DeepLinkAction> {
+  if url.scheme() != SCHEME || url.host_str() != Some("session") {
+    return Err(DeepLinkError::Unsupported(url.to_string()));
+  }
+  let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+  match url.path() {
+    "/start" => {
+      let package = query
+        .get("package")
+        .filter(|p| !p.is_empty())
+        .ok_or(DeepLinkError::Missing("package"))?
+        .clone();
+      let metrics = match query.get("metrics") {
+        Some(list) => list
+          .split(',')
+          .map(str::trim)
+          .filter(|m| !m.is_empty())
+          .map(|m| m.parse())
+          .collect::<std::result::Result<Vec<MetricKey>, _>>()
+          .map_err(|e| DeepLinkError::Invalid("metrics", e.to_string()))?,
+        None => vec![MetricKey::Fps, MetricKey::Cpu, MetricKey::Memory],
+      };
+      let number = |key: &'static str| -> Result<Option<u64>> {
+        query
+          .get(key)
+          .map(|v| {
+            v.parse()
+              .map_err(|_| DeepLinkError::Invalid(key, v.clone()))
+          })
+          .transpose()
+      };
+      let mut options = RecordingOptions::default();
+      if let Some(interval_ms) = number("interval")? {
+        options.interval_ms = interval_ms;
+      }
+      let duration_secs = number("duration")?.filter(|d| *d > 0);
+      Ok(DeepLinkAction::Start {
+        config: Box::new(RecordingConfig {
+          device_id: resolve_device(query.get("device"))?,
+          package,
+          metrics,
+          name: query.get("name").cloned(),
+          options,
+        }),
+        duration_secs,
+      })
+    }
+    "/stop" => Ok(DeepLinkAction::Stop {
+      device_id: query.get("device").cloned(),
+    }),
+    _ => Err(DeepLinkError::Unsupported(url.to_string())),
+  }
+}
+
+fn execute(action: DeepLinkAction) -> Result<String> {
+  match action {
+    DeepLinkAction::Start {
+      config,
+      duration_secs: Some(duration_secs),
+    } => {
+      let package = config.package.clone();
+      let run = start_timed_run(TimedRunConfig {
+        recording: *config,
+        duration_secs,
+        pre_steps: Default::default(),
+        export: Default::default(),
+      });
+      Ok(format!("已开始 {package} 的定时录制（任务 {}）", run.id))
+    }
+    DeepLinkAction::Start { config, .. } => {
+      let session_id =
+        start_recording(*config).map_err(|e| DeepLinkError::Action(e.to_string()))?;
+      Ok(format!("已开始录制会话 #{session_id}"))
+    }
+    DeepLinkAction::Stop { device_id } => {
+      let session_id =
+        active_recording(device_id.as_deref()).map_err(|e| DeepLinkError::Action(e.to_string()))?;
+      stop_recording(session_id).map_err(|e| DeepLinkError::Action(e.to_string()))?;
+      Ok(format!("已停止录制会话 #{session_id}"))
+    }
+  }
+}
+
+/// 解析并执行链接，结果写日志并通知前端
+pub fn handle_url(app: &AppHandle, url: &Url) {
+  info!("收到链接 {}", url);
+  let result = parse(url).and_then(execute);
+  let handled = match result {
+    Ok(message) => Handled {
+      url: url.to_string(),
+      ok: true,
+      message,
+    },
+    Err(e) => {
+      warn!("处理链接 {} 失败: {}", url, e);
+      Handled {
+        url: url.to_string(),
+        ok: false,
+        message: e.to_string(),
+      }
+    }
+  };
+  let _ = app.emit(DEEP_LINK_EVENT, handled);
+  if let Some(window) = app.get_webview_window("main") {
+    let _ = window.show();
+    let _ = window.set_focus();
+  }
+}
+
+/// 录制会访问设备，链接在后台线程中处理
+fn dispatch(app: &AppHandle, urls: Vec<Url>) {
+  let app = app.clone();
+  std::thread::spawn(move || {
+    for url in &urls {
+      handle_url(&app, url);
+    }
+  });
+}
+
+pub fn init(app: &AppHandle) {
+  let deep_link = app.deep_link();
+  // Windows 与 Linux 需要在运行时写入协议关联，macOS 由安装包声明
+  #[cfg(any(windows, target_os = "linux"))]
+  if let Err(e) = deep_link.register_all() {
+    warn!("注册 perfx:// 协议失败: {}", e);
+  }
+  let handle = app.clone();
+  deep_link.on_open_url(move |event| dispatch(&handle, event.urls()));
+  // 通过链接冷启动时，链接在启动参数中
+  if let Ok(Some(urls)) = deep_link.get_current() {
+    dispatch(app, urls);
+  }
+}
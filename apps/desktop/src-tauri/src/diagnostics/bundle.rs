@@ -0,0 +1,201 @@
+use crate::{
+  diagnostics::Diagnostics,
+  export::error::Result,
+  session::{error::Result as SessionResult, meta::query_meta, store::open},
+};
+use perfx_core::{adb::DeviceSnapshot, settings::settings_path};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{
+  fs,
+  io::{Cursor, Seek, Write},
+  path::Path,
+};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+/// 附带设备信息的最近会话数
+const RECENT_SESSIONS: i64 = 20;
+const REDACTED: &str = "<redacted>";
+/// 键名包含这些片段的值视为机密
+const SECRET_KEYS: &[&str] = &[
+  "token",
+  "password",
+  "secret",
+  "passphrase",
+  "authorization",
+  "api_key",
+  "apikey",
+];
+/// 键名包含这些片段的对象整体视为机密，只保留其中的键名（如 OTLP 自定义请求头）
+const SECRET_MAPS: &[&str] = &["header"];
+
+#[derive(Debug, Serialize)]
+struct RecentSession {
+  id: i64,
+  device_id: String,
+  device_model: Option<String>,
+  app_package: String,
+  start_time: i64,
+  duration: i64,
+  device: Option<DeviceSnapshot>,
+  app_version_name: Option<String>,
+}
+
+/// 打包反馈问题所需的信息：日志、最近的 adb 调用、脱敏后的设置、最近会话的设备信息与版本信息
+pub fn export_support_bundle(
+  diagnostics: &Diagnostics,
+  log_dir: Option<&Path>,
+  path: &Path,
+) -> Result<()> {
+  let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+  let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+  write_json(&mut zip, "diagnostics.json", diagnostics, options)?;
+  write_json(
+    &mut zip,
+    "adb-commands.json",
+    &diagnostics.recent_commands,
+    options,
+  )?;
+  write_json(&mut zip, "settings.json", &redacted_settings()?, options)?;
+  write_json(&mut zip, "sessions.json", &recent_sessions()?, options)?;
+
+  if let Some(dir) = log_dir.filter(|d| d.is_dir()) {
+    for entry in fs::read_dir(dir)? {
+      let entry = entry?;
+      if !entry.file_type()?.is_file() {
+        continue;
+      }
+      zip.start_file(
+        format!("logs/{}", entry.file_name().to_string_lossy()),
+        options,
+      )?;
+      zip.write_all(&fs::read(entry.path())?)?;
+    }
+  }
+
+  fs::write(path, zip.finish()?.into_inner())?;
+  Ok(())
+}
+
+/// 设置文件与数据库中的各项配置，机密字段替换为占位符
+fn redacted_settings() -> SessionResult<Value> {
+  let mut out = Map::new();
+  if let Ok(path) = settings_path() {
+    if let Ok(raw) = fs::read(&path) {
+      out.insert(
+        "settings_file".into(),
+        serde_json::from_slice(&raw).unwrap_or(Value::Null),
+      );
+    }
+  }
+  let conn = open()?;
+  let mut stmt = conn.prepare("SELECT key, value FROM app_kv ORDER BY key")?;
+  let rows = stmt.query_map([], |row| {
+    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+  })?;
+  let mut kv = Map::new();
+  for row in rows {
+    let (key, value) = row?;
+    kv.insert(
+      key,
+      serde_json::from_str(&value).unwrap_or(Value::String(value)),
+    );
+  }
+  out.insert("app_kv".into(), Value::Object(kv));
+
+  let mut value = Value::Object(out);
+  redact(&mut value);
+  Ok(value)
+}
+
+fn redact(value: &mut Value) {
+  match value {
+    Value::Object(map) => {
+      for (key, v) in map.iter_mut() {
+        let key = key.to_ascii_lowercase();
+        if SECRET_KEYS.iter().any(|s| key.contains(s)) {
+          redact_value(v);
+        } else if SECRET_MAPS.iter().any(|s| key.contains(s)) {
+          match v {
+            Value::Object(entries) => entries.values_mut().for_each(redact_value),
+            v => redact_value(v),
+          }
+        } else if let Value::String(s) = v {
+          *s = redact_url(s);
+        } else {
+          redact(v);
+        }
+      }
+    }
+    Value::Array(items) => items.iter_mut().for_each(redact),
+    _ => {}
+  }
+}
+
+fn redact_value(v: &mut Value) {
+  if !v.is_null() {
+    *v = Value::String(REDACTED.into());
+  }
+}
+
+/// 只保留地址的协议与主机：账号、路径与查询参数都可能带令牌
+/// （如 webhook 路径里的密钥、机器人的 access_token）
+fn redact_url(s: &str) -> String {
+  let Some((scheme, rest)) = s.split_once("://") else {
+    return s.to_string();
+  };
+  let authority_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+  let (authority, tail) = rest.split_at(authority_end);
+  let host = authority.rsplit('@').next().unwrap_or(authority);
+  if tail.trim_start_matches('/').is_empty() {
+    format!("{scheme}://{host}")
+  } else {
+    format!("{scheme}://{host}/{REDACTED}")
+  }
+}
+
+fn recent_sessions() -> SessionResult<Vec<RecentSession>> {
+  let conn = open()?;
+  let mut stmt = conn.prepare(
+    "SELECT id, device_id, device_model, app_package, start_time, duration
+     FROM reports ORDER BY id DESC LIMIT ?1",
+  )?;
+  let rows = stmt.query_map([RECENT_SESSIONS], |row| {
+    Ok((
+      row.get::<_, i64>(0)?,
+      row.get::<_, String>(1)?,
+      row.get::<_, Option<String>>(2)?,
+      row.get::<_, String>(3)?,
+      row.get::<_, i64>(4)?,
+      row.get::<_, i64>(5)?,
+    ))
+  })?;
+  let mut sessions = Vec::new();
+  for row in rows {
+    let (id, device_id, device_model, app_package, start_time, duration) = row?;
+    let meta = query_meta(&conn, id)?;
+    sessions.push(RecentSession {
+      id,
+      device_id,
+      device_model,
+      app_package,
+      start_time,
+      duration,
+      device: meta.device,
+      app_version_name: meta.app_version_name,
+    });
+  }
+  Ok(sessions)
+}
+
+fn write_json<W: Write + Seek, T: Serialize + ?Sized>(
+  zip: &mut ZipWriter<W>,
+  entry: &str,
+  value: &T,
+  options: SimpleFileOptions,
+) -> Result<()> {
+  zip.start_file(entry, options)?;
+  serde_json::to_writer_pretty(&mut *zip, value)?;
+  Ok(())
+}
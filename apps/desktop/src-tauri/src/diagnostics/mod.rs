@@ -0,0 +1,76 @@
+//! 诊断信息：汇总 adb 路径解析、控制服务状态、最近的 adb 调用与版本信息，
+//! 方便用户一键截图反馈问题
+
+pub mod bundle;
+
+use crate::{
+  adb::command::{adb_binary, current_adb_path, recent_commands, run_host, CommandRecord},
+  server::server_status,
+};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AdbDiagnostics {
+  /// 手动指定的路径
+  pub custom: Option<String>,
+  /// 随应用打包的路径
+  pub bundled: Option<String>,
+  /// 实际使用的路径
+  pub resolved: String,
+  /// `adb version` 的首行，执行失败时为错误信息
+  pub version: String,
+}
+
+/// 控制服务状态，不包含令牌，便于直接截图
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerDiagnostics {
+  pub port: u16,
+  pub allow_lan: bool,
+  pub grpc_port: Option<u16>,
+  pub prometheus: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostics {
+  pub app_version: String,
+  pub tauri_version: &'static str,
+  pub os: &'static str,
+  pub arch: &'static str,
+  pub config_dir: Option<String>,
+  pub log_dir: Option<String>,
+  pub adb: AdbDiagnostics,
+  /// 控制服务未运行时为空
+  pub server: Option<ServerDiagnostics>,
+  pub recent_commands: Vec<CommandRecord>,
+}
+
+pub fn collect(app: &AppHandle) -> Diagnostics {
+  let binary = adb_binary();
+  let version = match run_host(&["version"]) {
+    Ok(out) => out.lines().next().unwrap_or_default().trim().to_string(),
+    Err(e) => e.to_string(),
+  };
+  let path = app.path();
+  Diagnostics {
+    app_version: app.package_info().version.to_string(),
+    tauri_version: tauri::VERSION,
+    os: std::env::consts::OS,
+    arch: std::env::consts::ARCH,
+    config_dir: path.app_config_dir().ok().map(|p| p.display().to_string()),
+    log_dir: path.app_log_dir().ok().map(|p| p.display().to_string()),
+    adb: AdbDiagnostics {
+      custom: binary.as_ref().and_then(|b| b.custom.clone()),
+      bundled: binary.and_then(|b| b.bundled),
+      resolved: current_adb_path(),
+      version,
+    },
+    server: server_status().map(|s| ServerDiagnostics {
+      port: s.port,
+      allow_lan: s.allow_lan,
+      grpc_port: s.grpc_port,
+      prometheus: s.prometheus,
+    }),
+    recent_commands: recent_commands(),
+  }
+}
@@ -0,0 +1,107 @@
+use crate::{
+  adb::MetricsSnapshot,
+  session::{
+    anomaly::Anomaly,
+    events::SessionEvent,
+    recorder::{add_observer, RecordingObserver},
+    Sample,
+  },
+};
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
+
+pub const SAMPLE_EVENT: &str = "recording://sample";
+pub const EVENT_EVENT: &str = "recording://event";
+pub const ANOMALY_EVENT: &str = "recording://anomaly";
+pub const STOPPED_EVENT: &str = "recording://stopped";
+pub const BUGREPORT_PROGRESS_EVENT: &str = "capture://bugreport-progress";
+pub const REMOTE_FRAME_EVENT: &str = "remote://frame";
+pub const OVERLAY_EVENT: &str = "overlay://readout";
+pub const CHART_FRAME_EVENT: &str = "chart://frame";
+
+#[derive(Debug, Clone, Serialize)]
+struct SamplePayload<'a> {
+  session_id: i64,
+  sample: &'a Sample,
+  snapshot: &'a MetricsSnapshot,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct EventPayload<'a> {
+  session_id: i64,
+  event: &'a SessionEvent,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct AnomalyPayload<'a> {
+  session_id: i64,
+  anomaly: &'a Anomaly,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct BugreportProgress<'a> {
+  device_id: &'a str,
+  percent: u32,
+  message: &'a str,
+}
+
+/// 将后端录制事件转发给前端
+struct EventEmitter {
+  app: AppHandle,
+}
+
+impl RecordingObserver for EventEmitter {
+  fn on_sample(&self, session_id: i64, sample: &Sample, snapshot: &MetricsSnapshot) {
+    let _ = self.app.emit(
+      SAMPLE_EVENT,
+      SamplePayload {
+        session_id,
+        sample,
+        snapshot,
+      },
+    );
+  }
+
+  fn on_event(&self, session_id: i64, event: &SessionEvent) {
+    let _ = self
+      .app
+      .emit(EVENT_EVENT, EventPayload { session_id, event });
+  }
+
+  fn on_anomaly(&self, session_id: i64, anomaly: &Anomaly) {
+    let _ = self.app.emit(
+      ANOMALY_EVENT,
+      AnomalyPayload {
+        session_id,
+        anomaly,
+      },
+    );
+  }
+
+  fn on_stopped(&self, session_id: i64) {
+    let _ = self.app.emit(STOPPED_EVENT, session_id);
+  }
+}
+
+pub fn register(app: &AppHandle) {
+  add_observer(Arc::new(EventEmitter { app: app.clone() }));
+}
+
+pub fn emit_bugreport_progress(app: &AppHandle, device_id: &str, percent: u32, message: &str) {
+  let _ = app.emit(
+    BUGREPORT_PROGRESS_EVENT,
+    BugreportProgress {
+      device_id,
+      percent,
+      message,
+    },
+  );
+}
+
+/// 转发远端实例的实时帧，帧格式与 `/api/live` 一致
+pub fn emit_remote_frame(app: &AppHandle, frame: &str) {
+  if let Ok(frame) = serde_json::from_str::<serde_json::Value>(frame) {
+    let _ = app.emit(REMOTE_FRAME_EVENT, frame);
+  }
+}
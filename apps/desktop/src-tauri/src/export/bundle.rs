@@ -0,0 +1,103 @@
+use crate::{
+  export::error::Result,
+  session::{
+    bundle::{
+      attachment_entry, screenshot_entry, BundleManifest, ATTACHMENTS_ENTRY, BUNDLE_FORMAT,
+      BUNDLE_VERSION, EVENTS_ENTRY, MANIFEST_ENTRY, RAW_ENTRY, SCREENSHOTS_ENTRY, SESSION_ENTRY,
+    },
+    crypto::encrypt,
+    events::{list_events, load_attachment, query_attachments},
+    raw::load_raw,
+    screenshot::{list_screenshots, load_screenshot},
+    store::open,
+    unix_now, SessionData,
+  },
+};
+use serde::Serialize;
+use std::{
+  ffi::OsString,
+  fs::{self, File},
+  io::{BufReader, BufWriter, Seek, Write},
+  path::{Path, PathBuf},
+};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+/// 导出 .perfx 会话包，包含采样、标记、元信息、事件及其附件、截图与原始输出
+///
+/// 提供口令时整个包以 age 加密，导入时需输入同一口令。
+/// 包直接写入目标旁的临时文件，完成后改名或加密到目标，失败时不留下不完整的文件。
+pub fn export_bundle(data: &SessionData, path: &Path, passphrase: Option<&str>) -> Result<()> {
+  let tmp = temp_path(path);
+  let result = write_zip(data, &tmp).and_then(|_| match passphrase.filter(|p| !p.is_empty()) {
+    Some(passphrase) => {
+      let plain = BufReader::new(File::open(&tmp)?);
+      let encrypted = encrypt(plain, BufWriter::new(File::create(path)?), passphrase);
+      if encrypted.is_err() {
+        let _ = fs::remove_file(path);
+      }
+      Ok(encrypted?)
+    }
+    None => Ok(fs::rename(&tmp, path)?),
+  });
+  let _ = fs::remove_file(&tmp);
+  result
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+  let mut name = OsString::from(".");
+  name.push(path.file_name().unwrap_or_default());
+  name.push(".tmp");
+  path.with_file_name(name)
+}
+
+fn write_zip(data: &SessionData, path: &Path) -> Result<()> {
+  let session_id = data.session.id;
+  let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+  // JPEG 与 zstd 数据本身已压缩，直接存储
+  let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+  let mut zip = ZipWriter::new(BufWriter::new(File::create(path)?));
+  let manifest = BundleManifest {
+    format: BUNDLE_FORMAT.to_string(),
+    version: BUNDLE_VERSION,
+    exported_at: unix_now(),
+    generator: format!("PerfX {}", env!("CARGO_PKG_VERSION")),
+    schema_version: data.session.schema_version,
+  };
+  write_json(&mut zip, MANIFEST_ENTRY, &manifest, deflated)?;
+  write_json(&mut zip, SESSION_ENTRY, data, deflated)?;
+
+  write_json(&mut zip, EVENTS_ENTRY, &list_events(session_id)?, deflated)?;
+  let attachments = query_attachments(&open()?, session_id)?;
+  write_json(&mut zip, ATTACHMENTS_ENTRY, &attachments, deflated)?;
+  for info in &attachments {
+    zip.start_file(attachment_entry(info), deflated)?;
+    zip.write_all(&load_attachment(info.id)?)?;
+  }
+
+  let screenshots = list_screenshots(session_id)?;
+  write_json(&mut zip, SCREENSHOTS_ENTRY, &screenshots, deflated)?;
+  for info in &screenshots {
+    zip.start_file(screenshot_entry(info), stored)?;
+    zip.write_all(&load_screenshot(info.id)?)?;
+  }
+
+  let raw = load_raw(session_id, 0, i64::MAX as u64, None)?;
+  if !raw.is_empty() {
+    write_json(&mut zip, RAW_ENTRY, &raw, deflated)?;
+  }
+
+  zip.finish()?.flush()?;
+  Ok(())
+}
+
+fn write_json<W: Write + Seek, T: Serialize + ?Sized>(
+  zip: &mut ZipWriter<W>,
+  entry: &str,
+  value: &T,
+  options: SimpleFileOptions,
+) -> Result<()> {
+  zip.start_file(entry, options)?;
+  serde_json::to_writer(&mut *zip, value)?;
+  Ok(())
+}
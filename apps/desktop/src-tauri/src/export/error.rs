@@ -0,0 +1,53 @@
+use crate::{
+  analysis::error::AnalysisError,
+  i18n::{detail, Localized},
+  session::error::SessionError,
+};
+use serde_json::Value;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+  #[error(transparent)]
+  Session(#[from] SessionError),
+  #[error(transparent)]
+  Analysis(#[from] AnalysisError),
+  #[error("文件读写失败: {0}")]
+  Io(#[from] std::io::Error),
+  #[error("JSON 序列化失败: {0}")]
+  Json(#[from] serde_json::Error),
+  #[error("Excel 生成失败: {0}")]
+  Xlsx(#[from] rust_xlsxwriter::XlsxError),
+  #[error("会话包生成失败: {0}")]
+  Zip(#[from] zip::result::ZipError),
+  #[error("没有数据可导出")]
+  Empty,
+}
+
+pub type Result<T> = std::result::Result<T, ExportError>;
+
+impl Localized for ExportError {
+  fn code(&self) -> &'static str {
+    match self {
+      Self::Session(e) => e.code(),
+      Self::Analysis(e) => e.code(),
+      Self::Io(_) => "IO",
+      Self::Json(_) => "JSON",
+      Self::Xlsx(_) => "EXPORT_XLSX",
+      Self::Zip(_) => "EXPORT_BUNDLE",
+      Self::Empty => "EXPORT_EMPTY",
+    }
+  }
+
+  fn params(&self) -> Value {
+    match self {
+      Self::Session(e) => e.params(),
+      Self::Analysis(e) => e.params(),
+      Self::Io(e) => detail(e),
+      Self::Json(e) => detail(e),
+      Self::Xlsx(e) => detail(e),
+      Self::Zip(e) => detail(e),
+      Self::Empty => Value::Null,
+    }
+  }
+}
@@ -0,0 +1,9 @@
+use crate::{export::error::Result, session::SessionData};
+use std::{fs::File, io::BufWriter, path::Path};
+
+/// 导出完整会话（会话信息 + 全部采样），可通过会话导入原样读回
+pub fn export_json(data: &SessionData, path: &Path) -> Result<()> {
+  let writer = BufWriter::new(File::create(path)?);
+  serde_json::to_writer_pretty(writer, data)?;
+  Ok(())
+}
@@ -0,0 +1,207 @@
+use crate::{
+  analysis::{
+    error::AnalysisError,
+    gate::{gate_session, load_config, CheckStatus, GateReport},
+  },
+  export::error::Result,
+  session::{
+    alerts::{list_rules, AlertRule},
+    events::{list_events, EventKind, SessionEvent},
+    load_session, SessionData,
+  },
+};
+use std::{fmt::Write as _, fs, path::Path};
+
+/// 导出 JUnit XML：回归门禁的每项检查、每条启用的告警规则各为一个测试用例，
+/// 门禁未通过（含缺少数据）或录制中触发告警即记为失败，供 Jenkins / GitLab 等直接展示
+pub fn export_junit(
+  data: &SessionData,
+  gate: Option<&GateReport>,
+  rules: &[AlertRule],
+  events: &[SessionEvent],
+  path: &Path,
+) -> Result<()> {
+  fs::write(path, junit_xml(data, gate, rules, events))?;
+  Ok(())
+}
+
+/// 载入会话、门禁结果（未指定基线时使用同组合的基线，没有基线则只输出告警规则用例）
+/// 与告警规则后导出
+pub fn export_session_junit(
+  session_id: i64,
+  baseline_session_id: Option<i64>,
+  path: &Path,
+) -> Result<()> {
+  let data = load_session(session_id)?;
+  let gate = match gate_session(session_id, baseline_session_id, &load_config()?) {
+    Ok(report) => Some(report),
+    Err(AnalysisError::NoBaseline(_)) => None,
+    Err(e) => return Err(e.into()),
+  };
+  let rules = list_rules()?;
+  let events = list_events(session_id)?;
+  export_junit(&data, gate.as_ref(), &rules, &events, path)
+}
+
+struct TestCase {
+  name: String,
+  /// 失败原因与详情
+  failure: Option<(String, String)>,
+}
+
+pub fn junit_xml(
+  data: &SessionData,
+  gate: Option<&GateReport>,
+  rules: &[AlertRule],
+  events: &[SessionEvent],
+) -> String {
+  let session = &data.session;
+  let class = format!("perfx.{}", session.app_package);
+  let mut suites = Vec::new();
+
+  if let Some(report) = gate {
+    let cases: Vec<TestCase> = report
+      .checks
+      .iter()
+      .map(|check| {
+        let stat = serde_json::to_value(check.stat)
+          .ok()
+          .and_then(|v| v.as_str().map(str::to_string))
+          .unwrap_or_default();
+        let detail = format!(
+          "本次: {}\n基线: {}（会话 {}）\n变化: {}\n容差: {}%",
+          fmt_value(check.current),
+          fmt_value(check.baseline),
+          report.baseline_session_id,
+          check
+            .delta_pct
+            .map_or_else(|| "-".into(), |d| format!("{d:+.1}%")),
+          check.tolerance_pct
+        );
+        let reason = check.reason.clone().unwrap_or_default();
+        // 门禁把缺少数据计为未通过，这里同样记为失败
+        let failed = matches!(check.status, CheckStatus::Fail | CheckStatus::Missing);
+        TestCase {
+          name: format!("{} ({stat})", check.metric),
+          failure: failed.then_some((reason, detail)),
+        }
+      })
+      .collect();
+    suites.push(("回归门禁", "regression", cases));
+  }
+
+  let alerts: Vec<&SessionEvent> = events
+    .iter()
+    .filter(|e| e.kind == EventKind::Alert)
+    .collect();
+  let cases: Vec<TestCase> = rules
+    .iter()
+    .filter(|r| r.enabled)
+    .map(|rule| {
+      // 按事件记录的规则 id 归类；旧版本的事件没有规则 id，按消息前缀 “规则描述，” 匹配
+      let description = rule.describe();
+      let prefix = format!("{description}，");
+      let hits: Vec<&&SessionEvent> = alerts
+        .iter()
+        .filter(|e| match e.rule_id {
+          Some(id) => id == rule.id,
+          None => e.message.starts_with(&prefix),
+        })
+        .collect();
+      let failure = (!hits.is_empty()).then(|| {
+        let detail = hits
+          .iter()
+          .map(|e| format!("{:.1}s {}", e.t_ms as f64 / 1000.0, e.message))
+          .collect::<Vec<_>>()
+          .join("\n");
+        (format!("触发 {} 次", hits.len()), detail)
+      });
+      TestCase {
+        name: description,
+        failure,
+      }
+    })
+    .collect();
+  suites.push(("告警规则", "alert", cases));
+
+  let total: usize = suites.iter().map(|(_, _, c)| c.len()).sum();
+  let failed: usize = suites
+    .iter()
+    .map(|(_, _, c)| c.iter().filter(|t| t.failure.is_some()).count())
+    .sum();
+  let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+  let _ = writeln!(
+    xml,
+    "<testsuites name=\"{}\" tests=\"{total}\" failures=\"{failed}\" time=\"{}\">",
+    escape(&session.name),
+    session.duration
+  );
+  for (name, kind, cases) in &suites {
+    let failures = cases.iter().filter(|t| t.failure.is_some()).count();
+    let _ = writeln!(
+      xml,
+      "  <testsuite name=\"{name}\" tests=\"{}\" failures=\"{failures}\">",
+      cases.len()
+    );
+    xml.push_str("    <properties>\n");
+    for (key, value) in [
+      ("session_id", session.id.to_string()),
+      ("device", session.device_id.clone()),
+      (
+        "device_model",
+        session.device_model.clone().unwrap_or_default(),
+      ),
+      ("package", session.app_package.clone()),
+    ] {
+      let _ = writeln!(
+        xml,
+        "      <property name=\"{key}\" value=\"{}\"/>",
+        escape(&value)
+      );
+    }
+    xml.push_str("    </properties>\n");
+    for case in cases {
+      let _ = write!(
+        xml,
+        "    <testcase classname=\"{}.{kind}\" name=\"{}\" time=\"0\"",
+        escape(&class),
+        escape(&case.name)
+      );
+      match &case.failure {
+        Some((message, detail)) => {
+          let _ = writeln!(
+            xml,
+            ">\n      <failure message=\"{}\" type=\"{kind}\">{}</failure>\n    </testcase>",
+            escape(message),
+            escape(detail)
+          );
+        }
+        None => xml.push_str("/>\n"),
+      }
+    }
+    xml.push_str("  </testsuite>\n");
+  }
+  xml.push_str("</testsuites>\n");
+  xml
+}
+
+fn fmt_value(value: Option<f64>) -> String {
+  value.map_or_else(|| "-".into(), |v| format!("{v:.2}"))
+}
+
+fn escape(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => out.push_str("&amp;"),
+      '<' => out.push_str("&lt;"),
+      '>' => out.push_str("&gt;"),
+      '"' => out.push_str("&quot;"),
+      '\'' => out.push_str("&apos;"),
+      // XML 1.0 不允许的控制字符
+      c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+      c => out.push(c),
+    }
+  }
+  out
+}
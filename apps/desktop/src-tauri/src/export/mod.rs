@@ -0,0 +1,10 @@
+pub mod bundle;
+pub mod error;
+pub mod json;
+pub mod junit;
+pub mod xlsx;
+
+pub use bundle::export_bundle;
+pub use json::export_json;
+pub use junit::export_session_junit;
+pub use xlsx::export_xlsx;
@@ -0,0 +1,250 @@
+use crate::{
+  analysis::{drain::estimate_drain, summarize_in, summary_for},
+  export::error::{ExportError, Result},
+  session::{metric_label_in, SessionData},
+  settings::report_units,
+};
+use perfx_core::units::Units;
+use rust_xlsxwriter::{Format, Workbook, Worksheet};
+use std::{collections::HashSet, path::Path};
+
+/// 导出 Excel：首个 Sheet 为汇总（会话信息 + 各指标统计），其后每个指标一个 Sheet；
+/// 数值按设置中的展示单位换算
+pub fn export_xlsx(data: &SessionData, path: &Path) -> Result<()> {
+  if data.samples.is_empty() {
+    return Err(ExportError::Empty);
+  }
+
+  let bold = Format::new().set_bold();
+  let number = Format::new().set_num_format("0.00");
+  let mut workbook = Workbook::new();
+
+  let units = report_units();
+  let summary = workbook.add_worksheet();
+  summary.set_name("汇总")?;
+  write_summary(summary, data, &units, &bold, &number)?;
+
+  let samples = data.samples_in(&units);
+  let mut used = HashSet::from(["汇总".to_string()]);
+  for metric in data.metric_names() {
+    let (label, unit) = metric_label_in(&metric, &units);
+    let sheet = workbook.add_worksheet();
+    sheet.set_name(sheet_name(label, &mut used))?;
+
+    let value_header = if unit.is_empty() {
+      label.to_string()
+    } else {
+      format!("{label} ({unit})")
+    };
+    for (col, header) in ["序号", "相对时间 (s)", "时刻", value_header.as_str()]
+      .iter()
+      .enumerate()
+    {
+      sheet.write_string_with_format(0, col as u16, *header, &bold)?;
+    }
+
+    let mut row = 1u32;
+    for sample in &samples {
+      let Some(value) = sample.values.get(&metric) else {
+        continue;
+      };
+      sheet.write_number(row, 0, row as f64)?;
+      sheet.write_number_with_format(row, 1, sample.t_ms as f64 / 1000.0, &number)?;
+      if let Some(time) = &sample.time {
+        sheet.write_string(row, 2, time)?;
+      }
+      sheet.write_number_with_format(row, 3, *value, &number)?;
+      row += 1;
+    }
+    sheet.set_column_width(1, 14)?;
+    sheet.set_column_width(2, 12)?;
+    sheet.set_column_width(3, 16)?;
+  }
+
+  workbook.save(path)?;
+  Ok(())
+}
+
+fn write_summary(
+  sheet: &mut Worksheet,
+  data: &SessionData,
+  units: &Units,
+  bold: &Format,
+  number: &Format,
+) -> Result<()> {
+  let session = &data.session;
+  let meta = &data.meta;
+  let device = meta.device.as_ref();
+  let mut info: Vec<(&str, String)> = vec![
+    ("报告名称", session.name.clone()),
+    (
+      "设备",
+      session
+        .device_model
+        .clone()
+        .unwrap_or_else(|| session.device_id.clone()),
+    ),
+    ("设备序列号", session.device_id.clone()),
+    (
+      "应用",
+      session
+        .app_label
+        .clone()
+        .unwrap_or_else(|| session.app_package.clone()),
+    ),
+    ("包名", session.app_package.clone()),
+    ("时长 (s)", session.duration.to_string()),
+    ("数据格式版本", session.schema_version.to_string()),
+  ];
+  let optional: [(&str, Option<String>); 11] = [
+    (
+      "场景",
+      Some(meta.scenario.clone()).filter(|s| !s.is_empty()),
+    ),
+    ("应用版本", meta.app_version_name.clone()),
+    ("版本号", meta.app_version_code.map(|c| c.to_string())),
+    ("厂商", device.and_then(|d| d.manufacturer.clone())),
+    (
+      "Android 版本",
+      device.and_then(|d| d.android_release.clone()),
+    ),
+    ("ROM 指纹", meta.build_fingerprint.clone()),
+    ("测试人员", meta.tester.clone()),
+    ("备注", meta.notes.clone()),
+    ("CPU 锁定", meta.cpu_lock.as_ref().map(|c| c.lock.label())),
+    ("显示参数", meta.display.as_ref().map(|d| d.label())),
+    ("游戏模式", meta.game_mode.as_ref().map(|g| g.label())),
+  ];
+  info.extend(optional.into_iter().filter_map(|(k, v)| v.map(|v| (k, v))));
+  // 摘要依赖数据库中的基线与事件，读取失败时省略
+  if let Ok(summary) = summary_for(data) {
+    info.push(("摘要", summary.narrative));
+  }
+
+  for (row, (key, value)) in info.iter().enumerate() {
+    sheet.write_string_with_format(row as u32, 0, *key, bold)?;
+    sheet.write_string(row as u32, 1, value)?;
+  }
+
+  let header_row = info.len() as u32 + 1;
+  let headers = [
+    "指标",
+    "单位",
+    "样本数",
+    "最小值",
+    "平均值",
+    "最大值",
+    "P50",
+    "P90",
+    "P95",
+    "P99",
+  ];
+  for (col, header) in headers.iter().enumerate() {
+    sheet.write_string_with_format(header_row, col as u16, *header, bold)?;
+  }
+
+  let stats = summarize_in(data, units);
+  for (index, stats) in stats.iter().enumerate() {
+    let row = header_row + 1 + index as u32;
+    let (label, unit) = metric_label_in(&stats.metric, units);
+    sheet.write_string(row, 0, label)?;
+    sheet.write_string(row, 1, unit)?;
+    sheet.write_number(row, 2, stats.count as f64)?;
+    let values = [
+      stats.min, stats.avg, stats.max, stats.p50, stats.p90, stats.p95, stats.p99,
+    ];
+    for (offset, value) in values.iter().enumerate() {
+      sheet.write_number_with_format(row, 3 + offset as u16, *value, number)?;
+    }
+  }
+
+  if let Some(drain) = estimate_drain(data) {
+    let mut row = header_row + stats.len() as u32 + 2;
+    let voltage_note = if drain.voltage_estimated {
+      "能耗 (mWh，按标称电压)"
+    } else {
+      "能耗 (mWh)"
+    };
+    for (col, header) in [
+      "分段",
+      "耗电量 (mAh)",
+      voltage_note,
+      "平均电流 (mA)",
+      "平均功率 (mW)",
+    ]
+    .iter()
+    .enumerate()
+    {
+      sheet.write_string_with_format(row, col as u16, *header, bold)?;
+    }
+    let segments = std::iter::once(("整场", &drain.total)).chain(drain.segments.iter().map(|s| {
+      let name = if s.name.is_empty() {
+        "开始"
+      } else {
+        s.name.as_str()
+      };
+      (name, &s.drain)
+    }));
+    for (name, stats) in segments {
+      row += 1;
+      sheet.write_string(row, 0, name)?;
+      let values = [
+        stats.charge_mah,
+        stats.energy_mwh,
+        stats.avg_current_ma,
+        stats.avg_power_mw,
+      ];
+      for (offset, value) in values.iter().enumerate() {
+        sheet.write_number_with_format(row, 1 + offset as u16, *value, number)?;
+      }
+    }
+  }
+
+  sheet.set_column_width(0, 14)?;
+  sheet.set_column_width(1, 24)?;
+  Ok(())
+}
+
+// Excel 的 Sheet 名最长 31 个字符、不能为空、不能包含 []:*?/\ 且不区分大小写地唯一；
+// 截断后重名时追加序号
+fn sheet_name(label: &str, used: &mut HashSet<String>) -> String {
+  let base: String = label
+    .chars()
+    .filter(|c| !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+    .collect();
+  let base = match base.trim() {
+    "" => "指标".to_string(),
+    trimmed => trimmed.to_string(),
+  };
+  let mut name: String = base.chars().take(31).collect();
+  let mut n = 2;
+  while !used.insert(name.to_lowercase()) {
+    let suffix = format!(" ({n})");
+    name = base.chars().take(31 - suffix.chars().count()).collect();
+    name.push_str(&suffix);
+    n += 1;
+  }
+  name
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sheet_names_are_unique_after_truncation() {
+    let mut used = HashSet::from(["汇总".to_string()]);
+    let long = "a".repeat(40);
+    assert_eq!(sheet_name(&long, &mut used), "a".repeat(31));
+    assert_eq!(
+      sheet_name(&long, &mut used),
+      format!("{} (2)", "a".repeat(27))
+    );
+    assert_eq!(
+      sheet_name("A".repeat(35).as_str(), &mut used),
+      format!("{} (3)", "A".repeat(27))
+    );
+    assert_eq!(sheet_name("[]", &mut used), "指标");
+    assert_eq!(sheet_name("汇总", &mut used), "汇总 (2)");
+  }
+}
@@ -0,0 +1,51 @@
+use crate::{
+  adb::error::AdbError,
+  i18n::{detail, Localized},
+  session::error::SessionError,
+};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum FarmError {
+  #[error(transparent)]
+  Session(#[from] SessionError),
+  #[error(transparent)]
+  Adb(#[from] AdbError),
+  #[error("尚未配置设备农场")]
+  NotConfigured,
+  #[error("设备农场配置无效: {0}")]
+  Config(String),
+  #[error("设备农场请求失败: {0}")]
+  Api(String),
+  #[error("远程 adb 连接失败: {0}")]
+  Connect(String),
+  #[error("设备未被占用: {0}")]
+  NotReserved(String),
+}
+
+pub type Result<T> = std::result::Result<T, FarmError>;
+
+impl Localized for FarmError {
+  fn code(&self) -> &'static str {
+    match self {
+      Self::Session(e) => e.code(),
+      Self::Adb(e) => e.code(),
+      Self::NotConfigured => "FARM_NOT_CONFIGURED",
+      Self::Config(_) => "FARM_CONFIG_INVALID",
+      Self::Api(_) => "FARM_API",
+      Self::Connect(_) => "FARM_CONNECT",
+      Self::NotReserved(_) => "FARM_NOT_RESERVED",
+    }
+  }
+
+  fn params(&self) -> Value {
+    match self {
+      Self::Session(e) => e.params(),
+      Self::Adb(e) => e.params(),
+      Self::NotConfigured => Value::Null,
+      Self::Config(d) | Self::Api(d) | Self::Connect(d) => detail(d),
+      Self::NotReserved(serial) => json!({ "serial": serial }),
+    }
+  }
+}
@@ -0,0 +1,271 @@
+//! OpenSTF / DeviceHub 设备农场：占用远程设备并通过 `adb connect` 接入，
+//! 之后与本地设备一样出现在设备列表中，录制结束或手动释放时归还
+
+use crate::{
+  adb::command::run_host,
+  farm::error::{FarmError, Result},
+  session::{
+    load_session,
+    recorder::{add_observer, list_recordings, RecordingObserver},
+    sink::batch::agent,
+    store::{get_kv, set_kv},
+  },
+};
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+  thread,
+};
+
+pub mod error;
+
+const CONFIG_KEY: &str = "device_farm";
+
+/// 农场地址与访问令牌（在 STF 的 Settings → Keys 中生成）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FarmConfig {
+  /// 如 `https://stf.example.com`，不含 `/api/v1`
+  pub url: String,
+  pub token: String,
+  /// 占用超时（毫秒），超时后由农场自动回收；不指定时使用服务端默认值
+  #[serde(default)]
+  pub timeout_ms: Option<u64>,
+}
+
+impl FarmConfig {
+  fn validate(&self) -> Result<()> {
+    if !self.url.starts_with("http://") && !self.url.starts_with("https://") {
+      return Err(FarmError::Config(format!(
+        "地址须以 http:// 或 https:// 开头: {}",
+        self.url
+      )));
+    }
+    if self.token.trim().is_empty() {
+      return Err(FarmError::Config("访问令牌为空".into()));
+    }
+    Ok(())
+  }
+
+  fn endpoint(&self, path: &str) -> String {
+    format!("{}/api/v1{}", self.url.trim_end_matches('/'), path)
+  }
+
+  fn request(&self, method: &str, path: &str, body: Option<Value>) -> Result<Value> {
+    let request = agent()
+      .request(method, &self.endpoint(path))
+      .set("Authorization", &format!("Bearer {}", self.token));
+    let response = match body {
+      Some(body) => request.send_json(body),
+      None => request.call(),
+    };
+    match response {
+      Ok(response) => response
+        .into_json()
+        .map_err(|e| FarmError::Api(e.to_string())),
+      // 出错时 STF 在响应体的 description 中给出原因
+      Err(ureq::Error::Status(code, response)) => {
+        let description = response
+          .into_json::<Value>()
+          .ok()
+          .and_then(|v| v["description"].as_str().map(str::to_string))
+          .unwrap_or_default();
+        Err(FarmError::Api(format!(
+          "{method} {path} 返回 {code} {description}"
+        )))
+      }
+      Err(e) => Err(FarmError::Api(e.to_string())),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FarmDevice {
+  pub serial: String,
+  #[serde(default)]
+  pub manufacturer: Option<String>,
+  #[serde(default)]
+  pub model: Option<String>,
+  #[serde(default)]
+  pub sdk: Option<String>,
+  /// 已接入农场
+  #[serde(default)]
+  pub present: bool,
+  /// 可被占用
+  #[serde(default)]
+  pub ready: bool,
+  /// 当前用户已占用
+  #[serde(default)]
+  pub using: bool,
+  /// 占用者邮箱，空闲时为空
+  #[serde(default, deserialize_with = "owner_email")]
+  pub owner: Option<String>,
+}
+
+fn owner_email<'de, D: serde::Deserializer<'de>>(
+  deserializer: D,
+) -> std::result::Result<Option<String>, D::Error> {
+  let owner = Option::<Value>::deserialize(deserializer)?;
+  Ok(owner.and_then(|o| o["email"].as_str().map(str::to_string)))
+}
+
+/// 本机占用中的农场设备，`adb_id` 即 `adb connect` 的地址，也是设备列表中的 ID
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reservation {
+  pub serial: String,
+  pub adb_id: String,
+  pub model: Option<String>,
+  /// 在该设备上的录制结束后自动释放
+  pub release_on_stop: bool,
+}
+
+static RESERVATIONS: Lazy<Mutex<HashMap<String, Reservation>>> =
+  Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn get_farm_config() -> Result<Option<FarmConfig>> {
+  Ok(get_kv(CONFIG_KEY)?)
+}
+
+pub fn set_farm_config(config: &FarmConfig) -> Result<()> {
+  config.validate()?;
+  Ok(set_kv(CONFIG_KEY, config)?)
+}
+
+fn config() -> Result<FarmConfig> {
+  get_farm_config()?.ok_or(FarmError::NotConfigured)
+}
+
+/// 列出农场中的全部设备
+pub fn list_farm_devices() -> Result<Vec<FarmDevice>> {
+  let config = config()?;
+  let response = config.request(
+    "GET",
+    "/devices?fields=serial,manufacturer,model,sdk,present,ready,using,owner",
+    None,
+  )?;
+  serde_json::from_value(response["devices"].clone()).map_err(|e| FarmError::Api(e.to_string()))
+}
+
+pub fn list_reservations() -> Vec<Reservation> {
+  RESERVATIONS
+    .lock()
+    .map(|r| r.values().cloned().collect())
+    .unwrap_or_default()
+}
+
+/// 占用设备、开启远程调试并 `adb connect`，任一步失败都会归还设备
+pub fn reserve_device(serial: &str, release_on_stop: bool) -> Result<Reservation> {
+  let config = config()?;
+  let mut body = json!({ "serial": serial });
+  if let Some(timeout) = config.timeout_ms {
+    body["timeout"] = json!(timeout);
+  }
+  config.request("POST", "/user/devices", Some(body))?;
+
+  let connected = config
+    .request(
+      "POST",
+      &format!("/user/devices/{serial}/remoteConnect"),
+      None,
+    )
+    .and_then(|response| {
+      let url = response["remoteConnectUrl"]
+        .as_str()
+        .ok_or_else(|| FarmError::Api("响应中缺少 remoteConnectUrl".into()))?
+        .to_string();
+      adb_connect(&url)?;
+      Ok(url)
+    });
+  let adb_id = match connected {
+    Ok(url) => url,
+    Err(e) => {
+      let _ = config.request("DELETE", &format!("/user/devices/{serial}"), None);
+      return Err(e);
+    }
+  };
+
+  let model = config
+    .request("GET", &format!("/devices/{serial}?fields=model"), None)
+    .ok()
+    .and_then(|v| v["device"]["model"].as_str().map(str::to_string));
+  let reservation = Reservation {
+    serial: serial.to_string(),
+    adb_id: adb_id.clone(),
+    model,
+    release_on_stop,
+  };
+  if let Ok(mut reservations) = RESERVATIONS.lock() {
+    reservations.insert(adb_id.clone(), reservation.clone());
+  }
+  info!("已占用农场设备 {}，adb 地址 {}", serial, adb_id);
+  Ok(reservation)
+}
+
+/// `adb connect` 失败时退出码仍为 0，需要检查输出
+fn adb_connect(address: &str) -> Result<()> {
+  let output = run_host(&["connect", address])?;
+  if output.contains("connected to") {
+    Ok(())
+  } else {
+    Err(FarmError::Connect(output.trim().to_string()))
+  }
+}
+
+/// 断开远程 adb 并归还设备
+pub fn release_device(adb_id: &str) -> Result<()> {
+  let reservation = RESERVATIONS
+    .lock()
+    .ok()
+    .and_then(|mut r| r.remove(adb_id))
+    .ok_or_else(|| FarmError::NotReserved(adb_id.to_string()))?;
+  if let Err(e) = run_host(&["disconnect", adb_id]) {
+    warn!("断开 {} 失败: {}", adb_id, e);
+  }
+  let config = config()?;
+  let serial = &reservation.serial;
+  let _ = config.request(
+    "DELETE",
+    &format!("/user/devices/{serial}/remoteConnect"),
+    None,
+  );
+  config.request("DELETE", &format!("/user/devices/{serial}"), None)?;
+  info!("已释放农场设备 {}", serial);
+  Ok(())
+}
+
+/// 录制结束后，若设备设置了自动释放且没有其他录制在用，则归还
+fn release_after(session_id: i64) {
+  let device_id = match load_session(session_id) {
+    Ok(data) => data.session.device_id,
+    Err(e) => {
+      warn!("读取会话 {} 失败: {}", session_id, e);
+      return;
+    }
+  };
+  let auto = list_reservations()
+    .iter()
+    .any(|r| r.adb_id == device_id && r.release_on_stop);
+  let in_use = list_recordings().iter().any(|r| r.device_id == device_id);
+  if auto && !in_use {
+    if let Err(e) = release_device(&device_id) {
+      warn!("释放农场设备 {} 失败: {}", device_id, e);
+    }
+  }
+}
+
+struct FarmReleaser;
+
+impl RecordingObserver for FarmReleaser {
+  fn on_stopped(&self, session_id: i64) {
+    if !list_reservations().is_empty() {
+      thread::spawn(move || release_after(session_id));
+    }
+  }
+}
+
+pub fn register() {
+  add_observer(Arc::new(FarmReleaser));
+}
@@ -0,0 +1,204 @@
+//! 全局快捷键：插入标记、开始 / 停止录制与截图，应用不在前台时同样生效
+
+use crate::{
+  i18n::{detail, Localized},
+  session::{
+    error::SessionError,
+    recorder::{
+      capture_recording_screenshot, last_recording_config, latest_recording, mark_recording,
+      start_recording, stop_recording,
+    },
+    store::{get_kv, set_kv},
+  },
+};
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{collections::HashMap, sync::Mutex, thread};
+use tauri::{AppHandle, Runtime};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use thiserror::Error;
+
+const CONFIG_KEY: &str = "hotkeys";
+
+#[derive(Debug, Error)]
+pub enum HotkeyError {
+  #[error(transparent)]
+  Session(#[from] SessionError),
+  #[error("快捷键格式无效: {0}")]
+  Invalid(String),
+  #[error("快捷键 {0} 重复")]
+  Duplicate(String),
+  #[error("注册快捷键失败: {0}")]
+  Register(String),
+}
+
+pub type Result<T> = std::result::Result<T, HotkeyError>;
+
+impl Localized for HotkeyError {
+  fn code(&self) -> &'static str {
+    match self {
+      Self::Session(e) => e.code(),
+      Self::Invalid(_) => "HOTKEY_INVALID",
+      Self::Duplicate(_) => "HOTKEY_DUPLICATE",
+      Self::Register(_) => "HOTKEY_REGISTER",
+    }
+  }
+
+  fn params(&self) -> Value {
+    match self {
+      Self::Session(e) => e.params(),
+      Self::Invalid(shortcut) | Self::Duplicate(shortcut) => json!({ "shortcut": shortcut }),
+      Self::Register(d) => detail(d),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+  Marker,
+  ToggleRecording,
+  Screenshot,
+}
+
+/// 快捷键写法如 `CmdOrCtrl+Shift+M`，为空表示不注册
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotkeyConfig {
+  #[serde(default)]
+  pub marker: Option<String>,
+  #[serde(default)]
+  pub toggle_recording: Option<String>,
+  #[serde(default)]
+  pub screenshot: Option<String>,
+}
+
+impl Default for HotkeyConfig {
+  fn default() -> Self {
+    Self {
+      marker: Some("CmdOrCtrl+Shift+M".into()),
+      toggle_recording: Some("CmdOrCtrl+Shift+R".into()),
+      screenshot: Some("CmdOrCtrl+Shift+S".into()),
+    }
+  }
+}
+
+impl HotkeyConfig {
+  /// 解析全部快捷键，同一组合不能绑定两个操作
+  fn bindings(&self) -> Result<Vec<(Shortcut, Action)>> {
+    let mut bindings: Vec<(Shortcut, Action)> = Vec::new();
+    for (keys, action) in [
+      (&self.marker, Action::Marker),
+      (&self.toggle_recording, Action::ToggleRecording),
+      (&self.screenshot, Action::Screenshot),
+    ] {
+      let Some(keys) = keys.as_deref().map(str::trim).filter(|k| !k.is_empty()) else {
+        continue;
+      };
+      let shortcut: Shortcut = keys
+        .parse()
+        .map_err(|_| HotkeyError::Invalid(keys.to_string()))?;
+      if bindings.iter().any(|(s, _)| s.id() == shortcut.id()) {
+        return Err(HotkeyError::Duplicate(keys.to_string()));
+      }
+      bindings.push((shortcut, action));
+    }
+    Ok(bindings)
+  }
+}
+
+/// 已注册快捷键到操作的映射
+static BINDINGS: Lazy<Mutex<HashMap<u32, Action>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn get_hotkeys() -> Result<HotkeyConfig> {
+  Ok(get_kv(CONFIG_KEY)?.unwrap_or_default())
+}
+
+/// 保存并立即重新注册，新配置无效时保留原有快捷键
+pub fn set_hotkeys<R: Runtime>(app: &AppHandle<R>, config: &HotkeyConfig) -> Result<()> {
+  config.bindings()?;
+  set_kv(CONFIG_KEY, config)?;
+  apply(app, config)
+}
+
+fn apply<R: Runtime>(app: &AppHandle<R>, config: &HotkeyConfig) -> Result<()> {
+  let bindings = config.bindings()?;
+  let shortcuts = app.global_shortcut();
+  shortcuts
+    .unregister_all()
+    .map_err(|e| HotkeyError::Register(e.to_string()))?;
+  let mut registered = HashMap::new();
+  for (shortcut, action) in bindings {
+    // 被其他程序占用的组合跳过，不影响其余快捷键
+    match shortcuts.register(shortcut) {
+      Ok(()) => {
+        registered.insert(shortcut.id(), action);
+      }
+      Err(e) => warn!("注册快捷键 {} 失败: {}", shortcut, e),
+    }
+  }
+  info!("已注册 {} 个全局快捷键", registered.len());
+  if let Ok(mut current) = BINDINGS.lock() {
+    *current = registered;
+  }
+  Ok(())
+}
+
+fn run(action: Action) -> std::result::Result<(), String> {
+  let current = latest_recording();
+  match (action, current) {
+    (Action::Marker, Some(r)) => mark_recording(r.session_id, "快捷键标记")
+      .map(|_| ())
+      .map_err(|e| e.to_string()),
+    (Action::Screenshot, Some(r)) => {
+      capture_recording_screenshot(r.session_id).map_err(|e| e.to_string())
+    }
+    (Action::ToggleRecording, Some(r)) => stop_recording(r.session_id)
+      .map(|_| ())
+      .map_err(|e| e.to_string()),
+    // 没有录制时按上次的配置开始
+    (Action::ToggleRecording, None) => {
+      let config = last_recording_config()
+        .map_err(|e| e.to_string())?
+        .ok_or("还没有录制过，请先在主窗口开始一次录制")?;
+      start_recording(config)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+    }
+    (_, None) => Err("没有录制中的会话".into()),
+  }
+}
+
+fn on_shortcut(shortcut: &Shortcut, state: ShortcutState) {
+  if state != ShortcutState::Pressed {
+    return;
+  }
+  let action = BINDINGS
+    .lock()
+    .ok()
+    .and_then(|b| b.get(&shortcut.id()).copied());
+  if let Some(action) = action {
+    // 录制的开始与停止会访问设备，不能阻塞事件循环
+    thread::spawn(move || {
+      if let Err(e) = run(action) {
+        warn!("快捷键操作失败: {}", e);
+      }
+    });
+  }
+}
+
+pub fn init<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<()> {
+  app.plugin(
+    tauri_plugin_global_shortcut::Builder::new()
+      .with_handler(|_app, shortcut, event| on_shortcut(shortcut, event.state()))
+      .build(),
+  )?;
+  let config = get_hotkeys().unwrap_or_else(|e| {
+    warn!("读取快捷键配置失败: {}", e);
+    HotkeyConfig::default()
+  });
+  if let Err(e) = apply(app, &config) {
+    warn!("注册全局快捷键失败: {}", e);
+  }
+  Ok(())
+}
@@ -0,0 +1,96 @@
+//! 后端文案的本地化：命令出错时返回稳定的错误码、文案键与参数，
+//! 由前端按用户语言渲染；message 保留中文原文，前端缺少译文时直接显示
+
+use crate::adb::error::AdbError;
+use perfx_core::settings::SettingsError;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fmt;
+
+/// 命令返回给前端的错误
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandError {
+  /// 稳定的错误码，如 SESSION_NOT_FOUND，前端可据此分支处理
+  pub code: &'static str,
+  /// 文案键，如 error.session_not_found
+  pub key: String,
+  /// 文案中的占位参数
+  pub params: Value,
+  /// 中文原文
+  pub message: String,
+}
+
+impl fmt::Display for CommandError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.message)
+  }
+}
+
+/// 可本地化的错误：提供错误码与文案参数，中文原文取自 Display
+pub trait Localized: fmt::Display {
+  fn code(&self) -> &'static str;
+
+  fn params(&self) -> Value {
+    Value::Null
+  }
+}
+
+impl<E: Localized> From<E> for CommandError {
+  fn from(e: E) -> Self {
+    let code = e.code();
+    Self {
+      code,
+      key: format!("error.{}", code.to_ascii_lowercase()),
+      params: e.params(),
+      message: e.to_string(),
+    }
+  }
+}
+
+/// 第三方错误只带原始描述，统一放在 detail 参数中
+pub(crate) fn detail(e: &impl fmt::Display) -> Value {
+  json!({ "detail": e.to_string() })
+}
+
+impl Localized for tauri::Error {
+  fn code(&self) -> &'static str {
+    "INTERNAL"
+  }
+
+  fn params(&self) -> Value {
+    detail(self)
+  }
+}
+
+impl Localized for AdbError {
+  fn code(&self) -> &'static str {
+    AdbError::code(self)
+  }
+
+  fn params(&self) -> Value {
+    match self {
+      Self::CommandFailed(d) | Self::ParseFailed(d) | Self::Script(d) | Self::Client(d) => {
+        detail(d)
+      }
+      Self::NotFound | Self::Metric(_) => Value::Null,
+    }
+  }
+}
+
+impl Localized for SettingsError {
+  fn code(&self) -> &'static str {
+    match self {
+      Self::NoConfigDir => "SETTINGS_NO_CONFIG_DIR",
+      Self::Io(_) => "SETTINGS_IO",
+      Self::Json(_) => "SETTINGS_FORMAT",
+    }
+  }
+
+  fn params(&self) -> Value {
+    match self {
+      Self::NoConfigDir => Value::Null,
+      Self::Io(e) => detail(e),
+      Self::Json(e) => detail(e),
+    }
+  }
+}
@@ -1,18 +1,49 @@
-mod adb;
+mod analysis;
 mod commands;
+mod deeplink;
+mod diagnostics;
+mod events;
+mod export;
+mod farm;
+mod hotkey;
+mod i18n;
+mod notify;
+mod overlay;
+mod runner;
+mod server;
+mod session;
+mod settings;
+mod tray;
+mod updater;
+mod webhook;
+mod window_state;
+mod windows;
 
 use crate::adb::set_bundled_adb_path;
+use perfx_core::adb;
 use std::{env, path::PathBuf};
-use tauri::{path::BaseDirectory, Manager};
+use tauri::{
+  menu::{Menu, MenuItem, Submenu},
+  path::BaseDirectory,
+  Manager,
+};
 use tauri_plugin_log::{Target, TargetKind, WEBVIEW_TARGET};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
   tauri::Builder::default()
+    // 需最先注册：重复启动时聚焦已有窗口，perfx:// 参数转交 deep-link 插件处理，
+    // 避免两个实例同时操作 adb 服务与数据库
+    .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+      tray::show_main_window(app);
+    }))
     .plugin(tauri_plugin_sql::Builder::default().build())
     .plugin(tauri_plugin_shell::init())
     .plugin(tauri_plugin_dialog::init())
     .plugin(tauri_plugin_fs::init())
+    .plugin(tauri_plugin_notification::init())
+    .plugin(tauri_plugin_updater::Builder::new().build())
+    .plugin(tauri_plugin_deep_link::init())
     .plugin(
       tauri_plugin_log::Builder::new()
         .level(log::LevelFilter::Info)
@@ -34,11 +65,148 @@ pub fn run() {
     .invoke_handler(tauri::generate_handler![
       commands::tauri_list_devices,
       commands::tauri_list_apps,
+      commands::tauri_list_users,
       commands::tauri_get_metrics,
+      commands::tauri_get_procstats,
       commands::tauri_execute_adb_command,
-      commands::tauri_set_adb_path
+      commands::tauri_set_adb_path,
+      commands::tauri_probe_capabilities,
+      commands::tauri_reset_batterystats,
+      commands::tauri_stop_background_apps,
+      commands::export::tauri_export_xlsx,
+      commands::export::tauri_export_json,
+      commands::export::tauri_export_bundle,
+      commands::export::tauri_export_junit,
+      commands::session::tauri_import_session,
+      commands::session::tauri_add_marker,
+      commands::session::tauri_list_markers,
+      commands::session::tauri_list_session_events,
+      commands::session::tauri_get_attachment,
+      commands::session::tauri_list_alert_rules,
+      commands::session::tauri_save_alert_rule,
+      commands::session::tauri_delete_alert_rule,
+      commands::session::tauri_get_session_meta,
+      commands::session::tauri_update_session_meta,
+      commands::session::tauri_set_session_scenario,
+      commands::session::tauri_capture_session_meta,
+      commands::session::tauri_set_baseline,
+      commands::session::tauri_clear_baseline,
+      commands::session::tauri_list_baselines,
+      commands::session::tauri_get_retention_policy,
+      commands::session::tauri_set_retention_policy,
+      commands::session::tauri_run_db_maintenance,
+      commands::session::tauri_get_db_usage,
+      commands::recording::tauri_start_recording,
+      commands::recording::tauri_stop_recording,
+      commands::recording::tauri_list_recordings,
+      commands::recording::tauri_mark_recording,
+      commands::recording::tauri_pause_recording,
+      commands::recording::tauri_resume_recording,
+      commands::recording::tauri_get_raw_dumps,
+      commands::recording::tauri_query_logcat,
+      commands::recording::tauri_capture_screenshot,
+      commands::recording::tauri_list_screenshots,
+      commands::recording::tauri_get_screenshot,
+      commands::recording::tauri_run_startup_benchmark,
+      commands::recording::tauri_get_startup_run,
+      commands::recording::tauri_start_monkey,
+      commands::recording::tauri_stop_monkey,
+      commands::recording::tauri_list_monkey_runs,
+      commands::recording::tauri_get_connectivity,
+      commands::recording::tauri_set_connectivity,
+      commands::recording::tauri_restore_connectivity,
+      commands::recording::tauri_get_cpu_policies,
+      commands::recording::tauri_lock_cpu,
+      commands::recording::tauri_restore_cpu,
+      commands::recording::tauri_get_animation_scales,
+      commands::recording::tauri_set_animation_scales,
+      commands::recording::tauri_restore_animation_scales,
+      commands::recording::tauri_get_display,
+      commands::recording::tauri_set_display,
+      commands::recording::tauri_restore_display,
+      commands::recording::tauri_get_device_overlays,
+      commands::recording::tauri_set_device_overlay,
+      commands::recording::tauri_restore_device_overlays,
+      commands::recording::tauri_get_proxy,
+      commands::recording::tauri_set_proxy,
+      commands::recording::tauri_restore_proxy,
+      commands::recording::tauri_get_idle_state,
+      commands::recording::tauri_set_doze,
+      commands::recording::tauri_get_standby_bucket,
+      commands::recording::tauri_set_standby_bucket,
+      commands::recording::tauri_get_game_mode,
+      commands::recording::tauri_set_game_mode,
+      commands::recording::tauri_reset_game_mode,
+      commands::recording::tauri_run_script,
+      commands::recording::tauri_stop_script,
+      commands::recording::tauri_list_script_runs,
+      commands::runner::tauri_start_timed_run,
+      commands::runner::tauri_cancel_run,
+      commands::runner::tauri_list_runs,
+      commands::runner::tauri_parse_scenario,
+      commands::runner::tauri_run_scenario,
+      commands::server::tauri_start_server,
+      commands::server::tauri_stop_server,
+      commands::server::tauri_server_status,
+      commands::server::tauri_connect_remote,
+      commands::server::tauri_disconnect_remote,
+      commands::server::tauri_remote_status,
+      commands::webhook::tauri_list_webhooks,
+      commands::webhook::tauri_save_webhooks,
+      commands::webhook::tauri_test_webhook,
+      commands::farm::tauri_get_farm_config,
+      commands::farm::tauri_set_farm_config,
+      commands::farm::tauri_list_farm_devices,
+      commands::farm::tauri_reserve_farm_device,
+      commands::farm::tauri_release_farm_device,
+      commands::farm::tauri_list_farm_reservations,
+      commands::notify::tauri_get_notification_settings,
+      commands::notify::tauri_set_notification_settings,
+      commands::hotkey::tauri_get_hotkeys,
+      commands::hotkey::tauri_set_hotkeys,
+      commands::overlay::tauri_show_overlay,
+      commands::overlay::tauri_hide_overlay,
+      commands::updater::tauri_get_update_settings,
+      commands::updater::tauri_set_update_settings,
+      commands::updater::tauri_check_update,
+      commands::updater::tauri_download_update,
+      commands::updater::tauri_apply_update,
+      commands::windows::tauri_open_chart_window,
+      commands::windows::tauri_list_chart_windows,
+      commands::windows::tauri_close_chart_window,
+      commands::windows::tauri_chart_window_ready,
+      commands::profiling::tauri_start_simpleperf,
+      commands::profiling::tauri_start_tcpdump,
+      commands::profiling::tauri_start_perfetto,
+      commands::profiling::tauri_start_heapprofd,
+      commands::profiling::tauri_start_method_trace,
+      commands::profiling::tauri_stop_capture,
+      commands::profiling::tauri_generate_flamegraph,
+      commands::profiling::tauri_dump_heap,
+      commands::profiling::tauri_capture_bugreport,
+      commands::profiling::tauri_collect_dropbox,
+      commands::profiling::tauri_list_captures,
+      commands::analysis::tauri_compare_sessions,
+      commands::analysis::tauri_get_session_summary,
+      commands::analysis::tauri_query_series,
+      commands::analysis::tauri_query_overlay,
+      commands::analysis::tauri_get_score_model,
+      commands::analysis::tauri_set_score_model,
+      commands::analysis::tauri_get_session_score,
+      commands::analysis::tauri_list_anomalies,
+      commands::analysis::tauri_analyze_memory_leak,
+      commands::analysis::tauri_get_gate_config,
+      commands::analysis::tauri_set_gate_config,
+      commands::analysis::tauri_evaluate_gate,
+      commands::analysis::tauri_estimate_energy,
+      commands::settings::tauri_get_settings,
+      commands::settings::tauri_set_settings,
+      commands::settings::tauri_update_selection,
+      commands::diagnostics::tauri_get_diagnostics,
+      commands::diagnostics::tauri_export_support_bundle
     ])
     .setup(|app| {
+      #[cfg(any(target_os = "macos", target_os = "windows"))]
       if let Some(window) = app.get_webview_window("main") {
         #[cfg(target_os = "macos")]
         {
@@ -53,6 +221,35 @@ pub fn run() {
         }
       }
 
+      let config_dir = app.path().app_config_dir()?;
+      std::fs::create_dir_all(&config_dir)?;
+      session::set_db_path(config_dir.join("reports.db"));
+      settings::init(app.handle(), config_dir.clone());
+      if let Some(window) = app.get_webview_window("main") {
+        window_state::track(&window);
+      }
+      events::register(app.handle());
+      notify::register(app.handle());
+      overlay::register(app.handle());
+      server::live::register();
+      webhook::register();
+      farm::register();
+      session::hwui::register();
+      tray::init(app.handle())?;
+      hotkey::init(app.handle())?;
+      updater::init(app.handle());
+      deeplink::init(app.handle());
+      std::thread::spawn(|| {
+        if let Err(e) = session::retention::run_maintenance() {
+          log::warn!("数据库维护失败: {}", e);
+        }
+        session::hooks::restore_pending(None);
+        session::animation::restore_all_animations();
+        session::display::restore_all_displays();
+        session::hwui::restore_all_overlays(None);
+        session::proxy::restore_all_proxies();
+      });
+
       #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
       {
         let resolver = app.path();
@@ -106,35 +303,33 @@ pub fn run() {
         }
       }
 
-      // TODO: 添加开发者工具菜单（暂时注释以修复CI编译）
-      // let enable_devtools = cfg!(debug_assertions) ||
-      //   std::env::var("DEVTOOLS").map(|v| v == "true").unwrap_or(false);
-      //
-      // if enable_devtools {
-      //   let devtools_item = MenuItem::with_id(app, "devtools", "打开开发者工具", true, Some("F12"))?;
-      //   let reload_item = MenuItem::with_id(app, "reload", "重新加载", true, Some("CmdOrCtrl+R"))?;
-      //
-      //   let dev_menu = Submenu::with_items(app, "开发", true, &[&devtools_item, &reload_item])?;
-      //   let menu = Menu::with_items(app, &[&dev_menu])?;
-      //
-      //   app.set_menu(menu)?;
-      //
-      //   app.on_menu_event(|app, event| {
-      //     match event.id().as_ref() {
-      //       "devtools" => {
-      //         if let Some(window) = app.get_webview_window("main") {
-      //           let _ = window.open_devtools();
-      //         }
-      //       }
-      //       "reload" => {
-      //         if let Some(window) = app.get_webview_window("main") {
-      //           let _ = window.eval("window.location.reload()");
-      //         }
-      //       }
-      //       _ => {}
-      //     }
-      //   });
-      // }
+      // 调试构建或设置环境变量 DEVTOOLS=true 时提供开发菜单
+      let enable_devtools = cfg!(debug_assertions)
+        || env::var("DEVTOOLS").map(|v| v == "true").unwrap_or(false);
+
+      if enable_devtools {
+        let devtools_item = MenuItem::with_id(app, "devtools", "打开开发者工具", true, Some("F12"))?;
+        let reload_item = MenuItem::with_id(app, "reload", "重新加载", true, Some("CmdOrCtrl+R"))?;
+
+        let dev_menu = Submenu::with_items(app, "开发", true, &[&devtools_item, &reload_item])?;
+        let menu = Menu::with_items(app, &[&dev_menu])?;
+
+        app.set_menu(menu)?;
+
+        app.on_menu_event(|app, event| match event.id().as_ref() {
+          "devtools" => {
+            if let Some(window) = app.get_webview_window("main") {
+              window.open_devtools();
+            }
+          }
+          "reload" => {
+            if let Some(window) = app.get_webview_window("main") {
+              let _ = window.eval("window.location.reload()");
+            }
+          }
+          _ => {}
+        });
+      }
 
       Ok(())
     })
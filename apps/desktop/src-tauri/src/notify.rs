@@ -0,0 +1,114 @@
+//! 系统通知：告警触发、录制中设备掉线与耗时较长的导出完成时提醒，
+//! 测试人员切到其他窗口也能及时发现
+
+use crate::session::{
+  error::Result,
+  events::{EventKind, SessionEvent},
+  recorder::{add_observer, recording_target, RecordingObserver},
+  store::{get_kv, set_kv},
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+  path::Path,
+  sync::Arc,
+  time::{Duration, Instant},
+};
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+const CONFIG_KEY: &str = "notifications";
+
+fn default_true() -> bool {
+  true
+}
+
+fn default_export_min_secs() -> u64 {
+  10
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationSettings {
+  #[serde(default = "default_true")]
+  pub alerts: bool,
+  #[serde(default = "default_true")]
+  pub disconnects: bool,
+  #[serde(default = "default_true")]
+  pub exports: bool,
+  /// 导出耗时超过该秒数才通知，快速导出不打扰
+  #[serde(default = "default_export_min_secs")]
+  pub export_min_secs: u64,
+}
+
+impl Default for NotificationSettings {
+  fn default() -> Self {
+    Self {
+      alerts: true,
+      disconnects: true,
+      exports: true,
+      export_min_secs: default_export_min_secs(),
+    }
+  }
+}
+
+pub fn get_notification_settings() -> Result<NotificationSettings> {
+  Ok(get_kv(CONFIG_KEY)?.unwrap_or_default())
+}
+
+pub fn set_notification_settings(settings: &NotificationSettings) -> Result<()> {
+  set_kv(CONFIG_KEY, settings)
+}
+
+fn settings() -> NotificationSettings {
+  get_notification_settings()
+    .map_err(|e| warn!("读取通知设置失败: {}", e))
+    .unwrap_or_default()
+}
+
+fn show(app: &AppHandle, title: &str, body: &str) {
+  if let Err(e) = app.notification().builder().title(title).body(body).show() {
+    warn!("发送系统通知失败: {}", e);
+  }
+}
+
+/// 导出完成后调用，耗时不足阈值或关闭了导出通知时不提醒
+pub fn notify_export(app: &AppHandle, path: &Path, started: Instant, ok: bool) {
+  let settings = settings();
+  if !settings.exports || started.elapsed() < Duration::from_secs(settings.export_min_secs) {
+    return;
+  }
+  let name = path
+    .file_name()
+    .map(|n| n.to_string_lossy().to_string())
+    .unwrap_or_else(|| path.display().to_string());
+  if ok {
+    show(app, "导出完成", &name);
+  } else {
+    show(app, "导出失败", &name);
+  }
+}
+
+struct Notifier {
+  app: AppHandle,
+}
+
+impl RecordingObserver for Notifier {
+  fn on_event(&self, session_id: i64, event: &SessionEvent) {
+    let title = match event.kind {
+      EventKind::Alert if settings().alerts => "性能告警",
+      EventKind::Disconnected if settings().disconnects => "设备掉线",
+      _ => return,
+    };
+    let body = match recording_target(session_id) {
+      Some((device_id, package, _)) => {
+        format!("#{session_id} {device_id} / {package}\n{}", event.message)
+      }
+      None => format!("#{session_id} {}", event.message),
+    };
+    show(&self.app, title, &body);
+  }
+}
+
+pub fn register(app: &AppHandle) {
+  add_observer(Arc::new(Notifier { app: app.clone() }));
+}
@@ -0,0 +1,98 @@
+//! 置顶悬浮窗：无边框的小窗口显示当前会话的帧率、CPU 与内存，
+//! 主窗口最小化时也能看到读数；数据由后端推送，不依赖主窗口
+
+use crate::{
+  adb::MetricsSnapshot,
+  events::OVERLAY_EVENT,
+  session::{
+    recorder::{add_observer, latest_recording, RecordingObserver},
+    Sample,
+  },
+  window_state,
+};
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+
+const OVERLAY_LABEL: &str = "overlay";
+
+#[derive(Debug, Clone, Serialize)]
+struct Readout<'a> {
+  session_id: i64,
+  package: &'a str,
+  fps: Option<f64>,
+  cpu: Option<f64>,
+  memory_mb: Option<f64>,
+}
+
+pub fn show_overlay(app: &AppHandle) -> tauri::Result<()> {
+  if let Some(window) = app.get_webview_window(OVERLAY_LABEL) {
+    return window.show();
+  }
+  let window =
+    WebviewWindowBuilder::new(app, OVERLAY_LABEL, WebviewUrl::App("overlay.html".into()))
+      .title("PerfX")
+      .inner_size(320.0, 40.0)
+      .position(24.0, 24.0)
+      .decorations(false)
+      .always_on_top(true)
+      .skip_taskbar(true)
+      .resizable(false)
+      .focused(false)
+      .build()?;
+  window_state::track(&window);
+  Ok(())
+}
+
+pub fn hide_overlay(app: &AppHandle) -> tauri::Result<()> {
+  match app.get_webview_window(OVERLAY_LABEL) {
+    Some(window) => window.close(),
+    None => Ok(()),
+  }
+}
+
+pub fn toggle_overlay(app: &AppHandle) -> tauri::Result<()> {
+  match app.get_webview_window(OVERLAY_LABEL) {
+    Some(window) => window.close(),
+    None => show_overlay(app),
+  }
+}
+
+struct OverlayFeed {
+  app: AppHandle,
+}
+
+impl RecordingObserver for OverlayFeed {
+  fn on_sample(&self, session_id: i64, _sample: &Sample, snapshot: &MetricsSnapshot) {
+    if self.app.get_webview_window(OVERLAY_LABEL).is_none() {
+      return;
+    }
+    // 多个会话同时录制时只显示最近开始的一个
+    let Some(current) = latest_recording().filter(|r| r.session_id == session_id) else {
+      return;
+    };
+    let _ = self.app.emit_to(
+      OVERLAY_LABEL,
+      OVERLAY_EVENT,
+      Readout {
+        session_id,
+        package: &current.package,
+        fps: snapshot.fps,
+        cpu: snapshot.cpu,
+        memory_mb: snapshot.memory_mb,
+      },
+    );
+  }
+
+  fn on_stopped(&self, _session_id: i64) {
+    if latest_recording().is_none() {
+      let _ = self
+        .app
+        .emit_to(OVERLAY_LABEL, OVERLAY_EVENT, Option::<()>::None);
+    }
+  }
+}
+
+pub fn register(app: &AppHandle) {
+  add_observer(Arc::new(OverlayFeed { app: app.clone() }));
+}
@@ -0,0 +1,59 @@
+use crate::{
+  adb::error::AdbError,
+  analysis::error::AnalysisError,
+  export::error::ExportError,
+  i18n::{detail, Localized},
+  session::error::SessionError,
+};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RunnerError {
+  #[error(transparent)]
+  Adb(#[from] AdbError),
+  #[error(transparent)]
+  Session(#[from] SessionError),
+  #[error(transparent)]
+  Analysis(#[from] AnalysisError),
+  #[error(transparent)]
+  Export(#[from] ExportError),
+  #[error("场景定义有误: {0}")]
+  Scenario(String),
+  #[error("运行不存在: {0}")]
+  NotFound(u64),
+  #[error("未设置导出目录")]
+  NoExportDir,
+  #[error("文件读写失败: {0}")]
+  Io(#[from] std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, RunnerError>;
+
+impl Localized for RunnerError {
+  fn code(&self) -> &'static str {
+    match self {
+      Self::Adb(e) => e.code(),
+      Self::Session(e) => e.code(),
+      Self::Analysis(e) => e.code(),
+      Self::Export(e) => e.code(),
+      Self::Scenario(_) => "SCENARIO_INVALID",
+      Self::NotFound(_) => "RUN_NOT_FOUND",
+      Self::NoExportDir => "RUN_NO_EXPORT_DIR",
+      Self::Io(_) => "IO",
+    }
+  }
+
+  fn params(&self) -> Value {
+    match self {
+      Self::Adb(e) => e.params(),
+      Self::Session(e) => e.params(),
+      Self::Analysis(e) => e.params(),
+      Self::Export(e) => e.params(),
+      Self::Scenario(d) => detail(d),
+      Self::NotFound(id) => json!({ "runId": id }),
+      Self::NoExportDir => Value::Null,
+      Self::Io(e) => detail(e),
+    }
+  }
+}
@@ -0,0 +1,278 @@
+//! 无人值守的自动化运行：按流程完成准备、录制、汇总与导出
+
+use crate::{
+  analysis::{summarize, summary::MetricSummary},
+  export::{export_bundle, export_json, export_session_junit, export_xlsx},
+  runner::error::{Result, RunnerError},
+  session::{
+    load_session,
+    recorder::{recording_target, stop_recording},
+    store::db_path,
+  },
+  settings,
+};
+use log::warn;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+  any::Any,
+  collections::BTreeMap,
+  fs,
+  panic::{self, AssertUnwindSafe},
+  path::{Path, PathBuf},
+  sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc, Mutex,
+  },
+  thread,
+};
+
+pub mod error;
+pub mod scenario;
+pub mod timed;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static RUNS: Lazy<Mutex<BTreeMap<u64, Arc<RunHandle>>>> = Lazy::new(|| Mutex::new(BTreeMap::new()));
+// 保留最近结束的运行供查询结果，更早的从登记中移除
+const KEEP_FINISHED: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunPhase {
+  Preparing,
+  Recording,
+  Exporting,
+  Done,
+  Failed,
+  Cancelled,
+}
+
+impl RunPhase {
+  pub fn finished(self) -> bool {
+    matches!(self, Self::Done | Self::Failed | Self::Cancelled)
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunStatus {
+  pub id: u64,
+  pub name: String,
+  pub phase: RunPhase,
+  pub session_id: Option<i64>,
+  /// 录制已进行的时长
+  pub elapsed_ms: u64,
+  /// 场景运行的步骤进度，定时运行为 0
+  pub step: usize,
+  pub total_steps: usize,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub summary: Vec<MetricSummary>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub outputs: Vec<PathBuf>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+}
+
+pub(crate) struct RunHandle {
+  status: Mutex<RunStatus>,
+  cancel: AtomicBool,
+}
+
+impl RunHandle {
+  // 运行线程崩溃后状态仍需可写，忽略锁中毒
+  pub(crate) fn update(&self, f: impl FnOnce(&mut RunStatus)) {
+    f(&mut self.status.lock().unwrap_or_else(|e| e.into_inner()));
+  }
+
+  fn snapshot(&self) -> RunStatus {
+    self
+      .status
+      .lock()
+      .unwrap_or_else(|e| e.into_inner())
+      .clone()
+  }
+
+  pub(crate) fn cancelled(&self) -> bool {
+    self.cancel.load(Ordering::Relaxed)
+  }
+}
+
+/// 登记运行并在后台线程执行，出错时记为失败
+pub(crate) fn spawn_run(
+  name: String,
+  total_steps: usize,
+  run: impl FnOnce(&RunHandle) -> Result<()> + Send + 'static,
+) -> RunStatus {
+  let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+  let status = RunStatus {
+    id,
+    name,
+    phase: RunPhase::Preparing,
+    session_id: None,
+    elapsed_ms: 0,
+    step: 0,
+    total_steps,
+    summary: Vec::new(),
+    outputs: Vec::new(),
+    error: None,
+  };
+  let handle = Arc::new(RunHandle {
+    status: Mutex::new(status.clone()),
+    cancel: AtomicBool::new(false),
+  });
+  if let Ok(mut runs) = RUNS.lock() {
+    runs.insert(id, handle.clone());
+  }
+
+  thread::spawn(move || {
+    let error = match panic::catch_unwind(AssertUnwindSafe(|| run(&handle))) {
+      Ok(Ok(())) => None,
+      Ok(Err(e)) => Some(e.to_string()),
+      Err(payload) => Some(panic_message(payload.as_ref())),
+    };
+    if let Some(error) = error {
+      warn!("运行 {} 失败: {}", id, error);
+      handle.update(|s| {
+        s.phase = RunPhase::Failed;
+        s.error = Some(error);
+      });
+      // 中途失败时停止已开始的录制，不让会话一直录下去
+      let session_id = handle.snapshot().session_id;
+      if let Some(session_id) = session_id.filter(|id| recording_target(*id).is_some()) {
+        if let Err(e) = stop_recording(session_id) {
+          warn!("停止运行 {} 的会话 {} 失败: {}", id, session_id, e);
+        }
+      }
+    }
+    prune_finished();
+  });
+  status
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+  let detail = payload
+    .downcast_ref::<&str>()
+    .map(|s| s.to_string())
+    .or_else(|| payload.downcast_ref::<String>().cloned())
+    .unwrap_or_default();
+  format!("运行异常中止: {detail}")
+}
+
+fn prune_finished() {
+  let Ok(mut runs) = RUNS.lock() else {
+    return;
+  };
+  let finished: Vec<u64> = runs
+    .iter()
+    .filter(|(_, h)| h.snapshot().phase.finished())
+    .map(|(id, _)| *id)
+    .collect();
+  // id 递增，先移除最早的
+  let excess = finished.len().saturating_sub(KEEP_FINISHED);
+  for id in &finished[..excess] {
+    runs.remove(id);
+  }
+}
+
+/// 取消运行；录制中的会话会提前停止，已录制的数据照常汇总导出
+pub fn cancel_run(id: u64) -> Result<()> {
+  let runs = RUNS.lock().map_err(|_| RunnerError::NotFound(id))?;
+  let handle = runs.get(&id).ok_or(RunnerError::NotFound(id))?;
+  handle.cancel.store(true, Ordering::Relaxed);
+  Ok(())
+}
+
+pub fn list_runs() -> Vec<RunStatus> {
+  RUNS
+    .lock()
+    .map(|runs| runs.values().map(|h| h.snapshot()).collect())
+    .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+  Json,
+  Xlsx,
+  Bundle,
+  Junit,
+}
+
+impl ExportFormat {
+  fn extension(self) -> &'static str {
+    match self {
+      Self::Json => "json",
+      Self::Xlsx => "xlsx",
+      Self::Bundle => "perfx",
+      Self::Junit => "junit.xml",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoExport {
+  pub dir: Option<PathBuf>,
+  pub formats: Vec<ExportFormat>,
+}
+
+/// 停止仍在录制的会话，汇总并按配置导出，最后更新运行状态
+pub(crate) fn finish_run(handle: &RunHandle, session_id: i64, export: &AutoExport) -> Result<()> {
+  if recording_target(session_id).is_some() {
+    stop_recording(session_id)?;
+  }
+  handle.update(|s| s.phase = RunPhase::Exporting);
+  let data = load_session(session_id)?;
+  let summary = summarize(&data);
+  let outputs = export_all(session_id, export)?;
+  let cancelled = handle.cancelled();
+  handle.update(|s| {
+    s.phase = if cancelled {
+      RunPhase::Cancelled
+    } else {
+      RunPhase::Done
+    };
+    s.summary = summary;
+    s.outputs = outputs;
+  });
+  Ok(())
+}
+
+/// 未指定导出目录时依次使用设置中的导出目录、应用数据目录下的 exports；
+/// 不使用进程工作目录，GUI 应用的工作目录不可预期
+fn default_export_dir() -> Option<PathBuf> {
+  settings::export_dir().or_else(|| {
+    db_path()
+      .and_then(|db| db.parent())
+      .map(|dir| dir.join("exports"))
+  })
+}
+
+fn export_all(session_id: i64, export: &AutoExport) -> Result<Vec<PathBuf>> {
+  if export.formats.is_empty() {
+    return Ok(Vec::new());
+  }
+  let dir = export
+    .dir
+    .clone()
+    .or_else(default_export_dir)
+    .ok_or(RunnerError::NoExportDir)?;
+  fs::create_dir_all(&dir)?;
+  let mut outputs = Vec::new();
+  for format in &export.formats {
+    let path = dir.join(format!("perfx-{session_id}.{}", format.extension()));
+    export_one(session_id, *format, &path)?;
+    outputs.push(path);
+  }
+  Ok(outputs)
+}
+
+fn export_one(session_id: i64, format: ExportFormat, path: &Path) -> Result<()> {
+  let data = load_session(session_id)?;
+  match format {
+    ExportFormat::Json => export_json(&data, path)?,
+    ExportFormat::Xlsx => export_xlsx(&data, path)?,
+    ExportFormat::Bundle => export_bundle(&data, path, None)?,
+    ExportFormat::Junit => export_session_junit(session_id, None, path)?,
+  }
+  Ok(())
+}
@@ -0,0 +1,185 @@
+use crate::{
+  adb::MetricKey,
+  runner::{
+    error::{Result, RunnerError},
+    finish_run, spawn_run, AutoExport, RunHandle, RunPhase, RunStatus,
+  },
+  session::{
+    recorder::{
+      capture_recording_screenshot, mark_recording, recording_target, start_recording,
+      RecordingConfig, RecordingOptions,
+    },
+    script::{default_swipe_ms, perform_action, StepAction},
+  },
+};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+fn default_metrics() -> Vec<MetricKey> {
+  vec![MetricKey::Fps, MetricKey::Cpu, MetricKey::Memory]
+}
+
+/// 场景中的一步；YAML 中写作 `- launch`、`- wait: 10`、`- marker: 首页`、`- tap: {x: 540, y: 1200}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScenarioStep {
+  Launch,
+  ForceStop,
+  Back,
+  /// 等待秒数
+  Wait(f64),
+  /// 保持录制秒数，与 wait 相同，用于表达在当前界面录制
+  Record(f64),
+  Marker(String),
+  Screenshot,
+  Tap {
+    x: u32,
+    y: u32,
+  },
+  Swipe {
+    x1: u32,
+    y1: u32,
+    x2: u32,
+    y2: u32,
+    #[serde(default = "default_swipe_ms")]
+    duration_ms: u32,
+  },
+  Text(String),
+  Key(String),
+  Shell(String),
+}
+
+impl ScenarioStep {
+  fn action(&self) -> Option<StepAction> {
+    Some(match self {
+      Self::Launch => StepAction::Launch,
+      Self::ForceStop => StepAction::ForceStop,
+      Self::Back => StepAction::Back,
+      Self::Wait(secs) | Self::Record(secs) => StepAction::Wait {
+        ms: (secs.max(0.0) * 1000.0) as u64,
+      },
+      Self::Tap { x, y } => StepAction::Tap { x: *x, y: *y },
+      Self::Swipe {
+        x1,
+        y1,
+        x2,
+        y2,
+        duration_ms,
+      } => StepAction::Swipe {
+        x1: *x1,
+        y1: *y1,
+        x2: *x2,
+        y2: *y2,
+        duration_ms: *duration_ms,
+      },
+      Self::Text(text) => StepAction::Text { text: text.clone() },
+      Self::Key(key) => StepAction::Key { key: key.clone() },
+      Self::Shell(command) => StepAction::Shell {
+        command: command.clone(),
+      },
+      Self::Marker(_) | Self::Screenshot => return None,
+    })
+  }
+}
+
+/// 声明式测试场景：整个步骤序列在同一个会话的录制中执行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+  pub name: String,
+  pub package: String,
+  /// 运行时未指定设备则使用此设备
+  #[serde(default)]
+  pub device_id: Option<String>,
+  #[serde(default = "default_metrics")]
+  pub metrics: Vec<MetricKey>,
+  #[serde(default)]
+  pub options: RecordingOptions,
+  pub steps: Vec<ScenarioStep>,
+  #[serde(default)]
+  pub export: AutoExport,
+}
+
+/// 解析 YAML 或 JSON 格式的场景定义
+pub fn parse_scenario(text: &str) -> Result<Scenario> {
+  // serde_yaml 只按 `!tag` 识别枚举，经 JSON 值中转以支持 `- wait: 10` 这样的单键写法
+  let value: serde_json::Value =
+    serde_yaml::from_str(text).map_err(|e| RunnerError::Scenario(e.to_string()))?;
+  let scenario: Scenario =
+    serde_json::from_value(value).map_err(|e| RunnerError::Scenario(e.to_string()))?;
+  if scenario.steps.is_empty() {
+    return Err(RunnerError::Scenario("场景没有任何步骤".into()));
+  }
+  Ok(scenario)
+}
+
+pub fn load_scenario(path: &Path) -> Result<Scenario> {
+  parse_scenario(&fs::read_to_string(path)?)
+}
+
+/// 后台执行场景：开始录制 → 逐步执行 → 停止并汇总 → 按配置导出
+pub fn start_scenario(scenario: Scenario, device_id: Option<String>) -> Result<RunStatus> {
+  let device_id = device_id
+    .or_else(|| scenario.device_id.clone())
+    .ok_or_else(|| RunnerError::Scenario("未指定运行设备".into()))?;
+  let name = scenario.name.clone();
+  let total = scenario.steps.len();
+  Ok(spawn_run(name, total, move |handle| {
+    run(handle, &scenario, device_id)
+  }))
+}
+
+fn run(handle: &RunHandle, scenario: &Scenario, device_id: String) -> Result<()> {
+  let session_id = start_recording(RecordingConfig {
+    device_id: device_id.clone(),
+    package: scenario.package.clone(),
+    metrics: scenario.metrics.clone(),
+    name: Some(scenario.name.clone()),
+    options: scenario.options.clone(),
+  })?;
+  handle.update(|s| {
+    s.phase = RunPhase::Recording;
+    s.session_id = Some(session_id);
+  });
+  info!("场景「{}」开始，会话 {}", scenario.name, session_id);
+
+  // 步骤失败时仍停止录制并导出已有数据，再报告错误
+  let result = run_steps(handle, scenario, &device_id, session_id);
+  finish_run(handle, session_id, &scenario.export)?;
+  result
+}
+
+fn run_steps(
+  handle: &RunHandle,
+  scenario: &Scenario,
+  device_id: &str,
+  session_id: i64,
+) -> Result<()> {
+  let stopped = || handle.cancelled() || recording_target(session_id).is_none();
+  for (index, step) in scenario.steps.iter().enumerate() {
+    if stopped() {
+      break;
+    }
+    let elapsed_ms = recording_target(session_id).map_or(0, |(_, _, t)| t);
+    handle.update(|s| {
+      s.step = index + 1;
+      s.elapsed_ms = elapsed_ms;
+    });
+    let result = match step {
+      ScenarioStep::Marker(name) => mark_recording(session_id, name).map(|_| ()),
+      ScenarioStep::Screenshot => capture_recording_screenshot(session_id),
+      _ => match step.action() {
+        Some(action) => perform_action(
+          device_id,
+          &scenario.package,
+          scenario.options.user,
+          &action,
+          stopped,
+        ),
+        None => Ok(()),
+      },
+    };
+    result.map_err(|e| RunnerError::Scenario(format!("第 {} 步失败: {e}", index + 1)))?;
+  }
+  Ok(())
+}
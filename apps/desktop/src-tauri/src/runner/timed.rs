@@ -0,0 +1,153 @@
+use crate::{
+  adb::{
+    app::{force_stop, launcher_activity, start_activity_wait},
+    collect_metrics_with, CollectOptions, MetricKey,
+  },
+  runner::{error::Result, finish_run, spawn_run, AutoExport, RunHandle, RunPhase, RunStatus},
+  session::recorder::{recording_target, start_recording, RecordingConfig},
+};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+  thread,
+  time::{Duration, Instant},
+};
+
+// 轮询录制剩余时间与取消标记的间隔
+const POLL: Duration = Duration::from_millis(500);
+
+fn default_window_secs() -> u64 {
+  5
+}
+
+fn default_tolerance_fps() -> f64 {
+  3.0
+}
+
+fn default_timeout_secs() -> u64 {
+  60
+}
+
+/// 等待帧率稳定：最近窗口内的最大与最小帧率差不超过容差
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StableFps {
+  #[serde(default = "default_window_secs")]
+  pub window_secs: u64,
+  #[serde(default = "default_tolerance_fps")]
+  pub tolerance_fps: f64,
+  /// 超时后不再等待，直接开始录制
+  #[serde(default = "default_timeout_secs")]
+  pub timeout_secs: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreSteps {
+  /// 结束进程后冷启动应用
+  pub launch_app: bool,
+  pub stable_fps: Option<StableFps>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimedRunConfig {
+  pub recording: RecordingConfig,
+  pub duration_secs: u64,
+  #[serde(default)]
+  pub pre_steps: PreSteps,
+  #[serde(default)]
+  pub export: AutoExport,
+}
+
+/// 后台执行定时运行：准备 → 录制指定时长 → 停止并汇总 → 按配置导出
+pub fn start_timed_run(config: TimedRunConfig) -> RunStatus {
+  let name = format!(
+    "{} 定时 {}s",
+    config.recording.package, config.duration_secs
+  );
+  spawn_run(name, 0, move |handle| run(handle, &config))
+}
+
+fn run(handle: &RunHandle, config: &TimedRunConfig) -> Result<()> {
+  let recording = &config.recording;
+  if config.pre_steps.launch_app {
+    let user = recording.options.user;
+    force_stop(&recording.device_id, &recording.package, user)?;
+    thread::sleep(Duration::from_secs(1));
+    let component = launcher_activity(&recording.device_id, &recording.package, user)?;
+    start_activity_wait(&recording.device_id, &component, user)?;
+  }
+  if let Some(stable) = &config.pre_steps.stable_fps {
+    let options = CollectOptions {
+      keep_raw: false,
+      ..recording.options.collect_options()
+    };
+    wait_stable_fps(handle, recording, options, stable);
+  }
+  if handle.cancelled() {
+    handle.update(|s| s.phase = RunPhase::Cancelled);
+    return Ok(());
+  }
+
+  let session_id = start_recording(recording.clone())?;
+  handle.update(|s| {
+    s.phase = RunPhase::Recording;
+    s.session_id = Some(session_id);
+  });
+  info!(
+    "定时运行开始录制会话 {}，时长 {}s",
+    session_id, config.duration_secs
+  );
+
+  let deadline = Duration::from_secs(config.duration_secs);
+  let started = Instant::now();
+  // 会话被手动停止时 recording_target 返回 None，同样视为结束
+  while started.elapsed() < deadline && !handle.cancelled() {
+    if recording_target(session_id).is_none() {
+      break;
+    }
+    handle.update(|s| s.elapsed_ms = started.elapsed().as_millis() as u64);
+    thread::sleep(POLL);
+  }
+  handle.update(|s| s.elapsed_ms = started.elapsed().as_millis() as u64);
+  finish_run(handle, session_id, &config.export)?;
+  info!("定时运行结束，会话 {}", session_id);
+  Ok(())
+}
+
+/// 按录制的采集选项读取帧率，指定用户时读取该用户下的应用
+fn wait_stable_fps(
+  handle: &RunHandle,
+  recording: &RecordingConfig,
+  options: CollectOptions,
+  stable: &StableFps,
+) {
+  let (device_id, package) = (recording.device_id.as_str(), recording.package.as_str());
+  let started = Instant::now();
+  let timeout = Duration::from_secs(stable.timeout_secs);
+  let window = stable.window_secs.max(2) as usize;
+  let mut recent: Vec<f64> = Vec::new();
+  while started.elapsed() < timeout && !handle.cancelled() {
+    let tick = Instant::now();
+    if let Some(fps) = collect_metrics_with(device_id, package, &[MetricKey::Fps], options.clone())
+      .ok()
+      .and_then(|(s, _)| s.fps)
+    {
+      recent.push(fps);
+      if recent.len() > window {
+        recent.remove(0);
+      }
+      if recent.len() == window {
+        let max = recent.iter().copied().fold(f64::MIN, f64::max);
+        let min = recent.iter().copied().fold(f64::MAX, f64::min);
+        if max - min <= stable.tolerance_fps {
+          info!("帧率已稳定（{:.1}~{:.1}）", min, max);
+          return;
+        }
+      }
+    }
+    if let Some(left) = Duration::from_secs(1).checked_sub(tick.elapsed()) {
+      thread::sleep(left);
+    }
+  }
+  warn!("等待帧率稳定超时，直接开始录制");
+}
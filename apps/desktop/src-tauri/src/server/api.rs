@@ -0,0 +1,291 @@
+use crate::{
+  adb::{list_devices, DeviceInfo},
+  runner::{
+    cancel_run, list_runs,
+    scenario::{parse_scenario, start_scenario},
+    timed::{start_timed_run, TimedRunConfig},
+    RunStatus,
+  },
+  server::{
+    live::{history_frames, subscribe},
+    prometheus, token_matches,
+  },
+  session::{
+    error::SessionError,
+    recorder::{
+      active_recording, list_recordings, mark_recording, start_recording, stop_recording,
+      RecordingConfig,
+    },
+    Marker, Session,
+  },
+};
+use axum::{
+  extract::{
+    ws::{Message, WebSocket, WebSocketUpgrade},
+    Path, Query, Request, State,
+  },
+  http::{
+    header::{AUTHORIZATION, CONTENT_TYPE},
+    Method, StatusCode,
+  },
+  middleware::{self, Next},
+  response::{Html, IntoResponse, Response},
+  routing::{get, post},
+  Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+use tauri::async_runtime::spawn_blocking;
+use tokio::sync::broadcast::error::RecvError;
+
+pub struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+  fn into_response(self) -> Response {
+    (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+  }
+}
+
+impl From<SessionError> for ApiError {
+  fn from(e: SessionError) -> Self {
+    let status = match e {
+      SessionError::NotFound(_)
+      | SessionError::NotRecording(_)
+      | SessionError::NoRecordingOnDevice(_)
+      | SessionError::NoActiveRecording => StatusCode::NOT_FOUND,
+      SessionError::AmbiguousRecording(_) => StatusCode::CONFLICT,
+      _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    Self(status, e.to_string())
+  }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// 在阻塞线程池中执行会话 / adb 操作
+async fn blocking<T: Send + 'static>(
+  f: impl FnOnce() -> Result<T, ApiError> + Send + 'static,
+) -> ApiResult<T> {
+  spawn_blocking(f)
+    .await
+    .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    .map(Json)
+}
+
+/// 控制令牌可访问全部接口；观看令牌只能访问 GET 接口，用于局域网内只读观看
+#[derive(Clone)]
+pub struct Tokens {
+  pub control: Arc<str>,
+  pub viewer: Arc<str>,
+}
+
+pub fn router(tokens: Tokens, prometheus: bool) -> Router {
+  let mut router = Router::new();
+  if prometheus {
+    router = router.route("/metrics", get(metrics));
+  }
+  router
+    .route("/viewer", get(viewer))
+    .route("/api/status", get(status))
+    .route("/api/devices", get(devices))
+    .route("/api/recordings", get(recordings).post(start))
+    .route("/api/recordings/{id}/stop", post(stop))
+    .route("/api/recordings/{id}/markers", post(mark))
+    .route("/api/markers", post(mark_active))
+    .route("/api/runs", get(runs).post(start_run))
+    .route("/api/runs/{id}/cancel", post(cancel))
+    .route("/api/scenarios", post(scenario))
+    .route("/api/live", get(live))
+    .layer(middleware::from_fn_with_state(tokens, authorize))
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenQuery {
+  token: Option<String>,
+}
+
+/// 校验 `Authorization: Bearer <token>`，浏览器 WebSocket 无法设置请求头时可用 `?token=`
+async fn authorize(
+  State(tokens): State<Tokens>,
+  Query(query): Query<TokenQuery>,
+  request: Request,
+  next: Next,
+) -> Response {
+  let header = request
+    .headers()
+    .get(AUTHORIZATION)
+    .and_then(|v| v.to_str().ok())
+    .and_then(|v| v.strip_prefix("Bearer "));
+  let provided = header.or(query.token.as_deref());
+  if token_matches(provided, &tokens.control) {
+    return next.run(request).await;
+  }
+  if !token_matches(provided, &tokens.viewer) {
+    return ApiError(StatusCode::UNAUTHORIZED, "令牌无效".into()).into_response();
+  }
+  if request.method() != Method::GET {
+    return ApiError(StatusCode::FORBIDDEN, "观看令牌只能读取".into()).into_response();
+  }
+  next.run(request).await
+}
+
+/// 浏览器观看页，令牌通过 `?token=` 传入
+async fn viewer() -> Html<&'static str> {
+  Html(include_str!("viewer.html"))
+}
+
+async fn metrics() -> impl IntoResponse {
+  (
+    [(CONTENT_TYPE, prometheus::CONTENT_TYPE)],
+    prometheus::render(),
+  )
+}
+
+#[derive(Debug, Serialize)]
+struct Status {
+  version: &'static str,
+  recordings: usize,
+}
+
+async fn status() -> Json<Status> {
+  Json(Status {
+    version: env!("CARGO_PKG_VERSION"),
+    recordings: list_recordings().len(),
+  })
+}
+
+async fn devices() -> ApiResult<Vec<DeviceInfo>> {
+  blocking(|| list_devices().map_err(|e| ApiError(StatusCode::BAD_GATEWAY, e.to_string()))).await
+}
+
+async fn recordings() -> Json<serde_json::Value> {
+  Json(serde_json::json!(list_recordings()))
+}
+
+#[derive(Debug, Serialize)]
+struct Started {
+  session_id: i64,
+}
+
+async fn start(Json(config): Json<RecordingConfig>) -> ApiResult<Started> {
+  blocking(move || {
+    Ok(Started {
+      session_id: start_recording(config)?,
+    })
+  })
+  .await
+}
+
+async fn stop(Path(id): Path<i64>) -> ApiResult<Session> {
+  blocking(move || Ok(stop_recording(id)?)).await
+}
+
+#[derive(Debug, Deserialize)]
+struct MarkBody {
+  name: String,
+}
+
+async fn mark(Path(id): Path<i64>, Json(body): Json<MarkBody>) -> ApiResult<Marker> {
+  blocking(move || Ok(mark_recording(id, &body.name)?)).await
+}
+
+#[derive(Debug, Deserialize)]
+struct ActiveMarkBody {
+  name: String,
+  #[serde(default)]
+  device_id: Option<String>,
+  #[serde(default)]
+  session_id: Option<i64>,
+}
+
+/// 供 Appium 等外部框架在测试步骤处打点，无需预先知道会话 ID
+async fn mark_active(Json(body): Json<ActiveMarkBody>) -> ApiResult<Marker> {
+  blocking(move || {
+    let session_id = match body.session_id {
+      Some(id) => id,
+      None => active_recording(body.device_id.as_deref())?,
+    };
+    Ok(mark_recording(session_id, &body.name)?)
+  })
+  .await
+}
+
+async fn runs() -> Json<Vec<RunStatus>> {
+  Json(list_runs())
+}
+
+/// 定时运行：录制指定时长后自动停止、汇总并导出
+async fn start_run(Json(config): Json<TimedRunConfig>) -> Json<RunStatus> {
+  Json(start_timed_run(config))
+}
+
+async fn cancel(Path(id): Path<u64>) -> ApiResult<()> {
+  cancel_run(id).map_err(|e| ApiError(StatusCode::NOT_FOUND, e.to_string()))?;
+  Ok(Json(()))
+}
+
+#[derive(Debug, Deserialize)]
+struct ScenarioQuery {
+  device_id: Option<String>,
+}
+
+/// 请求体为 YAML 或 JSON 场景定义，`?device_id=` 可覆盖场景中的设备
+async fn scenario(Query(query): Query<ScenarioQuery>, body: String) -> ApiResult<RunStatus> {
+  let scenario =
+    parse_scenario(&body).map_err(|e| ApiError(StatusCode::BAD_REQUEST, e.to_string()))?;
+  start_scenario(scenario, query.device_id)
+    .map(Json)
+    .map_err(|e| ApiError(StatusCode::BAD_REQUEST, e.to_string()))
+}
+
+#[derive(Debug, Deserialize)]
+struct LiveQuery {
+  session_id: Option<i64>,
+}
+
+/// 实时推送采样、事件与异常，连接时先补发录制中会话的已有采样；可按 `session_id` 过滤
+async fn live(ws: WebSocketUpgrade, Query(query): Query<LiveQuery>) -> Response {
+  ws.on_upgrade(move |socket| forward(socket, query.session_id))
+}
+
+async fn forward(mut socket: WebSocket, session_id: Option<i64>) {
+  // 先订阅再补发历史，避免两者之间的采样丢失；已随历史补发的采样不再重复推送
+  let mut frames = subscribe();
+  let mut replayed = HashMap::new();
+  for frame in history_frames(session_id) {
+    if let Some(t_ms) = frame.t_ms {
+      replayed.insert(frame.session_id, t_ms);
+    }
+    if socket
+      .send(Message::Text(frame.text.as_ref().into()))
+      .await
+      .is_err()
+    {
+      return;
+    }
+  }
+  loop {
+    tokio::select! {
+      frame = frames.recv() => match frame {
+        Ok(frame) => {
+          if session_id.is_some_and(|s| s != frame.session_id) {
+            continue;
+          }
+          let last = replayed.get(&frame.session_id);
+          if frame.t_ms.zip(last).is_some_and(|(t, last)| t <= *last) {
+            continue;
+          }
+          if socket.send(Message::Text(frame.text.as_ref().into())).await.is_err() {
+            break;
+          }
+        }
+        Err(RecvError::Lagged(_)) => continue,
+        Err(RecvError::Closed) => break,
+      },
+      incoming = socket.recv() => match incoming {
+        Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+        _ => {}
+      },
+    }
+  }
+}
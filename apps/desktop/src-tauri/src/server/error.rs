@@ -0,0 +1,43 @@
+use crate::i18n::{detail, Localized};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ServerError {
+  #[error("控制服务已在运行（端口 {0}）")]
+  AlreadyRunning(u16),
+  #[error("控制服务未运行")]
+  NotRunning,
+  #[error("监听端口失败: {0}")]
+  Bind(#[from] std::io::Error),
+  #[error("连接远端实例失败: {0}")]
+  Remote(String),
+  #[error("未连接远端实例")]
+  RemoteNotConnected,
+  #[error("生成访问令牌失败: {0}")]
+  Token(String),
+}
+
+pub type Result<T> = std::result::Result<T, ServerError>;
+
+impl Localized for ServerError {
+  fn code(&self) -> &'static str {
+    match self {
+      Self::AlreadyRunning(_) => "SERVER_ALREADY_RUNNING",
+      Self::NotRunning => "SERVER_NOT_RUNNING",
+      Self::Bind(_) => "SERVER_BIND",
+      Self::Remote(_) => "REMOTE_CONNECT",
+      Self::RemoteNotConnected => "REMOTE_NOT_CONNECTED",
+      Self::Token(_) => "SERVER_TOKEN",
+    }
+  }
+
+  fn params(&self) -> Value {
+    match self {
+      Self::AlreadyRunning(port) => json!({ "port": port }),
+      Self::Bind(e) => detail(e),
+      Self::Remote(d) | Self::Token(d) => detail(d),
+      Self::NotRunning | Self::RemoteNotConnected => Value::Null,
+    }
+  }
+}
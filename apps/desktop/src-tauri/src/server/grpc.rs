@@ -0,0 +1,220 @@
+//! gRPC 控制服务，接口定义见仓库根目录 `proto/perfx/v1/control.proto`
+
+use crate::{
+  adb::{list_devices, MetricKey},
+  server::{
+    live::{subscribe_samples, SampleFrame},
+    token_matches,
+  },
+  session::{
+    error::SessionError,
+    recorder::{
+      active_recording, list_recordings, mark_recording, start_recording, stop_recording,
+      RecordingConfig,
+    },
+  },
+};
+use std::{pin::Pin, sync::Arc};
+use tauri::async_runtime::spawn_blocking;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+use tonic::{service::Interceptor, Request, Response, Status};
+
+#[allow(clippy::all)]
+pub mod proto {
+  tonic::include_proto!("perfx.v1");
+}
+
+use proto::control_server::{Control, ControlServer};
+
+impl From<SessionError> for Status {
+  fn from(e: SessionError) -> Self {
+    match e {
+      SessionError::NotFound(_)
+      | SessionError::NotRecording(_)
+      | SessionError::NoRecordingOnDevice(_)
+      | SessionError::NoActiveRecording => Status::not_found(e.to_string()),
+      SessionError::AmbiguousRecording(_) => Status::failed_precondition(e.to_string()),
+      SessionError::Adb(_) => Status::unavailable(e.to_string()),
+      _ => Status::internal(e.to_string()),
+    }
+  }
+}
+
+/// 在阻塞线程池中执行会话 / adb 操作
+async fn blocking<T: Send + 'static>(
+  f: impl FnOnce() -> Result<T, Status> + Send + 'static,
+) -> Result<Response<T>, Status> {
+  spawn_blocking(f)
+    .await
+    .map_err(|e| Status::internal(e.to_string()))?
+    .map(Response::new)
+}
+
+/// 校验 `authorization: Bearer <token>` 元数据
+#[derive(Clone)]
+pub struct TokenCheck(Arc<str>);
+
+impl Interceptor for TokenCheck {
+  fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+    let provided = request
+      .metadata()
+      .get("authorization")
+      .and_then(|v| v.to_str().ok())
+      .and_then(|v| v.strip_prefix("Bearer "));
+    if !token_matches(provided, &self.0) {
+      return Err(Status::unauthenticated("令牌无效"));
+    }
+    Ok(request)
+  }
+}
+
+pub fn service(
+  token: Arc<str>,
+) -> tonic::service::interceptor::InterceptedService<ControlServer<ControlService>, TokenCheck> {
+  ControlServer::with_interceptor(ControlService, TokenCheck(token))
+}
+
+pub struct ControlService;
+
+type SampleStream = Pin<Box<dyn Stream<Item = Result<proto::Sample, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl Control for ControlService {
+  async fn list_devices(
+    &self,
+    _request: Request<proto::ListDevicesRequest>,
+  ) -> Result<Response<proto::ListDevicesResponse>, Status> {
+    blocking(|| {
+      let devices = list_devices().map_err(|e| Status::unavailable(e.to_string()))?;
+      Ok(proto::ListDevicesResponse {
+        devices: devices
+          .into_iter()
+          .map(|d| proto::Device {
+            id: d.id,
+            model: d.model,
+            state: d.state,
+          })
+          .collect(),
+      })
+    })
+    .await
+  }
+
+  async fn list_recordings(
+    &self,
+    _request: Request<proto::ListRecordingsRequest>,
+  ) -> Result<Response<proto::ListRecordingsResponse>, Status> {
+    let recordings = list_recordings()
+      .into_iter()
+      .map(|r| proto::Recording {
+        session_id: r.session_id,
+        device_id: r.device_id,
+        package: r.package,
+        start_time: r.start_time,
+        elapsed_ms: r.elapsed_ms,
+        sample_count: r.sample_count as u64,
+      })
+      .collect();
+    Ok(Response::new(proto::ListRecordingsResponse { recordings }))
+  }
+
+  async fn start_recording(
+    &self,
+    request: Request<proto::StartRecordingRequest>,
+  ) -> Result<Response<proto::StartRecordingResponse>, Status> {
+    let request = request.into_inner();
+    let metrics = if request.metrics.is_empty() {
+      vec![MetricKey::Fps, MetricKey::Cpu, MetricKey::Memory]
+    } else {
+      request
+        .metrics
+        .iter()
+        .map(|m| m.parse())
+        .collect::<Result<_, _>>()
+        .map_err(|e: crate::adb::error::AdbError| Status::invalid_argument(e.to_string()))?
+    };
+    let options = match request.options_json.as_deref() {
+      Some(json) => serde_json::from_str(json)
+        .map_err(|e| Status::invalid_argument(format!("options_json: {e}")))?,
+      None => Default::default(),
+    };
+    let config = RecordingConfig {
+      device_id: request.device_id,
+      package: request.package,
+      metrics,
+      name: request.name,
+      options,
+    };
+    blocking(move || {
+      Ok(proto::StartRecordingResponse {
+        session_id: start_recording(config)?,
+      })
+    })
+    .await
+  }
+
+  async fn stop_recording(
+    &self,
+    request: Request<proto::StopRecordingRequest>,
+  ) -> Result<Response<proto::SessionSummary>, Status> {
+    let session_id = request.into_inner().session_id;
+    blocking(move || {
+      let session = stop_recording(session_id)?;
+      Ok(proto::SessionSummary {
+        session_id: session.id,
+        name: session.name,
+        device_id: session.device_id,
+        package: session.app_package,
+        start_time: session.start_time,
+        end_time: session.end_time,
+        duration: session.duration,
+      })
+    })
+    .await
+  }
+
+  async fn add_marker(
+    &self,
+    request: Request<proto::AddMarkerRequest>,
+  ) -> Result<Response<proto::Marker>, Status> {
+    let request = request.into_inner();
+    blocking(move || {
+      let session_id = match request.session_id {
+        Some(id) => id,
+        None => active_recording(request.device_id.as_deref())?,
+      };
+      let marker = mark_recording(session_id, &request.name)?;
+      Ok(proto::Marker {
+        id: marker.id,
+        session_id,
+        t_ms: marker.t_ms,
+        name: marker.name,
+      })
+    })
+    .await
+  }
+
+  type StreamSamplesStream = SampleStream;
+
+  async fn stream_samples(
+    &self,
+    request: Request<proto::StreamSamplesRequest>,
+  ) -> Result<Response<Self::StreamSamplesStream>, Status> {
+    let filter = request.into_inner().session_id;
+    // 跟不上时跳过丢失的帧，继续推送最新采样
+    let stream = BroadcastStream::new(subscribe_samples())
+      .filter_map(|frame| frame.ok())
+      .take_while(move |frame| !matches!(frame, SampleFrame::Stopped(id) if Some(*id) == filter))
+      .filter_map(move |frame| match frame {
+        SampleFrame::Sample(session_id, sample) if filter.map_or(true, |id| id == session_id) => {
+          Some(Ok(proto::Sample {
+            session_id,
+            t_ms: sample.t_ms,
+            values: sample.values.into_iter().collect(),
+          }))
+        }
+        _ => None,
+      });
+    Ok(Response::new(Box::pin(stream)))
+  }
+}
@@ -0,0 +1,161 @@
+use crate::{
+  adb::MetricsSnapshot,
+  session::{
+    anomaly::Anomaly,
+    events::SessionEvent,
+    recorder::{
+      add_observer, list_recordings, recording_samples, RecordingObserver, RecordingStatus,
+    },
+    Sample,
+  },
+};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+// 客户端处理不过来时丢弃最旧的帧，不阻塞采样线程
+const CHANNEL_CAPACITY: usize = 256;
+
+/// 推送给 WebSocket 客户端的一帧，与前端收到的 Tauri 事件内容一致
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum LiveFrame<'a> {
+  Sample {
+    session_id: i64,
+    sample: &'a Sample,
+    snapshot: &'a MetricsSnapshot,
+  },
+  Event {
+    session_id: i64,
+    event: &'a SessionEvent,
+  },
+  Anomaly {
+    session_id: i64,
+    anomaly: &'a Anomaly,
+  },
+  Stopped {
+    session_id: i64,
+  },
+  /// 连接建立时补发录制中会话已有的采样
+  History {
+    session_id: i64,
+    recording: &'a RecordingStatus,
+    samples: &'a [Sample],
+  },
+}
+
+/// 已序列化的帧及其所属会话，便于按会话过滤
+#[derive(Debug, Clone)]
+pub struct Frame {
+  pub session_id: i64,
+  /// 采样帧为该采样的时间偏移，历史帧为其中最后一个采样的偏移
+  pub t_ms: Option<u64>,
+  pub text: Arc<str>,
+}
+
+static FRAMES: Lazy<broadcast::Sender<Frame>> =
+  Lazy::new(|| broadcast::channel(CHANNEL_CAPACITY).0);
+
+/// 未序列化的采样，供 gRPC 等需要自行编码的订阅方使用
+#[derive(Debug, Clone)]
+pub enum SampleFrame {
+  Sample(i64, Sample),
+  Stopped(i64),
+}
+
+static SAMPLES: Lazy<broadcast::Sender<SampleFrame>> =
+  Lazy::new(|| broadcast::channel(CHANNEL_CAPACITY).0);
+
+pub fn subscribe() -> broadcast::Receiver<Frame> {
+  FRAMES.subscribe()
+}
+
+/// 录制中会话的历史帧，可按会话过滤
+pub fn history_frames(session_id: Option<i64>) -> Vec<Frame> {
+  list_recordings()
+    .iter()
+    .filter(|r| session_id.map_or(true, |id| id == r.session_id))
+    .filter_map(|recording| {
+      let samples = recording_samples(recording.session_id)?;
+      let frame = LiveFrame::History {
+        session_id: recording.session_id,
+        recording,
+        samples: &samples,
+      };
+      let text = serde_json::to_string(&frame).ok()?;
+      Some(Frame {
+        session_id: recording.session_id,
+        t_ms: samples.last().map(|s| s.t_ms),
+        text: text.into(),
+      })
+    })
+    .collect()
+}
+
+pub fn subscribe_samples() -> broadcast::Receiver<SampleFrame> {
+  SAMPLES.subscribe()
+}
+
+fn publish_sample(frame: SampleFrame) {
+  if SAMPLES.receiver_count() > 0 {
+    let _ = SAMPLES.send(frame);
+  }
+}
+
+fn publish(session_id: i64, frame: LiveFrame) {
+  let t_ms = match &frame {
+    LiveFrame::Sample { sample, .. } => Some(sample.t_ms),
+    _ => None,
+  };
+  // 没有订阅者时不序列化
+  if FRAMES.receiver_count() == 0 {
+    return;
+  }
+  if let Ok(text) = serde_json::to_string(&frame) {
+    let _ = FRAMES.send(Frame {
+      session_id,
+      t_ms,
+      text: text.into(),
+    });
+  }
+}
+
+struct LiveBroadcaster;
+
+impl RecordingObserver for LiveBroadcaster {
+  fn on_sample(&self, session_id: i64, sample: &Sample, snapshot: &MetricsSnapshot) {
+    publish_sample(SampleFrame::Sample(session_id, sample.clone()));
+    publish(
+      session_id,
+      LiveFrame::Sample {
+        session_id,
+        sample,
+        snapshot,
+      },
+    );
+  }
+
+  fn on_event(&self, session_id: i64, event: &SessionEvent) {
+    publish(session_id, LiveFrame::Event { session_id, event });
+  }
+
+  fn on_anomaly(&self, session_id: i64, anomaly: &Anomaly) {
+    publish(
+      session_id,
+      LiveFrame::Anomaly {
+        session_id,
+        anomaly,
+      },
+    );
+  }
+
+  fn on_stopped(&self, session_id: i64) {
+    publish_sample(SampleFrame::Stopped(session_id));
+    publish(session_id, LiveFrame::Stopped { session_id });
+  }
+}
+
+pub fn register() {
+  add_observer(Arc::new(LiveBroadcaster));
+}
@@ -0,0 +1,225 @@
+//! 可选的本地 HTTP / WebSocket 控制服务，供外部测试框架、看板驱动与观察录制
+
+use crate::server::error::{Result, ServerError};
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+  net::{Ipv4Addr, SocketAddr},
+  sync::{Arc, Mutex},
+};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::transport::server::TcpIncoming;
+
+mod api;
+pub mod error;
+pub mod grpc;
+pub mod live;
+pub mod prometheus;
+pub mod remote;
+
+pub const DEFAULT_PORT: u16 = 7391;
+
+fn default_port() -> u16 {
+  DEFAULT_PORT
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerConfig {
+  #[serde(default = "default_port")]
+  pub port: u16,
+  /// 访问令牌，不填时随机生成
+  #[serde(default)]
+  pub token: Option<String>,
+  /// 只读观看令牌，不填时随机生成
+  #[serde(default)]
+  pub viewer_token: Option<String>,
+  /// 监听所有网卡；默认只监听本机
+  #[serde(default)]
+  pub allow_lan: bool,
+  /// 同时在该端口提供 gRPC 服务，不填则不启用
+  #[serde(default)]
+  pub grpc_port: Option<u16>,
+  /// 提供 Prometheus 抓取用的 `/metrics`，观看令牌即可访问
+  #[serde(default)]
+  pub prometheus: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerStatus {
+  pub port: u16,
+  pub token: String,
+  pub viewer_token: String,
+  pub allow_lan: bool,
+  #[serde(default)]
+  pub grpc_port: Option<u16>,
+  #[serde(default)]
+  pub prometheus: bool,
+}
+
+struct RunningServer {
+  status: ServerStatus,
+  shutdown: Vec<oneshot::Sender<()>>,
+}
+
+enum ServerSlot {
+  Idle,
+  /// 已占位、正在绑定端口，记录请求的端口
+  Starting(u16),
+  Running(RunningServer),
+}
+
+static SERVER: Lazy<Mutex<ServerSlot>> = Lazy::new(|| Mutex::new(ServerSlot::Idle));
+
+/// 启动期间的占位，未转为运行状态就释放
+struct Reservation {
+  armed: bool,
+}
+
+impl Drop for Reservation {
+  fn drop(&mut self) {
+    if !self.armed {
+      return;
+    }
+    if let Ok(mut slot) = SERVER.lock() {
+      if matches!(*slot, ServerSlot::Starting(_)) {
+        *slot = ServerSlot::Idle;
+      }
+    }
+  }
+}
+
+pub async fn start_server(config: ServerConfig) -> Result<ServerStatus> {
+  // 检查与占位在同一把锁内完成，并发启动时只有一个能继续
+  let mut reservation = {
+    let mut slot = SERVER.lock().unwrap_or_else(|e| e.into_inner());
+    match &*slot {
+      ServerSlot::Starting(port) => return Err(ServerError::AlreadyRunning(*port)),
+      ServerSlot::Running(running) => return Err(ServerError::AlreadyRunning(running.status.port)),
+      ServerSlot::Idle => *slot = ServerSlot::Starting(config.port),
+    }
+    Reservation { armed: true }
+  };
+
+  let host = if config.allow_lan {
+    Ipv4Addr::UNSPECIFIED
+  } else {
+    Ipv4Addr::LOCALHOST
+  };
+  let listener = TcpListener::bind(SocketAddr::from((host, config.port))).await?;
+  let port = listener.local_addr()?.port();
+  // 两个端口都绑定成功后再启动，避免只起了一半
+  let grpc_listener = match config.grpc_port {
+    Some(grpc_port) => Some(TcpListener::bind(SocketAddr::from((host, grpc_port))).await?),
+    None => None,
+  };
+  let grpc_port = grpc_listener
+    .as_ref()
+    .map(|l| l.local_addr().map(|a| a.port()))
+    .transpose()?;
+  let token: Arc<str> = config
+    .token
+    .filter(|t| !t.trim().is_empty())
+    .map_or_else(random_token, Ok)?
+    .into();
+  let viewer_token: Arc<str> = config
+    .viewer_token
+    .filter(|t| !t.trim().is_empty())
+    .map_or_else(random_token, Ok)?
+    .into();
+  let status = ServerStatus {
+    port,
+    token: token.to_string(),
+    viewer_token: viewer_token.to_string(),
+    allow_lan: config.allow_lan,
+    grpc_port,
+    prometheus: config.prometheus,
+  };
+
+  let mut shutdown = Vec::new();
+  let (tx, stopped) = oneshot::channel();
+  shutdown.push(tx);
+  let app = api::router(
+    api::Tokens {
+      control: token.clone(),
+      viewer: viewer_token,
+    },
+    config.prometheus,
+  );
+  tauri::async_runtime::spawn(async move {
+    let serve = axum::serve(listener, app).with_graceful_shutdown(async {
+      let _ = stopped.await;
+    });
+    if let Err(e) = serve.await {
+      warn!("控制服务异常退出: {}", e);
+    }
+  });
+  if let Some(listener) = grpc_listener {
+    let (tx, stopped) = oneshot::channel();
+    shutdown.push(tx);
+    let service = grpc::service(token);
+    tauri::async_runtime::spawn(async move {
+      let serve = tonic::transport::Server::builder()
+        .add_service(service)
+        .serve_with_incoming_shutdown(TcpIncoming::from(listener), async {
+          let _ = stopped.await;
+        });
+      if let Err(e) = serve.await {
+        warn!("gRPC 服务异常退出: {}", e);
+      }
+    });
+  }
+
+  *SERVER.lock().unwrap_or_else(|e| e.into_inner()) = ServerSlot::Running(RunningServer {
+    status: status.clone(),
+    shutdown,
+  });
+  reservation.armed = false;
+  info!("控制服务已启动，端口 {}", port);
+  Ok(status)
+}
+
+pub fn stop_server() -> Result<()> {
+  let running = {
+    let mut slot = SERVER.lock().unwrap_or_else(|e| e.into_inner());
+    if !matches!(*slot, ServerSlot::Running(_)) {
+      return Err(ServerError::NotRunning);
+    }
+    match std::mem::replace(&mut *slot, ServerSlot::Idle) {
+      ServerSlot::Running(running) => running,
+      _ => unreachable!(),
+    }
+  };
+  for shutdown in running.shutdown {
+    let _ = shutdown.send(());
+  }
+  info!("控制服务已停止");
+  Ok(())
+}
+
+pub fn server_status() -> Option<ServerStatus> {
+  match &*SERVER.lock().ok()? {
+    ServerSlot::Running(running) => Some(running.status.clone()),
+    _ => None,
+  }
+}
+
+/// 取自系统随机源的 128 位令牌
+fn random_token() -> Result<String> {
+  let mut bytes = [0u8; 16];
+  getrandom::fill(&mut bytes).map_err(|e| ServerError::Token(e.to_string()))?;
+  Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// 比较令牌，耗时与首个不同字节的位置无关，避免按响应时间逐字节猜测
+pub(crate) fn token_matches(provided: Option<&str>, expected: &str) -> bool {
+  let Some(provided) = provided else {
+    return false;
+  };
+  provided.len() == expected.len()
+    && provided
+      .bytes()
+      .zip(expected.bytes())
+      .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+      == 0
+}
@@ -0,0 +1,80 @@
+//! 以 Prometheus 文本格式输出录制中会话的最新采样，供实验室的 Grafana 直接抓取
+
+use crate::session::recorder::{latest_sample, list_recordings};
+use std::{collections::BTreeMap, fmt::Write};
+
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// 指标键转为合法的 Prometheus 指标名
+fn metric_name(key: &str) -> String {
+  let name: String = key
+    .chars()
+    .map(|c| {
+      if c.is_ascii_alphanumeric() {
+        c.to_ascii_lowercase()
+      } else {
+        '_'
+      }
+    })
+    .collect();
+  format!("perfx_{name}")
+}
+
+fn escape_label(value: &str) -> String {
+  value
+    .replace('\\', "\\\\")
+    .replace('"', "\\\"")
+    .replace('\n', "\\n")
+}
+
+/// 文本格式要求非有限值写作 `+Inf`、`-Inf`、`NaN`
+fn format_value(value: f64) -> String {
+  if value.is_nan() {
+    "NaN".to_string()
+  } else if value.is_infinite() {
+    if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+  } else {
+    value.to_string()
+  }
+}
+
+/// 每个录制中会话一组标签，同名指标归到同一个 gauge 下
+pub fn render() -> String {
+  let recordings = list_recordings();
+  let mut gauges: BTreeMap<String, Vec<(String, f64)>> = BTreeMap::new();
+  let mut out = String::new();
+  let _ = writeln!(
+    out,
+    "# HELP perfx_recording_elapsed_seconds 录制已进行的时长"
+  );
+  let _ = writeln!(out, "# TYPE perfx_recording_elapsed_seconds gauge");
+  for recording in &recordings {
+    let labels = format!(
+      "device=\"{}\",package=\"{}\",session_id=\"{}\"",
+      escape_label(&recording.device_id),
+      escape_label(&recording.package),
+      recording.session_id
+    );
+    let _ = writeln!(
+      out,
+      "perfx_recording_elapsed_seconds{{{labels}}} {}",
+      recording.elapsed_ms as f64 / 1000.0
+    );
+    let Some(sample) = latest_sample(recording.session_id) else {
+      continue;
+    };
+    for (key, value) in sample.values {
+      gauges
+        .entry(metric_name(&key))
+        .or_default()
+        .push((labels.clone(), value));
+    }
+  }
+  for (name, series) in gauges {
+    let _ = writeln!(out, "# TYPE {name} gauge");
+    for (labels, value) in series {
+      let _ = writeln!(out, "{name}{{{labels}}} {}", format_value(value));
+    }
+  }
+  out
+}
@@ -0,0 +1,122 @@
+//! 以观看令牌连接另一台实例的控制服务，只读接收其实时采样
+
+use crate::server::error::{Result, ServerError};
+use futures_util::{SinkExt, StreamExt};
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tokio::sync::oneshot;
+use tokio_tungstenite::tungstenite::{client::IntoClientRequest, http::HeaderValue, Message};
+
+// 连接断开时推给回调的帧，与远端的帧格式一致
+const DISCONNECTED_FRAME: &str = r#"{"type":"disconnected"}"#;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteConfig {
+  /// 远端地址，如 `192.168.1.20:7391` 或 `http://192.168.1.20:7391`
+  pub address: String,
+  /// 远端的观看令牌（控制令牌也可）
+  pub token: String,
+  /// 只观看指定会话
+  #[serde(default)]
+  pub session_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteStatus {
+  pub address: String,
+  pub session_id: Option<i64>,
+}
+
+struct RunningRemote {
+  status: RemoteStatus,
+  shutdown: oneshot::Sender<()>,
+}
+
+static REMOTE: Lazy<Mutex<Option<RunningRemote>>> = Lazy::new(|| Mutex::new(None));
+
+fn live_url(config: &RemoteConfig) -> String {
+  let host = config
+    .address
+    .trim()
+    .trim_start_matches("http://")
+    .trim_start_matches("ws://")
+    .trim_end_matches('/');
+  match config.session_id {
+    Some(id) => format!("ws://{host}/api/live?session_id={id}"),
+    None => format!("ws://{host}/api/live"),
+  }
+}
+
+/// 连接远端并把收到的每一帧 JSON 交给回调，断开时回调收到 `{"type":"disconnected"}`；
+/// 同一时间只保留一个远端连接
+pub async fn connect_remote(
+  config: RemoteConfig,
+  on_frame: impl Fn(&str) + Send + 'static,
+) -> Result<RemoteStatus> {
+  let _ = disconnect_remote();
+  let mut request = live_url(&config)
+    .into_client_request()
+    .map_err(|e| ServerError::Remote(e.to_string()))?;
+  let auth = HeaderValue::from_str(&format!("Bearer {}", config.token))
+    .map_err(|e| ServerError::Remote(e.to_string()))?;
+  request.headers_mut().insert("Authorization", auth);
+  let (socket, _) = tokio_tungstenite::connect_async(request)
+    .await
+    .map_err(|e| ServerError::Remote(e.to_string()))?;
+
+  let status = RemoteStatus {
+    address: config.address.clone(),
+    session_id: config.session_id,
+  };
+  let (shutdown, mut stopped) = oneshot::channel();
+  if let Ok(mut remote) = REMOTE.lock() {
+    *remote = Some(RunningRemote {
+      status: status.clone(),
+      shutdown,
+    });
+  }
+  info!("已连接远端实例 {}", config.address);
+
+  tauri::async_runtime::spawn(async move {
+    let (mut sink, mut stream) = socket.split();
+    loop {
+      tokio::select! {
+        _ = &mut stopped => {
+          let _ = sink.send(Message::Close(None)).await;
+          break;
+        }
+        message = stream.next() => match message {
+          Some(Ok(Message::Text(text))) => on_frame(text.as_str()),
+          Some(Ok(Message::Close(_))) | None => break,
+          Some(Err(e)) => {
+            warn!("远端连接中断: {}", e);
+            break;
+          }
+          _ => {}
+        },
+      }
+    }
+    on_frame(DISCONNECTED_FRAME);
+    info!("已断开远端实例 {}", config.address);
+  });
+  Ok(status)
+}
+
+pub fn disconnect_remote() -> Result<()> {
+  let running = REMOTE
+    .lock()
+    .ok()
+    .and_then(|mut r| r.take())
+    .ok_or(ServerError::RemoteNotConnected)?;
+  let _ = running.shutdown.send(());
+  Ok(())
+}
+
+pub fn remote_status() -> Option<RemoteStatus> {
+  REMOTE
+    .lock()
+    .ok()
+    .and_then(|r| r.as_ref().map(|r| r.status.clone()))
+}
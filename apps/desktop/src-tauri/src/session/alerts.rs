@@ -0,0 +1,249 @@
+use crate::session::{
+  error::{Result, SessionError},
+  metric_label,
+  store::open,
+  Sample,
+};
+use log::warn;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// 规则增删改后递增，录制中的评估器据此重新加载
+static RULES_REVISION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+  Lt,
+  Le,
+  Gt,
+  Ge,
+}
+
+impl Comparison {
+  fn as_str(self) -> &'static str {
+    match self {
+      Self::Lt => "lt",
+      Self::Le => "le",
+      Self::Gt => "gt",
+      Self::Ge => "ge",
+    }
+  }
+
+  fn parse(value: &str) -> Option<Self> {
+    match value {
+      "lt" => Some(Self::Lt),
+      "le" => Some(Self::Le),
+      "gt" => Some(Self::Gt),
+      "ge" => Some(Self::Ge),
+      _ => None,
+    }
+  }
+
+  fn symbol(self) -> &'static str {
+    match self {
+      Self::Lt => "<",
+      Self::Le => "≤",
+      Self::Gt => ">",
+      Self::Ge => "≥",
+    }
+  }
+
+  fn matches(self, value: f64, threshold: f64) -> bool {
+    match self {
+      Self::Lt => value < threshold,
+      Self::Le => value <= threshold,
+      Self::Gt => value > threshold,
+      Self::Ge => value >= threshold,
+    }
+  }
+}
+
+/// 阈值告警规则，如 “FPS < 30 持续 5 秒”
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRule {
+  #[serde(default)]
+  pub id: i64,
+  #[serde(default)]
+  pub name: String,
+  pub metric: String,
+  pub op: Comparison,
+  pub threshold: f64,
+  /// 条件需持续的时长，0 表示单个采样即触发
+  #[serde(default)]
+  pub duration_ms: u64,
+  #[serde(default = "default_enabled")]
+  pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+  true
+}
+
+impl AlertRule {
+  /// 规则的可读描述，用于事件消息
+  pub fn describe(&self) -> String {
+    let (label, unit) = metric_label(&self.metric);
+    let mut text = format!("{} {} {}{}", label, self.op.symbol(), self.threshold, unit);
+    if self.duration_ms > 0 {
+      text.push_str(&format!(" 持续 {}s", self.duration_ms as f64 / 1000.0));
+    }
+    if self.name.is_empty() {
+      text
+    } else {
+      format!("{}（{}）", self.name, text)
+    }
+  }
+}
+
+pub fn list_rules() -> Result<Vec<AlertRule>> {
+  let conn = open()?;
+  let mut stmt = conn.prepare(
+    "SELECT id, name, metric, op, threshold, duration_ms, enabled FROM alert_rules ORDER BY id",
+  )?;
+  let rows = stmt.query_map([], |row| {
+    Ok((
+      row.get::<_, i64>(0)?,
+      row.get::<_, String>(1)?,
+      row.get::<_, String>(2)?,
+      row.get::<_, String>(3)?,
+      row.get::<_, f64>(4)?,
+      row.get::<_, i64>(5)?,
+      row.get::<_, bool>(6)?,
+    ))
+  })?;
+
+  let mut rules = Vec::new();
+  for row in rows {
+    let (id, name, metric, op, threshold, duration_ms, enabled) = row?;
+    let Some(op) = Comparison::parse(&op) else {
+      continue;
+    };
+    rules.push(AlertRule {
+      id,
+      name,
+      metric,
+      op,
+      threshold,
+      duration_ms: duration_ms.max(0) as u64,
+      enabled,
+    });
+  }
+  Ok(rules)
+}
+
+/// 新建（id 为 0）或更新规则，返回保存后的规则
+pub fn save_rule(rule: &AlertRule) -> Result<AlertRule> {
+  if rule.metric.trim().is_empty() || !rule.threshold.is_finite() {
+    return Err(SessionError::Format("告警规则缺少指标或阈值".into()));
+  }
+  let conn = open()?;
+  let id = if rule.id > 0 {
+    let updated = conn.execute(
+      "UPDATE alert_rules SET name = ?2, metric = ?3, op = ?4, threshold = ?5,
+         duration_ms = ?6, enabled = ?7
+       WHERE id = ?1",
+      params![
+        rule.id,
+        rule.name,
+        rule.metric,
+        rule.op.as_str(),
+        rule.threshold,
+        rule.duration_ms as i64,
+        rule.enabled,
+      ],
+    )?;
+    if updated == 0 {
+      return Err(SessionError::Format(format!("告警规则不存在: {}", rule.id)));
+    }
+    rule.id
+  } else {
+    conn.execute(
+      "INSERT INTO alert_rules (name, metric, op, threshold, duration_ms, enabled)
+       VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+      params![
+        rule.name,
+        rule.metric,
+        rule.op.as_str(),
+        rule.threshold,
+        rule.duration_ms as i64,
+        rule.enabled,
+      ],
+    )?;
+    conn.last_insert_rowid()
+  };
+  RULES_REVISION.fetch_add(1, Ordering::Relaxed);
+  Ok(AlertRule { id, ..rule.clone() })
+}
+
+pub fn delete_rule(id: i64) -> Result<()> {
+  let conn = open()?;
+  conn.execute("DELETE FROM alert_rules WHERE id = ?1", params![id])?;
+  RULES_REVISION.fetch_add(1, Ordering::Relaxed);
+  Ok(())
+}
+
+#[derive(Debug, Default)]
+struct RuleState {
+  since_ms: Option<u64>,
+  tripped: bool,
+}
+
+/// 录制中逐个采样评估规则；条件持续满足到设定时长时触发一次，条件解除后重新布防
+pub struct AlertEvaluator {
+  revision: u64,
+  rules: Vec<(AlertRule, RuleState)>,
+}
+
+impl Default for AlertEvaluator {
+  fn default() -> Self {
+    let mut evaluator = Self {
+      revision: u64::MAX,
+      rules: Vec::new(),
+    };
+    evaluator.reload();
+    evaluator
+  }
+}
+
+impl AlertEvaluator {
+  fn reload(&mut self) {
+    let revision = RULES_REVISION.load(Ordering::Relaxed);
+    if revision == self.revision {
+      return;
+    }
+    self.revision = revision;
+    match list_rules() {
+      Ok(rules) => {
+        self.rules = rules
+          .into_iter()
+          .filter(|r| r.enabled)
+          .map(|r| (r, RuleState::default()))
+          .collect();
+      }
+      Err(e) => warn!("加载告警规则失败: {}", e),
+    }
+  }
+
+  /// 返回本次采样触发的规则及触发时的指标值
+  pub fn evaluate(&mut self, sample: &Sample) -> Vec<(AlertRule, f64)> {
+    self.reload();
+    let mut tripped = Vec::new();
+    for (rule, state) in &mut self.rules {
+      let Some(value) = sample.values.get(&rule.metric).copied() else {
+        continue;
+      };
+      if !rule.op.matches(value, rule.threshold) {
+        *state = RuleState::default();
+        continue;
+      }
+      let since = *state.since_ms.get_or_insert(sample.t_ms);
+      if !state.tripped && sample.t_ms.saturating_sub(since) >= rule.duration_ms {
+        state.tripped = true;
+        tripped.push((rule.clone(), value));
+      }
+    }
+    tripped
+  }
+}
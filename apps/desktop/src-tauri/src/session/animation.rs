@@ -0,0 +1,32 @@
+//! 手动调整系统动画缩放：首次修改前把原值写入数据库，退出应用时自动恢复；
+//! 应用崩溃未能恢复的记录在下次启动时处理
+
+use crate::{
+  adb::settings::{animation_scales, set_animation_scales, AnimationScales},
+  session::{error::Result, restore::DeviceSetting},
+};
+
+static SAVED: DeviceSetting<AnimationScales> = DeviceSetting::new("animation_restore:", "动画缩放");
+
+pub fn get_animation_scales(device_id: &str) -> Result<AnimationScales> {
+  Ok(animation_scales(device_id)?)
+}
+
+/// 写入动画缩放，返回写入后的值
+pub fn set_animations(device_id: &str, scales: &AnimationScales) -> Result<AnimationScales> {
+  SAVED.save_original(device_id, || Ok(animation_scales(device_id)?))?;
+  set_animation_scales(device_id, scales)?;
+  Ok(animation_scales(device_id)?)
+}
+
+/// 恢复修改前的值；没有修改过时返回 false
+pub fn restore_animations(device_id: &str) -> Result<bool> {
+  SAVED.restore(device_id, |original| {
+    Ok(set_animation_scales(device_id, original)?)
+  })
+}
+
+/// 恢复所有设备的动画缩放；设备不在线的记录保留到下次
+pub fn restore_all_animations() {
+  SAVED.restore_all(|_| true, restore_animations);
+}
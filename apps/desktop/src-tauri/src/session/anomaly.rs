@@ -0,0 +1,112 @@
+use crate::session::Sample;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+// 滚动窗口长度（采样数），以及窗口未填满前不做判断的最少采样数
+const WINDOW: usize = 60;
+const MIN_HISTORY: usize = 10;
+// 稳健 z 分数超过该值视为异常，3.5 为 Iglewicz–Hoaglin 推荐值
+const THRESHOLD: f64 = 3.5;
+// MAD 换算为标准差的系数
+const MAD_SCALE: f64 = 1.4826;
+// 离散度下限（相对中位数），避免近乎恒定的序列上微小波动被放大
+const MIN_RELATIVE_SPREAD: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyDirection {
+  Spike,
+  Dip,
+}
+
+/// 被标记为异常的采样点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Anomaly {
+  pub metric: String,
+  pub t_ms: u64,
+  pub value: f64,
+  /// 判断时窗口内的中位数
+  pub baseline: f64,
+  /// 偏离程度，以稳健标准差为单位
+  pub score: f64,
+  pub direction: AnomalyDirection,
+}
+
+/// 按指标维护滚动窗口，用中位数 / MAD 计算稳健 z 分数
+///
+/// MAD 为 0 时退回到标准差，并以中位数的 1% 作为离散度下限；
+/// 窗口全为 0 时不判断。
+#[derive(Debug, Default)]
+pub struct AnomalyDetector {
+  windows: HashMap<String, VecDeque<f64>>,
+}
+
+impl AnomalyDetector {
+  /// 评估一个新采样，返回其中的异常指标，随后将该采样纳入窗口
+  pub fn push(&mut self, sample: &Sample) -> Vec<Anomaly> {
+    let mut anomalies = Vec::new();
+    for (metric, &value) in &sample.values {
+      if !value.is_finite() {
+        continue;
+      }
+      let window = self.windows.entry(metric.clone()).or_default();
+      if window.len() >= MIN_HISTORY {
+        if let Some((baseline, score)) = robust_score(window, value) {
+          if score.abs() > THRESHOLD {
+            anomalies.push(Anomaly {
+              metric: metric.clone(),
+              t_ms: sample.t_ms,
+              value,
+              baseline,
+              score,
+              direction: if score > 0.0 {
+                AnomalyDirection::Spike
+              } else {
+                AnomalyDirection::Dip
+              },
+            });
+          }
+        }
+      }
+      if window.len() == WINDOW {
+        window.pop_front();
+      }
+      window.push_back(value);
+    }
+    anomalies
+  }
+}
+
+/// 对整场会话离线检测，结果与录制时实时检测一致
+pub fn detect(samples: &[Sample]) -> Vec<Anomaly> {
+  let mut detector = AnomalyDetector::default();
+  samples.iter().flat_map(|s| detector.push(s)).collect()
+}
+
+fn robust_score(window: &VecDeque<f64>, value: f64) -> Option<(f64, f64)> {
+  let mut sorted: Vec<f64> = window.iter().copied().collect();
+  sorted.sort_by(|a, b| a.total_cmp(b));
+  let center = median(&sorted);
+
+  let mut deviations: Vec<f64> = sorted.iter().map(|v| (v - center).abs()).collect();
+  deviations.sort_by(|a, b| a.total_cmp(b));
+  let mut scale = median(&deviations) * MAD_SCALE;
+  if scale <= f64::EPSILON {
+    let mean = sorted.iter().sum::<f64>() / sorted.len() as f64;
+    scale = (sorted.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / sorted.len() as f64).sqrt();
+  }
+  let scale = scale.max(center.abs() * MIN_RELATIVE_SPREAD);
+  if scale <= f64::EPSILON {
+    return None;
+  }
+  Some((center, (value - center) / scale))
+}
+
+fn median(sorted: &[f64]) -> f64 {
+  let mid = sorted.len() / 2;
+  if sorted.len() % 2 == 0 {
+    (sorted[mid - 1] + sorted[mid]) / 2.0
+  } else {
+    sorted[mid]
+  }
+}
@@ -0,0 +1,101 @@
+use crate::session::{
+  error::{Result, SessionError},
+  store::open,
+};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// 某个 (包名, 设备型号, 场景) 组合的基线会话
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Baseline {
+  pub id: i64,
+  pub app_package: String,
+  pub device_model: String,
+  pub scenario: String,
+  pub session_id: i64,
+  pub created_at: i64,
+}
+
+/// 会话所属的 (包名, 设备型号, 场景)，未记录型号/场景时为空字符串
+fn baseline_key(conn: &Connection, session_id: i64) -> Result<(String, String, String)> {
+  conn
+    .query_row(
+      "SELECT r.app_package, COALESCE(r.device_model, ''), COALESCE(m.scenario, '')
+       FROM reports r LEFT JOIN session_meta m ON m.session_id = r.id
+       WHERE r.id = ?1",
+      params![session_id],
+      |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    )
+    .optional()?
+    .ok_or(SessionError::NotFound(session_id))
+}
+
+/// 将会话设为其组合的基线，替换已有基线
+pub fn set_baseline(session_id: i64) -> Result<Baseline> {
+  let conn = open()?;
+  let (app_package, device_model, scenario) = baseline_key(&conn, session_id)?;
+  conn.execute(
+    "INSERT INTO baselines (app_package, device_model, scenario, session_id)
+     VALUES (?1, ?2, ?3, ?4)
+     ON CONFLICT(app_package, device_model, scenario) DO UPDATE SET
+       session_id = excluded.session_id,
+       created_at = strftime('%s', 'now')",
+    params![app_package, device_model, scenario, session_id],
+  )?;
+  query_baseline(&conn, &app_package, &device_model, &scenario)?
+    .ok_or(SessionError::NotFound(session_id))
+}
+
+pub fn clear_baseline(id: i64) -> Result<()> {
+  let conn = open()?;
+  conn.execute("DELETE FROM baselines WHERE id = ?1", params![id])?;
+  Ok(())
+}
+
+pub fn list_baselines() -> Result<Vec<Baseline>> {
+  let conn = open()?;
+  let mut stmt = conn.prepare(
+    "SELECT id, app_package, device_model, scenario, session_id, created_at
+     FROM baselines ORDER BY app_package, device_model, scenario",
+  )?;
+  let baselines = stmt
+    .query_map([], baseline_from_row)?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+  Ok(baselines)
+}
+
+/// 查找与会话同组合的基线；会话自身即为基线时返回 None
+pub fn baseline_for_session(session_id: i64) -> Result<Option<Baseline>> {
+  let conn = open()?;
+  let (app_package, device_model, scenario) = baseline_key(&conn, session_id)?;
+  let baseline = query_baseline(&conn, &app_package, &device_model, &scenario)?;
+  Ok(baseline.filter(|b| b.session_id != session_id))
+}
+
+fn query_baseline(
+  conn: &Connection,
+  app_package: &str,
+  device_model: &str,
+  scenario: &str,
+) -> Result<Option<Baseline>> {
+  let baseline = conn
+    .query_row(
+      "SELECT id, app_package, device_model, scenario, session_id, created_at
+       FROM baselines WHERE app_package = ?1 AND device_model = ?2 AND scenario = ?3",
+      params![app_package, device_model, scenario],
+      baseline_from_row,
+    )
+    .optional()?;
+  Ok(baseline)
+}
+
+fn baseline_from_row(row: &rusqlite::Row) -> rusqlite::Result<Baseline> {
+  Ok(Baseline {
+    id: row.get(0)?,
+    app_package: row.get(1)?,
+    device_model: row.get(2)?,
+    scenario: row.get(3)?,
+    session_id: row.get(4)?,
+    created_at: row.get::<_, Option<i64>>(5)?.unwrap_or_default(),
+  })
+}
@@ -0,0 +1,147 @@
+use crate::session::{
+  crypto::{decrypt, is_encrypted},
+  error::{Result, SessionError},
+  events::{add_attachment, add_event, set_causes, AttachmentInfo, SessionEvent},
+  raw::{insert_raw, RawDumpRecord},
+  screenshot::{store_screenshot, ScreenshotInfo},
+  store::{insert_session_in, open},
+  SessionData,
+};
+use perfx_core::schema::{check_version, legacy_version};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+  collections::HashMap,
+  fs,
+  io::{Cursor, Read, Seek},
+  path::Path,
+};
+use zip::ZipArchive;
+
+/// .perfx 会话包：zip 内包含以下条目，附件与截图按 id 存为单独文件
+pub const BUNDLE_FORMAT: &str = "perfx";
+pub const BUNDLE_VERSION: u32 = 1;
+
+pub const MANIFEST_ENTRY: &str = "manifest.json";
+pub const SESSION_ENTRY: &str = "session.json";
+pub const EVENTS_ENTRY: &str = "events.json";
+pub const ATTACHMENTS_ENTRY: &str = "attachments.json";
+pub const SCREENSHOTS_ENTRY: &str = "screenshots.json";
+pub const RAW_ENTRY: &str = "raw.json";
+
+pub fn attachment_entry(info: &AttachmentInfo) -> String {
+  format!("attachments/{}-{}", info.id, info.name)
+}
+
+pub fn screenshot_entry(info: &ScreenshotInfo) -> String {
+  format!("screenshots/{}.jpg", info.id)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+  pub format: String,
+  pub version: u32,
+  pub exported_at: i64,
+  /// 导出时的 PerfX 版本
+  pub generator: String,
+  /// 其中会话数据的格式版本
+  #[serde(default = "legacy_version")]
+  pub schema_version: u32,
+}
+
+/// 导入 .perfx 会话包，会话及其事件、附件、截图、原始输出作为新会话写入
+///
+/// 加密的会话包需提供导出时设置的口令。
+pub fn import_bundle(path: &Path, name: Option<String>, passphrase: Option<&str>) -> Result<i64> {
+  let mut content = fs::read(path)?;
+  if is_encrypted(&content) {
+    let passphrase = passphrase
+      .filter(|p| !p.is_empty())
+      .ok_or(SessionError::PassphraseRequired)?;
+    content = decrypt(&content, passphrase)?;
+  }
+  let mut archive = ZipArchive::new(Cursor::new(content))?;
+
+  let manifest: BundleManifest = read_json(&mut archive, MANIFEST_ENTRY)?
+    .ok_or_else(|| SessionError::Format("会话包缺少 manifest.json".into()))?;
+  if manifest.format != BUNDLE_FORMAT || manifest.version > BUNDLE_VERSION {
+    return Err(SessionError::Format(format!(
+      "不支持的会话包版本: {} v{}",
+      manifest.format, manifest.version
+    )));
+  }
+
+  check_version(manifest.schema_version).map_err(|e| SessionError::Format(e.to_string()))?;
+
+  let mut data: SessionData = read_json(&mut archive, SESSION_ENTRY)?
+    .ok_or_else(|| SessionError::Format("会话包缺少 session.json".into()))?;
+  data.upgrade()?;
+  if let Some(name) = name.filter(|n| !n.trim().is_empty()) {
+    data.session.name = name;
+  }
+  let events: Vec<SessionEvent> = read_json(&mut archive, EVENTS_ENTRY)?.unwrap_or_default();
+  let attachments: Vec<AttachmentInfo> =
+    read_json(&mut archive, ATTACHMENTS_ENTRY)?.unwrap_or_default();
+  let screenshots: Vec<ScreenshotInfo> =
+    read_json(&mut archive, SCREENSHOTS_ENTRY)?.unwrap_or_default();
+  let raw: Vec<RawDumpRecord> = read_json(&mut archive, RAW_ENTRY)?.unwrap_or_default();
+
+  let mut conn = open()?;
+  let tx = conn.transaction()?;
+  let id = insert_session_in(&tx, &data)?;
+
+  // 事件 id 在新库中会变化，附件按映射后的 id 关联
+  let mut event_ids = HashMap::new();
+  for event in &events {
+    let new_id = add_event(&tx, id, event.t_ms, event.kind, &event.message)?;
+    if !event.causes.is_empty() {
+      set_causes(&tx, new_id, &event.causes)?;
+    }
+    event_ids.insert(event.id, new_id);
+  }
+  for info in &attachments {
+    let Some(content) = read_bytes(&mut archive, &attachment_entry(info))? else {
+      continue;
+    };
+    let event_id = info.event_id.and_then(|e| event_ids.get(&e).copied());
+    add_attachment(&tx, id, event_id, &info.kind, &info.name, &content)?;
+  }
+  for info in &screenshots {
+    let Some(content) = read_bytes(&mut archive, &screenshot_entry(info))? else {
+      continue;
+    };
+    store_screenshot(
+      &tx,
+      id,
+      info.t_ms,
+      info.trigger,
+      (info.width, info.height, content),
+    )?;
+  }
+  for record in &raw {
+    insert_raw(&tx, id, record.t_ms, &record.kind, &record.text)?;
+  }
+
+  tx.commit()?;
+  Ok(id)
+}
+
+fn read_bytes<R: Read + Seek>(archive: &mut ZipArchive<R>, entry: &str) -> Result<Option<Vec<u8>>> {
+  let mut file = match archive.by_name(entry) {
+    Ok(file) => file,
+    Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+    Err(e) => return Err(e.into()),
+  };
+  let mut content = Vec::with_capacity(file.size() as usize);
+  file.read_to_end(&mut content)?;
+  Ok(Some(content))
+}
+
+fn read_json<R: Read + Seek, T: DeserializeOwned>(
+  archive: &mut ZipArchive<R>,
+  entry: &str,
+) -> Result<Option<T>> {
+  match read_bytes(archive, entry)? {
+    Some(content) => Ok(Some(serde_json::from_slice(&content)?)),
+    None => Ok(None),
+  }
+}
@@ -0,0 +1,708 @@
+use crate::{
+  adb::{
+    bugreport::capture_bugreport as run_bugreport,
+    perfetto::{
+      check_heapprofd, fire_trigger, start_perfetto as spawn_perfetto, HeapprofdConfig,
+      PerfettoConfig, JANK_TRIGGER, TRACE_DIR,
+    },
+    profiling::{
+      convert_hprof, dump_heap as run_dump_heap, ensure_simpleperf, pull_file, remove_file,
+      simpleperf_report_sample, spawn_background, start_method_trace as run_method_trace,
+      stop_method_trace, stop_process, wait_exit, DEVICE_TMP,
+    },
+    tcpdump::{ensure_tcpdump, start_tcpdump as run_tcpdump, stop_tcpdump},
+  },
+  session::{
+    error::{Result, SessionError},
+    events::{add_attachment, add_event, EventKind, SessionEvent},
+    recorder::{active_recording, notify, recording_target},
+    store::{elapsed_label, load_session, open},
+  },
+};
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::{HashMap, HashSet},
+  fs,
+  path::{Path, PathBuf},
+  sync::Mutex,
+  thread,
+  time::{Duration, Instant},
+};
+
+// simpleperf 收到 SIGINT 后还要写出采样与符号信息
+const SIMPLEPERF_STOP_TIMEOUT: Duration = Duration::from_secs(60);
+const PERFETTO_STOP_TIMEOUT: Duration = Duration::from_secs(30);
+// 插桩模式的 trace 文件较大，应用写出需要一段时间
+const METHOD_TRACE_STOP_TIMEOUT: Duration = Duration::from_secs(60);
+const TCPDUMP_STOP_TIMEOUT: Duration = Duration::from_secs(10);
+// 大应用的 Java 堆转储可达数百 MB
+const HEAP_DUMP_TIMEOUT: Duration = Duration::from_secs(180);
+// 两次卡顿触发的 trace 至少间隔该时长，避免连续卡顿时反复抓取
+const JANK_TRACE_GAP: Duration = Duration::from_secs(30);
+
+/// 保存为会话附件的性能抓取，堆转储为一次性抓取，其余需开始 / 停止两步完成
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureKind {
+  Simpleperf,
+  Perfetto,
+  Heapprofd,
+  MethodTrace,
+  HeapDump,
+  NativeHeapDump,
+  Bugreport,
+  Tcpdump,
+}
+
+impl CaptureKind {
+  pub fn label(self) -> &'static str {
+    match self {
+      Self::Simpleperf => "CPU 采样（simpleperf）",
+      Self::Perfetto => "系统 trace（perfetto）",
+      Self::Heapprofd => "Native 内存分配（heapprofd）",
+      Self::MethodTrace => "方法 trace（ART）",
+      Self::HeapDump => "Java 堆转储",
+      Self::NativeHeapDump => "Native 堆转储",
+      Self::Bugreport => "bugreport",
+      Self::Tcpdump => "网络抓包（tcpdump）",
+    }
+  }
+
+  fn file_name(self) -> &'static str {
+    match self {
+      Self::Simpleperf => "perf.data",
+      Self::Perfetto => "trace.perfetto-trace",
+      Self::Heapprofd => "heapprofd.perfetto-trace",
+      Self::MethodTrace => "method.trace",
+      Self::HeapDump => "heap.hprof",
+      Self::NativeHeapDump => "native-heap.txt",
+      Self::Bugreport => "bugreport.zip",
+      Self::Tcpdump => "capture.pcap",
+    }
+  }
+
+  fn remote_path(self, session_id: i64) -> String {
+    let dir = match self {
+      Self::Perfetto | Self::Heapprofd => TRACE_DIR,
+      _ => DEVICE_TMP,
+    };
+    format!("{dir}/perfx-{session_id}-{}", self.file_name())
+  }
+
+  fn stop_timeout(self) -> Duration {
+    match self {
+      Self::Simpleperf => SIMPLEPERF_STOP_TIMEOUT,
+      Self::Perfetto | Self::Heapprofd => PERFETTO_STOP_TIMEOUT,
+      Self::MethodTrace => METHOD_TRACE_STOP_TIMEOUT,
+      Self::Tcpdump => TCPDUMP_STOP_TIMEOUT,
+      Self::HeapDump | Self::NativeHeapDump | Self::Bugreport => HEAP_DUMP_TIMEOUT,
+    }
+  }
+
+  fn stop_signal(self) -> &'static str {
+    match self {
+      Self::Simpleperf => "INT",
+      _ => "TERM",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureStatus {
+  pub session_id: i64,
+  pub kind: CaptureKind,
+  pub device_id: String,
+  pub package: String,
+  /// 开始时刻（会话内 t_ms），会话不在录制中时为 0
+  pub started_ms: u64,
+  /// 等待卡顿触发的环形缓冲 trace
+  #[serde(default)]
+  pub armed: bool,
+}
+
+struct RunningCapture {
+  status: CaptureStatus,
+  started: Instant,
+  /// 设备上的抓取进程，方法 trace 由应用进程自身写出，为 0；
+  /// tcpdump 以 root 运行，通过 pid 文件管理，同样为 0
+  pid: u32,
+  remote_path: String,
+  /// 卡顿触发模式下的配置，保存后用于重新布防
+  trigger: Option<PerfettoConfig>,
+}
+
+/// CPU 采样停止时在设备上解析出的样本文本，火焰图由它生成，不再依赖设备
+pub const SIMPLEPERF_REPORT: &str = "perf.report.txt";
+
+static RUNNING: Lazy<Mutex<HashMap<(i64, CaptureKind), RunningCapture>>> =
+  Lazy::new(|| Mutex::new(HashMap::new()));
+static STARTING: Lazy<Mutex<HashSet<(i64, CaptureKind)>>> =
+  Lazy::new(|| Mutex::new(HashSet::new()));
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallGraph {
+  /// 帧指针回溯，开销小，arm64 上通常足够
+  #[default]
+  Fp,
+  /// DWARF 回溯，栈更完整但数据量大
+  Dwarf,
+  None,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SimpleperfOptions {
+  /// 每秒采样次数
+  pub frequency: u32,
+  pub call_graph: CallGraph,
+  /// 设备没有自带 simpleperf 时推送的本地可执行文件
+  pub binary: Option<PathBuf>,
+}
+
+impl Default for SimpleperfOptions {
+  fn default() -> Self {
+    Self {
+      frequency: 4000,
+      call_graph: CallGraph::default(),
+      binary: None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MethodTraceMode {
+  /// 定时采样调用栈，开销小，适合长时间运行
+  #[default]
+  Sampling,
+  /// 对每次方法调用插桩，调用次数准确但会显著拖慢应用
+  Tracing,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MethodTraceOptions {
+  pub mode: MethodTraceMode,
+  /// 采样间隔（微秒）
+  pub sampling_interval_us: u32,
+}
+
+impl Default for MethodTraceOptions {
+  fn default() -> Self {
+    Self {
+      mode: MethodTraceMode::default(),
+      sampling_interval_us: 1000,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeapDumpOptions {
+  /// 转储 native 堆，需应用可调试并已开启 malloc debug
+  pub native: bool,
+  /// 转储前先触发 GC，需 Android 11 及以上
+  pub gc: bool,
+  /// 用 hprof-conv 转为标准 hprof
+  pub convert: bool,
+  /// hprof-conv 路径，未指定时在 adb 所在目录查找
+  pub hprof_conv: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TcpdumpOptions {
+  /// BPF 过滤表达式，如 `host api.example.com and port 443`
+  pub filter: Option<String>,
+  /// 设备没有 tcpdump 时推送的静态编译可执行文件
+  pub binary: Option<PathBuf>,
+}
+
+/// 抓取目标：录制中的会话取录制配置，否则取会话记录中的设备与应用
+fn capture_target(session_id: i64) -> Result<(String, String, u64)> {
+  if let Some(target) = recording_target(session_id) {
+    return Ok(target);
+  }
+  let session = load_session(session_id)?.session;
+  Ok((session.device_id, session.app_package, 0))
+}
+
+/// 正在启动的抓取占位，启动失败时随 drop 释放
+struct Reservation {
+  key: (i64, CaptureKind),
+}
+
+impl Drop for Reservation {
+  fn drop(&mut self) {
+    if let Ok(mut starting) = STARTING.lock() {
+      starting.remove(&self.key);
+    }
+  }
+}
+
+/// 在设备上启动抓取前先占位，同一会话的同类抓取同时只能有一个在启动或运行
+fn reserve(session_id: i64, kind: CaptureKind) -> Result<Reservation> {
+  let key = (session_id, kind);
+  let running = RUNNING.lock().map_err(|_| SessionError::NotInitialized)?;
+  let mut starting = STARTING.lock().map_err(|_| SessionError::NotInitialized)?;
+  if running.contains_key(&key) || !starting.insert(key) {
+    return Err(SessionError::CaptureRunning(session_id, kind.label()));
+  }
+  Ok(Reservation { key })
+}
+
+fn register(reservation: Reservation, capture: RunningCapture) -> Result<CaptureStatus> {
+  let status = capture.status.clone();
+  RUNNING
+    .lock()
+    .map_err(|_| SessionError::NotInitialized)?
+    .insert(reservation.key, capture);
+  Ok(status)
+}
+
+/// 以 `--app` 方式对目标应用的进程做 CPU 采样，无需 root
+pub fn start_simpleperf(session_id: i64, options: &SimpleperfOptions) -> Result<CaptureStatus> {
+  let kind = CaptureKind::Simpleperf;
+  let reservation = reserve(session_id, kind)?;
+  let (device_id, package, started_ms) = capture_target(session_id)?;
+  let binary = ensure_simpleperf(&device_id, options.binary.as_deref())?;
+  let remote_path = kind.remote_path(session_id);
+  remove_file(&device_id, &remote_path);
+
+  let call_graph = match options.call_graph {
+    CallGraph::Fp => " --call-graph fp",
+    CallGraph::Dwarf => " --call-graph dwarf",
+    CallGraph::None => "",
+  };
+  let command = format!(
+    "{binary} record --app {package} -f {}{call_graph} -o {remote_path}",
+    options.frequency.max(1)
+  );
+  let pid = spawn_background(&device_id, &command)?;
+  info!("会话 {} 开始 simpleperf 采样，设备进程 {}", session_id, pid);
+
+  register(
+    reservation,
+    RunningCapture {
+      status: CaptureStatus {
+        session_id,
+        kind,
+        device_id,
+        package,
+        started_ms,
+        armed: false,
+      },
+      started: Instant::now(),
+      pid,
+      remote_path,
+      trigger: None,
+    },
+  )
+}
+
+/// 在设备上以 root 运行 tcpdump，覆盖开始到停止这段时间内的全部网络报文
+pub fn start_tcpdump(session_id: i64, options: &TcpdumpOptions) -> Result<CaptureStatus> {
+  let kind = CaptureKind::Tcpdump;
+  let reservation = reserve(session_id, kind)?;
+  let (device_id, package, started_ms) = capture_target(session_id)?;
+  let binary = ensure_tcpdump(&device_id, options.binary.as_deref())?;
+  let remote_path = kind.remote_path(session_id);
+  run_tcpdump(&device_id, &binary, &remote_path, options.filter.as_deref())?;
+  info!("会话 {} 开始 tcpdump 抓包", session_id);
+
+  register(
+    reservation,
+    RunningCapture {
+      status: CaptureStatus {
+        session_id,
+        kind,
+        device_id,
+        package,
+        started_ms,
+        armed: false,
+      },
+      started: Instant::now(),
+      pid: 0,
+      remote_path,
+      trigger: None,
+    },
+  )
+}
+
+/// 对应用主进程开始 ART 方法 trace，结果可在 Android Studio 或 Perfetto UI 中打开
+pub fn start_method_trace(session_id: i64, options: &MethodTraceOptions) -> Result<CaptureStatus> {
+  let kind = CaptureKind::MethodTrace;
+  let reservation = reserve(session_id, kind)?;
+  let (device_id, package, started_ms) = capture_target(session_id)?;
+  let remote_path = kind.remote_path(session_id);
+  remove_file(&device_id, &remote_path);
+  let sampling_us = match options.mode {
+    MethodTraceMode::Sampling => Some(options.sampling_interval_us),
+    MethodTraceMode::Tracing => None,
+  };
+  run_method_trace(&device_id, &package, &remote_path, sampling_us)?;
+  info!("会话 {} 开始 ART 方法 trace", session_id);
+
+  register(
+    reservation,
+    RunningCapture {
+      status: CaptureStatus {
+        session_id,
+        kind,
+        device_id,
+        package,
+        started_ms,
+        armed: false,
+      },
+      started: Instant::now(),
+      pid: 0,
+      remote_path,
+      trigger: None,
+    },
+  )
+}
+
+/// 按配置启动 perfetto 系统 trace，设置了时长时到时自动结束，仍需调用停止以取回文件
+pub fn start_perfetto(session_id: i64, config: &PerfettoConfig) -> Result<CaptureStatus> {
+  let (device_id, package, started_ms) = capture_target(session_id)?;
+  launch_perfetto(session_id, device_id, package, started_ms, config, false)
+}
+
+/// 通过 perfetto 启动 heapprofd，记录目标应用的 native 分配调用栈
+pub fn start_heapprofd(session_id: i64, config: &HeapprofdConfig) -> Result<CaptureStatus> {
+  let kind = CaptureKind::Heapprofd;
+  let reservation = reserve(session_id, kind)?;
+  let (device_id, package, started_ms) = capture_target(session_id)?;
+  check_heapprofd(&device_id, &package)?;
+  let remote_path = kind.remote_path(session_id);
+  remove_file(&device_id, &remote_path);
+  let pid = spawn_perfetto(&device_id, &config.to_pbtxt(&package), &remote_path)?;
+  info!("会话 {} 开始 heapprofd，设备进程 {}", session_id, pid);
+
+  register(
+    reservation,
+    RunningCapture {
+      status: CaptureStatus {
+        session_id,
+        kind,
+        device_id,
+        package,
+        started_ms,
+        armed: false,
+      },
+      started: Instant::now(),
+      pid,
+      remote_path,
+      trigger: None,
+    },
+  )
+}
+
+/// 录制开始时以环形缓冲布防，卡顿发生时保留此前一段时间的 trace
+pub fn arm_jank_trace(session_id: i64, config: &PerfettoConfig) -> Result<CaptureStatus> {
+  let (device_id, package, started_ms) = capture_target(session_id)?;
+  launch_perfetto(session_id, device_id, package, started_ms, config, true)
+}
+
+fn launch_perfetto(
+  session_id: i64,
+  device_id: String,
+  package: String,
+  started_ms: u64,
+  config: &PerfettoConfig,
+  armed: bool,
+) -> Result<CaptureStatus> {
+  let kind = CaptureKind::Perfetto;
+  let reservation = reserve(session_id, kind)?;
+  let remote_path = kind.remote_path(session_id);
+  remove_file(&device_id, &remote_path);
+  let pid = spawn_perfetto(&device_id, &config.to_pbtxt(&package, armed), &remote_path)?;
+  info!("会话 {} 开始 perfetto trace，设备进程 {}", session_id, pid);
+
+  register(
+    reservation,
+    RunningCapture {
+      status: CaptureStatus {
+        session_id,
+        kind,
+        device_id,
+        package,
+        started_ms,
+        armed,
+      },
+      started: Instant::now(),
+      pid,
+      remote_path,
+      trigger: armed.then(|| config.clone()),
+    },
+  )
+}
+
+/// 卡顿发生时触发已布防的 trace，后台等待写出后保存，并在录制仍进行时重新布防
+pub fn fire_jank_trace(session_id: i64) {
+  let key = (session_id, CaptureKind::Perfetto);
+  let capture = {
+    let Ok(mut running) = RUNNING.lock() else {
+      return;
+    };
+    match running.get(&key) {
+      Some(c) if c.status.armed && c.started.elapsed() >= JANK_TRACE_GAP => running.remove(&key),
+      _ => None,
+    }
+  };
+  let Some(capture) = capture else {
+    return;
+  };
+
+  thread::spawn(move || {
+    let device_id = capture.status.device_id.clone();
+    let fired_ms = recording_target(session_id).map_or(0, |(_, _, t_ms)| t_ms);
+    if let Err(e) = fire_trigger(&device_id, JANK_TRIGGER) {
+      warn!("触发 perfetto 失败: {}", e);
+    }
+    wait_exit(&device_id, capture.pid, PERFETTO_STOP_TIMEOUT);
+    let status = CaptureStatus {
+      // 环形缓冲只保留最近的数据，事件记在触发时刻
+      started_ms: fired_ms,
+      ..capture.status.clone()
+    };
+    match pull_file(&device_id, &capture.remote_path) {
+      Ok(data) => {
+        if let Err(e) = store_capture(&status, CaptureKind::Perfetto.file_name(), &data, 0) {
+          warn!("保存卡顿 trace 失败: {}", e);
+        }
+      }
+      Err(e) => warn!("拉取卡顿 trace 失败: {}", e),
+    }
+    remove_file(&device_id, &capture.remote_path);
+
+    if let (Some(config), Some(_)) = (&capture.trigger, recording_target(session_id)) {
+      if let Err(e) = arm_jank_trace(session_id, config) {
+        warn!("重新布防 perfetto 失败: {}", e);
+      }
+    }
+  });
+}
+
+/// 录制结束时丢弃未触发的环形缓冲 trace
+pub fn disarm_session(session_id: i64) {
+  let key = (session_id, CaptureKind::Perfetto);
+  let capture = RUNNING
+    .lock()
+    .ok()
+    .and_then(|mut running| match running.get(&key) {
+      Some(c) if c.status.armed => running.remove(&key),
+      _ => None,
+    });
+  if let Some(capture) = capture {
+    let device_id = &capture.status.device_id;
+    let _ = stop_process(device_id, capture.pid, "KILL", PERFETTO_STOP_TIMEOUT);
+    remove_file(device_id, &capture.remote_path);
+  }
+}
+
+/// 停止抓取、拉取结果文件并作为抓取事件的附件保存
+pub fn stop_capture(session_id: i64, kind: CaptureKind) -> Result<SessionEvent> {
+  let capture = RUNNING
+    .lock()
+    .ok()
+    .and_then(|mut running| running.remove(&(session_id, kind)))
+    .ok_or(SessionError::CaptureNotRunning(session_id, kind.label()))?;
+  let device_id = &capture.status.device_id;
+
+  let exited = match kind {
+    CaptureKind::MethodTrace => stop_method_trace(
+      device_id,
+      &capture.status.package,
+      &capture.remote_path,
+      kind.stop_timeout(),
+    ),
+    CaptureKind::Tcpdump => stop_tcpdump(device_id, &capture.remote_path, kind.stop_timeout()),
+    _ => stop_process(
+      device_id,
+      capture.pid,
+      kind.stop_signal(),
+      kind.stop_timeout(),
+    ),
+  }?;
+  if !exited {
+    warn!("{} 未在超时内退出，尝试读取已写出的数据", kind.label());
+  }
+  let data = pull_file(device_id, &capture.remote_path);
+  let report = (kind == CaptureKind::Simpleperf && data.is_ok())
+    .then(|| {
+      ensure_simpleperf(device_id, None)
+        .and_then(|binary| simpleperf_report_sample(device_id, &binary, &capture.remote_path))
+    })
+    .transpose();
+  remove_file(device_id, &capture.remote_path);
+  let data = data?;
+  let duration_ms = capture.started.elapsed().as_millis() as u64;
+  let mut event = store_capture(&capture.status, kind.file_name(), &data, duration_ms)?;
+  match report {
+    Ok(Some(report)) => {
+      let conn = open()?;
+      event.attachments.push(add_attachment(
+        &conn,
+        session_id,
+        Some(event.id),
+        "capture",
+        SIMPLEPERF_REPORT,
+        report.as_bytes(),
+      )?);
+    }
+    Ok(None) => {}
+    Err(e) => warn!("解析 CPU 采样失败，火焰图不可用: {}", e),
+  }
+  Ok(event)
+}
+
+/// 转储目标应用的堆并等待写完，拉取后作为抓取事件的附件保存
+pub fn dump_heap(session_id: i64, options: &HeapDumpOptions) -> Result<SessionEvent> {
+  let kind = if options.native {
+    CaptureKind::NativeHeapDump
+  } else {
+    CaptureKind::HeapDump
+  };
+  let (device_id, package, started_ms) = capture_target(session_id)?;
+  let remote_path = kind.remote_path(session_id);
+  remove_file(&device_id, &remote_path);
+  let started = Instant::now();
+
+  let dumped = run_dump_heap(
+    &device_id,
+    &package,
+    options.native,
+    options.gc,
+    &remote_path,
+    HEAP_DUMP_TIMEOUT,
+  )
+  .and_then(|_| pull_file(&device_id, &remote_path));
+  remove_file(&device_id, &remote_path);
+  let mut data = dumped?;
+  if options.convert && !options.native {
+    data = convert_hprof(&data, options.hprof_conv.as_deref())?;
+  }
+
+  let status = CaptureStatus {
+    session_id,
+    kind,
+    device_id,
+    package,
+    started_ms,
+    armed: false,
+  };
+  let duration_ms = started.elapsed().as_millis() as u64;
+  store_capture(&status, kind.file_name(), &data, duration_ms)
+}
+
+/// bugreport 的保存位置
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SavedBugreport {
+  /// 作为会话中抓取事件的附件
+  Session {
+    event: SessionEvent,
+  },
+  File {
+    path: PathBuf,
+    size: u64,
+  },
+}
+
+/// 生成 bugreport：指定会话时保存到该会话，否则写入 `path`；两者都未指定时保存到该设备
+/// 正在录制的会话，没有录制时报错
+pub fn capture_bugreport(
+  device_id: &str,
+  session_id: Option<i64>,
+  path: Option<&Path>,
+  on_progress: impl FnMut(u32, &str),
+) -> Result<SavedBugreport> {
+  let session_id = match (session_id, path) {
+    (Some(id), _) => id,
+    (None, Some(path)) => {
+      let data = run_bugreport(device_id, on_progress)?;
+      fs::write(path, &data)?;
+      info!(
+        "设备 {} 的 bugreport 已保存到 {}",
+        device_id,
+        path.display()
+      );
+      return Ok(SavedBugreport::File {
+        path: path.to_path_buf(),
+        size: data.len() as u64,
+      });
+    }
+    (None, None) => active_recording(Some(device_id))?,
+  };
+  let (_, package, started_ms) = capture_target(session_id)?;
+  let started = Instant::now();
+  let data = run_bugreport(device_id, on_progress)?;
+
+  let kind = CaptureKind::Bugreport;
+  let status = CaptureStatus {
+    session_id,
+    kind,
+    device_id: device_id.to_string(),
+    package,
+    started_ms,
+    armed: false,
+  };
+  let duration_ms = started.elapsed().as_millis() as u64;
+  let event = store_capture(&status, kind.file_name(), &data, duration_ms)?;
+  Ok(SavedBugreport::Session { event })
+}
+
+pub fn list_captures() -> Vec<CaptureStatus> {
+  RUNNING
+    .lock()
+    .map(|running| running.values().map(|c| c.status.clone()).collect())
+    .unwrap_or_default()
+}
+
+/// 在抓取开始时刻记录事件，结果文件作为其附件
+fn store_capture(
+  status: &CaptureStatus,
+  name: &str,
+  data: &[u8],
+  duration_ms: u64,
+) -> Result<SessionEvent> {
+  let message = if status.armed {
+    format!("卡顿触发的{}", status.kind.label())
+  } else {
+    format!(
+      "{}，时长 {}",
+      status.kind.label(),
+      elapsed_label(duration_ms)
+    )
+  };
+  let conn = open()?;
+  let id = add_event(
+    &conn,
+    status.session_id,
+    status.started_ms,
+    EventKind::Capture,
+    &message,
+  )?;
+  let attachment = add_attachment(&conn, status.session_id, Some(id), "capture", name, data)?;
+  let event = SessionEvent {
+    id,
+    t_ms: status.started_ms,
+    kind: EventKind::Capture,
+    message,
+    attachments: vec![attachment],
+    causes: Vec::new(),
+    rule_id: None,
+  };
+  notify(|o| o.on_event(status.session_id, &event));
+  info!(
+    "会话 {} 保存{}，{} 字节",
+    status.session_id,
+    status.kind.label(),
+    data.len()
+  );
+  Ok(event)
+}
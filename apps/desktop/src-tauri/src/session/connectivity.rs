@@ -0,0 +1,60 @@
+//! 网络场景测试：切换 Wi-Fi、移动数据与飞行模式，首次切换前记录原状态以便恢复；
+//! 设备上有录制中的会话时在时间线上插入标记，便于对照断网前后的表现
+
+use crate::{
+  adb::connectivity::{
+    connectivity_state, restore_connectivity, set_radio, ConnectivityState, Radio,
+  },
+  session::{
+    error::Result,
+    recorder::{active_recording, mark_recording},
+  },
+};
+use log::warn;
+use once_cell::sync::Lazy;
+use std::{collections::HashMap, sync::Mutex};
+
+/// 各设备首次切换前的状态
+static SAVED: Lazy<Mutex<HashMap<String, ConnectivityState>>> =
+  Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn get_connectivity(device_id: &str) -> Result<ConnectivityState> {
+  Ok(connectivity_state(device_id)?)
+}
+
+/// 切换单个开关，返回切换后的状态
+pub fn set_connectivity(device_id: &str, radio: Radio, enabled: bool) -> Result<ConnectivityState> {
+  let current = connectivity_state(device_id)?;
+  if let Ok(mut saved) = SAVED.lock() {
+    saved.entry(device_id.to_string()).or_insert(current);
+  }
+  set_radio(device_id, radio, enabled)?;
+  let action = if enabled { "开启" } else { "关闭" };
+  mark(device_id, &format!("{}{}", action, radio.label()));
+  Ok(connectivity_state(device_id)?)
+}
+
+/// 恢复到首次切换前的状态；没有切换过时返回 None
+pub fn restore_saved_connectivity(device_id: &str) -> Result<Option<ConnectivityState>> {
+  let Some(state) = SAVED.lock().ok().and_then(|mut s| s.remove(device_id)) else {
+    return Ok(None);
+  };
+  if let Err(e) = restore_connectivity(device_id, &state) {
+    // 恢复失败时保留原状态，允许重试
+    if let Ok(mut saved) = SAVED.lock() {
+      saved.insert(device_id.to_string(), state);
+    }
+    return Err(e.into());
+  }
+  mark(device_id, "恢复网络状态");
+  Ok(Some(connectivity_state(device_id)?))
+}
+
+fn mark(device_id: &str, name: &str) {
+  let Ok(session_id) = active_recording(Some(device_id)) else {
+    return;
+  };
+  if let Err(e) = mark_recording(session_id, name) {
+    warn!("插入网络切换标记失败: {}", e);
+  }
+}
@@ -0,0 +1,81 @@
+//! CPU 频率锁定（需要 root）：首次锁定前记录各簇原配置并写入数据库，应用重启后仍可恢复；
+//! 锁定时若设备上有录制中的会话，把实际生效的配置写入会话信息
+
+use crate::{
+  adb::cpufreq::{apply_cpu_lock, cpu_policies, restore_cpu_policies, CpuLock, CpuPolicy},
+  session::{
+    error::Result,
+    meta::{query_meta, write_meta},
+    recorder::active_recording,
+    restore::DeviceSetting,
+    store::open,
+    unix_now_ms,
+  },
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+static SAVED: DeviceSetting<LockState> = DeviceSetting::new("cpu_lock:", "CPU 频率");
+
+/// 实际生效的锁定配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedCpuLock {
+  pub lock: CpuLock,
+  pub policies: Vec<CpuPolicy>,
+  pub applied_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LockState {
+  original: Vec<CpuPolicy>,
+  applied: AppliedCpuLock,
+}
+
+pub fn get_cpu_policies(device_id: &str) -> Result<Vec<CpuPolicy>> {
+  Ok(cpu_policies(device_id)?)
+}
+
+/// 设备当前由本工具设置的锁定配置
+pub fn active_cpu_lock(device_id: &str) -> Option<AppliedCpuLock> {
+  match SAVED.get(device_id) {
+    Ok(state) => state.map(|s| s.applied),
+    Err(e) => {
+      warn!("读取 CPU 锁定状态失败: {}", e);
+      None
+    }
+  }
+}
+
+pub fn lock_cpu(device_id: &str, lock: CpuLock) -> Result<AppliedCpuLock> {
+  // 重复锁定时保留最初的配置，恢复时回到锁定前的状态
+  let original = match SAVED.get(device_id)? {
+    Some(state) => state.original,
+    None => cpu_policies(device_id)?,
+  };
+  let applied = AppliedCpuLock {
+    lock,
+    policies: apply_cpu_lock(device_id, lock)?,
+    applied_at: unix_now_ms(),
+  };
+  SAVED.save(
+    device_id,
+    &LockState {
+      original,
+      applied: applied.clone(),
+    },
+  )?;
+  if let Ok(session_id) = active_recording(Some(device_id)) {
+    let conn = open()?;
+    let mut meta = query_meta(&conn, session_id)?;
+    meta.cpu_lock = Some(applied.clone());
+    write_meta(&conn, session_id, &meta)?;
+  }
+  Ok(applied)
+}
+
+/// 恢复锁定前的配置；没有锁定过时返回 false
+pub fn restore_cpu(device_id: &str) -> Result<bool> {
+  SAVED.restore(device_id, |state| {
+    Ok(restore_cpu_policies(device_id, &state.original)?)
+  })
+}
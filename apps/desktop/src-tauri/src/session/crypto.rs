@@ -0,0 +1,33 @@
+use crate::session::error::{Result, SessionError};
+use age::{scrypt, secrecy::SecretString, Decryptor, Encryptor};
+use std::{
+  io::{self, Read, Write},
+  iter,
+};
+
+// age 文件头，用于识别加密的会话包
+const AGE_MAGIC: &[u8] = b"age-encryption.org/v1";
+
+pub fn is_encrypted(data: &[u8]) -> bool {
+  data.starts_with(AGE_MAGIC)
+}
+
+/// 使用口令加密（age / scrypt），边读边写，不整体载入内存
+pub fn encrypt(mut plain: impl Read, out: impl Write, passphrase: &str) -> Result<()> {
+  let encryptor = Encryptor::with_user_passphrase(SecretString::from(passphrase.to_owned()));
+  let mut writer = encryptor.wrap_output(out)?;
+  io::copy(&mut plain, &mut writer)?;
+  writer.finish()?.flush()?;
+  Ok(())
+}
+
+pub fn decrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+  let identity = scrypt::Identity::new(SecretString::from(passphrase.to_owned()));
+  let decryptor = Decryptor::new_buffered(data).map_err(|_| SessionError::DecryptFailed)?;
+  let mut reader = decryptor
+    .decrypt(iter::once(&identity as &dyn age::Identity))
+    .map_err(|_| SessionError::DecryptFailed)?;
+  let mut plain = Vec::new();
+  reader.read_to_end(&mut plain)?;
+  Ok(plain)
+}
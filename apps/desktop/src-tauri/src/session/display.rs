@@ -0,0 +1,76 @@
+//! 统一显示参数：首次修改前把亮度、分辨率与密度原值写入数据库，退出应用时自动恢复；
+//! 修改时若设备上有录制中的会话，把生效的参数写入会话信息，便于对比功耗与 GPU 数据
+
+use crate::{
+  adb::display::{apply_display, display_state, restore_display, DisplaySettings, DisplayState},
+  session::{
+    error::Result,
+    meta::{query_meta, write_meta},
+    recorder::active_recording,
+    restore::DeviceSetting,
+    store::open,
+  },
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+static SAVED: DeviceSetting<SavedDisplay> = DeviceSetting::new("display_restore:", "显示参数");
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedDisplay {
+  original: DisplayState,
+  /// 多次修改合并后的参数
+  applied: DisplaySettings,
+}
+
+pub fn get_display(device_id: &str) -> Result<DisplayState> {
+  Ok(display_state(device_id)?)
+}
+
+/// 当前由本工具设置的显示参数
+pub fn active_display(device_id: &str) -> Option<DisplaySettings> {
+  match SAVED.get(device_id) {
+    Ok(saved) => saved.map(|s| s.applied),
+    Err(e) => {
+      warn!("读取显示参数失败: {}", e);
+      None
+    }
+  }
+}
+
+/// 写入显示参数，返回写入后的状态
+pub fn set_display(device_id: &str, settings: DisplaySettings) -> Result<DisplayState> {
+  let mut saved = match SAVED.get(device_id)? {
+    Some(saved) => saved,
+    None => SavedDisplay {
+      original: display_state(device_id)?,
+      applied: DisplaySettings::default(),
+    },
+  };
+  apply_display(device_id, &settings)?;
+  saved.applied = DisplaySettings {
+    brightness: settings.brightness.or(saved.applied.brightness),
+    size: settings.size.or(saved.applied.size),
+    density: settings.density.or(saved.applied.density),
+  };
+  SAVED.save(device_id, &saved)?;
+  if let Ok(session_id) = active_recording(Some(device_id)) {
+    let conn = open()?;
+    let mut meta = query_meta(&conn, session_id)?;
+    meta.display = Some(saved.applied);
+    write_meta(&conn, session_id, &meta)?;
+  }
+  Ok(display_state(device_id)?)
+}
+
+/// 恢复修改前的显示参数；没有修改过时返回 false
+pub fn restore_saved_display(device_id: &str) -> Result<bool> {
+  SAVED.restore(device_id, |saved| {
+    Ok(restore_display(device_id, &saved.original)?)
+  })
+}
+
+/// 恢复所有设备的显示参数；设备不在线的记录保留到下次
+pub fn restore_all_displays() {
+  SAVED.restore_all(|_| true, restore_saved_display);
+}
@@ -0,0 +1,136 @@
+use crate::{
+  adb::dropbox::{dropbox_entries, entry_matches, DropboxEntry, APP_TAGS},
+  session::{
+    error::Result,
+    events::{
+      add_attachment, add_event, query_attachments, AttachmentInfo, EventKind, SessionEvent,
+    },
+    recorder::{notify, recording_target},
+    store::{load_session, open},
+    unix_now,
+  },
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+// 未指定起始时间时回溯的时长
+const DEFAULT_LOOKBACK_SECS: i64 = 24 * 3600;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DropboxOptions {
+  pub tags: Vec<String>,
+  /// 只收集该时刻（Unix 秒）之后的条目，默认回溯 24 小时
+  pub since: Option<i64>,
+}
+
+impl Default for DropboxOptions {
+  fn default() -> Self {
+    Self {
+      tags: APP_TAGS.iter().map(|t| t.to_string()).collect(),
+      since: None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectedEntry {
+  pub tag: String,
+  pub time: String,
+  pub attachment: AttachmentInfo,
+  /// 条目落在会话时间范围内时在时间线上生成的事件
+  pub event: Option<SessionEvent>,
+}
+
+/// 收集目标应用近期的 DropBox 崩溃 / ANR / tombstone 记录并保存到会话，已收集过的条目跳过
+pub fn collect_dropbox(session_id: i64, options: &DropboxOptions) -> Result<Vec<CollectedEntry>> {
+  let session = load_session(session_id)?.session;
+  let recording = recording_target(session_id).is_some();
+  let end_time = if recording {
+    unix_now()
+  } else {
+    session.end_time
+  };
+  let since = options
+    .since
+    .unwrap_or_else(|| unix_now() - DEFAULT_LOOKBACK_SECS);
+
+  let mut entries: Vec<DropboxEntry> = Vec::new();
+  for tag in &options.tags {
+    match dropbox_entries(&session.device_id, tag) {
+      Ok(found) => entries.extend(
+        found
+          .into_iter()
+          .filter(|e| e.epoch_secs >= since && entry_matches(e, &session.app_package)),
+      ),
+      Err(e) => warn!("读取 DropBox {} 失败: {}", tag, e),
+    }
+  }
+  entries.sort_by_key(|e| e.epoch_secs);
+
+  let conn = open()?;
+  let existing: HashSet<String> = query_attachments(&conn, session_id)?
+    .into_iter()
+    .filter(|a| a.kind == "dropbox")
+    .map(|a| a.name)
+    .collect();
+
+  let mut collected = Vec::new();
+  for entry in entries {
+    let name = format!("dropbox-{}-{}.txt", entry.tag, entry.epoch_secs);
+    if existing.contains(&name) {
+      continue;
+    }
+    let in_session = (session.start_time..=end_time).contains(&entry.epoch_secs);
+    let event = match event_kind(&entry.tag).filter(|_| in_session) {
+      Some(kind) => {
+        let t_ms = (entry.epoch_secs - session.start_time) as u64 * 1000;
+        let message = format!("DropBox {}（{}）", entry.tag, entry.time);
+        Some((
+          add_event(&conn, session_id, t_ms, kind, &message)?,
+          t_ms,
+          kind,
+          message,
+        ))
+      }
+      None => None,
+    };
+    let attachment = add_attachment(
+      &conn,
+      session_id,
+      event.as_ref().map(|(id, ..)| *id),
+      "dropbox",
+      &name,
+      entry.text.as_bytes(),
+    )?;
+    let event = event.map(|(id, t_ms, kind, message)| SessionEvent {
+      id,
+      t_ms,
+      kind,
+      message,
+      attachments: vec![attachment.clone()],
+      causes: Vec::new(),
+      rule_id: None,
+    });
+    if let Some(event) = &event {
+      notify(|o| o.on_event(session_id, event));
+    }
+    collected.push(CollectedEntry {
+      tag: entry.tag,
+      time: entry.time,
+      attachment,
+      event,
+    });
+  }
+  Ok(collected)
+}
+
+fn event_kind(tag: &str) -> Option<EventKind> {
+  match tag {
+    "data_app_crash" => Some(EventKind::Crash),
+    "data_app_anr" => Some(EventKind::Anr),
+    "data_app_native_crash" | "system_tombstone" => Some(EventKind::NativeCrash),
+    _ => None,
+  }
+}
@@ -0,0 +1,56 @@
+use crate::{
+  adb::energy::{power_profile, EnergyProbe, PowerProfile},
+  session::{
+    error::Result,
+    store::{get_kv, open, set_kv},
+  },
+};
+use log::{info, warn};
+use rusqlite::{params, Connection};
+
+/// power_profile 随系统版本固定，按 build fingerprint 缓存在 app_kv
+fn profile_key(fingerprint: &str) -> String {
+  format!("power_profile:{fingerprint}")
+}
+
+pub fn cached_profile(fingerprint: &str) -> Result<Option<PowerProfile>> {
+  get_kv(&profile_key(fingerprint))
+}
+
+/// 尚未缓存时从设备拉取 power_profile，framework-res.apk 较大，应在后台线程调用
+pub fn ensure_profile(device_id: &str, fingerprint: &str) {
+  match cached_profile(fingerprint) {
+    Ok(Some(_)) => return,
+    Ok(None) => {}
+    Err(e) => warn!("读取 power_profile 缓存失败: {}", e),
+  }
+  match power_profile(device_id) {
+    Ok(profile) => {
+      info!("已缓存 {} 的 power_profile", fingerprint);
+      if let Err(e) = set_kv(&profile_key(fingerprint), &profile) {
+        warn!("缓存 power_profile 失败: {}", e);
+      }
+    }
+    Err(e) => warn!("拉取 power_profile 失败: {}", e),
+  }
+}
+
+pub fn store_probe(conn: &Connection, session_id: i64, probe: &EnergyProbe) -> Result<()> {
+  conn.execute(
+    "INSERT INTO energy_probes (session_id, t_ms, data) VALUES (?1, ?2, ?3)",
+    params![session_id, probe.t_ms as i64, serde_json::to_string(probe)?],
+  )?;
+  Ok(())
+}
+
+pub fn load_probes(session_id: i64) -> Result<Vec<EnergyProbe>> {
+  let conn = open()?;
+  let mut stmt =
+    conn.prepare("SELECT data FROM energy_probes WHERE session_id = ?1 ORDER BY t_ms")?;
+  let rows = stmt.query_map(params![session_id], |row| row.get::<_, String>(0))?;
+  let mut probes = Vec::new();
+  for row in rows {
+    probes.push(serde_json::from_str(&row?)?);
+  }
+  Ok(probes)
+}
@@ -0,0 +1,103 @@
+use crate::i18n::{detail, Localized};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SessionError {
+  #[error("会话数据库未初始化")]
+  NotInitialized,
+  #[error("会话不存在: {0}")]
+  NotFound(i64),
+  #[error("会话 {0} 未在录制中")]
+  NotRecording(i64),
+  #[error("会话 {0} 已有进行中的{1}")]
+  CaptureRunning(i64, &'static str),
+  #[error("会话 {0} 没有进行中的{1}")]
+  CaptureNotRunning(i64, &'static str),
+  #[error("会话 {0} 已暂停")]
+  AlreadyPaused(i64),
+  #[error("会话 {0} 未暂停")]
+  NotPaused(i64),
+  #[error("设备 {0} 上没有录制中的会话")]
+  NoRecordingOnDevice(String),
+  #[error("没有录制中的会话")]
+  NoActiveRecording,
+  #[error("有 {0} 个会话正在录制，请指定设备或会话")]
+  AmbiguousRecording(usize),
+  #[error("数据库错误: {0}")]
+  Db(#[from] rusqlite::Error),
+  #[error("推送配置无效: {0}")]
+  Sink(String),
+  #[error("会话数据格式错误: {0}")]
+  Format(String),
+  #[error("JSON 解析失败: {0}")]
+  Json(#[from] serde_json::Error),
+  #[error("文件读写失败: {0}")]
+  Io(#[from] std::io::Error),
+  #[error(transparent)]
+  Adb(#[from] crate::adb::error::AdbError),
+  #[error("图片处理失败: {0}")]
+  Image(#[from] image::ImageError),
+  #[error("会话包读取失败: {0}")]
+  Bundle(#[from] zip::result::ZipError),
+  #[error("会话包已加密，请提供密码")]
+  PassphraseRequired,
+  #[error("解密失败，密码错误或文件已损坏")]
+  DecryptFailed,
+}
+
+pub type Result<T> = std::result::Result<T, SessionError>;
+
+impl Localized for SessionError {
+  fn code(&self) -> &'static str {
+    match self {
+      Self::NotInitialized => "DB_NOT_INITIALIZED",
+      Self::NotFound(_) => "SESSION_NOT_FOUND",
+      Self::NotRecording(_) => "SESSION_NOT_RECORDING",
+      Self::CaptureRunning(..) => "CAPTURE_RUNNING",
+      Self::CaptureNotRunning(..) => "CAPTURE_NOT_RUNNING",
+      Self::AlreadyPaused(_) => "SESSION_ALREADY_PAUSED",
+      Self::NotPaused(_) => "SESSION_NOT_PAUSED",
+      Self::NoRecordingOnDevice(_) => "NO_RECORDING_ON_DEVICE",
+      Self::NoActiveRecording => "NO_ACTIVE_RECORDING",
+      Self::AmbiguousRecording(_) => "AMBIGUOUS_RECORDING",
+      Self::Db(_) => "DATABASE",
+      Self::Sink(_) => "SINK_CONFIG_INVALID",
+      Self::Format(_) => "SESSION_FORMAT",
+      Self::Json(_) => "JSON",
+      Self::Io(_) => "IO",
+      Self::Adb(e) => e.code(),
+      Self::Image(_) => "IMAGE",
+      Self::Bundle(_) => "BUNDLE_READ",
+      Self::PassphraseRequired => "PASSPHRASE_REQUIRED",
+      Self::DecryptFailed => "DECRYPT_FAILED",
+    }
+  }
+
+  fn params(&self) -> Value {
+    match self {
+      Self::NotFound(id)
+      | Self::NotRecording(id)
+      | Self::AlreadyPaused(id)
+      | Self::NotPaused(id) => {
+        json!({ "sessionId": id })
+      }
+      Self::CaptureRunning(id, kind) | Self::CaptureNotRunning(id, kind) => {
+        json!({ "sessionId": id, "capture": kind })
+      }
+      Self::NoRecordingOnDevice(device) => json!({ "device": device }),
+      Self::AmbiguousRecording(count) => json!({ "count": count }),
+      Self::Db(e) => detail(e),
+      Self::Sink(d) | Self::Format(d) => detail(d),
+      Self::Json(e) => detail(e),
+      Self::Io(e) => detail(e),
+      Self::Adb(e) => e.params(),
+      Self::Image(e) => detail(e),
+      Self::Bundle(e) => detail(e),
+      Self::NotInitialized
+      | Self::NoActiveRecording
+      | Self::PassphraseRequired
+      | Self::DecryptFailed => Value::Null,
+    }
+  }
+}
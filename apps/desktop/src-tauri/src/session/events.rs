@@ -0,0 +1,251 @@
+use crate::session::{
+  error::{Result, SessionError},
+  jank::CauseHint,
+  store::open,
+};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// 附件多为日志文本，与原始输出一样压缩存储
+const ZSTD_LEVEL: i32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+  Jank,
+  Anr,
+  Crash,
+  NativeCrash,
+  Alert,
+  ThrottleBegin,
+  ThrottleEnd,
+  /// 性能抓取（CPU 采样、trace、堆转储等），文件作为附件
+  Capture,
+  /// 录制中设备掉线与重新连接
+  Disconnected,
+  Reconnected,
+  /// Doze 状态变化
+  Idle,
+  /// 目标应用进程退出与重新启动，期间应用指标为空
+  AppExited,
+  AppStarted,
+}
+
+impl EventKind {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Self::Jank => "jank",
+      Self::Anr => "anr",
+      Self::Crash => "crash",
+      Self::NativeCrash => "native_crash",
+      Self::Alert => "alert",
+      Self::ThrottleBegin => "throttle_begin",
+      Self::ThrottleEnd => "throttle_end",
+      Self::Capture => "capture",
+      Self::Disconnected => "disconnected",
+      Self::Reconnected => "reconnected",
+      Self::Idle => "idle",
+      Self::AppExited => "app_exited",
+      Self::AppStarted => "app_started",
+    }
+  }
+
+  fn parse(value: &str) -> Option<Self> {
+    match value {
+      "jank" => Some(Self::Jank),
+      "anr" => Some(Self::Anr),
+      "crash" => Some(Self::Crash),
+      "native_crash" => Some(Self::NativeCrash),
+      "alert" => Some(Self::Alert),
+      "throttle_begin" => Some(Self::ThrottleBegin),
+      "throttle_end" => Some(Self::ThrottleEnd),
+      "capture" => Some(Self::Capture),
+      "disconnected" => Some(Self::Disconnected),
+      "reconnected" => Some(Self::Reconnected),
+      "idle" => Some(Self::Idle),
+      "app_exited" => Some(Self::AppExited),
+      "app_started" => Some(Self::AppStarted),
+      _ => None,
+    }
+  }
+}
+
+/// 录制过程中检测到的卡顿 / ANR / 崩溃 / 告警 / 温控降频等事件，以及性能抓取记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionEvent {
+  pub id: i64,
+  pub t_ms: u64,
+  pub kind: EventKind,
+  pub message: String,
+  #[serde(default)]
+  pub attachments: Vec<AttachmentInfo>,
+  /// 卡顿事件关联出的可能原因，按置信度排序
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub causes: Vec<CauseHint>,
+  /// 告警规则触发的事件对应的规则 id
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub rule_id: Option<i64>,
+}
+
+/// 附件元信息，内容通过 `load_attachment` 单独读取
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentInfo {
+  pub id: i64,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub event_id: Option<i64>,
+  pub kind: String,
+  pub name: String,
+  pub size: u64,
+}
+
+pub fn add_event(
+  conn: &Connection,
+  session_id: i64,
+  t_ms: u64,
+  kind: EventKind,
+  message: &str,
+) -> Result<i64> {
+  conn.execute(
+    "INSERT INTO session_events (session_id, t_ms, kind, message) VALUES (?1, ?2, ?3, ?4)",
+    params![session_id, t_ms as i64, kind.as_str(), message],
+  )?;
+  Ok(conn.last_insert_rowid())
+}
+
+pub fn set_rule_id(conn: &Connection, event_id: i64, rule_id: i64) -> Result<()> {
+  conn.execute(
+    "UPDATE session_events SET rule_id = ?2 WHERE id = ?1",
+    params![event_id, rule_id],
+  )?;
+  Ok(())
+}
+
+pub fn set_causes(conn: &Connection, event_id: i64, causes: &[CauseHint]) -> Result<()> {
+  conn.execute(
+    "UPDATE session_events SET causes = ?2 WHERE id = ?1",
+    params![event_id, serde_json::to_string(causes)?],
+  )?;
+  Ok(())
+}
+
+pub fn add_attachment(
+  conn: &Connection,
+  session_id: i64,
+  event_id: Option<i64>,
+  kind: &str,
+  name: &str,
+  data: &[u8],
+) -> Result<AttachmentInfo> {
+  let compressed = zstd::encode_all(data, ZSTD_LEVEL)?;
+  conn.execute(
+    "INSERT INTO session_attachments (session_id, event_id, kind, name, size, data)
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    params![
+      session_id,
+      event_id,
+      kind,
+      name,
+      data.len() as i64,
+      compressed
+    ],
+  )?;
+  Ok(AttachmentInfo {
+    id: conn.last_insert_rowid(),
+    event_id,
+    kind: kind.to_string(),
+    name: name.to_string(),
+    size: data.len() as u64,
+  })
+}
+
+/// 会话的全部事件，按时间排序并附带各自的附件
+pub fn list_events(session_id: i64) -> Result<Vec<SessionEvent>> {
+  let conn = open()?;
+  let mut by_event: HashMap<i64, Vec<AttachmentInfo>> = HashMap::new();
+  for attachment in query_attachments(&conn, session_id)? {
+    if let Some(event_id) = attachment.event_id {
+      by_event.entry(event_id).or_default().push(attachment);
+    }
+  }
+
+  let mut stmt = conn.prepare(
+    "SELECT id, t_ms, kind, message, causes, rule_id FROM session_events
+     WHERE session_id = ?1 ORDER BY t_ms, id",
+  )?;
+  let rows = stmt.query_map(params![session_id], |row| {
+    Ok((
+      row.get::<_, i64>(0)?,
+      row.get::<_, i64>(1)?,
+      row.get::<_, String>(2)?,
+      row.get::<_, String>(3)?,
+      row.get::<_, Option<String>>(4)?,
+      row.get::<_, Option<i64>>(5)?,
+    ))
+  })?;
+
+  let mut events = Vec::new();
+  for row in rows {
+    let (id, t_ms, kind, message, causes, rule_id) = row?;
+    let Some(kind) = EventKind::parse(&kind) else {
+      continue;
+    };
+    events.push(SessionEvent {
+      id,
+      t_ms: t_ms.max(0) as u64,
+      kind,
+      message,
+      attachments: by_event.remove(&id).unwrap_or_default(),
+      causes: causes
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default(),
+      rule_id,
+    });
+  }
+  Ok(events)
+}
+
+pub fn query_attachments(conn: &Connection, session_id: i64) -> Result<Vec<AttachmentInfo>> {
+  let mut stmt = conn.prepare(
+    "SELECT id, event_id, kind, name, size FROM session_attachments
+     WHERE session_id = ?1 ORDER BY id",
+  )?;
+  let attachments = stmt
+    .query_map(params![session_id], |row| {
+      Ok(AttachmentInfo {
+        id: row.get(0)?,
+        event_id: row.get(1)?,
+        kind: row.get(2)?,
+        name: row.get(3)?,
+        size: row.get::<_, i64>(4)?.max(0) as u64,
+      })
+    })?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+  Ok(attachments)
+}
+
+/// 附件所属的会话与事件
+pub fn attachment_owner(conn: &Connection, id: i64) -> Result<(i64, Option<i64>)> {
+  conn
+    .query_row(
+      "SELECT session_id, event_id FROM session_attachments WHERE id = ?1",
+      params![id],
+      |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .optional()?
+    .ok_or_else(|| SessionError::Format(format!("附件不存在: {id}")))
+}
+
+/// 读取并解压附件内容
+pub fn load_attachment(id: i64) -> Result<Vec<u8>> {
+  let conn = open()?;
+  let data: Vec<u8> = conn
+    .query_row(
+      "SELECT data FROM session_attachments WHERE id = ?1",
+      params![id],
+      |row| row.get(0),
+    )
+    .optional()?
+    .ok_or_else(|| SessionError::Format(format!("附件不存在: {id}")))?;
+  Ok(zstd::decode_all(data.as_slice())?)
+}
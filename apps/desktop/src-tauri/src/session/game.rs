@@ -0,0 +1,74 @@
+//! 游戏模式控制：修改后若目标应用正在录制，更新会话信息中的游戏模式并插入标记
+
+use crate::{
+  adb::game::{
+    game_mode, reset_intervention, set_game_mode, set_intervention, GameMode, GameModeInfo,
+  },
+  session::{
+    error::Result,
+    meta::{query_meta, write_meta},
+    recorder::{active_recording, mark_recording, recording_target},
+    store::open,
+  },
+};
+use log::warn;
+use serde::Deserialize;
+
+/// 要设置的模式与可选的干预参数
+#[derive(Debug, Clone, Deserialize)]
+pub struct GameModeRequest {
+  pub mode: GameMode,
+  #[serde(default)]
+  pub downscale: Option<f32>,
+  #[serde(default)]
+  pub fps: Option<u32>,
+}
+
+pub fn get_game_mode(device_id: &str, package: &str) -> Result<GameModeInfo> {
+  Ok(game_mode(device_id, package)?)
+}
+
+pub fn set_game(device_id: &str, package: &str, request: &GameModeRequest) -> Result<GameModeInfo> {
+  set_game_mode(device_id, package, request.mode)?;
+  if request.downscale.is_some() || request.fps.is_some() {
+    set_intervention(
+      device_id,
+      package,
+      request.mode,
+      request.downscale,
+      request.fps,
+    )?;
+  }
+  let info = game_mode(device_id, package)?;
+  record(device_id, package, &info);
+  Ok(info)
+}
+
+/// 清除干预参数，返回清除后的状态
+pub fn reset_game(device_id: &str, package: &str) -> Result<GameModeInfo> {
+  reset_intervention(device_id, package)?;
+  let info = game_mode(device_id, package)?;
+  record(device_id, package, &info);
+  Ok(info)
+}
+
+/// 录制中的会话以该应用为目标时写入会话信息
+fn record(device_id: &str, package: &str, info: &GameModeInfo) {
+  let Ok(session_id) = active_recording(Some(device_id)) else {
+    return;
+  };
+  if !recording_target(session_id).is_some_and(|(_, p, _)| p == package) {
+    return;
+  }
+  let result = open().and_then(|conn| {
+    let mut meta = query_meta(&conn, session_id)?;
+    meta.game_mode = Some(info.clone());
+    write_meta(&conn, session_id, &meta)
+  });
+  if let Err(e) = result {
+    warn!("记录游戏模式失败: {}", e);
+  }
+  if let Err(e) = mark_recording(session_id, &format!("游戏模式 {}", info.label())) {
+    warn!("插入游戏模式标记失败: {}", e);
+  }
+}
@@ -0,0 +1,381 @@
+use crate::{
+  adb::{
+    app::{clear_app_data, stop_background_apps},
+    command::run_device,
+    energy::reset_batterystats,
+    error::Result as AdbResult,
+    frames::reset_gfxinfo,
+    settings::{
+      delete_setting, get_setting, put_setting, Namespace, ANIMATION_SCALES, BRIGHTNESS,
+      BRIGHTNESS_MODE,
+    },
+  },
+  session::{
+    error::Result,
+    events::{add_attachment, add_event, EventKind},
+    recorder::active_recording,
+    store::{delete_kv, open, set_kv},
+    unix_now_ms,
+  },
+};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+/// `svc power stayon` 实际修改的设置项
+const STAY_ON: &str = "stay_on_while_plugged_in";
+/// 待恢复设置在 app_kv 中的键前缀，完整键为 `前缀{时间戳}:{设备}`
+const PENDING_PREFIX: &str = "hook_restore:";
+
+/// 录制前后对设备执行的状态整理，使多次运行的结果可比
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionHooks {
+  /// 录制前清空 batterystats，耗电统计只覆盖本次录制
+  #[serde(default)]
+  pub reset_batterystats: bool,
+  /// 重置时一并开启完整唤醒锁历史，便于把耗电归因到具体唤醒锁
+  #[serde(default)]
+  pub full_wake_history: bool,
+  /// 录制前结束目标应用以外的第三方应用，减少后台同步等带来的干扰
+  #[serde(default)]
+  pub stop_background_apps: bool,
+  /// 不结束的包名
+  #[serde(default)]
+  pub background_allowlist: Vec<String>,
+  /// 录制前清空应用的 gfxinfo 帧统计
+  #[serde(default)]
+  pub reset_gfxinfo: bool,
+  /// 录制前清除应用数据（pm clear），从首次启动状态开始
+  #[serde(default)]
+  pub clear_app_data: bool,
+  /// 录制期间固定屏幕亮度（0~255）并关闭自动亮度，结束后恢复
+  #[serde(default)]
+  pub brightness: Option<u8>,
+  /// 录制期间关闭系统动画，结束后恢复原值
+  #[serde(default)]
+  pub disable_animations: bool,
+  /// 录制期间 USB 连接时保持屏幕常亮，避免中途息屏导致帧率与功耗数据失真
+  #[serde(default)]
+  pub keep_awake: bool,
+  /// 录制结束后把动画缩放统一设为 1，用于找回此前被测试框架关掉的动画
+  #[serde(default)]
+  pub enable_animations_after: bool,
+  /// 录制前后额外执行的 shell 命令
+  #[serde(default)]
+  pub pre_commands: Vec<String>,
+  #[serde(default)]
+  pub post_commands: Vec<String>,
+}
+
+impl SessionHooks {
+  pub fn is_empty(&self) -> bool {
+    !self.reset_batterystats
+      && !self.stop_background_apps
+      && !self.reset_gfxinfo
+      && !self.clear_app_data
+      && self.brightness.is_none()
+      && !self.disable_animations
+      && !self.keep_awake
+      && !self.enable_animations_after
+      && self.pre_commands.is_empty()
+      && self.post_commands.is_empty()
+  }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum HookStage {
+  Pre,
+  Post,
+}
+
+impl HookStage {
+  fn as_str(self) -> &'static str {
+    match self {
+      Self::Pre => "pre",
+      Self::Post => "post",
+    }
+  }
+
+  fn label(self) -> &'static str {
+    match self {
+      Self::Pre => "前",
+      Self::Post => "后",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookResult {
+  pub name: String,
+  pub ok: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedSetting {
+  namespace: Namespace,
+  key: String,
+  value: Option<String>,
+}
+
+/// 录制前被修改的设置项及原值，结束时写回；同时保存在数据库中，应用崩溃后由清理流程恢复
+#[derive(Debug, Default)]
+pub(crate) struct HookRestore {
+  settings: Vec<SavedSetting>,
+  pending_key: Option<String>,
+}
+
+impl HookRestore {
+  fn save(&mut self, namespace: Namespace, key: &str, value: Option<String>) {
+    self.settings.push(SavedSetting {
+      namespace,
+      key: key.to_string(),
+      value,
+    });
+  }
+}
+
+struct HookRunner<'a> {
+  device_id: &'a str,
+  results: Vec<HookResult>,
+}
+
+impl HookRunner<'_> {
+  fn run(&mut self, name: impl Into<String>, f: impl FnOnce() -> AdbResult<()>) {
+    let name = name.into();
+    let error = f().err().map(|e| e.to_string());
+    if let Some(e) = &error {
+      warn!("{} 失败: {}", name, e);
+    }
+    self.results.push(HookResult {
+      name,
+      ok: error.is_none(),
+      error,
+    });
+  }
+
+  fn shell(&mut self, command: &str) {
+    let device_id = self.device_id;
+    self.run(format!("执行 {command}"), || {
+      run_device(device_id, &["shell", command]).map(|_| ())
+    });
+  }
+}
+
+/// 执行录制前的操作，返回结果与需要在结束时恢复的设置
+pub(crate) fn run_pre_hooks(
+  device_id: &str,
+  package: &str,
+  hooks: &SessionHooks,
+) -> (Vec<HookResult>, HookRestore) {
+  let mut runner = HookRunner {
+    device_id,
+    results: Vec::new(),
+  };
+  let mut restore = HookRestore::default();
+  // 上次录制中途崩溃留下的设置先恢复，否则会把修改后的值当作原值
+  if active_recording(Some(device_id)).is_err() {
+    restore_pending(Some(device_id));
+  }
+  if hooks.clear_app_data {
+    runner.run("清除应用数据", || clear_app_data(device_id, package));
+  }
+  if hooks.stop_background_apps {
+    runner.run("结束后台应用", || {
+      let stopped = stop_background_apps(device_id, package, &hooks.background_allowlist)?;
+      info!("已结束后台应用: {}", stopped.join(", "));
+      Ok(())
+    });
+  }
+  if hooks.reset_batterystats {
+    runner.run("重置 batterystats", || {
+      reset_batterystats(device_id, hooks.full_wake_history)
+    });
+  }
+  if hooks.reset_gfxinfo {
+    runner.run("重置 gfxinfo", || reset_gfxinfo(device_id, package));
+  }
+  if let Some(level) = hooks.brightness {
+    runner.run(format!("屏幕亮度设为 {level}"), || {
+      for key in [BRIGHTNESS_MODE, BRIGHTNESS] {
+        let previous = get_setting(device_id, Namespace::System, key)?;
+        restore.save(Namespace::System, key, previous);
+      }
+      put_setting(device_id, Namespace::System, BRIGHTNESS_MODE, "0")?;
+      put_setting(device_id, Namespace::System, BRIGHTNESS, &level.to_string())
+    });
+  }
+  if hooks.disable_animations {
+    runner.run("关闭系统动画", || {
+      for key in ANIMATION_SCALES {
+        let previous = get_setting(device_id, Namespace::Global, key)?;
+        restore.save(Namespace::Global, key, previous);
+        put_setting(device_id, Namespace::Global, key, "0")?;
+      }
+      Ok(())
+    });
+  }
+  if hooks.keep_awake {
+    runner.run("保持屏幕常亮", || {
+      let previous = get_setting(device_id, Namespace::Global, STAY_ON)?;
+      restore.save(Namespace::Global, STAY_ON, previous);
+      run_device(device_id, &["shell", "svc", "power", "stayon", "usb"]).map(|_| ())
+    });
+  }
+  for command in &hooks.pre_commands {
+    runner.shell(command);
+  }
+  if !restore.settings.is_empty() {
+    let key = format!("{PENDING_PREFIX}{}:{device_id}", unix_now_ms());
+    match set_kv(&key, &restore.settings) {
+      Ok(()) => restore.pending_key = Some(key),
+      Err(e) => warn!("保存待恢复设置失败: {}", e),
+    }
+  }
+  (runner.results, restore)
+}
+
+/// 执行录制后的操作，先恢复录制前修改的设置
+pub(crate) fn run_post_hooks(
+  device_id: &str,
+  hooks: &SessionHooks,
+  restore: HookRestore,
+) -> Vec<HookResult> {
+  let mut runner = HookRunner {
+    device_id,
+    results: Vec::new(),
+  };
+  if !restore.settings.is_empty() {
+    runner.run("恢复录制前的设置", || {
+      apply_restore(device_id, &restore.settings)
+    });
+    // 恢复失败（如设备已断开）时保留记录，由清理流程稍后重试
+    if let (Some(key), Some(true)) = (&restore.pending_key, runner.results.last().map(|r| r.ok)) {
+      if let Err(e) = delete_kv(key) {
+        warn!("清除待恢复设置失败: {}", e);
+      }
+    }
+  }
+  if hooks.enable_animations_after {
+    runner.run("开启系统动画", || {
+      for key in ANIMATION_SCALES {
+        put_setting(device_id, Namespace::Global, key, "1")?;
+      }
+      Ok(())
+    });
+  }
+  for command in &hooks.post_commands {
+    runner.shell(command);
+  }
+  runner.results
+}
+
+fn apply_restore(device_id: &str, settings: &[SavedSetting]) -> AdbResult<()> {
+  // 亮度模式需在亮度之后恢复，否则自动亮度会被手动值覆盖
+  for saved in settings.iter().rev() {
+    match &saved.value {
+      Some(value) => put_setting(device_id, saved.namespace, &saved.key, value)?,
+      None => delete_setting(device_id, saved.namespace, &saved.key)?,
+    }
+  }
+  Ok(())
+}
+
+/// 清理流程：恢复此前录制未能写回的设置（应用崩溃或结束时设备已断开），
+/// 指定设备时只处理该设备；设备不在线的记录保留到下次
+pub fn restore_pending(device_id: Option<&str>) {
+  let pending = match list_pending() {
+    Ok(pending) => pending,
+    Err(e) => {
+      warn!("读取待恢复设置失败: {}", e);
+      return;
+    }
+  };
+  // 同一设备有多条时从最新的开始恢复，最早记录的原值最后写回
+  for (key, device, settings) in pending.into_iter().rev() {
+    if device_id.is_some_and(|d| d != device) {
+      continue;
+    }
+    match apply_restore(&device, &settings) {
+      Ok(()) => {
+        info!("已恢复设备 {} 上次录制前的设置", device);
+        if let Err(e) = delete_kv(&key) {
+          warn!("清除待恢复设置失败: {}", e);
+        }
+      }
+      Err(e) => warn!("恢复设备 {} 的设置失败，稍后重试: {}", device, e),
+    }
+  }
+}
+
+/// 按记录时间先后返回 (键, 设备, 设置)
+fn list_pending() -> Result<Vec<(String, String, Vec<SavedSetting>)>> {
+  let conn = open()?;
+  // 按范围匹配前缀，设备序列号中的 `_` 不会被 LIKE 当作通配符
+  let mut stmt =
+    conn.prepare("SELECT key, value FROM app_kv WHERE key >= ?1 AND key < ?1 || char(0x10FFFF)")?;
+  let rows = stmt.query_map([PENDING_PREFIX], |row| {
+    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+  })?;
+  let mut pending = Vec::new();
+  for row in rows {
+    let (key, value) = row?;
+    let Some((stamp, device)) = key[PENDING_PREFIX.len()..].split_once(':') else {
+      continue;
+    };
+    let Ok(stamp) = stamp.parse::<i64>() else {
+      continue;
+    };
+    pending.push((
+      stamp,
+      key.clone(),
+      device.to_string(),
+      serde_json::from_str(&value)?,
+    ));
+  }
+  pending.sort_by_key(|p| p.0);
+  Ok(
+    pending
+      .into_iter()
+      .map(|(_, key, device, settings)| (key, device, settings))
+      .collect(),
+  )
+}
+
+/// 保存执行结果为附件，失败的操作记为告警事件
+pub(crate) fn save_hook_results(
+  session_id: i64,
+  stage: HookStage,
+  t_ms: u64,
+  results: &[HookResult],
+) -> Result<()> {
+  if results.is_empty() {
+    return Ok(());
+  }
+  let conn = open()?;
+  for result in results.iter().filter(|r| !r.ok) {
+    let message = format!(
+      "录制{}操作失败: {}（{}）",
+      stage.label(),
+      result.name,
+      result.error.as_deref().unwrap_or("未知错误")
+    );
+    add_event(&conn, session_id, t_ms, EventKind::Alert, &message)?;
+  }
+  add_attachment(
+    &conn,
+    session_id,
+    None,
+    "hooks",
+    &format!("hooks-{}.json", stage.as_str()),
+    &serde_json::to_vec_pretty(results)?,
+  )?;
+  info!(
+    "会话 {} 录制{}操作完成，{}/{} 成功",
+    session_id,
+    stage.label(),
+    results.iter().filter(|r| r.ok).count(),
+    results.len()
+  );
+  Ok(())
+}
@@ -0,0 +1,73 @@
+//! 从 PerfX 切换设备端的 GPU 呈现模式条形图与刷新率显示：首次切换前记录原状态，
+//! 录制中切换的在该次录制结束时恢复，其余在退出应用时恢复
+
+use crate::{
+  adb::hwui::{overlay_state, restore_overlays, set_overlay, DeviceOverlay, OverlayState},
+  session::{
+    error::Result,
+    recorder::{active_recording, add_observer, mark_recording, RecordingObserver},
+    restore::DeviceSetting,
+  },
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, thread};
+
+static SAVED: DeviceSetting<SavedOverlays> = DeviceSetting::new("hwui_restore:", "渲染显示设置");
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedOverlays {
+  original: OverlayState,
+  /// 首次切换时设备上录制中的会话，结束时自动恢复
+  session_id: Option<i64>,
+}
+
+pub fn get_device_overlays(device_id: &str) -> Result<OverlayState> {
+  Ok(overlay_state(device_id)?)
+}
+
+pub fn set_device_overlay(device_id: &str, overlay: DeviceOverlay, enabled: bool) -> Result<()> {
+  let session_id = active_recording(Some(device_id)).ok();
+  SAVED.save_original(device_id, || {
+    Ok(SavedOverlays {
+      original: overlay_state(device_id)?,
+      session_id,
+    })
+  })?;
+  set_overlay(device_id, overlay, enabled)?;
+  if let Some(session_id) = session_id {
+    let action = if enabled { "开启" } else { "关闭" };
+    if let Err(e) = mark_recording(session_id, &format!("{}{}", action, overlay.label())) {
+      warn!("插入标记失败: {}", e);
+    }
+  }
+  Ok(())
+}
+
+/// 恢复切换前的状态；没有切换过时返回 false
+pub fn restore_device_overlays(device_id: &str) -> Result<bool> {
+  SAVED.restore(device_id, |saved| {
+    Ok(restore_overlays(device_id, &saved.original)?)
+  })
+}
+
+/// 恢复所有设备；`session_id` 非空时只处理在该次录制中切换的设备
+pub fn restore_all_overlays(session_id: Option<i64>) {
+  SAVED.restore_all(
+    |saved| session_id.map_or(true, |id| saved.session_id == Some(id)),
+    restore_device_overlays,
+  );
+}
+
+struct OverlayReverter;
+
+impl RecordingObserver for OverlayReverter {
+  fn on_stopped(&self, session_id: i64) {
+    // 回调在录制线程中执行，adb 调用放到后台
+    thread::spawn(move || restore_all_overlays(Some(session_id)));
+  }
+}
+
+pub fn register() {
+  add_observer(Arc::new(OverlayReverter));
+}
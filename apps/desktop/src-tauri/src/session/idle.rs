@@ -0,0 +1,73 @@
+//! Doze 与待机分组控制：录制中切换时在时间线上插入标记；
+//! 开启跟踪时定期读取 Doze 状态，状态变化记为事件
+
+use crate::{
+  adb::idle::{
+    force_idle, idle_state, set_standby_bucket, standby_bucket, unforce_idle, IdleState,
+    StandbyBucket,
+  },
+  session::{
+    error::Result,
+    recorder::{active_recording, mark_recording},
+  },
+};
+use log::warn;
+
+const ACTIVE: &str = "ACTIVE";
+
+pub fn get_idle_state(device_id: &str) -> Result<IdleState> {
+  Ok(idle_state(device_id)?)
+}
+
+/// 强制进入或退出 deep Doze，返回切换后的状态
+pub fn set_doze(device_id: &str, enabled: bool) -> Result<IdleState> {
+  let name = if enabled {
+    force_idle(device_id)?;
+    "强制进入 Doze"
+  } else {
+    unforce_idle(device_id)?;
+    "退出 Doze"
+  };
+  mark(device_id, name);
+  Ok(idle_state(device_id)?)
+}
+
+pub fn get_standby_bucket(device_id: &str, package: &str) -> Result<StandbyBucket> {
+  Ok(standby_bucket(device_id, package)?)
+}
+
+pub fn set_bucket(device_id: &str, package: &str, bucket: StandbyBucket) -> Result<()> {
+  set_standby_bucket(device_id, package, bucket)?;
+  mark(device_id, &format!("待机分组设为 {}", bucket.as_str()));
+  Ok(())
+}
+
+fn mark(device_id: &str, name: &str) {
+  let Ok(session_id) = active_recording(Some(device_id)) else {
+    return;
+  };
+  if let Err(e) = mark_recording(session_id, name) {
+    warn!("插入 Doze 标记失败: {}", e);
+  }
+}
+
+/// 录制中跟踪 Doze 状态，首次读取时不处于 ACTIVE 也记录一次
+#[derive(Debug, Default)]
+pub struct IdleTracker {
+  last: Option<IdleState>,
+}
+
+impl IdleTracker {
+  /// 状态变化时返回待记录的事件内容
+  pub fn update(&mut self, state: IdleState) -> Option<String> {
+    let message = match &self.last {
+      Some(last) if *last == state => None,
+      None if state.deep.as_deref() == Some(ACTIVE) && state.light.as_deref() == Some(ACTIVE) => {
+        None
+      }
+      _ => Some(state.label()),
+    };
+    self.last = Some(state);
+    message
+  }
+}
@@ -0,0 +1,149 @@
+use crate::session::{
+  bundle::{import_bundle, BUNDLE_FORMAT},
+  error::{Result, SessionError},
+  metric_label,
+  store::{insert_session, parse_chart_data},
+  stream::read_jsonl,
+  unix_now, Sample, Session, SessionData,
+};
+use perfx_core::schema::LEGACY_SCHEMA_VERSION;
+use serde_json::Value;
+use std::{collections::BTreeMap, fs, path::Path};
+
+// 前端按 1 秒间隔采样，CSV 中没有时间偏移列时按此推算
+const DEFAULT_INTERVAL_MS: u64 = 1000;
+
+/// 导入之前导出的会话文件，作为新会话写入数据库并返回其 id
+pub fn import_file(path: &Path, name: Option<String>, passphrase: Option<&str>) -> Result<i64> {
+  let ext = path
+    .extension()
+    .and_then(|e| e.to_str())
+    .map(|e| e.to_ascii_lowercase());
+
+  if ext.as_deref() == Some(BUNDLE_FORMAT) {
+    return import_bundle(path, name, passphrase);
+  }
+
+  let mut data = match ext.as_deref() {
+    Some("json") => read_json(path)?,
+    Some("csv") => read_csv(path)?,
+    Some("jsonl") => read_jsonl(path)?,
+    _ => {
+      return Err(SessionError::Format(format!(
+        "不支持的文件类型: {}",
+        path.display()
+      )));
+    }
+  };
+
+  if let Some(name) = name.filter(|n| !n.trim().is_empty()) {
+    data.session.name = name;
+  }
+  if data.samples.is_empty() {
+    return Err(SessionError::Format("文件中没有采样数据".into()));
+  }
+  data.upgrade()?;
+
+  insert_session(&data)
+}
+
+/// 支持两种 JSON：完整的 SessionData（导出格式），或前端 chart_data 数组
+fn read_json(path: &Path) -> Result<SessionData> {
+  let raw = fs::read_to_string(path)?;
+  let value: Value = serde_json::from_str(raw.trim_start_matches('\u{feff}'))?;
+
+  match value {
+    Value::Object(ref obj) if obj.contains_key("session") && obj.contains_key("samples") => {
+      Ok(serde_json::from_value(value)?)
+    }
+    Value::Array(ref rows) => {
+      let duration = rows.len().saturating_sub(1) as i64 * DEFAULT_INTERVAL_MS as i64 / 1000;
+      let samples = parse_chart_data(&value.to_string(), duration)?;
+      Ok(imported_session(path, samples))
+    }
+    _ => Err(SessionError::Format("无法识别的 JSON 会话格式".into())),
+  }
+}
+
+/// 读取前端导出的 CSV：首行可能只有 BOM，表头包含 `time` 与各指标列
+fn read_csv(path: &Path) -> Result<SessionData> {
+  let raw = fs::read_to_string(path)?;
+  let content = raw.trim_start_matches('\u{feff}').trim_start();
+
+  let mut reader = csv::ReaderBuilder::new()
+    .flexible(true)
+    .from_reader(content.as_bytes());
+  let headers = reader
+    .headers()
+    .map_err(|e| SessionError::Format(e.to_string()))?
+    .clone();
+
+  let mut samples = Vec::new();
+  for (index, record) in reader.records().enumerate() {
+    let record = record.map_err(|e| SessionError::Format(e.to_string()))?;
+    let mut sample = Sample {
+      t_ms: index as u64 * DEFAULT_INTERVAL_MS,
+      time: None,
+      values: BTreeMap::new(),
+    };
+
+    for (header, field) in headers.iter().zip(record.iter()) {
+      let field = field.trim();
+      match header.trim() {
+        "time" => sample.time = Some(field.to_string()).filter(|s| !s.is_empty()),
+        "t_ms" => sample.t_ms = field.parse().unwrap_or(sample.t_ms),
+        key => {
+          if let Ok(value) = field.parse::<f64>() {
+            sample.values.insert(key.to_string(), value);
+          }
+        }
+      }
+    }
+    samples.push(sample);
+  }
+
+  Ok(imported_session(path, samples))
+}
+
+fn imported_session(path: &Path, samples: Vec<Sample>) -> SessionData {
+  let now = unix_now();
+  let duration = samples.last().map(|s| (s.t_ms / 1000) as i64).unwrap_or(0);
+  let name = path
+    .file_stem()
+    .map(|s| s.to_string_lossy().to_string())
+    .unwrap_or_else(|| "导入的会话".into());
+
+  let mut data = SessionData {
+    session: Session {
+      id: 0,
+      name,
+      device_id: "imported".into(),
+      device_model: None,
+      app_package: "unknown".into(),
+      app_label: None,
+      metrics: Vec::new(),
+      start_time: now - duration,
+      end_time: now,
+      duration,
+      created_at: now,
+      // 前端图表与 CSV 不带版本号，按旧版本处理
+      schema_version: LEGACY_SCHEMA_VERSION,
+    },
+    samples,
+    markers: Vec::new(),
+    meta: Default::default(),
+  };
+  // 图表列名映射回前端的 MetricKey，未知列不计入
+  for name in data.metric_names() {
+    let key = match name.as_str() {
+      "traffic_rx" | "traffic_tx" => "traffic",
+      "network_rx" | "network_tx" => "network",
+      "cpu_normalized" => "cpu",
+      other => other,
+    };
+    if metric_label(&name).0 != name && !data.session.metrics.iter().any(|m| m == key) {
+      data.session.metrics.push(key.to_string());
+    }
+  }
+  data
+}
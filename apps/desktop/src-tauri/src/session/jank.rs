@@ -0,0 +1,155 @@
+use crate::{adb::logcat::parse_line, session::Sample};
+use serde::{Deserialize, Serialize};
+
+// 卡顿前后参与关联的时间窗口
+const BEFORE_MS: u64 = 2_000;
+const AFTER_MS: u64 = 1_000;
+// 窗口之前作为基线的时长
+const BASELINE_MS: u64 = 30_000;
+/// 关联时需要回看的采样时长
+pub const LOOKBACK_MS: u64 = BEFORE_MS + BASELINE_MS;
+// 电池温度达到该值时系统通常已开始温控降频
+const THERMAL_TEMP_C: f64 = 42.0;
+// 流量突增的最低绝对值，过滤空闲时的小幅波动
+const MIN_BURST_KBPS: f64 = 100.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JankCause {
+  CpuSpike,
+  Gc,
+  ThermalThrottle,
+  BinderStall,
+  NetworkBurst,
+}
+
+/// 卡顿的可能原因，confidence 为 0–1 的粗略置信度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CauseHint {
+  pub cause: JankCause,
+  pub confidence: f64,
+  pub detail: String,
+}
+
+/// 将卡顿时刻与同期的采样和日志关联，按置信度从高到低返回可能原因
+///
+/// `logs` 为 (相对会话开始的毫秒, logcat 原始行)。
+pub fn correlate(t_ms: u64, samples: &[Sample], logs: &[(u64, String)]) -> Vec<CauseHint> {
+  let from = t_ms.saturating_sub(BEFORE_MS);
+  let to = t_ms + AFTER_MS;
+  let baseline_from = from.saturating_sub(BASELINE_MS);
+  let in_window = |t: u64| (from..=to).contains(&t);
+  let in_baseline = |t: u64| (baseline_from..from).contains(&t);
+
+  let mut hints = Vec::new();
+
+  let cpu = |s: &Sample| s.values.get("cpu").copied();
+  if let Some((peak, base)) = metric_window(samples, cpu, in_window, in_baseline) {
+    let rise = peak - base.unwrap_or(peak);
+    if rise >= 15.0 || (base.is_none() && peak >= 80.0) {
+      hints.push(CauseHint {
+        cause: JankCause::CpuSpike,
+        confidence: (rise.max(peak - 50.0) / 50.0).clamp(0.3, 0.9),
+        detail: match base {
+          Some(base) => format!("CPU 由 {base:.0}% 升至 {peak:.0}%"),
+          None => format!("CPU 峰值 {peak:.0}%"),
+        },
+      });
+    }
+  }
+
+  let traffic = |s: &Sample| {
+    let rx = s.values.get("traffic_rx");
+    let tx = s.values.get("traffic_tx");
+    (rx.is_some() || tx.is_some()).then(|| rx.unwrap_or(&0.0) + tx.unwrap_or(&0.0))
+  };
+  if let Some((peak, base)) = metric_window(samples, traffic, in_window, in_baseline) {
+    let base = base.unwrap_or(0.0);
+    if peak >= MIN_BURST_KBPS && peak >= base * 3.0 {
+      let ratio = if base > 0.0 { peak / base } else { 10.0 };
+      hints.push(CauseHint {
+        cause: JankCause::NetworkBurst,
+        confidence: (ratio / 20.0).clamp(0.2, 0.6),
+        detail: format!("流量由 {base:.0} KB/s 增至 {peak:.0} KB/s"),
+      });
+    }
+  }
+
+  let temp = samples
+    .iter()
+    .filter(|s| in_window(s.t_ms))
+    .filter_map(|s| s.values.get("battery_temp").copied())
+    .reduce(f64::max);
+  let mut gc = Vec::new();
+  let mut binder = Vec::new();
+  let mut thermal = Vec::new();
+  for (_, line) in logs.iter().filter(|(t, _)| in_window(*t)) {
+    let Some(parsed) = parse_line(line) else {
+      continue;
+    };
+    let message = parsed.message.trim();
+    if message.contains("GC freed") || (message.contains("paused") && message.contains("GC")) {
+      gc.push(message);
+    } else if message.contains("Slow Binder")
+      || message.contains("Long monitor contention")
+      || message.contains("binder transaction")
+    {
+      binder.push(message);
+    } else if parsed.tag.contains("Thermal") || message.to_lowercase().contains("throttl") {
+      thermal.push(message);
+    }
+  }
+
+  if let Some(first) = gc.first() {
+    hints.push(CauseHint {
+      cause: JankCause::Gc,
+      confidence: (0.4 + 0.15 * gc.len() as f64).min(0.85),
+      detail: format!("{} 次 GC：{}", gc.len(), first),
+    });
+  }
+  if let Some(first) = binder.first() {
+    hints.push(CauseHint {
+      cause: JankCause::BinderStall,
+      confidence: (0.5 + 0.1 * binder.len() as f64).min(0.8),
+      detail: first.to_string(),
+    });
+  }
+  if let Some(first) = thermal.first() {
+    hints.push(CauseHint {
+      cause: JankCause::ThermalThrottle,
+      confidence: 0.7,
+      detail: first.to_string(),
+    });
+  } else if let Some(temp) = temp.filter(|t| *t >= THERMAL_TEMP_C) {
+    hints.push(CauseHint {
+      cause: JankCause::ThermalThrottle,
+      confidence: ((temp - THERMAL_TEMP_C) / 10.0 + 0.3).min(0.6),
+      detail: format!("电池温度 {temp:.1}°C"),
+    });
+  }
+
+  hints.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+  hints
+}
+
+/// 窗口内的峰值与基线中位数
+fn metric_window(
+  samples: &[Sample],
+  value: impl Fn(&Sample) -> Option<f64>,
+  in_window: impl Fn(u64) -> bool,
+  in_baseline: impl Fn(u64) -> bool,
+) -> Option<(f64, Option<f64>)> {
+  let values = |keep: &dyn Fn(u64) -> bool| -> Vec<f64> {
+    samples
+      .iter()
+      .filter(|s| keep(s.t_ms))
+      .filter_map(&value)
+      .filter(|v| v.is_finite())
+      .collect()
+  };
+  let peak = values(&in_window).into_iter().reduce(f64::max)?;
+  let mut base = values(&in_baseline);
+  base.sort_by(|a, b| a.total_cmp(b));
+  let median = (!base.is_empty()).then(|| base[base.len() / 2]);
+  Some((peak, median))
+}
@@ -0,0 +1,505 @@
+use crate::{
+  adb::{
+    logcat::{parse_line, spawn_logcat, LogLine},
+    metrics::resolve_pid,
+  },
+  session::{
+    error::{Result, SessionError},
+    events::{add_attachment, add_event, set_causes, EventKind, SessionEvent},
+    jank::{correlate, LOOKBACK_MS},
+    recorder::{notify, RecordingConfig},
+    store::open,
+    Sample,
+  },
+};
+use log::warn;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::{HashMap, VecDeque},
+  io::{BufRead, BufReader},
+  process::Child,
+  sync::{
+    atomic::{AtomicU32, Ordering},
+    mpsc::{self, RecvTimeoutError},
+    Arc, Mutex,
+  },
+  thread::{self, JoinHandle},
+  time::{Duration, Instant},
+};
+
+// 事件前后各截取的日志时长
+const EXCERPT_WINDOW_MS: u64 = 5_000;
+// 缓冲保留时长需大于窗口，给落盘留出余量
+const BUFFER_KEEP_MS: u64 = 30_000;
+// 与崩溃 / ANR 相关、但不一定由应用进程输出的 tag
+const SYSTEM_TAGS: &[&str] = &["AndroidRuntime", "ActivityManager", "DEBUG", "libc"];
+// 温控相关 tag，用于卡顿原因关联，不限日志级别
+const THERMAL_TAGS: &[&str] = &["ThermalManagerService", "thermal-engine", "ThermalEngine"];
+// 录制的日志按块压缩落盘，满足任一条件即写出一块
+const CHUNK_FLUSH_MS: u64 = 10_000;
+const CHUNK_MAX_LINES: usize = 2_000;
+const ZSTD_LEVEL: i32 = 3;
+const DEFAULT_QUERY_LIMIT: usize = 5_000;
+
+/// 待落盘的事件，日志窗口结束后再截取
+#[derive(Debug, Clone)]
+pub struct PendingEvent {
+  pub t_ms: u64,
+  pub kind: EventKind,
+  pub message: String,
+}
+
+type LogBuffer = Arc<Mutex<VecDeque<(u64, String)>>>;
+type LogSink = Arc<Mutex<Vec<(u64, String)>>>;
+type SampleBuffer = Arc<Mutex<Vec<Sample>>>;
+/// 应用主进程的 PID，0 表示未知
+pub type SharedPid = Arc<AtomicU32>;
+
+/// 录制期间持续读取 logcat，检测 ANR / 崩溃并为事件保存前后日志片段，可选保存完整日志
+pub struct LogcatWatcher {
+  child: Child,
+  events_tx: mpsc::Sender<PendingEvent>,
+  reader: JoinHandle<()>,
+  processor: JoinHandle<()>,
+  pid: SharedPid,
+}
+
+impl LogcatWatcher {
+  pub fn start(
+    session_id: i64,
+    config: &RecordingConfig,
+    started: Instant,
+    samples: SampleBuffer,
+  ) -> Result<Self> {
+    let (device_id, package) = (config.device_id.as_str(), config.package.as_str());
+    let detect_events = config.options.logcat_on_events;
+    let record = config.options.record_logcat;
+    let mut child = spawn_logcat(device_id)?;
+    let stdout = child.stdout.take();
+    let pid: SharedPid = Arc::new(AtomicU32::new(0));
+    refresh_pid(&pid, device_id, package, config.options.user, Some(true));
+
+    let buffer: LogBuffer = Arc::new(Mutex::new(VecDeque::new()));
+    let sink: Option<LogSink> = record.then(|| Arc::new(Mutex::new(Vec::new())));
+    let (events_tx, events_rx) = mpsc::channel();
+
+    let reader = {
+      let buffer = buffer.clone();
+      let sink = sink.clone();
+      let events_tx = events_tx.clone();
+      let package = package.to_string();
+      let pid = pid.clone();
+      thread::spawn(move || {
+        let Some(stdout) = stdout else {
+          return;
+        };
+        for line in BufReader::new(stdout).lines() {
+          let Ok(line) = line else {
+            break;
+          };
+          let t_ms = started.elapsed().as_millis() as u64;
+          let Some(parsed) = parse_line(&line) else {
+            continue;
+          };
+          // 采样周期内完成的重启由系统日志得知新 PID
+          if let Some(started) = started_pid(&parsed, &package) {
+            pid.store(started, Ordering::Relaxed);
+          }
+          let pid = Some(pid.load(Ordering::Relaxed)).filter(|p| *p != 0);
+          if !is_relevant(&parsed, pid, &package) {
+            continue;
+          }
+          if let Some((kind, message)) = detect_event(&parsed, &package).filter(|_| detect_events) {
+            let _ = events_tx.send(PendingEvent {
+              t_ms,
+              kind,
+              message,
+            });
+          }
+          if let Some(Ok(mut sink)) = sink.as_ref().map(|s| s.lock()) {
+            sink.push((t_ms, line.clone()));
+          }
+          if let Ok(mut buffer) = buffer.lock() {
+            buffer.push_back((t_ms, line));
+            while buffer
+              .front()
+              .is_some_and(|(t, _)| *t + BUFFER_KEEP_MS < t_ms)
+            {
+              buffer.pop_front();
+            }
+          }
+        }
+      })
+    };
+
+    let processor =
+      thread::spawn(move || excerpt_loop(session_id, started, buffer, samples, sink, events_rx));
+
+    Ok(Self {
+      child,
+      events_tx,
+      reader,
+      processor,
+      pid,
+    })
+  }
+
+  /// 供采样线程在应用重启后更新 PID
+  pub fn pid(&self) -> SharedPid {
+    self.pid.clone()
+  }
+
+  /// 供采样线程上报卡顿等外部检测到的事件
+  pub fn reporter(&self) -> mpsc::Sender<PendingEvent> {
+    self.events_tx.clone()
+  }
+
+  /// 结束 logcat 进程，并立即保存尚未落盘的事件
+  pub fn stop(mut self) {
+    let _ = self.child.kill();
+    let _ = self.child.wait();
+    let _ = self.reader.join();
+    drop(self.events_tx);
+    let _ = self.processor.join();
+  }
+}
+
+/// 按采样得到的应用运行状态更新 PID：退出后清空，运行中但 PID 未知时重新解析
+pub fn refresh_pid(
+  pid: &SharedPid,
+  device_id: &str,
+  package: &str,
+  user: Option<u32>,
+  running: Option<bool>,
+) {
+  match running {
+    Some(false) => pid.store(0, Ordering::Relaxed),
+    Some(true) if pid.load(Ordering::Relaxed) == 0 => {
+      if let Some(resolved) = resolve_pid(device_id, package, user)
+        .ok()
+        .and_then(|p| p.parse::<u32>().ok())
+      {
+        pid.store(resolved, Ordering::Relaxed);
+      }
+    }
+    _ => {}
+  }
+}
+
+/// `ActivityManager: Start proc 12345:com.example/u0a123 for ...` 中主进程的 PID
+fn started_pid(line: &LogLine, package: &str) -> Option<u32> {
+  if line.tag != "ActivityManager" {
+    return None;
+  }
+  let (pid, rest) = line
+    .message
+    .trim()
+    .strip_prefix("Start proc ")?
+    .split_once(':')?;
+  rest
+    .strip_prefix(package)
+    .filter(|r| r.starts_with('/'))
+    .and_then(|_| pid.parse().ok())
+}
+
+/// 应用进程的全部日志，以及系统 tag 中警告及以上级别的日志
+fn is_relevant(line: &LogLine, pid: Option<u32>, package: &str) -> bool {
+  Some(line.pid) == pid
+    || line.message.contains(package)
+    || (SYSTEM_TAGS.contains(&line.tag) && matches!(line.level, 'W' | 'E' | 'F'))
+    || THERMAL_TAGS.contains(&line.tag)
+}
+
+/// 从系统日志识别应用崩溃与 ANR
+fn detect_event(line: &LogLine, package: &str) -> Option<(EventKind, String)> {
+  let message = line.message.trim();
+  match line.tag {
+    "AndroidRuntime" if message.starts_with("Process:") && message.contains(package) => {
+      Some((EventKind::Crash, message.to_string()))
+    }
+    "ActivityManager" if message.starts_with("ANR in") && message.contains(package) => {
+      Some((EventKind::Anr, message.to_string()))
+    }
+    "DEBUG" if message.contains(&format!(">>> {package} <<<")) => {
+      Some((EventKind::NativeCrash, message.to_string()))
+    }
+    _ => None,
+  }
+}
+
+fn excerpt_loop(
+  session_id: i64,
+  started: Instant,
+  buffer: LogBuffer,
+  samples: SampleBuffer,
+  sink: Option<LogSink>,
+  events_rx: mpsc::Receiver<PendingEvent>,
+) {
+  let mut conn: Option<Connection> = None;
+  let mut pending: Vec<PendingEvent> = Vec::new();
+  let mut last_by_kind: HashMap<EventKind, u64> = HashMap::new();
+  let mut last_flush_ms = 0;
+
+  loop {
+    let disconnected = match events_rx.recv_timeout(Duration::from_millis(500)) {
+      Ok(event) => {
+        // 同类事件在一个窗口内只记录一次，避免连续卡顿刷屏
+        let duplicate = last_by_kind
+          .get(&event.kind)
+          .is_some_and(|last| event.t_ms < last + EXCERPT_WINDOW_MS);
+        if !duplicate {
+          last_by_kind.insert(event.kind, event.t_ms);
+          pending.push(event);
+        }
+        false
+      }
+      Err(RecvTimeoutError::Timeout) => false,
+      Err(RecvTimeoutError::Disconnected) => true,
+    };
+
+    let now_ms = started.elapsed().as_millis() as u64;
+    let (ready, waiting): (Vec<_>, Vec<_>) = pending
+      .drain(..)
+      .partition(|e| disconnected || now_ms >= e.t_ms + EXCERPT_WINDOW_MS);
+    pending = waiting;
+
+    if !ready.is_empty() {
+      if conn.is_none() {
+        conn = open().map_err(|e| warn!("打开数据库失败: {}", e)).ok();
+      }
+      if let Some(conn) = &conn {
+        for event in ready {
+          if let Err(e) = persist_event(conn, session_id, &buffer, &samples, event) {
+            warn!("保存会话事件失败: {}", e);
+          }
+        }
+      }
+    }
+
+    let lines = sink
+      .as_ref()
+      .and_then(|sink| sink.lock().ok())
+      .filter(|sink| {
+        !sink.is_empty()
+          && (disconnected
+            || sink.len() >= CHUNK_MAX_LINES
+            || now_ms >= last_flush_ms + CHUNK_FLUSH_MS)
+      })
+      .map(|mut sink| std::mem::take(&mut *sink));
+    if let Some(lines) = lines {
+      last_flush_ms = now_ms;
+      if conn.is_none() {
+        conn = open().map_err(|e| warn!("打开数据库失败: {}", e)).ok();
+      }
+      if let Some(conn) = &conn {
+        if let Err(e) = store_chunk(conn, session_id, &lines) {
+          warn!("保存 logcat 失败: {}", e);
+        }
+      }
+    }
+    if disconnected {
+      break;
+    }
+  }
+}
+
+fn persist_event(
+  conn: &Connection,
+  session_id: i64,
+  buffer: &LogBuffer,
+  samples: &SampleBuffer,
+  event: PendingEvent,
+) -> Result<()> {
+  let from = event.t_ms.saturating_sub(EXCERPT_WINDOW_MS);
+  let to = event.t_ms + EXCERPT_WINDOW_MS;
+  let lines: Vec<(u64, String)> = buffer
+    .lock()
+    .map(|buffer| {
+      buffer
+        .iter()
+        .filter(|(t, _)| (from..=to).contains(t))
+        .cloned()
+        .collect()
+    })
+    .unwrap_or_default();
+  let excerpt = lines
+    .iter()
+    .map(|(_, line)| line.as_str())
+    .collect::<Vec<_>>()
+    .join("\n");
+
+  let id = add_event(conn, session_id, event.t_ms, event.kind, &event.message)?;
+  let causes = if event.kind == EventKind::Jank {
+    let samples: Vec<Sample> = samples
+      .lock()
+      .map(|s| {
+        s.iter()
+          .filter(|s| s.t_ms + LOOKBACK_MS >= event.t_ms)
+          .cloned()
+          .collect()
+      })
+      .unwrap_or_default();
+    correlate(event.t_ms, &samples, &lines)
+  } else {
+    Vec::new()
+  };
+  if !causes.is_empty() {
+    set_causes(conn, id, &causes)?;
+  }
+  let mut attachments = Vec::new();
+  if !excerpt.is_empty() {
+    let name = format!("logcat-{}-{}.txt", event.kind.as_str(), event.t_ms);
+    attachments.push(add_attachment(
+      conn,
+      session_id,
+      Some(id),
+      "logcat",
+      &name,
+      excerpt.as_bytes(),
+    )?);
+  }
+
+  let event = SessionEvent {
+    id,
+    t_ms: event.t_ms,
+    kind: event.kind,
+    message: event.message,
+    attachments,
+    causes,
+    rule_id: None,
+  };
+  notify(|o| o.on_event(session_id, &event));
+  Ok(())
+}
+
+/// 每行存为 `t_ms\t原始日志`
+fn store_chunk(conn: &Connection, session_id: i64, lines: &[(u64, String)]) -> Result<()> {
+  let (Some(first), Some(last)) = (lines.first(), lines.last()) else {
+    return Ok(());
+  };
+  let mut text = String::new();
+  for (t_ms, line) in lines {
+    text.push_str(&t_ms.to_string());
+    text.push('\t');
+    text.push_str(line);
+    text.push('\n');
+  }
+  let data = zstd::encode_all(text.as_bytes(), ZSTD_LEVEL)?;
+  conn.execute(
+    "INSERT INTO session_logcat (session_id, start_ms, end_ms, line_count, data)
+     VALUES (?1, ?2, ?3, ?4, ?5)",
+    params![
+      session_id,
+      first.0 as i64,
+      last.0 as i64,
+      lines.len() as i64,
+      data
+    ],
+  )?;
+  Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+  /// 会话内相对时刻，与采样的 t_ms 对齐
+  pub t_ms: u64,
+  pub pid: u32,
+  pub level: char,
+  pub tag: String,
+  pub message: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LogcatQuery {
+  pub session_id: i64,
+  #[serde(default)]
+  pub from_ms: Option<u64>,
+  #[serde(default)]
+  pub to_ms: Option<u64>,
+  /// 最低日志级别，如 'W' 只返回警告及以上
+  #[serde(default)]
+  pub min_level: Option<char>,
+  /// 按 tag 或内容包含的文本过滤，不区分大小写
+  #[serde(default)]
+  pub contains: Option<String>,
+  #[serde(default)]
+  pub limit: Option<usize>,
+}
+
+/// 按时间范围查询录制期间保存的 logcat，结果按时间排序
+pub fn query_logcat(query: &LogcatQuery) -> Result<Vec<LogEntry>> {
+  let from = query.from_ms.unwrap_or(0);
+  let to = query.to_ms.unwrap_or(i64::MAX as u64);
+  let min_rank = query.min_level.map_or(0, level_rank);
+  let needle = query
+    .contains
+    .as_deref()
+    .map(str::trim)
+    .filter(|s| !s.is_empty())
+    .map(str::to_lowercase);
+  let limit = query.limit.unwrap_or(DEFAULT_QUERY_LIMIT);
+
+  let conn = open()?;
+  let mut stmt = conn.prepare(
+    "SELECT data FROM session_logcat
+     WHERE session_id = ?1 AND end_ms >= ?2 AND start_ms <= ?3
+     ORDER BY start_ms, id",
+  )?;
+  let chunks = stmt.query_map(params![query.session_id, from as i64, to as i64], |row| {
+    row.get::<_, Vec<u8>>(0)
+  })?;
+
+  let mut entries = Vec::new();
+  for chunk in chunks {
+    let text = zstd::decode_all(chunk?.as_slice())?;
+    let text = String::from_utf8(text).map_err(|e| SessionError::Format(e.to_string()))?;
+    for line in text.lines() {
+      let Some((t_ms, raw)) = line.split_once('\t') else {
+        continue;
+      };
+      let Ok(t_ms) = t_ms.parse::<u64>() else {
+        continue;
+      };
+      if !(from..=to).contains(&t_ms) {
+        continue;
+      }
+      let Some(parsed) = parse_line(raw) else {
+        continue;
+      };
+      if level_rank(parsed.level) < min_rank {
+        continue;
+      }
+      if let Some(needle) = &needle {
+        if !parsed.tag.to_lowercase().contains(needle)
+          && !parsed.message.to_lowercase().contains(needle)
+        {
+          continue;
+        }
+      }
+      entries.push(LogEntry {
+        t_ms,
+        pid: parsed.pid,
+        level: parsed.level,
+        tag: parsed.tag.to_string(),
+        message: parsed.message.to_string(),
+      });
+      if entries.len() >= limit {
+        return Ok(entries);
+      }
+    }
+  }
+  Ok(entries)
+}
+
+fn level_rank(level: char) -> u8 {
+  match level.to_ascii_uppercase() {
+    'V' => 0,
+    'D' => 1,
+    'I' => 2,
+    'W' => 3,
+    'E' => 4,
+    'F' | 'A' => 5,
+    _ => 0,
+  }
+}
@@ -0,0 +1,194 @@
+use crate::{
+  adb::{
+    app_version, device_snapshot,
+    display::DisplaySettings,
+    game::{game_mode, GameModeInfo},
+    DeviceSnapshot,
+  },
+  session::{
+    cpufreq::{active_cpu_lock, AppliedCpuLock},
+    display::active_display,
+    error::Result,
+    store::open,
+  },
+};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// 会话的附加信息，随每次导出一并写出
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionMeta {
+  #[serde(default)]
+  pub scenario: String,
+  #[serde(default)]
+  pub device: Option<DeviceSnapshot>,
+  #[serde(default)]
+  pub app_version_name: Option<String>,
+  #[serde(default)]
+  pub app_version_code: Option<i64>,
+  #[serde(default)]
+  pub build_fingerprint: Option<String>,
+  #[serde(default)]
+  pub tester: Option<String>,
+  #[serde(default)]
+  pub notes: Option<String>,
+  /// 录制期间生效的 CPU 频率锁定
+  #[serde(default)]
+  pub cpu_lock: Option<AppliedCpuLock>,
+  /// 录制期间统一设置的显示参数
+  #[serde(default)]
+  pub display: Option<DisplaySettings>,
+  /// 录制开始时目标应用的游戏模式，Android 13 以下为空
+  #[serde(default)]
+  pub game_mode: Option<GameModeInfo>,
+}
+
+/// 可编辑字段，未提供的字段保持不变
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SessionMetaPatch {
+  pub scenario: Option<String>,
+  pub tester: Option<String>,
+  pub notes: Option<String>,
+}
+
+pub fn get_meta(session_id: i64) -> Result<SessionMeta> {
+  let conn = open()?;
+  query_meta(&conn, session_id)
+}
+
+pub(crate) fn query_meta(conn: &Connection, session_id: i64) -> Result<SessionMeta> {
+  let meta = conn
+    .query_row(
+      "SELECT scenario, device_info, app_version_name, app_version_code, build_fingerprint, tester, notes,
+         cpu_lock, display_config, game_mode
+       FROM session_meta WHERE session_id = ?1",
+      params![session_id],
+      |row| {
+        let device: Option<String> = row.get(1)?;
+        Ok(SessionMeta {
+          scenario: row.get(0)?,
+          device: device.and_then(|d| serde_json::from_str(&d).ok()),
+          app_version_name: row.get(2)?,
+          app_version_code: row.get(3)?,
+          build_fingerprint: row.get(4)?,
+          tester: row.get(5)?,
+          notes: row.get(6)?,
+          cpu_lock: row
+            .get::<_, Option<String>>(7)?
+            .and_then(|c| serde_json::from_str(&c).ok()),
+          display: row
+            .get::<_, Option<String>>(8)?
+            .and_then(|d| serde_json::from_str(&d).ok()),
+          game_mode: row
+            .get::<_, Option<String>>(9)?
+            .and_then(|g| serde_json::from_str(&g).ok()),
+        })
+      },
+    )
+    .optional()?;
+  Ok(meta.unwrap_or_default())
+}
+
+pub(crate) fn write_meta(conn: &Connection, session_id: i64, meta: &SessionMeta) -> Result<()> {
+  let device = meta
+    .device
+    .as_ref()
+    .map(serde_json::to_string)
+    .transpose()?;
+  let cpu_lock = meta
+    .cpu_lock
+    .as_ref()
+    .map(serde_json::to_string)
+    .transpose()?;
+  let display = meta
+    .display
+    .as_ref()
+    .map(serde_json::to_string)
+    .transpose()?;
+  let game_mode = meta
+    .game_mode
+    .as_ref()
+    .map(serde_json::to_string)
+    .transpose()?;
+  conn.execute(
+    "INSERT INTO session_meta (
+       session_id, scenario, device_info, app_version_name, app_version_code,
+       build_fingerprint, tester, notes, cpu_lock,
+       display_config, game_mode
+     ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+     ON CONFLICT(session_id) DO UPDATE SET
+       scenario = excluded.scenario,
+       device_info = excluded.device_info,
+       app_version_name = excluded.app_version_name,
+       app_version_code = excluded.app_version_code,
+       build_fingerprint = excluded.build_fingerprint,
+       tester = excluded.tester,
+       notes = excluded.notes,
+       cpu_lock = excluded.cpu_lock,
+       display_config = excluded.display_config,
+       game_mode = excluded.game_mode",
+    params![
+      session_id,
+      meta.scenario.trim(),
+      device,
+      meta.app_version_name,
+      meta.app_version_code,
+      meta.build_fingerprint,
+      meta.tester,
+      meta.notes,
+      cpu_lock,
+      display,
+      game_mode,
+    ],
+  )?;
+  Ok(())
+}
+
+pub fn update_meta(session_id: i64, patch: SessionMetaPatch) -> Result<SessionMeta> {
+  let conn = open()?;
+  let mut meta = query_meta(&conn, session_id)?;
+  if let Some(scenario) = patch.scenario {
+    meta.scenario = scenario;
+  }
+  if let Some(tester) = patch.tester {
+    meta.tester = Some(tester).filter(|t| !t.trim().is_empty());
+  }
+  if let Some(notes) = patch.notes {
+    meta.notes = Some(notes).filter(|n| !n.trim().is_empty());
+  }
+  write_meta(&conn, session_id, &meta)?;
+  Ok(meta)
+}
+
+/// 从设备读取设备信息与应用版本写入会话；设备不可用时保留已有内容
+pub fn capture_meta(session_id: i64, device_id: &str, package: &str) -> Result<SessionMeta> {
+  let conn = open()?;
+  let mut meta = query_meta(&conn, session_id)?;
+
+  match device_snapshot(device_id) {
+    Ok(device) => {
+      meta.build_fingerprint = device.build_fingerprint.clone();
+      meta.device = Some(device);
+    }
+    Err(e) => log::warn!("读取设备信息失败: {}", e),
+  }
+  match app_version(device_id, package) {
+    Ok(version) => {
+      meta.app_version_name = version.version_name;
+      meta.app_version_code = version.version_code;
+    }
+    Err(e) => log::warn!("读取应用版本失败: {}", e),
+  }
+  if meta.cpu_lock.is_none() {
+    meta.cpu_lock = active_cpu_lock(device_id);
+  }
+  if meta.display.is_none() {
+    meta.display = active_display(device_id);
+  }
+  if meta.game_mode.is_none() {
+    meta.game_mode = game_mode(device_id, package).ok();
+  }
+
+  write_meta(&conn, session_id, &meta)?;
+  Ok(meta)
+}
@@ -0,0 +1,246 @@
+use crate::session::error::{Result, SessionError};
+use perfx_core::{
+  adb::provider::provider_unit,
+  schema::{
+    check_version, legacy_network_rates, legacy_power_is_cumulative, legacy_version,
+    SCHEMA_VERSION,
+  },
+  units::Units,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+  borrow::Cow,
+  collections::BTreeMap,
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+pub mod alerts;
+pub mod animation;
+pub mod anomaly;
+pub mod baseline;
+pub mod bundle;
+pub mod capture;
+pub mod connectivity;
+pub mod cpufreq;
+pub mod crypto;
+pub mod display;
+pub mod dropbox;
+pub mod energy;
+pub mod error;
+pub mod events;
+pub mod game;
+pub mod hooks;
+pub mod hwui;
+pub mod idle;
+pub mod import;
+pub mod jank;
+pub mod logcat;
+pub mod meta;
+pub mod monkey;
+pub mod proxy;
+pub mod raw;
+pub mod recorder;
+pub mod restore;
+pub mod retention;
+pub mod screenshot;
+pub mod script;
+pub mod sink;
+pub mod startup;
+pub mod store;
+pub mod stream;
+pub mod thermal;
+
+pub use import::import_file;
+pub use meta::SessionMeta;
+pub use store::{add_marker, list_markers, load_session, set_db_path};
+
+/// 一次监控会话，对应前端 `reports` 表中的一行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+  pub id: i64,
+  pub name: String,
+  pub device_id: String,
+  pub device_model: Option<String>,
+  pub app_package: String,
+  pub app_label: Option<String>,
+  pub metrics: Vec<String>,
+  pub start_time: i64, // unix timestamp in seconds
+  pub end_time: i64,
+  pub duration: i64, // 秒
+  pub created_at: i64,
+  /// 采样数据的格式版本，缺省时按旧版本读取
+  #[serde(default = "legacy_version")]
+  pub schema_version: u32,
+}
+
+/// 单个采样点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sample {
+  pub t_ms: u64, // 相对会话开始的毫秒偏移
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub time: Option<String>, // 前端记录的时刻标签，如 "14:32:05"
+  pub values: BTreeMap<String, f64>,
+}
+
+/// 会话时间线上的标记，用于分段与多会话对齐
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Marker {
+  #[serde(default)]
+  pub id: i64,
+  pub t_ms: u64,
+  pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionData {
+  pub session: Session,
+  pub samples: Vec<Sample>,
+  #[serde(default)]
+  pub markers: Vec<Marker>,
+  #[serde(default)]
+  pub meta: SessionMeta,
+}
+
+impl SessionData {
+  /// 将读回或导入的旧版本会话转换为当前格式，更新版本的数据拒绝读取
+  pub fn upgrade(&mut self) -> Result<()> {
+    check_version(self.session.schema_version)
+      .map_err(|e| SessionError::Format(e.to_string()))?;
+    // 版本 1 的 CPU 按 100% 封顶，无法还原，原样保留
+    if self.session.schema_version < 2 {
+      self.upgrade_legacy_network();
+    }
+    if self.session.schema_version < 3 {
+      let power: Vec<f64> = self.series("power").into_iter().map(|(_, v)| v).collect();
+      if legacy_power_is_cumulative(&power) {
+        self.rename_metric("power", "power_mah");
+      }
+    }
+    self.session.schema_version = SCHEMA_VERSION;
+    Ok(())
+  }
+
+  /// 累计流量改为速率，无法求速率的点去掉该指标
+  fn upgrade_legacy_network(&mut self) {
+    let mut rates = legacy_network_rates(&self.series("network")).into_iter();
+    for sample in &mut self.samples {
+      if !sample.values.contains_key("network") {
+        continue;
+      }
+      match rates.next().flatten() {
+        Some(rate) => sample.values.insert("network".to_string(), rate),
+        None => sample.values.remove("network"),
+      };
+    }
+  }
+
+  fn rename_metric(&mut self, from: &str, to: &str) {
+    for sample in &mut self.samples {
+      if let Some(value) = sample.values.remove(from) {
+        sample.values.insert(to.to_string(), value);
+      }
+    }
+  }
+
+  /// 按首次出现顺序返回采样中包含的指标名
+  pub fn metric_names(&self) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for sample in &self.samples {
+      for key in sample.values.keys() {
+        if !names.contains(key) {
+          names.push(key.clone());
+        }
+      }
+    }
+    names.sort_by_key(|name| metric_order(name));
+    names
+  }
+
+  /// 按名称查找第一个标记
+  pub fn marker(&self, name: &str) -> Option<&Marker> {
+    self.markers.iter().find(|m| m.name == name)
+  }
+
+  /// 换算为展示单位后的采样，图表键与 `samples` 一致
+  pub fn samples_in(&self, units: &Units) -> Vec<Sample> {
+    self
+      .samples
+      .iter()
+      .map(|s| Sample {
+        t_ms: s.t_ms,
+        time: s.time.clone(),
+        values: units.convert(&s.values),
+      })
+      .collect()
+  }
+
+  /// 取出某个指标的 (t_ms, value) 序列
+  pub fn series(&self, metric: &str) -> Vec<(u64, f64)> {
+    self
+      .samples
+      .iter()
+      .filter_map(|s| s.values.get(metric).map(|v| (s.t_ms, *v)))
+      .collect()
+  }
+}
+
+const METRIC_LABELS: &[(&str, &str, &str)] = &[
+  ("fps", "FPS", ""),
+  ("cpu", "CPU", "%"),
+  ("cpu_normalized", "CPU（整机）", "%"),
+  ("memory", "内存", "MB"),
+  ("power", "功耗", "mA"),
+  ("power_mah", "应用累计耗电", "mAh"),
+  ("battery", "电量", "%"),
+  ("battery_temp", "电池温度", "°C"),
+  ("battery_voltage", "电池电压", "V"),
+  ("traffic_rx", "下行流量", "KiB/s"),
+  ("traffic_tx", "上行流量", "KiB/s"),
+  ("network_rx", "整机下行", "KiB/s"),
+  ("network_tx", "整机上行", "KiB/s"),
+  ("frame_sd", "帧间隔标准差", "ms"),
+  ("frame_cv", "帧间隔变异系数", "%"),
+  ("frame_on_target", "帧间隔达标率", "%"),
+];
+
+/// 指标的展示名称与单位；未知指标以键为名称，单位取自定义指标声明的单位
+pub fn metric_label(metric: &str) -> (&str, Cow<'static, str>) {
+  match METRIC_LABELS.iter().find(|(key, _, _)| *key == metric) {
+    Some((_, label, unit)) => (*label, Cow::Borrowed(*unit)),
+    None => (metric, provider_unit(metric).map_or(Cow::Borrowed(""), Cow::Owned)),
+  }
+}
+
+/// 按展示单位给出指标名称与单位
+pub fn metric_label_in<'a>(metric: &'a str, units: &Units) -> (&'a str, Cow<'static, str>) {
+  match metric_label(metric) {
+    (label, Cow::Borrowed(unit)) => (label, Cow::Borrowed(units.unit(metric, unit))),
+    custom => custom,
+  }
+}
+
+/// 已知的图表指标键，按展示顺序排列
+pub fn metric_keys() -> impl Iterator<Item = &'static str> {
+  METRIC_LABELS.iter().map(|(key, _, _)| *key)
+}
+
+fn metric_order(metric: &str) -> usize {
+  METRIC_LABELS
+    .iter()
+    .position(|(key, _, _)| *key == metric)
+    .unwrap_or(METRIC_LABELS.len())
+}
+
+pub(crate) fn unix_now() -> i64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_secs() as i64
+}
+
+pub(crate) fn unix_now_ms() -> i64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_millis() as i64
+}
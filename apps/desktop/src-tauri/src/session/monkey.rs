@@ -0,0 +1,232 @@
+use crate::{
+  adb::{
+    command::spawn_device,
+    monkey::{kill_monkey, parse_line, MonkeyConfig, MonkeyLine},
+  },
+  session::{
+    error::{Result, SessionError},
+    events::{add_attachment, add_event, EventKind, SessionEvent},
+    recorder::{mark_recording, notify, recording_target},
+    store::open,
+  },
+};
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::HashMap,
+  io::{BufRead, BufReader},
+  process::Child,
+  sync::{Arc, Mutex},
+  thread,
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+// 崩溃 / ANR 之后附带的输出行数，包含 Short Msg / Long Msg 与堆栈
+const CRASH_CONTEXT_LINES: usize = 40;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonkeyStatus {
+  pub session_id: i64,
+  pub seed: u64,
+  pub events: u32,
+  /// 开始时的录制相对时刻
+  pub t_ms: u64,
+  /// 完整命令，可直接在其他设备上复现
+  pub command: String,
+}
+
+struct RunningMonkey {
+  status: MonkeyStatus,
+  device_id: String,
+  child: Arc<Mutex<Child>>,
+}
+
+static RUNNING: Lazy<Mutex<HashMap<i64, RunningMonkey>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 对录制中会话的目标应用运行 monkey，崩溃与 ANR 记为会话事件，开始与结束处插入标记
+pub fn start_monkey(session_id: i64, config: &MonkeyConfig) -> Result<MonkeyStatus> {
+  let (device_id, package, t_ms) =
+    recording_target(session_id).ok_or(SessionError::NotRecording(session_id))?;
+  // 检查与登记在同一次加锁内完成，并发启动时只有一个会拉起 monkey；
+  // spawn 只启动本地 adb 进程，不等待设备
+  let mut running = RUNNING.lock().map_err(|_| SessionError::NotInitialized)?;
+  if running.contains_key(&session_id) {
+    return Err(SessionError::CaptureRunning(session_id, "monkey 测试"));
+  }
+
+  let seed = config.seed.unwrap_or_else(random_seed);
+  let command = config.command(&package, seed);
+  let mut child = spawn_device(&device_id, &["shell", &command])?;
+  let stdout = child.stdout.take();
+  let child = Arc::new(Mutex::new(child));
+  let status = MonkeyStatus {
+    session_id,
+    seed,
+    events: config.events,
+    t_ms,
+    command,
+  };
+  running.insert(
+    session_id,
+    RunningMonkey {
+      status: status.clone(),
+      device_id,
+      child: child.clone(),
+    },
+  );
+  drop(running);
+  if let Err(e) = mark_recording(session_id, &format!("monkey 开始 seed={seed}")) {
+    warn!("插入 monkey 标记失败: {}", e);
+  }
+  info!("会话 {} 开始 monkey: {}", session_id, status.command);
+
+  thread::spawn(move || {
+    let lines = stdout
+      .map(|out| {
+        read_output(
+          session_id,
+          BufReader::new(out).lines().map_while(|l| l.ok()),
+        )
+      })
+      .unwrap_or_default();
+    if let Ok(mut child) = child.lock() {
+      let _ = child.wait();
+    }
+    finish(session_id, seed, &lines);
+  });
+  Ok(status)
+}
+
+/// 中止会话上运行中的 monkey，结果照常保存
+pub fn stop_monkey(session_id: i64) -> Result<()> {
+  let running = RUNNING
+    .lock()
+    .ok()
+    .and_then(|running| {
+      running
+        .get(&session_id)
+        .map(|r| (r.device_id.clone(), r.child.clone()))
+    })
+    .ok_or(SessionError::CaptureNotRunning(session_id, "monkey 测试"))?;
+  let (device_id, child) = running;
+  if let Err(e) = kill_monkey(&device_id) {
+    warn!("结束设备上的 monkey 失败: {}", e);
+  }
+  if let Ok(mut child) = child.try_lock() {
+    let _ = child.kill();
+  }
+  Ok(())
+}
+
+pub fn list_monkey_runs() -> Vec<MonkeyStatus> {
+  RUNNING
+    .lock()
+    .map(|running| running.values().map(|r| r.status.clone()).collect())
+    .unwrap_or_default()
+}
+
+/// 逐行读取输出，崩溃 / ANR 在读完后续上下文后记为事件，时刻取检测到时的录制时刻
+fn read_output(session_id: i64, output: impl Iterator<Item = String>) -> Vec<String> {
+  let mut lines = Vec::new();
+  let mut pending: Option<(EventKind, String, u64, usize)> = None;
+  for line in output {
+    let parsed = parse_line(&line);
+    lines.push(line);
+    if let Some((kind, message, t_ms, start)) = pending.take() {
+      if lines.len() - start < CRASH_CONTEXT_LINES && parsed.is_none() {
+        pending = Some((kind, message, t_ms, start));
+      } else {
+        record_crash(session_id, kind, &message, t_ms, &lines[start..]);
+      }
+    }
+    let found = match parsed {
+      Some(MonkeyLine::Crash(what)) => Some((EventKind::Crash, what)),
+      Some(MonkeyLine::Anr(what)) => Some((EventKind::Anr, what)),
+      _ => None,
+    };
+    if let Some((kind, what)) = found {
+      let t_ms = recording_target(session_id).map_or(0, |(_, _, t)| t);
+      pending = Some((kind, format!("Monkey: {what}"), t_ms, lines.len() - 1));
+    }
+  }
+  if let Some((kind, message, t_ms, start)) = pending {
+    record_crash(session_id, kind, &message, t_ms, &lines[start..]);
+  }
+  lines
+}
+
+fn record_crash(session_id: i64, kind: EventKind, message: &str, t_ms: u64, context: &[String]) {
+  let result = open().and_then(|conn| {
+    let id = add_event(&conn, session_id, t_ms, kind, message)?;
+    let attachment = add_attachment(
+      &conn,
+      session_id,
+      Some(id),
+      "monkey",
+      &format!("monkey-{}-{t_ms}.txt", kind.as_str()),
+      context.join("\n").as_bytes(),
+    )?;
+    Ok(SessionEvent {
+      id,
+      t_ms,
+      kind,
+      message: message.to_string(),
+      attachments: vec![attachment],
+      causes: Vec::new(),
+      rule_id: None,
+    })
+  });
+  match result {
+    Ok(event) => notify(|o| o.on_event(session_id, &event)),
+    Err(e) => warn!("保存 monkey 事件失败: {}", e),
+  }
+}
+
+/// 保存完整输出并插入结束标记
+fn finish(session_id: i64, seed: u64, lines: &[String]) {
+  if let Ok(mut running) = RUNNING.lock() {
+    running.remove(&session_id);
+  }
+  let injected = lines.iter().rev().find_map(|l| match parse_line(l) {
+    Some(MonkeyLine::Injected(n)) => Some(n),
+    _ => None,
+  });
+  let aborted = lines
+    .iter()
+    .any(|l| parse_line(l) == Some(MonkeyLine::Aborted));
+
+  let saved = open().and_then(|conn| {
+    add_attachment(
+      &conn,
+      session_id,
+      None,
+      "monkey",
+      &format!("monkey-{seed}.log"),
+      lines.join("\n").as_bytes(),
+    )
+  });
+  if let Err(e) = saved {
+    warn!("保存 monkey 输出失败: {}", e);
+  }
+
+  let name = match (injected, aborted) {
+    (Some(n), false) => format!("monkey 结束 seed={seed}，注入 {n} 个事件"),
+    (Some(n), true) => format!("monkey 中止 seed={seed}，注入 {n} 个事件"),
+    (None, _) => format!("monkey 结束 seed={seed}"),
+  };
+  // 录制已停止时无法再插入标记，输出已作为附件保存
+  if recording_target(session_id).is_some() {
+    if let Err(e) = mark_recording(session_id, &name) {
+      warn!("插入 monkey 标记失败: {}", e);
+    }
+  }
+  info!("会话 {} {}", session_id, name);
+}
+
+fn random_seed() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_nanos() as u64 % 1_000_000_000)
+    .unwrap_or(0)
+}
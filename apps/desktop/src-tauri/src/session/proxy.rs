@@ -0,0 +1,76 @@
+//! 从 PerfX 设置设备全局代理：首次设置前记录原代理并写入数据库，退出应用时自动清理；
+//! 录制中设置或清除时在时间线上插入标记
+
+use crate::{
+  adb::proxy::{http_proxy, restore_http_proxy, set_http_proxy, ProxyConfig},
+  session::{
+    error::Result,
+    recorder::{active_recording, mark_recording},
+    restore::DeviceSetting,
+  },
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+static SAVED: DeviceSetting<SavedProxy> = DeviceSetting::new("proxy_restore:", "代理设置");
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedProxy {
+  previous: Option<String>,
+  /// 建立过端口转发的端口，清理时一并移除
+  reverse_ports: Vec<u16>,
+}
+
+pub fn get_proxy(device_id: &str) -> Result<Option<String>> {
+  Ok(http_proxy(device_id)?)
+}
+
+pub fn set_proxy(device_id: &str, config: &ProxyConfig) -> Result<()> {
+  let mut saved = match SAVED.get(device_id)? {
+    Some(saved) => saved,
+    None => SavedProxy {
+      previous: http_proxy(device_id)?,
+      reverse_ports: Vec::new(),
+    },
+  };
+  if config.reverse && !saved.reverse_ports.contains(&config.port) {
+    saved.reverse_ports.push(config.port);
+  }
+  // 先保存，设置中途失败也能清理已建立的转发
+  SAVED.save(device_id, &saved)?;
+  set_http_proxy(device_id, config)?;
+  mark(
+    device_id,
+    &format!("设置代理 {}:{}", config.host, config.port),
+  );
+  Ok(())
+}
+
+/// 恢复设置前的代理；没有设置过时返回 false
+pub fn restore_proxy(device_id: &str) -> Result<bool> {
+  let restored = SAVED.restore(device_id, |saved| {
+    Ok(restore_http_proxy(
+      device_id,
+      saved.previous.as_deref(),
+      &saved.reverse_ports,
+    )?)
+  })?;
+  if restored {
+    mark(device_id, "恢复代理设置");
+  }
+  Ok(restored)
+}
+
+/// 清理所有设备上由 PerfX 设置的代理；设备不在线的记录保留到下次
+pub fn restore_all_proxies() {
+  SAVED.restore_all(|_| true, restore_proxy);
+}
+
+fn mark(device_id: &str, name: &str) {
+  let Ok(session_id) = active_recording(Some(device_id)) else {
+    return;
+  };
+  if let Err(e) = mark_recording(session_id, name) {
+    warn!("插入代理标记失败: {}", e);
+  }
+}
@@ -0,0 +1,78 @@
+use crate::{
+  adb::RawDump,
+  session::{
+    error::{Result, SessionError},
+    store::open,
+  },
+};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+// 文本类输出压缩率很高，较低的等级已足够且几乎不占 CPU
+const ZSTD_LEVEL: i32 = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RawDumpRecord {
+  pub t_ms: u64,
+  pub kind: String,
+  pub text: String,
+}
+
+pub fn store_raw(conn: &Connection, session_id: i64, t_ms: u64, dumps: &[RawDump]) -> Result<()> {
+  for dump in dumps {
+    insert_raw(conn, session_id, t_ms, dump.kind, &dump.text)?;
+  }
+  Ok(())
+}
+
+pub fn insert_raw(
+  conn: &Connection,
+  session_id: i64,
+  t_ms: u64,
+  kind: &str,
+  text: &str,
+) -> Result<()> {
+  let data = zstd::encode_all(text.as_bytes(), ZSTD_LEVEL)?;
+  conn.execute(
+    "INSERT INTO session_raw (session_id, t_ms, kind, data) VALUES (?1, ?2, ?3, ?4)",
+    params![session_id, t_ms as i64, kind, data],
+  )?;
+  Ok(())
+}
+
+/// 读取并解压某时间范围内的原始输出，`kind` 为空时返回全部类型
+pub fn load_raw(
+  session_id: i64,
+  from_ms: u64,
+  to_ms: u64,
+  kind: Option<&str>,
+) -> Result<Vec<RawDumpRecord>> {
+  let conn = open()?;
+  let mut stmt = conn.prepare(
+    "SELECT t_ms, kind, data FROM session_raw
+     WHERE session_id = ?1 AND t_ms BETWEEN ?2 AND ?3 AND (?4 IS NULL OR kind = ?4)
+     ORDER BY t_ms, id",
+  )?;
+  let rows = stmt.query_map(
+    params![session_id, from_ms as i64, to_ms as i64, kind],
+    |row| {
+      Ok((
+        row.get::<_, i64>(0)?,
+        row.get::<_, String>(1)?,
+        row.get::<_, Vec<u8>>(2)?,
+      ))
+    },
+  )?;
+
+  let mut records = Vec::new();
+  for row in rows {
+    let (t_ms, kind, data) = row?;
+    let text = zstd::decode_all(data.as_slice())?;
+    records.push(RawDumpRecord {
+      t_ms: t_ms.max(0) as u64,
+      kind,
+      text: String::from_utf8(text).map_err(|e| SessionError::Format(e.to_string()))?,
+    });
+  }
+  Ok(records)
+}
@@ -0,0 +1,1050 @@
+use crate::{
+  adb::{
+    app_version, collect_metrics_with, device_snapshot,
+    energy::{energy_probe, EnergyProbe},
+    game::game_mode,
+    idle::{idle_state, IdleState},
+    list_devices,
+    metrics::snapshot_values,
+    perfetto::PerfettoConfig,
+    thermal::{thermal_snapshot, ThermalSnapshot},
+    CollectOptions, InterfaceFilter, MetricKey, MetricsSnapshot, ProcessState,
+  },
+  session::{
+    alerts::AlertEvaluator,
+    anomaly::{Anomaly, AnomalyDetector},
+    capture::{arm_jank_trace, disarm_session, fire_jank_trace},
+    cpufreq::active_cpu_lock,
+    display::active_display,
+    energy::{ensure_profile, store_probe},
+    error::{Result, SessionError},
+    events::{add_event, set_rule_id, EventKind, SessionEvent},
+    hooks::{
+      run_post_hooks, run_pre_hooks, save_hook_results, HookRestore, HookStage, SessionHooks,
+    },
+    idle::IdleTracker,
+    logcat::{refresh_pid, LogcatWatcher, PendingEvent, SharedPid},
+    meta::SessionMeta,
+    monkey::stop_monkey,
+    raw::store_raw,
+    screenshot::{capture_thumbnail, store_screenshot, ScreenshotTrigger},
+    script::stop_script,
+    sink::{self, SinkConfig, SinkMessage, SinkSender, SinkSet, SinkTarget},
+    store::{
+      add_marker, delete_session, finish_session, get_kv, insert_session, load_session, open,
+      set_kv,
+    },
+    stream::SampleStream,
+    thermal::ThrottleDetector,
+    unix_now, unix_now_ms, Marker, Sample, Session, SessionData,
+  },
+};
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use perfx_core::schema::SCHEMA_VERSION;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::{BTreeMap, HashMap},
+  path::PathBuf,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::{self, RecvTimeoutError},
+    Arc, Mutex, RwLock,
+  },
+  thread::{self, JoinHandle},
+  time::{Duration, Instant},
+};
+
+// 低于该间隔时 adb 往返本身就会占满整个周期
+const MIN_INTERVAL_MS: u64 = 200;
+// screencap 单次耗时约 0.3~1s，非标记触发的截图至少间隔这么久
+const MIN_SCREENSHOT_GAP_MS: u64 = 1000;
+// 温度与频率变化较慢，探测间隔大于采样间隔即可
+const THERMAL_PROBE_MS: u64 = 2000;
+// 频率驻留为累计计数，低频探测再按时间分摊即可
+const ENERGY_PROBE_MS: u64 = 5000;
+// Doze 状态切换以分钟计，低频读取即可
+const IDLE_PROBE_MS: u64 = 5000;
+// 后台探测线程检查是否到期的间隔
+const PROBE_POLL_MS: u64 = 500;
+const LAST_CONFIG_KEY: &str = "last_recording_config";
+
+fn default_interval_ms() -> u64 {
+  crate::settings::default_interval_ms()
+}
+
+fn default_jank_drop_pct() -> f64 {
+  30.0
+}
+
+fn default_stream_sync_secs() -> u64 {
+  5
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingOptions {
+  #[serde(default = "default_interval_ms")]
+  pub interval_ms: u64,
+  /// 保存 gfxinfo / meminfo 原始输出（压缩存储），便于事后排查解析问题
+  #[serde(default)]
+  pub keep_raw: bool,
+  /// CPU 与内存汇总应用的全部进程（`:push`、`:render` 等子进程）
+  #[serde(default)]
+  pub all_processes: bool,
+  /// 汇总多进程时同时记录每个进程的 CPU 与内存
+  #[serde(default)]
+  pub process_breakdown: bool,
+  /// 目标应用所在的 Android 用户（工作资料、分身等），为空时不区分用户
+  #[serde(default)]
+  pub user: Option<u32>,
+  /// 流量统计计入的网卡，使用全局 VPN 时需包含 tun 接口
+  #[serde(default)]
+  pub interfaces: InterfaceFilter,
+  /// 定时截图间隔，0 表示不定时截图
+  #[serde(default)]
+  pub screenshot_interval_ms: u64,
+  #[serde(default)]
+  pub screenshot_on_marker: bool,
+  #[serde(default)]
+  pub screenshot_on_jank: bool,
+  /// 检测卡顿 / ANR / 崩溃事件，并保存事件前后的 logcat 片段
+  #[serde(default)]
+  pub logcat_on_events: bool,
+  /// 保存录制期间应用相关的 logcat，可按时间范围查询
+  #[serde(default)]
+  pub record_logcat: bool,
+  /// 帧率较上一采样下降超过该百分比时视为卡顿
+  #[serde(default = "default_jank_drop_pct")]
+  pub jank_drop_pct: f64,
+  /// 录制中实时追加写入的 CSV / JSONL 文件，防止宿主崩溃丢失数据
+  #[serde(default)]
+  pub stream_path: Option<PathBuf>,
+  #[serde(default = "default_stream_sync_secs")]
+  pub stream_sync_secs: u64,
+  /// 定期读取温区温度与 CPU / GPU 频率，在时间线上标记温控降频的开始与结束
+  #[serde(default)]
+  pub detect_throttling: bool,
+  /// 记录 CPU 频率驻留与屏幕状态，结合设备 power_profile 估算各分段能耗
+  #[serde(default)]
+  pub estimate_energy: bool,
+  /// 定期读取 Doze 状态，状态变化时在时间线上记录
+  #[serde(default)]
+  pub track_idle: bool,
+  /// 录制期间以环形缓冲持续记录 perfetto trace，卡顿时保存此前的片段
+  #[serde(default)]
+  pub perfetto_on_jank: Option<PerfettoConfig>,
+  /// 录制前后对设备执行的状态整理
+  #[serde(default)]
+  pub hooks: SessionHooks,
+  /// 录制中把采样同时推送到的外部系统
+  #[serde(default)]
+  pub sinks: Vec<SinkConfig>,
+}
+
+impl RecordingOptions {
+  fn screenshots_enabled(&self) -> bool {
+    self.screenshot_interval_ms > 0 || self.screenshot_on_marker || self.screenshot_on_jank
+  }
+
+  /// 采集时使用的进程、用户与网卡选项
+  pub fn collect_options(&self) -> CollectOptions {
+    CollectOptions {
+      keep_raw: self.keep_raw,
+      all_processes: self.all_processes,
+      process_breakdown: self.process_breakdown,
+      user: self.user,
+      interfaces: self.interfaces.clone(),
+    }
+  }
+}
+
+impl Default for RecordingOptions {
+  fn default() -> Self {
+    Self {
+      interval_ms: default_interval_ms(),
+      keep_raw: false,
+      all_processes: false,
+      process_breakdown: false,
+      user: None,
+      interfaces: InterfaceFilter::default(),
+      screenshot_interval_ms: 0,
+      screenshot_on_marker: false,
+      screenshot_on_jank: false,
+      logcat_on_events: false,
+      record_logcat: false,
+      jank_drop_pct: default_jank_drop_pct(),
+      stream_path: None,
+      stream_sync_secs: default_stream_sync_secs(),
+      detect_throttling: false,
+      estimate_energy: false,
+      track_idle: false,
+      perfetto_on_jank: None,
+      hooks: SessionHooks::default(),
+      sinks: Vec::new(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingConfig {
+  pub device_id: String,
+  pub package: String,
+  /// 为空时使用设置中的默认指标
+  #[serde(default)]
+  pub metrics: Vec<MetricKey>,
+  #[serde(default)]
+  pub name: Option<String>,
+  #[serde(default)]
+  pub options: RecordingOptions,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingStatus {
+  pub session_id: i64,
+  pub device_id: String,
+  pub package: String,
+  pub start_time: i64,
+  pub elapsed_ms: u64,
+  pub sample_count: usize,
+  #[serde(default)]
+  pub paused: bool,
+}
+
+/// 录制过程的观察者，Tauri 事件、外部数据推送等都通过它接入
+pub trait RecordingObserver: Send + Sync {
+  fn on_sample(&self, _session_id: i64, _sample: &Sample, _snapshot: &MetricsSnapshot) {}
+  fn on_event(&self, _session_id: i64, _event: &SessionEvent) {}
+  fn on_anomaly(&self, _session_id: i64, _anomaly: &Anomaly) {}
+  fn on_stopped(&self, _session_id: i64) {}
+}
+
+struct Recording {
+  config: RecordingConfig,
+  started: Instant,
+  start_time: i64,
+  samples: Arc<Mutex<Vec<Sample>>>,
+  paused: Arc<AtomicBool>,
+  stop_tx: mpsc::Sender<()>,
+  handle: JoinHandle<()>,
+  screenshot_tx: Option<mpsc::Sender<ScreenshotTrigger>>,
+  screenshot_handle: Option<JoinHandle<()>>,
+  logcat: Option<LogcatWatcher>,
+  stream: Option<Arc<Mutex<SampleStream>>>,
+  sinks: SinkSet,
+  restore: HookRestore,
+}
+
+static RECORDINGS: Lazy<Mutex<HashMap<i64, Recording>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static OBSERVERS: Lazy<RwLock<Vec<Arc<dyn RecordingObserver>>>> =
+  Lazy::new(|| RwLock::new(vec![sink::observer()]));
+
+pub fn add_observer(observer: Arc<dyn RecordingObserver>) {
+  if let Ok(mut observers) = OBSERVERS.write() {
+    observers.push(observer);
+  }
+}
+
+pub(crate) fn notify(f: impl Fn(&dyn RecordingObserver)) {
+  if let Ok(observers) = OBSERVERS.read() {
+    for observer in observers.iter() {
+      f(observer.as_ref());
+    }
+  }
+}
+
+/// 开始录制：立即创建会话记录，后台线程按间隔采样，返回会话 id
+pub fn start_recording(config: RecordingConfig) -> Result<i64> {
+  // 在记录开始时间之前执行，清除数据等耗时操作不计入录制时长
+  let (hook_results, restore) =
+    run_pre_hooks(&config.device_id, &config.package, &config.options.hooks);
+  let device = device_snapshot(&config.device_id).ok();
+  let version = app_version(&config.device_id, &config.package).ok();
+  let now = unix_now();
+
+  let metrics = config
+    .metrics
+    .iter()
+    .filter_map(|m| serde_json::to_value(m).ok())
+    .filter_map(|v| v.as_str().map(str::to_string))
+    .collect();
+  let data = SessionData {
+    session: Session {
+      id: 0,
+      name: config
+        .name
+        .clone()
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or_else(|| format!("{}-{}-{}", config.device_id, config.package, now)),
+      device_id: config.device_id.clone(),
+      device_model: device.as_ref().and_then(|d| d.model.clone()),
+      app_package: config.package.clone(),
+      app_label: None,
+      metrics,
+      start_time: now,
+      end_time: now,
+      duration: 0,
+      created_at: now,
+      schema_version: SCHEMA_VERSION,
+    },
+    samples: Vec::new(),
+    markers: Vec::new(),
+    meta: SessionMeta {
+      build_fingerprint: device.as_ref().and_then(|d| d.build_fingerprint.clone()),
+      device,
+      app_version_name: version.as_ref().and_then(|v| v.version_name.clone()),
+      app_version_code: version.as_ref().and_then(|v| v.version_code),
+      cpu_lock: active_cpu_lock(&config.device_id),
+      display: active_display(&config.device_id),
+      game_mode: game_mode(&config.device_id, &config.package).ok(),
+      ..Default::default()
+    },
+  };
+  let session_id = match insert_session(&data) {
+    Ok(id) => id,
+    Err(e) => {
+      run_post_hooks(&config.device_id, &config.options.hooks, restore);
+      return Err(e);
+    }
+  };
+  if let Err(e) = save_hook_results(session_id, HookStage::Pre, 0, &hook_results) {
+    warn!("保存录制前操作结果失败: {}", e);
+  }
+  if let (true, Some(fingerprint)) = (
+    config.options.estimate_energy,
+    data.meta.build_fingerprint.clone(),
+  ) {
+    let device_id = config.device_id.clone();
+    thread::spawn(move || ensure_profile(&device_id, &fingerprint));
+  }
+  let stream = match &config.options.stream_path {
+    Some(path) => {
+      let session = Session {
+        id: session_id,
+        ..data.session.clone()
+      };
+      let sync_interval = Duration::from_secs(config.options.stream_sync_secs.max(1));
+      match SampleStream::create(path, &session, sync_interval) {
+        Ok(stream) => Some(Arc::new(Mutex::new(stream))),
+        Err(e) => {
+          if let Ok(conn) = open() {
+            let _ = delete_session(&conn, session_id);
+          }
+          run_post_hooks(&config.device_id, &config.options.hooks, restore);
+          return Err(e);
+        }
+      }
+    }
+    None => None,
+  };
+  let started = Instant::now();
+  let target = SinkTarget {
+    session_id,
+    device_id: config.device_id.clone(),
+    package: config.package.clone(),
+    start_ms: unix_now_ms(),
+  };
+  let sinks = match SinkSet::start(&config.options.sinks, &target) {
+    Ok(sinks) => sinks,
+    Err(e) => {
+      if let Ok(conn) = open() {
+        let _ = delete_session(&conn, session_id);
+      }
+      run_post_hooks(&config.device_id, &config.options.hooks, restore);
+      return Err(e);
+    }
+  };
+
+  let samples = Arc::new(Mutex::new(Vec::new()));
+  let paused = Arc::new(AtomicBool::new(false));
+  let (stop_tx, stop_rx) = mpsc::channel();
+  let (screenshot_tx, screenshot_handle) = if config.options.screenshots_enabled() {
+    let (tx, rx) = mpsc::channel();
+    let device_id = config.device_id.clone();
+    let handle = thread::spawn(move || screenshot_loop(session_id, device_id, started, rx));
+    (Some(tx), Some(handle))
+  } else {
+    (None, None)
+  };
+  let logcat = if config.options.logcat_on_events || config.options.record_logcat {
+    LogcatWatcher::start(session_id, &config, started, samples.clone())
+      .map_err(|e| warn!("启动 logcat 监听失败: {}", e))
+      .ok()
+  } else {
+    None
+  };
+  let handle = {
+    let config = config.clone();
+    let samples = samples.clone();
+    let triggers = Triggers {
+      screenshot: screenshot_tx.clone(),
+      events: logcat
+        .as_ref()
+        .filter(|_| config.options.logcat_on_events)
+        .map(LogcatWatcher::reporter),
+      logcat_pid: logcat.as_ref().map(LogcatWatcher::pid),
+      stream: stream.clone(),
+      sinks: sinks.sender(),
+      paused: paused.clone(),
+    };
+    thread::spawn(move || sample_loop(session_id, config, started, samples, stop_rx, triggers))
+  };
+
+  info!(
+    "开始录制会话 {}: {} / {}",
+    session_id, config.device_id, config.package
+  );
+  if let Err(e) = set_kv(LAST_CONFIG_KEY, &config) {
+    warn!("保存录制配置失败: {}", e);
+  }
+  let perfetto_on_jank = config.options.perfetto_on_jank.clone();
+  if let Ok(mut recordings) = RECORDINGS.lock() {
+    recordings.insert(
+      session_id,
+      Recording {
+        config,
+        started,
+        start_time: now,
+        samples,
+        paused,
+        stop_tx,
+        handle,
+        screenshot_tx,
+        screenshot_handle,
+        logcat,
+        stream,
+        sinks,
+        restore,
+      },
+    );
+  }
+  if let Some(trace_config) = perfetto_on_jank {
+    if let Err(e) = arm_jank_trace(session_id, &trace_config) {
+      warn!("启动卡顿 trace 失败: {}", e);
+    }
+  }
+  Ok(session_id)
+}
+
+/// 停止录制并把采样写入会话
+pub fn stop_recording(session_id: i64) -> Result<Session> {
+  let recording = RECORDINGS
+    .lock()
+    .ok()
+    .and_then(|mut recordings| recordings.remove(&session_id))
+    .ok_or(SessionError::NotRecording(session_id))?;
+
+  let _ = recording.stop_tx.send(());
+  let _ = recording.handle.join();
+  // 采样结束即为录制时长，不计入后续落盘与录制后操作的耗时
+  let elapsed_secs = recording.started.elapsed().as_secs() as i64;
+  disarm_session(session_id);
+  let _ = stop_monkey(session_id);
+  let _ = stop_script(session_id);
+  // 发送端全部释放后截图线程处理完剩余请求自行退出
+  drop(recording.screenshot_tx);
+  if let Some(handle) = recording.screenshot_handle {
+    let _ = handle.join();
+  }
+  if let Some(logcat) = recording.logcat {
+    logcat.stop();
+  }
+  if let Some(Ok(mut stream)) = recording.stream.as_ref().map(|s| s.lock()) {
+    if let Err(e) = stream.finish() {
+      warn!("实时导出文件落盘失败: {}", e);
+    }
+  }
+  recording.sinks.finish();
+
+  let hooks = &recording.config.options.hooks;
+  if !hooks.is_empty() {
+    let t_ms = recording.started.elapsed().as_millis() as u64;
+    let results = run_post_hooks(&recording.config.device_id, hooks, recording.restore);
+    if let Err(e) = save_hook_results(session_id, HookStage::Post, t_ms, &results) {
+      warn!("保存录制后操作结果失败: {}", e);
+    }
+  }
+
+  let samples = recording
+    .samples
+    .lock()
+    .map(|s| s.clone())
+    .unwrap_or_default();
+  finish_session(
+    session_id,
+    &samples,
+    recording.start_time + elapsed_secs,
+    elapsed_secs,
+  )?;
+
+  info!("会话 {} 录制结束，共 {} 个采样", session_id, samples.len());
+  notify(|o| o.on_stopped(session_id));
+  Ok(load_session(session_id)?.session)
+}
+
+pub fn list_recordings() -> Vec<RecordingStatus> {
+  let Ok(recordings) = RECORDINGS.lock() else {
+    return Vec::new();
+  };
+  recordings
+    .iter()
+    .map(|(id, r)| RecordingStatus {
+      session_id: *id,
+      device_id: r.config.device_id.clone(),
+      package: r.config.package.clone(),
+      start_time: r.start_time,
+      elapsed_ms: r.started.elapsed().as_millis() as u64,
+      sample_count: r.samples.lock().map(|s| s.len()).unwrap_or(0),
+      paused: r.paused.load(Ordering::Relaxed),
+    })
+    .collect()
+}
+
+/// 托盘、快捷键等不指定会话的入口操作的对象：多个会话同时录制时取最近开始的一个
+pub fn latest_recording() -> Option<RecordingStatus> {
+  list_recordings().into_iter().max_by_key(|r| r.session_id)
+}
+
+/// 最近一次开始录制时使用的配置，托盘等入口据此快速开始新的录制
+pub fn last_recording_config() -> Result<Option<RecordingConfig>> {
+  get_kv(LAST_CONFIG_KEY)
+}
+
+/// 暂停或继续采样，录制时长照常计算，并在时间线上插入标记
+pub fn set_recording_paused(session_id: i64, paused: bool) -> Result<Marker> {
+  let changed = RECORDINGS
+    .lock()
+    .ok()
+    .and_then(|recordings| {
+      let r = recordings.get(&session_id)?;
+      Some(r.paused.swap(paused, Ordering::Relaxed) != paused)
+    })
+    .ok_or(SessionError::NotRecording(session_id))?;
+  match (changed, paused) {
+    (false, true) => return Err(SessionError::AlreadyPaused(session_id)),
+    (false, false) => return Err(SessionError::NotPaused(session_id)),
+    _ => {}
+  }
+  info!(
+    "会话 {} {}",
+    session_id,
+    if paused {
+      "暂停采样"
+    } else {
+      "继续采样"
+    }
+  );
+  mark_recording(session_id, if paused { "暂停" } else { "继续" })
+}
+
+/// 录制中会话到目前为止的采样，供中途加入的观看端补齐历史
+pub fn recording_samples(session_id: i64) -> Option<Vec<Sample>> {
+  let recordings = RECORDINGS.lock().ok()?;
+  let samples = recordings.get(&session_id)?.samples.lock().ok()?.clone();
+  Some(samples)
+}
+
+/// 录制中会话最近一次采样
+pub fn latest_sample(session_id: i64) -> Option<Sample> {
+  let recordings = RECORDINGS.lock().ok()?;
+  let samples = recordings.get(&session_id)?.samples.lock().ok()?;
+  samples.last().cloned()
+}
+
+/// 录制中会话的推送目标
+pub(crate) fn recording_sinks(session_id: i64) -> Option<SinkSender> {
+  let recordings = RECORDINGS.lock().ok()?;
+  Some(recordings.get(&session_id)?.sinks.sender())
+}
+
+/// 录制中会话的设备、应用与当前相对时刻
+pub fn recording_target(session_id: i64) -> Option<(String, String, u64)> {
+  let recordings = RECORDINGS.lock().ok()?;
+  let r = recordings.get(&session_id)?;
+  Some((
+    r.config.device_id.clone(),
+    r.config.package.clone(),
+    r.started.elapsed().as_millis() as u64,
+  ))
+}
+
+/// 录制目标应用所在的 Android 用户，未指定用户或会话未在录制时为 None
+pub fn recording_user(session_id: i64) -> Option<u32> {
+  let recordings = RECORDINGS.lock().ok()?;
+  recordings.get(&session_id)?.config.options.user
+}
+
+/// 找出要操作的录制中会话：指定设备时取该设备上的会话，否则要求只有一个会话在录制
+pub fn active_recording(device_id: Option<&str>) -> Result<i64> {
+  let recordings = list_recordings();
+  match device_id {
+    Some(device_id) => recordings
+      .iter()
+      .find(|r| r.device_id == device_id)
+      .map(|r| r.session_id)
+      .ok_or_else(|| SessionError::NoRecordingOnDevice(device_id.to_string())),
+    None => match recordings.as_slice() {
+      [only] => Ok(only.session_id),
+      [] => Err(SessionError::NoActiveRecording),
+      _ => Err(SessionError::AmbiguousRecording(recordings.len())),
+    },
+  }
+}
+
+/// 在录制中的会话当前时刻插入标记
+pub fn mark_recording(session_id: i64, name: &str) -> Result<Marker> {
+  let (t_ms, stream, sinks) = RECORDINGS
+    .lock()
+    .ok()
+    .and_then(|recordings| {
+      let r = recordings.get(&session_id)?;
+      if r.config.options.screenshot_on_marker {
+        if let Some(tx) = &r.screenshot_tx {
+          let _ = tx.send(ScreenshotTrigger::Marker);
+        }
+      }
+      Some((
+        r.started.elapsed().as_millis() as u64,
+        r.stream.clone(),
+        r.sinks.sender(),
+      ))
+    })
+    .ok_or(SessionError::NotRecording(session_id))?;
+
+  let marker = add_marker(session_id, t_ms, name)?;
+  sinks.send(SinkMessage::Marker(marker.clone()));
+  if let Some(Ok(mut stream)) = stream.as_ref().map(|s| s.lock()) {
+    if let Err(e) = stream.write_marker(&marker) {
+      warn!("实时导出标记失败: {}", e);
+    }
+  }
+  Ok(marker)
+}
+
+/// 录制中手动截图一次
+pub fn capture_recording_screenshot(session_id: i64) -> Result<()> {
+  RECORDINGS
+    .lock()
+    .ok()
+    .and_then(|recordings| {
+      let tx = recordings.get(&session_id)?.screenshot_tx.as_ref()?;
+      tx.send(ScreenshotTrigger::Manual).ok()
+    })
+    .ok_or(SessionError::NotRecording(session_id))
+}
+
+/// 采样线程需要通知的下游：截图、事件检测、实时导出与外部推送
+struct Triggers {
+  screenshot: Option<mpsc::Sender<ScreenshotTrigger>>,
+  events: Option<mpsc::Sender<PendingEvent>>,
+  /// logcat 过滤用的 PID，应用重启后重新解析
+  logcat_pid: Option<SharedPid>,
+  stream: Option<Arc<Mutex<SampleStream>>>,
+  sinks: SinkSender,
+  paused: Arc<AtomicBool>,
+}
+
+fn sample_loop(
+  session_id: i64,
+  config: RecordingConfig,
+  started: Instant,
+  samples: Arc<Mutex<Vec<Sample>>>,
+  stop_rx: mpsc::Receiver<()>,
+  triggers: Triggers,
+) {
+  let options = &config.options;
+  let interval = Duration::from_millis(options.interval_ms.max(MIN_INTERVAL_MS));
+  let mut conn: Option<Connection> = None;
+  let mut alerts = AlertEvaluator::default();
+  let mut anomalies = AnomalyDetector::default();
+  let mut throttle = ThrottleDetector::default();
+  let mut idle = IdleTracker::default();
+  let (probe_tx, probe_rx) = mpsc::channel();
+  let (probe_stop, probe_stop_rx) = mpsc::channel::<()>();
+  let probes = ProbeKinds {
+    thermal: options.detect_throttling,
+    idle: options.track_idle,
+    energy: options.estimate_energy,
+  };
+  let prober = (probes.thermal || probes.idle || probes.energy).then(|| {
+    let device_id = config.device_id.clone();
+    let paused = triggers.paused.clone();
+    thread::spawn(move || {
+      probe_loop(
+        &device_id,
+        started,
+        probes,
+        &paused,
+        &probe_tx,
+        &probe_stop_rx,
+      )
+    })
+  });
+  let mut last_fps: Option<f64> = None;
+  let mut next_screenshot_ms = 0;
+  // 设备掉线的时刻，重新连上后清除
+  let mut lost_at_ms: Option<u64> = None;
+  // 上次采样时应用是否在运行，以及上次记录过的失败指标
+  let mut app_running: Option<bool> = None;
+  let mut last_failures = BTreeMap::new();
+
+  loop {
+    let tick = Instant::now();
+    let t_ms = started.elapsed().as_millis() as u64;
+
+    let collected = if triggers.paused.load(Ordering::Relaxed) {
+      None
+    } else {
+      Some(collect_metrics_with(
+        &config.device_id,
+        &config.package,
+        &config.metrics,
+        options.collect_options(),
+      ))
+    };
+    // 暂停期间跳过采集，只等待下一个周期
+    match collected {
+      Some(Ok((snapshot, dumps))) => {
+        if let Some(lost_ms) = lost_at_ms.take() {
+          let message = format!(
+            "设备重新连接，掉线 {:.0} 秒",
+            (t_ms - lost_ms) as f64 / 1000.0
+          );
+          record_event(&mut conn, session_id, t_ms, EventKind::Reconnected, message);
+        }
+        let running = match snapshot.process_state {
+          ProcessState::Running => Some(true),
+          ProcessState::NotRunning => Some(false),
+          ProcessState::Unknown => None,
+        };
+        match (app_running, running) {
+          (Some(true) | None, Some(false)) => {
+            let message = format!("应用 {} 未在运行", config.package);
+            record_event(&mut conn, session_id, t_ms, EventKind::AppExited, message);
+          }
+          (Some(false), Some(true)) => {
+            let message = format!("应用 {} 已重新启动", config.package);
+            record_event(&mut conn, session_id, t_ms, EventKind::AppStarted, message);
+          }
+          _ => {}
+        }
+        app_running = running.or(app_running);
+        if let Some(pid) = &triggers.logcat_pid {
+          refresh_pid(
+            pid,
+            &config.device_id,
+            &config.package,
+            options.user,
+            running,
+          );
+        }
+        // 解析失败只在失败的指标变化时记录一次，避免每个周期刷屏
+        if snapshot.failures != last_failures {
+          if !snapshot.failures.is_empty() {
+            warn!("会话 {} 指标采集失败: {:?}", session_id, snapshot.failures);
+          }
+          last_failures = snapshot.failures.clone();
+        }
+        if !dumps.is_empty() {
+          if let Some(conn) = db(&mut conn) {
+            if let Err(e) = store_raw(conn, session_id, t_ms, &dumps) {
+              warn!("保存原始输出失败: {}", e);
+            }
+          }
+        }
+
+        let jank = match (last_fps, snapshot.fps) {
+          (Some(prev), Some(fps)) if fps < prev * (1.0 - options.jank_drop_pct / 100.0) => {
+            Some(format!("帧率由 {prev:.0} 降至 {fps:.0}"))
+          }
+          _ => None,
+        };
+        last_fps = snapshot.fps.or(last_fps);
+
+        if let (Some(message), Some(tx)) = (&jank, &triggers.events) {
+          let _ = tx.send(PendingEvent {
+            t_ms,
+            kind: EventKind::Jank,
+            message: message.clone(),
+          });
+        }
+        if jank.is_some() && options.perfetto_on_jank.is_some() {
+          fire_jank_trace(session_id);
+        }
+        if let Some(tx) = &triggers.screenshot {
+          if jank.is_some() && options.screenshot_on_jank {
+            let _ = tx.send(ScreenshotTrigger::Jank);
+          } else if options.screenshot_interval_ms > 0 && t_ms >= next_screenshot_ms {
+            let _ = tx.send(ScreenshotTrigger::Interval);
+            next_screenshot_ms = t_ms + options.screenshot_interval_ms;
+          }
+        }
+
+        let sample = Sample {
+          t_ms,
+          time: None,
+          values: snapshot_values(&snapshot),
+        };
+        for (rule, value) in alerts.evaluate(&sample) {
+          let message = format!("{}，当前值 {:.1}", rule.describe(), value);
+          record_rule_event(&mut conn, session_id, t_ms, message, rule.id);
+        }
+        for probe in probe_rx.try_iter() {
+          match probe {
+            Probe::Thermal(probe_ms, thermal) => {
+              if let Some((kind, message)) = throttle.update(probe_ms, &thermal, snapshot.cpu) {
+                record_event(&mut conn, session_id, probe_ms, kind, message);
+              }
+            }
+            Probe::Idle(probe_ms, state) => {
+              if let Some(message) = idle.update(state) {
+                record_event(&mut conn, session_id, probe_ms, EventKind::Idle, message);
+              }
+            }
+            Probe::Energy(probe) => save_energy(&mut conn, session_id, &probe),
+          }
+        }
+        for anomaly in anomalies.push(&sample) {
+          notify(|o| o.on_anomaly(session_id, &anomaly));
+        }
+        if let Some(Ok(mut stream)) = triggers.stream.as_ref().map(|s| s.lock()) {
+          if let Err(e) = stream.write_sample(&sample) {
+            warn!("实时导出采样失败: {}", e);
+          }
+        }
+        triggers.sinks.send(SinkMessage::Sample(sample.clone()));
+        notify(|o| o.on_sample(session_id, &sample, &snapshot));
+        if let Ok(mut samples) = samples.lock() {
+          samples.push(sample);
+        }
+      }
+      Some(Err(e)) => {
+        warn!("会话 {} 采集失败: {}", session_id, e);
+        if lost_at_ms.is_none() && !device_online(&config.device_id) {
+          lost_at_ms = Some(t_ms);
+          let message = format!("设备 {} 连接断开", config.device_id);
+          record_event(
+            &mut conn,
+            session_id,
+            t_ms,
+            EventKind::Disconnected,
+            message,
+          );
+        }
+      }
+      None => {}
+    }
+
+    match stop_rx.recv_timeout(interval.saturating_sub(tick.elapsed())) {
+      Err(RecvTimeoutError::Timeout) => continue,
+      _ => break,
+    }
+  }
+
+  drop(probe_stop);
+  if let Some(prober) = prober {
+    let _ = prober.join();
+  }
+  // 尚未处理的能耗探测照常保存，其余探测只用于实时事件
+  for probe in probe_rx.try_iter() {
+    if let Probe::Energy(probe) = probe {
+      save_energy(&mut conn, session_id, &probe);
+    }
+  }
+  // 结束时补一次探测，覆盖最后一段
+  if options.estimate_energy {
+    let t_ms = started.elapsed().as_millis() as u64;
+    match energy_probe(&config.device_id, t_ms) {
+      Ok(probe) => save_energy(&mut conn, session_id, &probe),
+      Err(e) => warn!("读取频率驻留失败: {}", e),
+    }
+  }
+}
+
+/// 后台探测的结果及其时刻，由采样线程在下一个周期处理
+enum Probe {
+  Thermal(u64, ThermalSnapshot),
+  Idle(u64, IdleState),
+  Energy(EnergyProbe),
+}
+
+/// 需要在后台运行的探测
+#[derive(Debug, Clone, Copy)]
+struct ProbeKinds {
+  thermal: bool,
+  idle: bool,
+  energy: bool,
+}
+
+/// 温度、Doze 状态与频率驻留各需一次 adb 往返，在独立线程中按各自间隔探测，不占用采样周期；
+/// `stop` 的发送端释放后退出
+fn probe_loop(
+  device_id: &str,
+  started: Instant,
+  probes: ProbeKinds,
+  paused: &AtomicBool,
+  results: &mpsc::Sender<Probe>,
+  stop: &mpsc::Receiver<()>,
+) {
+  let mut next_thermal_ms = 0;
+  let mut next_idle_ms = 0;
+  let mut next_energy_ms = 0;
+  loop {
+    let t_ms = started.elapsed().as_millis() as u64;
+    if !paused.load(Ordering::Relaxed) {
+      if probes.thermal && t_ms >= next_thermal_ms {
+        next_thermal_ms = t_ms + THERMAL_PROBE_MS;
+        match thermal_snapshot(device_id) {
+          Ok(snapshot) => {
+            let _ = results.send(Probe::Thermal(t_ms, snapshot));
+          }
+          Err(e) => warn!("读取温度与频率失败: {}", e),
+        }
+      }
+      if probes.idle && t_ms >= next_idle_ms {
+        next_idle_ms = t_ms + IDLE_PROBE_MS;
+        match idle_state(device_id) {
+          Ok(state) => {
+            let _ = results.send(Probe::Idle(t_ms, state));
+          }
+          Err(e) => warn!("读取 Doze 状态失败: {}", e),
+        }
+      }
+      if probes.energy && t_ms >= next_energy_ms {
+        next_energy_ms = t_ms + ENERGY_PROBE_MS;
+        match energy_probe(device_id, t_ms) {
+          Ok(probe) => {
+            let _ = results.send(Probe::Energy(probe));
+          }
+          Err(e) => warn!("读取频率驻留失败: {}", e),
+        }
+      }
+    }
+    match stop.recv_timeout(Duration::from_millis(PROBE_POLL_MS)) {
+      Err(RecvTimeoutError::Timeout) => continue,
+      _ => break,
+    }
+  }
+}
+
+/// 采集失败时确认设备是否仍在线，adb 本身不可用时不判为掉线
+fn device_online(device_id: &str) -> bool {
+  list_devices()
+    .map(|devices| {
+      devices
+        .iter()
+        .any(|d| d.id == device_id && d.state == "device")
+    })
+    .unwrap_or(true)
+}
+
+fn save_energy(conn: &mut Option<Connection>, session_id: i64, probe: &EnergyProbe) {
+  if let Some(conn) = db(conn) {
+    if let Err(e) = store_probe(conn, session_id, probe) {
+      warn!("保存能耗探测失败: {}", e);
+    }
+  }
+}
+
+/// 保存采样线程检测到的事件并通知观察者
+fn record_event(
+  conn: &mut Option<Connection>,
+  session_id: i64,
+  t_ms: u64,
+  kind: EventKind,
+  message: String,
+) {
+  store_event(conn, session_id, t_ms, kind, message, None);
+}
+
+/// 告警规则触发的事件，记下规则 id 供导出按规则归类
+fn record_rule_event(
+  conn: &mut Option<Connection>,
+  session_id: i64,
+  t_ms: u64,
+  message: String,
+  rule_id: i64,
+) {
+  store_event(
+    conn,
+    session_id,
+    t_ms,
+    EventKind::Alert,
+    message,
+    Some(rule_id),
+  );
+}
+
+fn store_event(
+  conn: &mut Option<Connection>,
+  session_id: i64,
+  t_ms: u64,
+  kind: EventKind,
+  message: String,
+  rule_id: Option<i64>,
+) {
+  let Some(conn) = db(conn) else {
+    return;
+  };
+  let result = add_event(conn, session_id, t_ms, kind, &message).and_then(|id| {
+    if let Some(rule_id) = rule_id {
+      set_rule_id(conn, id, rule_id)?;
+    }
+    Ok(id)
+  });
+  match result {
+    Ok(id) => {
+      let event = SessionEvent {
+        id,
+        t_ms,
+        kind,
+        message,
+        attachments: Vec::new(),
+        causes: Vec::new(),
+        rule_id,
+      };
+      notify(|o| o.on_event(session_id, &event));
+    }
+    Err(e) => warn!("保存会话事件失败: {}", e),
+  }
+}
+
+/// 采样线程按需打开数据库连接，失败时仅记录日志
+fn db(conn: &mut Option<Connection>) -> Option<&Connection> {
+  if conn.is_none() {
+    *conn = open().map_err(|e| warn!("打开数据库失败: {}", e)).ok();
+  }
+  conn.as_ref()
+}
+
+fn screenshot_loop(
+  session_id: i64,
+  device_id: String,
+  started: Instant,
+  rx: mpsc::Receiver<ScreenshotTrigger>,
+) {
+  let mut conn: Option<Connection> = None;
+  let mut last_ms: Option<u64> = None;
+
+  for trigger in rx {
+    let t_ms = started.elapsed().as_millis() as u64;
+    let too_soon = last_ms.is_some_and(|last| t_ms < last + MIN_SCREENSHOT_GAP_MS);
+    if too_soon && trigger != ScreenshotTrigger::Marker && trigger != ScreenshotTrigger::Manual {
+      continue;
+    }
+
+    let thumbnail = match capture_thumbnail(&device_id) {
+      Ok(thumbnail) => thumbnail,
+      Err(e) => {
+        warn!("会话 {} 截图失败: {}", session_id, e);
+        continue;
+      }
+    };
+    if let Some(conn) = db(&mut conn) {
+      match store_screenshot(conn, session_id, t_ms, trigger, thumbnail) {
+        Ok(_) => last_ms = Some(t_ms),
+        Err(e) => warn!("保存截图失败: {}", e),
+      }
+    }
+  }
+}
@@ -0,0 +1,80 @@
+//! 修改设备设置前保存原值的通用记录：按设备写入数据库，恢复后删除；
+//! 应用崩溃未能恢复的记录在下次启动或退出时统一处理
+
+use crate::session::{
+  error::Result,
+  store::{delete_kv, get_kv, kv_keys, set_kv},
+};
+use log::{info, warn};
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+
+/// 一类设备设置的待恢复记录，`T` 为恢复所需的数据
+pub struct DeviceSetting<T> {
+  prefix: &'static str,
+  /// 日志中的设置名称
+  label: &'static str,
+  _value: PhantomData<fn() -> T>,
+}
+
+impl<T: Serialize + DeserializeOwned> DeviceSetting<T> {
+  pub const fn new(prefix: &'static str, label: &'static str) -> Self {
+    Self {
+      prefix,
+      label,
+      _value: PhantomData,
+    }
+  }
+
+  pub fn get(&self, device_id: &str) -> Result<Option<T>> {
+    get_kv(&self.key(device_id))
+  }
+
+  pub fn save(&self, device_id: &str, value: &T) -> Result<()> {
+    set_kv(&self.key(device_id), value)
+  }
+
+  /// 没有记录时读取原值并保存，多次修改时保留最初的值
+  pub fn save_original(&self, device_id: &str, read: impl FnOnce() -> Result<T>) -> Result<()> {
+    if self.get(device_id)?.is_none() {
+      self.save(device_id, &read()?)?;
+    }
+    Ok(())
+  }
+
+  /// 按记录恢复并删除记录；没有记录时返回 false，恢复失败时保留记录
+  pub fn restore(&self, device_id: &str, apply: impl FnOnce(&T) -> Result<()>) -> Result<bool> {
+    let Some(saved) = self.get(device_id)? else {
+      return Ok(false);
+    };
+    apply(&saved)?;
+    delete_kv(&self.key(device_id))?;
+    Ok(true)
+  }
+
+  /// 对记录满足 `filter` 的每台设备调用 `restore`；设备不在线的记录保留到下次
+  pub fn restore_all(&self, filter: impl Fn(&T) -> bool, restore: impl Fn(&str) -> Result<bool>) {
+    let keys = match kv_keys(self.prefix) {
+      Ok(keys) => keys,
+      Err(e) => {
+        warn!("读取待恢复的{}失败: {}", self.label, e);
+        return;
+      }
+    };
+    for key in keys {
+      match get_kv::<T>(&key) {
+        Ok(Some(saved)) if filter(&saved) => {}
+        _ => continue,
+      }
+      let device = &key[self.prefix.len()..];
+      match restore(device) {
+        Ok(_) => info!("已恢复设备 {} 的{}", device, self.label),
+        Err(e) => warn!("恢复设备 {} 的{}失败: {}", device, self.label, e),
+      }
+    }
+  }
+
+  fn key(&self, device_id: &str) -> String {
+    format!("{}{device_id}", self.prefix)
+  }
+}
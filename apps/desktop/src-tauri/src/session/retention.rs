@@ -0,0 +1,202 @@
+use crate::session::{
+  error::Result,
+  store::{db_path, delete_session, get_kv, open, set_kv, SESSION_SIZE_QUERIES},
+};
+use log::info;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::{HashMap, HashSet},
+  fs,
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+const POLICY_KEY: &str = "retention";
+
+/// 数据保留策略，各项为 None 时不限制
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+  #[serde(default)]
+  pub max_sessions: Option<u32>,
+  #[serde(default)]
+  pub max_age_days: Option<u32>,
+  #[serde(default)]
+  pub max_db_mb: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSize {
+  pub session_id: i64,
+  pub name: String,
+  pub created_at: i64,
+  pub bytes: u64,
+  pub is_baseline: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbUsage {
+  pub file_bytes: u64,
+  pub sessions: Vec<SessionSize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceReport {
+  pub deleted: Vec<i64>,
+  pub file_bytes_before: u64,
+  pub file_bytes_after: u64,
+}
+
+pub fn load_policy() -> Result<RetentionPolicy> {
+  Ok(get_kv(POLICY_KEY)?.unwrap_or_default())
+}
+
+pub fn save_policy(policy: &RetentionPolicy) -> Result<()> {
+  set_kv(POLICY_KEY, policy)
+}
+
+fn file_bytes() -> u64 {
+  db_path()
+    .and_then(|p| fs::metadata(p).ok())
+    .map(|m| m.len())
+    .unwrap_or(0)
+}
+
+/// 各会话占用空间（按创建时间从新到旧）
+pub fn db_usage() -> Result<DbUsage> {
+  let conn = open()?;
+  let mut bytes: HashMap<i64, u64> = HashMap::new();
+  for sql in SESSION_SIZE_QUERIES {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map([], |row| {
+      Ok((
+        row.get::<_, i64>(0)?,
+        row.get::<_, Option<i64>>(1)?.unwrap_or(0),
+      ))
+    })?;
+    for row in rows {
+      let (id, size) = row?;
+      *bytes.entry(id).or_default() += size.max(0) as u64;
+    }
+  }
+
+  let baselines: HashSet<i64> = conn
+    .prepare("SELECT session_id FROM baselines")?
+    .query_map([], |row| row.get(0))?
+    .collect::<rusqlite::Result<_>>()?;
+
+  let mut stmt = conn.prepare(
+    "SELECT id, name, COALESCE(created_at, 0) FROM reports ORDER BY created_at DESC, id DESC",
+  )?;
+  let sessions = stmt
+    .query_map([], |row| {
+      let id: i64 = row.get(0)?;
+      Ok(SessionSize {
+        session_id: id,
+        name: row.get(1)?,
+        created_at: row.get(2)?,
+        bytes: bytes.get(&id).copied().unwrap_or(0),
+        is_baseline: baselines.contains(&id),
+      })
+    })?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+
+  Ok(DbUsage {
+    file_bytes: file_bytes(),
+    sessions,
+  })
+}
+
+/// 数据库中数据页占用的字节数，不含 VACUUM 后会释放的空闲页
+fn used_bytes(conn: &Connection) -> Result<u64> {
+  let pragma = |name: &str| {
+    conn
+      .query_row(&format!("PRAGMA {name}"), [], |row| row.get::<_, i64>(0))
+      .map(|v| v.max(0) as u64)
+  };
+  let page_size = pragma("page_size")?;
+  let pages = pragma("page_count")?.saturating_sub(pragma("freelist_count")?);
+  Ok(pages * page_size)
+}
+
+fn delete_sessions(conn: &mut Connection, ids: &[i64]) -> Result<()> {
+  let tx = conn.transaction()?;
+  for id in ids {
+    delete_session(&tx, *id)?;
+  }
+  tx.commit()?;
+  Ok(())
+}
+
+/// 按保留策略清理旧会话并 VACUUM；基线会话始终保留
+///
+/// 空间上限按数据库实际的页占用判断：从最旧的会话开始删除，删除后重新测量，直到不超过上限
+pub fn run_maintenance() -> Result<MaintenanceReport> {
+  let policy = load_policy()?;
+  let usage = db_usage()?;
+  let now = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_secs() as i64;
+
+  let mut doomed: HashSet<i64> = HashSet::new();
+  let mut kept = 0u32;
+  for session in &usage.sessions {
+    if session.is_baseline {
+      kept += 1;
+      continue;
+    }
+    let too_old = policy
+      .max_age_days
+      .is_some_and(|days| session.created_at < now - days as i64 * 86_400);
+    let over_count = policy.max_sessions.is_some_and(|max| kept >= max);
+    if too_old || over_count {
+      doomed.insert(session.session_id);
+    } else {
+      kept += 1;
+    }
+  }
+
+  let mut conn = open()?;
+  let mut deleted: Vec<i64> = doomed.iter().copied().collect();
+  delete_sessions(&mut conn, &deleted)?;
+
+  if let Some(max_mb) = policy.max_db_mb {
+    let limit = max_mb * 1024 * 1024;
+    loop {
+      let mut total = used_bytes(&conn)?;
+      // 按各会话的内容大小估算需要删除的数量，删除后以实际占用复查
+      let mut batch = Vec::new();
+      for session in usage.sessions.iter().rev() {
+        if total <= limit {
+          break;
+        }
+        if session.is_baseline || doomed.contains(&session.session_id) {
+          continue;
+        }
+        doomed.insert(session.session_id);
+        batch.push(session.session_id);
+        total = total.saturating_sub(session.bytes);
+      }
+      if batch.is_empty() {
+        break;
+      }
+      delete_sessions(&mut conn, &batch)?;
+      deleted.extend(batch);
+    }
+  }
+  deleted.sort_unstable();
+
+  let free_pages: i64 = conn.query_row("PRAGMA freelist_count", [], |row| row.get(0))?;
+  if !deleted.is_empty() || free_pages > 0 {
+    conn.execute_batch("VACUUM")?;
+  }
+  if !deleted.is_empty() {
+    info!("数据保留策略清理了 {} 个会话", deleted.len());
+  }
+
+  Ok(MaintenanceReport {
+    deleted,
+    file_bytes_before: usage.file_bytes,
+    file_bytes_after: file_bytes(),
+  })
+}
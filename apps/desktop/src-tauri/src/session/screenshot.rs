@@ -0,0 +1,124 @@
+use crate::{
+  adb::screencap_png,
+  session::{
+    error::{Result, SessionError},
+    store::open,
+  },
+};
+use image::{codecs::jpeg::JpegEncoder, imageops::FilterType};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+// 缩略图最大宽度，足够辨认界面内容，单张约 10~30KB
+const THUMB_MAX_WIDTH: u32 = 360;
+const THUMB_JPEG_QUALITY: u8 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenshotTrigger {
+  Interval,
+  Marker,
+  Jank,
+  Manual,
+}
+
+impl ScreenshotTrigger {
+  fn as_str(self) -> &'static str {
+    match self {
+      Self::Interval => "interval",
+      Self::Marker => "marker",
+      Self::Jank => "jank",
+      Self::Manual => "manual",
+    }
+  }
+
+  fn parse(value: &str) -> Self {
+    match value {
+      "interval" => Self::Interval,
+      "marker" => Self::Marker,
+      "jank" => Self::Jank,
+      _ => Self::Manual,
+    }
+  }
+}
+
+/// 截图元信息，图片数据通过 `load_screenshot` 单独读取
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreenshotInfo {
+  pub id: i64,
+  pub t_ms: u64,
+  pub trigger: ScreenshotTrigger,
+  pub width: u32,
+  pub height: u32,
+}
+
+/// 截屏并缩放为 JPEG 缩略图，返回 (宽, 高, 数据)
+pub fn capture_thumbnail(device_id: &str) -> Result<(u32, u32, Vec<u8>)> {
+  let png = screencap_png(device_id)?;
+  let image = image::load_from_memory(&png)?;
+  let image = if image.width() > THUMB_MAX_WIDTH {
+    image.resize(THUMB_MAX_WIDTH, u32::MAX, FilterType::Triangle)
+  } else {
+    image
+  };
+  let rgb = image.to_rgb8();
+
+  let mut data = Vec::new();
+  JpegEncoder::new_with_quality(&mut data, THUMB_JPEG_QUALITY).encode_image(&rgb)?;
+  Ok((rgb.width(), rgb.height(), data))
+}
+
+pub fn store_screenshot(
+  conn: &Connection,
+  session_id: i64,
+  t_ms: u64,
+  trigger: ScreenshotTrigger,
+  (width, height, data): (u32, u32, Vec<u8>),
+) -> Result<i64> {
+  conn.execute(
+    "INSERT INTO session_screenshots (session_id, t_ms, trigger, width, height, data)
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    params![
+      session_id,
+      t_ms as i64,
+      trigger.as_str(),
+      width,
+      height,
+      data
+    ],
+  )?;
+  Ok(conn.last_insert_rowid())
+}
+
+pub fn list_screenshots(session_id: i64) -> Result<Vec<ScreenshotInfo>> {
+  let conn = open()?;
+  let mut stmt = conn.prepare(
+    "SELECT id, t_ms, trigger, width, height FROM session_screenshots
+     WHERE session_id = ?1 ORDER BY t_ms, id",
+  )?;
+  let screenshots = stmt
+    .query_map(params![session_id], |row| {
+      Ok(ScreenshotInfo {
+        id: row.get(0)?,
+        t_ms: row.get::<_, i64>(1)?.max(0) as u64,
+        trigger: ScreenshotTrigger::parse(&row.get::<_, String>(2)?),
+        width: row.get(3)?,
+        height: row.get(4)?,
+      })
+    })?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+  Ok(screenshots)
+}
+
+/// 读取截图的 JPEG 数据
+pub fn load_screenshot(id: i64) -> Result<Vec<u8>> {
+  let conn = open()?;
+  conn
+    .query_row(
+      "SELECT data FROM session_screenshots WHERE id = ?1",
+      params![id],
+      |row| row.get(0),
+    )
+    .optional()?
+    .ok_or_else(|| SessionError::Format(format!("截图不存在: {id}")))
+}
@@ -0,0 +1,498 @@
+use crate::{
+  adb::{
+    app::{force_stop, launcher_activity, start_activity},
+    command::run_device,
+    input::{input_text, keyevent, swipe, tap},
+  },
+  session::{
+    error::{Result, SessionError},
+    events::{add_attachment, add_event, EventKind},
+    recorder::{mark_recording, recording_target, recording_user},
+    store::open,
+  },
+};
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::HashMap,
+  io::{BufRead, BufReader, Read},
+  process::{Child, Command, Stdio},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+  },
+  thread,
+  time::Duration,
+};
+
+/// 外部脚本通过输出此前缀的行声明步骤，如 `PERFX_STEP: 打开详情页`
+pub const STEP_PREFIX: &str = "PERFX_STEP:";
+
+pub(crate) fn default_swipe_ms() -> u32 {
+  300
+}
+
+/// 内置步骤支持的操作
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum StepAction {
+  /// 启动会话的目标应用
+  Launch,
+  /// 结束目标应用进程
+  ForceStop,
+  Tap {
+    x: u32,
+    y: u32,
+  },
+  Swipe {
+    x1: u32,
+    y1: u32,
+    x2: u32,
+    y2: u32,
+    #[serde(default = "default_swipe_ms")]
+    duration_ms: u32,
+  },
+  Text {
+    text: String,
+  },
+  Key {
+    key: String,
+  },
+  Back,
+  Wait {
+    ms: u64,
+  },
+  Shell {
+    command: String,
+  },
+}
+
+impl StepAction {
+  fn describe(&self) -> String {
+    match self {
+      Self::Launch => "启动应用".into(),
+      Self::ForceStop => "结束应用".into(),
+      Self::Tap { x, y } => format!("点击 ({x}, {y})"),
+      Self::Swipe { x1, y1, x2, y2, .. } => format!("滑动 ({x1}, {y1}) → ({x2}, {y2})"),
+      Self::Text { text } => format!("输入 {text}"),
+      Self::Key { key } => format!("按键 {key}"),
+      Self::Back => "返回".into(),
+      Self::Wait { ms } => format!("等待 {ms}ms"),
+      Self::Shell { command } => format!("执行 {command}"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptStep {
+  /// 标记名称，不填时按操作生成
+  #[serde(default)]
+  pub name: Option<String>,
+  #[serde(flatten)]
+  pub action: StepAction,
+  /// 操作完成后等待界面稳定的时间
+  #[serde(default)]
+  pub settle_ms: u64,
+}
+
+/// 自动化脚本来源
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScriptSource {
+  /// 内置步骤列表，逐条通过 `adb shell input` 执行
+  Steps { steps: Vec<ScriptStep> },
+  /// Maestro flow 文件，按输出的每条命令插入标记
+  Maestro {
+    flow: String,
+    #[serde(default)]
+    bin: Option<String>,
+  },
+  /// uiautomator2 Python 脚本，脚本输出 `PERFX_STEP:` 行声明步骤；
+  /// 设备序列号通过 `ANDROID_SERIAL` 环境变量传入
+  Uiautomator2 {
+    script: String,
+    #[serde(default)]
+    python: Option<String>,
+  },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepResult {
+  pub name: String,
+  /// 步骤开始的录制相对时刻
+  pub t_ms: u64,
+  pub ok: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptRun {
+  pub session_id: i64,
+  pub steps: Vec<StepResult>,
+  pub finished: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+}
+
+struct RunningScript {
+  run: Arc<Mutex<ScriptRun>>,
+  cancel: Arc<AtomicBool>,
+  child: Arc<Mutex<Option<Child>>>,
+}
+
+static RUNNING: Lazy<Mutex<HashMap<i64, RunningScript>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 在录制中的会话上执行自动化脚本，每个步骤开始时插入标记，结束后结果与输出保存为附件
+pub fn run_script(session_id: i64, source: ScriptSource) -> Result<ScriptRun> {
+  let (device_id, package, _) =
+    recording_target(session_id).ok_or(SessionError::NotRecording(session_id))?;
+  let user = recording_user(session_id);
+  let mut running = RUNNING.lock().map_err(|_| SessionError::NotInitialized)?;
+  if running.contains_key(&session_id) {
+    return Err(SessionError::CaptureRunning(session_id, "自动化脚本"));
+  }
+
+  let run = Arc::new(Mutex::new(ScriptRun {
+    session_id,
+    steps: Vec::new(),
+    finished: false,
+    error: None,
+  }));
+  let cancel = Arc::new(AtomicBool::new(false));
+  let child = Arc::new(Mutex::new(None));
+  let ctx = ScriptContext {
+    session_id,
+    device_id,
+    package,
+    user,
+    run: run.clone(),
+    cancel: cancel.clone(),
+    child: child.clone(),
+  };
+  running.insert(session_id, RunningScript { run, cancel, child });
+  drop(running);
+
+  let snapshot = ctx.snapshot();
+  thread::spawn(move || {
+    let log = match &source {
+      ScriptSource::Steps { steps } => ctx.run_steps(steps),
+      ScriptSource::Maestro { flow, bin } => {
+        let mut cmd = Command::new(bin.as_deref().unwrap_or("maestro"));
+        cmd.args(["--device", &ctx.device_id, "test", flow]);
+        ctx.run_external(cmd, maestro_step)
+      }
+      ScriptSource::Uiautomator2 { script, python } => {
+        let mut cmd = Command::new(python.as_deref().unwrap_or("python3"));
+        cmd.arg(script).env("ANDROID_SERIAL", &ctx.device_id);
+        ctx.run_external(cmd, declared_step)
+      }
+    };
+    ctx.finish(&log);
+  });
+  Ok(snapshot)
+}
+
+/// 中止会话上执行中的脚本，已完成步骤的结果照常保存
+pub fn stop_script(session_id: i64) -> Result<()> {
+  let running = RUNNING.lock().map_err(|_| SessionError::NotInitialized)?;
+  let script = running
+    .get(&session_id)
+    .ok_or(SessionError::CaptureNotRunning(session_id, "自动化脚本"))?;
+  script.cancel.store(true, Ordering::Relaxed);
+  if let Ok(mut child) = script.child.lock() {
+    if let Some(child) = child.as_mut() {
+      let _ = child.kill();
+    }
+  }
+  Ok(())
+}
+
+pub fn list_script_runs() -> Vec<ScriptRun> {
+  RUNNING
+    .lock()
+    .map(|running| {
+      running
+        .values()
+        .filter_map(|s| s.run.lock().ok().map(|r| r.clone()))
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// 在设备上执行一个内置操作，启动与结束应用作用于 `user` 下的应用；
+/// 等待期间 `stopped` 返回 true 时提前结束
+pub fn perform_action(
+  device_id: &str,
+  package: &str,
+  user: Option<u32>,
+  action: &StepAction,
+  stopped: impl Fn() -> bool,
+) -> Result<()> {
+  match action {
+    StepAction::Launch => {
+      let component = launcher_activity(device_id, package, user)?;
+      start_activity(device_id, &component, user)?;
+    }
+    StepAction::ForceStop => force_stop(device_id, package, user)?,
+    StepAction::Tap { x, y } => tap(device_id, *x, *y)?,
+    StepAction::Swipe {
+      x1,
+      y1,
+      x2,
+      y2,
+      duration_ms,
+    } => swipe(device_id, (*x1, *y1), (*x2, *y2), *duration_ms)?,
+    StepAction::Text { text } => input_text(device_id, text)?,
+    StepAction::Key { key } => keyevent(device_id, key)?,
+    StepAction::Back => keyevent(device_id, "KEYCODE_BACK")?,
+    StepAction::Wait { ms } => {
+      // 分段等待，便于及时响应中止
+      let mut left = *ms;
+      while left > 0 && !stopped() {
+        let chunk = left.min(200);
+        thread::sleep(Duration::from_millis(chunk));
+        left -= chunk;
+      }
+    }
+    StepAction::Shell { command } => {
+      run_device(device_id, &["shell", command])?;
+    }
+  }
+  Ok(())
+}
+
+/// 外部脚本输出行中声明的步骤
+enum StepLine {
+  Begin(String),
+  Failed,
+}
+
+/// Maestro 非交互输出形如 `Tap on "登录"... RUNNING` / `... COMPLETED` / `... FAILED`
+fn maestro_step(line: &str) -> Option<StepLine> {
+  let (command, status) = line.trim().rsplit_once("... ")?;
+  let command = command.trim().to_string();
+  match status.trim() {
+    "RUNNING" => Some(StepLine::Begin(command)),
+    "FAILED" => Some(StepLine::Failed),
+    _ => None,
+  }
+}
+
+fn declared_step(line: &str) -> Option<StepLine> {
+  let name = line.trim().strip_prefix(STEP_PREFIX)?.trim();
+  (!name.is_empty()).then(|| StepLine::Begin(name.to_string()))
+}
+
+struct ScriptContext {
+  session_id: i64,
+  device_id: String,
+  package: String,
+  user: Option<u32>,
+  run: Arc<Mutex<ScriptRun>>,
+  cancel: Arc<AtomicBool>,
+  child: Arc<Mutex<Option<Child>>>,
+}
+
+impl ScriptContext {
+  fn snapshot(&self) -> ScriptRun {
+    self
+      .run
+      .lock()
+      .map(|r| r.clone())
+      .unwrap_or_else(|_| ScriptRun {
+        session_id: self.session_id,
+        steps: Vec::new(),
+        finished: false,
+        error: None,
+      })
+  }
+
+  /// 插入步骤标记并记录开始时刻
+  fn begin_step(&self, name: &str) {
+    let t_ms = match mark_recording(self.session_id, name) {
+      Ok(marker) => marker.t_ms,
+      Err(e) => {
+        warn!("插入步骤标记失败: {}", e);
+        recording_target(self.session_id).map_or(0, |(_, _, t)| t)
+      }
+    };
+    if let Ok(mut run) = self.run.lock() {
+      run.steps.push(StepResult {
+        name: name.to_string(),
+        t_ms,
+        ok: true,
+        error: None,
+      });
+    }
+  }
+
+  fn fail_step(&self, error: String) {
+    if let Ok(mut run) = self.run.lock() {
+      if let Some(step) = run.steps.last_mut() {
+        step.ok = false;
+        step.error = Some(error);
+      }
+    }
+  }
+
+  fn stopped(&self) -> bool {
+    self.cancel.load(Ordering::Relaxed) || recording_target(self.session_id).is_none()
+  }
+
+  fn run_steps(&self, steps: &[ScriptStep]) -> String {
+    let mut log = String::new();
+    for step in steps {
+      if self.stopped() {
+        break;
+      }
+      let name = step.name.clone().unwrap_or_else(|| step.action.describe());
+      self.begin_step(&name);
+      log.push_str(&format!("{name}\n"));
+      if let Err(e) = self.execute(&step.action) {
+        log.push_str(&format!("  失败: {e}\n"));
+        self.fail_step(e.to_string());
+        break;
+      }
+      if step.settle_ms > 0 {
+        thread::sleep(Duration::from_millis(step.settle_ms));
+      }
+    }
+    log
+  }
+
+  fn execute(&self, action: &StepAction) -> Result<()> {
+    perform_action(
+      &self.device_id,
+      &self.package,
+      self.user,
+      action,
+      || self.stopped(),
+    )
+  }
+
+  /// 执行主机上的脚本进程，按输出行识别步骤；返回合并后的 stdout / stderr
+  fn run_external(&self, mut cmd: Command, parse: fn(&str) -> Option<StepLine>) -> String {
+    cmd
+      .env("PERFX_SESSION_ID", self.session_id.to_string())
+      .stdout(Stdio::piped())
+      .stderr(Stdio::piped());
+    #[cfg(target_os = "windows")]
+    {
+      use std::os::windows::process::CommandExt;
+      cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+
+    let mut child = match cmd.spawn() {
+      Ok(child) => child,
+      Err(e) => {
+        self.set_error(format!("启动脚本失败: {e}"));
+        return String::new();
+      }
+    };
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    if let Ok(mut slot) = self.child.lock() {
+      *slot = Some(child);
+    }
+    let stderr = thread::spawn(move || {
+      let mut text = String::new();
+      if let Some(mut err) = stderr {
+        let _ = err.read_to_string(&mut text);
+      }
+      text
+    });
+
+    let mut log = String::new();
+    if let Some(out) = stdout {
+      for line in BufReader::new(out).lines().map_while(|l| l.ok()) {
+        match parse(&line) {
+          Some(StepLine::Begin(name)) => self.begin_step(&name),
+          Some(StepLine::Failed) => self.fail_step("执行失败".into()),
+          None => {}
+        }
+        log.push_str(&line);
+        log.push('\n');
+      }
+    }
+    let status = self
+      .child
+      .lock()
+      .ok()
+      .and_then(|mut slot| slot.take())
+      .and_then(|mut child| child.wait().ok());
+    log.push_str(&stderr.join().unwrap_or_default());
+    match status {
+      Some(status) if !status.success() && !self.cancel.load(Ordering::Relaxed) => {
+        self.set_error(format!("脚本退出码 {}", status.code().unwrap_or(-1)));
+      }
+      _ => {}
+    }
+    log
+  }
+
+  fn set_error(&self, error: String) {
+    if let Ok(mut run) = self.run.lock() {
+      run.error = Some(error);
+    }
+  }
+
+  /// 保存结果与输出，失败的步骤记为告警事件
+  fn finish(&self, log: &str) {
+    if let Ok(mut running) = RUNNING.lock() {
+      running.remove(&self.session_id);
+    }
+    let run = {
+      let Ok(mut run) = self.run.lock() else {
+        return;
+      };
+      run.finished = true;
+      run.clone()
+    };
+
+    let saved = open().and_then(|conn| {
+      for step in run.steps.iter().filter(|s| !s.ok) {
+        let message = format!(
+          "脚本步骤失败: {}（{}）",
+          step.name,
+          step.error.as_deref().unwrap_or("未知错误")
+        );
+        add_event(
+          &conn,
+          self.session_id,
+          step.t_ms,
+          EventKind::Alert,
+          &message,
+        )?;
+      }
+      add_attachment(
+        &conn,
+        self.session_id,
+        None,
+        "script",
+        "script-result.json",
+        &serde_json::to_vec_pretty(&run)?,
+      )?;
+      if !log.is_empty() {
+        add_attachment(
+          &conn,
+          self.session_id,
+          None,
+          "script",
+          "script.log",
+          log.as_bytes(),
+        )?;
+      }
+      Ok(())
+    });
+    if let Err(e) = saved {
+      warn!("保存脚本结果失败: {}", e);
+    }
+    info!(
+      "会话 {} 脚本执行结束，共 {} 个步骤",
+      self.session_id,
+      run.steps.len()
+    );
+  }
+}
@@ -0,0 +1,97 @@
+//! HTTP 类推送目标共用的攒批与重试
+
+use crate::session::sink::SinkMessage;
+use log::warn;
+use std::{
+  sync::mpsc::{Receiver, RecvTimeoutError},
+  thread,
+  time::{Duration, Instant},
+};
+
+// 重试的初始等待，之后每次翻倍
+const RETRY_BASE_MS: u64 = 500;
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+pub(crate) fn default_batch_size() -> usize {
+  100
+}
+
+pub(crate) fn default_max_retries() -> u32 {
+  3
+}
+
+pub(crate) fn agent() -> ureq::Agent {
+  ureq::AgentBuilder::new().timeout(TIMEOUT).build()
+}
+
+/// 提交请求体，网络错误与 5xx / 429 按指数退避重试，其余错误直接放弃
+pub(crate) fn post_with_retry(
+  max_retries: u32,
+  request: impl Fn() -> ureq::Request,
+  body: &str,
+) -> Result<(), String> {
+  let mut attempt = 0;
+  loop {
+    let error = match request().send_string(body) {
+      Ok(_) => return Ok(()),
+      Err(ureq::Error::Status(code, response)) => {
+        let message = format!(
+          "HTTP {code}: {}",
+          response.into_string().unwrap_or_default()
+        );
+        if code != 429 && code < 500 {
+          return Err(message);
+        }
+        message
+      }
+      Err(e) => e.to_string(),
+    };
+    if attempt >= max_retries {
+      return Err(error);
+    }
+    thread::sleep(Duration::from_millis(RETRY_BASE_MS << attempt.min(6)));
+    attempt += 1;
+  }
+}
+
+/// 攒够 `batch_size` 条或距上次提交超过 `interval` 即提交一批，发送端关闭时提交剩余部分；
+/// 提交失败的批次丢弃，返回成功与丢弃的条数
+pub(crate) fn run_batched<T>(
+  name: &str,
+  rx: Receiver<SinkMessage>,
+  batch_size: usize,
+  interval: Duration,
+  mut convert: impl FnMut(SinkMessage) -> Option<T>,
+  mut flush: impl FnMut(&[T]) -> Result<(), String>,
+) -> (usize, usize) {
+  let interval = interval.max(Duration::from_millis(100));
+  let batch_size = batch_size.max(1);
+  let mut batch = Vec::new();
+  let mut last_flush = Instant::now();
+  let (mut written, mut dropped) = (0, 0);
+  loop {
+    let message = rx.recv_timeout(interval.saturating_sub(last_flush.elapsed()));
+    let closed = matches!(message, Err(RecvTimeoutError::Disconnected));
+    if let Some(item) = message.ok().and_then(&mut convert) {
+      batch.push(item);
+    }
+    if !batch.is_empty()
+      && (closed || batch.len() >= batch_size || last_flush.elapsed() >= interval)
+    {
+      match flush(&batch) {
+        Ok(()) => written += batch.len(),
+        Err(e) => {
+          warn!("写入 {} 失败，丢弃 {} 条: {}", name, batch.len(), e);
+          dropped += batch.len();
+        }
+      }
+      batch.clear();
+    }
+    if batch.is_empty() {
+      last_flush = Instant::now();
+    }
+    if closed {
+      return (written, dropped);
+    }
+  }
+}
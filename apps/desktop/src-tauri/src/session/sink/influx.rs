@@ -0,0 +1,173 @@
+use crate::session::sink::{
+  batch::{agent, default_batch_size, default_max_retries, post_with_retry, run_batched},
+  SinkMessage, SinkTarget,
+};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{sync::mpsc::Receiver, time::Duration};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InfluxVersion {
+  V1,
+  #[default]
+  V2,
+}
+
+pub(crate) fn default_measurement() -> String {
+  "perfx".into()
+}
+
+fn default_flush_interval_ms() -> u64 {
+  1000
+}
+
+/// 以行协议写入 InfluxDB，v1 写 `/write`，v2 写 `/api/v2/write`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InfluxConfig {
+  /// 服务地址，如 `http://influx.lab:8086`
+  pub url: String,
+  #[serde(default)]
+  pub version: InfluxVersion,
+  /// v1 为数据库名，v2 为 bucket
+  pub bucket: String,
+  /// v2 组织名
+  #[serde(default)]
+  pub org: Option<String>,
+  /// v2 的 API Token；v1 可填 `用户名:密码`
+  #[serde(default)]
+  pub token: Option<String>,
+  #[serde(default = "default_measurement")]
+  pub measurement: String,
+  /// 攒够该条数或距上次写入超过间隔即提交一批
+  #[serde(default = "default_batch_size")]
+  pub batch_size: usize,
+  #[serde(default = "default_flush_interval_ms")]
+  pub flush_interval_ms: u64,
+  /// 写入失败后的重试次数，仍失败则丢弃该批
+  #[serde(default = "default_max_retries")]
+  pub max_retries: u32,
+}
+
+impl InfluxConfig {
+  pub(crate) fn validate(&self) -> Result<(), String> {
+    if !self.url.starts_with("http://") && !self.url.starts_with("https://") {
+      return Err(format!("地址须以 http:// 或 https:// 开头: {}", self.url));
+    }
+    if self.bucket.trim().is_empty() {
+      return Err("未指定数据库 / bucket".into());
+    }
+    if self.version == InfluxVersion::V2 && self.org.as_deref().map_or(true, str::is_empty) {
+      return Err("InfluxDB 2.x 需要指定 org".into());
+    }
+    Ok(())
+  }
+
+  fn request(&self, agent: &ureq::Agent) -> ureq::Request {
+    let base = self.url.trim_end_matches('/');
+    let request = match self.version {
+      InfluxVersion::V1 => agent
+        .post(&format!("{base}/write"))
+        .query("db", &self.bucket),
+      InfluxVersion::V2 => agent
+        .post(&format!("{base}/api/v2/write"))
+        .query("org", self.org.as_deref().unwrap_or_default())
+        .query("bucket", &self.bucket),
+    }
+    .query("precision", "ms")
+    .set("Content-Type", "text/plain; charset=utf-8");
+    match &self.token {
+      Some(token) => request.set("Authorization", &format!("Token {token}")),
+      None => request,
+    }
+  }
+}
+
+/// 度量名只需转义逗号与空格；行协议中不能出现换行，换行替换为空格
+fn escape_measurement(value: &str) -> String {
+  value
+    .replace(['\n', '\r'], " ")
+    .replace(',', "\\,")
+    .replace(' ', "\\ ")
+}
+
+/// 标签键值与字段键还需转义等号
+fn escape_key(value: &str) -> String {
+  escape_measurement(value).replace('=', "\\=")
+}
+
+/// 字符串字段值转义引号与反斜杠，换行写作 `\n`，否则一条消息会被拆成多行导致整批写入失败
+fn escape_string(value: &str) -> String {
+  value
+    .replace('\\', "\\\\")
+    .replace('"', "\\\"")
+    .replace('\n', "\\n")
+    .replace('\r', "\\r")
+}
+
+/// 采样写为一行，标记与事件分别写入 `<measurement>_marker` / `<measurement>_event`，
+/// 便于在 Grafana 中作为注释
+pub(crate) fn to_line(
+  measurement: &str,
+  target: &SinkTarget,
+  message: &SinkMessage,
+) -> Option<String> {
+  let tags = format!(
+    "device={},package={},session_id={}",
+    escape_key(&target.device_id),
+    escape_key(&target.package),
+    target.session_id
+  );
+  let measurement = escape_measurement(measurement);
+  match message {
+    SinkMessage::Sample(sample) => {
+      let fields: Vec<String> = sample
+        .values
+        .iter()
+        .filter(|(_, v)| v.is_finite())
+        .map(|(key, value)| format!("{}={}", escape_key(key), value))
+        .collect();
+      if fields.is_empty() {
+        return None;
+      }
+      Some(format!(
+        "{measurement},{tags} {},t_ms={}i {}",
+        fields.join(","),
+        sample.t_ms,
+        target.start_ms + sample.t_ms as i64
+      ))
+    }
+    SinkMessage::Marker(marker) => Some(format!(
+      "{measurement}_marker,{tags} name=\"{}\",t_ms={}i {}",
+      escape_string(&marker.name),
+      marker.t_ms,
+      target.start_ms + marker.t_ms as i64
+    )),
+    SinkMessage::Event(event) => Some(format!(
+      "{measurement}_event,{tags},kind={} message=\"{}\",t_ms={}i {}",
+      event.kind.as_str(),
+      escape_string(&event.message),
+      event.t_ms,
+      target.start_ms + event.t_ms as i64
+    )),
+  }
+}
+
+pub(crate) fn run(config: InfluxConfig, target: &SinkTarget, rx: Receiver<SinkMessage>) {
+  let agent = agent();
+  let (written, dropped) = run_batched(
+    "InfluxDB",
+    rx,
+    config.batch_size,
+    Duration::from_millis(config.flush_interval_ms),
+    |message| to_line(&config.measurement, target, &message),
+    |lines: &[String]| {
+      let body = lines.join("\n");
+      post_with_retry(config.max_retries, || config.request(&agent), &body)
+    },
+  );
+  info!(
+    "会话 {} 推送 InfluxDB 结束，写入 {} 条，丢弃 {} 条",
+    target.session_id, written, dropped
+  );
+}
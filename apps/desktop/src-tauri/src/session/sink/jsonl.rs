@@ -0,0 +1,108 @@
+use crate::session::sink::{to_json, SinkMessage, SinkTarget};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+  fs::{File, OpenOptions},
+  io::{self, Write},
+  path::{Path, PathBuf},
+  sync::mpsc::{Receiver, RecvTimeoutError},
+  time::{Duration, Instant},
+};
+
+/// 命名管道等待读端的最长时间，超时后放弃输出
+const OPEN_TIMEOUT: Duration = Duration::from_secs(30);
+const OPEN_RETRY: Duration = Duration::from_millis(200);
+
+/// 每条数据写一行 JSON，写完立即刷新，供外部进程边录边读
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JsonlConfig {
+  /// 输出文件或命名管道；不指定时写到标准输出
+  #[serde(default)]
+  pub path: Option<PathBuf>,
+}
+
+impl JsonlConfig {
+  pub(crate) fn validate(&self) -> Result<(), String> {
+    match &self.path {
+      Some(path) if path.is_dir() => Err(format!("输出路径是目录: {}", path.display())),
+      _ => Ok(()),
+    }
+  }
+
+  /// 打开输出；命名管道没有读端时按间隔重试，期间到达的数据直接丢弃，
+  /// 录制结束或超时即返回 `None`，不会阻塞停止录制
+  fn open(&self, rx: &Receiver<SinkMessage>) -> Option<Box<dyn Write>> {
+    let Some(path) = &self.path else {
+      return Some(Box::new(io::stdout()));
+    };
+    let deadline = Instant::now() + OPEN_TIMEOUT;
+    loop {
+      match open_append(path) {
+        Ok(file) => return Some(Box::new(file)),
+        Err(e) if !waiting_for_reader(&e) => {
+          warn!("打开 JSONL 输出失败: {}", e);
+          return None;
+        }
+        Err(_) if Instant::now() >= deadline => {
+          warn!("命名管道 {} 一直没有读端，放弃输出", path.display());
+          return None;
+        }
+        Err(_) => match rx.recv_timeout(OPEN_RETRY) {
+          Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+          Err(RecvTimeoutError::Disconnected) => return None,
+        },
+      }
+    }
+  }
+}
+
+/// 追加打开，命名管道和已有文件都不会被截断
+fn open_append(path: &Path) -> io::Result<File> {
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::OpenOptionsExt;
+    // 非阻塞打开没有读端的命名管道会立即报 ENXIO，而不是一直等下去；
+    // 确认有读端后再以阻塞方式重新打开，后续写入不必处理 WouldBlock
+    OpenOptions::new()
+      .create(true)
+      .append(true)
+      .custom_flags(libc::O_NONBLOCK)
+      .open(path)?;
+  }
+  OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn waiting_for_reader(e: &io::Error) -> bool {
+  #[cfg(unix)]
+  {
+    e.raw_os_error() == Some(libc::ENXIO)
+  }
+  #[cfg(not(unix))]
+  {
+    let _ = e;
+    false
+  }
+}
+
+pub(crate) fn run(config: JsonlConfig, target: &SinkTarget, rx: Receiver<SinkMessage>) {
+  let Some(mut out) = config.open(&rx) else {
+    return;
+  };
+  let mut written = 0usize;
+  for message in rx {
+    let Some(mut line) = to_json(target, &message) else {
+      continue;
+    };
+    line.push('\n');
+    if let Err(e) = out.write_all(line.as_bytes()).and_then(|_| out.flush()) {
+      // 读端关闭后不再写入，余下的数据直接丢弃
+      warn!("JSONL 输出中断: {}", e);
+      break;
+    }
+    written += 1;
+  }
+  info!(
+    "会话 {} 输出 JSONL 结束，共 {} 行",
+    target.session_id, written
+  );
+}
@@ -0,0 +1,197 @@
+//! 录制过程中把采样推送到外部系统，每个目标一个后台线程，推送失败不影响录制
+
+use crate::session::{
+  error::{Result, SessionError},
+  events::SessionEvent,
+  recorder::{recording_sinks, RecordingObserver},
+  Marker, Sample,
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+  sync::{
+    mpsc::{self, Receiver, Sender},
+    Arc,
+  },
+  thread::{self, JoinHandle},
+};
+
+pub(crate) mod batch;
+pub mod influx;
+pub mod jsonl;
+pub mod mqtt;
+pub mod otlp;
+pub mod websocket;
+
+use influx::InfluxConfig;
+use jsonl::JsonlConfig;
+use mqtt::MqttConfig;
+use otlp::OtlpConfig;
+use websocket::WebSocketConfig;
+
+/// 单个推送目标，按 `type` 区分
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkConfig {
+  Influx(InfluxConfig),
+  #[serde(rename = "websocket")]
+  WebSocket(WebSocketConfig),
+  Otlp(OtlpConfig),
+  Mqtt(MqttConfig),
+  Jsonl(JsonlConfig),
+}
+
+impl SinkConfig {
+  fn name(&self) -> &'static str {
+    match self {
+      Self::Influx(_) => "InfluxDB",
+      Self::WebSocket(_) => "WebSocket",
+      Self::Otlp(_) => "OTLP",
+      Self::Mqtt(_) => "MQTT",
+      Self::Jsonl(_) => "JSONL",
+    }
+  }
+
+  fn validate(&self) -> std::result::Result<(), String> {
+    match self {
+      Self::Influx(config) => config.validate(),
+      Self::WebSocket(config) => config.validate(),
+      Self::Otlp(config) => config.validate(),
+      Self::Mqtt(config) => config.validate(),
+      Self::Jsonl(config) => config.validate(),
+    }
+  }
+
+  fn run(self, target: &SinkTarget, rx: Receiver<SinkMessage>) {
+    match self {
+      Self::Influx(config) => influx::run(config, target, rx),
+      Self::WebSocket(config) => websocket::run(config, target, rx),
+      Self::Otlp(config) => otlp::run(config, target, rx),
+      Self::Mqtt(config) => mqtt::run(config, target, rx),
+      Self::Jsonl(config) => jsonl::run(config, target, rx),
+    }
+  }
+}
+
+/// 推送数据所属的会话，作为标签 / 属性附加到每条数据上
+#[derive(Debug, Clone)]
+pub struct SinkTarget {
+  pub session_id: i64,
+  pub device_id: String,
+  pub package: String,
+  /// 录制开始的 Unix 毫秒时间，加上 `t_ms` 即为数据的绝对时刻
+  pub start_ms: i64,
+}
+
+#[derive(Debug, Clone)]
+pub enum SinkMessage {
+  Sample(Sample),
+  Marker(Marker),
+  Event(SessionEvent),
+}
+
+/// 发往所有推送线程的发送端，可复制给采样线程
+#[derive(Clone, Default)]
+pub struct SinkSender(Vec<Sender<SinkMessage>>);
+
+impl SinkSender {
+  pub fn send(&self, message: SinkMessage) {
+    for tx in &self.0 {
+      let _ = tx.send(message.clone());
+    }
+  }
+}
+
+/// 一次录制的全部推送目标
+#[derive(Default)]
+pub struct SinkSet {
+  sender: SinkSender,
+  handles: Vec<JoinHandle<()>>,
+}
+
+impl SinkSet {
+  /// 校验配置并启动推送线程，配置有误时不启动任何线程
+  pub fn start(configs: &[SinkConfig], target: &SinkTarget) -> Result<Self> {
+    for config in configs {
+      config
+        .validate()
+        .map_err(|e| SessionError::Sink(format!("{}: {}", config.name(), e)))?;
+    }
+    let mut set = Self::default();
+    for config in configs {
+      let (tx, rx) = mpsc::channel();
+      let config = config.clone();
+      let target = target.clone();
+      set.sender.0.push(tx);
+      set
+        .handles
+        .push(thread::spawn(move || config.run(&target, rx)));
+    }
+    Ok(set)
+  }
+
+  pub fn sender(&self) -> SinkSender {
+    self.sender.clone()
+  }
+
+  /// 关闭发送端，等待各线程推送完剩余数据；采样线程持有的发送端需先释放
+  pub fn finish(self) {
+    drop(self.sender);
+    for handle in self.handles {
+      if handle.join().is_err() {
+        warn!("推送线程异常退出");
+      }
+    }
+  }
+}
+
+/// 事件来自采样线程、logcat、monkey 等多处，统一经观察者转发给所属会话的推送目标
+struct SinkEvents;
+
+impl RecordingObserver for SinkEvents {
+  fn on_event(&self, session_id: i64, event: &SessionEvent) {
+    if let Some(sinks) = recording_sinks(session_id) {
+      sinks.send(SinkMessage::Event(event.clone()));
+    }
+  }
+}
+
+pub(crate) fn observer() -> Arc<dyn RecordingObserver> {
+  Arc::new(SinkEvents)
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum FrameBody<'a> {
+  Sample(&'a Sample),
+  Marker(&'a Marker),
+  Event(&'a SessionEvent),
+}
+
+#[derive(Serialize)]
+struct JsonFrame<'a> {
+  session_id: i64,
+  device_id: &'a str,
+  package: &'a str,
+  /// Unix 毫秒时间
+  timestamp_ms: i64,
+  #[serde(flatten)]
+  body: FrameBody<'a>,
+}
+
+/// 带会话信息的单行 JSON，WebSocket 与 JSONL 推送共用
+pub(crate) fn to_json(target: &SinkTarget, message: &SinkMessage) -> Option<String> {
+  let (t_ms, body) = match message {
+    SinkMessage::Sample(sample) => (sample.t_ms, FrameBody::Sample(sample)),
+    SinkMessage::Marker(marker) => (marker.t_ms, FrameBody::Marker(marker)),
+    SinkMessage::Event(event) => (event.t_ms, FrameBody::Event(event)),
+  };
+  let frame = JsonFrame {
+    session_id: target.session_id,
+    device_id: &target.device_id,
+    package: &target.package,
+    timestamp_ms: target.start_ms + t_ms as i64,
+    body,
+  };
+  serde_json::to_string(&frame).ok()
+}
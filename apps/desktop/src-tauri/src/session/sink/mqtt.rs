@@ -0,0 +1,226 @@
+//! 发布到 MQTT：每个指标一个主题，告警与异常事件发到统一的告警主题
+
+use crate::session::{
+  events::EventKind,
+  sink::{SinkMessage, SinkTarget},
+};
+use log::{info, warn};
+use rumqttc::{Client, MqttOptions, QoS, Transport};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::Receiver,
+    Arc,
+  },
+  thread,
+  time::Duration,
+};
+
+// 待发送请求的队列长度，broker 断开期间超出的消息直接丢弃
+const QUEUE_CAPACITY: usize = 256;
+const RECONNECT_GAP: Duration = Duration::from_secs(3);
+
+fn default_topic_prefix() -> String {
+  "perfx".into()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MqttConfig {
+  /// broker 地址，`mqtt://host:1883` 或 `mqtts://host:8883`
+  pub broker: String,
+  /// 不填时按会话生成
+  #[serde(default)]
+  pub client_id: Option<String>,
+  #[serde(default)]
+  pub username: Option<String>,
+  #[serde(default)]
+  pub password: Option<String>,
+  /// 采样发到 `<前缀>/<设备>/<指标>`，告警发到 `<前缀>/alerts`
+  #[serde(default = "default_topic_prefix")]
+  pub topic_prefix: String,
+  /// 0 / 1 / 2
+  #[serde(default)]
+  pub qos: u8,
+  /// 保留最后一条采样，新订阅者可立即拿到当前值
+  #[serde(default)]
+  pub retain: bool,
+}
+
+impl MqttConfig {
+  pub(crate) fn validate(&self) -> Result<(), String> {
+    self.address()?;
+    if self.qos > 2 {
+      return Err(format!("QoS 只能为 0、1、2: {}", self.qos));
+    }
+    Ok(())
+  }
+
+  /// 解析出主机、端口与是否启用 TLS
+  fn address(&self) -> Result<(&str, u16, bool), String> {
+    let (rest, tls) = if let Some(rest) = self.broker.strip_prefix("mqtts://") {
+      (rest, true)
+    } else if let Some(rest) = self.broker.strip_prefix("mqtt://") {
+      (rest, false)
+    } else {
+      (self.broker.as_str(), false)
+    };
+    let rest = rest.trim_end_matches('/');
+    let (host, port) = match rest.rsplit_once(':') {
+      Some((host, port)) => (
+        host,
+        port
+          .parse()
+          .map_err(|_| format!("端口无效: {}", self.broker))?,
+      ),
+      None => (rest, if tls { 8883 } else { 1883 }),
+    };
+    if host.is_empty() {
+      return Err(format!("broker 地址无效: {}", self.broker));
+    }
+    Ok((host, port, tls))
+  }
+
+  fn options(&self, target: &SinkTarget) -> Result<MqttOptions, String> {
+    let (host, port, tls) = self.address()?;
+    let client_id = self
+      .client_id
+      .clone()
+      .unwrap_or_else(|| format!("perfx-{}-{}", target.session_id, target.start_ms));
+    let mut options = MqttOptions::new(client_id, host, port);
+    options.set_keep_alive(Duration::from_secs(30));
+    if tls {
+      options.set_transport(Transport::tls_with_default_config());
+    }
+    if let Some(username) = &self.username {
+      options.set_credentials(username, self.password.as_deref().unwrap_or_default());
+    }
+    Ok(options)
+  }
+
+  fn qos(&self) -> QoS {
+    match self.qos {
+      0 => QoS::AtMostOnce,
+      1 => QoS::AtLeastOnce,
+      _ => QoS::ExactlyOnce,
+    }
+  }
+}
+
+/// 主题层级中不能出现 `/`、`+`、`#`
+fn topic_level(value: &str) -> String {
+  value
+    .chars()
+    .map(|c| if matches!(c, '/' | '+' | '#') { '_' } else { c })
+    .collect()
+}
+
+/// 只有告警与异常事件进入告警主题，抓取记录等不发
+fn is_alert(kind: EventKind) -> bool {
+  matches!(
+    kind,
+    EventKind::Alert
+      | EventKind::Anr
+      | EventKind::Crash
+      | EventKind::NativeCrash
+      | EventKind::Disconnected
+  )
+}
+
+/// 转为待发布的 (主题, 负载, 是否保留)
+fn to_publishes(
+  config: &MqttConfig,
+  target: &SinkTarget,
+  message: &SinkMessage,
+) -> Vec<(String, String, bool)> {
+  let prefix = config.topic_prefix.trim_end_matches('/');
+  let device = topic_level(&target.device_id);
+  match message {
+    SinkMessage::Sample(sample) => sample
+      .values
+      .iter()
+      .filter(|(_, v)| v.is_finite())
+      .map(|(key, value)| {
+        let payload = json!({
+          "session_id": target.session_id,
+          "package": target.package,
+          "t_ms": sample.t_ms,
+          "timestamp_ms": target.start_ms + sample.t_ms as i64,
+          "value": value,
+        });
+        (
+          format!("{prefix}/{device}/{}", topic_level(key)),
+          payload.to_string(),
+          config.retain,
+        )
+      })
+      .collect(),
+    SinkMessage::Event(event) if is_alert(event.kind) => {
+      let payload = json!({
+        "session_id": target.session_id,
+        "device_id": target.device_id,
+        "package": target.package,
+        "t_ms": event.t_ms,
+        "timestamp_ms": target.start_ms + event.t_ms as i64,
+        "kind": event.kind.as_str(),
+        "message": event.message,
+      });
+      vec![(format!("{prefix}/alerts"), payload.to_string(), false)]
+    }
+    _ => Vec::new(),
+  }
+}
+
+pub(crate) fn run(config: MqttConfig, target: &SinkTarget, rx: Receiver<SinkMessage>) {
+  let options = match config.options(target) {
+    Ok(options) => options,
+    Err(e) => {
+      warn!("MQTT 配置无效: {}", e);
+      return;
+    }
+  };
+  let (client, mut connection) = Client::new(options, QUEUE_CAPACITY);
+  let closing = Arc::new(AtomicBool::new(false));
+  // 连接由事件循环驱动并自动重连，必须持续迭代
+  let driver = {
+    let closing = closing.clone();
+    thread::spawn(move || {
+      let mut connected = true;
+      for notification in connection.iter() {
+        match notification {
+          Ok(_) => connected = true,
+          Err(_) if closing.load(Ordering::Relaxed) => break,
+          Err(e) => {
+            if connected {
+              warn!("MQTT 连接中断: {}", e);
+            }
+            connected = false;
+            // 结束时会被唤醒，不必等满重连间隔
+            thread::park_timeout(RECONNECT_GAP);
+          }
+        }
+      }
+    })
+  };
+
+  let qos = config.qos();
+  let (mut sent, mut dropped) = (0usize, 0usize);
+  for message in rx {
+    for (topic, payload, retain) in to_publishes(&config, target, &message) {
+      match client.try_publish(topic, qos, retain, payload) {
+        Ok(()) => sent += 1,
+        Err(_) => dropped += 1,
+      }
+    }
+  }
+  closing.store(true, Ordering::Relaxed);
+  driver.thread().unpark();
+  let _ = client.disconnect();
+  drop(client);
+  let _ = driver.join();
+  info!(
+    "会话 {} 发布 MQTT 结束，发布 {} 条，丢弃 {} 条",
+    target.session_id, sent, dropped
+  );
+}
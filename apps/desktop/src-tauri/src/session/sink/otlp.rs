@@ -0,0 +1,166 @@
+//! 以 OTLP/HTTP（JSON 编码）导出 gauge，可直接发往 OpenTelemetry Collector
+
+use crate::session::{
+  metric_label,
+  sink::{
+    batch::{agent, default_batch_size, default_max_retries, post_with_retry, run_batched},
+    SinkMessage, SinkTarget,
+  },
+  Sample,
+};
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, sync::mpsc::Receiver, time::Duration};
+
+fn default_prefix() -> String {
+  "perfx.".into()
+}
+
+fn default_flush_interval_ms() -> u64 {
+  5000
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OtlpConfig {
+  /// Collector 的 OTLP/HTTP 地址，如 `http://collector:4318`，未带路径时补 `/v1/metrics`
+  pub endpoint: String,
+  /// 额外请求头，如鉴权用的 `Authorization`
+  #[serde(default)]
+  pub headers: BTreeMap<String, String>,
+  /// 附加到 resource 上的属性，如 `deployment.environment`
+  #[serde(default)]
+  pub resource_attributes: BTreeMap<String, String>,
+  /// 指标名前缀，`fps` 导出为 `perfx.fps`
+  #[serde(default = "default_prefix")]
+  pub prefix: String,
+  #[serde(default = "default_batch_size")]
+  pub batch_size: usize,
+  #[serde(default = "default_flush_interval_ms")]
+  pub flush_interval_ms: u64,
+  #[serde(default = "default_max_retries")]
+  pub max_retries: u32,
+}
+
+impl OtlpConfig {
+  pub(crate) fn validate(&self) -> Result<(), String> {
+    if !self.endpoint.starts_with("http://") && !self.endpoint.starts_with("https://") {
+      return Err(format!(
+        "地址须以 http:// 或 https:// 开头: {}",
+        self.endpoint
+      ));
+    }
+    Ok(())
+  }
+
+  fn url(&self) -> String {
+    let endpoint = self.endpoint.trim_end_matches('/');
+    if endpoint.ends_with("/v1/metrics") {
+      endpoint.to_string()
+    } else {
+      format!("{endpoint}/v1/metrics")
+    }
+  }
+
+  fn request(&self, agent: &ureq::Agent, url: &str) -> ureq::Request {
+    self.headers.iter().fold(
+      agent.post(url).set("Content-Type", "application/json"),
+      |request, (key, value)| request.set(key, value),
+    )
+  }
+}
+
+/// 展示单位转为 OTLP 约定的 UCUM 单位
+fn ucum_unit(unit: &str) -> &str {
+  match unit {
+    "" => "1",
+    "MB" => "MBy",
+    "KB/s" => "KiBy/s",
+    "°C" => "Cel",
+    unit => unit,
+  }
+}
+
+fn string_attribute(key: &str, value: &str) -> Value {
+  json!({ "key": key, "value": { "stringValue": value } })
+}
+
+fn point_attributes(target: &SinkTarget) -> Value {
+  json!([
+    string_attribute("device.id", &target.device_id),
+    string_attribute("app.package", &target.package),
+    { "key": "perfx.session_id", "value": { "intValue": target.session_id.to_string() } },
+  ])
+}
+
+/// 一批采样组装为一个 ExportMetricsServiceRequest，同名指标的数据点归到一起
+fn export_request(config: &OtlpConfig, target: &SinkTarget, samples: &[Sample]) -> Value {
+  let attributes = point_attributes(target);
+  let mut points: BTreeMap<&str, Vec<Value>> = BTreeMap::new();
+  for sample in samples {
+    let nanos = (target.start_ms + sample.t_ms as i64) as i128 * 1_000_000;
+    for (key, value) in sample.values.iter().filter(|(_, v)| v.is_finite()) {
+      points.entry(key).or_default().push(json!({
+        "attributes": attributes,
+        "timeUnixNano": nanos.to_string(),
+        "asDouble": value,
+      }));
+    }
+  }
+  let metrics: Vec<Value> = points
+    .into_iter()
+    .map(|(key, data_points)| {
+      let (label, unit) = metric_label(key);
+      json!({
+        "name": format!("{}{}", config.prefix, key),
+        "description": label,
+        "unit": ucum_unit(&unit),
+        "gauge": { "dataPoints": data_points },
+      })
+    })
+    .collect();
+  let mut resource = vec![
+    string_attribute("service.name", "perfx"),
+    string_attribute("device.id", &target.device_id),
+    string_attribute("app.package", &target.package),
+  ];
+  resource.extend(
+    config
+      .resource_attributes
+      .iter()
+      .map(|(key, value)| string_attribute(key, value)),
+  );
+  json!({
+    "resourceMetrics": [{
+      "resource": { "attributes": resource },
+      "scopeMetrics": [{
+        "scope": { "name": "perfx", "version": env!("CARGO_PKG_VERSION") },
+        "metrics": metrics,
+      }],
+    }],
+  })
+}
+
+pub(crate) fn run(config: OtlpConfig, target: &SinkTarget, rx: Receiver<SinkMessage>) {
+  let agent = agent();
+  let url = config.url();
+  let (written, dropped) = run_batched(
+    "OTLP",
+    rx,
+    config.batch_size,
+    Duration::from_millis(config.flush_interval_ms),
+    |message| match message {
+      SinkMessage::Sample(sample) => Some(sample),
+      // 标记不是指标，OTLP 中不导出
+      _ => None,
+    },
+    |samples: &[Sample]| {
+      let body = export_request(&config, target, samples).to_string();
+      post_with_retry(config.max_retries, || config.request(&agent, &url), &body)
+    },
+  );
+  info!(
+    "会话 {} 导出 OTLP 结束，写入 {} 个采样，丢弃 {} 个",
+    target.session_id, written, dropped
+  );
+}
@@ -0,0 +1,108 @@
+use crate::session::sink::{
+  influx::{default_measurement, to_line},
+  to_json, SinkMessage, SinkTarget,
+};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+  net::TcpStream,
+  sync::mpsc::Receiver,
+  time::{Duration, Instant},
+};
+use tokio_tungstenite::tungstenite::{
+  client::IntoClientRequest, http::HeaderValue, stream::MaybeTlsStream, Message, WebSocket,
+};
+
+// 断线后至少间隔这么久再重连，期间的帧直接丢弃
+const RECONNECT_GAP: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameFormat {
+  /// 每帧一个 JSON 对象
+  #[default]
+  Json,
+  /// InfluxDB 行协议，Grafana Live 的 `/api/live/push/<stream>` 使用该格式
+  LineProtocol,
+}
+
+/// 把每个采样实时转发到 WebSocket 端点，不缓存、不补发
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebSocketConfig {
+  /// `ws://` 或 `wss://` 地址
+  pub url: String,
+  /// 以 `Authorization: Bearer` 发送
+  #[serde(default)]
+  pub token: Option<String>,
+  #[serde(default)]
+  pub format: FrameFormat,
+  /// 行协议的度量名
+  #[serde(default = "default_measurement")]
+  pub measurement: String,
+}
+
+impl WebSocketConfig {
+  pub(crate) fn validate(&self) -> Result<(), String> {
+    if !self.url.starts_with("ws://") && !self.url.starts_with("wss://") {
+      return Err(format!("地址须以 ws:// 或 wss:// 开头: {}", self.url));
+    }
+    Ok(())
+  }
+
+  fn connect(&self) -> Result<WebSocket<MaybeTlsStream<TcpStream>>, String> {
+    let mut request = self
+      .url
+      .as_str()
+      .into_client_request()
+      .map_err(|e| e.to_string())?;
+    if let Some(token) = &self.token {
+      let auth = HeaderValue::from_str(&format!("Bearer {token}")).map_err(|e| e.to_string())?;
+      request.headers_mut().insert("Authorization", auth);
+    }
+    let (socket, _) =
+      tokio_tungstenite::tungstenite::connect(request).map_err(|e| e.to_string())?;
+    Ok(socket)
+  }
+}
+
+pub(crate) fn run(config: WebSocketConfig, target: &SinkTarget, rx: Receiver<SinkMessage>) {
+  let mut socket = None;
+  let mut last_attempt: Option<Instant> = None;
+  let (mut sent, mut dropped) = (0usize, 0usize);
+  for message in rx {
+    let text = match config.format {
+      FrameFormat::Json => to_json(target, &message),
+      FrameFormat::LineProtocol => to_line(&config.measurement, target, &message),
+    };
+    let Some(text) = text else {
+      continue;
+    };
+    if socket.is_none() && last_attempt.map_or(true, |t| t.elapsed() >= RECONNECT_GAP) {
+      last_attempt = Some(Instant::now());
+      match config.connect() {
+        Ok(s) => socket = Some(s),
+        Err(e) => warn!("连接 WebSocket 推送端点失败: {}", e),
+      }
+    }
+    let Some(ws) = socket.as_mut() else {
+      dropped += 1;
+      continue;
+    };
+    match ws.send(Message::text(text)) {
+      Ok(()) => sent += 1,
+      Err(e) => {
+        warn!("WebSocket 推送中断: {}", e);
+        dropped += 1;
+        socket = None;
+      }
+    }
+  }
+  if let Some(mut ws) = socket {
+    let _ = ws.close(None);
+    let _ = ws.flush();
+  }
+  info!(
+    "会话 {} 推送 WebSocket 结束，发送 {} 帧，丢弃 {} 帧",
+    target.session_id, sent, dropped
+  );
+}
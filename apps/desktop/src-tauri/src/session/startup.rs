@@ -0,0 +1,360 @@
+use crate::{
+  adb::{
+    app::{drop_caches, force_stop, launcher_activity, press_home, start_activity_wait},
+    app_version, device_snapshot,
+    game::game_mode,
+  },
+  session::{
+    cpufreq::active_cpu_lock,
+    display::active_display,
+    error::Result,
+    store::{insert_session_in, open},
+    unix_now, Marker, Sample, Session, SessionData, SessionMeta,
+  },
+};
+use log::{info, warn};
+use perfx_core::schema::SCHEMA_VERSION;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::{
+  thread,
+  time::{Duration, Instant},
+};
+
+/// 启动测试会话的指标名，采样值为每次启动的 TotalTime
+pub const STARTUP_METRIC: &str = "startup";
+// 结束进程 / 回到桌面后等待系统稳定的时间
+const SETTLE_BEFORE_LAUNCH: Duration = Duration::from_millis(1_000);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupMode {
+  /// 每次启动前结束进程
+  #[default]
+  Cold,
+  /// 进程保留在后台，从桌面重新拉起
+  Warm,
+}
+
+impl StartupMode {
+  fn as_str(self) -> &'static str {
+    match self {
+      Self::Cold => "cold",
+      Self::Warm => "warm",
+    }
+  }
+
+  fn label(self) -> &'static str {
+    match self {
+      Self::Cold => "冷启动",
+      Self::Warm => "温启动",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StartupBenchConfig {
+  pub device_id: String,
+  pub package: String,
+  #[serde(default)]
+  pub name: Option<String>,
+  #[serde(default)]
+  pub mode: StartupMode,
+  #[serde(default = "default_iterations")]
+  pub iterations: u32,
+  /// 启动完成后停留的时长，之后结束本轮
+  #[serde(default = "default_hold_ms")]
+  pub hold_ms: u64,
+  /// 冷启动前尝试清空页缓存（需要 root）
+  #[serde(default)]
+  pub drop_caches: bool,
+  /// 应用所在的 Android 用户，为空时使用当前用户
+  #[serde(default)]
+  pub user: Option<u32>,
+}
+
+fn default_iterations() -> u32 {
+  5
+}
+
+fn default_hold_ms() -> u64 {
+  3_000
+}
+
+/// 单次启动的结果，失败时 error 有值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartupIteration {
+  pub index: u32,
+  pub t_ms: u64,
+  pub launch_state: Option<String>,
+  pub total_time_ms: Option<u64>,
+  pub wait_time_ms: Option<u64>,
+  #[serde(default)]
+  pub caches_dropped: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+}
+
+/// TotalTime 的统计，单位毫秒
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StartupStats {
+  pub count: usize,
+  pub failed: usize,
+  pub min: Option<f64>,
+  pub max: Option<f64>,
+  pub mean: Option<f64>,
+  pub median: Option<f64>,
+  pub p90: Option<f64>,
+  pub std_dev: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartupRun {
+  pub session_id: i64,
+  pub mode: StartupMode,
+  pub iterations: Vec<StartupIteration>,
+  pub stats: StartupStats,
+}
+
+/// 重复启动目标应用并保存为启动测试会话
+pub fn run_startup_benchmark(config: StartupBenchConfig) -> Result<StartupRun> {
+  let device_id = config.device_id.as_str();
+  let package = config.package.as_str();
+  let component = launcher_activity(device_id, package, config.user)?;
+  let device = device_snapshot(device_id).ok();
+  let version = app_version(device_id, package).ok();
+  let start_time = unix_now();
+  let started = Instant::now();
+  info!(
+    "开始启动测试: {} / {} {} ×{}",
+    device_id,
+    package,
+    config.mode.label(),
+    config.iterations
+  );
+
+  // 温启动需要进程已在后台，先拉起一次且不计入结果
+  if config.mode == StartupMode::Warm {
+    force_stop(device_id, package, config.user)?;
+    start_activity_wait(device_id, &component, config.user)?;
+    thread::sleep(Duration::from_millis(config.hold_ms));
+  }
+
+  let mut iterations = Vec::new();
+  for index in 1..=config.iterations.max(1) {
+    let mut caches_dropped = false;
+    let prepared = match config.mode {
+      StartupMode::Cold => force_stop(device_id, package, config.user).map(|_| {
+        if config.drop_caches {
+          caches_dropped = drop_caches(device_id);
+        }
+      }),
+      StartupMode::Warm => press_home(device_id),
+    };
+    // 本轮准备失败只记为失败，不中断后续轮次
+    if let Err(e) = prepared {
+      warn!("第 {} 次启动前准备失败: {}", index, e);
+      iterations.push(StartupIteration {
+        index,
+        t_ms: started.elapsed().as_millis() as u64,
+        launch_state: None,
+        total_time_ms: None,
+        wait_time_ms: None,
+        caches_dropped,
+        error: Some(e.to_string()),
+      });
+      continue;
+    }
+    thread::sleep(SETTLE_BEFORE_LAUNCH);
+
+    let t_ms = started.elapsed().as_millis() as u64;
+    let iteration = match start_activity_wait(device_id, &component, config.user) {
+      Ok(timing) => StartupIteration {
+        index,
+        t_ms,
+        launch_state: timing.launch_state,
+        total_time_ms: timing.total_time_ms.or(timing.wait_time_ms),
+        wait_time_ms: timing.wait_time_ms,
+        caches_dropped,
+        error: None,
+      },
+      Err(e) => {
+        warn!("第 {} 次启动失败: {}", index, e);
+        StartupIteration {
+          index,
+          t_ms,
+          launch_state: None,
+          total_time_ms: None,
+          wait_time_ms: None,
+          caches_dropped,
+          error: Some(e.to_string()),
+        }
+      }
+    };
+    iterations.push(iteration);
+    thread::sleep(Duration::from_millis(config.hold_ms));
+  }
+  if let Err(e) = force_stop(device_id, package, config.user) {
+    warn!("结束应用失败: {}", e);
+  }
+
+  let end_time = unix_now();
+  let data = SessionData {
+    session: Session {
+      id: 0,
+      name: config
+        .name
+        .clone()
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or_else(|| format!("{} {} ×{}", package, config.mode.label(), iterations.len())),
+      device_id: config.device_id.clone(),
+      device_model: device.as_ref().and_then(|d| d.model.clone()),
+      app_package: config.package.clone(),
+      app_label: None,
+      metrics: vec![STARTUP_METRIC.to_string()],
+      start_time,
+      end_time,
+      duration: end_time - start_time,
+      created_at: start_time,
+      schema_version: SCHEMA_VERSION,
+    },
+    samples: iterations
+      .iter()
+      .filter_map(|it| {
+        it.total_time_ms.map(|ms| Sample {
+          t_ms: it.t_ms,
+          time: None,
+          values: [(STARTUP_METRIC.to_string(), ms as f64)]
+            .into_iter()
+            .collect(),
+        })
+      })
+      .collect(),
+    markers: iterations
+      .iter()
+      .map(|it| Marker {
+        id: 0,
+        t_ms: it.t_ms,
+        name: format!("#{}", it.index),
+      })
+      .collect(),
+    meta: SessionMeta {
+      scenario: format!("startup-{}", config.mode.as_str()),
+      build_fingerprint: device.as_ref().and_then(|d| d.build_fingerprint.clone()),
+      device,
+      app_version_name: version.as_ref().and_then(|v| v.version_name.clone()),
+      app_version_code: version.as_ref().and_then(|v| v.version_code),
+      cpu_lock: active_cpu_lock(device_id),
+      display: active_display(device_id),
+      game_mode: game_mode(device_id, package).ok(),
+      ..Default::default()
+    },
+  };
+
+  let mut conn = open()?;
+  let tx = conn.transaction()?;
+  let session_id = insert_session_in(&tx, &data)?;
+  for iteration in &iterations {
+    insert_iteration(&tx, session_id, config.mode, iteration)?;
+  }
+  tx.commit()?;
+
+  Ok(StartupRun {
+    session_id,
+    mode: config.mode,
+    stats: startup_stats(&iterations),
+    iterations,
+  })
+}
+
+fn insert_iteration(
+  conn: &Connection,
+  session_id: i64,
+  mode: StartupMode,
+  iteration: &StartupIteration,
+) -> Result<()> {
+  conn.execute(
+    "INSERT INTO startup_iterations (
+      session_id, iteration, mode, t_ms, launch_state, total_time_ms, wait_time_ms,
+      caches_dropped, error
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+    params![
+      session_id,
+      iteration.index,
+      mode.as_str(),
+      iteration.t_ms as i64,
+      iteration.launch_state,
+      iteration.total_time_ms.map(|v| v as i64),
+      iteration.wait_time_ms.map(|v| v as i64),
+      iteration.caches_dropped,
+      iteration.error,
+    ],
+  )?;
+  Ok(())
+}
+
+/// 读取启动测试会话的逐次结果，普通录制会话返回 None
+pub fn load_startup_run(session_id: i64) -> Result<Option<StartupRun>> {
+  let conn = open()?;
+  let mut stmt = conn.prepare(
+    "SELECT iteration, mode, t_ms, launch_state, total_time_ms, wait_time_ms, caches_dropped, error
+     FROM startup_iterations WHERE session_id = ?1 ORDER BY iteration",
+  )?;
+  let mut mode = StartupMode::Cold;
+  let mut iterations = Vec::new();
+  let mut rows = stmt.query(params![session_id])?;
+  while let Some(row) = rows.next()? {
+    if row.get::<_, String>(1)? == StartupMode::Warm.as_str() {
+      mode = StartupMode::Warm;
+    }
+    iterations.push(StartupIteration {
+      index: row.get(0)?,
+      t_ms: row.get::<_, i64>(2)?.max(0) as u64,
+      launch_state: row.get(3)?,
+      total_time_ms: row.get::<_, Option<i64>>(4)?.map(|v| v.max(0) as u64),
+      wait_time_ms: row.get::<_, Option<i64>>(5)?.map(|v| v.max(0) as u64),
+      caches_dropped: row.get(6)?,
+      error: row.get(7)?,
+    });
+  }
+  if iterations.is_empty() {
+    return Ok(None);
+  }
+  Ok(Some(StartupRun {
+    session_id,
+    mode,
+    stats: startup_stats(&iterations),
+    iterations,
+  }))
+}
+
+pub fn startup_stats(iterations: &[StartupIteration]) -> StartupStats {
+  let mut values: Vec<f64> = iterations
+    .iter()
+    .filter_map(|it| it.total_time_ms)
+    .map(|v| v as f64)
+    .collect();
+  let failed = iterations.len() - values.len();
+  if values.is_empty() {
+    return StartupStats {
+      failed,
+      ..Default::default()
+    };
+  }
+  values.sort_by(|a, b| a.total_cmp(b));
+  let count = values.len();
+  let mean = values.iter().sum::<f64>() / count as f64;
+  let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / count as f64;
+  // 最近秩法，次数较少时不做插值
+  let rank = |p: f64| values[((p / 100.0 * count as f64).ceil() as usize).clamp(1, count) - 1];
+  StartupStats {
+    count,
+    failed,
+    min: values.first().copied(),
+    max: values.last().copied(),
+    mean: Some(mean),
+    median: Some(rank(50.0)),
+    p90: Some(rank(90.0)),
+    std_dev: Some(variance.sqrt()),
+  }
+}
@@ -0,0 +1,477 @@
+use crate::session::{
+  error::{Result, SessionError},
+  meta::{query_meta, write_meta},
+  Marker, Sample, Session, SessionData,
+};
+use once_cell::sync::OnceCell;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+use std::{collections::BTreeMap, path::PathBuf};
+
+// 与前端 tauri-plugin-sql 共用同一个 reports.db（位于 app_config_dir）
+static DB_PATH: OnceCell<PathBuf> = OnceCell::new();
+
+pub fn set_db_path(path: PathBuf) {
+  let _ = DB_PATH.set(path);
+}
+
+pub fn db_path() -> Option<&'static PathBuf> {
+  DB_PATH.get()
+}
+
+pub fn open() -> Result<Connection> {
+  let path = DB_PATH.get().ok_or(SessionError::NotInitialized)?;
+  let conn = Connection::open(path)?;
+  conn.busy_timeout(std::time::Duration::from_secs(5))?;
+  ensure_schema(&conn)?;
+  Ok(conn)
+}
+
+// 按顺序执行的建表脚本，已执行到的位置记录在 PRAGMA user_version 中
+const MIGRATIONS: &[&str] = &[
+  // reports 表结构需与 src/hooks/queries/useReports.ts 保持一致
+  "CREATE TABLE IF NOT EXISTS reports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    device_id TEXT NOT NULL,
+    device_model TEXT,
+    app_package TEXT NOT NULL,
+    app_label TEXT,
+    metrics TEXT NOT NULL,
+    chart_data TEXT NOT NULL,
+    start_time INTEGER NOT NULL,
+    end_time INTEGER NOT NULL,
+    duration INTEGER NOT NULL,
+    created_at INTEGER DEFAULT (strftime('%s', 'now'))
+  );
+  CREATE TABLE IF NOT EXISTS session_markers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id INTEGER NOT NULL,
+    t_ms INTEGER NOT NULL,
+    name TEXT NOT NULL
+  );
+  CREATE INDEX IF NOT EXISTS idx_session_markers_session ON session_markers(session_id);",
+  "CREATE TABLE IF NOT EXISTS session_meta (
+    session_id INTEGER PRIMARY KEY,
+    scenario TEXT NOT NULL DEFAULT ''
+  );
+  CREATE TABLE IF NOT EXISTS baselines (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    app_package TEXT NOT NULL,
+    device_model TEXT NOT NULL DEFAULT '',
+    scenario TEXT NOT NULL DEFAULT '',
+    session_id INTEGER NOT NULL,
+    created_at INTEGER DEFAULT (strftime('%s', 'now')),
+    UNIQUE(app_package, device_model, scenario)
+  );",
+  "CREATE TABLE IF NOT EXISTS app_kv (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+  );",
+  "ALTER TABLE session_meta ADD COLUMN device_info TEXT;
+  ALTER TABLE session_meta ADD COLUMN app_version_name TEXT;
+  ALTER TABLE session_meta ADD COLUMN app_version_code INTEGER;
+  ALTER TABLE session_meta ADD COLUMN build_fingerprint TEXT;
+  ALTER TABLE session_meta ADD COLUMN tester TEXT;
+  ALTER TABLE session_meta ADD COLUMN notes TEXT;",
+  "CREATE TABLE IF NOT EXISTS session_raw (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id INTEGER NOT NULL,
+    t_ms INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    data BLOB NOT NULL
+  );
+  CREATE INDEX IF NOT EXISTS idx_session_raw_session ON session_raw(session_id, t_ms);",
+  "CREATE TABLE IF NOT EXISTS session_screenshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id INTEGER NOT NULL,
+    t_ms INTEGER NOT NULL,
+    trigger TEXT NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    data BLOB NOT NULL
+  );
+  CREATE INDEX IF NOT EXISTS idx_session_screenshots_session
+    ON session_screenshots(session_id, t_ms);",
+  "CREATE TABLE IF NOT EXISTS session_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id INTEGER NOT NULL,
+    t_ms INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    message TEXT NOT NULL DEFAULT ''
+  );
+  CREATE INDEX IF NOT EXISTS idx_session_events_session ON session_events(session_id, t_ms);
+  CREATE TABLE IF NOT EXISTS session_attachments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id INTEGER NOT NULL,
+    event_id INTEGER,
+    kind TEXT NOT NULL,
+    name TEXT NOT NULL,
+    size INTEGER NOT NULL DEFAULT 0,
+    data BLOB NOT NULL
+  );
+  CREATE INDEX IF NOT EXISTS idx_session_attachments_session
+    ON session_attachments(session_id);",
+  "CREATE TABLE IF NOT EXISTS alert_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL DEFAULT '',
+    metric TEXT NOT NULL,
+    op TEXT NOT NULL,
+    threshold REAL NOT NULL,
+    duration_ms INTEGER NOT NULL DEFAULT 0,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_at INTEGER DEFAULT (strftime('%s', 'now'))
+  );",
+  // 卡顿事件的可能原因，JSON 数组
+  "ALTER TABLE session_events ADD COLUMN causes TEXT;",
+  "CREATE TABLE IF NOT EXISTS startup_iterations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id INTEGER NOT NULL,
+    iteration INTEGER NOT NULL,
+    mode TEXT NOT NULL,
+    t_ms INTEGER NOT NULL,
+    launch_state TEXT,
+    total_time_ms INTEGER,
+    wait_time_ms INTEGER,
+    caches_dropped INTEGER NOT NULL DEFAULT 0,
+    error TEXT
+  );
+  CREATE INDEX IF NOT EXISTS idx_startup_iterations_session
+    ON startup_iterations(session_id, iteration);",
+  // 能耗估算用的 CPU 频率驻留与屏幕状态快照，JSON
+  "CREATE TABLE IF NOT EXISTS energy_probes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id INTEGER NOT NULL,
+    t_ms INTEGER NOT NULL,
+    data TEXT NOT NULL
+  );
+  CREATE INDEX IF NOT EXISTS idx_energy_probes_session ON energy_probes(session_id, t_ms);",
+  // 录制期间保存的 logcat，按时间分块 zstd 压缩
+  "CREATE TABLE IF NOT EXISTS session_logcat (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id INTEGER NOT NULL,
+    start_ms INTEGER NOT NULL,
+    end_ms INTEGER NOT NULL,
+    line_count INTEGER NOT NULL,
+    data BLOB NOT NULL
+  );
+  CREATE INDEX IF NOT EXISTS idx_session_logcat_session ON session_logcat(session_id, start_ms);",
+  "ALTER TABLE session_meta ADD COLUMN cpu_lock TEXT;",
+  "ALTER TABLE session_meta ADD COLUMN display_config TEXT;",
+  "ALTER TABLE session_meta ADD COLUMN game_mode TEXT;",
+  // 前端写入的行不带该列，按旧版本处理
+  "ALTER TABLE reports ADD COLUMN schema_version INTEGER NOT NULL DEFAULT 1;",
+  "ALTER TABLE session_events ADD COLUMN rule_id INTEGER;",
+];
+
+// 以 session_id 关联到 reports 的附属表，删除会话时一并清理
+pub(crate) const SESSION_CHILD_TABLES: &[&str] = &[
+  "session_markers",
+  "session_meta",
+  "session_raw",
+  "session_screenshots",
+  "session_events",
+  "session_attachments",
+  "startup_iterations",
+  "energy_probes",
+  "session_logcat",
+];
+
+// 估算每个会话占用空间的查询，均返回 (session_id, bytes)
+pub(crate) const SESSION_SIZE_QUERIES: &[&str] = &[
+  "SELECT id, LENGTH(chart_data) + LENGTH(metrics) + LENGTH(name) FROM reports",
+  "SELECT session_id, SUM(LENGTH(name) + 16) FROM session_markers GROUP BY session_id",
+  "SELECT session_id, COALESCE(LENGTH(device_info), 0) + COALESCE(LENGTH(notes), 0) FROM session_meta",
+  "SELECT session_id, SUM(LENGTH(data)) FROM session_raw GROUP BY session_id",
+  "SELECT session_id, SUM(LENGTH(data)) FROM session_screenshots GROUP BY session_id",
+  "SELECT session_id, SUM(LENGTH(message) + 24) FROM session_events GROUP BY session_id",
+  "SELECT session_id, SUM(LENGTH(data)) FROM session_attachments GROUP BY session_id",
+  "SELECT session_id, COUNT(*) * 64 FROM startup_iterations GROUP BY session_id",
+  "SELECT session_id, SUM(LENGTH(data)) FROM energy_probes GROUP BY session_id",
+  "SELECT session_id, SUM(LENGTH(data)) FROM session_logcat GROUP BY session_id",
+];
+
+fn ensure_schema(conn: &Connection) -> Result<()> {
+  let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+  for (index, sql) in MIGRATIONS.iter().enumerate().skip(version) {
+    // 迁移与版本号在同一事务内提交，中途失败不会留下半迁移的库
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(sql)?;
+    tx.pragma_update(None, "user_version", index + 1)?;
+    tx.commit()?;
+  }
+  Ok(())
+}
+
+const SESSION_COLUMNS: &str =
+  "id, name, device_id, device_model, app_package, app_label, metrics, \
+  start_time, end_time, duration, created_at, schema_version";
+
+pub fn load_session(id: i64) -> Result<SessionData> {
+  let conn = open()?;
+  let row = conn
+    .query_row(
+      &format!("SELECT {SESSION_COLUMNS}, chart_data FROM reports WHERE id = ?1"),
+      params![id],
+      |row| Ok((session_from_row(row)?, row.get::<_, String>(12)?)),
+    )
+    .optional()?;
+
+  let (session, chart_data) = row.ok_or(SessionError::NotFound(id))?;
+  let samples = parse_chart_data(&chart_data, session.duration)?;
+  let markers = query_markers(&conn, id)?;
+  let meta = query_meta(&conn, id)?;
+  let mut data = SessionData {
+    session,
+    samples,
+    markers,
+    meta,
+  };
+  data.upgrade()?;
+  Ok(data)
+}
+
+fn session_from_row(row: &Row) -> rusqlite::Result<Session> {
+  let metrics: String = row.get(6)?;
+  Ok(Session {
+    id: row.get(0)?,
+    name: row.get(1)?,
+    device_id: row.get(2)?,
+    device_model: row.get(3)?,
+    app_package: row.get(4)?,
+    app_label: row.get(5)?,
+    metrics: serde_json::from_str(&metrics).unwrap_or_default(),
+    start_time: row.get(7)?,
+    end_time: row.get(8)?,
+    duration: row.get(9)?,
+    created_at: row.get::<_, Option<i64>>(10)?.unwrap_or_default(),
+    schema_version: row.get(11)?,
+  })
+}
+
+/// 解析前端保存的 chart_data（每行一个对象，`time` 为时刻标签，其余数值字段为指标）
+///
+/// 前端记录的行不带时间偏移，按会话时长均匀分布；带 `t_ms` 字段的行直接使用该值。
+pub fn parse_chart_data(raw: &str, duration_secs: i64) -> Result<Vec<Sample>> {
+  let rows: Vec<Map<String, Value>> = serde_json::from_str(raw)?;
+
+  let step_ms = if rows.len() > 1 {
+    (duration_secs.max(0) as f64 * 1000.0) / (rows.len() - 1) as f64
+  } else {
+    0.0
+  };
+
+  let samples = rows
+    .into_iter()
+    .enumerate()
+    .map(|(index, row)| {
+      let mut t_ms = (index as f64 * step_ms) as u64;
+      let mut time = None;
+      let mut values = BTreeMap::new();
+      for (key, value) in row {
+        match (key.as_str(), value) {
+          ("t_ms", Value::Number(n)) => t_ms = n.as_u64().unwrap_or(t_ms),
+          ("time", Value::String(s)) => time = Some(s),
+          (_, Value::Number(n)) => {
+            if let Some(v) = n.as_f64() {
+              values.insert(key, v);
+            }
+          }
+          _ => {}
+        }
+      }
+      Sample { t_ms, time, values }
+    })
+    .collect();
+
+  Ok(samples)
+}
+
+/// 写入一个完整会话，返回新会话 id
+pub fn insert_session(data: &SessionData) -> Result<i64> {
+  let mut conn = open()?;
+  let tx = conn.transaction()?;
+  let id = insert_session_in(&tx, data)?;
+  tx.commit()?;
+  Ok(id)
+}
+
+/// 在调用方的事务中写入会话，便于与附属数据一起提交
+pub(crate) fn insert_session_in(tx: &Connection, data: &SessionData) -> Result<i64> {
+  let session = &data.session;
+  let metrics = serde_json::to_string(&session.metrics)?;
+  let chart_data = serde_json::to_string(&to_chart_data(&data.samples))?;
+
+  tx.execute(
+    "INSERT INTO reports (
+      name, device_id, device_model, app_package, app_label,
+      metrics, chart_data, start_time, end_time, duration, schema_version
+    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+    params![
+      session.name,
+      session.device_id,
+      session.device_model,
+      session.app_package,
+      session.app_label,
+      metrics,
+      chart_data,
+      session.start_time,
+      session.end_time,
+      session.duration,
+      session.schema_version,
+    ],
+  )?;
+  let id = tx.last_insert_rowid();
+
+  for marker in &data.markers {
+    tx.execute(
+      "INSERT INTO session_markers (session_id, t_ms, name) VALUES (?1, ?2, ?3)",
+      params![id, marker.t_ms as i64, marker.name],
+    )?;
+  }
+  write_meta(tx, id, &data.meta)?;
+  Ok(id)
+}
+
+pub fn add_marker(session_id: i64, t_ms: u64, name: &str) -> Result<Marker> {
+  let conn = open()?;
+  let exists = conn
+    .query_row(
+      "SELECT 1 FROM reports WHERE id = ?1",
+      params![session_id],
+      |_| Ok(()),
+    )
+    .optional()?
+    .is_some();
+  if !exists {
+    return Err(SessionError::NotFound(session_id));
+  }
+  conn.execute(
+    "INSERT INTO session_markers (session_id, t_ms, name) VALUES (?1, ?2, ?3)",
+    params![session_id, t_ms as i64, name],
+  )?;
+  Ok(Marker {
+    id: conn.last_insert_rowid(),
+    t_ms,
+    name: name.to_string(),
+  })
+}
+
+pub fn list_markers(session_id: i64) -> Result<Vec<Marker>> {
+  let conn = open()?;
+  query_markers(&conn, session_id)
+}
+
+fn query_markers(conn: &Connection, session_id: i64) -> Result<Vec<Marker>> {
+  let mut stmt = conn
+    .prepare("SELECT id, t_ms, name FROM session_markers WHERE session_id = ?1 ORDER BY t_ms")?;
+  let markers = stmt
+    .query_map(params![session_id], |row| {
+      Ok(Marker {
+        id: row.get(0)?,
+        t_ms: row.get::<_, i64>(1)?.max(0) as u64,
+        name: row.get(2)?,
+      })
+    })?
+    .collect::<rusqlite::Result<Vec<_>>>()?;
+  Ok(markers)
+}
+
+/// 录制结束时写入全部采样并更新结束时间
+pub fn finish_session(id: i64, samples: &[Sample], end_time: i64, duration: i64) -> Result<()> {
+  let conn = open()?;
+  let chart_data = serde_json::to_string(&to_chart_data(samples))?;
+  let updated = conn.execute(
+    "UPDATE reports SET chart_data = ?1, end_time = ?2, duration = ?3 WHERE id = ?4",
+    params![chart_data, end_time, duration, id],
+  )?;
+  if updated == 0 {
+    return Err(SessionError::NotFound(id));
+  }
+  Ok(())
+}
+
+/// 转换为前端图表使用的行格式，额外保留 `t_ms` 以便无损读回
+fn to_chart_data(samples: &[Sample]) -> Vec<Map<String, Value>> {
+  samples
+    .iter()
+    .map(|sample| {
+      let mut row = Map::new();
+      let time = sample
+        .time
+        .clone()
+        .unwrap_or_else(|| elapsed_label(sample.t_ms));
+      row.insert("time".into(), Value::String(time));
+      row.insert("t_ms".into(), Value::from(sample.t_ms));
+      for (key, value) in &sample.values {
+        if let Some(n) = serde_json::Number::from_f64(*value) {
+          row.insert(key.clone(), Value::Number(n));
+        }
+      }
+      row
+    })
+    .collect()
+}
+
+pub(crate) fn elapsed_label(t_ms: u64) -> String {
+  let secs = t_ms / 1000;
+  format!(
+    "{:02}:{:02}:{:02}",
+    secs / 3600,
+    secs % 3600 / 60,
+    secs % 60
+  )
+}
+
+/// 删除会话及其全部附属数据
+pub fn delete_session(conn: &Connection, id: i64) -> Result<()> {
+  for table in SESSION_CHILD_TABLES {
+    conn.execute(
+      &format!("DELETE FROM {table} WHERE session_id = ?1"),
+      params![id],
+    )?;
+  }
+  conn.execute("DELETE FROM baselines WHERE session_id = ?1", params![id])?;
+  conn.execute("DELETE FROM reports WHERE id = ?1", params![id])?;
+  Ok(())
+}
+
+/// 读取 app_kv 中以 JSON 保存的配置项
+pub fn get_kv<T: DeserializeOwned>(key: &str) -> Result<Option<T>> {
+  let conn = open()?;
+  let raw: Option<String> = conn
+    .query_row(
+      "SELECT value FROM app_kv WHERE key = ?1",
+      params![key],
+      |row| row.get(0),
+    )
+    .optional()?;
+  Ok(raw.map(|v| serde_json::from_str(&v)).transpose()?)
+}
+
+pub fn set_kv<T: Serialize>(key: &str, value: &T) -> Result<()> {
+  let conn = open()?;
+  conn.execute(
+    "INSERT INTO app_kv (key, value) VALUES (?1, ?2)
+     ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+    params![key, serde_json::to_string(value)?],
+  )?;
+  Ok(())
+}
+
+pub fn delete_kv(key: &str) -> Result<()> {
+  let conn = open()?;
+  conn.execute("DELETE FROM app_kv WHERE key = ?1", params![key])?;
+  Ok(())
+}
+
+/// 以指定前缀开头的键，按范围比较，前缀中的 `_`、`%` 不作通配符
+pub fn kv_keys(prefix: &str) -> Result<Vec<String>> {
+  let conn = open()?;
+  let mut stmt = conn.prepare(
+    "SELECT key FROM app_kv WHERE key >= ?1 AND key < ?1 || char(0x10FFFF) ORDER BY key",
+  )?;
+  let rows = stmt.query_map([prefix], |row| row.get::<_, String>(0))?;
+  Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
@@ -0,0 +1,163 @@
+use crate::session::{
+  error::{Result, SessionError},
+  metric_keys,
+  store::elapsed_label,
+  Marker, Sample, Session, SessionData,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+  fs::{self, File},
+  io::{BufWriter, Write},
+  path::Path,
+  time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamFormat {
+  Csv,
+  Jsonl,
+}
+
+impl StreamFormat {
+  pub fn from_path(path: &Path) -> Option<Self> {
+    match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+      "csv" => Some(Self::Csv),
+      "jsonl" => Some(Self::Jsonl),
+      _ => None,
+    }
+  }
+}
+
+/// JSONL 中的一行；首行为会话信息，之后为采样与标记
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamRecord {
+  Session(Session),
+  Sample(Sample),
+  Marker(Marker),
+}
+
+/// 录制过程中将采样逐条追加到磁盘文件，并定期 fsync
+///
+/// 宿主崩溃或断电时，已写入的部分仍可通过会话导入恢复。
+pub struct SampleStream {
+  format: StreamFormat,
+  writer: BufWriter<File>,
+  sync_interval: Duration,
+  last_sync: Instant,
+}
+
+impl SampleStream {
+  pub fn create(path: &Path, session: &Session, sync_interval: Duration) -> Result<Self> {
+    let format = StreamFormat::from_path(path)
+      .ok_or_else(|| SessionError::Format(format!("不支持的实时导出格式: {}", path.display())))?;
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+      fs::create_dir_all(dir)?;
+    }
+
+    let mut stream = Self {
+      format,
+      writer: BufWriter::new(File::create(path)?),
+      sync_interval,
+      last_sync: Instant::now(),
+    };
+    match format {
+      StreamFormat::Csv => {
+        let header: Vec<&str> = ["t_ms", "time"].into_iter().chain(metric_keys()).collect();
+        writeln!(stream.writer, "{}", header.join(","))?;
+      }
+      StreamFormat::Jsonl => stream.write_record(&StreamRecord::Session(session.clone()))?,
+    }
+    stream.sync()?;
+    Ok(stream)
+  }
+
+  pub fn write_sample(&mut self, sample: &Sample) -> Result<()> {
+    match self.format {
+      StreamFormat::Csv => {
+        let time = sample
+          .time
+          .clone()
+          .unwrap_or_else(|| elapsed_label(sample.t_ms));
+        let mut fields = vec![sample.t_ms.to_string(), time];
+        fields.extend(metric_keys().map(|key| {
+          sample
+            .values
+            .get(key)
+            .map(|v| v.to_string())
+            .unwrap_or_default()
+        }));
+        writeln!(self.writer, "{}", fields.join(","))?;
+      }
+      StreamFormat::Jsonl => self.write_record(&StreamRecord::Sample(sample.clone()))?,
+    }
+    self.flush()
+  }
+
+  /// CSV 没有标记列，仅 JSONL 记录标记
+  pub fn write_marker(&mut self, marker: &Marker) -> Result<()> {
+    if self.format == StreamFormat::Jsonl {
+      self.write_record(&StreamRecord::Marker(marker.clone()))?;
+      self.flush()?;
+    }
+    Ok(())
+  }
+
+  pub fn finish(&mut self) -> Result<()> {
+    self.sync()
+  }
+
+  fn write_record(&mut self, record: &StreamRecord) -> Result<()> {
+    serde_json::to_writer(&mut self.writer, record)?;
+    self.writer.write_all(b"\n")?;
+    Ok(())
+  }
+
+  // 每条都交给操作系统，按间隔落盘，兼顾断电安全与磁盘压力
+  fn flush(&mut self) -> Result<()> {
+    self.writer.flush()?;
+    if self.last_sync.elapsed() >= self.sync_interval {
+      self.sync()?;
+    }
+    Ok(())
+  }
+
+  fn sync(&mut self) -> Result<()> {
+    self.writer.flush()?;
+    self.writer.get_ref().sync_data()?;
+    self.last_sync = Instant::now();
+    Ok(())
+  }
+}
+
+/// 读取实时导出的 JSONL，末尾因崩溃而不完整的行会被忽略
+pub fn read_jsonl(path: &Path) -> Result<SessionData> {
+  let raw = fs::read_to_string(path)?;
+  let mut session = None;
+  let mut samples = Vec::new();
+  let mut markers = Vec::new();
+  for line in raw.lines().filter(|l| !l.trim().is_empty()) {
+    match serde_json::from_str(line) {
+      Ok(StreamRecord::Session(s)) => session = Some(s),
+      Ok(StreamRecord::Sample(s)) => samples.push(s),
+      Ok(StreamRecord::Marker(m)) => markers.push(m),
+      Err(_) => continue,
+    }
+  }
+
+  let mut session =
+    session.ok_or_else(|| SessionError::Format("JSONL 文件缺少会话信息行".into()))?;
+  // 中断的录制没有写入结束时间，按最后一个采样补齐
+  let duration = samples.last().map(|s| (s.t_ms / 1000) as i64).unwrap_or(0);
+  if session.duration < duration {
+    session.duration = duration;
+    session.end_time = session.start_time + duration;
+  }
+  Ok(SessionData {
+    session,
+    samples,
+    markers,
+    meta: Default::default(),
+  })
+}
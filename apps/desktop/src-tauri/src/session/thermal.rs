@@ -0,0 +1,92 @@
+use crate::{
+  adb::thermal::ThermalSnapshot,
+  session::{events::EventKind, store::elapsed_label},
+};
+
+// 温度较录制开始上升该值，或达到绝对温度时视为过热
+const RISE_C: f64 = 5.0;
+const HOT_C: f64 = 45.0;
+// 频率上限低于硬件最高频率的该比例视为被限频
+const CAP_RATIO: f64 = 0.9;
+// 高负载下当前频率低于该比例视为降频
+const LOADED_FREQ_RATIO: f64 = 0.6;
+const LOADED_CPU_PCT: f64 = 50.0;
+// 连续满足 / 不满足的探测次数，避免状态抖动
+const BEGIN_PROBES: u32 = 2;
+const END_PROBES: u32 = 3;
+
+/// 结合温度、频率上限与负载判断温控降频的开始与结束
+#[derive(Debug, Default)]
+pub struct ThrottleDetector {
+  start_temp: Option<f64>,
+  since_ms: Option<u64>,
+  peak_temp: f64,
+  hits: u32,
+  clears: u32,
+}
+
+impl ThrottleDetector {
+  /// 输入一次探测结果与同期应用 CPU 占用，状态切换时返回待记录的事件
+  pub fn update(
+    &mut self,
+    t_ms: u64,
+    snapshot: &ThermalSnapshot,
+    cpu_pct: Option<f64>,
+  ) -> Option<(EventKind, String)> {
+    let temp = snapshot.max_temp_c?;
+    let start_temp = *self.start_temp.get_or_insert(temp);
+    let hot = temp >= start_temp + RISE_C || temp >= HOT_C;
+    let capped = snapshot.cpu_cap_ratio.is_some_and(|r| r < CAP_RATIO);
+    let loaded = cpu_pct.is_some_and(|c| c >= LOADED_CPU_PCT);
+    let dropped = loaded
+      && [snapshot.cpu_freq_ratio, snapshot.gpu_freq_ratio]
+        .iter()
+        .flatten()
+        .any(|r| *r < LOADED_FREQ_RATIO);
+    let throttled = hot && (capped || dropped);
+
+    match self.since_ms {
+      None if throttled => {
+        self.hits += 1;
+        if self.hits < BEGIN_PROBES {
+          return None;
+        }
+        self.since_ms = Some(t_ms);
+        self.peak_temp = temp;
+        self.clears = 0;
+        let mut message = format!("温控降频开始：温度 {temp:.1}°C");
+        if let Some(cap) = snapshot.cpu_cap_ratio.filter(|_| capped) {
+          message.push_str(&format!("，CPU 频率上限 {:.0}%", cap * 100.0));
+        } else if let Some(ratio) = snapshot.cpu_freq_ratio {
+          message.push_str(&format!("，CPU 频率 {:.0}%", ratio * 100.0));
+        }
+        Some((EventKind::ThrottleBegin, message))
+      }
+      None => {
+        self.hits = 0;
+        None
+      }
+      Some(since) => {
+        self.peak_temp = self.peak_temp.max(temp);
+        if throttled {
+          self.clears = 0;
+          return None;
+        }
+        self.clears += 1;
+        if self.clears < END_PROBES {
+          return None;
+        }
+        self.since_ms = None;
+        self.hits = 0;
+        Some((
+          EventKind::ThrottleEnd,
+          format!(
+            "温控降频结束：持续 {}，峰值温度 {:.1}°C",
+            elapsed_label(t_ms.saturating_sub(since)),
+            self.peak_temp
+          ),
+        ))
+      }
+    }
+  }
+}
@@ -0,0 +1,270 @@
+//! 后端统一保存的设置：写入应用配置目录下的 settings.json，命令行读取同一文件，
+//! 两端的 adb 路径、默认指标与采样间隔保持一致
+
+use crate::{
+  adb::{
+    provider::{register_property_metrics, register_shell_metrics},
+    script::register_metric_scripts,
+    set_adb_path, DeviceInfo, MetricKey,
+  },
+  i18n::Localized,
+  session::{
+    alerts::{delete_rule, list_rules, save_rule, AlertRule},
+    error::SessionError,
+    recorder::RecordingConfig,
+    sink::SinkConfig,
+  },
+};
+use log::warn;
+use perfx_core::{
+  settings::{self as store, Settings, SettingsError},
+  units::Units,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+  path::PathBuf,
+  sync::Mutex,
+  time::{Duration, SystemTime},
+};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_fs::FsExt;
+use thiserror::Error;
+
+pub const SETTINGS_CHANGED_EVENT: &str = "settings://changed";
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 最近一次由本进程写入或读取到的文件修改时间，用于区分外部修改
+static LAST_SEEN: Mutex<Option<SystemTime>> = Mutex::new(None);
+
+#[derive(Debug, Error)]
+pub enum AppSettingsError {
+  #[error(transparent)]
+  Session(#[from] SessionError),
+  #[error(transparent)]
+  Store(#[from] SettingsError),
+}
+
+pub type Result<T> = std::result::Result<T, AppSettingsError>;
+
+impl Localized for AppSettingsError {
+  fn code(&self) -> &'static str {
+    match self {
+      Self::Session(e) => e.code(),
+      Self::Store(e) => e.code(),
+    }
+  }
+
+  fn params(&self) -> Value {
+    match self {
+      Self::Session(e) => e.params(),
+      Self::Store(e) => e.params(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppSettings {
+  #[serde(flatten)]
+  pub common: Settings,
+  /// 录制未单独配置推送目标时使用
+  #[serde(default)]
+  pub default_sinks: Vec<SinkConfig>,
+  /// 以数据库中的规则为准，文件中的副本便于随配置一起迁移；
+  /// 保存时不提供则保持数据库中的规则不变
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub alert_rules: Option<Vec<AlertRule>>,
+  /// 界面上次选择的设备与应用，启动时恢复
+  #[serde(default)]
+  pub last_device: Option<DeviceInfo>,
+  #[serde(default)]
+  pub last_app: Option<String>,
+}
+
+/// 界面当前的选择，变化时整体保存
+#[derive(Debug, Clone, Deserialize)]
+pub struct Selection {
+  pub device: Option<DeviceInfo>,
+  pub app: Option<String>,
+  pub metrics: Vec<MetricKey>,
+}
+
+/// 启动时调用：确定配置文件位置、应用 adb 路径并开始监听外部修改
+pub fn init(app: &AppHandle, config_dir: PathBuf) {
+  store::set_settings_dir(config_dir);
+  match store::load::<AppSettings>() {
+    Ok(settings) => {
+      if settings.common.adb_path.is_some() {
+        set_adb_path(settings.common.adb_path.clone());
+      }
+      register_custom_metrics(&settings.common);
+      allow_export_dir(app, &settings.common);
+    }
+    Err(e) => warn!("读取设置失败: {}", e),
+  }
+  mark_seen();
+  let app = app.clone();
+  std::thread::spawn(move || watch(app));
+}
+
+/// 按设置注册属性、命令与脚本指标，配置无效只记录日志
+fn register_custom_metrics(settings: &Settings) {
+  register_property_metrics(settings.property_metrics.clone());
+  for (key, e) in register_shell_metrics(settings.shell_metrics.clone()) {
+    warn!("命令指标 {} 无效: {}", key, e);
+  }
+  for (name, e) in register_metric_scripts(settings.metric_scripts.clone()) {
+    warn!("指标脚本 {} 无效: {}", name, e);
+  }
+}
+
+/// 导出目录加入前端文件读写的允许范围，设置了导出目录时前端直接写入而不弹出保存对话框
+fn allow_export_dir(app: &AppHandle, settings: &Settings) {
+  let (Some(dir), Some(scope)) = (&settings.export_dir, app.try_fs_scope()) else {
+    return;
+  };
+  if let Err(e) = scope.allow_directory(dir, true) {
+    warn!("无法允许写入导出目录 {}: {}", dir.display(), e);
+  }
+}
+
+pub fn get_settings() -> Result<AppSettings> {
+  let mut settings: AppSettings = store::load()?;
+  settings.alert_rules = Some(list_rules()?);
+  Ok(settings)
+}
+
+/// 保存设置，提供告警规则时同步到数据库，返回同步后的设置
+pub fn set_settings(app: &AppHandle, mut settings: AppSettings) -> Result<AppSettings> {
+  settings.alert_rules = Some(match &settings.alert_rules {
+    Some(rules) => sync_alert_rules(rules)?,
+    None => list_rules()?,
+  });
+  store::save(&settings)?;
+  mark_seen();
+  set_adb_path(settings.common.adb_path.clone());
+  register_custom_metrics(&settings.common);
+  allow_export_dir(app, &settings.common);
+  let _ = app.emit(SETTINGS_CHANGED_EVENT, &settings);
+  Ok(settings)
+}
+
+/// 记住界面上的设备、应用与指标选择；选择的指标同时作为默认指标
+pub fn update_selection(selection: Selection) -> Result<()> {
+  let mut settings: AppSettings = store::load()?;
+  settings.last_device = selection.device;
+  settings.last_app = selection.app.filter(|a| !a.trim().is_empty());
+  settings.common.default_metrics = selection.metrics;
+  store::save(&settings)?;
+  mark_seen();
+  Ok(())
+}
+
+/// 录制未指定采样间隔时使用，读取失败时为 1 秒
+pub fn default_interval_ms() -> u64 {
+  match store::load_settings() {
+    Ok(settings) if settings.interval_ms > 0 => settings.interval_ms,
+    Ok(_) => 1000,
+    Err(e) => {
+      warn!("读取设置失败: {}", e);
+      1000
+    }
+  }
+}
+
+/// 设置中的默认导出目录，未设置或读取失败时为 None
+pub fn export_dir() -> Option<PathBuf> {
+  match store::load_settings() {
+    Ok(settings) => settings.export_dir,
+    Err(e) => {
+      warn!("读取设置失败: {}", e);
+      None
+    }
+  }
+}
+
+/// 仅更新 adb 路径，供设备页手动选择 adb 时使用
+pub fn set_saved_adb_path(path: Option<String>) -> Result<()> {
+  let mut settings: AppSettings = store::load()?;
+  settings.common.adb_path = path.filter(|p| !p.trim().is_empty());
+  store::save(&settings)?;
+  mark_seen();
+  Ok(())
+}
+
+/// Excel 报表与会话汇总使用的单位，读取失败时用基准单位
+pub fn report_units() -> Units {
+  match store::load::<AppSettings>() {
+    Ok(settings) => settings.common.report_units,
+    Err(e) => {
+      warn!("读取设置失败: {}", e);
+      Units::default()
+    }
+  }
+}
+
+/// 录制配置未指定推送目标或指标时补上默认推送与默认指标
+pub fn apply_defaults(mut config: RecordingConfig) -> RecordingConfig {
+  if config.options.sinks.is_empty() || config.metrics.is_empty() {
+    match store::load::<AppSettings>() {
+      Ok(settings) => {
+        if config.options.sinks.is_empty() {
+          config.options.sinks = settings.default_sinks;
+        }
+        if config.metrics.is_empty() {
+          config.metrics = settings.common.default_metrics;
+        }
+      }
+      Err(e) => warn!("读取设置失败: {}", e),
+    }
+  }
+  config
+}
+
+fn sync_alert_rules(rules: &[AlertRule]) -> Result<Vec<AlertRule>> {
+  let kept: Vec<i64> = rules.iter().map(|r| r.id).filter(|id| *id > 0).collect();
+  for existing in list_rules()? {
+    if !kept.contains(&existing.id) {
+      delete_rule(existing.id)?;
+    }
+  }
+  let mut saved = Vec::with_capacity(rules.len());
+  for rule in rules {
+    saved.push(save_rule(rule)?);
+  }
+  Ok(saved)
+}
+
+fn mark_seen() {
+  if let Ok(mut guard) = LAST_SEEN.lock() {
+    *guard = store::modified_at();
+  }
+}
+
+/// 轮询文件修改时间，命令行或手动编辑后重新加载并通知前端
+fn watch(app: AppHandle) {
+  loop {
+    std::thread::sleep(WATCH_INTERVAL);
+    let modified = store::modified_at();
+    let changed = match LAST_SEEN.lock() {
+      Ok(mut guard) if *guard != modified => {
+        *guard = modified;
+        true
+      }
+      _ => false,
+    };
+    if !changed || modified.is_none() {
+      continue;
+    }
+    match get_settings() {
+      Ok(settings) => {
+        set_adb_path(settings.common.adb_path.clone());
+        register_custom_metrics(&settings.common);
+        allow_export_dir(&app, &settings.common);
+        let _ = app.emit(SETTINGS_CHANGED_EVENT, &settings);
+      }
+      Err(e) => warn!("重新加载设置失败: {}", e),
+    }
+  }
+}
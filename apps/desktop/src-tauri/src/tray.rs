@@ -0,0 +1,187 @@
+//! 系统托盘：显示录制中会话的实时读数，并提供开始 / 暂停 / 停止录制的快捷入口
+
+use crate::{
+  adb::MetricsSnapshot,
+  overlay::toggle_overlay,
+  session::{
+    animation::restore_all_animations,
+    display::restore_all_displays,
+    hwui::restore_all_overlays,
+    proxy::restore_all_proxies,
+    recorder::{
+      add_observer, last_recording_config, latest_recording, set_recording_paused, start_recording,
+      stop_recording, RecordingObserver,
+    },
+    Sample,
+  },
+  window_state,
+};
+use log::warn;
+use std::{sync::Arc, thread};
+use tauri::{
+  menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem},
+  tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+  AppHandle, Manager, Wry,
+};
+
+const TRAY_ID: &str = "main";
+const IDLE_TOOLTIP: &str = "PerfX：未在录制";
+
+/// 需要随录制状态更新的菜单项
+struct TrayMenu {
+  start: MenuItem<Wry>,
+  pause: MenuItem<Wry>,
+  stop: MenuItem<Wry>,
+}
+
+fn refresh_menu(app: &AppHandle) {
+  let Some(menu) = app.try_state::<TrayMenu>() else {
+    return;
+  };
+  let current = latest_recording();
+  let paused = current.as_ref().is_some_and(|r| r.paused);
+  let _ = menu.start.set_enabled(current.is_none());
+  let _ = menu.pause.set_enabled(current.is_some());
+  let _ = menu.pause.set_text(if paused {
+    "继续采样"
+  } else {
+    "暂停采样"
+  });
+  let _ = menu.stop.set_enabled(current.is_some());
+  if current.is_none() {
+    set_readout(app, IDLE_TOOLTIP, None);
+  } else if paused {
+    set_readout(app, "PerfX：已暂停", Some("暂停"));
+  }
+}
+
+fn set_readout(app: &AppHandle, tooltip: &str, title: Option<&str>) {
+  if let Some(tray) = app.tray_by_id(TRAY_ID) {
+    let _ = tray.set_tooltip(Some(tooltip));
+    // 标题只在 macOS 菜单栏与 Linux 上显示
+    let _ = tray.set_title(title);
+  }
+}
+
+pub(crate) fn show_main_window(app: &AppHandle) {
+  if let Some(window) = app.get_webview_window("main") {
+    let _ = window.unminimize();
+    let _ = window.show();
+    let _ = window.set_focus();
+  }
+}
+
+/// 菜单操作会访问设备，放到后台线程执行，完成后刷新菜单
+fn in_background(app: &AppHandle, f: impl FnOnce() -> Result<(), String> + Send + 'static) {
+  let app = app.clone();
+  thread::spawn(move || {
+    if let Err(e) = f() {
+      warn!("托盘操作失败: {}", e);
+    }
+    refresh_menu(&app);
+  });
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+  match event.id().as_ref() {
+    "start" => in_background(app, || {
+      let config = last_recording_config()
+        .map_err(|e| e.to_string())?
+        .ok_or("还没有录制过，请先在主窗口开始一次录制")?;
+      start_recording(config)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+    }),
+    "pause" => in_background(app, || {
+      let current = latest_recording().ok_or("没有录制中的会话")?;
+      set_recording_paused(current.session_id, !current.paused)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+    }),
+    "stop" => in_background(app, || {
+      let current = latest_recording().ok_or("没有录制中的会话")?;
+      stop_recording(current.session_id)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+    }),
+    "overlay" => {
+      if let Err(e) = toggle_overlay(app) {
+        warn!("切换悬浮窗失败: {}", e);
+      }
+    }
+    "show" => show_main_window(app),
+    "quit" => {
+      window_state::save_all();
+      restore_all_animations();
+      restore_all_displays();
+      restore_all_overlays(None);
+      restore_all_proxies();
+      app.exit(0);
+    }
+    _ => {}
+  }
+}
+
+/// 在托盘上显示当前会话的帧率与 CPU
+struct TrayReadout {
+  app: AppHandle,
+}
+
+impl RecordingObserver for TrayReadout {
+  fn on_sample(&self, session_id: i64, _sample: &Sample, snapshot: &MetricsSnapshot) {
+    let current = latest_recording();
+    if current.as_ref().map(|r| r.session_id) != Some(session_id) {
+      return;
+    }
+    let show = |v: Option<f64>| v.map_or_else(|| "-".to_string(), |v| format!("{v:.0}"));
+    let title = format!("FPS {} · CPU {}%", show(snapshot.fps), show(snapshot.cpu));
+    let tooltip = match current {
+      Some(r) => format!("PerfX #{} {}\n{}", session_id, r.package, title),
+      None => title.clone(),
+    };
+    set_readout(&self.app, &tooltip, Some(&title));
+    refresh_menu(&self.app);
+  }
+
+  fn on_stopped(&self, _session_id: i64) {
+    refresh_menu(&self.app);
+  }
+}
+
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+  let start = MenuItem::with_id(app, "start", "开始录制（上次配置）", true, None::<&str>)?;
+  let pause = MenuItem::with_id(app, "pause", "暂停采样", false, None::<&str>)?;
+  let stop = MenuItem::with_id(app, "stop", "停止录制", false, None::<&str>)?;
+  let overlay = MenuItem::with_id(app, "overlay", "显示 / 关闭悬浮窗", true, None::<&str>)?;
+  let show = MenuItem::with_id(app, "show", "显示主窗口", true, None::<&str>)?;
+  let quit = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
+  let separator = PredefinedMenuItem::separator(app)?;
+  let menu = Menu::with_items(
+    app,
+    &[&start, &pause, &stop, &separator, &overlay, &show, &quit],
+  )?;
+
+  let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+    .tooltip(IDLE_TOOLTIP)
+    .menu(&menu)
+    .show_menu_on_left_click(false)
+    .on_menu_event(on_menu_event)
+    .on_tray_icon_event(|tray, event| {
+      if let TrayIconEvent::Click {
+        button: MouseButton::Left,
+        button_state: MouseButtonState::Up,
+        ..
+      } = event
+      {
+        show_main_window(tray.app_handle());
+      }
+    });
+  if let Some(icon) = app.default_window_icon() {
+    builder = builder.icon(icon.clone());
+  }
+  builder.build(app)?;
+
+  app.manage(TrayMenu { start, pause, stop });
+  add_observer(Arc::new(TrayReadout { app: app.clone() }));
+  Ok(())
+}
@@ -0,0 +1,250 @@
+//! 自动更新：按所选渠道检查新版本，后台下载并校验签名，用户确认后重启安装
+
+use crate::{
+  i18n::{detail, Localized},
+  session::{
+    error::SessionError,
+    recorder::list_recordings,
+    store::{get_kv, set_kv},
+  },
+};
+use log::{info, warn};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use tauri::{async_runtime, AppHandle, Emitter, Url};
+use tauri_plugin_updater::{Update, UpdaterExt};
+use thiserror::Error;
+
+pub const UPDATE_PROGRESS_EVENT: &str = "update://progress";
+pub const UPDATE_READY_EVENT: &str = "update://ready";
+
+const CONFIG_KEY: &str = "update_settings";
+// 发布流程在构建时注入签名公钥与默认更新地址
+const PUBKEY: Option<&str> = option_env!("PERFX_UPDATER_PUBKEY");
+const DEFAULT_ENDPOINT: Option<&str> = option_env!("PERFX_UPDATE_ENDPOINT");
+
+#[derive(Debug, Error)]
+pub enum UpdateError {
+  #[error(transparent)]
+  Session(#[from] SessionError),
+  #[error("更新失败: {0}")]
+  Updater(#[from] tauri_plugin_updater::Error),
+  #[error("未配置{0}")]
+  NotConfigured(&'static str),
+  #[error("更新地址无效: {0}")]
+  InvalidEndpoint(String),
+  #[error("没有已下载的更新")]
+  NothingDownloaded,
+  #[error("有 {0} 个录制正在进行，请停止后再安装更新")]
+  RecordingActive(usize),
+}
+
+pub type Result<T> = std::result::Result<T, UpdateError>;
+
+
+impl Localized for UpdateError {
+  fn code(&self) -> &'static str {
+    match self {
+      Self::Session(e) => e.code(),
+      Self::Updater(_) => "UPDATE_FAILED",
+      Self::NotConfigured(_) => "UPDATE_NOT_CONFIGURED",
+      Self::InvalidEndpoint(_) => "UPDATE_ENDPOINT_INVALID",
+      Self::NothingDownloaded => "UPDATE_NOTHING_DOWNLOADED",
+      Self::RecordingActive(_) => "UPDATE_RECORDING_ACTIVE",
+    }
+  }
+
+  fn params(&self) -> Value {
+    match self {
+      Self::Session(e) => e.params(),
+      Self::Updater(e) => detail(e),
+      Self::NotConfigured(what) => json!({ "item": what }),
+      Self::InvalidEndpoint(d) => detail(d),
+      Self::NothingDownloaded => Value::Null,
+      Self::RecordingActive(count) => json!({ "count": count }),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateChannel {
+  #[default]
+  Stable,
+  Beta,
+}
+
+impl UpdateChannel {
+  fn as_str(self) -> &'static str {
+    match self {
+      Self::Stable => "stable",
+      Self::Beta => "beta",
+    }
+  }
+}
+
+fn default_true() -> bool {
+  true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateSettings {
+  #[serde(default)]
+  pub channel: UpdateChannel,
+  /// 更新清单地址，`{{channel}}` 替换为渠道名，`{{target}}`、`{{arch}}`、
+  /// `{{current_version}}` 由更新插件替换；不指定时使用构建时内置的地址
+  #[serde(default)]
+  pub endpoint: Option<String>,
+  /// 启动时在后台检查并下载
+  #[serde(default = "default_true")]
+  pub auto_check: bool,
+}
+
+impl Default for UpdateSettings {
+  fn default() -> Self {
+    Self {
+      channel: UpdateChannel::default(),
+      endpoint: None,
+      auto_check: true,
+    }
+  }
+}
+
+impl UpdateSettings {
+  fn endpoint(&self) -> Result<Url> {
+    let template = self
+      .endpoint
+      .as_deref()
+      .filter(|e| !e.trim().is_empty())
+      .or(DEFAULT_ENDPOINT)
+      .ok_or(UpdateError::NotConfigured("更新地址"))?;
+    let url = template.replace("{{channel}}", self.channel.as_str());
+    Url::parse(&url).map_err(|e| UpdateError::InvalidEndpoint(format!("{url}: {e}")))
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateInfo {
+  pub current_version: String,
+  pub version: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub date: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub notes: Option<String>,
+}
+
+impl From<&Update> for UpdateInfo {
+  fn from(update: &Update) -> Self {
+    Self {
+      current_version: update.current_version.clone(),
+      version: update.version.clone(),
+      date: update.date.map(|d| d.to_string()),
+      notes: update.body.clone(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Progress {
+  downloaded: u64,
+  total: Option<u64>,
+}
+
+/// 已下载并通过签名校验、等待重启安装的更新
+struct Downloaded {
+  update: Update,
+  bytes: Vec<u8>,
+}
+
+static DOWNLOADED: Lazy<Mutex<Option<Downloaded>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn get_update_settings() -> Result<UpdateSettings> {
+  Ok(get_kv(CONFIG_KEY)?.unwrap_or_default())
+}
+
+pub fn set_update_settings(settings: &UpdateSettings) -> Result<()> {
+  if settings.endpoint.is_some() {
+    settings.endpoint()?;
+  }
+  Ok(set_kv(CONFIG_KEY, settings)?)
+}
+
+async fn find_update(app: &AppHandle) -> Result<Option<Update>> {
+  let pubkey = PUBKEY.ok_or(UpdateError::NotConfigured("更新签名公钥"))?;
+  let settings = get_update_settings()?;
+  let updater = app
+    .updater_builder()
+    .pubkey(pubkey)
+    .endpoints(vec![settings.endpoint()?])?
+    .build()?;
+  Ok(updater.check().await?)
+}
+
+pub async fn check_update(app: &AppHandle) -> Result<Option<UpdateInfo>> {
+  Ok(find_update(app).await?.as_ref().map(UpdateInfo::from))
+}
+
+/// 下载最新版本并校验签名，完成后发出 `update://ready`，不会自动安装
+pub async fn download_update(app: &AppHandle) -> Result<Option<UpdateInfo>> {
+  let Some(update) = find_update(app).await? else {
+    return Ok(None);
+  };
+  let info = UpdateInfo::from(&update);
+  let already = DOWNLOADED
+    .lock()
+    .ok()
+    .and_then(|d| d.as_ref().map(|d| d.update.version == update.version))
+    .unwrap_or(false);
+  if already {
+    return Ok(Some(info));
+  }
+  let mut downloaded = 0u64;
+  let bytes = update
+    .download(
+      |chunk, total| {
+        downloaded += chunk as u64;
+        let _ = app.emit(UPDATE_PROGRESS_EVENT, Progress { downloaded, total });
+      },
+      || {},
+    )
+    .await?;
+  info!("新版本 {} 下载完成，等待重启安装", update.version);
+  if let Ok(mut slot) = DOWNLOADED.lock() {
+    *slot = Some(Downloaded { update, bytes });
+  }
+  let _ = app.emit(UPDATE_READY_EVENT, &info);
+  Ok(Some(info))
+}
+
+/// 安装已下载的更新并重启应用；录制进行中时拒绝，避免重启丢失数据
+pub fn apply_update(app: &AppHandle) -> Result<()> {
+  let active = list_recordings().len();
+  if active > 0 {
+    return Err(UpdateError::RecordingActive(active));
+  }
+  let Downloaded { update, bytes } = DOWNLOADED
+    .lock()
+    .ok()
+    .and_then(|mut d| d.take())
+    .ok_or(UpdateError::NothingDownloaded)?;
+  info!("安装新版本 {}", update.version);
+  update.install(bytes)?;
+  app.restart()
+}
+
+/// 启动时按设置在后台检查并下载，未配置更新地址时静默跳过
+pub fn init(app: &AppHandle) {
+  let auto_check = get_update_settings().map(|s| s.auto_check).unwrap_or(true);
+  if !auto_check {
+    return;
+  }
+  let app = app.clone();
+  async_runtime::spawn(async move {
+    match download_update(&app).await {
+      Ok(_) | Err(UpdateError::NotConfigured(_)) => {}
+      Err(e) => warn!("后台检查更新失败: {}", e),
+    }
+  });
+}
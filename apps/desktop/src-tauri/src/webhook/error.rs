@@ -0,0 +1,40 @@
+use crate::{
+  analysis::error::AnalysisError,
+  i18n::{detail, Localized},
+  session::error::SessionError,
+};
+use serde_json::Value;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+  #[error(transparent)]
+  Session(#[from] SessionError),
+  #[error(transparent)]
+  Analysis(#[from] AnalysisError),
+  #[error("Webhook 配置无效: {0}")]
+  Config(String),
+  #[error("发送失败: {0}")]
+  Send(String),
+}
+
+pub type Result<T> = std::result::Result<T, WebhookError>;
+
+impl Localized for WebhookError {
+  fn code(&self) -> &'static str {
+    match self {
+      Self::Session(e) => e.code(),
+      Self::Analysis(e) => e.code(),
+      Self::Config(_) => "WEBHOOK_CONFIG_INVALID",
+      Self::Send(_) => "WEBHOOK_SEND",
+    }
+  }
+
+  fn params(&self) -> Value {
+    match self {
+      Self::Session(e) => e.params(),
+      Self::Analysis(e) => e.params(),
+      Self::Config(d) | Self::Send(d) => detail(d),
+    }
+  }
+}
@@ -0,0 +1,234 @@
+//! 告警触发、会话结束与门禁未通过时向外部 Webhook / Slack 发送通知
+
+use crate::{
+  analysis::{
+    gate::{gate_session, load_config, GateReport},
+    session_summary, SessionSummary,
+  },
+  session::{
+    events::{EventKind, SessionEvent},
+    recorder::{add_observer, recording_target, RecordingObserver},
+    sink::batch::{agent, post_with_retry},
+    store::{get_kv, set_kv},
+  },
+  webhook::error::{Result, WebhookError},
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, thread};
+
+pub mod error;
+mod payload;
+
+const CONFIG_KEY: &str = "webhooks";
+const MAX_RETRIES: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+  /// 通用 JSON，字段见 `payload::Notification`
+  Json,
+  /// Slack Incoming Webhook
+  Slack,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookTrigger {
+  /// 阈值告警规则触发
+  Alert,
+  /// 录制结束，附带汇总
+  SessionEnd,
+  /// 录制结束后与基线比较未通过，或手动执行门禁未通过
+  GateFailed,
+}
+
+fn default_enabled() -> bool {
+  true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+  #[serde(default)]
+  pub name: String,
+  pub url: String,
+  pub format: WebhookFormat,
+  pub triggers: Vec<WebhookTrigger>,
+  /// 会话链接模板，`{session_id}` 会被替换，如 `https://perf.lab/sessions/{session_id}`
+  #[serde(default)]
+  pub link_template: Option<String>,
+  #[serde(default = "default_enabled")]
+  pub enabled: bool,
+}
+
+impl WebhookConfig {
+  fn validate(&self) -> Result<()> {
+    if !self.url.starts_with("http://") && !self.url.starts_with("https://") {
+      return Err(WebhookError::Config(format!(
+        "地址须以 http:// 或 https:// 开头: {}",
+        self.url
+      )));
+    }
+    Ok(())
+  }
+
+  fn link(&self, session_id: i64) -> Option<String> {
+    self
+      .link_template
+      .as_ref()
+      .map(|t| t.replace("{session_id}", &session_id.to_string()))
+  }
+}
+
+/// 一次要发送的通知
+#[derive(Debug, Clone)]
+pub enum Notification {
+  Alert {
+    session_id: i64,
+    device_id: String,
+    package: String,
+    event: SessionEvent,
+  },
+  SessionEnd(Box<SessionSummary>),
+  GateFailed(GateReport),
+  /// 配置页面的“发送测试”
+  Test,
+}
+
+impl Notification {
+  fn trigger(&self) -> Option<WebhookTrigger> {
+    match self {
+      Self::Alert { .. } => Some(WebhookTrigger::Alert),
+      Self::SessionEnd(_) => Some(WebhookTrigger::SessionEnd),
+      Self::GateFailed(_) => Some(WebhookTrigger::GateFailed),
+      Self::Test => None,
+    }
+  }
+
+  fn session_id(&self) -> Option<i64> {
+    match self {
+      Self::Alert { session_id, .. } => Some(*session_id),
+      Self::SessionEnd(summary) => Some(summary.session.id),
+      Self::GateFailed(report) => Some(report.session_id),
+      Self::Test => None,
+    }
+  }
+}
+
+pub fn list_webhooks() -> Result<Vec<WebhookConfig>> {
+  Ok(get_kv(CONFIG_KEY)?.unwrap_or_default())
+}
+
+pub fn save_webhooks(webhooks: &[WebhookConfig]) -> Result<()> {
+  for webhook in webhooks {
+    webhook.validate()?;
+  }
+  Ok(set_kv(CONFIG_KEY, &webhooks)?)
+}
+
+fn send(webhook: &WebhookConfig, notification: &Notification) -> Result<()> {
+  let link = notification.session_id().and_then(|id| webhook.link(id));
+  let body = match webhook.format {
+    WebhookFormat::Json => payload::json(notification, link.as_deref()),
+    WebhookFormat::Slack => payload::slack(notification, link.as_deref()),
+  };
+  let agent = agent();
+  post_with_retry(
+    MAX_RETRIES,
+    || {
+      agent
+        .post(&webhook.url)
+        .set("Content-Type", "application/json")
+    },
+    &body.to_string(),
+  )
+  .map_err(WebhookError::Send)
+}
+
+/// 发送测试通知，便于配置时确认地址可用
+pub fn test_webhook(webhook: &WebhookConfig) -> Result<()> {
+  webhook.validate()?;
+  send(webhook, &Notification::Test)
+}
+
+/// 发给订阅了该类通知的 Webhook，失败只记日志
+pub fn dispatch(notification: &Notification) {
+  let Some(trigger) = notification.trigger() else {
+    return;
+  };
+  let webhooks = match list_webhooks() {
+    Ok(webhooks) => webhooks,
+    Err(e) => {
+      warn!("读取 Webhook 配置失败: {}", e);
+      return;
+    }
+  };
+  for webhook in webhooks
+    .iter()
+    .filter(|w| w.enabled && w.triggers.contains(&trigger))
+  {
+    if let Err(e) = send(webhook, notification) {
+      warn!("Webhook {} 发送失败: {}", webhook.url, e);
+    }
+  }
+}
+
+fn wants(trigger: WebhookTrigger) -> bool {
+  list_webhooks()
+    .map(|w| w.iter().any(|w| w.enabled && w.triggers.contains(&trigger)))
+    .unwrap_or(false)
+}
+
+/// 门禁未通过时通知
+pub fn notify_gate(report: &GateReport) {
+  if !report.passed {
+    let notification = Notification::GateFailed(report.clone());
+    thread::spawn(move || dispatch(&notification));
+  }
+}
+
+/// 录制结束后生成汇总并按需执行门禁，在后台线程中完成
+fn on_session_end(session_id: i64) {
+  if wants(WebhookTrigger::SessionEnd) {
+    match session_summary(session_id) {
+      Ok(summary) => dispatch(&Notification::SessionEnd(Box::new(summary))),
+      Err(e) => warn!("生成会话 {} 汇总失败: {}", session_id, e),
+    }
+  }
+  if wants(WebhookTrigger::GateFailed) {
+    // 没有基线的会话不做门禁
+    let report = load_config().and_then(|config| gate_session(session_id, None, &config));
+    match report {
+      Ok(report) if !report.passed => dispatch(&Notification::GateFailed(report)),
+      _ => {}
+    }
+  }
+}
+
+struct WebhookNotifier;
+
+impl RecordingObserver for WebhookNotifier {
+  fn on_event(&self, session_id: i64, event: &SessionEvent) {
+    if event.kind != EventKind::Alert {
+      return;
+    }
+    let Some((device_id, package, _)) = recording_target(session_id) else {
+      return;
+    };
+    let notification = Notification::Alert {
+      session_id,
+      device_id,
+      package,
+      event: event.clone(),
+    };
+    thread::spawn(move || dispatch(&notification));
+  }
+
+  fn on_stopped(&self, session_id: i64) {
+    thread::spawn(move || on_session_end(session_id));
+  }
+}
+
+pub fn register() {
+  add_observer(Arc::new(WebhookNotifier));
+}
@@ -0,0 +1,96 @@
+use crate::{analysis::gate::CheckStatus, webhook::Notification};
+use serde_json::{json, Value};
+
+/// 通知的纯文本摘要，两种格式共用
+fn text(notification: &Notification) -> String {
+  match notification {
+    Notification::Alert {
+      session_id,
+      device_id,
+      package,
+      event,
+    } => format!(
+      "会话 #{session_id}（{device_id} / {package}）告警：{}",
+      event.message
+    ),
+    Notification::SessionEnd(summary) => {
+      let session = &summary.session;
+      format!(
+        "会话 #{} {}（{} / {}）录制结束，时长 {} 秒\n{}",
+        session.id,
+        session.name,
+        session.device_id,
+        session.app_package,
+        session.duration,
+        summary.narrative
+      )
+    }
+    Notification::GateFailed(report) => {
+      let mut lines = vec![format!(
+        "会话 #{} 门禁未通过（基线 #{}）",
+        report.session_id, report.baseline_session_id
+      )];
+      lines.extend(
+        report
+          .checks
+          .iter()
+          .filter(|c| c.status != CheckStatus::Pass)
+          .map(|c| {
+            format!(
+              "- {}: {}",
+              c.metric,
+              c.reason.as_deref().unwrap_or("缺少数据")
+            )
+          }),
+      );
+      lines.join("\n")
+    }
+    Notification::Test => "PerfX Webhook 测试消息".into(),
+  }
+}
+
+fn event_name(notification: &Notification) -> &'static str {
+  match notification {
+    Notification::Alert { .. } => "alert",
+    Notification::SessionEnd(_) => "session_end",
+    Notification::GateFailed(_) => "gate_failed",
+    Notification::Test => "test",
+  }
+}
+
+/// 通用 JSON：`event` 区分类型，`data` 为原始结构
+pub(crate) fn json(notification: &Notification, link: Option<&str>) -> Value {
+  let data = match notification {
+    Notification::Alert {
+      device_id,
+      package,
+      event,
+      ..
+    } => json!({ "device_id": device_id, "package": package, "event": event }),
+    Notification::SessionEnd(summary) => json!(summary),
+    Notification::GateFailed(report) => json!(report),
+    Notification::Test => Value::Null,
+  };
+  json!({
+    "event": event_name(notification),
+    "session_id": notification.session_id(),
+    "text": text(notification),
+    "link": link,
+    "data": data,
+  })
+}
+
+/// Slack Incoming Webhook 消息，链接以 mrkdwn 形式附在末尾
+pub(crate) fn slack(notification: &Notification, link: Option<&str>) -> Value {
+  let title = match notification {
+    Notification::Alert { .. } => ":warning: *PerfX 告警*",
+    Notification::SessionEnd(_) => ":white_check_mark: *PerfX 录制结束*",
+    Notification::GateFailed(_) => ":x: *PerfX 门禁未通过*",
+    Notification::Test => "*PerfX*",
+  };
+  let mut body = format!("{title}\n{}", text(notification));
+  if let Some(link) = link {
+    body.push_str(&format!("\n<{link}|查看会话>"));
+  }
+  json!({ "text": body })
+}
@@ -0,0 +1,131 @@
+//! 窗口几何信息的保存与恢复：主窗口与悬浮窗移动、缩放时记录位置、大小与最大化状态，
+//! 关闭时写入数据库，下次打开时还原；保存时所在的显示器已不存在则改为居中显示
+
+use crate::session::store::{get_kv, set_kv};
+use log::warn;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Mutex};
+use tauri::{Monitor, PhysicalPosition, PhysicalSize, WebviewWindow, WindowEvent};
+
+const KEY_PREFIX: &str = "window_state:";
+/// 窗口至少有这么多像素落在某个显示器内才视为可见
+const MIN_VISIBLE_PX: i64 = 64;
+
+/// 物理像素下的窗口位置与大小；最大化时保留还原后的尺寸
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct WindowGeometry {
+  x: i32,
+  y: i32,
+  width: u32,
+  height: u32,
+  #[serde(default)]
+  maximized: bool,
+}
+
+static GEOMETRY: Lazy<Mutex<HashMap<String, WindowGeometry>>> =
+  Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 还原窗口上次的几何信息，并在之后的移动、缩放与关闭时记录
+pub fn track(window: &WebviewWindow) {
+  restore(window);
+  let handle = window.clone();
+  window.on_window_event(move |event| match event {
+    WindowEvent::Moved(_) | WindowEvent::Resized(_) => remember(&handle),
+    WindowEvent::CloseRequested { .. } | WindowEvent::Destroyed => save(handle.label()),
+    _ => {}
+  });
+}
+
+/// 退出应用前写入所有窗口的几何信息，窗口未经关闭事件直接销毁时也不会丢失
+pub fn save_all() {
+  let labels: Vec<String> = match GEOMETRY.lock() {
+    Ok(map) => map.keys().cloned().collect(),
+    Err(_) => return,
+  };
+  for label in labels {
+    save(&label);
+  }
+}
+
+fn restore(window: &WebviewWindow) {
+  let key = format!("{KEY_PREFIX}{}", window.label());
+  let saved = match get_kv::<WindowGeometry>(&key) {
+    Ok(Some(saved)) => saved,
+    Ok(None) => return,
+    Err(e) => {
+      warn!("读取窗口状态失败: {}", e);
+      return;
+    }
+  };
+  if let Ok(mut map) = GEOMETRY.lock() {
+    map.insert(window.label().to_string(), saved);
+  }
+
+  let monitors = window.available_monitors().unwrap_or_default();
+  let mut size = PhysicalSize::new(saved.width, saved.height);
+  if monitors.iter().any(|m| is_visible_on(m, &saved)) {
+    let _ = window.set_size(size);
+    let _ = window.set_position(PhysicalPosition::new(saved.x, saved.y));
+  } else {
+    // 原显示器已断开：尺寸不超过主显示器，位置居中
+    if let Ok(Some(primary)) = window.primary_monitor() {
+      size.width = size.width.min(primary.size().width);
+      size.height = size.height.min(primary.size().height);
+    }
+    let _ = window.set_size(size);
+    let _ = window.center();
+  }
+  if saved.maximized {
+    let _ = window.maximize();
+  }
+}
+
+fn is_visible_on(monitor: &Monitor, geometry: &WindowGeometry) -> bool {
+  let (mx, my) = (monitor.position().x as i64, monitor.position().y as i64);
+  let (mw, mh) = (monitor.size().width as i64, monitor.size().height as i64);
+  let (x, y) = (geometry.x as i64, geometry.y as i64);
+  let overlap_w = (x + geometry.width as i64).min(mx + mw) - x.max(mx);
+  let overlap_h = (y + geometry.height as i64).min(my + mh) - y.max(my);
+  overlap_w >= MIN_VISIBLE_PX && overlap_h >= MIN_VISIBLE_PX
+}
+
+fn remember(window: &WebviewWindow) {
+  if window.is_minimized().unwrap_or(false) {
+    return;
+  }
+  let maximized = window.is_maximized().unwrap_or(false);
+  let Ok(mut map) = GEOMETRY.lock() else {
+    return;
+  };
+  let label = window.label().to_string();
+  if maximized {
+    // 最大化时的尺寸不记录，还原后仍回到原来的大小
+    if let Some(geometry) = map.get_mut(&label) {
+      geometry.maximized = true;
+    }
+    return;
+  }
+  let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) else {
+    return;
+  };
+  map.insert(
+    label,
+    WindowGeometry {
+      x: position.x,
+      y: position.y,
+      width: size.width,
+      height: size.height,
+      maximized: false,
+    },
+  );
+}
+
+fn save(label: &str) {
+  let Some(geometry) = GEOMETRY.lock().ok().and_then(|m| m.get(label).copied()) else {
+    return;
+  };
+  if let Err(e) = set_kv(&format!("{KEY_PREFIX}{label}"), &geometry) {
+    warn!("保存窗口状态失败: {}", e);
+  }
+}
@@ -0,0 +1,155 @@
+//! 独立图表窗口：每个窗口绑定一台设备或一个会话，只接收对应的实时帧，
+//! 多设备录制时可以把图表分散到多块屏幕上
+
+use crate::{
+  events::CHART_FRAME_EVENT,
+  server::live::{history_frames, subscribe, Frame},
+  session::recorder::{list_recordings, recording_target},
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::HashMap,
+  sync::{
+    atomic::{AtomicU32, Ordering},
+    Mutex,
+  },
+};
+use tauri::{async_runtime, AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+use tokio::sync::broadcast::error::RecvError;
+
+const LABEL_PREFIX: &str = "chart-";
+
+/// 窗口显示的数据范围；同时指定时以会话为准
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChartBinding {
+  #[serde(default)]
+  pub device_id: Option<String>,
+  #[serde(default)]
+  pub session_id: Option<i64>,
+}
+
+impl ChartBinding {
+  fn matches(&self, session_id: i64, device_id: Option<&str>) -> bool {
+    match (self.session_id, &self.device_id) {
+      (Some(id), _) => id == session_id,
+      (None, Some(device)) => device_id == Some(device.as_str()),
+      (None, None) => true,
+    }
+  }
+
+  fn title(&self) -> String {
+    match (self.session_id, &self.device_id) {
+      (Some(id), _) => format!("PerfX · 会话 #{id}"),
+      (None, Some(device)) => format!("PerfX · {device}"),
+      (None, None) => "PerfX · 全部设备".to_string(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChartWindow {
+  pub label: String,
+  pub binding: ChartBinding,
+}
+
+static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+static WINDOWS: Lazy<Mutex<HashMap<String, ChartBinding>>> =
+  Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn binding(label: &str) -> Option<ChartBinding> {
+  WINDOWS.lock().ok()?.get(label).cloned()
+}
+
+/// 打开新的图表窗口并开始转发绑定范围内的实时帧，返回窗口标签
+pub fn open_chart_window(app: &AppHandle, binding: ChartBinding) -> tauri::Result<String> {
+  let label = format!("{LABEL_PREFIX}{}", NEXT_ID.fetch_add(1, Ordering::Relaxed));
+  WebviewWindowBuilder::new(app, &label, WebviewUrl::App("chart.html".into()))
+    .title(binding.title())
+    .inner_size(720.0, 520.0)
+    .build()?;
+  if let Ok(mut windows) = WINDOWS.lock() {
+    windows.insert(label.clone(), binding);
+  }
+  spawn_forwarder(app.clone(), label.clone());
+  Ok(label)
+}
+
+pub fn list_chart_windows() -> Vec<ChartWindow> {
+  WINDOWS
+    .lock()
+    .map(|windows| {
+      windows
+        .iter()
+        .map(|(label, binding)| ChartWindow {
+          label: label.clone(),
+          binding: binding.clone(),
+        })
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+pub fn close_chart_window(app: &AppHandle, label: &str) -> tauri::Result<()> {
+  if let Ok(mut windows) = WINDOWS.lock() {
+    windows.remove(label);
+  }
+  match app.get_webview_window(label) {
+    Some(window) => window.close(),
+    None => Ok(()),
+  }
+}
+
+/// 页面加载完成后调用，补发绑定范围内录制中会话的历史采样
+pub fn send_history(app: &AppHandle, label: &str) {
+  let Some(binding) = binding(label) else {
+    return;
+  };
+  for recording in list_recordings()
+    .iter()
+    .filter(|r| binding.matches(r.session_id, Some(&r.device_id)))
+  {
+    for frame in history_frames(Some(recording.session_id)) {
+      emit_frame(app, label, &frame.text);
+    }
+  }
+}
+
+fn emit_frame(app: &AppHandle, label: &str, frame: &str) {
+  if let Ok(frame) = serde_json::from_str::<serde_json::Value>(frame) {
+    let _ = app.emit_to(label, CHART_FRAME_EVENT, frame);
+  }
+}
+
+/// 订阅实时帧并转发给单个窗口，窗口关闭后结束
+fn spawn_forwarder(app: AppHandle, label: String) {
+  let mut rx = subscribe();
+  async_runtime::spawn(async move {
+    // 停止帧到达时会话已不在录制列表中，先记下每个会话所属的设备
+    let mut devices: HashMap<i64, Option<String>> = HashMap::new();
+    loop {
+      let Frame {
+        session_id, text, ..
+      } = match rx.recv().await {
+        Ok(frame) => frame,
+        Err(RecvError::Lagged(_)) => continue,
+        Err(RecvError::Closed) => break,
+      };
+      if app.get_webview_window(&label).is_none() {
+        break;
+      }
+      let Some(binding) = binding(&label) else {
+        break;
+      };
+      let device = devices
+        .entry(session_id)
+        .or_insert_with(|| recording_target(session_id).map(|(device, _, _)| device));
+      if binding.matches(session_id, device.as_deref()) {
+        emit_frame(&app, &label, &text);
+      }
+    }
+    if let Ok(mut windows) = WINDOWS.lock() {
+      windows.remove(&label);
+    }
+  });
+}
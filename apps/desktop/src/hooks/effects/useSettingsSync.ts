@@ -0,0 +1,60 @@
+import { useEffect } from "react"
+import { getSettings, updateSelection } from "@/lib/tauri-settings"
+import { useDeviceStore } from "@/stores/use-device-store"
+import { supportedMetrics, useMonitoringStore } from "@/stores/use-monitoring-store"
+
+/**
+ * 启动时从后端设置恢复设备、应用、指标与采样间隔，之后选择变化时写回，
+ * 与命令行共用同一份配置
+ */
+export function useSettingsSync() {
+  useEffect(() => {
+    let cancelled = false
+    let unsubscribe: (() => void) | null = null
+
+    const save = () => {
+      const { selectedDevice } = useDeviceStore.getState()
+      const { selectedApp, selectedMetrics } = useMonitoringStore.getState()
+      updateSelection({
+        device: selectedDevice,
+        app: selectedApp || null,
+        metrics: selectedMetrics,
+      }).catch(err => console.warn("保存界面选择失败", err))
+    }
+
+    getSettings()
+      .then(settings => {
+        if (cancelled) return
+        useDeviceStore.getState().setSelectedDevice(settings.last_device)
+        const monitoring = useMonitoringStore.getState()
+        monitoring.setSelectedApp(settings.last_app ?? "")
+        monitoring.setSelectedMetrics(supportedMetrics(settings.default_metrics))
+        monitoring.setIntervalMs(settings.interval_ms)
+      })
+      .catch(err => console.warn("读取设置失败", err))
+      .finally(() => {
+        if (cancelled) return
+        // 恢复完成后再监听，避免用默认值覆盖已保存的选择
+        const offDevice = useDeviceStore.subscribe((state, prev) => {
+          if (state.selectedDevice !== prev.selectedDevice) save()
+        })
+        const offMonitoring = useMonitoringStore.subscribe((state, prev) => {
+          if (
+            state.selectedApp !== prev.selectedApp ||
+            state.selectedMetrics !== prev.selectedMetrics
+          ) {
+            save()
+          }
+        })
+        unsubscribe = () => {
+          offDevice()
+          offMonitoring()
+        }
+      })
+
+    return () => {
+      cancelled = true
+      unsubscribe?.()
+    }
+  }, [])
+}
@@ -21,8 +21,15 @@ export function useMonitoringControl(
   const { apps } = useAdbApps(selectedDevice?.id || null)
   const createReport = useCreateReport()
 
-  const { selectedApp, selectedMetrics, chartData, startTime, setStartTime, setRunning } =
-    useMonitoringStore()
+  const {
+    selectedApp,
+    selectedMetrics,
+    intervalMs,
+    chartData,
+    startTime,
+    setStartTime,
+    setRunning,
+  } = useMonitoringStore()
 
   const handleStart = useCallback(() => {
     // 检查设备是否已选择且连接正常
@@ -70,9 +77,18 @@ export function useMonitoringControl(
       deviceId: selectedDevice.id,
       packageName: selectedApp,
       metrics: metricsToRequest,
-      intervalMs: 1000,
+      intervalMs,
     })
-  }, [selectedDevice, selectedApp, selectedMetrics, start, setStartTime, setRunning, apps])
+  }, [
+    selectedDevice,
+    selectedApp,
+    selectedMetrics,
+    intervalMs,
+    start,
+    setStartTime,
+    setRunning,
+    apps,
+  ])
 
   const handleStop = useCallback(() => {
     stop()
@@ -1,6 +1,7 @@
 import { useCallback, useEffect, useMemo, useState } from "react"
 import { listApps } from "@/lib/tauri-adb"
 import type { AdbApp } from "@/types/adb"
+import { formatError } from "@/lib/errors"
 
 export function useAdbApps(deviceId: string | null) {
   const [apps, setApps] = useState<AdbApp[]>([])
@@ -20,7 +21,7 @@ export function useAdbApps(deviceId: string | null) {
       setApps(result)
       setError(null)
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err))
+      setError(formatError(err))
     } finally {
       setLoading(false)
     }
@@ -1,6 +1,7 @@
 import { useCallback, useEffect, useMemo, useState } from "react"
 import { listDevices } from "@/lib/tauri-adb"
 import type { AdbDevice } from "@/types/adb"
+import { formatError } from "@/lib/errors"
 
 export function useAdbDevices() {
   const [devices, setDevices] = useState<AdbDevice[]>([])
@@ -15,7 +16,7 @@ export function useAdbDevices() {
       setDevices(result)
       setError(null)
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err))
+      setError(formatError(err))
     } finally {
       setLoading(false)
     }
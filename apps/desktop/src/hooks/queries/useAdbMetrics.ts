@@ -2,6 +2,7 @@ import { useCallback, useEffect, useRef } from "react"
 import { getMetrics } from "@/lib/tauri-adb"
 import { useMonitoringStore } from "@/stores/use-monitoring-store"
 import type { MetricKey } from "@/types/adb"
+import { formatError } from "@/lib/errors"
 
 export interface StartMonitorPayload {
   deviceId: string
@@ -36,7 +37,7 @@ export function useAdbMetrics() {
         setMetrics(result)
         setMetricsError(null)
       } catch (err) {
-        setMetricsError(err instanceof Error ? err.message : String(err))
+        setMetricsError(formatError(err))
       }
     },
     [setMetrics, setMetricsError]
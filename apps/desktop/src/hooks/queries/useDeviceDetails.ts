@@ -1,5 +1,6 @@
 import { useCallback, useEffect, useState } from "react"
 import { executeAdbCommand } from "@/lib/tauri-adb"
+import { formatError } from "@/lib/errors"
 
 export interface DeviceDetails {
   id: string
@@ -222,7 +223,7 @@ export function useDeviceDetails(deviceId: string | null) {
       setDetails(deviceDetails)
       setError(null)
     } catch (err) {
-      setError(err instanceof Error ? err.message : String(err))
+      setError(formatError(err))
       setDetails(null)
     } finally {
       setLoading(false)
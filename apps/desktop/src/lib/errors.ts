@@ -0,0 +1,102 @@
+/** 后端命令返回的错误：code 稳定，key 与 params 用于本地化，message 为中文原文 */
+export interface BackendError {
+  code: string
+  key: string
+  params: Record<string, string | number> | null
+  message: string
+}
+
+type Messages = Record<string, string>
+
+// 中文直接使用后端原文，其他语言按文案键渲染，缺少译文时回退到原文
+const en: Messages = {
+  "error.internal": "Internal error: {detail}",
+  "error.adb_not_found": "adb not found. Check PATH or set the adb path in settings",
+  "error.adb_command_failed": "adb command failed: {detail}",
+  "error.adb_parse_failed": "Failed to parse adb output: {detail}",
+  "error.adb_client": "adb client error: {detail}",
+  "error.script_failed": "Metric script failed: {detail}",
+  "error.process_not_found": "Process not found",
+  "error.cpu_parse_failed": "Failed to parse CPU usage",
+  "error.memory_parse_failed": "Failed to parse memory usage",
+  "error.network_parse_failed": "Failed to parse network usage",
+  "error.frames_unavailable": "No frame data. Make sure the app is running",
+  "error.power_unavailable": "Power data unavailable",
+  "error.battery_unavailable": "Battery info unavailable",
+  "error.network_interface_not_found": "No usable network interface",
+  "error.settings_no_config_dir": "Cannot determine the config directory",
+  "error.settings_io": "Failed to read or write settings: {detail}",
+  "error.settings_format": "Invalid settings file: {detail}",
+  "error.db_not_initialized": "Session database is not initialized",
+  "error.session_not_found": "Session {sessionId} not found",
+  "error.session_not_recording": "Session {sessionId} is not recording",
+  "error.capture_running": "Session {sessionId} already has a running {capture}",
+  "error.capture_not_running": "Session {sessionId} has no running {capture}",
+  "error.session_already_paused": "Session {sessionId} is already paused",
+  "error.session_not_paused": "Session {sessionId} is not paused",
+  "error.no_recording_on_device": "No recording on device {device}",
+  "error.no_active_recording": "No active recording",
+  "error.ambiguous_recording": "{count} sessions are recording; specify a device or session",
+  "error.database": "Database error: {detail}",
+  "error.sink_config_invalid": "Invalid sink config: {detail}",
+  "error.session_format": "Invalid session data: {detail}",
+  "error.json": "JSON error: {detail}",
+  "error.io": "File I/O failed: {detail}",
+  "error.image": "Image processing failed: {detail}",
+  "error.bundle_read": "Failed to read session bundle: {detail}",
+  "error.passphrase_required": "The session bundle is encrypted; enter the passphrase",
+  "error.decrypt_failed": "Decryption failed: wrong passphrase or corrupted file",
+  "error.marker_not_found": "Marker {name} not found in session {sessionId}",
+  "error.no_baseline": "Session {sessionId} has no baseline",
+  "error.no_energy_data": "Session {sessionId} has no frequency data; enable energy estimation when recording",
+  "error.no_power_profile": "No cached power_profile for the device of session {sessionId}",
+  "error.flamegraph": "Failed to generate flame graph: {detail}",
+  "error.export_xlsx": "Failed to generate Excel file: {detail}",
+  "error.export_bundle": "Failed to create session bundle: {detail}",
+  "error.export_empty": "Nothing to export",
+  "error.scenario_invalid": "Invalid scenario: {detail}",
+  "error.run_not_found": "Run {runId} not found",
+  "error.run_no_export_dir": "No export directory is set",
+  "error.server_already_running": "Control server is already running on port {port}",
+  "error.server_not_running": "Control server is not running",
+  "error.server_bind": "Failed to listen on port: {detail}",
+  "error.server_token": "Failed to generate access token: {detail}",
+  "error.remote_connect": "Failed to connect to remote instance: {detail}",
+  "error.remote_not_connected": "Not connected to a remote instance",
+  "error.webhook_config_invalid": "Invalid webhook config: {detail}",
+  "error.webhook_send": "Webhook delivery failed: {detail}",
+  "error.farm_not_configured": "Device farm is not configured",
+  "error.farm_config_invalid": "Invalid device farm config: {detail}",
+  "error.farm_api": "Device farm request failed: {detail}",
+  "error.farm_connect": "Remote adb connect failed: {detail}",
+  "error.farm_not_reserved": "Device {serial} is not reserved",
+  "error.update_failed": "Update failed: {detail}",
+  "error.update_not_configured": "{item} is not configured",
+  "error.update_endpoint_invalid": "Invalid update endpoint: {detail}",
+  "error.update_nothing_downloaded": "No downloaded update",
+  "error.update_recording_active": "{count} recordings are running; stop them before installing the update",
+  "error.hotkey_invalid": "Invalid shortcut: {shortcut}",
+  "error.hotkey_duplicate": "Duplicate shortcut: {shortcut}",
+  "error.hotkey_register": "Failed to register shortcut: {detail}",
+}
+
+const catalogs: Record<string, Messages> = { en }
+
+export function isBackendError(err: unknown): err is BackendError {
+  return (
+    typeof err === "object" &&
+    err !== null &&
+    typeof (err as BackendError).code === "string" &&
+    typeof (err as BackendError).message === "string"
+  )
+}
+
+/** 按界面语言渲染任意错误，用于 toast 与错误提示 */
+export function formatError(err: unknown, locale = navigator.language): string {
+  if (isBackendError(err)) {
+    const template = catalogs[locale.split("-")[0]]?.[err.key]
+    if (!template) return err.message
+    return template.replace(/\{(\w+)\}/g, (_, name: string) => String(err.params?.[name] ?? ""))
+  }
+  return err instanceof Error ? err.message : String(err)
+}
@@ -1,9 +1,32 @@
+import { join } from "@tauri-apps/api/path"
 import { save } from "@tauri-apps/plugin-dialog"
 import { writeFile, writeTextFile } from "@tauri-apps/plugin-fs"
 import html2canvas from "html2canvas-pro"
 import { jsPDF } from "jspdf"
+import { getSettings } from "@/lib/tauri-settings"
 import type { ReportChartData } from "@/types/report"
 
+/**
+ * 设置了导出目录时直接写入该目录，否则弹出保存对话框；取消时返回 null
+ */
+async function chooseExportPath(fileName: string, name: string, extension: string) {
+  const exportDir = await getSettings()
+    .then(settings => settings.export_dir)
+    .catch(() => null)
+  if (exportDir) {
+    return join(exportDir, fileName)
+  }
+  return save({
+    defaultPath: fileName,
+    filters: [
+      {
+        name,
+        extensions: [extension],
+      },
+    ],
+  })
+}
+
 /**
  * 导出CSV文件
  */
@@ -41,17 +64,8 @@ export async function exportToCSV(chartData: ReportChartData, filename: string)
     csvRows.push(values.join(","))
   }
 
-  // 使用Tauri dialog保存文件
   const csvContent = csvRows.join("\n")
-  const filePath = await save({
-    defaultPath: `${filename}.csv`,
-    filters: [
-      {
-        name: "CSV",
-        extensions: ["csv"],
-      },
-    ],
-  })
+  const filePath = await chooseExportPath(`${filename}.csv`, "CSV", "csv")
 
   if (filePath) {
     await writeTextFile(filePath, csvContent)
@@ -247,16 +261,7 @@ export async function exportToPDF(element: HTMLElement, filename: string): Promi
   const pdfArrayBuffer = await pdfBlob.arrayBuffer()
   const pdfUint8Array = new Uint8Array(pdfArrayBuffer)
 
-  // 使用Tauri dialog保存文件
-  const filePath = await save({
-    defaultPath: `${filename}.pdf`,
-    filters: [
-      {
-        name: "PDF",
-        extensions: ["pdf"],
-      },
-    ],
-  })
+  const filePath = await chooseExportPath(`${filename}.pdf`, "PDF", "pdf")
 
   if (filePath) {
     await writeFile(filePath, pdfUint8Array)
@@ -0,0 +1,27 @@
+import { invoke } from "@tauri-apps/api/core"
+import type { AdbDevice, MetricKey } from "@/types/adb"
+
+/** 后端 settings.json 中前端用到的字段，桌面端与命令行共用同一文件 */
+export interface AppSettings {
+  adb_path: string | null
+  default_metrics: MetricKey[]
+  interval_ms: number
+  /** 设置后导出直接写入该目录，不再弹出保存对话框 */
+  export_dir: string | null
+  last_device: AdbDevice | null
+  last_app: string | null
+}
+
+export interface Selection {
+  device: AdbDevice | null
+  app: string | null
+  metrics: MetricKey[]
+}
+
+export async function getSettings() {
+  return invoke<AppSettings>("tauri_get_settings")
+}
+
+export async function updateSelection(selection: Selection) {
+  return invoke<void>("tauri_update_selection", { selection })
+}
@@ -3,6 +3,7 @@ import { PerfPage } from "@/pages/perf-page"
 import { DeviceInfoPage } from "@/pages/device-info-page"
 import { ReportPage } from "@/pages/report-page"
 import { useDeviceEffects } from "@/hooks/effects/useDeviceEffects"
+import { useSettingsSync } from "@/hooks/effects/useSettingsSync"
 import { useAppStore } from "@/stores/use-app-store"
 import { Tabs, TabsContent } from "@/components/ui/tabs"
 
@@ -11,6 +12,7 @@ export function HomePage() {
 
   // 处理设备变化副作用
   useDeviceEffects()
+  useSettingsSync()
 
   return (
     <div className="flex flex-1 flex-col overflow-hidden">
@@ -11,6 +11,7 @@ import { exportToCSV, exportToPDF } from "@/lib/export-utils"
 import { useRef, useState } from "react"
 import { toast } from "sonner"
 import type { MetricKey } from "@/types/adb"
+import { formatError } from "@/lib/errors"
 
 interface ReportDetailPageProps {
   reportId: number
@@ -84,7 +85,7 @@ export function ReportDetailPage({ reportId, onBack }: ReportDetailPageProps) {
       toast.success("CSV导出成功")
     } catch (error) {
       console.error("导出CSV失败:", error)
-      toast.error(`导出CSV失败: ${error instanceof Error ? error.message : String(error)}`)
+      toast.error(`导出CSV失败: ${formatError(error)}`)
     } finally {
       setExportingCSV(false)
     }
@@ -102,7 +103,7 @@ export function ReportDetailPage({ reportId, onBack }: ReportDetailPageProps) {
       toast.success("PDF导出成功")
     } catch (error) {
       console.error("导出PDF失败:", error)
-      toast.error(`导出PDF失败: ${error instanceof Error ? error.message : String(error)}`)
+      toast.error(`导出PDF失败: ${formatError(error)}`)
     } finally {
       setExportingPDF(false)
     }
@@ -1,13 +1,14 @@
 import { create } from "zustand"
-import { persist } from "zustand/middleware"
 import type { MetricKey, MetricsSnapshot } from "@/types/adb"
 
 const METRIC_KEYS: MetricKey[] = ["cpu", "memory", "power", "traffic", "fps"]
 const DEFAULT_METRICS: MetricKey[] = ["cpu"]
+const DEFAULT_INTERVAL_MS = 1000
 
 interface MonitoringState {
   selectedApp: string
   selectedMetrics: MetricKey[]
+  intervalMs: number
   chartData: Array<Record<string, number | string>>
   startTime: number | null
   running: boolean
@@ -16,6 +17,7 @@ interface MonitoringState {
   metricsError: string | null
   setSelectedApp: (app: string) => void
   setSelectedMetrics: (metrics: MetricKey[]) => void
+  setIntervalMs: (intervalMs: number) => void
   setChartData: (
     data:
       | Array<Record<string, number | string>>
@@ -29,75 +31,46 @@ interface MonitoringState {
   resetChartData: () => void
 }
 
-const STORAGE_KEYS = {
-  app: "PerfX:selected_app",
-  metrics: "PerfX:selected_metrics",
+/** 只保留界面支持的指标，设置中的默认指标可能包含命令行才用的项 */
+export function supportedMetrics(metrics: string[]): MetricKey[] {
+  const valid = metrics.filter((m): m is MetricKey => METRIC_KEYS.includes(m as MetricKey))
+  return valid.length ? valid : DEFAULT_METRICS
 }
 
-function readStoredApp(): string {
-  if (typeof window === "undefined") return ""
-  return window.localStorage.getItem(STORAGE_KEYS.app) ?? ""
-}
-
-function readStoredMetrics(): MetricKey[] {
-  if (typeof window === "undefined") return DEFAULT_METRICS
-  try {
-    const saved = window.localStorage.getItem(STORAGE_KEYS.metrics)
-    if (!saved) return DEFAULT_METRICS
-    const parsed = JSON.parse(saved)
-    if (Array.isArray(parsed)) {
-      const valid = parsed.filter((m: string): m is MetricKey =>
-        METRIC_KEYS.includes(m as MetricKey)
-      )
-      if (valid.length) return valid
-    }
-  } catch {
-    // ignore parse errors
-  }
-  return DEFAULT_METRICS
-}
-
-export const useMonitoringStore = create<MonitoringState>()(
-  persist(
-    set => ({
-      selectedApp: readStoredApp(),
-      selectedMetrics: readStoredMetrics(),
+// 选择的应用与指标由 useSettingsSync 从后端设置恢复并写回
+export const useMonitoringStore = create<MonitoringState>()(set => ({
+  selectedApp: "",
+  selectedMetrics: DEFAULT_METRICS,
+  intervalMs: DEFAULT_INTERVAL_MS,
+  chartData: [],
+  startTime: null,
+  running: false,
+  metrics: null,
+  metricsError: null,
+  setSelectedApp: app => set({ selectedApp: app }),
+  setSelectedMetrics: metrics => set({ selectedMetrics: metrics }),
+  setIntervalMs: intervalMs =>
+    set({ intervalMs: intervalMs > 0 ? intervalMs : DEFAULT_INTERVAL_MS }),
+  setChartData: data =>
+    set(state => ({
+      chartData: typeof data === "function" ? data(state.chartData) : data,
+    })),
+  setStartTime: time => set({ startTime: time }),
+  setRunning: running => {
+    set({ running })
+  },
+  setMetrics: metrics => set({ metrics, metricsError: null }),
+  setMetricsError: error => set({ metricsError: error }),
+  resetChartData: () =>
+    set({
+      chartData: [],
+    }),
+  resetMonitoring: () =>
+    set({
       chartData: [],
       startTime: null,
       running: false,
       metrics: null,
       metricsError: null,
-      setSelectedApp: app => set({ selectedApp: app }),
-      setSelectedMetrics: metrics => set({ selectedMetrics: metrics }),
-      setChartData: data =>
-        set(state => ({
-          chartData: typeof data === "function" ? data(state.chartData) : data,
-        })),
-      setStartTime: time => set({ startTime: time }),
-      setRunning: running => {
-        set({ running })
-      },
-      setMetrics: metrics => set({ metrics, metricsError: null }),
-      setMetricsError: error => set({ metricsError: error }),
-      resetChartData: () =>
-        set({
-          chartData: [],
-        }),
-      resetMonitoring: () =>
-        set({
-          chartData: [],
-          startTime: null,
-          running: false,
-          metrics: null,
-          metricsError: null,
-        }),
     }),
-    {
-      name: "perfX-monitoring-store",
-      partialize: state => ({
-        selectedApp: state.selectedApp,
-        selectedMetrics: state.selectedMetrics,
-      }),
-    }
-  )
-)
+}))
@@ -7,6 +7,7 @@ export type MetricKey =
   | "battery"
   | "battery_temp"
   | "traffic"
+  | "frame_pacing"
 
 export interface AdbDevice {
   id: string
@@ -30,10 +31,22 @@ export interface FrameStats {
   jank_count: number // 帧率不稳定的次数
 }
 
+export interface FramePacing {
+  intervals: number
+  mean_interval_ms: number
+  stddev_ms: number
+  cv_pct: number // 帧间隔变异系数（%）
+  target_interval_ms: number
+  on_target_pct: number // 帧间隔落在目标 ±1ms 内的比例（%）
+}
+
 export interface MetricsSnapshot {
+  schema_version?: number
   fps?: number | null
-  cpu?: number | null
+  cpu?: number | null // 按单核 100% 计，多核时可超过 100
+  cpu_normalized?: number | null // 按核数折算的整机占比（0–100）
   power?: number | null
+  power_mah?: number | null
   memory_mb?: number | null
   network_kbps?: number | null
   network_bps?: number | null
@@ -44,5 +57,13 @@ export interface MetricsSnapshot {
   battery_level?: number | null
   battery_temp_c?: number | null
   frame_stats?: FrameStats | null
+  frame_pacing?: FramePacing | null
   raw?: string | null
+  process_state?: ProcessState // 应用未运行时指标为空属正常，不是采集错误
+  failures?: Record<string, string> // 采集失败的指标 → 错误码
+  extra?: Record<string, MetricValue> // 扩展指标，数值与布尔值会随采样存储
 }
+
+export type MetricValue = number | boolean | string | MetricValue[]
+
+export type ProcessState = "unknown" | "running" | "not_running"
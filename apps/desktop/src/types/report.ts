@@ -15,6 +15,7 @@ export interface Report {
   end_time: number
   duration: number
   created_at: number
+  schema_version?: number // 采样数据格式版本，前端写入的行为 1
 }
 
 export interface CreateReportInput {
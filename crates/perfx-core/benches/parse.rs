@@ -0,0 +1,73 @@
+//! 大体积采集输出的解析耗时：`cargo bench -p perfx-core --features bench --bench parse`。
+//! `tick` 为一个采样周期要解析的全部输出，平均耗时超过 `TICK_BUDGET` 时以失败退出
+
+use criterion::{black_box, criterion_group, Criterion};
+use perfx_core::adb::{
+  metrics::InterfaceFilter,
+  parse::{parse_battery, parse_gfxinfo, parse_meminfo_kb, parse_net_dev, parse_top_cpu},
+};
+use std::time::{Duration, Instant};
+
+/// 单次采样解析全部输出的时间预算（release 构建）
+const TICK_BUDGET: Duration = Duration::from_millis(1);
+const BUDGET_RUNS: u32 = 1_000;
+
+macro_rules! fixture {
+  ($path:literal) => {
+    include_str!(concat!(
+      env!("CARGO_MANIFEST_DIR"),
+      "/tests/fixtures/",
+      $path
+    ))
+  };
+}
+
+const GFXINFO: &str = fixture!("gfxinfo/synthetic_large_framestats.txt");
+const TOP: &str = fixture!("top/synthetic_large.txt");
+const MEMINFO: &str = fixture!("meminfo/pixel_android14.txt");
+const BATTERY: &str = fixture!("battery/pixel_android14.txt");
+const NET_DEV: &str = fixture!("net_dev/pixel_android14.txt");
+const PIDS: &[&str] = &["23456"];
+
+fn tick() {
+  black_box(parse_gfxinfo(black_box(GFXINFO)));
+  black_box(parse_top_cpu(black_box(TOP), PIDS));
+  black_box(parse_meminfo_kb(black_box(MEMINFO)));
+  black_box(parse_battery(black_box(BATTERY)));
+  black_box(parse_net_dev(black_box(NET_DEV), |iface| {
+    InterfaceFilter::Default.matches(iface)
+  }));
+}
+
+fn parsers(c: &mut Criterion) {
+  c.bench_function("gfxinfo_framestats", |b| {
+    b.iter(|| parse_gfxinfo(black_box(GFXINFO)))
+  });
+  c.bench_function("top", |b| b.iter(|| parse_top_cpu(black_box(TOP), PIDS)));
+  c.bench_function("meminfo", |b| {
+    b.iter(|| parse_meminfo_kb(black_box(MEMINFO)))
+  });
+  c.bench_function("tick", |b| b.iter(tick));
+}
+
+/// 预算检查只在优化构建下进行，`cargo test --benches` 的调试构建跳过
+fn check_budget() {
+  if cfg!(debug_assertions) {
+    return;
+  }
+  let start = Instant::now();
+  for _ in 0..BUDGET_RUNS {
+    tick();
+  }
+  let mean = start.elapsed() / BUDGET_RUNS;
+  println!("tick 平均 {mean:?}，预算 {TICK_BUDGET:?}");
+  assert!(mean < TICK_BUDGET, "tick 平均耗时 {mean:?} 超出预算 {TICK_BUDGET:?}");
+}
+
+criterion_group!(benches, parsers);
+
+fn main() {
+  benches();
+  Criterion::default().configure_from_args().final_summary();
+  check_budget();
+}
@@ -0,0 +1,206 @@
+use std::collections::HashSet;
+
+use crate::adb::{
+  command::run_device,
+  error::{AdbError, Result},
+  user::user_args,
+  AppInfo, AppVersion, LaunchTiming,
+};
+
+/// 列出应用；`user` 为空时列出当前用户的应用
+pub fn list_apps(
+  device_id: &str,
+  keyword: Option<&str>,
+  user: Option<u32>,
+) -> Result<Vec<AppInfo>> {
+  let keyword = keyword.map(|k| k.to_ascii_lowercase());
+  let user = user.map(|u| u.to_string());
+  let mut seen = HashSet::new();
+  let mut apps = Vec::new();
+
+  for (filter, is_system) in [
+    ("-3", false), // 第三方安装应用
+    ("-s", true),  // 系统应用
+  ] {
+    let mut args = vec!["shell", "pm", "list", "packages", filter];
+    args.extend(user_args(user.as_deref()));
+    let raw = run_device(device_id, &args)?;
+    for pkg in raw.lines().filter_map(|line| line.strip_prefix("package:")) {
+      let pkg_lower = pkg.to_ascii_lowercase();
+      if let Some(k) = &keyword {
+        if !pkg_lower.contains(k) {
+          continue;
+        }
+      }
+      if !seen.insert(pkg.to_string()) {
+        continue;
+      }
+      apps.push(AppInfo {
+        package: pkg.to_string(),
+        label: None,
+        is_system,
+      });
+    }
+  }
+
+  Ok(apps)
+}
+
+/// 从 dumpsys package 中读取 versionName / versionCode
+pub fn app_version(device_id: &str, package: &str) -> Result<AppVersion> {
+  let raw = run_device(device_id, &["shell", "dumpsys", "package", package])?;
+  let mut version = AppVersion::default();
+
+  for line in raw.lines() {
+    let line = line.trim();
+    if version.version_name.is_none() {
+      if let Some(name) = line.strip_prefix("versionName=") {
+        version.version_name = Some(name.trim().to_string());
+      }
+    }
+    if version.version_code.is_none() {
+      // 格式: "versionCode=123 minSdk=21 targetSdk=33"
+      if let Some(rest) = line.strip_prefix("versionCode=") {
+        version.version_code = rest.split_whitespace().next().and_then(|v| v.parse().ok());
+      }
+    }
+  }
+
+  Ok(version)
+}
+
+/// 解析应用的启动 Activity，返回 "包名/类名"
+pub fn launcher_activity(device_id: &str, package: &str, user: Option<u32>) -> Result<String> {
+  let user = user.map(|u| u.to_string());
+  let mut args = vec!["shell", "cmd", "package", "resolve-activity", "--brief"];
+  args.extend(user_args(user.as_deref()));
+  args.extend(["-c", "android.intent.category.LAUNCHER", package]);
+  let raw = run_device(device_id, &args)?;
+  raw
+    .lines()
+    .rev()
+    .map(str::trim)
+    .find(|line| line.contains('/'))
+    .map(str::to_string)
+    .ok_or_else(|| AdbError::ParseFailed(format!("未找到 {package} 的启动 Activity")))
+}
+
+pub fn force_stop(device_id: &str, package: &str, user: Option<u32>) -> Result<()> {
+  let user = user.map(|u| u.to_string());
+  let mut args = vec!["shell", "am", "force-stop"];
+  args.extend(user_args(user.as_deref()));
+  args.push(package);
+  run_device(device_id, &args)?;
+  Ok(())
+}
+
+/// 结束目标应用与白名单以外正在运行的第三方应用，再用 `am kill-all` 清理后台缓存进程，
+/// 返回被结束的包名
+pub fn stop_background_apps(
+  device_id: &str,
+  target: &str,
+  allowlist: &[String],
+) -> Result<Vec<String>> {
+  let installed = run_device(device_id, &["shell", "pm", "list", "packages", "-3"])?;
+  let running: HashSet<String> = run_device(device_id, &["shell", "ps", "-A", "-o", "NAME"])?
+    .lines()
+    .skip(1)
+    // 子进程名形如 com.example:remote
+    .map(|name| {
+      name
+        .trim()
+        .split(':')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+    })
+    .collect();
+
+  let mut stopped = Vec::new();
+  for pkg in installed
+    .lines()
+    .filter_map(|line| line.trim().strip_prefix("package:"))
+  {
+    if pkg == target || !running.contains(pkg) || allowlist.iter().any(|a| a == pkg) {
+      continue;
+    }
+    force_stop(device_id, pkg, None)?;
+    stopped.push(pkg.to_string());
+  }
+  run_device(device_id, &["shell", "am", "kill-all"])?;
+  Ok(stopped)
+}
+
+/// 清除应用数据与缓存（pm clear），相当于重新安装后的首次启动
+pub fn clear_app_data(device_id: &str, package: &str) -> Result<()> {
+  let raw = run_device(device_id, &["shell", "pm", "clear", package])?;
+  if !raw.trim().ends_with("Success") {
+    return Err(AdbError::CommandFailed(format!(
+      "pm clear {package}: {}",
+      raw.trim()
+    )));
+  }
+  Ok(())
+}
+
+/// 回到桌面，使应用退到后台
+pub fn press_home(device_id: &str) -> Result<()> {
+  run_device(device_id, &["shell", "input", "keyevent", "KEYCODE_HOME"])?;
+  Ok(())
+}
+
+/// 清空页缓存，需要 root；返回是否成功
+pub fn drop_caches(device_id: &str) -> bool {
+  const SCRIPT: &str = "sync; echo 3 > /proc/sys/vm/drop_caches";
+  run_device(device_id, &["shell", SCRIPT]).is_ok()
+    || run_device(device_id, &["shell", "su", "0", "sh", "-c", SCRIPT]).is_ok()
+}
+
+/// 以 `am start -W` 启动 Activity 并等待首帧绘制完成
+/// 启动 Activity，不等待启动完成
+pub fn start_activity(device_id: &str, component: &str, user: Option<u32>) -> Result<()> {
+  let user = user.map(|u| u.to_string());
+  let mut args = vec!["shell", "am", "start"];
+  args.extend(user_args(user.as_deref()));
+  args.extend(["-n", component]);
+  run_device(device_id, &args)?;
+  Ok(())
+}
+
+pub fn start_activity_wait(
+  device_id: &str,
+  component: &str,
+  user: Option<u32>,
+) -> Result<LaunchTiming> {
+  let user = user.map(|u| u.to_string());
+  let mut args = vec!["shell", "am", "start", "-W"];
+  args.extend(user_args(user.as_deref()));
+  args.extend(["-n", component]);
+  let raw = run_device(device_id, &args)?;
+  parse_launch_timing(&raw)
+}
+
+fn parse_launch_timing(raw: &str) -> Result<LaunchTiming> {
+  let mut timing = LaunchTiming::default();
+  for line in raw.lines() {
+    let line = line.trim();
+    if line.starts_with("Error") {
+      return Err(AdbError::CommandFailed(line.to_string()));
+    }
+    let Some((key, value)) = line.split_once(':') else {
+      continue;
+    };
+    let value = value.trim();
+    match key {
+      "LaunchState" => timing.launch_state = Some(value.to_string()),
+      "ThisTime" => timing.this_time_ms = value.parse().ok(),
+      "TotalTime" => timing.total_time_ms = value.parse().ok(),
+      "WaitTime" => timing.wait_time_ms = value.parse().ok(),
+      _ => {}
+    }
+  }
+  if timing.total_time_ms.is_none() && timing.wait_time_ms.is_none() {
+    return Err(AdbError::ParseFailed("am start 未返回启动耗时".into()));
+  }
+  Ok(timing)
+}
@@ -0,0 +1,77 @@
+use crate::adb::{
+  command::spawn_device,
+  error::{AdbError, Result},
+};
+use std::{
+  fs,
+  io::{BufRead, BufReader},
+  path::Path,
+};
+
+/// 运行 `adb bugreport` 并读取生成的 zip；进度通过回调报告（百分比，说明）
+///
+/// 完整 bugreport 通常需要一到数分钟，调用方应在后台线程执行。
+pub fn capture_bugreport(
+  device_id: &str,
+  mut on_progress: impl FnMut(u32, &str),
+) -> Result<Vec<u8>> {
+  let dir = std::env::temp_dir().join(format!(
+    "perfx-bugreport-{}-{}",
+    std::process::id(),
+    device_id.replace([':', '/', '\\'], "_")
+  ));
+  let _ = fs::remove_dir_all(&dir);
+  fs::create_dir_all(&dir).map_err(|e| AdbError::CommandFailed(e.to_string()))?;
+  let result = run_bugreport(device_id, &dir, &mut on_progress);
+  let _ = fs::remove_dir_all(&dir);
+  result
+}
+
+fn run_bugreport(
+  device_id: &str,
+  dir: &Path,
+  on_progress: &mut impl FnMut(u32, &str),
+) -> Result<Vec<u8>> {
+  let mut child = spawn_device(device_id, &["bugreport", &dir.to_string_lossy()])?;
+  let mut last = None;
+  if let Some(stdout) = child.stdout.take() {
+    // 进度行以 \r 覆盖刷新
+    for chunk in BufReader::new(stdout).split(b'\r') {
+      let Ok(chunk) = chunk else {
+        break;
+      };
+      let text = String::from_utf8_lossy(&chunk);
+      for line in text.lines() {
+        if let Some((percent, message)) = parse_progress(line) {
+          if last != Some(percent) {
+            last = Some(percent);
+            on_progress(percent, message);
+          }
+        }
+      }
+    }
+  }
+  let status = child
+    .wait()
+    .map_err(|e| AdbError::CommandFailed(e.to_string()))?;
+  if !status.success() {
+    return Err(AdbError::CommandFailed("bugreport 生成失败".into()));
+  }
+
+  let zip = fs::read_dir(dir)
+    .map_err(|e| AdbError::CommandFailed(e.to_string()))?
+    .filter_map(|entry| entry.ok().map(|e| e.path()))
+    .find(|path| path.extension().is_some_and(|ext| ext == "zip"))
+    .ok_or_else(|| {
+      AdbError::CommandFailed("未生成 bugreport 文件，设备可能不支持 bugreportz".into())
+    })?;
+  on_progress(100, "完成");
+  fs::read(zip).map_err(|e| AdbError::CommandFailed(e.to_string()))
+}
+
+/// 解析 `[ 42%] generating bugreport-xxx.zip`
+fn parse_progress(line: &str) -> Option<(u32, &str)> {
+  let rest = line.trim().strip_prefix('[')?;
+  let (percent, message) = rest.split_once("%]")?;
+  Some((percent.trim().parse().ok()?, message.trim()))
+}
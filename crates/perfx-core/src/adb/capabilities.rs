@@ -0,0 +1,140 @@
+//! 按设备探测各指标能否采集：在当前 ROM 上实际执行一次对应命令，
+//! 界面据此置灰永远拿不到数据的指标，而不是一直显示 N/A
+
+use crate::adb::{
+  command::run_device,
+  error::{AdbError, MetricFailure},
+  frames::fetch_frame_pacing,
+  metrics::{
+    fetch_battery, fetch_cpu, fetch_fps, fetch_memory, fetch_network, fetch_power, fetch_traffic,
+    resolve_pid, InterfaceFilter, MetricKey,
+  },
+  thermal::thermal_snapshot,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 温控探测结果在返回值中的键，不属于 MetricKey
+pub const THERMAL_KEY: &str = "thermal";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Support {
+  Supported,
+  Unsupported,
+  /// 依赖应用运行，目标应用未启动时无法判断
+  Unknown,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Capability {
+  pub status: Support,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub reason: Option<String>,
+}
+
+impl Capability {
+  fn supported() -> Self {
+    Self {
+      status: Support::Supported,
+      reason: None,
+    }
+  }
+
+  fn unsupported(reason: impl Into<String>) -> Self {
+    Self {
+      status: Support::Unsupported,
+      reason: Some(reason.into()),
+    }
+  }
+
+  fn unknown(reason: impl Into<String>) -> Self {
+    Self {
+      status: Support::Unknown,
+      reason: Some(reason.into()),
+    }
+  }
+
+  fn from_result<T>(result: Result<T, AdbError>) -> Self {
+    match result {
+      Ok(_) => Self::supported(),
+      Err(e) => Self::unsupported(e.to_string()),
+    }
+  }
+}
+
+const ALL_METRICS: [MetricKey; 9] = [
+  MetricKey::Fps,
+  MetricKey::Cpu,
+  MetricKey::Power,
+  MetricKey::Memory,
+  MetricKey::Network,
+  MetricKey::Battery,
+  MetricKey::BatteryTemp,
+  MetricKey::Traffic,
+  MetricKey::FramePacing,
+];
+
+/// 逐项探测，键与 MetricKey 的序列化名称一致，另含温控（thermal）
+pub fn probe_capabilities(device_id: &str, package: &str) -> BTreeMap<String, Capability> {
+  let pid = resolve_pid(device_id, package, None).ok();
+  let not_running = || Capability::unknown(format!("{package} 未运行，启动应用后重新检测"));
+  let battery = fetch_battery(device_id);
+  let interfaces = InterfaceFilter::default();
+  let mut result = BTreeMap::new();
+
+  for metric in ALL_METRICS {
+    let capability = match metric {
+      MetricKey::Fps => match (&pid, fetch_fps(device_id, package, None, &mut None)) {
+        (None, Err(_)) => not_running(),
+        (_, r) => Capability::from_result(r),
+      },
+      MetricKey::FramePacing => match (&pid, fetch_frame_pacing(device_id, package, None)) {
+        (None, _) => not_running(),
+        (Some(_), r) => Capability::from_result(r),
+      },
+      MetricKey::Cpu => match &pid {
+        Some(pid) => Capability::from_result(fetch_cpu(device_id, pid)),
+        None => not_running(),
+      },
+      MetricKey::Traffic => match &pid {
+        Some(pid) => Capability::from_result(fetch_traffic(device_id, pid, &interfaces)),
+        None => not_running(),
+      },
+      MetricKey::Memory => match (&pid, fetch_memory(device_id, package, &mut None)) {
+        (None, Err(_)) => not_running(),
+        (_, r) => Capability::from_result(r),
+      },
+      MetricKey::Network => Capability::from_result(fetch_network(device_id, &interfaces)),
+      MetricKey::Power => Capability::from_result(fetch_power(device_id, package)),
+      MetricKey::Battery => match &battery {
+        Ok(b) if b.level.is_some() => Capability::supported(),
+        Ok(_) => Capability::unsupported(MetricFailure::Battery.message()),
+        Err(e) => Capability::unsupported(e.to_string()),
+      },
+      MetricKey::BatteryTemp => match &battery {
+        Ok(b) if b.temp_c.is_some() => Capability::supported(),
+        Ok(_) => Capability::unsupported("dumpsys battery 未提供温度"),
+        Err(e) => Capability::unsupported(e.to_string()),
+      },
+    };
+    result.insert(metric.as_str().to_string(), capability);
+  }
+
+  result.insert(THERMAL_KEY.to_string(), probe_thermal(device_id));
+  result
+}
+
+/// 温区可读即可判断降频；仅有 thermalservice 时也能读取温度状态
+fn probe_thermal(device_id: &str) -> Capability {
+  if let Ok(snapshot) = thermal_snapshot(device_id) {
+    if snapshot.max_temp_c.is_some() {
+      return Capability::supported();
+    }
+  }
+  match run_device(device_id, &["shell", "service", "check", "thermalservice"]) {
+    Ok(out) if out.contains("found") && !out.contains("not found") => Capability::supported(),
+    Ok(_) => Capability::unsupported("温区不可读，且设备没有 thermalservice"),
+    Err(e) => Capability::unsupported(e.to_string()),
+  }
+}
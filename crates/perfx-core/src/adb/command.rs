@@ -0,0 +1,267 @@
+use crate::adb::error::{AdbError, Result};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::Serialize;
+use std::{
+  collections::VecDeque,
+  io::Read,
+  path::Path,
+  process::{Child, Command, Stdio},
+  sync::Mutex,
+  thread,
+  time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// 保留的最近命令条数，供诊断信息展示
+const RECENT_LIMIT: usize = 50;
+/// 带超时执行时检查进程是否退出的间隔
+const EXIT_POLL: Duration = Duration::from_millis(20);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AdbBinary {
+  pub custom: Option<String>,
+  pub bundled: Option<String>,
+}
+
+static ADB_BIN: OnceCell<Mutex<AdbBinary>> = OnceCell::new();
+static RECENT: Lazy<Mutex<VecDeque<CommandRecord>>> =
+  Lazy::new(|| Mutex::new(VecDeque::with_capacity(RECENT_LIMIT)));
+
+/// 一次 adb 调用的记录
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandRecord {
+  pub at_ms: u64,
+  pub args: Vec<String>,
+  pub duration_ms: u64,
+  pub ok: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+}
+
+fn adb_bin() -> &'static Mutex<AdbBinary> {
+  ADB_BIN.get_or_init(|| Mutex::new(AdbBinary { custom: None, bundled: None }))
+}
+
+pub fn set_adb_path(path: Option<String>) {
+  if let Ok(mut guard) = adb_bin().lock() {
+    guard.custom = normalize_path(path);
+  }
+}
+
+pub fn set_bundled_adb_path(path: Option<String>) {
+  if let Ok(mut guard) = adb_bin().lock() {
+    guard.bundled = normalize_path(path).filter(|p| Path::new(p).exists());
+  }
+}
+
+/// 当前配置的 adb 路径（手动指定与内置）
+pub fn adb_binary() -> Option<AdbBinary> {
+  adb_bin().lock().ok().map(|g| g.clone())
+}
+
+/// 最近的 adb 调用，按时间先后排列
+pub fn recent_commands() -> Vec<CommandRecord> {
+  RECENT
+    .lock()
+    .map(|r| r.iter().cloned().collect())
+    .unwrap_or_default()
+}
+
+pub fn current_adb_path() -> String {
+  resolve_adb_path().unwrap_or_else(|_| "adb".to_string())
+}
+
+fn normalize_path(path: Option<String>) -> Option<String> {
+  path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty())
+}
+
+pub fn run_host(args: &[&str]) -> Result<String> {
+  let adb_path = resolve_adb_path()?;
+  run_raw(&adb_path, args)
+}
+
+pub fn run_device(device_id: &str, args: &[&str]) -> Result<String> {
+  let mut full = Vec::with_capacity(args.len() + 2);
+  full.push("-s");
+  full.push(device_id);
+  full.extend_from_slice(args);
+  let adb_path = resolve_adb_path()?;
+  run_raw(&adb_path, &full)
+}
+
+/// 同 `run_device`，超过 `timeout` 仍未结束时杀掉 adb 进程并返回错误，
+/// 用于执行用户配置的命令，避免卡住的命令拖住整个采集
+pub fn run_device_timeout(device_id: &str, args: &[&str], timeout: Duration) -> Result<String> {
+  let mut full = Vec::with_capacity(args.len() + 2);
+  full.push("-s");
+  full.push(device_id);
+  full.extend_from_slice(args);
+  let adb_path = resolve_adb_path()?;
+  let started = Instant::now();
+  let result = exec_timeout(&adb_path, &full, timeout);
+  record(&full, started, result.as_ref().err());
+  result.map(|out| String::from_utf8_lossy(&out).to_string())
+}
+
+/// shell 已是 root（adb root）时直接执行，否则经 su 执行
+pub fn run_root(device_id: &str, script: &str) -> Result<String> {
+  let uid = run_device(device_id, &["shell", "id", "-u"])?;
+  if uid.trim() == "0" {
+    return run_device(device_id, &["shell", script]);
+  }
+  // adb 会把参数拼接后交给设备 shell，脚本需整体加引号
+  let su = format!("su 0 sh -c '{script}'");
+  run_device(device_id, &["shell", &su])
+    .map_err(|e| AdbError::CommandFailed(format!("需要 root 权限: {e}")))
+}
+
+/// 以二进制形式返回输出，用于 screencap 等非文本命令
+pub fn run_device_bytes(device_id: &str, args: &[&str]) -> Result<Vec<u8>> {
+  let mut full = Vec::with_capacity(args.len() + 2);
+  full.push("-s");
+  full.push(device_id);
+  full.extend_from_slice(args);
+  let adb_path = resolve_adb_path()?;
+  run_output(&adb_path, &full)
+}
+
+/// 启动持续输出的设备命令（如 logcat），由调用方读取 stdout 并负责结束进程
+pub fn spawn_device(device_id: &str, args: &[&str]) -> Result<Child> {
+  let adb_path = resolve_adb_path()?;
+  let mut cmd = Command::new(adb_path);
+  cmd.args(["-s", device_id])
+    .args(args)
+    .stdout(Stdio::piped())
+    .stderr(Stdio::null());
+
+  #[cfg(target_os = "windows")]
+  {
+    use std::os::windows::process::CommandExt;
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+  }
+
+  cmd.spawn().map_err(|_| AdbError::NotFound)
+}
+
+fn run_raw(bin: &str, args: &[&str]) -> Result<String> {
+  run_output(bin, args).map(|out| String::from_utf8_lossy(&out).to_string())
+}
+
+fn run_output(bin: &str, args: &[&str]) -> Result<Vec<u8>> {
+  let started = Instant::now();
+  let result = exec(bin, args);
+  record(args, started, result.as_ref().err());
+  result
+}
+
+fn record(args: &[&str], started: Instant, error: Option<&AdbError>) {
+  let at_ms = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_millis() as u64)
+    .unwrap_or(0);
+  let entry = CommandRecord {
+    at_ms,
+    args: args.iter().map(|a| a.to_string()).collect(),
+    duration_ms: started.elapsed().as_millis() as u64,
+    ok: error.is_none(),
+    error: error.map(|e| e.to_string()),
+  };
+  if let Ok(mut recent) = RECENT.lock() {
+    if recent.len() == RECENT_LIMIT {
+      recent.pop_front();
+    }
+    recent.push_back(entry);
+  }
+}
+
+fn exec(bin: &str, args: &[&str]) -> Result<Vec<u8>> {
+  let mut cmd = Command::new(bin);
+  cmd.args(args)
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped());
+
+  // 在Windows上避免弹出命令窗口
+  #[cfg(target_os = "windows")]
+  {
+    use std::os::windows::process::CommandExt;
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+  }
+
+  let output = cmd.output().map_err(|_| AdbError::NotFound)?;
+
+  if !output.status.success() {
+    let err = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    return Err(AdbError::CommandFailed(err));
+  }
+
+  Ok(output.stdout)
+}
+
+fn exec_timeout(bin: &str, args: &[&str], timeout: Duration) -> Result<Vec<u8>> {
+  let deadline = Instant::now() + timeout;
+  let mut cmd = Command::new(bin);
+  cmd.args(args)
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped());
+
+  #[cfg(target_os = "windows")]
+  {
+    use std::os::windows::process::CommandExt;
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+  }
+
+  let mut child = cmd.spawn().map_err(|_| AdbError::NotFound)?;
+  // 输出可能超过管道缓冲，边运行边读取，否则子进程会阻塞在写入上
+  let stdout = child.stdout.take().map(read_in_background);
+  let stderr = child.stderr.take().map(read_in_background);
+  let status = loop {
+    match child.try_wait() {
+      Ok(Some(status)) => break status,
+      Ok(None) if Instant::now() >= deadline => {
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(AdbError::CommandFailed(format!(
+          "超过 {} 毫秒未结束，已中止",
+          timeout.as_millis()
+        )));
+      }
+      Ok(None) => thread::sleep(EXIT_POLL),
+      Err(e) => return Err(AdbError::CommandFailed(e.to_string())),
+    }
+  };
+  let collect = |reader: Option<thread::JoinHandle<Vec<u8>>>| {
+    reader.and_then(|r| r.join().ok()).unwrap_or_default()
+  };
+  if !status.success() {
+    let err = String::from_utf8_lossy(&collect(stderr)).trim().to_string();
+    return Err(AdbError::CommandFailed(err));
+  }
+  Ok(collect(stdout))
+}
+
+fn read_in_background<R: Read + Send + 'static>(mut reader: R) -> thread::JoinHandle<Vec<u8>> {
+  thread::spawn(move || {
+    let mut buf = Vec::new();
+    let _ = reader.read_to_end(&mut buf);
+    buf
+  })
+}
+
+fn resolve_adb_path() -> Result<String> {
+  if let Ok(guard) = adb_bin().lock() {
+    if let Some(path) = guard.custom.clone() {
+      return Ok(path);
+    }
+    if let Some(path) = guard.bundled.clone() {
+      return Ok(path);
+    }
+  }
+
+  Err(AdbError::NotFound)
+}
+
+#[allow(dead_code)]
+pub fn try_ping_server() -> Result<()> {
+  let _ = run_host(&["start-server"]).map_err(|e| AdbError::Client(format!("{e}")))?;
+  Ok(())
+}
+
@@ -0,0 +1,80 @@
+use crate::adb::{
+  command::run_device,
+  error::Result,
+  settings::{get_setting, Namespace},
+};
+use serde::{Deserialize, Serialize};
+
+/// 可切换的网络开关
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Radio {
+  Wifi,
+  Data,
+  Airplane,
+}
+
+impl Radio {
+  pub fn label(self) -> &'static str {
+    match self {
+      Self::Wifi => "Wi-Fi",
+      Self::Data => "移动数据",
+      Self::Airplane => "飞行模式",
+    }
+  }
+}
+
+/// 各开关当前状态，读取失败的项为空
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectivityState {
+  pub wifi: Option<bool>,
+  pub data: Option<bool>,
+  pub airplane: Option<bool>,
+}
+
+impl ConnectivityState {
+  pub fn get(&self, radio: Radio) -> Option<bool> {
+    match radio {
+      Radio::Wifi => self.wifi,
+      Radio::Data => self.data,
+      Radio::Airplane => self.airplane,
+    }
+  }
+}
+
+pub fn connectivity_state(device_id: &str) -> Result<ConnectivityState> {
+  // wifi_on 为 2 表示飞行模式下保持开启，同样视为开启
+  let flag = |key: &str| -> Result<Option<bool>> {
+    Ok(
+      get_setting(device_id, Namespace::Global, key)?
+        .and_then(|v| v.parse::<u8>().ok())
+        .map(|v| v != 0),
+    )
+  };
+  Ok(ConnectivityState {
+    wifi: flag("wifi_on")?,
+    data: flag("mobile_data")?,
+    airplane: flag("airplane_mode_on")?,
+  })
+}
+
+pub fn set_radio(device_id: &str, radio: Radio, enabled: bool) -> Result<()> {
+  let action = if enabled { "enable" } else { "disable" };
+  let args: &[&str] = match radio {
+    Radio::Wifi => &["shell", "svc", "wifi", action],
+    Radio::Data => &["shell", "svc", "data", action],
+    Radio::Airplane => &["shell", "cmd", "connectivity", "airplane-mode", action],
+  };
+  run_device(device_id, args)?;
+  Ok(())
+}
+
+/// 按保存的状态写回；先处理飞行模式，否则关闭飞行模式时会重新打开其他开关
+pub fn restore_connectivity(device_id: &str, state: &ConnectivityState) -> Result<()> {
+  for radio in [Radio::Airplane, Radio::Wifi, Radio::Data] {
+    if let Some(enabled) = state.get(radio) {
+      set_radio(device_id, radio, enabled)?;
+    }
+  }
+  Ok(())
+}
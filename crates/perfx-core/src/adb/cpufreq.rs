@@ -0,0 +1,178 @@
+//! CPU 调度器与频率锁定（需要 root），用于 A/B 对比测试时消除 DVFS 带来的波动
+
+use crate::adb::{
+  command::{run_device, run_root},
+  error::{AdbError, Result},
+};
+use serde::{Deserialize, Serialize};
+
+const CPUFREQ_DIR: &str = "/sys/devices/system/cpu/cpufreq";
+
+// 每个策略一行，字段以 | 分隔
+const READ_SCRIPT: &str = "for p in /sys/devices/system/cpu/cpufreq/policy*; do \
+  echo \"${p##*/}|$(cat $p/related_cpus)|$(cat $p/scaling_governor)|$(cat $p/scaling_min_freq)|\
+$(cat $p/scaling_max_freq)|$(cat $p/cpuinfo_min_freq)|$(cat $p/cpuinfo_max_freq)|\
+$(cat $p/scaling_available_governors 2>/dev/null)|$(cat $p/scaling_available_frequencies 2>/dev/null)\"; \
+  done";
+
+/// 一个 cpufreq 策略（簇）的当前配置，频率单位 kHz
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CpuPolicy {
+  /// 策略目录名，如 policy0
+  pub name: String,
+  pub cpus: Vec<u32>,
+  pub governor: String,
+  pub min_khz: u64,
+  pub max_khz: u64,
+  pub hw_min_khz: u64,
+  pub hw_max_khz: u64,
+  #[serde(default)]
+  pub available_governors: Vec<String>,
+  #[serde(default)]
+  pub available_khz: Vec<u64>,
+}
+
+/// 锁定方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum CpuLock {
+  /// 切换到 performance 调度器
+  Performance,
+  /// 最低频率提到硬件最高频率
+  Max,
+  /// 锁定到不超过给定值的最高可用频率
+  Fixed { khz: u64 },
+}
+
+impl CpuLock {
+  pub fn label(&self) -> String {
+    match self {
+      Self::Performance => "performance 调度器".into(),
+      Self::Max => "锁定最高频率".into(),
+      Self::Fixed { khz } => format!("锁定 {} MHz", khz / 1000),
+    }
+  }
+}
+
+pub fn cpu_policies(device_id: &str) -> Result<Vec<CpuPolicy>> {
+  let raw = run_device(device_id, &["shell", READ_SCRIPT])?;
+  let policies: Vec<CpuPolicy> = raw.lines().filter_map(parse_policy).collect();
+  if policies.is_empty() {
+    return Err(AdbError::ParseFailed("未找到 cpufreq 策略".into()));
+  }
+  Ok(policies)
+}
+
+fn parse_policy(line: &str) -> Option<CpuPolicy> {
+  let fields: Vec<&str> = line.trim().split('|').collect();
+  if fields.len() < 7 || !fields[0].starts_with("policy") {
+    return None;
+  }
+  let khz = |s: &str| s.trim().parse::<u64>().ok();
+  Some(CpuPolicy {
+    name: fields[0].to_string(),
+    cpus: fields[1]
+      .split_whitespace()
+      .filter_map(|c| c.parse().ok())
+      .collect(),
+    governor: fields[2].trim().to_string(),
+    min_khz: khz(fields[3])?,
+    max_khz: khz(fields[4])?,
+    hw_min_khz: khz(fields[5])?,
+    hw_max_khz: khz(fields[6])?,
+    available_governors: fields
+      .get(7)
+      .map(|s| s.split_whitespace().map(str::to_string).collect())
+      .unwrap_or_default(),
+    available_khz: fields
+      .get(8)
+      .map(|s| {
+        s.split_whitespace()
+          .filter_map(|f| f.parse().ok())
+          .collect()
+      })
+      .unwrap_or_default(),
+  })
+}
+
+/// 按锁定方式写入各策略，返回写入后的配置
+pub fn apply_cpu_lock(device_id: &str, lock: CpuLock) -> Result<Vec<CpuPolicy>> {
+  let policies = cpu_policies(device_id)?;
+  let mut writes = Vec::new();
+  for policy in &policies {
+    let dir = format!("{CPUFREQ_DIR}/{}", policy.name);
+    match lock {
+      CpuLock::Performance => {
+        if !policy.available_governors.is_empty()
+          && !policy
+            .available_governors
+            .iter()
+            .any(|g| g == "performance")
+        {
+          return Err(AdbError::CommandFailed(format!(
+            "{} 不支持 performance 调度器",
+            policy.name
+          )));
+        }
+        writes.push(write(&dir, "scaling_governor", "performance"));
+      }
+      CpuLock::Max => {
+        let max = policy.hw_max_khz.to_string();
+        writes.push(write(&dir, "scaling_max_freq", &max));
+        writes.push(write(&dir, "scaling_min_freq", &max));
+      }
+      CpuLock::Fixed { khz } => {
+        let target = nearest_khz(policy, khz).to_string();
+        // 先放开上限，否则目标高于当前上限时写下限会失败
+        writes.push(write(
+          &dir,
+          "scaling_max_freq",
+          &policy.hw_max_khz.to_string(),
+        ));
+        writes.push(write(&dir, "scaling_min_freq", &target));
+        writes.push(write(&dir, "scaling_max_freq", &target));
+      }
+    }
+  }
+  run_root(device_id, &writes.join(" && "))?;
+  cpu_policies(device_id)
+}
+
+/// 写回之前读取的配置
+pub fn restore_cpu_policies(device_id: &str, policies: &[CpuPolicy]) -> Result<()> {
+  let mut writes = Vec::new();
+  for policy in policies {
+    let dir = format!("{CPUFREQ_DIR}/{}", policy.name);
+    // 先把下限降到硬件最低，避免原上限低于当前下限时写入失败
+    writes.push(write(
+      &dir,
+      "scaling_min_freq",
+      &policy.hw_min_khz.to_string(),
+    ));
+    writes.push(write(&dir, "scaling_max_freq", &policy.max_khz.to_string()));
+    writes.push(write(&dir, "scaling_min_freq", &policy.min_khz.to_string()));
+    writes.push(write(&dir, "scaling_governor", &policy.governor));
+  }
+  if writes.is_empty() {
+    return Ok(());
+  }
+  run_root(device_id, &writes.join(" && "))?;
+  Ok(())
+}
+
+/// 不超过目标的最高可用频率；没有可用频率表时直接按硬件范围截断
+fn nearest_khz(policy: &CpuPolicy, khz: u64) -> u64 {
+  policy
+    .available_khz
+    .iter()
+    .copied()
+    .filter(|f| *f <= khz)
+    .max()
+    .or_else(|| policy.available_khz.iter().copied().min())
+    .unwrap_or(khz)
+    .clamp(policy.hw_min_khz, policy.hw_max_khz)
+}
+
+fn write(dir: &str, file: &str, value: &str) -> String {
+  format!("echo {value} > {dir}/{file}")
+}
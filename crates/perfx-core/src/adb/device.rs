@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::adb::{
+  command::{run_device, run_host},
+  error::Result,
+  parse::parse_cpu_list,
+  DeviceInfo, DeviceSnapshot,
+};
+use once_cell::sync::Lazy;
+
+/// 各设备的 SDK 版本，采集时按版本选择解析方式
+static SDK_LEVELS: Lazy<Mutex<HashMap<String, u32>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+/// 各设备的 CPU 核数，用于把 top 的占用折算为整机百分比
+static CPU_CORES: Lazy<Mutex<HashMap<String, u32>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn list_devices() -> Result<Vec<DeviceInfo>> {
+  // 直接使用 adb CLI，避免 adb_client 与本地 server 通信阻塞
+  list_with_cli()
+}
+
+fn list_with_cli() -> Result<Vec<DeviceInfo>> {
+  let raw = run_host(&["devices", "-l"])?;
+  let mut devices = Vec::new();
+
+  for line in raw.lines() {
+    if line.starts_with("List of devices") || line.trim().is_empty() {
+      continue;
+    }
+
+    let mut parts = line.split_whitespace();
+    let id = match parts.next() {
+      Some(v) => v.to_string(),
+      None => continue,
+    };
+
+    let state = parts.next().unwrap_or("unknown").to_string();
+    let mut model = None;
+
+    for part in parts {
+      if let Some(value) = part.strip_prefix("model:") {
+        model = Some(value.to_string());
+      }
+    }
+
+    devices.push(DeviceInfo { id, model, state });
+  }
+
+  Ok(devices)
+}
+
+/// 读取全部系统属性，解析 `[key]: [value]` 格式
+pub fn get_props(device_id: &str) -> Result<HashMap<String, String>> {
+  let raw = run_device(device_id, &["shell", "getprop"])?;
+  let mut props = HashMap::new();
+
+  for line in raw.lines() {
+    let Some((key, value)) = line.trim().split_once("]: [") else {
+      continue;
+    };
+    let key = key.trim_start_matches('[');
+    let value = value.trim_end_matches(']');
+    props.insert(key.to_string(), value.to_string());
+  }
+
+  Ok(props)
+}
+
+/// 设备的 SDK 版本（ro.build.version.sdk），读取失败时不缓存，下次重试
+pub fn sdk_level(device_id: &str) -> Option<u32> {
+  if let Some(sdk) = SDK_LEVELS
+    .lock()
+    .ok()
+    .and_then(|levels| levels.get(device_id).copied())
+  {
+    return Some(sdk);
+  }
+  let sdk = run_device(device_id, &["shell", "getprop", "ro.build.version.sdk"])
+    .ok()?
+    .trim()
+    .parse()
+    .ok()?;
+  if let Ok(mut levels) = SDK_LEVELS.lock() {
+    levels.insert(device_id.to_string(), sdk);
+  }
+  Some(sdk)
+}
+
+/// 设备的 CPU 核数（/sys/devices/system/cpu/present），读取失败时不缓存
+pub fn cpu_cores(device_id: &str) -> Option<u32> {
+  if let Some(cores) = CPU_CORES
+    .lock()
+    .ok()
+    .and_then(|cores| cores.get(device_id).copied())
+  {
+    return Some(cores);
+  }
+  let raw = run_device(
+    device_id,
+    &["shell", "cat", "/sys/devices/system/cpu/present"],
+  )
+  .ok()?;
+  let cores = parse_cpu_list(&raw).filter(|c| *c > 0)?;
+  if let Ok(mut cached) = CPU_CORES.lock() {
+    cached.insert(device_id.to_string(), cores);
+  }
+  Some(cores)
+}
+
+pub fn device_snapshot(device_id: &str) -> Result<DeviceSnapshot> {
+  let props = get_props(device_id)?;
+  let prop = |key: &str| props.get(key).filter(|v| !v.is_empty()).cloned();
+
+  Ok(DeviceSnapshot {
+    manufacturer: prop("ro.product.manufacturer"),
+    brand: prop("ro.product.brand"),
+    model: prop("ro.product.model"),
+    device: prop("ro.product.device"),
+    android_release: prop("ro.build.version.release"),
+    sdk_int: prop("ro.build.version.sdk").and_then(|v| v.parse().ok()),
+    build_id: prop("ro.build.display.id"),
+    build_fingerprint: prop("ro.build.fingerprint"),
+    abi: prop("ro.product.cpu.abi"),
+    hardware: prop("ro.board.platform").or_else(|| prop("ro.hardware")),
+  })
+}
+
@@ -0,0 +1,116 @@
+//! 显示参数：屏幕亮度、分辨率与像素密度，读取当前值、写入与按原值恢复
+
+use crate::adb::{
+  command::run_device,
+  error::Result,
+  settings::{delete_setting, get_setting, put_setting, Namespace, BRIGHTNESS, BRIGHTNESS_MODE},
+};
+use serde::{Deserialize, Serialize};
+
+/// 需要统一的显示参数，为空的项不修改
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisplaySettings {
+  /// 0~255，写入时关闭自动亮度
+  #[serde(default)]
+  pub brightness: Option<u8>,
+  /// 分辨率 `(宽, 高)`，像素
+  #[serde(default)]
+  pub size: Option<(u32, u32)>,
+  #[serde(default)]
+  pub density: Option<u32>,
+}
+
+impl DisplaySettings {
+  pub fn label(&self) -> String {
+    let mut parts = Vec::new();
+    if let Some(level) = self.brightness {
+      parts.push(format!("亮度 {level}"));
+    }
+    if let Some((width, height)) = self.size {
+      parts.push(format!("{width}x{height}"));
+    }
+    if let Some(density) = self.density {
+      parts.push(format!("{density} dpi"));
+    }
+    parts.join("，")
+  }
+}
+
+/// 设备当前的显示参数；覆盖值为空表示使用物理值
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisplayState {
+  pub brightness: Option<String>,
+  pub brightness_mode: Option<String>,
+  pub physical_size: Option<(u32, u32)>,
+  pub override_size: Option<(u32, u32)>,
+  pub physical_density: Option<u32>,
+  pub override_density: Option<u32>,
+}
+
+pub fn display_state(device_id: &str) -> Result<DisplayState> {
+  let size = run_device(device_id, &["shell", "wm", "size"])?;
+  let density = run_device(device_id, &["shell", "wm", "density"])?;
+  Ok(DisplayState {
+    brightness: get_setting(device_id, Namespace::System, BRIGHTNESS)?,
+    brightness_mode: get_setting(device_id, Namespace::System, BRIGHTNESS_MODE)?,
+    physical_size: labeled(&size, "Physical size").and_then(parse_size),
+    override_size: labeled(&size, "Override size").and_then(parse_size),
+    physical_density: labeled(&density, "Physical density").and_then(|v| v.parse().ok()),
+    override_density: labeled(&density, "Override density").and_then(|v| v.parse().ok()),
+  })
+}
+
+pub fn apply_display(device_id: &str, settings: &DisplaySettings) -> Result<()> {
+  if let Some(level) = settings.brightness {
+    put_setting(device_id, Namespace::System, BRIGHTNESS_MODE, "0")?;
+    put_setting(device_id, Namespace::System, BRIGHTNESS, &level.to_string())?;
+  }
+  if let Some((width, height)) = settings.size {
+    run_device(
+      device_id,
+      &["shell", "wm", "size", &format!("{width}x{height}")],
+    )?;
+  }
+  if let Some(density) = settings.density {
+    run_device(device_id, &["shell", "wm", "density", &density.to_string()])?;
+  }
+  Ok(())
+}
+
+/// 按之前读取的状态写回，没有覆盖值时重置为物理值
+pub fn restore_display(device_id: &str, state: &DisplayState) -> Result<()> {
+  // 亮度模式需在亮度之后写回，否则自动亮度会被手动值覆盖
+  for (key, value) in [
+    (BRIGHTNESS, &state.brightness),
+    (BRIGHTNESS_MODE, &state.brightness_mode),
+  ] {
+    match value {
+      Some(value) => put_setting(device_id, Namespace::System, key, value)?,
+      None => delete_setting(device_id, Namespace::System, key)?,
+    }
+  }
+  let size = match state.override_size {
+    Some((width, height)) => format!("{width}x{height}"),
+    None => "reset".to_string(),
+  };
+  run_device(device_id, &["shell", "wm", "size", &size])?;
+  let density = match state.override_density {
+    Some(density) => density.to_string(),
+    None => "reset".to_string(),
+  };
+  run_device(device_id, &["shell", "wm", "density", &density])?;
+  Ok(())
+}
+
+/// `wm size` / `wm density` 输出中 `标签: 值` 行的值
+fn labeled<'a>(raw: &'a str, label: &str) -> Option<&'a str> {
+  raw.lines().find_map(|line| {
+    let (key, value) = line.split_once(':')?;
+    (key.trim() == label).then(|| value.trim())
+  })
+}
+
+fn parse_size(value: &str) -> Option<(u32, u32)> {
+  let (width, height) = value.split_once('x')?;
+  Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+}
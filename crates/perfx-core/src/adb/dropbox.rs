@@ -0,0 +1,107 @@
+use crate::adb::{
+  command::run_device,
+  error::{AdbError, Result},
+};
+use serde::{Deserialize, Serialize};
+
+/// 与应用崩溃 / ANR 相关的 DropBox tag
+pub const APP_TAGS: &[&str] = &[
+  "data_app_crash",
+  "data_app_anr",
+  "data_app_native_crash",
+  "system_tombstone",
+];
+const ENTRY_SEPARATOR: &str = "========================================";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DropboxEntry {
+  pub tag: String,
+  /// 设备本地时间，`YYYY-MM-DD HH:MM:SS`
+  pub time: String,
+  /// 换算后的 Unix 时间（秒）
+  pub epoch_secs: i64,
+  pub text: String,
+}
+
+/// 读取某个 tag 的全部 DropBox 条目，压缩条目由 dumpsys 解压后输出
+pub fn dropbox_entries(device_id: &str, tag: &str) -> Result<Vec<DropboxEntry>> {
+  let offset = clock_offset(device_id)?;
+  let raw = run_device(device_id, &["shell", "dumpsys", "dropbox", "--print", tag])?;
+  Ok(parse_entries(&raw, tag, offset))
+}
+
+/// 设备本地时间相对 UTC 的偏移（秒），DropBox 只记录本地时间
+fn clock_offset(device_id: &str) -> Result<i64> {
+  let raw = run_device(device_id, &["shell", "date '+%s %Y-%m-%d %H:%M:%S'"])?;
+  let (epoch, local) = raw
+    .trim()
+    .split_once(' ')
+    .ok_or_else(|| AdbError::ParseFailed(format!("设备时间格式错误: {}", raw.trim())))?;
+  let epoch: i64 = epoch
+    .parse()
+    .map_err(|_| AdbError::ParseFailed(format!("设备时间格式错误: {}", raw.trim())))?;
+  let local = civil_secs(local)
+    .ok_or_else(|| AdbError::ParseFailed(format!("设备时间格式错误: {}", raw.trim())))?;
+  Ok(local - epoch)
+}
+
+/// 条目以分隔线开始，首行为 `2024-05-01 10:00:00[.123] tag (text, 1234 bytes)`
+fn parse_entries(raw: &str, tag: &str, offset: i64) -> Vec<DropboxEntry> {
+  raw
+    .split(ENTRY_SEPARATOR)
+    .filter_map(|block| {
+      let block = block.trim_start_matches(['\r', '\n']);
+      let (header, text) = block.split_once('\n').unwrap_or((block, ""));
+      let mut fields = header.split_whitespace();
+      let date = fields.next()?;
+      let time = fields.next()?;
+      if fields.next() != Some(tag) {
+        return None;
+      }
+      let time = format!("{date} {}", time.split('.').next().unwrap_or(time));
+      let epoch_secs = civil_secs(&time)? - offset;
+      Some(DropboxEntry {
+        tag: tag.to_string(),
+        time,
+        epoch_secs,
+        text: text.trim_end().to_string(),
+      })
+    })
+    .collect()
+}
+
+/// 把 `YYYY-MM-DD HH:MM:SS` 按 UTC 换算为秒数
+fn civil_secs(value: &str) -> Option<i64> {
+  let (date, time) = value.trim().split_once(' ')?;
+  let mut date = date.split('-').map(|v| v.parse::<i64>().ok());
+  let (y, m, d) = (date.next()??, date.next()??, date.next()??);
+  let mut time = time.split(':').map(|v| v.parse::<i64>().ok());
+  let (hh, mm, ss) = (time.next()??, time.next()??, time.next()??);
+
+  // 公历日期转天数，算法见 Howard Hinnant 的 days_from_civil
+  let y = if m <= 2 { y - 1 } else { y };
+  let era = y.div_euclid(400);
+  let yoe = y - era * 400;
+  let doy = (153 * ((m + 9) % 12) + 2) / 5 + d - 1;
+  let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+  let days = era * 146_097 + doe - 719_468;
+  Some(days * 86_400 + hh * 3_600 + mm * 60 + ss)
+}
+
+/// 条目是否属于目标应用（含其子进程）
+pub fn entry_matches(entry: &DropboxEntry, package: &str) -> bool {
+  let sub_process = format!("{package}:");
+  entry.text.lines().map(str::trim).any(|line| {
+    let process = line
+      .strip_prefix("Process: ")
+      .or_else(|| line.strip_prefix("Cmd line: "));
+    if let Some(process) = process {
+      return process == package || process.starts_with(&sub_process);
+    }
+    // tombstone: "pid: 123, tid: 456, name: RenderThread  >>> com.x <<<"
+    line
+      .split_once(">>> ")
+      .and_then(|(_, rest)| rest.split_once(" <<<"))
+      .is_some_and(|(name, _)| name == package || name.starts_with(&sub_process))
+  })
+}
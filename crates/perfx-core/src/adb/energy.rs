@@ -0,0 +1,262 @@
+use crate::adb::{
+  command::{run_device, run_device_bytes},
+  error::{AdbError, Result},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, io::Cursor, io::Read};
+use zip::ZipArchive;
+
+const FRAMEWORK_RES: &str = "/system/framework/framework-res.apk";
+const PROFILE_ENTRY: &str = "res/xml/power_profile.xml";
+
+// 一次读取各频率策略的 time_in_state、各核忙闲 jiffies 与屏幕状态
+const PROBE_SCRIPT: &str = "for p in /sys/devices/system/cpu/cpufreq/policy*; do \
+  echo \"policy $(cat $p/related_cpus 2>/dev/null)\"; \
+  cat $p/stats/time_in_state 2>/dev/null; done; \
+  grep '^cpu[0-9]' /proc/stat; \
+  echo \"screen $(dumpsys power | grep -m1 -o 'mWakefulness=[A-Za-z]*') \
+  $(settings get system screen_brightness)\"";
+
+/// 设备 framework 中的 power_profile.xml，数值单位为 mA
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PowerProfile {
+  pub items: BTreeMap<String, f64>,
+  pub arrays: BTreeMap<String, Vec<f64>>,
+}
+
+impl PowerProfile {
+  /// 依次尝试多个键名，兼容新旧版本的命名
+  pub fn item(&self, names: &[&str]) -> Option<f64> {
+    names.iter().find_map(|name| self.items.get(*name).copied())
+  }
+
+  pub fn array(&self, name: &str) -> Option<&[f64]> {
+    self.arrays.get(name).map(Vec::as_slice)
+  }
+}
+
+/// 一个 cpufreq 策略（簇）的频率驻留
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolicyResidency {
+  pub cpus: Vec<u32>,
+  /// `(频率 kHz, 累计驻留 10ms)`
+  pub time_in_state: Vec<(u64, u64)>,
+}
+
+/// 一次能耗相关计数器快照，计数器均为开机以来的累计值
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EnergyProbe {
+  pub t_ms: u64,
+  pub policies: Vec<PolicyResidency>,
+  /// 各核 `(忙碌, 总计)` jiffies
+  pub cpu_jiffies: BTreeMap<u32, (u64, u64)>,
+  pub screen_on: Option<bool>,
+  /// 屏幕亮度 0–1
+  pub brightness: Option<f64>,
+}
+
+/// 从 framework-res.apk 中提取并解析编译后的 power_profile.xml
+pub fn power_profile(device_id: &str) -> Result<PowerProfile> {
+  let apk = run_device_bytes(device_id, &["exec-out", "cat", FRAMEWORK_RES])?;
+  let mut archive =
+    ZipArchive::new(Cursor::new(apk)).map_err(|e| AdbError::ParseFailed(e.to_string()))?;
+  let mut entry = archive
+    .by_name(PROFILE_ENTRY)
+    .map_err(|e| AdbError::ParseFailed(format!("{PROFILE_ENTRY}: {e}")))?;
+  let mut xml = Vec::new();
+  entry
+    .read_to_end(&mut xml)
+    .map_err(|e| AdbError::ParseFailed(e.to_string()))?;
+  parse_power_profile(&xml)
+}
+
+/// 清空 batterystats 累计数据，使耗电统计只覆盖此后的时间段；
+/// `full_wake_history` 同时开启完整唤醒锁历史（系统设置，重启前一直有效）
+pub fn reset_batterystats(device_id: &str, full_wake_history: bool) -> Result<()> {
+  if full_wake_history {
+    run_device(
+      device_id,
+      &[
+        "shell",
+        "dumpsys",
+        "batterystats",
+        "--enable",
+        "full-wake-history",
+      ],
+    )?;
+  }
+  run_device(device_id, &["shell", "dumpsys", "batterystats", "--reset"])?;
+  Ok(())
+}
+
+pub fn energy_probe(device_id: &str, t_ms: u64) -> Result<EnergyProbe> {
+  let raw = run_device(device_id, &["shell", PROBE_SCRIPT])?;
+  Ok(parse_probe(&raw, t_ms))
+}
+
+fn parse_probe(raw: &str, t_ms: u64) -> EnergyProbe {
+  let mut probe = EnergyProbe {
+    t_ms,
+    ..Default::default()
+  };
+  for line in raw.lines() {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    match fields.as_slice() {
+      ["policy", cpus @ ..] => probe.policies.push(PolicyResidency {
+        cpus: cpus.iter().filter_map(|c| c.parse().ok()).collect(),
+        time_in_state: Vec::new(),
+      }),
+      ["screen", rest @ ..] => {
+        for field in rest {
+          if let Some(state) = field.strip_prefix("mWakefulness=") {
+            probe.screen_on = Some(state == "Awake");
+          } else if let Ok(level) = field.parse::<f64>() {
+            probe.brightness = Some((level / 255.0).clamp(0.0, 1.0));
+          }
+        }
+      }
+      [freq, ticks] => {
+        if let (Some(policy), Ok(freq), Ok(ticks)) =
+          (probe.policies.last_mut(), freq.parse(), ticks.parse())
+        {
+          policy.time_in_state.push((freq, ticks));
+        }
+      }
+      [cpu, counters @ ..] if cpu.starts_with("cpu") => {
+        let counters: Vec<u64> = counters.iter().filter_map(|c| c.parse().ok()).collect();
+        let (Ok(index), Some(idle)) = (cpu[3..].parse::<u32>(), counters.get(3)) else {
+          continue;
+        };
+        // user nice system idle iowait irq softirq ...，idle 与 iowait 计为空闲
+        let total: u64 = counters.iter().sum();
+        let idle = idle + counters.get(4).copied().unwrap_or(0);
+        probe.cpu_jiffies.insert(index, (total - idle, total));
+      }
+      _ => {}
+    }
+  }
+  probe
+}
+
+const RES_STRING_POOL: u16 = 0x0001;
+const RES_XML_START_ELEMENT: u16 = 0x0102;
+const RES_XML_END_ELEMENT: u16 = 0x0103;
+const RES_XML_CDATA: u16 = 0x0104;
+const UTF8_FLAG: u32 = 0x100;
+const NO_INDEX: u32 = u32::MAX;
+
+/// 解析 aapt 编译的二进制 XML，只关心 `<item>` 与 `<array><value>` 文本
+fn parse_power_profile(xml: &[u8]) -> Result<PowerProfile> {
+  let bad = || AdbError::ParseFailed("power_profile.xml 格式无法识别".into());
+  let mut strings: Vec<String> = Vec::new();
+  let mut profile = PowerProfile::default();
+  let mut stack: Vec<(String, Option<String>)> = Vec::new();
+  let mut offset = 8usize;
+
+  while offset + 8 <= xml.len() {
+    let kind = u16_at(xml, offset).ok_or_else(bad)?;
+    let header_size = u16_at(xml, offset + 2).ok_or_else(bad)? as usize;
+    let size = u32_at(xml, offset + 4).ok_or_else(bad)? as usize;
+    if size < 8 || offset + size > xml.len() {
+      return Err(bad());
+    }
+    let chunk = &xml[offset..offset + size];
+    match kind {
+      RES_STRING_POOL => strings = string_pool(chunk, header_size).ok_or_else(bad)?,
+      RES_XML_START_ELEMENT => {
+        let name = u32_at(chunk, 20)
+          .and_then(|i| strings.get(i as usize))
+          .ok_or_else(bad)?;
+        let attr_start = u16_at(chunk, 24).ok_or_else(bad)? as usize;
+        let attr_size = u16_at(chunk, 26).ok_or_else(bad)? as usize;
+        let attr_count = u16_at(chunk, 28).ok_or_else(bad)? as usize;
+        let mut name_attr = None;
+        for i in 0..attr_count {
+          let at = 16 + attr_start + i * attr_size;
+          let key = u32_at(chunk, at + 4).and_then(|k| strings.get(k as usize));
+          if key.map(String::as_str) != Some("name") {
+            continue;
+          }
+          let raw = u32_at(chunk, at + 8).ok_or_else(bad)?;
+          let data = u32_at(chunk, at + 16).ok_or_else(bad)?;
+          let index = if raw != NO_INDEX { raw } else { data };
+          name_attr = strings.get(index as usize).cloned();
+        }
+        stack.push((name.clone(), name_attr));
+      }
+      RES_XML_END_ELEMENT => {
+        stack.pop();
+      }
+      RES_XML_CDATA => {
+        let text = u32_at(chunk, 16).and_then(|i| strings.get(i as usize));
+        let Some(value) = text.and_then(|t| t.trim().parse::<f64>().ok()) else {
+          offset += size;
+          continue;
+        };
+        match stack.as_slice() {
+          [.., (element, Some(name))] if element == "item" => {
+            profile.items.insert(name.clone(), value);
+          }
+          [.., (array, Some(name)), (element, _)] if array == "array" && element == "value" => {
+            profile.arrays.entry(name.clone()).or_default().push(value);
+          }
+          _ => {}
+        }
+      }
+      _ => {}
+    }
+    offset += size;
+  }
+
+  if profile.items.is_empty() && profile.arrays.is_empty() {
+    return Err(bad());
+  }
+  Ok(profile)
+}
+
+fn string_pool(chunk: &[u8], header_size: usize) -> Option<Vec<String>> {
+  let count = u32_at(chunk, 8)? as usize;
+  let utf8 = u32_at(chunk, 16)? & UTF8_FLAG != 0;
+  let strings_start = u32_at(chunk, 20)? as usize;
+  (0..count)
+    .map(|i| {
+      let at = strings_start + u32_at(chunk, header_size + i * 4)? as usize;
+      if utf8 {
+        // UTF-16 长度与 UTF-8 字节数，各占 1 或 2 字节
+        let (_, skip) = utf8_len(chunk, at)?;
+        let (len, skip2) = utf8_len(chunk, at + skip)?;
+        let start = at + skip + skip2;
+        let bytes = chunk.get(start..start + len)?;
+        Some(String::from_utf8_lossy(bytes).into_owned())
+      } else {
+        let mut len = u16_at(chunk, at)? as usize;
+        let mut start = at + 2;
+        if len & 0x8000 != 0 {
+          len = ((len & 0x7fff) << 16) | u16_at(chunk, at + 2)? as usize;
+          start += 2;
+        }
+        let units: Vec<u16> = (0..len)
+          .map(|j| u16_at(chunk, start + j * 2))
+          .collect::<Option<_>>()?;
+        Some(String::from_utf16_lossy(&units))
+      }
+    })
+    .collect()
+}
+
+fn utf8_len(data: &[u8], at: usize) -> Option<(usize, usize)> {
+  let first = *data.get(at)? as usize;
+  if first & 0x80 != 0 {
+    Some((((first & 0x7f) << 8) | *data.get(at + 1)? as usize, 2))
+  } else {
+    Some((first, 1))
+  }
+}
+
+fn u16_at(data: &[u8], at: usize) -> Option<u16> {
+  Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn u32_at(data: &[u8], at: usize) -> Option<u32> {
+  Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
@@ -0,0 +1,80 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AdbError {
+  #[error("ADB 未找到，请检查环境变量或手动配置路径")]
+  NotFound,
+  #[error("ADB 执行失败: {0}")]
+  CommandFailed(String),
+  #[error("ADB 输出解析失败: {0}")]
+  ParseFailed(String),
+  #[error("{}", .0.message())]
+  Metric(MetricFailure),
+  #[error("指标脚本失败: {0}")]
+  Script(String),
+  #[error("ADB 客户端错误: {0}")]
+  #[allow(dead_code)]
+  Client(String),
+}
+
+pub type Result<T> = std::result::Result<T, AdbError>;
+
+impl AdbError {
+  /// 稳定的错误码，与 MetricFailure 的错误码共用一套命名
+  pub fn code(&self) -> &'static str {
+    match self {
+      Self::NotFound => "ADB_NOT_FOUND",
+      Self::CommandFailed(_) => "ADB_COMMAND_FAILED",
+      Self::ParseFailed(_) => "ADB_PARSE_FAILED",
+      Self::Metric(failure) => failure.code(),
+      Self::Script(_) => "SCRIPT_FAILED",
+      Self::Client(_) => "ADB_CLIENT",
+    }
+  }
+
+  /// 目标进程不存在，应用未运行而非采集出错
+  pub fn is_process_not_found(&self) -> bool {
+    matches!(self, Self::Metric(MetricFailure::ProcessNotFound))
+  }
+}
+
+/// 指标采集中常见的固定失败原因，带稳定的错误码便于前端本地化
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricFailure {
+  ProcessNotFound,
+  Cpu,
+  Memory,
+  Network,
+  Frames,
+  Power,
+  Battery,
+  NetworkInterface,
+}
+
+impl MetricFailure {
+  pub fn code(self) -> &'static str {
+    match self {
+      Self::ProcessNotFound => "PROCESS_NOT_FOUND",
+      Self::Cpu => "CPU_PARSE_FAILED",
+      Self::Memory => "MEMORY_PARSE_FAILED",
+      Self::Network => "NETWORK_PARSE_FAILED",
+      Self::Frames => "FRAMES_UNAVAILABLE",
+      Self::Power => "POWER_UNAVAILABLE",
+      Self::Battery => "BATTERY_UNAVAILABLE",
+      Self::NetworkInterface => "NETWORK_INTERFACE_NOT_FOUND",
+    }
+  }
+
+  pub fn message(self) -> &'static str {
+    match self {
+      Self::ProcessNotFound => "未找到进程",
+      Self::Cpu => "CPU 解析失败",
+      Self::Memory => "内存解析失败",
+      Self::Network => "网络解析失败",
+      Self::Frames => "无法获取帧数信息，请确保应用正在运行",
+      Self::Power => "无法获取功耗数据",
+      Self::Battery => "未获取到电池信息",
+      Self::NetworkInterface => "未找到可用网络接口",
+    }
+  }
+}
@@ -0,0 +1,168 @@
+use crate::adb::{
+  command::run_device,
+  error::Result,
+  metrics::{gfxinfo_framestats_command, run_with_pid},
+  parse::parse_framestats,
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Mutex};
+
+// SurfaceFlinger 对尚未上屏的帧返回 INT64_MAX
+const PENDING_FENCE: u64 = i64::MAX as u64;
+const DEFAULT_REFRESH_NS: u64 = 16_666_667;
+// 超过该间隔视为画面静止，不计入帧节奏
+const MAX_INTERVAL_MS: f64 = 250.0;
+// 与目标帧间隔相差不超过该值视为准时
+const ON_TARGET_TOLERANCE_MS: f64 = 1.0;
+
+/// 每个设备 / 应用上一次已统计的最后一帧时间戳，避免重复计入
+static LAST_FRAME: Lazy<Mutex<HashMap<String, u64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 帧节奏：同样 58 FPS，帧间隔均匀与忽快忽慢的体感完全不同
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FramePacing {
+  /// 参与统计的帧间隔数
+  pub intervals: usize,
+  pub mean_interval_ms: f64,
+  pub stddev_ms: f64,
+  /// 变异系数，标准差 / 均值
+  pub cv_pct: f64,
+  /// 按屏幕刷新周期取整的目标帧间隔，如 60Hz 屏上跑 30 帧时为 33.3ms
+  pub target_interval_ms: f64,
+  /// 帧间隔落在目标 ±1ms 内的比例
+  pub on_target_pct: f64,
+}
+
+/// 清空应用的 gfxinfo 帧统计，避免录制前的帧混入
+pub fn reset_gfxinfo(device_id: &str, package: &str) -> Result<()> {
+  run_device(device_id, &["shell", "dumpsys", "gfxinfo", package, "reset"])?;
+  Ok(())
+}
+
+/// 读取上次调用以来新上屏的帧并计算帧节奏；游戏多使用 SurfaceView，优先读 SurfaceFlinger
+///
+/// 图层名不区分用户，指定用户时只读该用户下应用进程的 gfxinfo
+pub fn fetch_frame_pacing(
+  device_id: &str,
+  package: &str,
+  user: Option<u32>,
+) -> Result<Option<FramePacing>> {
+  let layer = match user {
+    Some(_) => None,
+    None => surface_layer(device_id, package)?,
+  };
+  let (mut timestamps, refresh_ns) = match layer {
+    Some(layer) => {
+      let raw = run_device(
+        device_id,
+        &[
+          "shell",
+          "dumpsys",
+          "SurfaceFlinger",
+          "--latency",
+          &format!("'{layer}'"),
+        ],
+      )?;
+      parse_latency(&raw)
+    }
+    None => (Vec::new(), None),
+  };
+  if timestamps.is_empty() {
+    let raw = match user {
+      Some(_) => run_with_pid(device_id, package, user, gfxinfo_framestats_command)?.1,
+      None => run_device(
+        device_id,
+        &["shell", "dumpsys", "gfxinfo", package, "framestats"],
+      )?,
+    };
+    timestamps = parse_framestats(&raw);
+  }
+
+  let key = match user {
+    Some(user) => format!("{device_id}:{package}:{user}"),
+    None => format!("{device_id}:{package}"),
+  };
+  if let Ok(mut last) = LAST_FRAME.lock() {
+    let previous = last.get(&key).copied().unwrap_or(0);
+    // 保留上次最后一帧，使新旧两批之间的间隔也被统计
+    timestamps.retain(|t| *t >= previous);
+    if let Some(newest) = timestamps.last() {
+      last.insert(key, *newest);
+    }
+  }
+  Ok(frame_pacing(
+    &timestamps,
+    refresh_ns.unwrap_or(DEFAULT_REFRESH_NS),
+  ))
+}
+
+/// 在图层列表中找到应用的图层，SurfaceView 优先
+fn surface_layer(device_id: &str, package: &str) -> Result<Option<String>> {
+  let raw = run_device(device_id, &["shell", "dumpsys", "SurfaceFlinger", "--list"])?;
+  let layers: Vec<&str> = raw
+    .lines()
+    .map(str::trim)
+    .filter(|l| l.contains(package) && !l.starts_with("Background for"))
+    .collect();
+  Ok(
+    layers
+      .iter()
+      .find(|l| l.starts_with("SurfaceView"))
+      .or_else(|| layers.iter().find(|l| l.contains('/')))
+      .map(|l| l.to_string()),
+  )
+}
+
+/// `--latency` 首行为刷新周期，其后每行为期望上屏 / 实际上屏 / 就绪时间（ns）
+fn parse_latency(raw: &str) -> (Vec<u64>, Option<u64>) {
+  let mut lines = raw.lines();
+  let refresh_ns = lines
+    .next()
+    .and_then(|l| l.trim().parse::<u64>().ok())
+    .filter(|v| *v > 0);
+  let mut timestamps: Vec<u64> = lines
+    .filter_map(|line| {
+      line
+        .split_whitespace()
+        .filter_map(|f| f.parse::<u64>().ok())
+        .nth(1)
+        .filter(|t| *t != 0 && *t != PENDING_FENCE)
+    })
+    .collect();
+  timestamps.sort_unstable();
+  timestamps.dedup();
+  (timestamps, refresh_ns)
+}
+
+/// 由上屏时间戳计算帧间隔的离散程度，帧数过少时返回 None
+pub fn frame_pacing(timestamps_ns: &[u64], refresh_ns: u64) -> Option<FramePacing> {
+  let intervals: Vec<f64> = timestamps_ns
+    .windows(2)
+    .map(|pair| pair[1].saturating_sub(pair[0]) as f64 / 1_000_000.0)
+    .filter(|ms| *ms > 0.0 && *ms <= MAX_INTERVAL_MS)
+    .collect();
+  if intervals.len() < 2 {
+    return None;
+  }
+
+  let n = intervals.len() as f64;
+  let mean = intervals.iter().sum::<f64>() / n;
+  let stddev = (intervals.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+  // 目标间隔取最接近平均帧率的刷新周期整数倍
+  let refresh_ms = refresh_ns as f64 / 1_000_000.0;
+  let target = (mean / refresh_ms).round().max(1.0) * refresh_ms;
+  let on_target = intervals
+    .iter()
+    .filter(|v| (*v - target).abs() <= ON_TARGET_TOLERANCE_MS)
+    .count();
+
+  Some(FramePacing {
+    intervals: intervals.len(),
+    mean_interval_ms: mean,
+    stddev_ms: stddev,
+    cv_pct: stddev / mean * 100.0,
+    target_interval_ms: target,
+    on_target_pct: on_target as f64 / n * 100.0,
+  })
+}
@@ -0,0 +1,189 @@
+//! Android 游戏模式（Android 13 起）：读取与设置应用的游戏模式及其干预参数。
+//! 系统会按模式悄悄调整渲染分辨率与帧率上限，录制时需一并记录
+
+use crate::adb::{
+  command::run_device,
+  error::{AdbError, Result},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameMode {
+  Unsupported,
+  Standard,
+  Performance,
+  Battery,
+  Custom,
+}
+
+impl GameMode {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Self::Unsupported => "unsupported",
+      Self::Standard => "standard",
+      Self::Performance => "performance",
+      Self::Battery => "battery",
+      Self::Custom => "custom",
+    }
+  }
+
+  fn value(self) -> u32 {
+    match self {
+      Self::Unsupported => 0,
+      Self::Standard => 1,
+      Self::Performance => 2,
+      Self::Battery => 3,
+      Self::Custom => 4,
+    }
+  }
+
+  /// 不同系统版本的输出分别使用名称与数值
+  fn parse(value: &str) -> Option<Self> {
+    let value = value.trim().to_ascii_lowercase();
+    [
+      Self::Unsupported,
+      Self::Standard,
+      Self::Performance,
+      Self::Battery,
+      Self::Custom,
+    ]
+    .into_iter()
+    .find(|m| value == m.as_str() || value == m.value().to_string())
+  }
+}
+
+/// 应用当前的游戏模式与该模式下的干预参数
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GameModeInfo {
+  pub mode: Option<GameMode>,
+  #[serde(default)]
+  pub available: Vec<GameMode>,
+  /// 渲染分辨率缩放，1 表示不缩放
+  #[serde(default)]
+  pub downscale: Option<f32>,
+  /// 帧率上限，0 或为空表示不限制
+  #[serde(default)]
+  pub fps: Option<u32>,
+}
+
+impl GameModeInfo {
+  pub fn label(&self) -> String {
+    let mut label = self
+      .mode
+      .map(GameMode::as_str)
+      .unwrap_or("unknown")
+      .to_string();
+    if let Some(scale) = self.downscale.filter(|s| *s > 0.0 && *s < 1.0) {
+      label.push_str(&format!("，分辨率 {scale}x"));
+    }
+    if let Some(fps) = self.fps.filter(|f| *f > 0) {
+      label.push_str(&format!("，帧率上限 {fps}"));
+    }
+    label
+  }
+}
+
+pub fn game_mode(device_id: &str, package: &str) -> Result<GameModeInfo> {
+  let modes = run_device(device_id, &["shell", "cmd", "game", "list-modes", package])?;
+  let mut info = parse_modes(&modes)
+    .ok_or_else(|| AdbError::ParseFailed(format!("游戏模式: {}", modes.trim())))?;
+  // 干预参数读取失败不影响模式本身
+  if let (Some(mode), Ok(configs)) = (
+    info.mode,
+    run_device(
+      device_id,
+      &["shell", "cmd", "game", "list-configs", package],
+    ),
+  ) {
+    let (downscale, fps) = parse_intervention(&configs, mode);
+    info.downscale = downscale;
+    info.fps = fps;
+  }
+  Ok(info)
+}
+
+pub fn set_game_mode(device_id: &str, package: &str, mode: GameMode) -> Result<()> {
+  let raw = run_device(
+    device_id,
+    &["shell", "cmd", "game", "mode", mode.as_str(), package],
+  )?;
+  check(&raw)
+}
+
+/// 设置某个模式的干预参数：分辨率缩放与帧率上限
+pub fn set_intervention(
+  device_id: &str,
+  package: &str,
+  mode: GameMode,
+  downscale: Option<f32>,
+  fps: Option<u32>,
+) -> Result<()> {
+  let mode = mode.value().to_string();
+  let mut args = vec!["shell", "cmd", "game", "set", "--mode", &mode];
+  let downscale = downscale.map(|d| d.to_string());
+  if let Some(downscale) = &downscale {
+    args.extend(["--downscale", downscale]);
+  }
+  let fps = fps.map(|f| f.to_string());
+  if let Some(fps) = &fps {
+    args.extend(["--fps", fps]);
+  }
+  args.push(package);
+  let raw = run_device(device_id, &args)?;
+  check(&raw)
+}
+
+/// 清除通过 `cmd game set` 设置的干预参数
+pub fn reset_intervention(device_id: &str, package: &str) -> Result<()> {
+  let raw = run_device(device_id, &["shell", "cmd", "game", "reset", package])?;
+  check(&raw)
+}
+
+/// 命令失败时多数版本仍返回 0，只在输出中给出提示
+fn check(raw: &str) -> Result<()> {
+  let lower = raw.to_ascii_lowercase();
+  if lower.contains("error") || lower.contains("invalid") || lower.contains("unknown") {
+    return Err(AdbError::CommandFailed(raw.trim().to_string()));
+  }
+  Ok(())
+}
+
+/// `package current mode: 2, available game modes: [1,2,3]`
+fn parse_modes(raw: &str) -> Option<GameModeInfo> {
+  let (_, rest) = raw.split_once("current mode:")?;
+  let (current, rest) = rest.split_once(',').unwrap_or((rest, ""));
+  let available = rest
+    .split_once('[')
+    .and_then(|(_, list)| list.split_once(']'))
+    .map(|(list, _)| list.split(',').filter_map(GameMode::parse).collect())
+    .unwrap_or_default();
+  Some(GameModeInfo {
+    mode: GameMode::parse(current),
+    available,
+    downscale: None,
+    fps: None,
+  })
+}
+
+/// 在 list-configs 输出中找到对应模式的配置段，读取 Scaling 与 Fps
+fn parse_intervention(raw: &str, mode: GameMode) -> (Option<f32>, Option<u32>) {
+  let marker = format!("Game Mode:{}", mode.value());
+  let Some(segment) = raw
+    .split('[')
+    .find(|s| s.replace(' ', "").contains(&marker.replace(' ', "")))
+  else {
+    return (None, None);
+  };
+  let field = |name: &str| -> Option<&str> {
+    let (_, rest) = segment.split_once(name)?;
+    rest
+      .trim_start_matches([':', '=', ' '])
+      .split([',', ']', ' '])
+      .next()
+  };
+  (
+    field("Scaling").and_then(|v| v.parse().ok()),
+    field("Fps").and_then(|v| v.parse().ok()),
+  )
+}
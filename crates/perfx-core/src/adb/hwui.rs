@@ -0,0 +1,86 @@
+//! 设备端渲染可视化：GPU 呈现模式分析条形图（debug.hwui.profile）与刷新率悬浮显示
+
+use crate::adb::{command::run_device, error::Result};
+use serde::{Deserialize, Serialize};
+
+const PROFILE_PROP: &str = "debug.hwui.profile";
+/// 通知正在运行的应用重新读取系统属性（IBinder.SYSPROPS_TRANSACTION）
+const SYSPROPS_TRANSACTION: &str = "1599295570";
+/// SurfaceFlinger 刷新率悬浮显示：参数 1 开、0 关；旧系统上任何非零参数都会打开，因此不查询状态
+const SHOW_REFRESH_RATE: &str = "1034";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceOverlay {
+  /// 屏幕上的 GPU 渲染耗时条形图
+  GpuBars,
+  /// 左上角的刷新率显示
+  RefreshRate,
+}
+
+impl DeviceOverlay {
+  pub fn label(self) -> &'static str {
+    match self {
+      Self::GpuBars => "GPU 呈现模式分析",
+      Self::RefreshRate => "刷新率显示",
+    }
+  }
+}
+
+/// 当前开关状态；`gpu_profile` 为属性原值，恢复时写回
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OverlayState {
+  pub gpu_profile: Option<String>,
+  pub gpu_bars: bool,
+}
+
+pub fn overlay_state(device_id: &str) -> Result<OverlayState> {
+  let raw = run_device(device_id, &["shell", "getprop", PROFILE_PROP])?;
+  let gpu_profile = Some(raw.trim().to_string()).filter(|v| !v.is_empty());
+  Ok(OverlayState {
+    gpu_bars: gpu_profile.as_deref() == Some("visual_bars"),
+    gpu_profile,
+  })
+}
+
+pub fn set_overlay(device_id: &str, overlay: DeviceOverlay, enabled: bool) -> Result<()> {
+  match overlay {
+    DeviceOverlay::GpuBars => {
+      set_gpu_profile(device_id, if enabled { "visual_bars" } else { "false" })
+    }
+    DeviceOverlay::RefreshRate => set_refresh_rate(device_id, enabled),
+  }
+}
+
+/// 按之前读取的状态写回；刷新率显示无法读取原状态，恢复时关闭
+pub fn restore_overlays(device_id: &str, state: &OverlayState) -> Result<()> {
+  set_gpu_profile(device_id, state.gpu_profile.as_deref().unwrap_or("false"))?;
+  set_refresh_rate(device_id, false)
+}
+
+fn set_gpu_profile(device_id: &str, value: &str) -> Result<()> {
+  run_device(device_id, &["shell", "setprop", PROFILE_PROP, value])?;
+  // 不通知时需重启应用才生效
+  run_device(
+    device_id,
+    &["shell", "service", "call", "activity", SYSPROPS_TRANSACTION],
+  )?;
+  Ok(())
+}
+
+fn set_refresh_rate(device_id: &str, enabled: bool) -> Result<()> {
+  let arg = if enabled { "1" } else { "0" };
+  run_device(
+    device_id,
+    &[
+      "shell",
+      "service",
+      "call",
+      "SurfaceFlinger",
+      SHOW_REFRESH_RATE,
+      "i32",
+      arg,
+    ],
+  )?;
+  Ok(())
+}
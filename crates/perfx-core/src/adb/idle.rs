@@ -0,0 +1,127 @@
+//! Doze（deviceidle）与应用待机分组（App Standby bucket），用于后台行为与推送到达测试
+
+use crate::adb::{
+  command::run_device,
+  error::{AdbError, Result},
+};
+use serde::{Deserialize, Serialize};
+
+/// deep / light 两种 Doze 的当前状态，如 ACTIVE、IDLE、IDLE_MAINTENANCE
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdleState {
+  pub deep: Option<String>,
+  pub light: Option<String>,
+}
+
+impl IdleState {
+  pub fn label(&self) -> String {
+    format!(
+      "Doze deep {}，light {}",
+      self.deep.as_deref().unwrap_or("未知"),
+      self.light.as_deref().unwrap_or("未知")
+    )
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StandbyBucket {
+  Exempted,
+  Active,
+  WorkingSet,
+  Frequent,
+  Rare,
+  Restricted,
+  Never,
+}
+
+impl StandbyBucket {
+  /// `am set-standby-bucket` 接受的名称
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Self::Exempted => "exempted",
+      Self::Active => "active",
+      Self::WorkingSet => "working_set",
+      Self::Frequent => "frequent",
+      Self::Rare => "rare",
+      Self::Restricted => "restricted",
+      Self::Never => "never",
+    }
+  }
+
+  /// `am get-standby-bucket` 返回的数值
+  fn from_value(value: u32) -> Option<Self> {
+    Some(match value {
+      5 => Self::Exempted,
+      10 => Self::Active,
+      20 => Self::WorkingSet,
+      30 => Self::Frequent,
+      40 => Self::Rare,
+      45 => Self::Restricted,
+      50 => Self::Never,
+      _ => return None,
+    })
+  }
+}
+
+pub fn idle_state(device_id: &str) -> Result<IdleState> {
+  let get = |mode: &str| -> Result<Option<String>> {
+    let raw = run_device(device_id, &["shell", "dumpsys", "deviceidle", "get", mode])?;
+    Ok(Some(raw.trim().to_string()).filter(|v| !v.is_empty()))
+  };
+  Ok(IdleState {
+    deep: get("deep")?,
+    light: get("light")?,
+  })
+}
+
+/// 强制进入 deep Doze；充电时系统不会进入 Doze，先模拟拔出电源
+pub fn force_idle(device_id: &str) -> Result<()> {
+  run_device(device_id, &["shell", "dumpsys", "battery", "unplug"])?;
+  let result =
+    run_device(device_id, &["shell", "dumpsys", "deviceidle", "force-idle"]).and_then(|raw| {
+      // 屏幕亮着时命令返回提示但退出码为 0
+      if raw.contains("Unable") {
+        return Err(AdbError::CommandFailed(raw.trim().to_string()));
+      }
+      Ok(())
+    });
+  // 未能进入 Doze 时恢复真实电源状态，不让设备一直处于拔电状态
+  if result.is_err() {
+    let _ = run_device(device_id, &["shell", "dumpsys", "battery", "reset"]);
+  }
+  result
+}
+
+/// 退出强制 Doze 并恢复真实电源状态
+pub fn unforce_idle(device_id: &str) -> Result<()> {
+  let unforced = run_device(device_id, &["shell", "dumpsys", "deviceidle", "unforce"]);
+  // 退出 Doze 失败也要恢复电源状态
+  run_device(device_id, &["shell", "dumpsys", "battery", "reset"])?;
+  unforced?;
+  Ok(())
+}
+
+pub fn standby_bucket(device_id: &str, package: &str) -> Result<StandbyBucket> {
+  let raw = run_device(device_id, &["shell", "am", "get-standby-bucket", package])?;
+  raw
+    .trim()
+    .parse()
+    .ok()
+    .and_then(StandbyBucket::from_value)
+    .ok_or_else(|| AdbError::ParseFailed(format!("待机分组: {}", raw.trim())))
+}
+
+pub fn set_standby_bucket(device_id: &str, package: &str, bucket: StandbyBucket) -> Result<()> {
+  run_device(
+    device_id,
+    &[
+      "shell",
+      "am",
+      "set-standby-bucket",
+      package,
+      bucket.as_str(),
+    ],
+  )?;
+  Ok(())
+}
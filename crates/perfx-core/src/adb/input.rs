@@ -0,0 +1,46 @@
+use crate::adb::{command::run_device, error::Result};
+
+pub fn tap(device_id: &str, x: u32, y: u32) -> Result<()> {
+  run_device(
+    device_id,
+    &["shell", "input", "tap", &x.to_string(), &y.to_string()],
+  )?;
+  Ok(())
+}
+
+pub fn swipe(device_id: &str, from: (u32, u32), to: (u32, u32), duration_ms: u32) -> Result<()> {
+  run_device(
+    device_id,
+    &[
+      "shell",
+      "input",
+      "swipe",
+      &from.0.to_string(),
+      &from.1.to_string(),
+      &to.0.to_string(),
+      &to.1.to_string(),
+      &duration_ms.to_string(),
+    ],
+  )?;
+  Ok(())
+}
+
+/// 输入文本，`input text` 不接受空格，需转义为 `%s`
+pub fn input_text(device_id: &str, text: &str) -> Result<()> {
+  let escaped: String = text
+    .chars()
+    .flat_map(|c| match c {
+      ' ' => vec!['%', 's'],
+      '\'' | '"' | '\\' | '&' | '|' | ';' | '<' | '>' | '(' | ')' | '$' | '`' => vec!['\\', c],
+      _ => vec![c],
+    })
+    .collect();
+  run_device(device_id, &["shell", "input", "text", &escaped])?;
+  Ok(())
+}
+
+/// 发送按键，如 `KEYCODE_BACK` 或数字键码
+pub fn keyevent(device_id: &str, key: &str) -> Result<()> {
+  run_device(device_id, &["shell", "input", "keyevent", key])?;
+  Ok(())
+}
@@ -0,0 +1,54 @@
+use crate::adb::{command::spawn_device, error::Result};
+use std::process::Child;
+
+/// `logcat -v epoch` 格式的一行日志
+#[derive(Debug, Clone, Copy)]
+pub struct LogLine<'a> {
+  pub pid: u32,
+  pub level: char,
+  pub tag: &'a str,
+  pub message: &'a str,
+}
+
+/// 从当前位置开始持续输出 main/system/crash 缓冲区
+pub fn spawn_logcat(device_id: &str) -> Result<Child> {
+  spawn_device(
+    device_id,
+    &[
+      "logcat",
+      "-v",
+      "epoch",
+      "-b",
+      "main,system,crash",
+      "-T",
+      "1",
+    ],
+  )
+}
+
+/// 解析形如 `1700000000.123  1234  1250 E Tag: message` 的行
+pub fn parse_line(line: &str) -> Option<LogLine<'_>> {
+  let (epoch, rest) = next_field(line)?;
+  epoch.parse::<f64>().ok()?;
+  let (pid, rest) = next_field(rest)?;
+  let (_tid, rest) = next_field(rest)?;
+  let (level, rest) = next_field(rest)?;
+  let (tag, message) = rest.split_once(':')?;
+
+  let mut level_chars = level.chars();
+  let level = level_chars
+    .next()
+    .filter(|_| level_chars.next().is_none())?;
+  Some(LogLine {
+    pid: pid.parse().ok()?,
+    level,
+    tag: tag.trim(),
+    message: message.strip_prefix(' ').unwrap_or(message),
+  })
+}
+
+fn next_field(s: &str) -> Option<(&str, &str)> {
+  let s = s.trim_start();
+  let end = s.find(char::is_whitespace)?;
+  Some((&s[..end], &s[end..]))
+}
@@ -0,0 +1,1068 @@
+use crate::adb::{
+  command::run_device,
+  error::{AdbError, MetricFailure, Result},
+  device::{cpu_cores, sdk_level},
+  frames::{fetch_frame_pacing, FramePacing},
+  provider::collect_providers,
+  script::run_scripts,
+  parse::{
+    parse_battery, parse_batterystats_power, parse_current_now_ma, parse_gfxinfo,
+    parse_meminfo_kb, parse_net_dev, parse_qtaguid, parse_top_cpu, process_missing,
+    BatteryReading,
+  },
+  user::{uid_user, PER_USER_RANGE},
+};
+use crate::schema::{check_version, legacy_version, UnsupportedSchema, SCHEMA_VERSION};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone)]
+struct FpsHistory {
+  total_frames: Option<u64>,
+  /// framestats 中最后一帧的完成时间，汇总统计缺失时据此计数
+  last_frame_ns: Option<u64>,
+  timestamp: u64, // unix timestamp in milliseconds
+}
+
+/// 两次采样间隔不足该值时不计算帧率
+const MIN_FPS_INTERVAL_SEC: f64 = 0.1;
+
+/// gfxinfo framestats 自 Android 6（API 23）起提供
+const FRAMESTATS_SDK: u32 = 23;
+
+static FPS_HISTORY: Lazy<Mutex<HashMap<String, FpsHistory>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone)]
+struct TrafficHistory {
+  rx_bytes: u64,
+  tx_bytes: u64,
+  timestamp: u64,
+}
+
+static TRAFFIC_HISTORY: Lazy<Mutex<HashMap<String, TrafficHistory>>> =
+  Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// (设备, 包名, 用户)
+type PidKey = (String, String, Option<u32>);
+
+/// 按应用缓存的 PID，避免每次采样都执行 pidof
+static PID_CACHE: Lazy<Mutex<HashMap<PidKey, String>>> =
+  Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 分隔 cmdline 校验结果与取数命令输出
+const PID_SEPARATOR: &str = "--perfx-pid--";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricKey {
+  Fps,
+  Cpu,
+  Power,
+  Memory,
+  Network,
+  Battery,
+  BatteryTemp,
+  Traffic,
+  FramePacing,
+}
+
+impl std::str::FromStr for MetricKey {
+  type Err = AdbError;
+
+  /// 与序列化名称一致，如 `fps`、`battery_temp`
+  fn from_str(value: &str) -> Result<Self> {
+    match value.trim() {
+      "fps" => Ok(Self::Fps),
+      "cpu" => Ok(Self::Cpu),
+      "power" => Ok(Self::Power),
+      "memory" => Ok(Self::Memory),
+      "network" => Ok(Self::Network),
+      "battery" => Ok(Self::Battery),
+      "battery_temp" => Ok(Self::BatteryTemp),
+      "traffic" => Ok(Self::Traffic),
+      "frame_pacing" => Ok(Self::FramePacing),
+      other => Err(AdbError::ParseFailed(format!("未知指标: {other}"))),
+    }
+  }
+}
+
+impl MetricKey {
+  /// 序列化名称，与 FromStr 互逆
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Self::Fps => "fps",
+      Self::Cpu => "cpu",
+      Self::Power => "power",
+      Self::Memory => "memory",
+      Self::Network => "network",
+      Self::Battery => "battery",
+      Self::BatteryTemp => "battery_temp",
+      Self::Traffic => "traffic",
+      Self::FramePacing => "frame_pacing",
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameStats {
+  /// 首次采样（尚无基线）时为空
+  pub fps: Option<f64>,
+  pub avg_frame_time: Option<f64>, // 平均帧耗时（毫秒）
+  pub frame_times: Vec<f64>,       // 最近的帧耗时数组
+  pub jank_count: u32,             // 帧率不稳定的次数
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MetricsSnapshot {
+  /// 数据格式版本，采集时为 `SCHEMA_VERSION`，缺省时按旧版本读取
+  #[serde(default = "legacy_version")]
+  pub schema_version: u32,
+  pub fps: Option<f64>,
+  /// top 口径的 CPU 占用，按单核计 100%，多核设备上可超过 100%
+  pub cpu: Option<f64>,
+  /// 按核数折算的整机占比（0–100）
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub cpu_normalized: Option<f64>,
+  /// 电池瞬时电流（mA），来自 `current now`
+  pub power: Option<f64>,
+  /// batterystats 估算的应用累计耗电（mAh），部分机型不提供
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub power_mah: Option<f64>,
+  pub memory_mb: Option<f64>,
+  /// 收发合计速率（KiB/s）；采集了应用流量时取应用的，否则取整机的
+  pub network_kbps: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub network_bps: Option<f64>,
+  /// 整机（/proc/net/dev）收发速率，字节每秒
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub network_rx_bps: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub network_tx_bps: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub rx_bytes: Option<u64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub tx_bytes: Option<u64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub rx_bps: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub tx_bps: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub battery_level: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub battery_temp_c: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub battery_voltage_v: Option<f64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub frame_stats: Option<FrameStats>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub frame_pacing: Option<FramePacing>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub raw: Option<String>,
+  /// 按进程拆分的 CPU 与内存，仅在开启分进程明细时填充
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub processes: Option<Vec<ProcessMetrics>>,
+  /// 目标应用是否在运行；未运行时应用指标为空是正常现象
+  #[serde(default)]
+  pub process_state: ProcessState,
+  /// 采集失败的指标及其错误码，不含因应用未运行而缺失的指标
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub failures: BTreeMap<String, String>,
+  /// 结构体之外的扩展指标（新指标或插件提供），数值与布尔值随采样一起存储与导出
+  #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+  pub extra: HashMap<String, MetricValue>,
+}
+
+/// 扩展指标的取值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MetricValue {
+  Number(f64),
+  Bool(bool),
+  Text(String),
+  Array(Vec<MetricValue>),
+}
+
+impl MetricValue {
+  /// 可画成曲线的取值：数值原样，布尔值记为 1 / 0，其余为空
+  pub fn as_f64(&self) -> Option<f64> {
+    match self {
+      Self::Number(v) => Some(*v),
+      Self::Bool(v) => Some(if *v { 1.0 } else { 0.0 }),
+      Self::Text(_) | Self::Array(_) => None,
+    }
+  }
+}
+
+impl MetricsSnapshot {
+  /// 将读回的旧版本快照转换为当前含义
+  pub fn upgrade(&mut self) -> std::result::Result<(), UnsupportedSchema> {
+    check_version(self.schema_version)?;
+    // 版本 1 只采集整机流量时 network_kbps 是累计量，无法还原为速率
+    if self.schema_version < 2 && self.network_bps.is_none() {
+      self.network_kbps = None;
+    }
+    // 版本 3 之前的 power 可能是累计 mAh，单个快照无法判断，由读取整段序列的一方
+    // 按 `legacy_power_is_cumulative` 处理
+    self.schema_version = SCHEMA_VERSION;
+    Ok(())
+  }
+}
+
+/// 本次采样时目标应用的运行状态
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessState {
+  /// 未采集依赖进程的指标
+  #[default]
+  Unknown,
+  Running,
+  NotRunning,
+}
+
+/// 应用的单个进程及其 CPU / 内存
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessMetrics {
+  pub pid: String,
+  pub name: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub uid: Option<u32>,
+  pub cpu: Option<f64>,
+  pub memory_mb: Option<f64>,
+}
+
+/// 统计流量时计入的网卡
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum InterfaceFilter {
+  /// 常见外网接口：wlan、rmnet、ccmni 等
+  #[default]
+  Default,
+  /// 常见外网接口加上 tun、ppp 等 VPN 接口；VPN 流量会同时计入 tun 与底层网卡
+  WithVpn,
+  /// 除 lo 外的全部接口
+  AllExceptLoopback,
+  /// 按接口名前缀指定
+  Prefixes { prefixes: Vec<String> },
+}
+
+const DEFAULT_INTERFACES: [&str; 8] = [
+  "wlan", "rmnet", "ccmni", "eth", "usb", "pdp", "cell", "seth",
+];
+const VPN_INTERFACES: [&str; 3] = ["tun", "ppp", "ipsec"];
+
+impl InterfaceFilter {
+  pub fn matches(&self, iface: &str) -> bool {
+    let any = |prefixes: &[&str]| prefixes.iter().any(|p| iface.starts_with(p));
+    match self {
+      Self::Default => any(&DEFAULT_INTERFACES),
+      Self::WithVpn => any(&DEFAULT_INTERFACES) || any(&VPN_INTERFACES),
+      Self::AllExceptLoopback => iface != "lo",
+      Self::Prefixes { prefixes } => prefixes.iter().any(|p| iface.starts_with(p.as_str())),
+    }
+  }
+}
+
+/// 采集选项
+#[derive(Debug, Clone, Default)]
+pub struct CollectOptions {
+  /// 额外返回 gfxinfo / meminfo 原始输出
+  pub keep_raw: bool,
+  /// CPU、内存与流量汇总应用的全部进程，而不只是 pidof 找到的第一个
+  pub all_processes: bool,
+  /// 汇总时同时给出每个进程的明细
+  pub process_breakdown: bool,
+  /// 只采集该 Android 用户下的应用进程，为空时不区分用户
+  pub user: Option<u32>,
+  /// 流量统计计入的网卡
+  pub interfaces: InterfaceFilter,
+}
+
+/// 将快照转换为与前端图表一致的指标键值，数值为基准单位（见 `units`）
+pub fn snapshot_values(snapshot: &MetricsSnapshot) -> BTreeMap<String, f64> {
+  let mut values = BTreeMap::new();
+  let pacing = snapshot.frame_pacing.as_ref();
+  let fields = [
+    ("fps", snapshot.fps),
+    ("cpu", snapshot.cpu),
+    ("cpu_normalized", snapshot.cpu_normalized),
+    ("power", snapshot.power),
+    ("power_mah", snapshot.power_mah),
+    ("memory", snapshot.memory_mb),
+    ("battery", snapshot.battery_level),
+    ("battery_temp", snapshot.battery_temp_c),
+    ("battery_voltage", snapshot.battery_voltage_v),
+    ("traffic_rx", snapshot.rx_bps.map(|v| v / 1024.0)),
+    ("traffic_tx", snapshot.tx_bps.map(|v| v / 1024.0)),
+    ("network_rx", snapshot.network_rx_bps.map(|v| v / 1024.0)),
+    ("network_tx", snapshot.network_tx_bps.map(|v| v / 1024.0)),
+    ("frame_sd", pacing.map(|p| p.stddev_ms)),
+    ("frame_cv", pacing.map(|p| p.cv_pct)),
+    ("frame_on_target", pacing.map(|p| p.on_target_pct)),
+  ];
+  for (key, value) in fields {
+    if let Some(value) = value.filter(|v| v.is_finite()) {
+      values.insert(key.to_string(), value);
+    }
+  }
+  // 扩展指标不覆盖内置指标
+  for (key, value) in &snapshot.extra {
+    if let Some(value) = value.as_f64().filter(|v| v.is_finite()) {
+      values.entry(key.clone()).or_insert(value);
+    }
+  }
+  // 分进程明细记为 `cpu:进程名`、`memory:进程名`
+  for process in snapshot.processes.iter().flatten() {
+    for (key, value) in [("cpu", process.cpu), ("memory", process.memory_mb)] {
+      if let Some(value) = value.filter(|v| v.is_finite()) {
+        values.insert(format!("{key}:{}", process.name), value);
+      }
+    }
+  }
+  values
+}
+
+/// 采集过程中保留的原始命令输出
+#[derive(Debug, Clone)]
+pub struct RawDump {
+  pub kind: &'static str,
+  pub text: String,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct BatteryStats {
+  pub(crate) level: Option<f64>,
+  pub(crate) temp_c: Option<f64>,
+  pub(crate) voltage_v: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TrafficStats {
+  rx_bytes: u64,
+  tx_bytes: u64,
+  rx_bps: Option<f64>,
+  tx_bps: Option<f64>,
+}
+
+impl TrafficStats {
+  fn total_bps(&self) -> Option<f64> {
+    match (self.rx_bps, self.tx_bps) {
+      (Some(rx), Some(tx)) => Some(rx + tx),
+      (Some(rx), None) => Some(rx),
+      (None, Some(tx)) => Some(tx),
+      _ => None,
+    }
+  }
+
+  fn total_kbps(&self) -> Option<f64> {
+    self.total_bps().map(|v| v / 1024.0)
+  }
+}
+
+pub fn collect_metrics(
+  device_id: &str,
+  package: &str,
+  metrics: &[MetricKey],
+) -> Result<MetricsSnapshot> {
+  collect_metrics_with(device_id, package, metrics, CollectOptions::default())
+    .map(|(snapshot, _)| snapshot)
+}
+
+/// 与 collect_metrics 相同，按 `options` 汇总多进程并返回原始输出
+pub fn collect_metrics_with(
+  device_id: &str,
+  package: &str,
+  metrics: &[MetricKey],
+  options: CollectOptions,
+) -> Result<(MetricsSnapshot, Vec<RawDump>)> {
+  let mut dumps = options.keep_raw.then(Vec::new);
+  let mut snapshot = MetricsSnapshot {
+    schema_version: SCHEMA_VERSION,
+    ..Default::default()
+  };
+  // 列不出进程时（如旧系统的 ps 不支持 -o）退回单进程采集
+  let aggregate = options.all_processes
+    && metrics
+      .iter()
+      .any(|m| matches!(m, MetricKey::Cpu | MetricKey::Memory | MetricKey::Traffic));
+  let mut processes = if aggregate {
+    package_processes(device_id, package, options.user).ok()
+  } else {
+    None
+  };
+  // 首个依赖 PID 的指标顺带校验缓存的 PID，之后的指标直接复用
+  let mut pid: Option<String> = None;
+  let mut battery_stats: Option<BatteryStats> = None;
+  let mut traffic_stats: Option<TrafficStats> = None;
+  let mut system_kbps: Option<f64> = None;
+
+  for metric in metrics {
+    match metric {
+      MetricKey::Cpu => {
+        if let Some(list) = processes.as_mut() {
+          let pids: Vec<&str> = list.iter().map(|p| p.pid.as_str()).collect();
+          let result = fetch_processes_cpu(device_id, &pids);
+          if let Some(usage) = settle(&mut snapshot, metric, result) {
+            for process in list.iter_mut() {
+              process.cpu = usage.get(&process.pid).copied();
+            }
+            snapshot.cpu = Some(usage.values().sum::<f64>());
+          }
+          continue;
+        }
+        let result = match pid {
+          Some(ref pid) => fetch_cpu(device_id, pid),
+          None => run_with_pid(device_id, package, options.user, top_command).and_then(
+            |(resolved, raw)| {
+              pid = Some(resolved.clone());
+              parse_cpu(&raw, &resolved)
+            },
+          ),
+        };
+        snapshot.cpu = settle(&mut snapshot, metric, result);
+      }
+      MetricKey::Memory => {
+        if let Some(list) = processes.as_mut() {
+          let pids: Vec<&str> = list.iter().map(|p| p.pid.as_str()).collect();
+          let result = fetch_processes_memory(device_id, &pids, &mut dumps);
+          if let Some(usage) = settle(&mut snapshot, metric, result) {
+            for process in list.iter_mut() {
+              process.memory_mb = usage.get(&process.pid).copied();
+            }
+            snapshot.memory_mb = Some(usage.values().sum());
+          }
+          continue;
+        }
+        // dumpsys meminfo 按包名查询时不区分用户，指定用户时按 PID 查询
+        let result = if options.user.is_some() {
+          run_with_pid(device_id, package, options.user, meminfo_command).and_then(
+            |(resolved, raw)| {
+              let memory = parse_memory(&raw);
+              if let Some(dumps) = dumps.as_mut() {
+                dumps.push(RawDump {
+                  kind: "meminfo",
+                  text: raw,
+                });
+              }
+              pid.get_or_insert(resolved);
+              memory
+            },
+          )
+        } else {
+          fetch_memory(device_id, package, &mut dumps)
+        };
+        snapshot.memory_mb = settle(&mut snapshot, metric, result);
+      }
+      MetricKey::Network => {
+        let result = fetch_network(device_id, &options.interfaces);
+        if let Some(network) = settle(&mut snapshot, metric, result) {
+          snapshot.network_rx_bps = network.rx_bps;
+          snapshot.network_tx_bps = network.tx_bps;
+          system_kbps = network.total_kbps();
+        }
+      }
+      MetricKey::Traffic => {
+        // 汇总多进程时按各进程的 UID 统计（含独立 UID 的沙箱进程），系统不提供按 UID 的
+        // 统计时退回 /proc/<pid>/net/dev：它按网络命名空间统计，各进程读数相同，读主进程即可
+        if let Some(list) = processes.as_ref().filter(|_| traffic_stats.is_none()) {
+          let uids: Vec<u32> = list.iter().filter_map(|p| p.uid).collect();
+          traffic_stats = fetch_uid_traffic(device_id, package, &uids, &options.interfaces);
+        }
+        if traffic_stats.is_none() {
+          let result = match pid {
+            Some(ref pid) => fetch_traffic(device_id, pid, &options.interfaces),
+            None => run_with_pid(device_id, package, options.user, net_dev_command).and_then(
+              |(resolved, raw)| {
+                pid = Some(resolved.clone());
+                parse_traffic(device_id, &resolved, &raw, &options.interfaces)
+              },
+            ),
+          };
+          traffic_stats = settle(&mut snapshot, metric, result);
+        }
+        if let Some(ref traffic) = traffic_stats {
+          snapshot.rx_bytes = Some(traffic.rx_bytes);
+          snapshot.tx_bytes = Some(traffic.tx_bytes);
+          snapshot.rx_bps = traffic.rx_bps;
+          snapshot.tx_bps = traffic.tx_bps;
+          snapshot.network_bps = traffic.total_bps();
+        }
+      }
+      MetricKey::Fps => {
+        let result = fetch_fps(device_id, package, options.user, &mut dumps);
+        if let Some(frame_stats) = settle(&mut snapshot, metric, result) {
+          snapshot.fps = frame_stats.fps;
+          snapshot.frame_stats = Some(frame_stats);
+        }
+      }
+      MetricKey::FramePacing => {
+        let result = fetch_frame_pacing(device_id, package, options.user);
+        snapshot.frame_pacing = settle(&mut snapshot, metric, result).flatten();
+      }
+      MetricKey::Power => {
+        let result = fetch_power(device_id, package);
+        if let Some(reading) = settle(&mut snapshot, metric, result) {
+          snapshot.power = reading.current_ma;
+          snapshot.power_mah = reading.app_mah;
+        }
+        // 电压用于将电流积分换算为能耗
+        if battery_stats.is_none() {
+          battery_stats = fetch_battery(device_id).ok();
+        }
+        snapshot.battery_voltage_v = battery_stats.as_ref().and_then(|b| b.voltage_v);
+      }
+      MetricKey::Battery | MetricKey::BatteryTemp => {
+        if battery_stats.is_none() {
+          let result = fetch_battery(device_id);
+          battery_stats = settle(&mut snapshot, metric, result);
+        }
+        if let Some(ref battery) = battery_stats {
+          snapshot.battery_level = battery.level;
+          snapshot.battery_temp_c = battery.temp_c;
+          snapshot.battery_voltage_v = battery.voltage_v;
+        }
+      }
+    }
+  }
+
+  // 与指标顺序无关：优先应用流量，没有时用整机网络
+  snapshot.network_kbps = traffic_stats
+    .as_ref()
+    .and_then(TrafficStats::total_kbps)
+    .or(system_kbps);
+
+  // 解析出 PID 或进程列表即说明应用在运行；未运行时应用指标的失败都是连带结果
+  let resolved = processes.as_ref().is_some_and(|list| !list.is_empty()) || pid.is_some();
+  if resolved && snapshot.process_state == ProcessState::Unknown {
+    snapshot.process_state = ProcessState::Running;
+  }
+  if snapshot.process_state == ProcessState::NotRunning {
+    snapshot
+      .failures
+      .retain(|key, _| !APP_METRICS.iter().any(|m| m.as_str() == key));
+  }
+  // 自定义指标：失败记在提供者名称下，不影响内置指标
+  for (name, result) in collect_providers(device_id, package) {
+    match result {
+      Ok(values) => snapshot.extra.extend(values),
+      Err(e) => {
+        snapshot.failures.insert(name, e.code().to_string());
+      }
+    }
+  }
+  if let Some(cpu) = snapshot.cpu {
+    snapshot.cpu_normalized = cpu_cores(device_id).map(|cores| cpu / cores as f64);
+  }
+  if options.process_breakdown {
+    snapshot.processes = processes;
+  }
+  // 脚本最后运行，可读取本次快照中的全部指标
+  for (name, result) in run_scripts(device_id, package, &snapshot) {
+    match result {
+      Ok(values) => snapshot.extra.extend(values),
+      Err(e) => {
+        snapshot.failures.insert(name, e.code().to_string());
+      }
+    }
+  }
+  Ok((snapshot, dumps.unwrap_or_default()))
+}
+
+/// 依赖目标进程的指标
+const APP_METRICS: &[MetricKey] = &[
+  MetricKey::Cpu,
+  MetricKey::Memory,
+  MetricKey::Traffic,
+  MetricKey::Fps,
+  MetricKey::FramePacing,
+];
+
+/// 取出采集结果：进程不存在时标记应用未运行，其余失败按错误码记入 `failures`
+fn settle<T>(snapshot: &mut MetricsSnapshot, metric: &MetricKey, result: Result<T>) -> Option<T> {
+  match result {
+    Ok(value) => Some(value),
+    Err(e) if e.is_process_not_found() => {
+      snapshot.process_state = ProcessState::NotRunning;
+      None
+    }
+    Err(e) => {
+      snapshot
+        .failures
+        .insert(metric.as_str().to_string(), e.code().to_string());
+      None
+    }
+  }
+}
+
+fn run_dump(
+  device_id: &str,
+  args: &[&str],
+  kind: &'static str,
+  dumps: &mut Option<Vec<RawDump>>,
+) -> Result<String> {
+  let raw = run_device(device_id, args)?;
+  if let Some(dumps) = dumps {
+    dumps.push(RawDump {
+      kind,
+      text: raw.clone(),
+    });
+  }
+  Ok(raw)
+}
+
+/// 应用主进程的 PID；指定用户时从该用户的进程中查找
+pub fn resolve_pid(device_id: &str, package: &str, user: Option<u32>) -> Result<String> {
+  if user.is_some() {
+    return package_processes(device_id, package, user)?
+      .into_iter()
+      .find(|p| p.name == package)
+      .map(|p| p.pid)
+      .ok_or(AdbError::Metric(MetricFailure::ProcessNotFound));
+  }
+  // 没有匹配的进程时 pidof 退出码为 1，新版 adb 会透传退出码，这里统一按空输出处理
+  let raw = run_device(device_id, &["shell", &format!("pidof {package} || true")])?;
+  pid_from_pidof(&raw)
+}
+
+fn pid_from_pidof(raw: &str) -> Result<String> {
+  raw.split_whitespace()
+    .next()
+    .map(|s| s.to_string())
+    .ok_or(AdbError::Metric(MetricFailure::ProcessNotFound))
+}
+
+/// 使用缓存的 PID 执行 `command`：同一次 adb 调用中先读取 `/proc/<pid>/cmdline`
+/// 确认进程仍属于该应用；进程已退出或 PID 被复用时重新 pidof 后再执行
+pub(crate) fn run_with_pid(
+  device_id: &str,
+  package: &str,
+  user: Option<u32>,
+  command: fn(&str) -> String,
+) -> Result<(String, String)> {
+  let key = (device_id.to_string(), package.to_string(), user);
+  let cached = PID_CACHE.lock().ok().and_then(|cache| cache.get(&key).cloned());
+  if let Some(pid) = cached {
+    let script = format!(
+      "tr '\\0' '\\n' < /proc/{pid}/cmdline 2>/dev/null | head -n 1; echo {PID_SEPARATOR}; {}",
+      command(&pid)
+    );
+    let raw = run_device(device_id, &["shell", &script])?;
+    if let Some((name, output)) = raw.split_once(PID_SEPARATOR) {
+      if name.trim() == package {
+        return Ok((pid, output.to_string()));
+      }
+    }
+    if let Ok(mut cache) = PID_CACHE.lock() {
+      cache.remove(&key);
+    }
+  }
+
+  let pid = resolve_pid(device_id, package, user)?;
+  let raw = run_device(device_id, &["shell", &command(&pid)])?;
+  if let Ok(mut cache) = PID_CACHE.lock() {
+    cache.insert(key, pid.clone());
+  }
+  Ok((pid, raw))
+}
+
+/// 列出应用的全部进程：名称为包名或以 `包名:` 开头的进程（含独立 UID 的沙箱进程），
+/// 以及与它们同 UID 的其他进程；系统 UID 为多个应用共享，不按 UID 归并。主进程排在最前。
+/// 指定 `user` 时只保留该用户下的进程
+pub fn package_processes(
+  device_id: &str,
+  package: &str,
+  user: Option<u32>,
+) -> Result<Vec<ProcessMetrics>> {
+  let raw = run_device(device_id, &["shell", "ps", "-A", "-o", "PID,UID,NAME"])?;
+  let rows: Vec<(&str, &str, &str)> = raw
+    .lines()
+    .filter_map(|line| {
+      let mut cols = line.split_whitespace();
+      Some((cols.next()?, cols.next()?, cols.next()?))
+    })
+    .filter(|(pid, ..)| pid.chars().all(|c| c.is_ascii_digit()))
+    .filter(|(_, uid, _)| match (user, uid.parse::<u32>()) {
+      (None, _) => true,
+      (Some(user), Ok(uid)) => uid_user(uid) == user,
+      (Some(_), Err(_)) => false,
+    })
+    .collect();
+
+  let prefix = format!("{package}:");
+  let owned = |name: &str| name == package || name.starts_with(&prefix);
+  let app_uids: HashSet<&str> = rows
+    .iter()
+    .filter(|(_, uid, name)| {
+      owned(name) && uid.parse::<u32>().is_ok_and(|u| u % PER_USER_RANGE >= 10_000)
+    })
+    .map(|(_, uid, _)| *uid)
+    .collect();
+
+  let mut processes: Vec<ProcessMetrics> = rows
+    .iter()
+    .filter(|(_, uid, name)| owned(name) || app_uids.contains(uid))
+    .map(|(pid, uid, name)| ProcessMetrics {
+      pid: pid.to_string(),
+      name: name.to_string(),
+      uid: uid.parse().ok(),
+      cpu: None,
+      memory_mb: None,
+    })
+    .collect();
+  if processes.is_empty() {
+    return Err(AdbError::Metric(MetricFailure::ProcessNotFound));
+  }
+  processes.sort_by_key(|p| (p.name != package, p.name.clone()));
+  Ok(processes)
+}
+
+/// 一次 top 读取多个进程的 CPU 占用
+fn fetch_processes_cpu(device_id: &str, pids: &[&str]) -> Result<HashMap<String, f64>> {
+  let raw = run_device(device_id, &["shell", &top_command(&pids.join(","))])?;
+  let usage = parse_top_cpu(&raw, pids);
+  if usage.is_empty() {
+    return Err(AdbError::Metric(MetricFailure::Cpu));
+  }
+  Ok(usage)
+}
+
+/// 在同一次 adb 调用中逐个读取进程的 meminfo
+fn fetch_processes_memory(
+  device_id: &str,
+  pids: &[&str],
+  dumps: &mut Option<Vec<RawDump>>,
+) -> Result<HashMap<String, f64>> {
+  let script = format!(
+    "for p in {}; do echo {PID_SEPARATOR} $p; dumpsys meminfo $p; done",
+    pids.join(" ")
+  );
+  let raw = run_dump(device_id, &["shell", &script], "meminfo", dumps)?;
+  let usage: HashMap<String, f64> = raw
+    .split(PID_SEPARATOR)
+    .filter_map(|chunk| {
+      let chunk = chunk.trim_start();
+      let (pid, body) = chunk.split_once(char::is_whitespace)?;
+      Some((pid.to_string(), parse_memory(body).ok()?))
+    })
+    .collect();
+  if usage.is_empty() {
+    return Err(AdbError::Metric(MetricFailure::Memory));
+  }
+  Ok(usage)
+}
+
+/// 保留表头，用于定位 CPU 列
+fn top_command(pid: &str) -> String {
+  format!("top -b -n 1 -p {pid}")
+}
+
+fn meminfo_command(pid: &str) -> String {
+  format!("dumpsys meminfo {pid}")
+}
+
+fn net_dev_command(pid: &str) -> String {
+  format!("cat /proc/{pid}/net/dev")
+}
+
+fn gfxinfo_command(pid: &str) -> String {
+  format!("dumpsys gfxinfo {pid}")
+}
+
+pub(crate) fn gfxinfo_framestats_command(pid: &str) -> String {
+  format!("dumpsys gfxinfo {pid} framestats")
+}
+
+pub(crate) fn fetch_cpu(device_id: &str, pid: &str) -> Result<f64> {
+  let raw = run_device(device_id, &["shell", &top_command(pid)])?;
+  parse_cpu(&raw, pid)
+}
+
+fn parse_cpu(raw: &str, pid: &str) -> Result<f64> {
+  parse_top_cpu(raw, &[pid])
+    .remove(pid)
+    .ok_or(AdbError::Metric(MetricFailure::Cpu))
+}
+
+pub(crate) fn fetch_memory(
+  device_id: &str,
+  package: &str,
+  dumps: &mut Option<Vec<RawDump>>,
+) -> Result<f64> {
+  let raw = run_dump(
+    device_id,
+    &["shell", "dumpsys", "meminfo", package],
+    "meminfo",
+    dumps,
+  )?;
+  if process_missing(&raw) {
+    return Err(AdbError::Metric(MetricFailure::ProcessNotFound));
+  }
+  parse_memory(&raw)
+}
+
+fn parse_memory(raw: &str) -> Result<f64> {
+  parse_meminfo_kb(raw)
+    .map(|kb| kb / 1024.0) // 转换为 MB
+    .ok_or(AdbError::Metric(MetricFailure::Memory))
+}
+
+/// 整机收发字节数与速率，首次采样只有累计值
+pub(crate) fn fetch_network(
+  device_id: &str,
+  interfaces: &InterfaceFilter,
+) -> Result<TrafficStats> {
+  let raw = run_device(device_id, &["shell", "cat", "/proc/net/dev"])?;
+  match parse_net_dev(&raw, |iface| interfaces.matches(iface)) {
+    (0, 0) => Err(AdbError::Metric(MetricFailure::Network)),
+    (rx_bytes, tx_bytes) => Ok(traffic_rates(
+      format!("{device_id}:system"),
+      rx_bytes,
+      tx_bytes,
+    )),
+  }
+}
+
+pub(crate) fn fetch_fps(
+  device_id: &str,
+  package: &str,
+  user: Option<u32>,
+  dumps: &mut Option<Vec<RawDump>>,
+) -> Result<FrameStats> {
+  // 汇总统计缺失时（部分 Android 8/9 ROM）用 framestats 的帧时间戳计数；
+  // 版本未知时同样请求，旧版本会忽略该参数
+  let framestats = sdk_level(device_id).map_or(true, |sdk| sdk >= FRAMESTATS_SDK);
+  let raw = match user {
+    // 各用户下的同名应用是不同进程，按包名查询会混在一起，指定用户时按缓存的主进程 PID 查询
+    Some(_) => {
+      let command = if framestats {
+        gfxinfo_framestats_command
+      } else {
+        gfxinfo_command
+      };
+      let (_, raw) = run_with_pid(device_id, package, user, command)?;
+      if let Some(dumps) = dumps {
+        dumps.push(RawDump {
+          kind: "gfxinfo",
+          text: raw.clone(),
+        });
+      }
+      raw
+    }
+    None => {
+      let mut args = vec!["shell", "dumpsys", "gfxinfo", package];
+      if framestats {
+        args.push("framestats");
+      }
+      run_dump(device_id, &args, "gfxinfo", dumps)?
+    }
+  };
+  if process_missing(&raw) {
+    return Err(AdbError::Metric(MetricFailure::ProcessNotFound));
+  }
+
+  // 汇总统计与 framestats 都没有时返回错误
+  let gfx = parse_gfxinfo(&raw).ok_or(AdbError::Metric(MetricFailure::Frames))?;
+
+  // 获取当前时间戳
+  let now = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap()
+    .as_millis() as u64;
+
+  // 计算FPS（基于历史数据）
+  let key = match user {
+    Some(user) => format!("{}:{}:{}", device_id, package, user),
+    None => format!("{}:{}", device_id, package),
+  };
+  // 首次采样只记录基线，间隔过短时保留原基线，两种情况都不给出帧率
+  let fps = match FPS_HISTORY.lock() {
+    Ok(mut history) => {
+      let fps = match history.get(&key) {
+        Some(prev) => {
+          let time_diff_sec = now.saturating_sub(prev.timestamp) as f64 / 1000.0;
+          (time_diff_sec > MIN_FPS_INTERVAL_SEC).then(|| {
+            let frame_diff = match (gfx.total_frames, prev.total_frames) {
+              (Some(total), Some(prev_total)) => total.saturating_sub(prev_total),
+              // 只有 framestats 时统计上次以来新完成的帧
+              _ => gfx
+                .completed_ns
+                .iter()
+                .filter(|t| prev.last_frame_ns.map_or(true, |last| **t > last))
+                .count() as u64,
+            };
+            (frame_diff as f64) / time_diff_sec
+          })
+        }
+        None => None,
+      };
+      let baseline = history.contains_key(&key);
+      if fps.is_some() || !baseline {
+        history.insert(
+          key,
+          FpsHistory {
+            total_frames: gfx.total_frames,
+            last_frame_ns: gfx.completed_ns.last().copied(),
+            timestamp: now,
+          },
+        );
+      }
+      fps
+    }
+    Err(_) => None,
+  };
+
+  // 平均帧时间优先取 90 分位，没有时由帧率换算
+  let avg_frame_time = gfx
+    .p90_ms
+    .or_else(|| fps.filter(|f| *f > 0.0).map(|f| 1000.0 / f));
+
+  // 使用卡顿帧数作为 jank_count
+  let jank_count = gfx.janky_frames.unwrap_or(0);
+
+  // 构造帧时间数组（包含90th和95th百分位数）
+  let frame_times = avg_frame_time.into_iter().chain(gfx.p95_ms).collect();
+
+  Ok(FrameStats {
+    fps,
+    avg_frame_time,
+    frame_times,
+    jank_count,
+  })
+}
+
+/// 一次功耗采样：瞬时电流与应用累计耗电是两个量，分开保存
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PowerReading {
+  pub(crate) current_ma: Option<f64>,
+  pub(crate) app_mah: Option<f64>,
+}
+
+pub(crate) fn fetch_power(device_id: &str, package: &str) -> Result<PowerReading> {
+  // 瞬时电流用于积分耗电量；没有有效读数时不以电压代替，前端显示 N/A 更合适
+  let current_ma = run_device(device_id, &["shell", "dumpsys", "battery"])
+    .ok()
+    .and_then(|raw| parse_current_now_ma(&raw));
+  // batterystats 给出的是自上次重置以来的累计值，单独记录
+  let app_mah = run_device(device_id, &["shell", "dumpsys", "batterystats", package])
+    .ok()
+    .and_then(|raw| parse_batterystats_power(&raw));
+  if current_ma.is_none() && app_mah.is_none() {
+    return Err(AdbError::Metric(MetricFailure::Power));
+  }
+  Ok(PowerReading {
+    current_ma,
+    app_mah,
+  })
+}
+
+pub(crate) fn fetch_battery(device_id: &str) -> Result<BatteryStats> {
+  let raw = run_device(device_id, &["shell", "dumpsys", "battery"])?;
+  let BatteryReading {
+    level,
+    temp_c,
+    voltage_v,
+  } = parse_battery(&raw);
+  if level.is_none() && temp_c.is_none() && voltage_v.is_none() {
+    return Err(AdbError::Metric(MetricFailure::Battery));
+  }
+
+  Ok(BatteryStats { level, temp_c, voltage_v })
+}
+
+pub(crate) fn fetch_traffic(
+  device_id: &str,
+  pid: &str,
+  interfaces: &InterfaceFilter,
+) -> Result<TrafficStats> {
+  let raw = run_device(device_id, &["shell", &net_dev_command(pid)])?;
+  parse_traffic(device_id, pid, &raw, interfaces)
+}
+
+/// 按 UID 汇总应用全部进程的流量，读取 `/proc/net/xt_qtaguid/stats`；
+/// Android 10 起该文件已移除，此时返回 None
+fn fetch_uid_traffic(
+  device_id: &str,
+  package: &str,
+  uids: &[u32],
+  interfaces: &InterfaceFilter,
+) -> Option<TrafficStats> {
+  if uids.is_empty() {
+    return None;
+  }
+  let raw = run_device(
+    device_id,
+    &["shell", "cat /proc/net/xt_qtaguid/stats 2>/dev/null"],
+  )
+  .ok()?;
+  let (rx_bytes, tx_bytes) = parse_qtaguid(&raw, uids, |iface| interfaces.matches(iface))?;
+  Some(traffic_rates(
+    format!("{device_id}:{package}:uid"),
+    rx_bytes,
+    tx_bytes,
+  ))
+}
+
+/// 解析 `/proc/<pid>/net/dev` 并与上次读数比较得到速率
+fn parse_traffic(
+  device_id: &str,
+  pid: &str,
+  raw: &str,
+  interfaces: &InterfaceFilter,
+) -> Result<TrafficStats> {
+  let (rx_bytes, tx_bytes) = parse_net_dev(raw, |iface| interfaces.matches(iface));
+  if rx_bytes == 0 && tx_bytes == 0 {
+    return Err(AdbError::Metric(MetricFailure::NetworkInterface));
+  }
+  Ok(traffic_rates(format!("{device_id}:{pid}"), rx_bytes, tx_bytes))
+}
+
+/// 与 `key` 上次的累计值比较得到速率，计数器回绕或重置时按 0 计
+fn traffic_rates(key: String, rx_bytes: u64, tx_bytes: u64) -> TrafficStats {
+  let now = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_millis() as u64;
+
+  let mut rx_bps = None;
+  let mut tx_bps = None;
+
+  if let Ok(mut history) = TRAFFIC_HISTORY.lock() {
+    if let Some(prev) = history.get(&key) {
+      let dt_ms = now.saturating_sub(prev.timestamp).max(1);
+      let rx_diff = rx_bytes.saturating_sub(prev.rx_bytes);
+      let tx_diff = tx_bytes.saturating_sub(prev.tx_bytes);
+      rx_bps = Some((rx_diff as f64) * 1000.0 / (dt_ms as f64));
+      tx_bps = Some((tx_diff as f64) * 1000.0 / (dt_ms as f64));
+    }
+
+    history.insert(
+      key,
+      TrafficHistory {
+        rx_bytes,
+        tx_bytes,
+        timestamp: now,
+      },
+    );
+  }
+
+  TrafficStats {
+    rx_bytes,
+    tx_bytes,
+    rx_bps,
+    tx_bps,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn pidof_without_match_means_not_running() {
+    assert!(pid_from_pidof("").unwrap_err().is_process_not_found());
+    assert!(pid_from_pidof("\n").unwrap_err().is_process_not_found());
+    assert_eq!(pid_from_pidof("12345 12346\n").unwrap(), "12345");
+  }
+}
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+pub mod app;
+pub mod bugreport;
+pub mod capabilities;
+pub mod command;
+pub mod connectivity;
+pub mod cpufreq;
+pub mod device;
+pub mod display;
+pub mod dropbox;
+pub mod energy;
+pub mod error;
+pub mod frames;
+pub mod game;
+pub mod hwui;
+pub mod idle;
+pub mod input;
+pub mod logcat;
+pub mod metrics;
+pub mod monkey;
+// 仅在 bench 特性下为基准测试公开，不属于稳定接口
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod parse;
+#[cfg(not(feature = "bench"))]
+pub(crate) mod parse;
+pub mod perfetto;
+pub mod procstats;
+pub mod profiling;
+pub mod provider;
+pub mod proxy;
+pub mod screen;
+pub mod script;
+pub mod settings;
+pub mod tcpdump;
+pub mod thermal;
+pub mod user;
+
+pub use app::{app_version, list_apps};
+pub use command::{set_adb_path, set_bundled_adb_path};
+pub use device::{cpu_cores, device_snapshot, list_devices};
+pub use metrics::{
+  collect_metrics, collect_metrics_with, snapshot_values, CollectOptions, InterfaceFilter,
+  MetricKey, MetricValue, MetricsSnapshot, ProcessState, RawDump,
+};
+pub use screen::screencap_png;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceInfo {
+  pub id: String,
+  pub model: Option<String>,
+  pub state: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppInfo {
+  pub package: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub label: Option<String>,
+  #[serde(default)]
+  pub is_system: bool,
+}
+
+/// 会话开始时记录的设备信息快照（来自 getprop）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeviceSnapshot {
+  pub manufacturer: Option<String>,
+  pub brand: Option<String>,
+  pub model: Option<String>,
+  pub device: Option<String>,
+  pub android_release: Option<String>,
+  pub sdk_int: Option<u32>,
+  pub build_id: Option<String>,
+  pub build_fingerprint: Option<String>,
+  pub abi: Option<String>,
+  pub hardware: Option<String>,
+}
+
+/// `am start -W` 报告的启动耗时
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LaunchTiming {
+  /// COLD / WARM / HOT，Android 10 以下不输出
+  pub launch_state: Option<String>,
+  pub this_time_ms: Option<u64>,
+  pub total_time_ms: Option<u64>,
+  pub wait_time_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppVersion {
+  pub version_name: Option<String>,
+  pub version_code: Option<i64>,
+}
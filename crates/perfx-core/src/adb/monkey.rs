@@ -0,0 +1,100 @@
+use crate::adb::{command::run_device, error::Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const MONKEY_PROCESS: &str = "com.android.commands.monkey";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonkeyConfig {
+  /// 随机种子，相同种子与参数可复现同一事件序列；不指定时自动生成
+  pub seed: Option<u64>,
+  pub events: u32,
+  /// 事件间隔
+  pub throttle_ms: u32,
+  /// 只启动这些 category 的 Activity，如 android.intent.category.LAUNCHER
+  pub categories: Vec<String>,
+  /// 发生崩溃 / ANR 后继续注入事件
+  pub ignore_crashes: bool,
+  pub ignore_timeouts: bool,
+  /// 事件类型占比，如 `{"touch": 50, "motion": 20}`，对应 monkey 的 `--pct-*` 参数
+  pub pct: BTreeMap<String, u32>,
+}
+
+impl Default for MonkeyConfig {
+  fn default() -> Self {
+    Self {
+      seed: None,
+      events: 10_000,
+      throttle_ms: 300,
+      categories: Vec::new(),
+      ignore_crashes: false,
+      ignore_timeouts: false,
+      pct: BTreeMap::new(),
+    }
+  }
+}
+
+impl MonkeyConfig {
+  /// 生成 `adb shell` 执行的完整命令
+  pub fn command(&self, package: &str, seed: u64) -> String {
+    let mut command = format!(
+      "monkey -p {package} -s {seed} --throttle {} -v -v",
+      self.throttle_ms
+    );
+    for category in &self.categories {
+      command.push_str(&format!(" -c {category}"));
+    }
+    for (kind, percent) in &self.pct {
+      let kind: String = kind
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+      command.push_str(&format!(" --pct-{kind} {percent}"));
+    }
+    if self.ignore_crashes {
+      command.push_str(" --ignore-crashes");
+    }
+    if self.ignore_timeouts {
+      command.push_str(" --ignore-timeouts");
+    }
+    command.push_str(&format!(" {}", self.events.max(1)));
+    command
+  }
+}
+
+/// monkey 输出中需要关注的行
+#[derive(Debug, Clone, PartialEq)]
+pub enum MonkeyLine {
+  /// `// CRASH: com.x (pid 1234)`
+  Crash(String),
+  /// `// NOT RESPONDING: com.x (pid 1234)`
+  Anr(String),
+  /// `Events injected: 1000`
+  Injected(u32),
+  /// `** Monkey aborted due to error.`
+  Aborted,
+}
+
+pub fn parse_line(line: &str) -> Option<MonkeyLine> {
+  let line = line.trim();
+  if let Some(rest) = line.strip_prefix("// CRASH:") {
+    return Some(MonkeyLine::Crash(rest.trim().to_string()));
+  }
+  if let Some(rest) = line.strip_prefix("// NOT RESPONDING:") {
+    return Some(MonkeyLine::Anr(rest.trim().to_string()));
+  }
+  if let Some(rest) = line.strip_prefix("Events injected:") {
+    return rest.trim().parse().ok().map(MonkeyLine::Injected);
+  }
+  if line.starts_with("** Monkey aborted") {
+    return Some(MonkeyLine::Aborted);
+  }
+  None
+}
+
+/// 结束设备上的 monkey 进程，断开 adb shell 并不会让它退出
+pub fn kill_monkey(device_id: &str) -> Result<()> {
+  run_device(device_id, &["shell", "pkill", "-f", MONKEY_PROCESS])?;
+  Ok(())
+}
@@ -0,0 +1,552 @@
+//! dumpsys / top / meminfo 等命令输出的解析，均为纯函数，便于用各厂商 ROM 的样例输出测试。
+//! 不同 ROM 与系统版本的列顺序、表头写法差异较大，尽量按表头定位而不是按固定位置。
+//! 每次采样都会解析，gfxinfo framestats 可达上百 KB：逐行直接在 `&str` 切片上取字段，
+//! 不为每行分配 Vec 或 String
+
+use std::{borrow::Cow, collections::HashMap};
+
+/// dumpsys gfxinfo 的帧统计；汇总段缺失时（部分 Android 8/9 ROM）只有 framestats 时间戳
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GfxInfo {
+  pub total_frames: Option<u64>,
+  pub janky_frames: Option<u32>,
+  pub p90_ms: Option<f64>,
+  pub p95_ms: Option<f64>,
+  /// framestats 中各帧的 FrameCompleted 时间（纳秒），未请求 framestats 时为空
+  pub completed_ns: Vec<u64>,
+}
+
+/// dumpsys battery 的电量、温度与电压
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatteryReading {
+  pub level: Option<f64>,
+  pub temp_c: Option<f64>,
+  pub voltage_v: Option<f64>,
+}
+
+/// 去掉部分 ROM 的 top 在表头上输出的 ANSI 反显控制符，没有时原样借用
+fn strip_ansi(line: &str) -> Cow<'_, str> {
+  if !line.contains('\u{1b}') {
+    return Cow::Borrowed(line);
+  }
+  let mut out = String::with_capacity(line.len());
+  let mut chars = line.chars();
+  while let Some(c) = chars.next() {
+    if c == '\u{1b}' {
+      // 跳过 ESC [ ... 字母
+      for c in chars.by_ref() {
+        if c.is_ascii_alphabetic() {
+          break;
+        }
+      }
+      continue;
+    }
+    out.push(c);
+  }
+  Cow::Owned(out)
+}
+
+/// top 输出中各进程的 CPU 占用。按表头定位 CPU 列：toybox 为 `S[%CPU]`（表头一列对应数据两列），
+/// 部分 ROM 为独立的 `%CPU`，旧版 toolbox 为 `CPU%` 且数值带 `%`；没有表头时按 toybox 默认列序
+pub fn parse_top_cpu(raw: &str, pids: &[&str]) -> HashMap<String, f64> {
+  // toybox 默认: PID USER PR NI VIRT RES SHR S %CPU
+  let mut column = 8;
+  let mut usage = HashMap::new();
+  for line in raw.lines() {
+    let line = strip_ansi(line);
+    if line.split_whitespace().any(|p| p == "PID") {
+      if let Some((index, name)) = line
+        .split_whitespace()
+        .enumerate()
+        .find(|(_, p)| p.contains("CPU"))
+      {
+        column = if name.starts_with("S[") {
+          index + 1
+        } else {
+          index
+        };
+      }
+      continue;
+    }
+    let mut parts = line.split_whitespace();
+    let Some(pid) = parts.next().filter(|p| pids.contains(p)) else {
+      continue;
+    };
+    // 表头中 PID 总在 CPU 列之前，column 至少为 1
+    if let Some(value) = parts
+      .nth(column.saturating_sub(1))
+      .and_then(|v| v.trim_end_matches('%').parse::<f64>().ok())
+    {
+      // 多核设备上单进程可超过 100%，不做封顶
+      usage.insert(pid.to_string(), value);
+    }
+  }
+  usage
+}
+
+/// dumpsys meminfo / gfxinfo 在应用未运行时输出 `No process found for: <包名>`
+pub fn process_missing(raw: &str) -> bool {
+  raw.contains("No process found")
+}
+
+/// `/sys/devices/system/cpu/present` 形如 `0-7` 或 `0-3,6-7` 的核列表，返回核数
+pub fn parse_cpu_list(raw: &str) -> Option<u32> {
+  raw.trim().split(',').try_fold(0, |count, range| {
+    let (start, end) = range.split_once('-').unwrap_or((range, range));
+    let (start, end): (u32, u32) = (start.trim().parse().ok()?, end.trim().parse().ok()?);
+    Some(count + end.checked_sub(start)? + 1)
+  })
+}
+
+/// dumpsys meminfo 中的 PSS 合计（KB）。兼容表格行 `TOTAL 123456 ...`、
+/// 新版摘要 `TOTAL PSS: 123456` 与个别 ROM 的 `TOTAL: 123,456K`
+pub fn parse_meminfo_kb(raw: &str) -> Option<f64> {
+  raw
+    .lines()
+    .map(str::trim)
+    .filter(|line| line.starts_with("TOTAL"))
+    .find_map(|line| line.split_whitespace().find_map(parse_kb))
+}
+
+/// `123,456K` 之类的数值，只在带千分位时分配
+fn parse_kb(token: &str) -> Option<f64> {
+  let token = token.trim_end_matches(['K', 'k', 'B']);
+  if token.contains(',') {
+    token.replace(',', "").parse().ok()
+  } else {
+    token.parse().ok()
+  }
+}
+
+/// 解析汇总统计与 framestats，两者都没有时返回 None
+pub fn parse_gfxinfo(raw: &str) -> Option<GfxInfo> {
+  let mut info = GfxInfo::default();
+  let mut legacy_janky = None;
+  let percentile =
+    |value: &str| -> Option<f64> { value.trim().trim_end_matches("ms").trim().parse().ok() };
+  let janky = |value: &str| -> Option<u32> { value.split('(').next()?.trim().parse().ok() };
+
+  for line in raw.lines() {
+    let line = line.trim();
+    if let Some(total) = line.strip_prefix("Total frames rendered:") {
+      info.total_frames = total.trim().parse::<u64>().ok();
+    } else if let Some(value) = line.strip_prefix("Janky frames:") {
+      // "Janky frames: 50 (4.17%)"
+      info.janky_frames = janky(value);
+    } else if let Some(value) = line.strip_prefix("Janky frames (legacy):") {
+      // Android 12 起按帧截止时间判定卡顿，旧算法的结果另起一行，仅在新行缺失时使用
+      legacy_janky = janky(value);
+    } else if let Some(p90) = line.strip_prefix("90th percentile:") {
+      info.p90_ms = percentile(p90);
+    } else if let Some(p95) = line.strip_prefix("95th percentile:") {
+      info.p95_ms = percentile(p95);
+    }
+  }
+  info.janky_frames = info.janky_frames.or(legacy_janky);
+  info.completed_ns = parse_framestats(raw);
+  (info.total_frames.is_some() || !info.completed_ns.is_empty()).then_some(info)
+}
+
+pub fn parse_framestats(raw: &str) -> Vec<u64> {
+  let mut column = None;
+  let mut timestamps = Vec::new();
+  for line in raw.lines().map(str::trim) {
+    let mut fields = line.split(',');
+    let first = fields.next();
+    if first == Some("Flags") {
+      column = line.split(',').position(|f| f == "FrameCompleted");
+      continue;
+    }
+    let Some(index) = column else {
+      continue;
+    };
+    if line.starts_with("---") {
+      column = None;
+      continue;
+    }
+    if first != Some("0") {
+      continue;
+    }
+    // 首列已取出，FrameCompleted 不会是首列
+    if let Some(t) = fields
+      .nth(index.saturating_sub(1))
+      .and_then(|f| f.parse::<u64>().ok())
+    {
+      timestamps.push(t);
+    }
+  }
+  timestamps.sort_unstable();
+  timestamps.dedup();
+  timestamps
+}
+
+pub fn parse_battery(raw: &str) -> BatteryReading {
+  let mut reading = BatteryReading::default();
+  for line in raw.lines() {
+    let line = line.trim();
+    if let Some(rest) = line.strip_prefix("level:") {
+      reading.level = rest.trim().parse::<f64>().ok();
+    } else if let Some(rest) = line.strip_prefix("temperature:") {
+      // 单位为 0.1℃
+      reading.temp_c = rest.trim().parse::<f64>().ok().map(|t| t / 10.0);
+    } else if let Some(rest) = line.strip_prefix("voltage:") {
+      // 单位通常为 mV，个别机型直接给出 V
+      if let Ok(raw_voltage) = rest.trim().parse::<f64>() {
+        let volts = if raw_voltage > 100.0 {
+          raw_voltage / 1000.0
+        } else {
+          raw_voltage
+        };
+        reading.voltage_v = Some(volts).filter(|v| *v > 0.0);
+      }
+    }
+  }
+  reading
+}
+
+/// dumpsys battery 的 `current now`（微安）换算为毫安，过小的读数视为无效
+pub fn parse_current_now_ma(raw: &str) -> Option<f64> {
+  raw
+    .lines()
+    .map(str::trim)
+    .find_map(|line| line.strip_prefix("current now:"))
+    .and_then(|value| value.trim().parse::<f64>().ok())
+    .filter(|current| current.abs() > 100.0)
+    .map(|current| current / 1000.0)
+}
+
+/// batterystats 中应用的估算耗电（mAh）。部分 ROM 直接写在
+/// `Estimated power use (mAh): 12.3` 同一行，多数版本在该段下的 `Uid u0a123: 12.3 ( cpu=... )` 行
+pub fn parse_batterystats_power(raw: &str) -> Option<f64> {
+  let first_number = |value: &str| -> Option<f64> {
+    value
+      .split("mAh")
+      .next()?
+      .split_whitespace()
+      .next()?
+      .parse()
+      .ok()
+  };
+  let mut in_section = false;
+  for line in raw.lines() {
+    let line = line.trim();
+    if line.starts_with("Estimated power use") {
+      let inline = line
+        .split_once("):")
+        .or_else(|| line.split_once(':'))
+        .and_then(|(_, v)| first_number(v));
+      if inline.is_some() {
+        return inline;
+      }
+      in_section = true;
+    } else if in_section && line.starts_with("Uid ") {
+      if let Some(power) = line.split_once(':').and_then(|(_, v)| first_number(v)) {
+        return Some(power);
+      }
+    } else if line.is_empty() {
+      in_section = false;
+    }
+  }
+  None
+}
+
+/// `/proc/net/dev` 格式中 `keep` 选中接口的累计收发字节
+pub fn parse_net_dev(raw: &str, keep: impl Fn(&str) -> bool) -> (u64, u64) {
+  let mut rx_bytes: u64 = 0;
+  let mut tx_bytes: u64 = 0;
+
+  for line in raw.lines() {
+    // 旧内核接口名与数据之间没有空格，如 "wlan0:123 ..."
+    let Some((iface, payload)) = line.split_once(':') else {
+      continue;
+    };
+    if !keep(iface.trim()) {
+      continue;
+    }
+    // 共 16 列：第 1 列为接收字节，第 9 列为发送字节
+    let mut cols = payload.split_whitespace();
+    let (Some(rx), Some(tx), Some(_)) = (cols.next(), cols.nth(7), cols.nth(6)) else {
+      continue;
+    };
+    rx_bytes = rx_bytes.saturating_add(rx.parse::<u64>().unwrap_or(0));
+    tx_bytes = tx_bytes.saturating_add(tx.parse::<u64>().unwrap_or(0));
+  }
+  (rx_bytes, tx_bytes)
+}
+
+/// `/proc/net/xt_qtaguid/stats` 中属于 `uids`、`keep` 选中接口的累计收发字节；
+/// 只计未打标签（acct_tag_hex 为 0x0）的行，否则同一流量会按标签重复计入。没有表头时返回 None
+pub fn parse_qtaguid(raw: &str, uids: &[u32], keep: impl Fn(&str) -> bool) -> Option<(u64, u64)> {
+  let mut lines = raw.lines();
+  // idx iface acct_tag_hex uid_tag_int cnt_set rx_bytes rx_packets tx_bytes ...
+  lines.find(|l| l.starts_with("idx "))?;
+  let mut rx_bytes: u64 = 0;
+  let mut tx_bytes: u64 = 0;
+  for line in lines {
+    let mut cols = line.split_whitespace().skip(1);
+    let (Some(iface), Some(tag), Some(uid), Some(rx), Some(tx)) = (
+      cols.next(),
+      cols.next(),
+      cols.next(),
+      cols.nth(1),
+      cols.nth(1),
+    ) else {
+      continue;
+    };
+    if tag != "0x0" || !uid.parse::<u32>().is_ok_and(|u| uids.contains(&u)) || !keep(iface) {
+      continue;
+    }
+    rx_bytes = rx_bytes.saturating_add(rx.parse::<u64>().unwrap_or(0));
+    tx_bytes = tx_bytes.saturating_add(tx.parse::<u64>().unwrap_or(0));
+  }
+  Some((rx_bytes, tx_bytes))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::adb::metrics::InterfaceFilter;
+
+  macro_rules! fixture {
+    ($path:literal) => {
+      include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/",
+        $path
+      ))
+    };
+  }
+
+  #[test]
+  fn top_toybox_merged_state_column() {
+    let usage = parse_top_cpu(fixture!("top/pixel_android14.txt"), &["12345", "12402"]);
+    assert_eq!(usage.get("12345"), Some(&23.3));
+    assert_eq!(usage.get("12402"), Some(&3.0));
+  }
+
+  #[test]
+  fn top_separate_cpu_column() {
+    let usage = parse_top_cpu(fixture!("top/coloros_android13.txt"), &["8123"]);
+    assert_eq!(usage.get("8123"), Some(&41.7));
+  }
+
+  #[test]
+  fn top_header_with_ansi_escapes() {
+    let usage = parse_top_cpu(fixture!("top/miui_android12.txt"), &["5120"]);
+    assert_eq!(usage.get("5120"), Some(&12.0));
+  }
+
+  #[test]
+  fn top_legacy_toolbox_percent_suffix() {
+    let usage = parse_top_cpu(fixture!("top/android6_toolbox.txt"), &["3021"]);
+    assert_eq!(usage.get("3021"), Some(&17.0));
+  }
+
+  #[test]
+  fn top_quiet_output_without_header() {
+    let usage = parse_top_cpu(fixture!("top/oneui_quiet.txt"), &["9876"]);
+    assert_eq!(usage.get("9876"), Some(&8.6));
+  }
+
+  #[test]
+  fn top_keeps_multicore_usage_above_100() {
+    let usage = parse_top_cpu(fixture!("top/pixel_android14.txt"), &["12500"]);
+    assert_eq!(usage.get("12500"), Some(&187.0));
+  }
+
+  #[test]
+  fn cpu_list_counts_ranges() {
+    assert_eq!(parse_cpu_list("0-7\n"), Some(8));
+    assert_eq!(parse_cpu_list("0-3,6-7"), Some(6));
+    assert_eq!(parse_cpu_list("0"), Some(1));
+    assert_eq!(parse_cpu_list(""), None);
+  }
+
+  #[test]
+  fn meminfo_table_row() {
+    assert_eq!(
+      parse_meminfo_kb(fixture!("meminfo/android7.txt")),
+      Some(87_542.0)
+    );
+  }
+
+  #[test]
+  fn meminfo_summary_line() {
+    assert_eq!(
+      parse_meminfo_kb(fixture!("meminfo/pixel_android14.txt")),
+      Some(203_918.0)
+    );
+  }
+
+  #[test]
+  fn meminfo_thousands_separator() {
+    assert_eq!(
+      parse_meminfo_kb(fixture!("meminfo/oneui_android13.txt")),
+      Some(154_321.0)
+    );
+  }
+
+  #[test]
+  fn meminfo_missing_process() {
+    assert_eq!(parse_meminfo_kb(fixture!("meminfo/not_found.txt")), None);
+  }
+
+  #[test]
+  fn gfxinfo_modern() {
+    let info = parse_gfxinfo(fixture!("gfxinfo/pixel_android14.txt")).unwrap();
+    assert_eq!(info.total_frames, Some(4_821));
+    assert_eq!(info.janky_frames, Some(103));
+    assert_eq!(info.p90_ms, Some(14.0));
+    assert_eq!(info.p95_ms, Some(19.0));
+  }
+
+  #[test]
+  fn gfxinfo_legacy_janky_line_ignored() {
+    let info = parse_gfxinfo(fixture!("gfxinfo/miui_android12.txt")).unwrap();
+    assert_eq!(info.total_frames, Some(1_530));
+    assert_eq!(info.janky_frames, Some(42));
+  }
+
+  #[test]
+  fn gfxinfo_old_android() {
+    let info = parse_gfxinfo(fixture!("gfxinfo/android6.txt")).unwrap();
+    assert_eq!(info.total_frames, Some(912));
+    assert_eq!(info.janky_frames, Some(77));
+    assert_eq!(info.p90_ms, Some(21.0));
+    assert_eq!(info.p95_ms, None);
+  }
+
+  #[test]
+  fn gfxinfo_framestats_without_summary() {
+    let info = parse_gfxinfo(fixture!("gfxinfo/emui_android9_framestats.txt")).unwrap();
+    assert_eq!(info.total_frames, None);
+    assert_eq!(
+      info.completed_ns,
+      vec![1_000_020_000_000, 1_000_036_700_000, 1_000_053_300_000]
+    );
+  }
+
+  #[test]
+  fn gfxinfo_legacy_janky_only() {
+    let info = parse_gfxinfo(fixture!("gfxinfo/legacy_janky_only.txt")).unwrap();
+    assert_eq!(info.total_frames, Some(2_208));
+    assert_eq!(info.janky_frames, Some(131));
+  }
+
+  #[test]
+  fn gfxinfo_without_frames() {
+    assert_eq!(parse_gfxinfo(fixture!("gfxinfo/no_window.txt")), None);
+  }
+
+  #[test]
+  fn battery_millivolts() {
+    let reading = parse_battery(fixture!("battery/pixel_android14.txt"));
+    assert_eq!(reading.level, Some(87.0));
+    assert_eq!(reading.temp_c, Some(31.0));
+    assert_eq!(reading.voltage_v, Some(4.123));
+    assert_eq!(
+      parse_current_now_ma(fixture!("battery/pixel_android14.txt")),
+      None
+    );
+  }
+
+  #[test]
+  fn battery_volts_and_current() {
+    let raw = fixture!("battery/coloros_android13.txt");
+    let reading = parse_battery(raw);
+    assert_eq!(reading.level, Some(54.0));
+    assert_eq!(reading.voltage_v, Some(3.98));
+    assert_eq!(parse_current_now_ma(raw), Some(-512.0));
+  }
+
+  #[test]
+  fn battery_vendor_prefixed_fields_ignored() {
+    let reading = parse_battery(fixture!("battery/miui_android12.txt"));
+    assert_eq!(reading.level, Some(66.0));
+    assert_eq!(reading.temp_c, Some(29.5));
+  }
+
+  #[test]
+  fn batterystats_uid_line() {
+    assert_eq!(
+      parse_batterystats_power(fixture!("batterystats/android9.txt")),
+      Some(12.3)
+    );
+  }
+
+  #[test]
+  fn batterystats_inline_value() {
+    assert_eq!(
+      parse_batterystats_power(fixture!("batterystats/emui_inline.txt")),
+      Some(8.75)
+    );
+  }
+
+  fn net_dev(raw: &str, filter: InterfaceFilter) -> (u64, u64) {
+    parse_net_dev(raw, |iface| filter.matches(iface))
+  }
+
+  #[test]
+  fn qtaguid_sums_untagged_rows_of_app_uids() {
+    let raw = "\
+idx iface acct_tag_hex uid_tag_int cnt_set rx_bytes rx_packets tx_bytes tx_packets
+2 wlan0 0x0 10123 0 1000 10 200 2
+3 wlan0 0x0 10123 1 3000 30 400 4
+4 wlan0 0x3e800000000 10123 0 1000 10 200 2
+5 wlan0 0x0 99001 0 500 5 50 1
+6 wlan0 0x0 10200 0 7000 70 700 7
+7 lo 0x0 10123 0 9000 90 900 9
+";
+    let keep = |iface: &str| InterfaceFilter::Default.matches(iface);
+    assert_eq!(parse_qtaguid(raw, &[10123, 99001], keep), Some((4500, 650)));
+    assert_eq!(parse_qtaguid("", &[10123], keep), None);
+  }
+
+  #[test]
+  fn net_dev_skips_loopback_and_sums_interfaces() {
+    assert_eq!(
+      net_dev(
+        fixture!("net_dev/pixel_android14.txt"),
+        InterfaceFilter::Default
+      ),
+      (1_500_000 + 20_000, 300_000 + 4_000)
+    );
+  }
+
+  #[test]
+  fn net_dev_legacy_no_space_after_colon() {
+    assert_eq!(
+      net_dev(fixture!("net_dev/android5.txt"), InterfaceFilter::Default),
+      (987_654, 123_456)
+    );
+  }
+
+  #[test]
+  fn net_dev_vpn_interfaces_on_request() {
+    let raw = fixture!("net_dev/always_on_vpn.txt");
+    assert_eq!(net_dev(raw, InterfaceFilter::Default), (0, 0));
+    assert_eq!(net_dev(raw, InterfaceFilter::WithVpn), (640_000, 96_000));
+    assert_eq!(
+      net_dev(raw, InterfaceFilter::AllExceptLoopback),
+      (640_000 + 512, 96_000 + 256)
+    );
+    assert_eq!(
+      net_dev(
+        raw,
+        InterfaceFilter::Prefixes {
+          prefixes: vec!["tun".into()]
+        }
+      ),
+      (640_000, 96_000)
+    );
+  }
+
+  #[test]
+  fn large_dumps() {
+    let info = parse_gfxinfo(fixture!("gfxinfo/synthetic_large_framestats.txt")).unwrap();
+    assert_eq!(info.total_frames, Some(36_480));
+    assert_eq!(info.completed_ns.len(), 450);
+    let top = fixture!("top/synthetic_large.txt");
+    assert_eq!(parse_top_cpu(top, &["23456"]).get("23456"), Some(&41.0));
+  }
+}
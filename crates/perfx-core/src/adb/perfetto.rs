@@ -0,0 +1,266 @@
+use crate::adb::{
+  command::run_device,
+  device::get_props,
+  error::{AdbError, Result},
+  profiling::{push_file, remove_file, DEVICE_TMP},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Android 12 起 perfetto 只能写入该目录，shell 用户可读
+pub const TRACE_DIR: &str = "/data/misc/perfetto-traces";
+/// 卡顿时触发停止的 trigger 名称
+pub const JANK_TRIGGER: &str = "perfx_jank";
+// trigger 到达后继续记录的时长，覆盖卡顿后的恢复过程
+const TRIGGER_STOP_DELAY_MS: u64 = 3000;
+// 等待 trigger 的最长时间，覆盖一次录制即可
+const TRIGGER_TIMEOUT_MS: u64 = 24 * 3600 * 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceCategory {
+  /// 调度、唤醒与 CPU 频率 / 空闲状态
+  Sched,
+  /// 渲染、View、输入与 SurfaceFlinger 帧时间线
+  Gfx,
+  Binder,
+  /// 进程 RSS、LMK 与系统内存计数
+  Memory,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PerfettoConfig {
+  pub categories: Vec<TraceCategory>,
+  pub buffer_size_kb: u32,
+  /// 最长记录时长，到时自动停止；0 表示直到手动停止
+  pub duration_ms: u64,
+  /// 完整的 pbtxt 配置，提供时忽略上面的选项
+  pub config_text: Option<String>,
+}
+
+impl Default for PerfettoConfig {
+  fn default() -> Self {
+    Self {
+      categories: vec![TraceCategory::Sched, TraceCategory::Gfx],
+      buffer_size_kb: 64 * 1024,
+      duration_ms: 0,
+      config_text: None,
+    }
+  }
+}
+
+impl PerfettoConfig {
+  /// 生成文本格式的 TraceConfig；`trigger` 为 true 时以环形缓冲持续记录，收到 trigger 后停止
+  pub fn to_pbtxt(&self, package: &str, trigger: bool) -> String {
+    if let Some(text) = &self.config_text {
+      return text.clone();
+    }
+
+    let mut ftrace_events: Vec<&str> = Vec::new();
+    let mut atrace: Vec<&str> = Vec::new();
+    let mut extra = String::new();
+    for category in &self.categories {
+      match category {
+        TraceCategory::Sched => {
+          ftrace_events.extend([
+            "sched/sched_switch",
+            "sched/sched_wakeup",
+            "sched/sched_waking",
+            "sched/sched_process_exit",
+            "power/cpu_frequency",
+            "power/cpu_idle",
+          ]);
+        }
+        TraceCategory::Gfx => {
+          atrace.extend(["gfx", "view", "input", "wm", "am", "dalvik"]);
+          extra.push_str(
+            "data_sources { config { name: \"android.surfaceflinger.frametimeline\" } }\n",
+          );
+        }
+        TraceCategory::Binder => {
+          ftrace_events.extend([
+            "binder/binder_transaction",
+            "binder/binder_transaction_received",
+            "binder/binder_set_priority",
+          ]);
+          atrace.push("binder_driver");
+        }
+        TraceCategory::Memory => {
+          ftrace_events.extend([
+            "kmem/rss_stat",
+            "mm_event/mm_event_record",
+            "lowmemorykiller/lowmemory_kill",
+            "oom/oom_score_adj_update",
+          ]);
+          extra.push_str(
+            "data_sources { config { name: \"linux.sys_stats\" sys_stats_config { \
+             meminfo_period_ms: 1000 vmstat_period_ms: 1000 } } }\n",
+          );
+        }
+      }
+    }
+
+    let mut text = String::new();
+    let _ = writeln!(
+      text,
+      "buffers {{ size_kb: {} fill_policy: RING_BUFFER }}",
+      self.buffer_size_kb.max(1024)
+    );
+    let _ = writeln!(
+      text,
+      "data_sources {{ config {{ name: \"linux.process_stats\" process_stats_config {{ \
+       scan_all_processes_on_start: true proc_stats_poll_ms: 1000 }} }} }}"
+    );
+    if !ftrace_events.is_empty() || !atrace.is_empty() {
+      text.push_str("data_sources { config { name: \"linux.ftrace\" ftrace_config {");
+      for event in ftrace_events {
+        let _ = write!(text, " ftrace_events: \"{event}\"");
+      }
+      for category in &atrace {
+        let _ = write!(text, " atrace_categories: \"{category}\"");
+      }
+      if !atrace.is_empty() {
+        let _ = write!(text, " atrace_apps: \"{package}\"");
+      }
+      text.push_str(" } } }\n");
+    }
+    text.push_str(&extra);
+
+    if trigger {
+      let _ = writeln!(
+        text,
+        "trigger_config {{ trigger_mode: STOP_TRACING trigger_timeout_ms: {TRIGGER_TIMEOUT_MS} \
+         triggers {{ name: \"{JANK_TRIGGER}\" stop_delay_ms: {TRIGGER_STOP_DELAY_MS} }} }}"
+      );
+    } else if self.duration_ms > 0 {
+      let _ = writeln!(text, "duration_ms: {}", self.duration_ms);
+    }
+    text
+  }
+}
+
+/// heapprofd 采样 native（可选 Java）分配调用栈，结束时转储未释放的分配
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeapprofdConfig {
+  /// 平均每分配该字节数采样一次，越小越准确、开销越大
+  pub sampling_interval_bytes: u64,
+  /// 周期性转储间隔，可观察泄漏增长；0 表示只在结束时转储
+  pub dump_interval_ms: u32,
+  /// 同时记录 ART 托管堆的分配，需 Android 12 及以上
+  pub java_heap: bool,
+  pub buffer_size_kb: u32,
+  /// 最长记录时长，0 表示直到手动停止
+  pub duration_ms: u64,
+}
+
+impl Default for HeapprofdConfig {
+  fn default() -> Self {
+    Self {
+      sampling_interval_bytes: 4096,
+      dump_interval_ms: 0,
+      java_heap: false,
+      buffer_size_kb: 64 * 1024,
+      duration_ms: 0,
+    }
+  }
+}
+
+impl HeapprofdConfig {
+  pub fn to_pbtxt(&self, package: &str) -> String {
+    let mut text = String::new();
+    let _ = writeln!(
+      text,
+      "buffers {{ size_kb: {} fill_policy: DISCARD }}",
+      self.buffer_size_kb.max(1024)
+    );
+    let _ = write!(
+      text,
+      "data_sources {{ config {{ name: \"android.heapprofd\" heapprofd_config {{ \
+       sampling_interval_bytes: {} process_cmdline: \"{package}\" \
+       shmem_size_bytes: 8388608 block_client: true heaps: \"libc.malloc\"",
+      self.sampling_interval_bytes.max(1)
+    );
+    if self.java_heap {
+      text.push_str(" heaps: \"com.android.art\"");
+    }
+    if self.dump_interval_ms > 0 {
+      let _ = write!(
+        text,
+        " continuous_dump_config {{ dump_phase_ms: {0} dump_interval_ms: {0} }}",
+        self.dump_interval_ms
+      );
+    }
+    text.push_str(" } } }\n");
+    // 包列表用于在 UI 中把 uid 对应到应用与版本
+    text.push_str("data_sources { config { name: \"android.packages_list\" } }\n");
+    if self.duration_ms > 0 {
+      let _ = writeln!(text, "duration_ms: {}", self.duration_ms);
+    }
+    text
+  }
+}
+
+/// heapprofd 需要 Android 10 及以上，且系统为 userdebug 或应用可调试 / 可分析
+pub fn check_heapprofd(device_id: &str, package: &str) -> Result<()> {
+  let props = get_props(device_id)?;
+  let sdk: u32 = props
+    .get("ro.build.version.sdk")
+    .and_then(|v| v.parse().ok())
+    .unwrap_or(0);
+  if sdk < 29 {
+    return Err(AdbError::CommandFailed(
+      "heapprofd 需要 Android 10 及以上".into(),
+    ));
+  }
+  if props.get("ro.debuggable").map(String::as_str) == Some("1") {
+    return Ok(());
+  }
+
+  let raw = run_device(device_id, &["shell", "dumpsys", "package", package])?;
+  let allowed = raw
+    .lines()
+    .map(str::trim)
+    .filter(|l| l.starts_with("flags=") || l.starts_with("privateFlags="))
+    .any(|l| l.contains("DEBUGGABLE") || l.contains("PROFILEABLE"));
+  if allowed {
+    Ok(())
+  } else {
+    Err(AdbError::CommandFailed(format!(
+      "{package} 不可调试也未声明 profileable，user 版本系统上无法使用 heapprofd"
+    )))
+  }
+}
+
+/// 推送配置并以后台模式启动 perfetto，返回设备上的进程 pid
+///
+/// 新版本 SELinux 不允许 perfetto 读取 /data/local/tmp，因此由 shell 读出后经 stdin 传入。
+/// 配置文件按输出文件命名，同时进行的多个 trace 互不覆盖。
+pub fn start_perfetto(device_id: &str, config_text: &str, output: &str) -> Result<u32> {
+  let name = output.rsplit('/').next().unwrap_or(output);
+  let remote = format!("{DEVICE_TMP}/{name}.pbtxt");
+  push_file(device_id, config_text.as_bytes(), &remote)?;
+
+  // --background 在读完配置后才返回，之后即可删除配置文件
+  let raw = run_device(
+    device_id,
+    &[
+      "shell",
+      &format!("cat {remote} | perfetto --background --txt -c - -o {output}"),
+    ],
+  );
+  remove_file(device_id, &remote);
+  let raw = raw?;
+  raw
+    .split_whitespace()
+    .rev()
+    .find_map(|token| token.parse().ok())
+    .ok_or_else(|| AdbError::ParseFailed(format!("perfetto 启动失败: {}", raw.trim())))
+}
+
+/// 通知以 trigger 模式运行的 perfetto 停止记录
+pub fn fire_trigger(device_id: &str, name: &str) -> Result<()> {
+  run_device(device_id, &["shell", "perfetto", "--trigger", name])?;
+  Ok(())
+}
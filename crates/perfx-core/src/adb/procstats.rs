@@ -0,0 +1,135 @@
+use crate::adb::{command::run_device, error::Result};
+use serde::{Deserialize, Serialize};
+
+/// 某进程在一个状态下的驻留比例与该状态下的内存统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcStateStat {
+  /// TOTAL / Top / Imp Fg / Service / (Cached) 等
+  pub state: String,
+  /// 统计时段内处于该状态的时间占比
+  pub percent: f64,
+  pub pss_avg_mb: Option<f64>,
+  pub pss_max_mb: Option<f64>,
+  pub uss_avg_mb: Option<f64>,
+  pub rss_avg_mb: Option<f64>,
+  /// 参与内存统计的采样次数
+  pub samples: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessStats {
+  pub process: String,
+  pub states: Vec<ProcStateStat>,
+}
+
+/// framework 维护的进程状态驻留统计，覆盖时间比录制长得多
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcStats {
+  pub hours: u32,
+  pub processes: Vec<ProcessStats>,
+}
+
+pub fn fetch_procstats(device_id: &str, package: &str, hours: u32) -> Result<ProcStats> {
+  let hours = hours.max(1);
+  let raw = run_device(
+    device_id,
+    &[
+      "shell",
+      "dumpsys",
+      "procstats",
+      "--hours",
+      &hours.to_string(),
+      package,
+    ],
+  )?;
+  Ok(ProcStats {
+    hours,
+    processes: parse_procstats(&raw),
+  })
+}
+
+/// 解析 `Summary:` 段，每个进程以 `* 进程名 / uid / 版本:` 开始，其后每行一个状态
+fn parse_procstats(raw: &str) -> Vec<ProcessStats> {
+  let mut processes: Vec<ProcessStats> = Vec::new();
+  let mut in_summary = false;
+  for line in raw.lines() {
+    if !line.starts_with(' ') {
+      in_summary = line.trim_end() == "Summary:";
+      continue;
+    }
+    if !in_summary {
+      continue;
+    }
+    let line = line.trim();
+    if let Some(header) = line.strip_prefix("* ") {
+      let process = header.split(" / ").next().unwrap_or(header);
+      processes.push(ProcessStats {
+        process: process.trim_end_matches(':').to_string(),
+        states: Vec::new(),
+      });
+    } else if let (Some(process), Some(state)) = (processes.last_mut(), parse_state(line)) {
+      process.states.push(state);
+    }
+  }
+  processes
+}
+
+/// `Top: 10% (95MB-110MB-130MB/80MB-90MB-100MB/150MB-170MB-190MB over 18)`
+fn parse_state(line: &str) -> Option<ProcStateStat> {
+  let (state, rest) = line.split_once(": ")?;
+  let rest = rest.trim();
+  let (percent, detail) = match rest.split_once(' ') {
+    Some((percent, detail)) => (percent, Some(detail)),
+    None => (rest, None),
+  };
+  let percent = percent.strip_suffix('%')?.parse().ok()?;
+  let mut stat = ProcStateStat {
+    state: state.trim().to_string(),
+    percent,
+    pss_avg_mb: None,
+    pss_max_mb: None,
+    uss_avg_mb: None,
+    rss_avg_mb: None,
+    samples: None,
+  };
+
+  let Some(detail) = detail
+    .and_then(|d| d.strip_prefix('('))
+    .and_then(|d| d.strip_suffix(')'))
+  else {
+    return Some(stat);
+  };
+  let (memory, samples) = detail.split_once(" over ").unwrap_or((detail, ""));
+  stat.samples = samples.trim().parse().ok();
+  // 依次为 PSS / USS / RSS，每组为 最小-平均-最大
+  let groups: Vec<Vec<Option<f64>>> = memory
+    .split('/')
+    .map(|group| group.split('-').map(parse_size_mb).collect())
+    .collect();
+  let value = |group: usize, index: usize| {
+    groups
+      .get(group)
+      .and_then(|g| g.get(index))
+      .copied()
+      .flatten()
+  };
+  stat.pss_avg_mb = value(0, 1);
+  stat.pss_max_mb = value(0, 2);
+  stat.uss_avg_mb = value(1, 1);
+  stat.rss_avg_mb = value(2, 1);
+  Some(stat)
+}
+
+fn parse_size_mb(value: &str) -> Option<f64> {
+  let value = value.trim();
+  let (number, scale) = if let Some(v) = value.strip_suffix("GB") {
+    (v, 1024.0)
+  } else if let Some(v) = value.strip_suffix("MB") {
+    (v, 1.0)
+  } else if let Some(v) = value.strip_suffix("KB") {
+    (v, 1.0 / 1024.0)
+  } else {
+    (value, 1.0 / 1024.0 / 1024.0)
+  };
+  number.parse::<f64>().ok().map(|v| v * scale)
+}
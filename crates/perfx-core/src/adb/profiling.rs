@@ -0,0 +1,233 @@
+use crate::adb::{
+  command::{current_adb_path, run_device, run_device_bytes},
+  error::{AdbError, Result},
+};
+use std::{
+  path::{Path, PathBuf},
+  process::Command,
+  thread,
+  time::{Duration, Instant},
+};
+
+/// 抓取文件在设备上的临时目录，shell 用户可读写
+pub const DEVICE_TMP: &str = "/data/local/tmp";
+const SIMPLEPERF_SYSTEM: &str = "/system/bin/simpleperf";
+const EXIT_POLL: Duration = Duration::from_millis(300);
+// 连续两次读到相同大小视为文件已写完
+const FILE_POLL: Duration = Duration::from_millis(1000);
+
+/// 在设备后台启动命令并返回其 pid，adb shell 退出后命令继续运行
+pub fn spawn_background(device_id: &str, command: &str) -> Result<u32> {
+  let script = format!("nohup {command} > /dev/null 2>&1 & echo $!");
+  let raw = run_device(device_id, &["shell", &script])?;
+  raw
+    .trim()
+    .parse()
+    .map_err(|_| AdbError::ParseFailed(format!("后台进程启动失败: {}", raw.trim())))
+}
+
+pub fn process_alive(device_id: &str, pid: u32) -> bool {
+  run_device(
+    device_id,
+    &["shell", &format!("test -d /proc/{pid} && echo alive")],
+  )
+  .is_ok_and(|out| out.contains("alive"))
+}
+
+/// 发送信号并等待进程退出，超时返回 false
+pub fn stop_process(device_id: &str, pid: u32, signal: &str, timeout: Duration) -> Result<bool> {
+  run_device(device_id, &["shell", &format!("kill -{signal} {pid}")])?;
+  Ok(wait_exit(device_id, pid, timeout))
+}
+
+pub fn wait_exit(device_id: &str, pid: u32, timeout: Duration) -> bool {
+  let deadline = Instant::now() + timeout;
+  while Instant::now() < deadline {
+    if !process_alive(device_id, pid) {
+      return true;
+    }
+    thread::sleep(EXIT_POLL);
+  }
+  false
+}
+
+/// 以二进制读取设备文件，比 adb pull 少一次落盘
+pub fn pull_file(device_id: &str, path: &str) -> Result<Vec<u8>> {
+  let data = run_device_bytes(device_id, &["exec-out", "cat", path])?;
+  if data.is_empty() {
+    return Err(AdbError::CommandFailed(format!("文件为空或不存在: {path}")));
+  }
+  Ok(data)
+}
+
+/// 经本地临时文件推送数据到设备
+pub fn push_file(device_id: &str, data: &[u8], path: &str) -> Result<()> {
+  let name = path.rsplit('/').next().unwrap_or("perfx-push");
+  let local = std::env::temp_dir().join(format!("{}-{name}", std::process::id()));
+  std::fs::write(&local, data).map_err(|e| AdbError::CommandFailed(e.to_string()))?;
+  let pushed = run_device(device_id, &["push", &local.to_string_lossy(), path]);
+  let _ = std::fs::remove_file(&local);
+  pushed.map(|_| ())
+}
+
+pub fn remove_file(device_id: &str, path: &str) {
+  let _ = run_device(device_id, &["shell", "rm", "-f", path]);
+}
+
+/// Android 9 起系统自带 simpleperf，更早的版本需推送 NDK 中的可执行文件
+pub fn ensure_simpleperf(device_id: &str, host_binary: Option<&Path>) -> Result<String> {
+  let pushed = format!("{DEVICE_TMP}/simpleperf");
+  for candidate in [SIMPLEPERF_SYSTEM, pushed.as_str()] {
+    let probe = run_device(
+      device_id,
+      &["shell", &format!("test -x {candidate} && echo ok")],
+    );
+    if probe.is_ok_and(|out| out.contains("ok")) {
+      return Ok(candidate.to_string());
+    }
+  }
+
+  let host = host_binary.ok_or_else(|| {
+    AdbError::CommandFailed("设备上没有 simpleperf，请指定 NDK 中对应 ABI 的 simpleperf".into())
+  })?;
+  let host = host.to_string_lossy();
+  run_device(device_id, &["push", &host, &pushed])?;
+  run_device(device_id, &["shell", "chmod", "755", &pushed])?;
+  Ok(pushed)
+}
+
+/// 在设备上把 perf.data 解析为带调用链的文本样本
+pub fn simpleperf_report_sample(device_id: &str, binary: &str, path: &str) -> Result<String> {
+  run_device(
+    device_id,
+    &[
+      "shell",
+      &format!("{binary} report-sample --show-callchain -i {path}"),
+    ],
+  )
+}
+
+/// 触发堆转储并等待文件写完；旧版本的 `am dumpheap` 不等待转储结束，以文件大小不再变化为准
+pub fn dump_heap(
+  device_id: &str,
+  package: &str,
+  native: bool,
+  gc: bool,
+  path: &str,
+  timeout: Duration,
+) -> Result<()> {
+  let mut args = vec!["shell", "am", "dumpheap"];
+  if native {
+    args.push("-n");
+  } else if gc {
+    args.push("-g");
+  }
+  args.extend([package, path]);
+  am_checked(device_id, &args)?;
+  wait_file_complete(device_id, path, timeout)
+}
+
+/// 让应用进程开始 ART 方法 trace，`sampling_us` 为 None 时对每次方法调用插桩
+pub fn start_method_trace(
+  device_id: &str,
+  process: &str,
+  path: &str,
+  sampling_us: Option<u32>,
+) -> Result<()> {
+  let interval = sampling_us.map(|us| us.max(1).to_string());
+  let mut args = vec!["shell", "am", "profile", "start"];
+  if let Some(interval) = &interval {
+    args.extend(["--sampling", interval]);
+  }
+  args.extend([process, path]);
+  am_checked(device_id, &args)
+}
+
+/// 结束方法 trace 并等待应用写完文件，超时返回 false
+pub fn stop_method_trace(
+  device_id: &str,
+  process: &str,
+  path: &str,
+  timeout: Duration,
+) -> Result<bool> {
+  am_checked(device_id, &["shell", "am", "profile", "stop", process])?;
+  Ok(wait_file_complete(device_id, path, timeout).is_ok())
+}
+
+/// am 出错时多数版本仍返回 0，需检查输出
+fn am_checked(device_id: &str, args: &[&str]) -> Result<()> {
+  let out = run_device(device_id, args)?;
+  if out.contains("Error") || out.contains("Exception") {
+    return Err(AdbError::CommandFailed(out.trim().to_string()));
+  }
+  Ok(())
+}
+
+/// 等待设备上由其他进程写出的文件完成，超时返回错误
+pub fn wait_file_complete(device_id: &str, path: &str, timeout: Duration) -> Result<()> {
+  let deadline = Instant::now() + timeout;
+  let mut last = None;
+  while Instant::now() < deadline {
+    let size = file_size(device_id, path).filter(|size| *size > 0);
+    if size.is_some() && size == last {
+      return Ok(());
+    }
+    last = size;
+    thread::sleep(FILE_POLL);
+  }
+  Err(AdbError::CommandFailed(format!("等待文件写出超时: {path}")))
+}
+
+fn file_size(device_id: &str, path: &str) -> Option<u64> {
+  run_device(device_id, &["shell", "stat", "-c", "%s", path])
+    .ok()
+    .and_then(|out| out.trim().parse().ok())
+}
+
+/// 用 platform-tools 中的 hprof-conv 把 Android 格式的 hprof 转为 MAT 等工具可读的标准格式
+pub fn convert_hprof(data: &[u8], tool: Option<&Path>) -> Result<Vec<u8>> {
+  let tool = match tool {
+    Some(path) => path.to_path_buf(),
+    None => sibling_tool("hprof-conv"),
+  };
+  let dir = std::env::temp_dir();
+  let input = dir.join(format!("perfx-{}-android.hprof", std::process::id()));
+  let output = dir.join(format!("perfx-{}.hprof", std::process::id()));
+  let io_err = |e: std::io::Error| AdbError::CommandFailed(e.to_string());
+  std::fs::write(&input, data).map_err(io_err)?;
+
+  let mut cmd = Command::new(&tool);
+  cmd.arg(&input).arg(&output);
+  #[cfg(target_os = "windows")]
+  {
+    use std::os::windows::process::CommandExt;
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+  }
+  let result = cmd.output();
+  let _ = std::fs::remove_file(&input);
+  let status = result
+    .map_err(|_| AdbError::CommandFailed(format!("未找到 hprof-conv: {}", tool.display())))?;
+  if !status.status.success() {
+    let _ = std::fs::remove_file(&output);
+    return Err(AdbError::CommandFailed(
+      String::from_utf8_lossy(&status.stderr).trim().to_string(),
+    ));
+  }
+  let converted = std::fs::read(&output).map_err(io_err);
+  let _ = std::fs::remove_file(&output);
+  converted
+}
+
+/// SDK 工具与 adb 同在 platform-tools 目录
+fn sibling_tool(name: &str) -> PathBuf {
+  let file = if cfg!(target_os = "windows") {
+    format!("{name}.exe")
+  } else {
+    name.to_string()
+  };
+  let adb = PathBuf::from(current_adb_path());
+  match adb.parent() {
+    Some(dir) if !dir.as_os_str().is_empty() => dir.join(file),
+    _ => PathBuf::from(file),
+  }
+}
@@ -0,0 +1,320 @@
+//! 自定义指标：实现 `MetricProvider` 并注册后，每次采集都会调用，结果写入快照的 `extra`，
+//! 无需修改 metrics.rs 即可接入公司内部的指标
+
+use crate::adb::{
+  command::{run_device, run_device_timeout},
+  error::{AdbError, Result},
+  metrics::MetricValue,
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex, RwLock},
+  time::Duration,
+};
+
+pub trait MetricProvider: Send + Sync {
+  /// 唯一名称，同名的提供者注册时会替换旧的
+  fn name(&self) -> &str;
+
+  /// 设备与应用是否支持该指标；结果按设备与应用缓存，不支持时不再调用 `collect`
+  fn supported(&self, _device_id: &str, _package: &str) -> bool {
+    true
+  }
+
+  /// 采集一次，返回指标键与取值；键不应与内置指标重名，重名时以内置指标为准
+  fn collect(&self, device_id: &str, package: &str) -> Result<HashMap<String, MetricValue>>;
+
+  /// 指标的单位，用于导出与图表标注；未知时为空
+  fn unit(&self, _key: &str) -> Option<String> {
+    None
+  }
+}
+
+static PROVIDERS: Lazy<RwLock<Vec<Arc<dyn MetricProvider>>>> =
+  Lazy::new(|| RwLock::new(Vec::new()));
+
+/// (提供者, 设备, 包名) 的支持情况
+type SupportKey = (String, String, String);
+static SUPPORTED: Lazy<Mutex<HashMap<SupportKey, bool>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+pub fn register_provider(provider: Arc<dyn MetricProvider>) {
+  unregister_provider(provider.name());
+  if let Ok(mut providers) = PROVIDERS.write() {
+    providers.push(provider);
+  }
+}
+
+pub fn unregister_provider(name: &str) {
+  if let Ok(mut providers) = PROVIDERS.write() {
+    providers.retain(|p| p.name() != name);
+  }
+  if let Ok(mut supported) = SUPPORTED.lock() {
+    supported.retain(|(provider, _, _), _| provider != name);
+  }
+}
+
+pub fn provider_names() -> Vec<String> {
+  PROVIDERS
+    .read()
+    .map(|providers| providers.iter().map(|p| p.name().to_string()).collect())
+    .unwrap_or_default()
+}
+
+/// 由提供者声明的自定义指标单位
+pub fn provider_unit(key: &str) -> Option<String> {
+  PROVIDERS.read().ok()?.iter().find_map(|p| p.unit(key))
+}
+
+/// 依次调用已注册且受支持的提供者，返回各自的结果，键为提供者名称
+pub(crate) fn collect_providers(
+  device_id: &str,
+  package: &str,
+) -> Vec<(String, Result<HashMap<String, MetricValue>>)> {
+  let providers: Vec<Arc<dyn MetricProvider>> = match PROVIDERS.read() {
+    Ok(providers) => providers.clone(),
+    Err(_) => return Vec::new(),
+  };
+  providers
+    .iter()
+    .filter(|p| is_supported(p.as_ref(), device_id, package))
+    .map(|p| (p.name().to_string(), p.collect(device_id, package)))
+    .collect()
+}
+
+fn is_supported(provider: &dyn MetricProvider, device_id: &str, package: &str) -> bool {
+  let key = (
+    provider.name().to_string(),
+    device_id.to_string(),
+    package.to_string(),
+  );
+  if let Some(supported) = SUPPORTED
+    .lock()
+    .ok()
+    .and_then(|cache| cache.get(&key).copied())
+  {
+    return supported;
+  }
+  let supported = provider.supported(device_id, package);
+  if let Ok(mut cache) = SUPPORTED.lock() {
+    cache.insert(key, supported);
+  }
+  supported
+}
+
+/// 读取一个系统属性作为指标，如应用在 debug 包中通过 `setprop` 暴露的计数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PropertyMetric {
+  /// 指标键
+  pub key: String,
+  /// 属性名，如 `debug.example.cache_hits`
+  pub property: String,
+}
+
+/// 按配置读取一组系统属性，能解析为数值的记为数值，其余记为文本
+pub struct PropertyProvider {
+  metrics: Vec<PropertyMetric>,
+}
+
+impl PropertyProvider {
+  pub const NAME: &'static str = "property";
+
+  pub fn new(metrics: Vec<PropertyMetric>) -> Self {
+    Self { metrics }
+  }
+}
+
+/// 按设置注册属性指标，列表为空时移除
+pub fn register_property_metrics(metrics: Vec<PropertyMetric>) {
+  if metrics.is_empty() {
+    unregister_provider(PropertyProvider::NAME);
+  } else {
+    register_provider(Arc::new(PropertyProvider::new(metrics)));
+  }
+}
+
+impl MetricProvider for PropertyProvider {
+  fn name(&self) -> &str {
+    Self::NAME
+  }
+
+  fn collect(&self, device_id: &str, _package: &str) -> Result<HashMap<String, MetricValue>> {
+    // 一次 adb 调用读出全部属性，每行一个值
+    let script = self
+      .metrics
+      .iter()
+      .map(|m| format!("getprop '{}'", m.property.replace('\'', "")))
+      .collect::<Vec<_>>()
+      .join("; ");
+    let raw = run_device(device_id, &["shell", &script])?;
+    // 未设置的属性输出空行，末尾的空行可能被截掉
+    let lines = raw.lines().map(str::trim).chain(std::iter::repeat(""));
+    Ok(
+      self
+        .metrics
+        .iter()
+        .zip(lines)
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(metric, value)| {
+          let value = match value.parse::<f64>() {
+            Ok(number) => MetricValue::Number(number),
+            Err(_) => MetricValue::Text(value.to_string()),
+          };
+          (metric.key.clone(), value)
+        })
+        .collect(),
+    )
+  }
+}
+
+/// 执行一条 shell 命令，用正则从输出中取值，如读取某个 sysfs 节点
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShellMetric {
+  /// 指标键
+  pub key: String,
+  /// 在设备上执行的命令，如 `cat /sys/class/kgsl/kgsl-3d0/gpubusy`
+  pub command: String,
+  /// 取第一个捕获组，没有捕获组时取整个匹配；为空时取去掉首尾空白的整段输出
+  #[serde(default)]
+  pub pattern: String,
+  #[serde(default)]
+  pub unit: String,
+}
+
+/// 按配置执行一组命令，能解析为数值的记为数值，其余记为文本；未匹配的指标本次不记录
+pub struct ShellProvider {
+  metrics: Vec<(ShellMetric, Option<Regex>)>,
+}
+
+/// 各命令输出之间的分隔行
+const SHELL_SEPARATOR: &str = "__perfx_shell_metric__";
+/// 全部命令的执行时间上限，阻塞的命令（如读取会阻塞的节点）不会拖住采集
+const SHELL_TIMEOUT: Duration = Duration::from_secs(2);
+
+impl ShellProvider {
+  pub const NAME: &'static str = "shell";
+
+  /// 跳过正则无效的指标，一并返回它们的键与原因
+  pub fn new(metrics: Vec<ShellMetric>) -> (Self, Vec<(String, AdbError)>) {
+    let mut valid = Vec::new();
+    let mut errors = Vec::new();
+    for metric in metrics {
+      if metric.pattern.is_empty() {
+        valid.push((metric, None));
+        continue;
+      }
+      match Regex::new(&metric.pattern) {
+        Ok(re) => valid.push((metric, Some(re))),
+        Err(e) => errors.push((
+          metric.key,
+          AdbError::ParseFailed(format!("正则无效: {e}")),
+        )),
+      }
+    }
+    (Self { metrics: valid }, errors)
+  }
+}
+
+/// 按设置注册命令指标，没有可用的指标时移除；返回正则无效而未注册的指标键与原因
+pub fn register_shell_metrics(metrics: Vec<ShellMetric>) -> Vec<(String, AdbError)> {
+  let (provider, errors) = ShellProvider::new(metrics);
+  if provider.metrics.is_empty() {
+    unregister_provider(ShellProvider::NAME);
+  } else {
+    register_provider(Arc::new(provider));
+  }
+  errors
+}
+
+impl MetricProvider for ShellProvider {
+  fn name(&self) -> &str {
+    Self::NAME
+  }
+
+  fn collect(&self, device_id: &str, _package: &str) -> Result<HashMap<String, MetricValue>> {
+    // 一次 adb 调用执行全部命令，单条命令失败不影响其余
+    let script = self
+      .metrics
+      .iter()
+      .map(|(m, _)| format!("( {}\n) 2>/dev/null; echo {SHELL_SEPARATOR}", m.command))
+      .collect::<Vec<_>>()
+      .join("; ");
+    let raw = run_device_timeout(device_id, &["shell", &script], SHELL_TIMEOUT)?;
+    let outputs = raw.split(SHELL_SEPARATOR);
+    Ok(
+      self
+        .metrics
+        .iter()
+        .zip(outputs)
+        .filter_map(|((metric, re), output)| {
+          let value = extract_value(output, re.as_ref())?;
+          Some((metric.key.clone(), value))
+        })
+        .collect(),
+    )
+  }
+
+  fn unit(&self, key: &str) -> Option<String> {
+    self
+      .metrics
+      .iter()
+      .find(|(m, _)| m.key == key && !m.unit.is_empty())
+      .map(|(m, _)| m.unit.clone())
+  }
+}
+
+fn extract_value(output: &str, re: Option<&Regex>) -> Option<MetricValue> {
+  let text = match re {
+    Some(re) => {
+      let caps = re.captures(output)?;
+      caps.get(1).or_else(|| caps.get(0))?.as_str().trim()
+    }
+    None => output.trim(),
+  };
+  if text.is_empty() {
+    return None;
+  }
+  Some(match text.parse::<f64>() {
+    Ok(number) => MetricValue::Number(number),
+    Err(_) => MetricValue::Text(text.to_string()),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn shell_value_uses_first_capture_group() {
+    let re = Regex::new(r"busy:\s*(\d+)").unwrap();
+    assert_eq!(
+      extract_value("idle: 3\nbusy: 42\n", Some(&re)),
+      Some(MetricValue::Number(42.0))
+    );
+    assert_eq!(extract_value("idle: 3\n", Some(&re)), None);
+    assert_eq!(
+      extract_value(" 1250\n", None),
+      Some(MetricValue::Number(1250.0))
+    );
+  }
+
+  #[test]
+  fn invalid_pattern_skips_only_that_metric() {
+    let metric = |key: &str, pattern: &str| ShellMetric {
+      key: key.into(),
+      command: "true".into(),
+      pattern: pattern.into(),
+      unit: String::new(),
+    };
+    let (provider, errors) = ShellProvider::new(vec![
+      metric("bad", "("),
+      metric("good", r"(\d+)"),
+      metric("also_bad", "["),
+    ]);
+    assert_eq!(provider.metrics.len(), 1);
+    let keys: Vec<&str> = errors.iter().map(|(k, _)| k.as_str()).collect();
+    assert_eq!(keys, ["bad", "also_bad"]);
+  }
+}
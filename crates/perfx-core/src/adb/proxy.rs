@@ -0,0 +1,59 @@
+//! 设备全局 HTTP 代理（settings global http_proxy），配合主机上的 Charles / mitmproxy 抓包
+
+use crate::adb::{
+  command::run_device,
+  error::Result,
+  settings::{get_setting, put_setting, Namespace},
+};
+use serde::{Deserialize, Serialize};
+
+const HTTP_PROXY: &str = "http_proxy";
+/// 清除代理需写入 `:0`，仅删除设置项要等网络切换后才生效
+const NO_PROXY: &str = ":0";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxyConfig {
+  pub host: String,
+  pub port: u16,
+  /// 通过 `adb reverse` 把设备上的端口转到主机，设备与主机不在同一网络时使用，
+  /// 此时 host 应为 127.0.0.1
+  #[serde(default)]
+  pub reverse: bool,
+}
+
+/// 当前代理地址 `host:port`，未设置时为空
+pub fn http_proxy(device_id: &str) -> Result<Option<String>> {
+  Ok(get_setting(device_id, Namespace::Global, HTTP_PROXY)?.filter(|v| v != NO_PROXY))
+}
+
+pub fn set_http_proxy(device_id: &str, config: &ProxyConfig) -> Result<()> {
+  if config.reverse {
+    let port = format!("tcp:{}", config.port);
+    run_device(device_id, &["reverse", &port, &port])?;
+  }
+  put_setting(
+    device_id,
+    Namespace::Global,
+    HTTP_PROXY,
+    &format!("{}:{}", config.host, config.port),
+  )
+}
+
+/// 写回之前的代理地址，为空时清除；`reverse_ports` 为设置时建立的端口转发
+pub fn restore_http_proxy(
+  device_id: &str,
+  previous: Option<&str>,
+  reverse_ports: &[u16],
+) -> Result<()> {
+  put_setting(
+    device_id,
+    Namespace::Global,
+    HTTP_PROXY,
+    previous.unwrap_or(NO_PROXY),
+  )?;
+  for port in reverse_ports {
+    // 转发可能已随设备重连失效，忽略错误
+    let _ = run_device(device_id, &["reverse", "--remove", &format!("tcp:{port}")]);
+  }
+  Ok(())
+}
@@ -0,0 +1,15 @@
+use crate::adb::{
+  command::run_device_bytes,
+  error::{AdbError, Result},
+};
+
+const PNG_MAGIC: &[u8] = b"\x89PNG";
+
+/// 截取当前屏幕，返回 PNG 数据
+pub fn screencap_png(device_id: &str) -> Result<Vec<u8>> {
+  let data = run_device_bytes(device_id, &["exec-out", "screencap", "-p"])?;
+  if !data.starts_with(PNG_MAGIC) {
+    return Err(AdbError::ParseFailed("screencap 输出不是 PNG".into()));
+  }
+  Ok(data)
+}
@@ -0,0 +1,310 @@
+//! 脚本指标：每次采集后运行用户提供的 Lua 片段，可调用 adb 并读取本次快照，
+//! 返回的表写入快照的 `extra`，用于不值得内置的一次性测量
+//!
+//! 脚本中可用：
+//! - `device`、`package`：当前设备与包名
+//! - `snapshot`：本次采集的快照（与导出 JSON 的字段一致）
+//! - `shell(cmd)`：在设备上执行 shell 命令并返回输出
+//! - `adb(...)`：以 `adb -s <device> ...` 执行任意参数
+//!
+//! 每个设备与应用组合各有一份 Lua 状态，全局变量在该组合的各次采集之间保留，可据此计算增量。
+//! 需启用 `scripting` 特性，未启用时注册的脚本全部报错且不会运行。
+
+use crate::adb::{
+  error::{AdbError, Result},
+  metrics::{MetricValue, MetricsSnapshot},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 设置中的一段脚本
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptMetric {
+  /// 名称，失败时以此记入 `failures`
+  pub name: String,
+  /// Lua 代码，最后 `return` 一个以指标键为键的表
+  pub source: String,
+}
+
+#[cfg(feature = "scripting")]
+pub use lua::{register_metric_scripts, MetricScript};
+#[cfg(feature = "scripting")]
+pub(crate) use lua::run_scripts;
+
+/// 未启用 `scripting` 特性：全部脚本报错
+#[cfg(not(feature = "scripting"))]
+pub fn register_metric_scripts(scripts: Vec<ScriptMetric>) -> Vec<(String, AdbError)> {
+  scripts
+    .into_iter()
+    .map(|s| (s.name, AdbError::Script("未启用脚本支持（scripting 特性）".into())))
+    .collect()
+}
+
+#[cfg(not(feature = "scripting"))]
+pub(crate) fn run_scripts(
+  _device_id: &str,
+  _package: &str,
+  _snapshot: &MetricsSnapshot,
+) -> Vec<(String, Result<HashMap<String, MetricValue>>)> {
+  Vec::new()
+}
+
+#[cfg(feature = "scripting")]
+mod lua {
+  use super::*;
+  use crate::adb::command::run_device_timeout;
+  use mlua::{
+    Function, HookTriggers, Lua, LuaOptions, LuaSerdeExt, RegistryKey, StdLib, Value, Variadic,
+  };
+  use once_cell::sync::Lazy;
+  use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+  };
+
+  /// 单次运行的时间上限（含 adb 调用），超时后在下一条指令或下一次 adb 调用处中止
+  const RUN_TIMEOUT: Duration = Duration::from_secs(3);
+  const MEMORY_LIMIT: usize = 16 * 1024 * 1024;
+  /// 每执行这么多条指令检查一次超时
+  const HOOK_INSTRUCTIONS: u32 = 10_000;
+
+  type Deadline = Arc<Mutex<Option<Instant>>>;
+
+  /// 编译好的脚本及其独立的 Lua 状态
+  pub struct MetricScript {
+    name: String,
+    lua: Lua,
+    chunk: RegistryKey,
+    deadline: Deadline,
+  }
+
+  /// 已注册的脚本源码；版本号在重新注册时递增，各目标的 Lua 状态据此重建
+  struct Registered {
+    generation: u64,
+    scripts: Arc<Vec<ScriptMetric>>,
+  }
+
+  /// 某个设备与应用组合上的脚本状态
+  struct TargetScripts {
+    generation: u64,
+    scripts: Vec<MetricScript>,
+  }
+
+  type Target = Arc<Mutex<TargetScripts>>;
+
+  static REGISTERED: Lazy<Mutex<Registered>> = Lazy::new(|| {
+    Mutex::new(Registered {
+      generation: 0,
+      scripts: Arc::new(Vec::new()),
+    })
+  });
+  static TARGETS: Lazy<Mutex<HashMap<(String, String), Target>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+  fn script_error(e: mlua::Error) -> AdbError {
+    AdbError::Script(e.to_string())
+  }
+
+  fn remaining(deadline: &Deadline) -> Duration {
+    deadline
+      .lock()
+      .unwrap_or_else(|e| e.into_inner())
+      .map_or(RUN_TIMEOUT, |at| at.saturating_duration_since(Instant::now()))
+  }
+
+  /// 在剩余时间内执行 adb，时间用完时直接报超时
+  fn run_adb(device_id: &str, args: &[&str], deadline: &Deadline) -> mlua::Result<String> {
+    let left = remaining(deadline);
+    if left.is_zero() {
+      return Err(mlua::Error::runtime("脚本运行超时"));
+    }
+    run_device_timeout(device_id, args, left).map_err(mlua::Error::external)
+  }
+
+  impl MetricScript {
+    /// 编译脚本；只加载 table、string、math、utf8 库，不能访问文件与进程
+    pub fn compile(name: &str, source: &str) -> Result<Self> {
+      let libs = StdLib::TABLE | StdLib::STRING | StdLib::MATH | StdLib::UTF8;
+      let lua = Lua::new_with(libs, LuaOptions::default()).map_err(script_error)?;
+      lua.set_memory_limit(MEMORY_LIMIT).map_err(script_error)?;
+
+      let deadline: Deadline = Arc::new(Mutex::new(None));
+      let hook_deadline = deadline.clone();
+      lua.set_hook(
+        HookTriggers::new().every_nth_instruction(HOOK_INSTRUCTIONS),
+        move |_, _| match *hook_deadline.lock().unwrap_or_else(|e| e.into_inner()) {
+          Some(at) if Instant::now() > at => Err(mlua::Error::runtime("脚本运行超时")),
+          _ => Ok(()),
+        },
+      );
+
+      let chunk = lua
+        .load(source)
+        .set_name(name)
+        .into_function()
+        .map_err(script_error)?;
+      let chunk = lua.create_registry_value(chunk).map_err(script_error)?;
+      Ok(Self {
+        name: name.to_string(),
+        lua,
+        chunk,
+        deadline,
+      })
+    }
+
+    pub fn name(&self) -> &str {
+      &self.name
+    }
+
+    /// 运行一次，返回脚本给出的指标
+    pub fn run(
+      &self,
+      device_id: &str,
+      package: &str,
+      snapshot: &MetricsSnapshot,
+    ) -> Result<HashMap<String, MetricValue>> {
+      self.set_deadline(Some(Instant::now() + RUN_TIMEOUT));
+      let result = self.call(device_id, package, snapshot);
+      self.set_deadline(None);
+      result.map_err(script_error)
+    }
+
+    fn set_deadline(&self, at: Option<Instant>) {
+      *self.deadline.lock().unwrap_or_else(|e| e.into_inner()) = at;
+    }
+
+    fn call(
+      &self,
+      device_id: &str,
+      package: &str,
+      snapshot: &MetricsSnapshot,
+    ) -> mlua::Result<HashMap<String, MetricValue>> {
+      let lua = &self.lua;
+      let globals = lua.globals();
+      globals.set("device", device_id)?;
+      globals.set("package", package)?;
+      globals.set("snapshot", lua.to_value(snapshot)?)?;
+
+      let device = device_id.to_string();
+      let deadline = self.deadline.clone();
+      let shell = lua.create_function(move |_, cmd: String| {
+        run_adb(&device, &["shell", &cmd], &deadline)
+      })?;
+      globals.set("shell", shell)?;
+      let device = device_id.to_string();
+      let deadline = self.deadline.clone();
+      let adb = lua.create_function(move |_, args: Variadic<String>| {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        run_adb(&device, &args, &deadline)
+      })?;
+      globals.set("adb", adb)?;
+
+      let chunk: Function = lua.registry_value(&self.chunk)?;
+      match chunk.call::<_, Value>(())? {
+        Value::Nil => Ok(HashMap::new()),
+        value => lua.from_value(value),
+      }
+    }
+  }
+
+  /// 按设置替换全部脚本，返回编译失败的脚本名称与原因；失败的脚本不会运行
+  pub fn register_metric_scripts(scripts: Vec<ScriptMetric>) -> Vec<(String, AdbError)> {
+    let mut valid = Vec::new();
+    let mut errors = Vec::new();
+    for script in scripts {
+      match MetricScript::compile(&script.name, &script.source) {
+        Ok(_) => valid.push(script),
+        Err(e) => errors.push((script.name, e)),
+      }
+    }
+    if let Ok(mut registered) = REGISTERED.lock() {
+      registered.generation += 1;
+      registered.scripts = Arc::new(valid);
+    }
+    // 旧的 Lua 状态不再使用，运行中的采集持有自己的引用
+    if let Ok(mut targets) = TARGETS.lock() {
+      targets.clear();
+    }
+    errors
+  }
+
+  /// 依次运行已注册的脚本，返回各自的结果，键为脚本名称。
+  /// 全局锁只在取状态时短暂持有，不同设备与应用的采集互不等待
+  pub(crate) fn run_scripts(
+    device_id: &str,
+    package: &str,
+    snapshot: &MetricsSnapshot,
+  ) -> Vec<(String, Result<HashMap<String, MetricValue>>)> {
+    let Ok((generation, sources)) = REGISTERED
+      .lock()
+      .map(|r| (r.generation, r.scripts.clone()))
+    else {
+      return Vec::new();
+    };
+    if sources.is_empty() {
+      return Vec::new();
+    }
+    let target = {
+      let Ok(mut targets) = TARGETS.lock() else {
+        return Vec::new();
+      };
+      targets
+        .entry((device_id.to_string(), package.to_string()))
+        .or_insert_with(|| {
+          Arc::new(Mutex::new(TargetScripts {
+            generation: 0,
+            scripts: Vec::new(),
+          }))
+        })
+        .clone()
+    };
+    let Ok(mut target) = target.lock() else {
+      return Vec::new();
+    };
+    if target.generation != generation {
+      // 注册时已检查过能否编译
+      target.scripts = sources
+        .iter()
+        .filter_map(|s| MetricScript::compile(&s.name, &s.source).ok())
+        .collect();
+      target.generation = generation;
+    }
+    target
+      .scripts
+      .iter()
+      .map(|s| (s.name.clone(), s.run(device_id, package, snapshot)))
+      .collect()
+  }
+
+  #[cfg(test)]
+  mod tests {
+    use super::*;
+
+    #[test]
+    fn script_reads_snapshot_and_keeps_state() {
+      let script = MetricScript::compile(
+        "t",
+        "ticks = (ticks or 0) + 1\nreturn { double_fps = snapshot.fps * 2, ticks = ticks, ok = true }",
+      )
+      .unwrap();
+      let snapshot = MetricsSnapshot {
+        fps: Some(30.0),
+        ..Default::default()
+      };
+      script.run("d", "p", &snapshot).unwrap();
+      let values = script.run("d", "p", &snapshot).unwrap();
+      assert_eq!(values["double_fps"], MetricValue::Number(60.0));
+      assert_eq!(values["ticks"], MetricValue::Number(2.0));
+      assert_eq!(values["ok"], MetricValue::Bool(true));
+    }
+
+    #[test]
+    fn runaway_script_times_out() {
+      let script = MetricScript::compile("loop", "while true do end").unwrap();
+      let err = script
+        .run("d", "p", &MetricsSnapshot::default())
+        .unwrap_err();
+      assert_eq!(err.code(), "SCRIPT_FAILED");
+    }
+  }
+}
@@ -0,0 +1,103 @@
+use crate::adb::{command::run_device, error::Result};
+use serde::{Deserialize, Serialize};
+
+/// `settings` 命令的命名空间
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Namespace {
+  System,
+  Secure,
+  Global,
+}
+
+impl Namespace {
+  fn as_str(self) -> &'static str {
+    match self {
+      Self::System => "system",
+      Self::Secure => "secure",
+      Self::Global => "global",
+    }
+  }
+}
+
+/// 屏幕亮度与亮度模式（0 手动，1 自动），均位于 system
+pub const BRIGHTNESS: &str = "screen_brightness";
+pub const BRIGHTNESS_MODE: &str = "screen_brightness_mode";
+
+/// 系统动画缩放的三个设置项，均位于 global
+pub const ANIMATION_SCALES: [&str; 3] = [
+  "window_animation_scale",
+  "transition_animation_scale",
+  "animator_duration_scale",
+];
+
+/// 三项动画缩放，未设置的项为空
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnimationScales {
+  pub window: Option<f32>,
+  pub transition: Option<f32>,
+  pub animator: Option<f32>,
+}
+
+impl AnimationScales {
+  /// 三项统一设为同一缩放
+  pub fn uniform(scale: f32) -> Self {
+    Self {
+      window: Some(scale),
+      transition: Some(scale),
+      animator: Some(scale),
+    }
+  }
+
+  fn values(&self) -> [Option<f32>; 3] {
+    [self.window, self.transition, self.animator]
+  }
+}
+
+pub fn animation_scales(device_id: &str) -> Result<AnimationScales> {
+  let read = |key: &str| -> Result<Option<f32>> {
+    Ok(get_setting(device_id, Namespace::Global, key)?.and_then(|v| v.parse().ok()))
+  };
+  Ok(AnimationScales {
+    window: read(ANIMATION_SCALES[0])?,
+    transition: read(ANIMATION_SCALES[1])?,
+    animator: read(ANIMATION_SCALES[2])?,
+  })
+}
+
+/// 写入三项动画缩放，为空的项删除设置，回到系统默认
+pub fn set_animation_scales(device_id: &str, scales: &AnimationScales) -> Result<()> {
+  for (key, value) in ANIMATION_SCALES.iter().zip(scales.values()) {
+    match value {
+      Some(value) => put_setting(device_id, Namespace::Global, key, &value.to_string())?,
+      None => delete_setting(device_id, Namespace::Global, key)?,
+    }
+  }
+  Ok(())
+}
+
+/// 读取设置项，未设置时返回 None
+pub fn get_setting(device_id: &str, namespace: Namespace, key: &str) -> Result<Option<String>> {
+  let raw = run_device(
+    device_id,
+    &["shell", "settings", "get", namespace.as_str(), key],
+  )?;
+  let value = raw.trim();
+  Ok((!value.is_empty() && value != "null").then(|| value.to_string()))
+}
+
+pub fn put_setting(device_id: &str, namespace: Namespace, key: &str, value: &str) -> Result<()> {
+  run_device(
+    device_id,
+    &["shell", "settings", "put", namespace.as_str(), key, value],
+  )?;
+  Ok(())
+}
+
+pub fn delete_setting(device_id: &str, namespace: Namespace, key: &str) -> Result<()> {
+  run_device(
+    device_id,
+    &["shell", "settings", "delete", namespace.as_str(), key],
+  )?;
+  Ok(())
+}
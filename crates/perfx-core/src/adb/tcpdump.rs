@@ -0,0 +1,94 @@
+//! 设备端 tcpdump 抓包（需要 root），结果为 pcap 文件，可直接用 Wireshark 打开
+
+use crate::adb::{
+  command::{run_device, run_root},
+  error::{AdbError, Result},
+  profiling::{push_file, DEVICE_TMP},
+};
+use std::{
+  path::Path,
+  thread,
+  time::{Duration, Instant},
+};
+
+const SYSTEM_PATHS: [&str; 2] = ["/system/bin/tcpdump", "/system/xbin/tcpdump"];
+const EXIT_POLL: Duration = Duration::from_millis(300);
+
+/// 查找设备上的 tcpdump，没有时推送本地的静态编译版本
+pub fn ensure_tcpdump(device_id: &str, host_binary: Option<&Path>) -> Result<String> {
+  let pushed = format!("{DEVICE_TMP}/tcpdump");
+  for candidate in SYSTEM_PATHS.iter().copied().chain([pushed.as_str()]) {
+    let probe = run_device(
+      device_id,
+      &["shell", &format!("test -x {candidate} && echo ok")],
+    );
+    if probe.is_ok_and(|out| out.contains("ok")) {
+      return Ok(candidate.to_string());
+    }
+  }
+
+  let host = host_binary.ok_or_else(|| {
+    AdbError::CommandFailed("设备上没有 tcpdump，请指定对应 ABI 的静态编译 tcpdump".into())
+  })?;
+  let host = host.to_string_lossy();
+  run_device(device_id, &["push", &host, &pushed])?;
+  run_device(device_id, &["shell", "chmod", "755", &pushed])?;
+  Ok(pushed)
+}
+
+/// 以 root 在后台抓取所有网卡的完整报文写入 `path`；`filter` 为 BPF 过滤表达式
+pub fn start_tcpdump(
+  device_id: &str,
+  binary: &str,
+  path: &str,
+  filter: Option<&str>,
+) -> Result<()> {
+  // 过滤表达式写入文件经 -F 读取，不经过 shell，无需转义
+  let filter_arg = match filter.map(str::trim).filter(|f| !f.is_empty()) {
+    Some(filter) => {
+      push_file(device_id, filter.as_bytes(), &format!("{path}.bpf"))?;
+      format!("-F {path}.bpf")
+    }
+    None => String::new(),
+  };
+  let script = format!(
+    "rm -f {path}; nohup {binary} -i any -s 0 -U -w {path} {filter_arg} > /dev/null 2>&1 & echo $! > {path}.pid"
+  );
+  run_root(device_id, &script)?;
+  // 过滤表达式有误时 tcpdump 会立即退出
+  thread::sleep(Duration::from_millis(500));
+  if !is_running(device_id, path) {
+    return Err(AdbError::CommandFailed(
+      "tcpdump 启动失败，请检查过滤表达式".into(),
+    ));
+  }
+  Ok(())
+}
+
+/// 发送 SIGINT 让 tcpdump 写完缓冲后退出，返回是否在超时内退出
+pub fn stop_tcpdump(device_id: &str, path: &str, timeout: Duration) -> Result<bool> {
+  run_root(device_id, &format!("kill -INT $(cat {path}.pid)"))?;
+  let started = Instant::now();
+  while started.elapsed() < timeout {
+    if !is_running(device_id, path) {
+      // 文件属主为 root，放开读取权限以便 adb pull 与清理
+      run_root(device_id, &format!("chmod 666 {path}; rm -f {path}.pid {path}.bpf"))?;
+      return Ok(true);
+    }
+    thread::sleep(EXIT_POLL);
+  }
+  let _ = run_root(
+    device_id,
+    &format!("kill -KILL $(cat {path}.pid); chmod 666 {path}; rm -f {path}.pid {path}.bpf"),
+  );
+  Ok(false)
+}
+
+/// 进程以 root 运行，普通 shell 用户看不到，同样经 root 检查
+fn is_running(device_id: &str, path: &str) -> bool {
+  run_root(
+    device_id,
+    &format!("kill -0 $(cat {path}.pid) && echo alive"),
+  )
+  .is_ok_and(|out| out.contains("alive"))
+}
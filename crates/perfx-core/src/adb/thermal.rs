@@ -0,0 +1,96 @@
+use crate::adb::{command::run_device, error::Result};
+use serde::{Deserialize, Serialize};
+
+// 一次读取温区温度、各 CPU 频率及 Adreno / Mali GPU 频率
+const PROBE_SCRIPT: &str = "for z in /sys/class/thermal/thermal_zone*; do \
+  echo \"zone $(cat $z/type 2>/dev/null) $(cat $z/temp 2>/dev/null)\"; done; \
+  for c in /sys/devices/system/cpu/cpu[0-9]*/cpufreq; do \
+  echo \"cpu $(cat $c/scaling_cur_freq 2>/dev/null) $(cat $c/scaling_max_freq 2>/dev/null) \
+  $(cat $c/cpuinfo_max_freq 2>/dev/null)\"; done; \
+  echo \"gpu $(cat /sys/class/kgsl/kgsl-3d0/gpuclk 2>/dev/null) \
+  $(cat /sys/class/kgsl/kgsl-3d0/max_gpuclk 2>/dev/null)\"; \
+  echo \"gpu $(cat /sys/class/misc/mali0/device/cur_freq 2>/dev/null) \
+  $(cat /sys/class/misc/mali0/device/max_freq 2>/dev/null)\"";
+
+// 反映芯片温度的温区类型关键字，电池、充电等温区不参与
+const CHIP_ZONES: &[&str] = &["cpu", "soc", "gpu", "tsens", "skin"];
+
+/// 温区温度与 CPU / GPU 频率快照，读不到的字段为空
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThermalSnapshot {
+  /// 芯片相关温区中的最高温度
+  pub max_temp_c: Option<f64>,
+  /// 当前频率之和 / 硬件最高频率之和
+  pub cpu_freq_ratio: Option<f64>,
+  /// 调度允许的频率上限之和 / 硬件最高频率之和，温控降频时会下调
+  pub cpu_cap_ratio: Option<f64>,
+  pub gpu_freq_ratio: Option<f64>,
+}
+
+pub fn thermal_snapshot(device_id: &str) -> Result<ThermalSnapshot> {
+  let raw = run_device(device_id, &["shell", PROBE_SCRIPT])?;
+  Ok(parse_thermal(&raw))
+}
+
+fn parse_thermal(raw: &str) -> ThermalSnapshot {
+  let mut chip_temps = Vec::new();
+  let mut other_temps = Vec::new();
+  let (mut cur, mut cap, mut max) = (0u64, 0u64, 0u64);
+  let mut snapshot = ThermalSnapshot::default();
+
+  for line in raw.lines() {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    match fields.as_slice() {
+      ["zone", kind, temp] => {
+        let Some(temp) = temp.parse::<f64>().ok().map(normalize_temp) else {
+          continue;
+        };
+        if !(0.0..150.0).contains(&temp) {
+          continue;
+        }
+        let kind = kind.to_ascii_lowercase();
+        if CHIP_ZONES.iter().any(|k| kind.contains(k)) {
+          chip_temps.push(temp);
+        } else if !kind.contains("batt") && !kind.contains("charger") {
+          other_temps.push(temp);
+        }
+      }
+      ["cpu", c, s, m] => {
+        if let (Ok(c), Ok(s), Ok(m)) = (c.parse::<u64>(), s.parse::<u64>(), m.parse::<u64>()) {
+          cur += c;
+          cap += s;
+          max += m;
+        }
+      }
+      ["gpu", c, m] if snapshot.gpu_freq_ratio.is_none() => {
+        if let (Ok(c), Ok(m)) = (c.parse::<f64>(), m.parse::<f64>()) {
+          snapshot.gpu_freq_ratio = (m > 0.0).then(|| c / m);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  let temps = if chip_temps.is_empty() {
+    other_temps
+  } else {
+    chip_temps
+  };
+  snapshot.max_temp_c = temps.into_iter().reduce(f64::max);
+  if max > 0 {
+    snapshot.cpu_freq_ratio = Some(cur as f64 / max as f64);
+    snapshot.cpu_cap_ratio = Some(cap as f64 / max as f64);
+  }
+  snapshot
+}
+
+/// 温区温度多以毫摄氏度表示，少数机型为摄氏度或分摄氏度
+fn normalize_temp(value: f64) -> f64 {
+  if value >= 1000.0 {
+    value / 1000.0
+  } else if value >= 150.0 {
+    value / 10.0
+  } else {
+    value
+  }
+}
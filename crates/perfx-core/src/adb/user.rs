@@ -0,0 +1,52 @@
+//! 多用户：列出设备上的 Android 用户（含工作资料与访客），应用 UID 按用户划分
+
+use crate::adb::{
+  command::run_device,
+  error::{AdbError, Result},
+};
+use serde::{Deserialize, Serialize};
+
+/// 每个用户占用的 UID 区间大小，UID / PER_USER_RANGE 即用户 ID
+pub const PER_USER_RANGE: u32 = 100_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AndroidUser {
+  pub id: u32,
+  pub name: String,
+  pub running: bool,
+}
+
+/// `pm list users`，输出形如 `UserInfo{10:Work profile:1030} running`
+pub fn list_users(device_id: &str) -> Result<Vec<AndroidUser>> {
+  let raw = run_device(device_id, &["shell", "pm", "list", "users"])?;
+  let users: Vec<AndroidUser> = raw
+    .lines()
+    .filter_map(|line| {
+      let (_, rest) = line.split_once("UserInfo{")?;
+      let (info, state) = rest.split_once('}')?;
+      let mut parts = info.split(':');
+      Some(AndroidUser {
+        id: parts.next()?.trim().parse().ok()?,
+        name: parts.next().unwrap_or_default().to_string(),
+        running: state.contains("running"),
+      })
+    })
+    .collect();
+  if users.is_empty() {
+    return Err(AdbError::ParseFailed(format!("用户列表: {}", raw.trim())));
+  }
+  Ok(users)
+}
+
+/// UID 所属的用户
+pub fn uid_user(uid: u32) -> u32 {
+  uid / PER_USER_RANGE
+}
+
+/// `am` / `pm` / `cmd package` 的 `--user` 参数，未指定用户时为空
+pub(crate) fn user_args(user: Option<&str>) -> Vec<&str> {
+  match user {
+    Some(user) => vec!["--user", user],
+    None => Vec::new(),
+  }
+}
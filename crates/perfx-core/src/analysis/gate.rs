@@ -0,0 +1,199 @@
+use crate::analysis::summary::MetricSummary;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// 平均值变化在该百分比以内视为持平
+pub const DEFAULT_TOLERANCE_PCT: f64 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+  HigherIsBetter,
+  LowerIsBetter,
+}
+
+/// 指标的优劣方向；电量等不代表性能好坏的指标返回 None，不参与判定
+pub fn metric_direction(metric: &str) -> Option<Direction> {
+  match metric {
+    "fps" | "frame_on_target" => Some(Direction::HigherIsBetter),
+    "cpu" | "memory" | "power" | "power_mah" | "battery_temp" | "traffic_rx" | "traffic_tx"
+    | "frame_sd" | "frame_cv" => Some(Direction::LowerIsBetter),
+    _ => None,
+  }
+}
+
+/// 参与比较的统计量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GateStat {
+  #[default]
+  Avg,
+  P50,
+  P90,
+  P95,
+  P99,
+  Min,
+  Max,
+}
+
+impl GateStat {
+  fn pick(self, stats: &MetricSummary) -> f64 {
+    match self {
+      Self::Avg => stats.avg,
+      Self::P50 => stats.p50,
+      Self::P90 => stats.p90,
+      Self::P95 => stats.p95,
+      Self::P99 => stats.p99,
+      Self::Min => stats.min,
+      Self::Max => stats.max,
+    }
+  }
+}
+
+/// 单个指标的门禁规则
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricTolerance {
+  /// 允许劣化的百分比，缺省使用全局容差
+  pub tolerance_pct: Option<f64>,
+  pub stat: GateStat,
+  /// 未知指标需指定优劣方向，否则只检查绝对上下限
+  pub direction: Option<Direction>,
+  /// 绝对上下限，与基线无关
+  pub max_value: Option<f64>,
+  pub min_value: Option<f64>,
+}
+
+/// 门禁配置；metrics 为空时检查全部有优劣方向的指标，否则只检查列出的指标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GateConfig {
+  pub default_tolerance_pct: f64,
+  pub metrics: BTreeMap<String, MetricTolerance>,
+}
+
+impl Default for GateConfig {
+  fn default() -> Self {
+    Self {
+      default_tolerance_pct: DEFAULT_TOLERANCE_PCT,
+      metrics: BTreeMap::new(),
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+  Pass,
+  Fail,
+  /// 本次或基线会话缺少该指标
+  Missing,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GateCheck {
+  pub metric: String,
+  pub stat: GateStat,
+  pub status: CheckStatus,
+  pub current: Option<f64>,
+  pub baseline: Option<f64>,
+  pub delta_pct: Option<f64>,
+  pub tolerance_pct: f64,
+  /// 失败原因，通过时为空
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub reason: Option<String>,
+}
+
+/// 按配置逐项比较本次与基线的指标统计
+pub fn evaluate_checks(
+  current: &[MetricSummary],
+  baseline: &[MetricSummary],
+  config: &GateConfig,
+) -> Vec<GateCheck> {
+  let find =
+    |stats: &[MetricSummary], metric: &str| stats.iter().find(|s| s.metric == metric).cloned();
+
+  let targets: Vec<(String, MetricTolerance)> = if config.metrics.is_empty() {
+    current
+      .iter()
+      .filter(|s| metric_direction(&s.metric).is_some())
+      .map(|s| (s.metric.clone(), MetricTolerance::default()))
+      .collect()
+  } else {
+    config
+      .metrics
+      .iter()
+      .map(|(metric, rule)| (metric.clone(), rule.clone()))
+      .collect()
+  };
+
+  targets
+    .into_iter()
+    .map(|(metric, rule)| {
+      let direction = rule.direction.or_else(|| metric_direction(&metric));
+      let tolerance_pct = rule.tolerance_pct.unwrap_or(config.default_tolerance_pct);
+      let value = find(current, &metric).map(|s| rule.stat.pick(&s));
+      let base = find(baseline, &metric).map(|s| rule.stat.pick(&s));
+      check(metric, &rule, direction, tolerance_pct, value, base)
+    })
+    .collect()
+}
+
+fn check(
+  metric: String,
+  rule: &MetricTolerance,
+  direction: Option<Direction>,
+  tolerance_pct: f64,
+  current: Option<f64>,
+  baseline: Option<f64>,
+) -> GateCheck {
+  let mut result = GateCheck {
+    metric,
+    stat: rule.stat,
+    status: CheckStatus::Pass,
+    current,
+    baseline,
+    delta_pct: None,
+    tolerance_pct,
+    reason: None,
+  };
+  let (Some(value), Some(base)) = (current, baseline) else {
+    result.status = CheckStatus::Missing;
+    result.reason = Some(if current.is_none() {
+      "本次会话缺少该指标".into()
+    } else {
+      "基线会话缺少该指标".into()
+    });
+    return result;
+  };
+
+  let delta_pct = if base.abs() > f64::EPSILON {
+    (value - base) / base.abs() * 100.0
+  } else {
+    0.0
+  };
+  result.delta_pct = Some(delta_pct);
+  let loss_pct = match direction {
+    Some(Direction::HigherIsBetter) => -delta_pct,
+    Some(Direction::LowerIsBetter) => delta_pct,
+    None => 0.0,
+  };
+
+  let reason = if loss_pct > tolerance_pct {
+    Some(format!(
+      "较基线劣化 {loss_pct:.1}%，超过容差 {tolerance_pct}%"
+    ))
+  } else if let Some(max) = rule.max_value.filter(|max| value > *max) {
+    Some(format!("{value:.2} 超过上限 {max}"))
+  } else {
+    rule
+      .min_value
+      .filter(|min| value < *min)
+      .map(|min| format!("{value:.2} 低于下限 {min}"))
+  };
+  if reason.is_some() {
+    result.status = CheckStatus::Fail;
+    result.reason = reason;
+  }
+  result
+}
@@ -0,0 +1,4 @@
+//! 与存储无关的统计与门禁判定，输入为各指标的数值序列
+
+pub mod gate;
+pub mod summary;
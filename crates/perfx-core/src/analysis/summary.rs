@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricSummary {
+  pub metric: String,
+  pub count: usize,
+  pub min: f64,
+  pub avg: f64,
+  pub max: f64,
+  pub p50: f64,
+  pub p90: f64,
+  pub p95: f64,
+  pub p99: f64,
+}
+
+pub fn summarize_values(metric: &str, values: &[f64]) -> Option<MetricSummary> {
+  let mut sorted: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+  if sorted.is_empty() {
+    return None;
+  }
+  sorted.sort_by(|a, b| a.total_cmp(b));
+
+  let sum: f64 = sorted.iter().sum();
+  Some(MetricSummary {
+    metric: metric.to_string(),
+    count: sorted.len(),
+    min: sorted[0],
+    avg: sum / sorted.len() as f64,
+    max: sorted[sorted.len() - 1],
+    p50: percentile(&sorted, 50.0),
+    p90: percentile(&sorted, 90.0),
+    p95: percentile(&sorted, 95.0),
+    p99: percentile(&sorted, 99.0),
+  })
+}
+
+/// 线性插值分位数，`sorted` 需已升序排列
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+  if sorted.is_empty() {
+    return f64::NAN;
+  }
+  let rank = (p / 100.0).clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+  let lower = rank.floor() as usize;
+  let upper = rank.ceil() as usize;
+  let weight = rank - lower as f64;
+  sorted[lower] + (sorted[upper] - sorted[lower]) * weight
+}
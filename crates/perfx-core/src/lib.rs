@@ -0,0 +1,37 @@
+//! 不依赖 Tauri 的采集核心，桌面端与命令行共用，也可嵌入其他工具。
+//!
+//! - [`adb`]：设备、应用与各类指标的采集，入口为 [`adb::collect_metrics_with`]
+//! - [`sampler`]：按间隔连续采样（命令行录制使用；桌面端录制另有截图、告警等逻辑，未使用）
+//! - [`analysis`]：与存储无关的统计与门禁判定；会话对比、回归、耗电、评分与泄漏检测依赖
+//!   桌面端的会话存储，仍在桌面端
+//! - [`units`]：报表单位换算
+//! - [`schema`]：快照与导出的数据格式版本
+//! - [`settings`]：与桌面端共用的配置文件
+//!
+//! ```no_run
+//! use perfx_core::{
+//!   adb::{set_adb_path, MetricKey},
+//!   sampler::{sample_loop, SampleConfig},
+//! };
+//! use std::time::Duration;
+//!
+//! set_adb_path(Some("adb".into()));
+//! let config = SampleConfig {
+//!   duration: Some(Duration::from_secs(10)),
+//!   ..SampleConfig::new("emulator-5554", "com.example.app", &[MetricKey::Fps, MetricKey::Cpu])
+//! };
+//! sample_loop::<()>(&config, |t_ms, result| {
+//!   if let Ok(snapshot) = result {
+//!     println!("{t_ms} fps={:?} cpu={:?}", snapshot.fps, snapshot.cpu);
+//!   }
+//!   Ok(true)
+//! })
+//! .unwrap();
+//! ```
+
+pub mod adb;
+pub mod analysis;
+pub mod sampler;
+pub mod schema;
+pub mod settings;
+pub mod units;
@@ -0,0 +1,68 @@
+//! 定时采样：按间隔调用 `collect_metrics_with` 直到达到时长或回调要求停止，
+//! 命令行录制与嵌入方共用，不负责存储与展示
+
+use crate::adb::{collect_metrics_with, error::Result, CollectOptions, MetricKey, MetricsSnapshot};
+use std::{
+  thread,
+  time::{Duration, Instant},
+};
+
+/// 采样间隔下限，过短时 adb 命令会堆积
+pub const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone)]
+pub struct SampleConfig {
+  pub device_id: String,
+  pub package: String,
+  pub metrics: Vec<MetricKey>,
+  pub interval: Duration,
+  /// 为空时一直采集，直到回调返回 false
+  pub duration: Option<Duration>,
+  pub options: CollectOptions,
+}
+
+impl SampleConfig {
+  pub fn new(device_id: &str, package: &str, metrics: &[MetricKey]) -> Self {
+    Self {
+      device_id: device_id.to_string(),
+      package: package.to_string(),
+      metrics: metrics.to_vec(),
+      interval: Duration::from_secs(1),
+      duration: None,
+      options: CollectOptions::default(),
+    }
+  }
+}
+
+/// 逐周期采样，回调收到相对开始的毫秒数与本次结果；单次失败交给回调处理，不中断采样。
+/// 回调返回 false 或出错时结束，返回成功采样的次数
+pub fn sample_loop<E>(
+  config: &SampleConfig,
+  mut on_tick: impl FnMut(u64, Result<MetricsSnapshot>) -> std::result::Result<bool, E>,
+) -> std::result::Result<usize, E> {
+  let interval = config.interval.max(MIN_INTERVAL);
+  let started = Instant::now();
+  let mut count = 0;
+
+  while config.duration.map_or(true, |d| started.elapsed() < d) {
+    let tick = Instant::now();
+    let t_ms = started.elapsed().as_millis() as u64;
+    let result = collect_metrics_with(
+      &config.device_id,
+      &config.package,
+      &config.metrics,
+      config.options.clone(),
+    )
+    .map(|(snapshot, _)| snapshot);
+    if result.is_ok() {
+      count += 1;
+    }
+    if !on_tick(t_ms, result)? {
+      break;
+    }
+    if let Some(left) = interval.checked_sub(tick.elapsed()) {
+      thread::sleep(left);
+    }
+  }
+  Ok(count)
+}
@@ -0,0 +1,72 @@
+//! 快照、会话记录与导出文件的数据格式版本。字段改名或换单位时递增版本，
+//! 并在读取处按版本做兼容转换，避免旧数据被按新含义悄悄解读
+
+use thiserror::Error;
+
+/// 当前版本。2：CPU 不再按 100% 封顶；整机流量 `network_kbps` 由累计 KiB 改为速率。
+/// 3：`power` 固定为瞬时电流（mA），batterystats 的应用累计耗电另存为 `power_mah`
+pub const SCHEMA_VERSION: u32 = 3;
+/// 引入版本号之前写入的数据
+pub const LEGACY_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+#[error("数据格式版本 {0} 高于当前支持的 {SCHEMA_VERSION}，请升级 PerfX")]
+pub struct UnsupportedSchema(pub u32);
+
+/// serde 缺省值：没有版本字段的数据按旧版本读取
+pub fn legacy_version() -> u32 {
+  LEGACY_SCHEMA_VERSION
+}
+
+/// 版本 3 之前 `power` 优先记录 batterystats 的累计耗电（mAh），取不到时才是瞬时电流（mA），
+/// 同一会话内来源不变。序列非负且单调不减时判定为累计值，应改记为 `power_mah`
+pub fn legacy_power_is_cumulative(values: &[f64]) -> bool {
+  values.len() > 1
+    && values.iter().all(|v| *v >= 0.0)
+    && values.windows(2).all(|pair| pair[1] >= pair[0])
+}
+
+/// 版本 2 之前会话中的 `network` 是整机累计流量（KiB），按相邻采样求差改为速率（KiB/s）。
+/// `points` 为按时间排序的 (毫秒, 值)，返回与之一一对应的速率；首个点、时间不增或
+/// 计数回落（重启、网卡切换）的点无法求速率，为 None
+pub fn legacy_network_rates(points: &[(u64, f64)]) -> Vec<Option<f64>> {
+  let mut rates = Vec::with_capacity(points.len());
+  let mut prev: Option<(u64, f64)> = None;
+  for &(t_ms, value) in points {
+    let rate = prev.and_then(|(prev_t, prev_value)| {
+      (t_ms > prev_t && value >= prev_value)
+        .then(|| (value - prev_value) * 1000.0 / (t_ms - prev_t) as f64)
+    });
+    rates.push(rate);
+    prev = Some((t_ms, value));
+  }
+  rates
+}
+
+/// 更新的版本可能改过字段含义，拒绝读取而不是猜测
+pub fn check_version(version: u32) -> Result<(), UnsupportedSchema> {
+  if version > SCHEMA_VERSION {
+    return Err(UnsupportedSchema(version));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn cumulative_network_becomes_rate() {
+    let points = [
+      (0, 100.0),
+      (1000, 150.0),
+      (3000, 250.0),
+      (4000, 10.0),
+      (4000, 20.0),
+    ];
+    assert_eq!(
+      legacy_network_rates(&points),
+      vec![None, Some(50.0), Some(50.0), None, None]
+    );
+  }
+}
@@ -0,0 +1,126 @@
+//! 桌面端与命令行共用的配置文件，位于应用配置目录下的 settings.json
+
+use crate::{
+  adb::{
+    provider::{PropertyMetric, ShellMetric},
+    script::ScriptMetric,
+    MetricKey,
+  },
+  units::Units,
+};
+use once_cell::sync::Lazy;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+  fs, io,
+  path::PathBuf,
+  sync::Mutex,
+  time::SystemTime,
+};
+use thiserror::Error;
+
+/// 与桌面端 tauri.conf.json 中的 identifier 一致
+const APP_IDENTIFIER: &str = "com.PerfX.desktop";
+const FILE_NAME: &str = "settings.json";
+/// 可通过该环境变量覆盖配置目录
+pub const CONFIG_DIR_ENV: &str = "PERFX_CONFIG_DIR";
+
+static DIR_OVERRIDE: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Error)]
+pub enum SettingsError {
+  #[error("无法确定配置目录")]
+  NoConfigDir,
+  #[error("配置文件读写失败: {0}")]
+  Io(#[from] io::Error),
+  #[error("配置文件格式错误: {0}")]
+  Json(#[from] serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, SettingsError>;
+
+/// 两端都会用到的设置项；其余字段由各端在同一文件中自行扩展
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+  /// 为空时使用内置或 PATH 中的 adb
+  pub adb_path: Option<String>,
+  pub default_metrics: Vec<MetricKey>,
+  pub interval_ms: u64,
+  /// 为空时导出前询问保存位置
+  pub export_dir: Option<PathBuf>,
+  /// 报表单位：只作用于 Excel 报表与会话汇总；JSON、数据包、命令行导出与实时数据
+  /// 供程序读取或再次导入，始终为基准单位
+  #[serde(alias = "units")]
+  pub report_units: Units,
+  /// 额外采集的系统属性，如应用 debug 包暴露的计数
+  pub property_metrics: Vec<PropertyMetric>,
+  /// 额外执行的 shell 命令，按正则取值，如读取 sysfs 节点
+  pub shell_metrics: Vec<ShellMetric>,
+  /// 每次采集后运行的 Lua 脚本
+  pub metric_scripts: Vec<ScriptMetric>,
+}
+
+impl Default for Settings {
+  fn default() -> Self {
+    Self {
+      adb_path: None,
+      default_metrics: vec![MetricKey::Fps, MetricKey::Cpu, MetricKey::Memory],
+      interval_ms: 1000,
+      export_dir: None,
+      report_units: Units::default(),
+      property_metrics: Vec::new(),
+      shell_metrics: Vec::new(),
+      metric_scripts: Vec::new(),
+    }
+  }
+}
+
+/// 桌面端启动时传入 Tauri 的应用配置目录
+pub fn set_settings_dir(dir: PathBuf) {
+  if let Ok(mut guard) = DIR_OVERRIDE.lock() {
+    *guard = Some(dir);
+  }
+}
+
+pub fn settings_path() -> Result<PathBuf> {
+  let overridden = DIR_OVERRIDE.lock().ok().and_then(|g| g.clone());
+  let dir = overridden
+    .or_else(|| std::env::var_os(CONFIG_DIR_ENV).map(PathBuf::from))
+    .or_else(|| dirs::config_dir().map(|d| d.join(APP_IDENTIFIER)))
+    .ok_or(SettingsError::NoConfigDir)?;
+  Ok(dir.join(FILE_NAME))
+}
+
+/// 读取两端共用的设置项
+pub fn load_settings() -> Result<Settings> {
+  load()
+}
+
+/// 按任意结构读取配置文件，文件不存在时返回默认值
+pub fn load<T: DeserializeOwned + Default>() -> Result<T> {
+  let path = settings_path()?;
+  match fs::read(&path) {
+    Ok(bytes) if bytes.iter().all(u8::is_ascii_whitespace) => Ok(T::default()),
+    Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+    Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(T::default()),
+    Err(e) => Err(e.into()),
+  }
+}
+
+/// 先写临时文件再替换，避免另一端读到写了一半的内容
+pub fn save<T: Serialize>(value: &T) -> Result<()> {
+  let path = settings_path()?;
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir)?;
+  }
+  let tmp = path.with_extension("json.tmp");
+  fs::write(&tmp, serde_json::to_vec_pretty(value)?)?;
+  fs::rename(&tmp, &path)?;
+  Ok(())
+}
+
+/// 配置文件的修改时间，用于轮询外部修改
+pub fn modified_at() -> Option<SystemTime> {
+  let path = settings_path().ok()?;
+  fs::metadata(path).and_then(|m| m.modified()).ok()
+}
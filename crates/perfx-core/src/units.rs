@@ -0,0 +1,151 @@
+//! 报表单位：采样、会话与机器可读的导出始终按基准单位（流量 KiB/s、温度 °C、电流 mA），
+//! 只有 Excel 报表与会话汇总按设置换算，避免各处各自除以 1024 或乘以电压
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 以 KiB/s 保存的速率类指标
+const RATE_METRICS: &[&str] = &["traffic_rx", "traffic_tx", "network_rx", "network_tx"];
+const TEMPERATURE_METRICS: &[&str] = &["battery_temp"];
+const POWER_METRIC: &str = "power";
+/// 与功耗同一次采样得到的电池电压（V），换算 mW 时使用
+const VOLTAGE_METRIC: &str = "battery_voltage";
+
+/// 流量速率单位
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateUnit {
+  /// 1 KiB = 1024 字节，与历史数据一致
+  #[default]
+  KibPerSec,
+  /// 1 KB = 1000 字节
+  KbPerSec,
+  /// 千比特每秒，与运营商带宽口径一致
+  Kbps,
+}
+
+impl RateUnit {
+  pub fn label(self) -> &'static str {
+    match self {
+      Self::KibPerSec => "KiB/s",
+      Self::KbPerSec => "KB/s",
+      Self::Kbps => "kbps",
+    }
+  }
+
+  /// 由 KiB/s 换算
+  fn convert(self, kib: f64) -> f64 {
+    match self {
+      Self::KibPerSec => kib,
+      Self::KbPerSec => kib * 1024.0 / 1000.0,
+      Self::Kbps => kib * 1024.0 * 8.0 / 1000.0,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TemperatureUnit {
+  #[default]
+  Celsius,
+  Fahrenheit,
+}
+
+impl TemperatureUnit {
+  pub fn label(self) -> &'static str {
+    match self {
+      Self::Celsius => "°C",
+      Self::Fahrenheit => "°F",
+    }
+  }
+
+  fn convert(self, celsius: f64) -> f64 {
+    match self {
+      Self::Celsius => celsius,
+      Self::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+    }
+  }
+}
+
+/// 功耗指标的单位：采集到的是电流，按同时刻电压换算为功率
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerUnit {
+  #[default]
+  MilliAmps,
+  MilliWatts,
+}
+
+impl PowerUnit {
+  pub fn label(self) -> &'static str {
+    match self {
+      Self::MilliAmps => "mA",
+      Self::MilliWatts => "mW",
+    }
+  }
+}
+
+/// 用户选择的展示单位
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Units {
+  pub rate: RateUnit,
+  pub temperature: TemperatureUnit,
+  pub power: PowerUnit,
+}
+
+impl Units {
+  /// 指标在当前设置下的单位，不受设置影响的指标返回 `base`
+  pub fn unit<'a>(&self, metric: &str, base: &'a str) -> &'a str {
+    if RATE_METRICS.contains(&metric) {
+      self.rate.label()
+    } else if TEMPERATURE_METRICS.contains(&metric) {
+      self.temperature.label()
+    } else if metric == POWER_METRIC {
+      self.power.label()
+    } else {
+      base
+    }
+  }
+
+  /// 将一次采样的指标值由基准单位换算为展示单位；
+  /// 功耗换算 mW 时缺少同时刻电压则去掉该值，不以 mA 冒充
+  pub fn convert(&self, values: &BTreeMap<String, f64>) -> BTreeMap<String, f64> {
+    let voltage = values.get(VOLTAGE_METRIC).copied();
+    values
+      .iter()
+      .filter_map(|(key, value)| {
+        let value = if RATE_METRICS.contains(&key.as_str()) {
+          self.rate.convert(*value)
+        } else if TEMPERATURE_METRICS.contains(&key.as_str()) {
+          self.temperature.convert(*value)
+        } else if key == POWER_METRIC && self.power == PowerUnit::MilliWatts {
+          value * voltage?
+        } else {
+          *value
+        };
+        Some((key.clone(), value))
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn milliwatts_from_current_and_voltage() {
+    let units = Units {
+      power: PowerUnit::MilliWatts,
+      ..Units::default()
+    };
+    let values = BTreeMap::from([
+      ("power".to_string(), 500.0),
+      ("battery_voltage".to_string(), 4.0),
+    ]);
+    assert_eq!(units.convert(&values).get("power"), Some(&2000.0));
+    let without_voltage = BTreeMap::from([("power".to_string(), 500.0)]);
+    assert_eq!(units.convert(&without_voltage).get("power"), None);
+  }
+}
@@ -0,0 +1,38 @@
+#!/usr/bin/env sh
+# 从已连接的设备抓取解析器用到的原始输出，按 <类别>/<名称>.txt 写入本目录，
+# 用于以真机输出替换或补充样例（包括非英文系统的表头）。
+#
+# 用法：capture.sh <设备序列号> <包名> <名称>
+# 名称建议为 ROM 与系统版本，如 miui_android12、oneui_android13_zh
+# 抓取前请先打开目标应用并操作几秒，gfxinfo 才有帧数据。
+set -eu
+
+if [ $# -ne 3 ]; then
+  echo "用法: $0 <设备序列号> <包名> <名称>" >&2
+  exit 1
+fi
+serial=$1
+package=$2
+name=$3
+dir=$(dirname "$0")
+
+shell() {
+  adb -s "$serial" shell "$@" | tr -d '\r'
+}
+
+pid=$(shell pidof "$package" | awk '{print $1}')
+if [ -z "$pid" ]; then
+  echo "$package 未运行" >&2
+  exit 1
+fi
+
+mkdir -p "$dir/top" "$dir/meminfo" "$dir/gfxinfo" "$dir/battery" "$dir/batterystats" "$dir/net_dev"
+# 与采集时的命令一致
+shell top -b -n 1 -p "$pid" >"$dir/top/$name.txt"
+shell dumpsys meminfo "$pid" >"$dir/meminfo/$name.txt"
+shell dumpsys gfxinfo "$package" framestats >"$dir/gfxinfo/${name}_framestats.txt"
+shell dumpsys battery >"$dir/battery/$name.txt"
+shell dumpsys batterystats "$package" >"$dir/batterystats/$name.txt"
+shell cat "/proc/$pid/net/dev" >"$dir/net_dev/$name.txt"
+
+echo "已写入 $dir/*/$name*.txt，补充对应的解析测试后提交"